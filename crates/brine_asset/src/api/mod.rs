@@ -1,25 +1,34 @@
 //! API for accessing Minecraft asset data at runtime.
 
+mod progress;
+mod render;
+
 use std::{
+    io,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 use bevy_ecs::prelude::Resource;
-use minecraft_assets::api::{AssetPack, ResourcePath};
+use minecraft_assets::api::{AssetPack, LayeredResourceProvider, ResourceIdentifier, ResourcePath};
 use tracing::*;
 
 pub use minecraft_assets::{api::Result, schemas::models::BlockFace};
 
+pub use progress::{LoadProgress, LoadStage};
+pub use render::BlockRenderProperties;
+
 pub use brine_data::{
-    blocks::{BlockId, BlockStateId},
+    blocks::{BlockId, BlockStateId, RenderLayer},
     MinecraftData, Version,
 };
 
 use crate::bakery::{
     self,
-    block_states::BakedBlockStateTable,
-    models::BakedModelTable,
+    block_states::BakedBlockState,
+    cache::BakeCache,
+    lazy::{LazyBlockStateTable, LazyModelTable},
+    models::{BakedModelKey, BakedQuad},
     textures::{TextureKey, TextureTable},
     BakedAssets,
 };
@@ -36,26 +45,152 @@ pub struct MinecraftAssets {
 
 impl MinecraftAssets {
     pub fn new(path: impl AsRef<Path>, data: &MinecraftData) -> Result<Self> {
-        let inner = MinecraftAssetsInner::build(path.as_ref(), data)?;
+        Self::new_with_progress(path, data, &LoadProgress::new())
+    }
+
+    /// Same as [`new`][Self::new], but reports progress to `progress` so that
+    /// another thread can display it while this one is busy.
+    pub fn new_with_progress(
+        path: impl AsRef<Path>,
+        data: &MinecraftData,
+        progress: &LoadProgress,
+    ) -> Result<Self> {
+        Self::build(path.as_ref(), &[], data, None, progress)
+    }
+
+    /// Same as [`new_with_progress`][Self::new_with_progress], but reuses the
+    /// baked block states and models stored in `cache_path` if the assets
+    /// haven't changed since it was written.
+    ///
+    /// If they have, block states are baked as they are looked up as usual,
+    /// while a background thread bakes the rest and (re)writes the cache.
+    pub fn new_cached(
+        path: impl AsRef<Path>,
+        data: &MinecraftData,
+        cache_path: impl AsRef<Path>,
+        progress: &LoadProgress,
+    ) -> Result<Self> {
+        Self::build(
+            path.as_ref(),
+            &[],
+            data,
+            Some(cache_path.as_ref()),
+            progress,
+        )
+    }
+
+    /// Same as [`new_cached`][Self::new_cached], but layers the resource packs
+    /// in `resource_packs` over the vanilla assets at `path`, lowest priority
+    /// first. A model, block state or texture in a pack replaces the one with
+    /// the same name in the packs before it and in the vanilla assets.
+    ///
+    /// Like `path`, each pack is a directory containing an `assets/`
+    /// directory, and must be inside Bevy's asset directory for its textures
+    /// to load.
+    pub fn new_with_resource_packs(
+        path: impl AsRef<Path>,
+        resource_packs: &[PathBuf],
+        data: &MinecraftData,
+        cache_path: Option<&Path>,
+        progress: &LoadProgress,
+    ) -> Result<Self> {
+        Self::build(path.as_ref(), resource_packs, data, cache_path, progress)
+    }
+
+    /// Builds the assets from the contents of a cache file written by
+    /// [`new_cached`][Self::new_cached] rather than from the asset files, for
+    /// where those can't be listed (e.g. in the browser, where the textures
+    /// are still fetched from `path` through Bevy's asset server).
+    ///
+    /// The cache isn't checked against the assets at `path`, so it must have
+    /// been baked from the same ones. There are no resource packs.
+    pub fn from_bake_cache(path: impl AsRef<Path>, cache: &[u8]) -> Result<Self> {
+        let BakedAssets {
+            block_states,
+            models,
+            textures,
+        } = bakery::cache::read_baked(cache)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
 
         Ok(Self {
-            inner: Arc::new(inner),
+            inner: Arc::new(MinecraftAssetsInner {
+                roots: vec![PathBuf::from(path.as_ref())],
+                block_state_table: LazyBlockStateTable::from_baked(block_states, models),
+                texture_table: textures,
+                texture_layers: Vec::new(),
+            }),
         })
     }
 
+    fn build(
+        root: &Path,
+        resource_packs: &[PathBuf],
+        data: &MinecraftData,
+        cache_path: Option<&Path>,
+        progress: &LoadProgress,
+    ) -> Result<Self> {
+        let (inner, stale_cache) =
+            MinecraftAssetsInner::build(root, resource_packs, data, cache_path, progress)?;
+        let assets = Self {
+            inner: Arc::new(inner),
+        };
+
+        if let Some(cache) = stale_cache {
+            assets.write_bake_cache_in_background(cache);
+        }
+
+        Ok(assets)
+    }
+
+    /// Bakes the block states that haven't been looked up yet on another
+    /// thread, then writes them all to `cache` for the next run.
+    ///
+    /// If the program exits first, the cache is left as it was and the next
+    /// run tries again.
+    fn write_bake_cache_in_background(&self, cache: BakeCache) {
+        let assets = self.clone();
+        let spawned = std::thread::Builder::new()
+            .name(String::from("bake cache"))
+            .spawn(move || {
+                let block_state_table = &assets.inner.block_state_table;
+                block_state_table.bake_all();
+
+                let (block_states, models) = block_state_table.to_baked();
+                cache.write(&BakedAssets {
+                    block_states,
+                    models,
+                    textures: assets.textures().clone(),
+                });
+            });
+
+        if let Err(err) = spawned {
+            warn!("Failed to start writing the bake cache: {}", err);
+        }
+    }
+
+    /// Returns the directory of the vanilla assets.
     #[inline]
     pub fn root(&self) -> &Path {
-        &self.inner.root
+        &self.inner.roots[0]
     }
 
+    /// Returns the directories of the resource packs layered over the vanilla
+    /// assets, lowest priority first.
     #[inline]
-    pub fn block_states(&self) -> &BakedBlockStateTable {
+    pub fn resource_packs(&self) -> &[PathBuf] {
+        &self.inner.roots[1..]
+    }
+
+    /// Returns the baked block states, each baked the first time it is
+    /// looked up.
+    #[inline]
+    pub fn block_states(&self) -> &LazyBlockStateTable {
         &self.inner.block_state_table
     }
 
     #[inline]
-    pub fn models(&self) -> &BakedModelTable {
-        &self.inner.model_table
+    pub fn models(&self) -> &LazyModelTable {
+        self.inner.block_state_table.models()
     }
 
     #[inline]
@@ -63,15 +198,63 @@ impl MinecraftAssets {
         &self.inner.texture_table
     }
 
+    /// Returns the asset path of a texture, in whichever resource pack (or the
+    /// vanilla assets) it comes from.
     #[inline]
     pub fn get_texture_path(&self, texture_key: TextureKey) -> Option<PathBuf> {
         let texture_id = self.textures().get_by_key(texture_key)?;
 
-        let texture_path = ResourcePath::for_resource(&self.root(), texture_id);
+        let layer = self
+            .inner
+            .texture_layers
+            .get(texture_key.0)
+            .copied()
+            .unwrap_or(0);
+        let texture_path = ResourcePath::for_resource(&self.inner.roots[layer], texture_id);
 
         Some(texture_path.strip_prefix("assets").unwrap().into())
     }
 
+    /// Returns the path to a texture given its name (e.g.,
+    /// `"block/destroy_stage_0"`), or `None` if the texture does not exist.
+    #[inline]
+    pub fn get_texture_path_by_name(&self, name: &str) -> Option<PathBuf> {
+        let texture_key = self
+            .textures()
+            .get_key(&ResourceIdentifier::texture(name))?;
+
+        self.get_texture_path(texture_key)
+    }
+
+    /// Returns the path of the texture with the given name (e.g.
+    /// `misc/forcefield`), including textures that no block model uses and
+    /// that therefore aren't in the [`TextureTable`]. The file may not exist.
+    pub fn get_any_texture_path(&self, name: &str) -> PathBuf {
+        let texture_id = ResourceIdentifier::texture(name);
+        let texture_path = self
+            .resource_packs()
+            .iter()
+            .rev()
+            .map(|pack| ResourcePath::for_resource(pack, &texture_id))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| ResourcePath::for_resource(self.root(), &texture_id));
+
+        texture_path.strip_prefix("assets").unwrap().into()
+    }
+
+    /// Returns the path of the emissive overlay of the texture at
+    /// `texture_path`, a path returned by the other texture path methods: the
+    /// texture with `_e` added to its name, whose opaque texels resource packs
+    /// make glow. Returns `None` if there is no such texture.
+    pub fn get_emissive_overlay_path(&self, texture_path: &Path) -> Option<PathBuf> {
+        let name = texture_path.file_stem()?.to_str()?;
+        let overlay_path = texture_path.with_file_name(format!("{name}_e.png"));
+        Path::new("assets")
+            .join(&overlay_path)
+            .exists()
+            .then_some(overlay_path)
+    }
+
     // TODO: deprecate
     pub fn get_texture_path_for_block_state_and_face(
         &self,
@@ -117,31 +300,334 @@ impl MinecraftAssets {
     }
 }
 
+/// Comparing two loads of the same assets, for hot reloading.
+impl MinecraftAssets {
+    /// Returns the key of the texture at `path`, as returned by
+    /// [`get_texture_path`][Self::get_texture_path].
+    pub fn get_texture_key_by_path(&self, path: impl AsRef<Path>) -> Option<TextureKey> {
+        let path = path.as_ref();
+
+        self.textures()
+            .iter()
+            .map(|(key, _)| key)
+            .find(|key| self.get_texture_path(*key).as_deref() == Some(path))
+    }
+
+    /// Returns every block state with a model that uses `texture`.
+    ///
+    /// Only block states baked so far are considered: no other has been drawn
+    /// yet.
+    pub fn block_states_using_texture(&self, texture: TextureKey) -> Vec<BlockStateId> {
+        self.baked_block_states()
+            .filter(|id| {
+                self.block_states()
+                    .get_baked(*id)
+                    .is_some_and(|block_state| {
+                        model_keys(block_state)
+                            .filter_map(|key| self.models().get_by_key(key))
+                            .any(|model| model.quads.iter().any(|quad| quad.texture == texture))
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns every block state that looks different in `other`: its models,
+    /// their quads, or the textures they use have changed.
+    ///
+    /// Model and texture keys are compared by what they refer to, so the two
+    /// may have been baked separately. Only block states baked so far in
+    /// `self` are compared: no other has been drawn yet.
+    pub fn changed_block_states(&self, other: &MinecraftAssets) -> Vec<BlockStateId> {
+        self.baked_block_states()
+            .filter(|id| !self.same_block_state(other, *id))
+            .collect()
+    }
+
+    fn baked_block_states(&self) -> impl Iterator<Item = BlockStateId> + '_ {
+        (0..self.block_states().len())
+            .map(|index| BlockStateId(index as u16))
+            .filter(|id| self.block_states().get_baked(*id).is_some())
+    }
+
+    fn same_block_state(&self, other: &MinecraftAssets, id: BlockStateId) -> bool {
+        let (ours, theirs) = match (
+            self.block_states().get_by_key(id),
+            other.block_states().get_by_key(id),
+        ) {
+            (Some(ours), Some(theirs)) => (ours, theirs),
+            (None, None) => return true,
+            _ => return false,
+        };
+
+        ours.is_full_cube == theirs.is_full_cube
+            && ours.models.len() == theirs.models.len()
+            && ours
+                .models
+                .iter()
+                .zip(theirs.models.iter())
+                .all(|(ours, theirs)| {
+                    ours.choices.len() == theirs.choices.len()
+                        && ours
+                            .choices
+                            .iter()
+                            .zip(theirs.choices.iter())
+                            .all(|(ours, theirs)| self.same_model(other, *ours, *theirs))
+                })
+    }
+
+    fn same_model(
+        &self,
+        other: &MinecraftAssets,
+        ours: BakedModelKey,
+        theirs: BakedModelKey,
+    ) -> bool {
+        let (ours, theirs) = match (
+            self.models().get_by_key(ours),
+            other.models().get_by_key(theirs),
+        ) {
+            (Some(ours), Some(theirs)) => (ours, theirs),
+            _ => return false,
+        };
+
+        ours.is_full_cube == theirs.is_full_cube
+            && ours.ambient_occlusion == theirs.ambient_occlusion
+            && ours.display == theirs.display
+            && ours.quads.len() == theirs.quads.len()
+            && ours
+                .quads
+                .iter()
+                .zip(theirs.quads.iter())
+                .all(|(ours, theirs)| {
+                    let texture = TextureKey::default();
+                    BakedQuad {
+                        texture,
+                        ..ours.clone()
+                    } == BakedQuad {
+                        texture,
+                        ..theirs.clone()
+                    } && self.textures().get_by_key(ours.texture)
+                        == other.textures().get_by_key(theirs.texture)
+                })
+    }
+}
+
+fn model_keys(block_state: &BakedBlockState) -> impl Iterator<Item = BakedModelKey> + '_ {
+    block_state
+        .models
+        .iter()
+        .flat_map(|grab_bag| grab_bag.choices.iter().copied())
+}
+
 #[derive(Debug)]
 pub(crate) struct MinecraftAssetsInner {
-    pub(crate) root: PathBuf,
-    pub(crate) block_state_table: BakedBlockStateTable,
-    pub(crate) model_table: BakedModelTable,
+    /// The vanilla assets, then each resource pack.
+    pub(crate) roots: Vec<PathBuf>,
+    pub(crate) block_state_table: LazyBlockStateTable,
     pub(crate) texture_table: TextureTable,
+    /// Index into `roots` of the layer each texture is read from, by
+    /// [`TextureKey`]. Empty when there are no resource packs.
+    pub(crate) texture_layers: Vec<usize>,
 }
 
 impl MinecraftAssetsInner {
-    fn build(root: &Path, data: &MinecraftData) -> Result<Self> {
-        let assets = AssetPack::at_path(root);
+    /// Loads the assets, from the bake cache at `cache_path` if it is up to
+    /// date. Returns the cache too if it isn't, so that it can be rewritten.
+    fn build(
+        root: &Path,
+        resource_packs: &[PathBuf],
+        data: &MinecraftData,
+        cache_path: Option<&Path>,
+        progress: &LoadProgress,
+    ) -> Result<(Self, Option<BakeCache>)> {
+        let roots: Vec<PathBuf> = std::iter::once(PathBuf::from(root))
+            .chain(resource_packs.iter().cloned())
+            .collect();
 
-        let BakedAssets {
-            block_states,
-            models,
-            textures,
-        } = bakery::bake_all(data, &assets)?;
+        progress.start_stage(LoadStage::Cache, 0);
+        let cache = cache_path.map(|cache_path| {
+            let root_paths: Vec<&Path> = roots.iter().map(PathBuf::as_path).collect();
+            BakeCache::new(cache_path, &root_paths, data)
+        });
+
+        let cached = cache.as_ref().and_then(BakeCache::read);
+        let (block_state_table, texture_table, stale_cache) = match cached {
+            Some(BakedAssets {
+                block_states,
+                models,
+                textures,
+            }) => (
+                LazyBlockStateTable::from_baked(block_states, models),
+                textures,
+                None,
+            ),
+            None => {
+                let assets = if resource_packs.is_empty() {
+                    AssetPack::at_path(root)
+                } else {
+                    info!(
+                        "Layering {} resource packs over the assets",
+                        resource_packs.len()
+                    );
+                    AssetPack::new(LayeredResourceProvider::from_roots(&roots))
+                };
+
+                let (block_state_table, texture_table) = load_unbaked(data, &assets, progress)?;
+                (block_state_table, texture_table, cache)
+            }
+        };
+
+        let texture_layers = if resource_packs.is_empty() {
+            Vec::new()
+        } else {
+            texture_table
+                .iter()
+                .map(|(_, texture_id)| {
+                    roots
+                        .iter()
+                        .rposition(|root| ResourcePath::for_resource(root, texture_id).exists())
+                        .unwrap_or(0)
+                })
+                .collect()
+        };
+
+        progress.start_stage(LoadStage::Done, 0);
 
         let new = Self {
-            root: PathBuf::from(root),
-            block_state_table: block_states,
-            model_table: models,
-            texture_table: textures,
+            roots,
+            block_state_table,
+            texture_table,
+            texture_layers,
         };
 
-        Ok(new)
+        Ok((new, stale_cache))
+    }
+}
+
+/// Reads the textures, models and block state definitions, leaving the block
+/// states to be baked as they are looked up.
+fn load_unbaked(
+    data: &MinecraftData,
+    assets: &AssetPack,
+    progress: &LoadProgress,
+) -> Result<(LazyBlockStateTable, TextureTable)> {
+    progress.start_stage(LoadStage::Textures, 0);
+    let texture_table = bakery::textures::load_texture_table(assets)?;
+
+    progress.start_stage(LoadStage::Models, 0);
+    let unbaked_models = bakery::models::load_unbaked_block_models(assets)?;
+
+    progress.start_stage(LoadStage::BlockStates, 0);
+    let unbaked_block_states = bakery::block_states::load_unbaked_block_states(assets)?;
+
+    let block_state_table = LazyBlockStateTable::new(
+        data,
+        unbaked_models,
+        unbaked_block_states,
+        texture_table.clone(),
+    );
+
+    Ok((block_state_table, texture_table))
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use crate::bakery::{
+        block_states::{BakedBlockStateTable, BlockStateGrabBag},
+        models::{BakedModel, BakedModelTable},
+    };
+
+    use super::*;
+
+    fn quad(texture: TextureKey) -> BakedQuad {
+        BakedQuad {
+            positions: [[0.0; 3]; 4],
+            normal: [0.0, 1.0, 0.0],
+            tex_coords: [[0.0; 2]; 4],
+            texture,
+            face: BlockFace::Up,
+            cull_face: Some(BlockFace::Up),
+            tinted: false,
+            shade: true,
+        }
+    }
+
+    /// Assets with one single-quad model per block state, textured with the
+    /// given texture names.
+    fn assets(textures: &[&'static str], block_state_textures: &[&'static str]) -> MinecraftAssets {
+        let mut texture_table = TextureTable::default();
+        for name in textures {
+            texture_table.insert(ResourceIdentifier::texture(name));
+        }
+
+        let mut model_table = BakedModelTable::default();
+        let mut block_state_table = BakedBlockStateTable::default();
+        for name in block_state_textures {
+            let texture = texture_table
+                .get_key(&ResourceIdentifier::texture(name))
+                .unwrap();
+            let model = model_table.insert(BakedModel {
+                is_full_cube: true,
+                quads: smallvec![quad(texture)],
+                ambient_occlusion: true,
+                display: Default::default(),
+            });
+            block_state_table.insert(BakedBlockState {
+                is_full_cube: true,
+                models: smallvec![BlockStateGrabBag {
+                    choices: smallvec![model],
+                }],
+            });
+        }
+
+        MinecraftAssets {
+            inner: Arc::new(MinecraftAssetsInner {
+                roots: vec![PathBuf::from("assets/1.21.4")],
+                block_state_table: LazyBlockStateTable::from_baked(block_state_table, model_table),
+                texture_table,
+                texture_layers: Vec::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn block_states_are_compared_by_texture_name() {
+        let before = assets(
+            &["block/dirt", "block/stone"],
+            &["block/stone", "block/dirt"],
+        );
+
+        // Same looks, but baked with a different texture order.
+        let reordered = assets(
+            &["block/stone", "block/dirt"],
+            &["block/stone", "block/dirt"],
+        );
+        assert!(before.changed_block_states(&reordered).is_empty());
+
+        let retextured = assets(
+            &["block/dirt", "block/stone"],
+            &["block/stone", "block/stone"],
+        );
+        assert_eq!(
+            before.changed_block_states(&retextured),
+            vec![BlockStateId(1)]
+        );
+    }
+
+    #[test]
+    fn texture_paths_map_back_to_block_states() {
+        let assets = assets(
+            &["block/dirt", "block/stone"],
+            &["block/stone", "block/dirt"],
+        );
+
+        let path = assets.get_texture_path_by_name("block/dirt").unwrap();
+        let key = assets.get_texture_key_by_path(&path).unwrap();
+
+        assert_eq!(
+            assets.block_states_using_texture(key),
+            vec![BlockStateId(1)]
+        );
     }
 }
@@ -0,0 +1,132 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
+
+/// A step of [`MinecraftAssets`][super::MinecraftAssets] construction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum LoadStage {
+    Starting = 0,
+    Cache,
+    Textures,
+    Models,
+    BlockStates,
+    Baking,
+    Done,
+}
+
+impl LoadStage {
+    const ALL: [Self; 7] = [
+        Self::Starting,
+        Self::Cache,
+        Self::Textures,
+        Self::Models,
+        Self::BlockStates,
+        Self::Baking,
+        Self::Done,
+    ];
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL.get(value as usize).copied().unwrap_or(Self::Done)
+    }
+}
+
+impl fmt::Display for LoadStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Starting => "Starting",
+            Self::Cache => "Checking bake cache",
+            Self::Textures => "Indexing textures",
+            Self::Models => "Loading block models",
+            Self::BlockStates => "Loading block states",
+            Self::Baking => "Baking block states",
+            Self::Done => "Done",
+        })
+    }
+}
+
+/// Progress of [`MinecraftAssets`][super::MinecraftAssets] construction that
+/// can be observed from another thread.
+///
+/// Pass a shared reference to
+/// [`MinecraftAssets::new_with_progress`][super::MinecraftAssets::new_with_progress]
+/// and poll [`stage`][Self::stage] and [`fraction`][Self::fraction] while it
+/// runs.
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    stage: AtomicU8,
+    completed: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl LoadProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the step that is currently running.
+    pub fn stage(&self) -> LoadStage {
+        LoadStage::from_u8(self.stage.load(Ordering::Acquire))
+    }
+
+    /// Returns the number of items completed and the total number of items in
+    /// the current step, if the step reports them.
+    pub fn items(&self) -> Option<(usize, usize)> {
+        let total = self.total.load(Ordering::Acquire);
+        if total == 0 {
+            return None;
+        }
+        let completed = self.completed.load(Ordering::Acquire).min(total);
+        Some((completed, total))
+    }
+
+    /// Returns the overall progress from `0.0` to `1.0`.
+    ///
+    /// Each step counts for an equal share of the total.
+    pub fn fraction(&self) -> f32 {
+        let stage = self.stage();
+        if stage == LoadStage::Done {
+            return 1.0;
+        }
+
+        let within_stage = self
+            .items()
+            .map(|(completed, total)| completed as f32 / total as f32)
+            .unwrap_or(0.0);
+        let steps = LoadStage::Done as u8 as f32;
+
+        (stage as u8 as f32 + within_stage) / steps
+    }
+
+    pub(crate) fn start_stage(&self, stage: LoadStage, total: usize) {
+        self.completed.store(0, Ordering::Release);
+        self.total.store(total, Ordering::Release);
+        self.stage.store(stage as u8, Ordering::Release);
+    }
+
+    pub(crate) fn complete_item(&self) {
+        self.completed.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fraction_advances_through_stages() {
+        let progress = LoadProgress::new();
+        assert_eq!(progress.fraction(), 0.0);
+
+        progress.start_stage(LoadStage::Baking, 4);
+        let before = progress.fraction();
+        progress.complete_item();
+        progress.complete_item();
+        assert!(progress.fraction() > before);
+        assert_eq!(progress.items(), Some((2, 4)));
+
+        progress.start_stage(LoadStage::Done, 0);
+        assert_eq!(progress.fraction(), 1.0);
+    }
+}
@@ -0,0 +1,62 @@
+use brine_data::{blocks::RenderLayer, BlockStateId, MinecraftData};
+
+use super::{model_keys, MinecraftAssets};
+
+/// How a block state is drawn, from its models and block data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockRenderProperties {
+    pub render_layer: RenderLayer,
+
+    /// Whether every model the block state can have leaves ambient occlusion
+    /// on.
+    pub ambient_occlusion: bool,
+
+    /// Light level the block gives off, from 0 to 15.
+    pub light_emission: u8,
+}
+
+impl Default for BlockRenderProperties {
+    fn default() -> Self {
+        Self {
+            render_layer: RenderLayer::Solid,
+            ambient_occlusion: true,
+            light_emission: 0,
+        }
+    }
+}
+
+impl BlockRenderProperties {
+    /// Returns whether ambient occlusion darkens the block's faces. Like
+    /// vanilla, blocks that give off light are never darkened.
+    #[inline]
+    pub fn smooth_lighting(&self) -> bool {
+        self.ambient_occlusion && self.light_emission == 0
+    }
+}
+
+impl MinecraftAssets {
+    /// Returns how a block state is drawn, or `None` if there is no such
+    /// block state.
+    pub fn block_render_properties(
+        &self,
+        mc_data: &MinecraftData,
+        block_state_id: BlockStateId,
+    ) -> Option<BlockRenderProperties> {
+        let block = mc_data.blocks().get_by_state_id(block_state_id)?;
+
+        let ambient_occlusion =
+            self.block_states()
+                .get_by_key(block_state_id)
+                .is_none_or(|block_state| {
+                    model_keys(block_state)
+                        .filter_map(|key| self.models().get_by_key(key))
+                        .all(|model| model.ambient_occlusion)
+                });
+
+        Some(BlockRenderProperties {
+            render_layer: block.render_layer(),
+            ambient_occlusion,
+            light_emission: block.light_emission,
+        })
+    }
+}
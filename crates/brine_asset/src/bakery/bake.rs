@@ -1,22 +1,26 @@
 use minecraft_assets::api::{AssetPack, Result};
 
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use tracing::*;
 
 use brine_data::{BlockStateId, MinecraftData};
 
-use crate::bakery::{
-    self,
-    block_states::{
-        BakedBlockState, BakedBlockStateTable, BlockStateGrabBag, BlockStatesBakery,
-        HalfBakedBlockState, HalfBakedGrabBagChoice,
+use crate::{
+    api::{LoadProgress, LoadStage},
+    bakery::{
+        self,
+        block_states::{
+            BakedBlockState, BakedBlockStateTable, BlockStateGrabBag, BlockStatesBakery,
+            HalfBakedBlockState, HalfBakedGrabBagChoice,
+        },
+        models::{BakedModel, BakedModelKey, BakedModelTable, ModelBakery},
+        textures::TextureTable,
     },
-    models::{BakedModelTable, ModelBakery},
-    textures::TextureTable,
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct BakedAssets {
     pub block_states: BakedBlockStateTable,
     pub models: BakedModelTable,
@@ -24,19 +28,36 @@ pub struct BakedAssets {
 }
 
 pub fn bake_all(mc_data: &MinecraftData, asset_pack: &AssetPack) -> Result<BakedAssets> {
+    bake_all_with_progress(mc_data, asset_pack, &LoadProgress::new())
+}
+
+/// Same as [`bake_all`], but reports progress as it goes.
+pub fn bake_all_with_progress(
+    mc_data: &MinecraftData,
+    asset_pack: &AssetPack,
+    progress: &LoadProgress,
+) -> Result<BakedAssets> {
+    progress.start_stage(LoadStage::Textures, 0);
     let texture_table = bakery::textures::load_texture_table(asset_pack)?;
 
+    progress.start_stage(LoadStage::Models, 0);
     let unbaked_models = bakery::models::load_unbaked_block_models(asset_pack)?;
     let model_bakery = ModelBakery::new(&unbaked_models, &texture_table);
 
+    progress.start_stage(LoadStage::BlockStates, 0);
     let unbaked_block_states = bakery::block_states::load_unbaked_block_states(asset_pack)?;
     let block_states_bakery = BlockStatesBakery::new(mc_data, &unbaked_block_states, model_bakery);
 
     // (Half-)Bake block states in parallel.
+    progress.start_stage(LoadStage::Baking, unbaked_block_states.len());
     let half_baked_block_states: Vec<(BlockStateId, HalfBakedBlockState)> = unbaked_block_states
         .par_iter()
         .map(|(key, _)| key)
-        .flat_map(|block_name| block_states_bakery.bake_block_states_for_block(block_name))
+        .flat_map(|block_name| {
+            let baked = block_states_bakery.bake_block_states_for_block(block_name);
+            progress.complete_item();
+            baked
+        })
         .collect();
 
     debug!("Finished half-baking block states");
@@ -72,39 +93,8 @@ pub fn bake_all(mc_data: &MinecraftData, asset_pack: &AssetPack) -> Result<Baked
     for (block_state_id, half_baked_block_state) in half_baked_block_states.into_iter() {
         trace!("{:?}", block_state_id);
 
-        // The block state is a full cube if all of its models are full cubes.
-        let is_full_cube = half_baked_block_state.models.iter().all(|grab_bag| {
-            grab_bag
-                .choices
-                .iter()
-                .all(|choice| choice.model.is_full_cube)
-        });
-
-        let baked_grab_bags = half_baked_block_state
-            .models
-            .into_iter()
-            .map(|half_baked_grab_bag| {
-                let mut choices = SmallVec::new();
-
-                for HalfBakedGrabBagChoice { model, weight } in
-                    half_baked_grab_bag.choices.into_iter()
-                {
-                    let model_key = baked_models.insert(model);
-                    for _ in 0..weight {
-                        choices.push(model_key);
-                    }
-                }
-
-                BlockStateGrabBag { choices }
-            })
-            .collect();
-
-        let baked_block_state = BakedBlockState {
-            models: baked_grab_bags,
-            is_full_cube,
-        };
-
-        baked_block_states[block_state_id.0 as usize] = baked_block_state;
+        baked_block_states[block_state_id.0 as usize] =
+            finish_block_state(half_baked_block_state, |model| baked_models.insert(model));
     }
 
     debug!("Finished fully baking block states");
@@ -118,6 +108,8 @@ pub fn bake_all(mc_data: &MinecraftData, asset_pack: &AssetPack) -> Result<Baked
     //         .collect::<Vec<_>>()
     // );
 
+    progress.start_stage(LoadStage::Done, 0);
+
     Ok(BakedAssets {
         block_states: BakedBlockStateTable {
             block_states: baked_block_states,
@@ -126,3 +118,41 @@ pub fn bake_all(mc_data: &MinecraftData, asset_pack: &AssetPack) -> Result<Baked
         textures: texture_table,
     })
 }
+
+/// Finishes baking a block state by adding its models to a model table with
+/// `insert_model`, which returns the key of each.
+pub(crate) fn finish_block_state(
+    half_baked_block_state: HalfBakedBlockState,
+    mut insert_model: impl FnMut(BakedModel) -> BakedModelKey,
+) -> BakedBlockState {
+    // The block state is a full cube if all of its models are full cubes.
+    let is_full_cube = half_baked_block_state.models.iter().all(|grab_bag| {
+        grab_bag
+            .choices
+            .iter()
+            .all(|choice| choice.model.is_full_cube)
+    });
+
+    let baked_grab_bags = half_baked_block_state
+        .models
+        .into_iter()
+        .map(|half_baked_grab_bag| {
+            let mut choices = SmallVec::new();
+
+            for HalfBakedGrabBagChoice { model, weight } in half_baked_grab_bag.choices.into_iter()
+            {
+                let model_key = insert_model(model);
+                for _ in 0..weight {
+                    choices.push(model_key);
+                }
+            }
+
+            BlockStateGrabBag { choices }
+        })
+        .collect();
+
+    BakedBlockState {
+        models: baked_grab_bags,
+        is_full_cube,
+    }
+}
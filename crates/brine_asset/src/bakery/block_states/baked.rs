@@ -1,10 +1,11 @@
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 use brine_data::BlockStateId;
 
 use crate::bakery::models::BakedModelKey;
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct BakedBlockState {
     pub is_full_cube: bool,
     pub models: SmallVec<[BlockStateGrabBag; 1]>,
@@ -18,12 +19,12 @@ impl BakedBlockState {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct BlockStateGrabBag {
     pub choices: SmallVec<[BakedModelKey; 1]>,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct BakedBlockStateTable {
     /// Indexed by [`BlockStateId`].
     pub block_states: Vec<BakedBlockState>,
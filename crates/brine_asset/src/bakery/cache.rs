@@ -0,0 +1,296 @@
+//! On-disk cache of [`BakedAssets`].
+//!
+//! Block states are baked as they are looked up (see the [`lazy`] module),
+//! but the models and block state definitions still have to be read and
+//! parsed first, and the result only changes when the assets or the block
+//! data do. The cache file stores the baked tables along with a hash of
+//! everything they were baked from; if the hash still matches on the next
+//! run, nothing is read or baked at all.
+//!
+//! [`lazy`]: crate::bakery::lazy
+
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use tracing::*;
+
+use brine_data::MinecraftData;
+
+use crate::bakery::BakedAssets;
+
+/// Bumped whenever the layout of [`BakedAssets`] or the baking process changes,
+/// so that stale caches from older builds are ignored.
+const CACHE_FORMAT_VERSION: u32 = 3;
+
+/// Asset directories whose contents affect the baked output.
+const HASHED_DIRS: [&str; 3] = ["blockstates", "models", "textures"];
+
+/// A bake cache file, for the assets it was opened for.
+///
+/// Problems reading or writing the cache are logged and otherwise ignored.
+#[derive(Debug, Clone)]
+pub struct BakeCache {
+    path: PathBuf,
+
+    /// `None` if the assets couldn't be hashed, in which case the cache is
+    /// neither read nor written.
+    hash: Option<u64>,
+}
+
+impl BakeCache {
+    /// Opens the cache file at `path` for the assets in `roots`: the vanilla
+    /// assets followed by any resource packs layered over them.
+    pub fn new(path: &Path, roots: &[&Path], mc_data: &MinecraftData) -> Self {
+        let hash = asset_hash(roots, mc_data)
+            .map_err(|err| warn!("Failed to hash assets, not using bake cache: {}", err))
+            .ok();
+
+        Self {
+            path: path.to_path_buf(),
+            hash,
+        }
+    }
+
+    /// Returns the baked assets in the cache file, or `None` if there are
+    /// none or they were baked from different assets.
+    pub fn read(&self) -> Option<BakedAssets> {
+        let hash = self.hash?;
+
+        match read_cache(&self.path, hash) {
+            Ok(Some(baked)) => {
+                info!("Loaded baked assets from {}", self.path.display());
+                Some(baked)
+            }
+            Ok(None) => {
+                debug!("Bake cache at {} is missing or stale", self.path.display());
+                None
+            }
+            Err(err) => {
+                warn!("Failed to read bake cache {}: {}", self.path.display(), err);
+                None
+            }
+        }
+    }
+
+    /// Replaces the cache file with `baked`.
+    pub fn write(&self, baked: &BakedAssets) {
+        let Some(hash) = self.hash else {
+            return;
+        };
+
+        match write_cache(&self.path, hash, baked) {
+            Ok(()) => debug!("Wrote bake cache to {}", self.path.display()),
+            Err(err) => warn!(
+                "Failed to write bake cache {}: {}",
+                self.path.display(),
+                err
+            ),
+        }
+    }
+}
+
+/// Reads the cache file, returning `None` if it doesn't exist or was made from
+/// different assets.
+fn read_cache(path: &Path, expected_hash: u64) -> bincode::Result<Option<BakedAssets>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut reader = BufReader::new(file);
+
+    let (format_version, hash): (u32, u64) = bincode::deserialize_from(&mut reader)?;
+    if format_version != CACHE_FORMAT_VERSION || hash != expected_hash {
+        return Ok(None);
+    }
+
+    Ok(Some(bincode::deserialize_from(&mut reader)?))
+}
+
+/// Reads baked assets from the contents of a cache file without checking
+/// which assets they were baked from, for where the assets can't be listed to
+/// hash them (e.g. in the browser).
+pub fn read_baked(mut bytes: &[u8]) -> bincode::Result<BakedAssets> {
+    let (format_version, _hash): (u32, u64) = bincode::deserialize_from(&mut bytes)?;
+    if format_version != CACHE_FORMAT_VERSION {
+        return Err(Box::new(bincode::ErrorKind::Custom(format!(
+            "bake cache format version {format_version} is not {CACHE_FORMAT_VERSION}"
+        ))));
+    }
+
+    bincode::deserialize_from(&mut bytes)
+}
+
+fn write_cache(path: &Path, hash: u64, baked: &BakedAssets) -> bincode::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    bincode::serialize_into(&mut writer, &(CACHE_FORMAT_VERSION, hash))?;
+    bincode::serialize_into(&mut writer, baked)?;
+
+    Ok(())
+}
+
+/// Computes a hash identifying the inputs to baking: the data version and the
+/// name, size, and modification time of every relevant asset file in each of
+/// the layered `roots`.
+///
+/// File contents are not read, which keeps this fast enough to run on every
+/// startup.
+fn asset_hash(roots: &[&Path], mc_data: &MinecraftData) -> io::Result<u64> {
+    let mut hasher = Fnv1a::new();
+    hasher.write(&CACHE_FORMAT_VERSION.to_le_bytes());
+    hasher.write(mc_data.version().minecraft_version.as_bytes());
+
+    for (layer, root) in roots.iter().enumerate() {
+        hasher.write(&(layer as u64).to_le_bytes());
+
+        let namespace_dir = root.join("assets").join("minecraft");
+        for dir in HASHED_DIRS {
+            let mut files = Vec::new();
+            collect_files(&namespace_dir.join(dir), &mut files)?;
+            files.sort();
+
+            for file in files {
+                let metadata = fs::metadata(&file)?;
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_nanos())
+                    .unwrap_or_default();
+
+                let relative = file.strip_prefix(root).unwrap_or(&file);
+                hasher.write(relative.to_string_lossy().as_bytes());
+                hasher.write(&metadata.len().to_le_bytes());
+                hasher.write(&modified.to_le_bytes());
+            }
+        }
+    }
+
+    Ok(hasher.finish())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+
+    Ok(())
+}
+
+/// 64-bit FNV-1a.
+///
+/// Used instead of [`std::hash::DefaultHasher`], whose output may change
+/// between Rust releases, so that the hash stays stable across builds.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft_assets::api::{ResourceIdentifier, ResourceKind};
+
+    use super::*;
+    use crate::bakery::{
+        block_states::{BakedBlockState, BakedBlockStateTable},
+        models::{BakedModel, BakedModelTable},
+        textures::TextureTable,
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("brine_asset_{}_{}", std::process::id(), name))
+    }
+
+    fn sample_assets() -> BakedAssets {
+        let mut textures = TextureTable::default();
+        textures.insert(ResourceIdentifier::new_owned(
+            ResourceKind::Texture,
+            String::from("block/stone"),
+        ));
+
+        BakedAssets {
+            block_states: BakedBlockStateTable {
+                block_states: vec![BakedBlockState {
+                    is_full_cube: true,
+                    models: Default::default(),
+                }],
+            },
+            models: BakedModelTable {
+                models: vec![BakedModel::default()],
+            },
+            textures,
+        }
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let path = temp_path("round_trip.bin");
+        let assets = sample_assets();
+
+        write_cache(&path, 42, &assets).unwrap();
+        let read = read_cache(&path, 42).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, Some(assets));
+    }
+
+    #[test]
+    fn cache_with_different_hash_is_stale() {
+        let path = temp_path("stale.bin");
+
+        write_cache(&path, 1, &sample_assets()).unwrap();
+        let read = read_cache(&path, 2).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, None);
+    }
+
+    #[test]
+    fn baked_assets_read_from_cache_contents() {
+        let path = temp_path("contents.bin");
+        let assets = sample_assets();
+
+        write_cache(&path, 7, &assets).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_baked(&bytes).unwrap(), assets);
+        assert!(read_baked(&bytes[..4]).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! Baking block states the first time they are looked up.
+//!
+//! Baking every block state up front takes several seconds, most of it spent
+//! on blocks a session may never see. A [`LazyBlockStateTable`] instead bakes
+//! a block's states (all of them at once, since they share models) the first
+//! time any of them is looked up, from any thread.
+//!
+//! Baked models go into a [`LazyModelTable`] shared by every block state, so
+//! [`BakedModelKey`]s work the same as in a [`BakedModelTable`]. Models are
+//! never moved once added, so a reference to one stays valid while other
+//! threads add more.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+use rayon::prelude::*;
+use tracing::*;
+
+use brine_data::{BlockId, BlockStateId, MinecraftData};
+
+use crate::bakery::{
+    bake::finish_block_state,
+    block_states::{
+        BakedBlockState, BakedBlockStateTable, BlockStatesBakery, UnbakedBlockStatesTable,
+    },
+    models::{BakedModel, BakedModelKey, BakedModelTable, ModelBakery, UnbakedModels},
+    textures::TextureTable,
+};
+
+/// Number of models in the first segment of a [`LazyModelTable`]. Each
+/// segment after it is twice as large as the one before.
+const FIRST_SEGMENT_LEN: usize = 1024;
+
+/// Number of segments of a [`LazyModelTable`], enough for over a billion
+/// models.
+const SEGMENTS: usize = 20;
+
+/// Everything needed to bake a block state, kept around until every state
+/// has been baked.
+struct Bakery {
+    mc_data: MinecraftData,
+    unbaked_models: UnbakedModels,
+    unbaked_block_states: UnbakedBlockStatesTable,
+    textures: TextureTable,
+}
+
+/// Baked block states, indexed by [`BlockStateId`], each baked when first
+/// looked up.
+pub struct LazyBlockStateTable {
+    block_states: Box<[OnceLock<BakedBlockState>]>,
+
+    /// Set once the states of the block at each index in
+    /// [`Blocks`][brine_data::blocks::Blocks] have been baked.
+    baked_blocks: Box<[OnceLock<()>]>,
+
+    models: LazyModelTable,
+
+    /// `None` if every block state was baked already.
+    bakery: Option<Bakery>,
+}
+
+impl LazyBlockStateTable {
+    /// Creates a table that bakes each block state of `mc_data` from the
+    /// given models and block state definitions when it is looked up.
+    pub fn new(
+        mc_data: &MinecraftData,
+        unbaked_models: UnbakedModels,
+        unbaked_block_states: UnbakedBlockStatesTable,
+        textures: TextureTable,
+    ) -> Self {
+        let blocks = mc_data.blocks();
+
+        Self {
+            block_states: empty_slots(blocks.state_id_to_block.len()),
+            baked_blocks: empty_slots(blocks.count()),
+            models: LazyModelTable::default(),
+            bakery: Some(Bakery {
+                mc_data: mc_data.clone(),
+                unbaked_models,
+                unbaked_block_states,
+                textures,
+            }),
+        }
+    }
+
+    /// Creates a table of block states that were all baked already, e.g. read
+    /// from the bake cache.
+    pub fn from_baked(block_states: BakedBlockStateTable, models: BakedModelTable) -> Self {
+        let table = Self {
+            block_states: block_states
+                .block_states
+                .into_iter()
+                .map(OnceLock::from)
+                .collect(),
+            baked_blocks: Box::default(),
+            models: LazyModelTable::default(),
+            bakery: None,
+        };
+
+        // Inserted in order, so the keys stay the same.
+        for model in models.models {
+            table.models.insert(model);
+        }
+
+        table
+    }
+
+    /// Returns the baked block state, baking it first if it hasn't been, or
+    /// `None` if there is no such block state.
+    pub fn get_by_key(&self, key: BlockStateId) -> Option<&BakedBlockState> {
+        let slot = self.block_states.get(key.0 as usize)?;
+        if let Some(block_state) = slot.get() {
+            return Some(block_state);
+        }
+
+        if let Some(bakery) = &self.bakery {
+            let block_index = *bakery
+                .mc_data
+                .blocks()
+                .state_id_to_block
+                .get(key.0 as usize)? as usize;
+            self.bake_block(bakery, block_index);
+        }
+
+        // States without a block state definition have no models.
+        Some(slot.get_or_init(BakedBlockState::default))
+    }
+
+    /// Returns the baked block state, or `None` if it hasn't been baked yet or
+    /// there is no such block state.
+    pub fn get_baked(&self, key: BlockStateId) -> Option<&BakedBlockState> {
+        self.block_states.get(key.0 as usize)?.get()
+    }
+
+    /// Returns the number of block states, baked or not.
+    pub fn len(&self) -> usize {
+        self.block_states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.block_states.is_empty()
+    }
+
+    /// Returns the table the models of these block states are in.
+    pub fn models(&self) -> &LazyModelTable {
+        &self.models
+    }
+
+    /// Bakes every block state that hasn't been yet, in parallel.
+    pub fn bake_all(&self) {
+        let Some(bakery) = &self.bakery else {
+            return;
+        };
+
+        (0..self.baked_blocks.len())
+            .into_par_iter()
+            .for_each(|block_index| self.bake_block(bakery, block_index));
+
+        for slot in self.block_states.iter() {
+            slot.get_or_init(BakedBlockState::default);
+        }
+
+        debug!("Finished baking every block state");
+    }
+
+    /// Copies the block states and models into plain tables, e.g. to write
+    /// them to the bake cache. Block states that haven't been baked are
+    /// left empty, so call [`bake_all`](Self::bake_all) first.
+    pub fn to_baked(&self) -> (BakedBlockStateTable, BakedModelTable) {
+        let block_states = BakedBlockStateTable {
+            block_states: self
+                .block_states
+                .iter()
+                .map(|slot| slot.get().cloned().unwrap_or_default())
+                .collect(),
+        };
+
+        let models = BakedModelTable {
+            models: (0..self.models.len())
+                .map(|index| {
+                    self.models
+                        .get_by_key(BakedModelKey(index))
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect(),
+        };
+
+        (block_states, models)
+    }
+
+    /// Bakes the states of the block at `block_index`, unless they have been
+    /// already. Other threads wanting the same block wait for it.
+    fn bake_block(&self, bakery: &Bakery, block_index: usize) {
+        let Some(baked) = self.baked_blocks.get(block_index) else {
+            return;
+        };
+
+        baked.get_or_init(|| {
+            // A block's id is its index.
+            let block_id = BlockId(block_index as _);
+            let Some(block) = bakery.mc_data.blocks().get_by_id(block_id) else {
+                return;
+            };
+            if !bakery.unbaked_block_states.contains_key(block.name) {
+                return;
+            }
+
+            let model_bakery = ModelBakery::new(&bakery.unbaked_models, &bakery.textures);
+            let block_states_bakery =
+                BlockStatesBakery::new(&bakery.mc_data, &bakery.unbaked_block_states, model_bakery);
+
+            for (block_state_id, half_baked) in
+                block_states_bakery.bake_block_states_for_block(block.name)
+            {
+                let baked = finish_block_state(half_baked, |model| self.models.insert(model));
+                if let Some(slot) = self.block_states.get(block_state_id.0 as usize) {
+                    let _ = slot.set(baked);
+                }
+            }
+        });
+    }
+}
+
+impl fmt::Debug for LazyBlockStateTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let baked = self
+            .block_states
+            .iter()
+            .filter(|slot| slot.get().is_some())
+            .count();
+
+        f.debug_struct("LazyBlockStateTable")
+            .field("len", &self.len())
+            .field("baked", &baked)
+            .field("models", &self.models)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Baked models, which can be added to while other threads read them.
+///
+/// Models are kept in segments that are never moved or freed until the table
+/// is dropped, each twice as large as the one before, so adding a model never
+/// invalidates a reference to another.
+pub struct LazyModelTable {
+    segments: [OnceLock<Box<[OnceLock<BakedModel>]>>; SEGMENTS],
+    len: AtomicUsize,
+}
+
+impl LazyModelTable {
+    pub fn insert(&self, baked_model: BakedModel) -> BakedModelKey {
+        let index = self.len.fetch_add(1, Ordering::Relaxed);
+
+        let (segment, offset) = locate(index);
+        let segment =
+            self.segments[segment].get_or_init(|| empty_slots(FIRST_SEGMENT_LEN << segment));
+        let _ = segment[offset].set(baked_model);
+
+        BakedModelKey(index)
+    }
+
+    pub fn get_by_key(&self, key: BakedModelKey) -> Option<&BakedModel> {
+        let (segment, offset) = locate(key.0);
+
+        self.segments.get(segment)?.get()?.get(offset)?.get()
+    }
+
+    /// Returns the number of models added so far.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for LazyModelTable {
+    fn default() -> Self {
+        Self {
+            segments: std::array::from_fn(|_| OnceLock::new()),
+            len: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl fmt::Debug for LazyModelTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyModelTable")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the segment of a [`LazyModelTable`] that the model at `index` is
+/// in, and its offset within that segment.
+fn locate(index: usize) -> (usize, usize) {
+    let segment = (index / FIRST_SEGMENT_LEN + 1).ilog2() as usize;
+    let segment_start = FIRST_SEGMENT_LEN * ((1 << segment) - 1);
+
+    (segment, index - segment_start)
+}
+
+fn empty_slots<T>(len: usize) -> Box<[OnceLock<T>]> {
+    (0..len).map(|_| OnceLock::new()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(is_full_cube: bool) -> BakedModel {
+        BakedModel {
+            is_full_cube,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn models_keep_their_keys_across_segments() {
+        let models = LazyModelTable::default();
+        let count = FIRST_SEGMENT_LEN * 3 + 1;
+
+        for index in 0..count {
+            assert_eq!(models.insert(model(index % 2 == 0)), BakedModelKey(index));
+        }
+
+        assert_eq!(locate(FIRST_SEGMENT_LEN * 3), (2, 0));
+        assert_eq!(models.len(), count);
+        assert!(
+            models
+                .get_by_key(BakedModelKey(count - 1))
+                .unwrap()
+                .is_full_cube
+        );
+        assert!(
+            !models
+                .get_by_key(BakedModelKey(FIRST_SEGMENT_LEN + 1))
+                .unwrap()
+                .is_full_cube
+        );
+        assert!(models.get_by_key(BakedModelKey(count)).is_none());
+    }
+}
@@ -1,6 +1,8 @@
 mod bake;
 pub mod block_states;
+pub mod cache;
+pub mod lazy;
 pub mod models;
 pub mod textures;
 
-pub use bake::{bake_all, BakedAssets};
+pub use bake::{bake_all, bake_all_with_progress, BakedAssets};
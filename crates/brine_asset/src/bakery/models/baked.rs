@@ -1,10 +1,14 @@
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 use minecraft_assets::schemas::models::BlockFace;
 
-use crate::bakery::{models::Cuboid, textures::TextureKey};
+use crate::bakery::{
+    models::{Cuboid, DisplayTransforms},
+    textures::TextureKey,
+};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct BakedQuad {
     pub positions: [[f32; 3]; 4],
 
@@ -35,22 +39,27 @@ pub struct BakedCuboid {
     pub quads: SmallVec<[BakedQuad; 6]>,
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct BakedModel {
     pub is_full_cube: bool,
     pub quads: SmallVec<[BakedQuad; 6]>,
+
+    /// Whether ambient occlusion darkens the model's faces (the model's
+    /// `ambientocclusion`, on unless it or a parent turns it off).
+    pub ambient_occlusion: bool,
+
+    /// How the model is placed when drawn as an item.
+    pub display: DisplayTransforms,
     /*
     TODO:
-        - ambient_occlusion
-        - display_transforms
         - gui_light_mode
     */
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct BakedModelKey(pub usize);
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct BakedModelTable {
     pub models: Vec<BakedModel>,
 }
@@ -0,0 +1,233 @@
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use minecraft_assets::schemas::models::{Display, Transform};
+
+use crate::bakery::models::UnbakedModel;
+
+/// Where a model is drawn as an item, each with its own [`DisplayTransform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum DisplayContext {
+    ThirdPersonRightHand,
+    ThirdPersonLeftHand,
+    FirstPersonRightHand,
+    FirstPersonLeftHand,
+    /// In inventories and the hotbar.
+    Gui,
+    /// Worn on the head.
+    Head,
+    /// Dropped on the ground.
+    Ground,
+    /// In an item frame.
+    Fixed,
+}
+
+impl DisplayContext {
+    pub const ALL: [Self; 8] = [
+        Self::ThirdPersonRightHand,
+        Self::ThirdPersonLeftHand,
+        Self::FirstPersonRightHand,
+        Self::FirstPersonLeftHand,
+        Self::Gui,
+        Self::Head,
+        Self::Ground,
+        Self::Fixed,
+    ];
+
+    #[inline]
+    pub fn is_left_hand(self) -> bool {
+        matches!(self, Self::ThirdPersonLeftHand | Self::FirstPersonLeftHand)
+    }
+
+    /// The context whose transform a left hand uses when it has none of its
+    /// own.
+    fn right_hand(self) -> Option<Self> {
+        match self {
+            Self::ThirdPersonLeftHand => Some(Self::ThirdPersonRightHand),
+            Self::FirstPersonLeftHand => Some(Self::FirstPersonRightHand),
+            _ => None,
+        }
+    }
+
+    fn transform_in(self, display: &Display) -> Option<&Transform> {
+        match self {
+            Self::ThirdPersonRightHand => display.thirdperson_righthand.as_ref(),
+            Self::ThirdPersonLeftHand => display.thirdperson_lefthand.as_ref(),
+            Self::FirstPersonRightHand => display.firstperson_righthand.as_ref(),
+            Self::FirstPersonLeftHand => display.firstperson_lefthand.as_ref(),
+            Self::Gui => display.gui.as_ref(),
+            Self::Head => display.head.as_ref(),
+            Self::Ground => display.ground.as_ref(),
+            Self::Fixed => display.fixed.as_ref(),
+        }
+    }
+}
+
+/// How a model is placed in a [`DisplayContext`]: translated, then rotated,
+/// then scaled about its center.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct DisplayTransform {
+    /// Rotation about the X, Y and Z axes, in that order, in degrees.
+    pub rotation: [f32; 3],
+
+    /// Translation in blocks, at most 5 along each axis.
+    pub translation: [f32; 3],
+
+    /// Scale along each axis, at most 4.
+    pub scale: [f32; 3],
+}
+
+impl Default for DisplayTransform {
+    fn default() -> Self {
+        Self {
+            rotation: [0.0; 3],
+            translation: [0.0; 3],
+            scale: [1.0; 3],
+        }
+    }
+}
+
+impl From<&Transform> for DisplayTransform {
+    /// Converts the translation from sixteenths of a block and clamps it and
+    /// the scale, like vanilla.
+    fn from(transform: &Transform) -> Self {
+        Self {
+            rotation: transform.rotation,
+            translation: transform
+                .translation
+                .map(|component| (component / 16.0).clamp(-5.0, 5.0)),
+            scale: transform.scale.map(|component| component.clamp(-4.0, 4.0)),
+        }
+    }
+}
+
+impl DisplayTransform {
+    /// Returns the transform as a matrix, for a model centered on the origin.
+    /// Left hands mirror it across the X axis.
+    pub fn matrix(&self, left_hand: bool) -> Mat4 {
+        let [x, mut y, mut z] = self.rotation.map(f32::to_radians);
+        let mut translation = Vec3::from(self.translation);
+        if left_hand {
+            y = -y;
+            z = -z;
+            translation.x = -translation.x;
+        }
+
+        Mat4::from_scale_rotation_translation(
+            Vec3::from(self.scale),
+            Quat::from_euler(EulerRot::XYZ, x, y, z),
+            translation,
+        )
+    }
+}
+
+/// A model's [`DisplayTransform`] in each [`DisplayContext`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DisplayTransforms {
+    transforms: [DisplayTransform; 8],
+}
+
+impl DisplayTransforms {
+    /// Resolves each context's transform from the first model in
+    /// `parent_chain` (the model, then its parents) that sets it, so a model
+    /// can override some contexts and inherit the rest. Left hands without
+    /// one of their own use the right hand's, and contexts no model sets
+    /// aren't transformed.
+    pub fn resolve(parent_chain: &[&UnbakedModel]) -> Self {
+        let find = |context: DisplayContext| {
+            parent_chain.iter().find_map(|model| {
+                model
+                    .display
+                    .as_ref()
+                    .and_then(|display| context.transform_in(display))
+            })
+        };
+
+        Self {
+            transforms: DisplayContext::ALL.map(|context| {
+                find(context)
+                    .or_else(|| context.right_hand().and_then(find))
+                    .map(DisplayTransform::from)
+                    .unwrap_or_default()
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, context: DisplayContext) -> &DisplayTransform {
+        &self.transforms[context as usize]
+    }
+
+    /// Returns the matrix placing the model, centered on the origin, in
+    /// `context`.
+    #[inline]
+    pub fn matrix(&self, context: DisplayContext) -> Mat4 {
+        self.get(context).matrix(context.is_left_hand())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(parent: Option<&str>, display: Display) -> UnbakedModel {
+        UnbakedModel {
+            parent: parent.map(String::from),
+            display: Some(display),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn contexts_are_inherited_one_by_one() {
+        let parent = model(
+            None,
+            Display {
+                gui: Some(Transform {
+                    rotation: [30.0, 225.0, 0.0],
+                    translation: [0.0; 3],
+                    scale: [0.625; 3],
+                }),
+                thirdperson_righthand: Some(Transform {
+                    rotation: [75.0, 45.0, 0.0],
+                    translation: [0.0, 2.5, 0.0],
+                    scale: [0.375; 3],
+                }),
+                ..Default::default()
+            },
+        );
+        let child = model(
+            Some("block/parent"),
+            Display {
+                ground: Some(Transform {
+                    rotation: [0.0; 3],
+                    translation: [0.0, 3.0, 200.0],
+                    scale: [0.25; 3],
+                }),
+                ..Default::default()
+            },
+        );
+
+        let transforms = DisplayTransforms::resolve(&[&child, &parent]);
+        let ground = transforms.get(DisplayContext::Ground);
+        assert_eq!(ground.translation, [0.0, 3.0 / 16.0, 5.0]);
+        assert_eq!(ground.scale, [0.25; 3]);
+        assert_eq!(transforms.get(DisplayContext::Gui).scale, [0.625; 3]);
+        assert_eq!(
+            transforms.get(DisplayContext::ThirdPersonLeftHand),
+            transforms.get(DisplayContext::ThirdPersonRightHand)
+        );
+        assert_eq!(
+            *transforms.get(DisplayContext::Fixed),
+            DisplayTransform::default()
+        );
+
+        // The left hand mirrors the right.
+        let right = transforms.matrix(DisplayContext::ThirdPersonRightHand);
+        let left = transforms.matrix(DisplayContext::ThirdPersonLeftHand);
+        let point = Vec3::new(0.5, 0.0, 0.0);
+        let mirrored = right.transform_point3(point * Vec3::new(-1.0, 1.0, 1.0));
+        let expected = left.transform_point3(point) * Vec3::new(-1.0, 1.0, 1.0);
+        assert!(mirrored.abs_diff_eq(expected, 1e-5));
+    }
+}
@@ -1,12 +1,14 @@
 mod baked;
 mod cuboid_bakery;
 mod cuboid_math;
+mod display;
 mod model_bakery;
 mod unbaked;
 
 pub use baked::{BakedCuboid, BakedModel, BakedModelKey, BakedModelTable, BakedQuad};
 pub use cuboid_bakery::CuboidBakery;
 pub use cuboid_math::{Cuboid, CuboidRotation, EighthRotation, QuarterRotation};
+pub use display::{DisplayContext, DisplayTransform, DisplayTransforms};
 pub use model_bakery::ModelBakery;
 pub use unbaked::{
     load_unbaked_block_models, UnbakedCuboid, UnbakedModel, UnbakedModels, UnbakedQuad,
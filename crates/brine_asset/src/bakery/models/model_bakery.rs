@@ -7,8 +7,8 @@ use tracing::*;
 
 use crate::bakery::{
     models::{
-        cuboid_math::QuadRotation, BakedCuboid, BakedModel, CuboidBakery, UnbakedCuboid,
-        UnbakedModel, UnbakedModels,
+        cuboid_math::QuadRotation, BakedCuboid, BakedModel, CuboidBakery, DisplayTransforms,
+        UnbakedCuboid, UnbakedModel, UnbakedModels,
     },
     textures::TextureTable,
 };
@@ -71,9 +71,14 @@ impl<'a> ModelBakery<'a> {
             }
         }
 
+        let ambient_occlusion =
+            ModelResolver::resolve_ambient_occlusion(parent_chain.iter().copied()).unwrap_or(true);
+
         Some(BakedModel {
             quads: baked_quads,
             is_full_cube: all_cuboids_full_cubes,
+            ambient_occlusion,
+            display: DisplayTransforms::resolve(&parent_chain),
         })
     }
 
@@ -1,10 +1,12 @@
 use indexmap::IndexSet;
 use minecraft_assets::api::{AssetPack, ResourceIdentifier, ResourceKind, Result};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct TextureKey(pub usize);
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "Vec<String>", into = "Vec<String>")]
 pub struct TextureTable {
     textures: IndexSet<ResourceIdentifier<'static>>,
 }
@@ -36,6 +38,27 @@ impl TextureTable {
     }
 }
 
+/// Texture tables are serialized as a list of texture ids, in key order.
+impl From<Vec<String>> for TextureTable {
+    fn from(ids: Vec<String>) -> Self {
+        let mut table = Self::default();
+        for id in ids {
+            table.insert(ResourceIdentifier::new_owned(ResourceKind::Texture, id));
+        }
+        table
+    }
+}
+
+impl From<TextureTable> for Vec<String> {
+    fn from(table: TextureTable) -> Self {
+        table
+            .textures
+            .iter()
+            .map(|id| id.as_ref().to_string())
+            .collect()
+    }
+}
+
 pub fn load_texture_table(assets: &AssetPack) -> Result<TextureTable> {
     let mut table = TextureTable::default();
 
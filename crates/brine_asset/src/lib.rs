@@ -4,9 +4,13 @@
 pub mod api;
 pub mod bakery;
 
-pub use api::{BlockFace, MinecraftAssets};
+pub use api::{BlockFace, BlockRenderProperties, LoadProgress, LoadStage, MinecraftAssets};
 pub use bakery::{
     block_states::BakedBlockStateTable,
-    models::{BakedModel, BakedModelKey, BakedModelTable, BakedQuad},
+    lazy::{LazyBlockStateTable, LazyModelTable},
+    models::{
+        BakedModel, BakedModelKey, BakedModelTable, BakedQuad, DisplayContext, DisplayTransform,
+        DisplayTransforms,
+    },
     textures::{TextureKey, TextureTable},
 };
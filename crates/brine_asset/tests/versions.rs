@@ -0,0 +1,86 @@
+//! Baking the vanilla assets of every Minecraft version the client can load.
+//!
+//! Needs each version's assets at `assets/<version>` in the workspace, from
+//! `cargo xtask fetch-assets --version <version>`, and so only runs with the
+//! `tests` feature: `cargo test -p brine_asset --features tests`.
+
+#![cfg(feature = "tests")]
+
+use std::path::PathBuf;
+
+use brine_asset::MinecraftAssets;
+use brine_data::MinecraftData;
+use minecraft_assets::api::{
+    AssetPack, ModelResolver, ResourceIdentifier, ResourceKind, ResourcePath,
+};
+
+const VERSIONS: [&str; 3] = ["1.14.4", "1.18.2", "1.21.4"];
+
+fn assets_root(version: &str) -> PathBuf {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../assets")
+        .join(version);
+    assert!(
+        root.join("assets").is_dir(),
+        "run `cargo xtask fetch-assets --version {version}` first"
+    );
+    root
+}
+
+#[test]
+fn every_block_state_bakes() {
+    for version in VERSIONS {
+        let data = MinecraftData::for_version(version);
+        let assets = MinecraftAssets::new(assets_root(version), &data).unwrap();
+        assets.block_states().bake_all();
+
+        assert_eq!(
+            assets.block_states().len(),
+            data.blocks().state_id_to_block.len(),
+            "{version}"
+        );
+
+        let stone = data.blocks().default_state_id_by_name("stone").unwrap();
+        let baked = assets.block_states().get_by_key(stone).unwrap();
+        assert!(baked.is_full_cube, "{version}");
+        let model = assets
+            .models()
+            .get_by_key(baked.get_first_model().unwrap())
+            .unwrap();
+        assert_eq!(model.quads.len(), 6, "{version}");
+        for quad in &model.quads {
+            let texture = assets.textures().get_by_key(quad.texture).unwrap();
+            assert_eq!(texture.path(), "block/stone", "{version}: {:?}", quad.face);
+        }
+    }
+}
+
+#[test]
+fn every_block_model_texture_exists() {
+    for version in VERSIONS {
+        let root = assets_root(version);
+        let assets = AssetPack::at_path(&root);
+
+        let models = assets
+            .enumerate_resources("minecraft", ResourceKind::BlockModel)
+            .unwrap();
+        assert!(!models.is_empty(), "{version}");
+
+        let mut missing = Vec::new();
+        for model in &models {
+            let parent_chain = assets.load_block_model_recursive(model.as_str()).unwrap();
+            let textures = ModelResolver::resolve_textures(parent_chain.iter());
+
+            // Variables still unresolved belong to templates (e.g.
+            // `block/cube_all`) that other models fill in.
+            for location in textures.variables.values().filter_map(|t| t.location()) {
+                let texture = ResourceIdentifier::texture(location);
+                if !ResourcePath::for_resource(&root, &texture).exists() {
+                    missing.push(format!("{} uses {}", model.as_str(), location));
+                }
+            }
+        }
+
+        assert!(missing.is_empty(), "{version}: {missing:#?}");
+    }
+}
@@ -1,12 +1,12 @@
 use std::{io, num::TryFromIntError};
 
 use byteorder::{BigEndian, ReadBytesExt};
-use tracing::trace;
+use tracing::{debug_span, info_span, trace};
 
 use crate::{
     palette::{Palette, SectionPalette},
-    Biomes, BlockState, BlockStates, Chunk, ChunkSection, BLOCKS_PER_SECTION, SECTION_Y_BASE,
-    SECTIONS_PER_CHUNK,
+    Biomes, BlockState, BlockStates, Chunk, ChunkSection, BLOCKS_PER_SECTION, SECTIONS_PER_CHUNK,
+    SECTION_Y_BASE,
 };
 
 mod packed_vec;
@@ -22,6 +22,37 @@ pub enum Error {
 
     #[error(transparent)]
     InvalidInt(#[from] TryFromIntError),
+
+    #[error("{what} length {length} exceeds the maximum of {max}")]
+    TooLong {
+        what: &'static str,
+        length: usize,
+        max: usize,
+    },
+
+    #[error("{0} bits per block doesn't fit the block state array")]
+    InvalidBitsPerBlock(u8),
+
+    #[error("block state id {0} is not in the palette")]
+    InvalidPaletteId(u32),
+
+    #[error(
+        "heightmap has {0} longs instead of {len}",
+        len = crate::heightmap::PACKED_HEIGHTMAP_LEN
+    )]
+    InvalidHeightmapLength(usize),
+}
+
+/// Returns an error if a length read from the data is larger than `max`.
+///
+/// Lengths come from the server, so they are checked before allocating
+/// anything based on them.
+pub(crate) fn check_length(what: &'static str, length: usize, max: usize) -> Result<usize> {
+    if length > max {
+        Err(Error::TooLong { what, length, max })
+    } else {
+        Ok(length)
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -60,6 +91,7 @@ impl Chunk {
         global_palette: &impl Palette,
         data: &mut impl io::Read,
     ) -> Result<Self> {
+        let _span = info_span!("chunk_decode", chunk_x, chunk_z).entered();
         trace!("Chunk::decode");
 
         // Blob will always contain chunk sections.
@@ -76,6 +108,7 @@ impl Chunk {
             chunk_z,
             sections,
             biomes,
+            heightmap: None,
         })
     }
 
@@ -128,6 +161,7 @@ impl ChunkSection {
         global_palette: &impl Palette,
         data: &mut impl io::Read,
     ) -> Result<Self> {
+        let _span = debug_span!("chunk_section_decode", chunk_y).entered();
         trace!("ChunkSection::decode");
         let block_count = data.read_i16::<BigEndian>()?.try_into()?;
 
@@ -171,19 +205,30 @@ impl BlockStates {
         let array_length = data.read_var_i32()?;
         trace!("array_length: {}", array_length);
 
-        let mut longs = Vec::<u64>::with_capacity(array_length.try_into()?);
+        // Even at 64 bits per block, a section takes one word per block.
+        let array_length = check_length(
+            "block state array",
+            array_length.try_into()?,
+            BLOCKS_PER_SECTION,
+        )?;
+
+        let mut longs = Vec::<u64>::with_capacity(array_length);
         for _ in 0..array_length {
             longs.push(data.read_u64::<BigEndian>()?);
         }
 
         let packed_vec_length = BLOCKS_PER_SECTION;
-        let packed_vec =
-            PackedIntVec::from_parts(longs, packed_vec_length, bits_per_block).unwrap();
+        let packed_vec = PackedIntVec::from_parts(longs, packed_vec_length, bits_per_block)
+            .ok_or(Error::InvalidBitsPerBlock(bits_per_block))?;
 
-        let block_states: Vec<BlockState> = packed_vec
+        let block_states = packed_vec
             .iter()
-            .map(|block_state_id| palette.id_to_block_state(block_state_id).unwrap())
-            .collect();
+            .map(|block_state_id| {
+                palette
+                    .id_to_block_state(block_state_id)
+                    .ok_or(Error::InvalidPaletteId(block_state_id))
+            })
+            .collect::<Result<Vec<BlockState>>>()?;
 
         Ok(Self(block_states.try_into().unwrap()))
     }
@@ -195,3 +240,47 @@ impl Biomes {
         Ok(Default::default())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct SmallPalette;
+
+    impl Palette for SmallPalette {
+        fn id_to_block_state(&self, id: u32) -> Option<BlockState> {
+            (id < 16).then_some(BlockState(id))
+        }
+    }
+
+    /// Returns the start of a chunk section with 4 bits per block.
+    fn section_header() -> Vec<u8> {
+        vec![0x10, 0x00, 4]
+    }
+
+    #[test]
+    fn huge_palette_length_is_rejected() {
+        let mut data = section_header();
+        // Palette length i32::MAX as a VarInt.
+        data.extend([0xFF, 0xFF, 0xFF, 0xFF, 0x07]);
+
+        let result = ChunkSection::decode(0, &SmallPalette, &mut data.as_slice());
+        assert!(matches!(
+            result,
+            Err(Error::TooLong {
+                what: "section palette",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_block_state_is_rejected() {
+        let mut data = section_header();
+        // A palette with a single entry the global palette doesn't have.
+        data.extend([1, 100]);
+
+        let result = ChunkSection::decode(0, &SmallPalette, &mut data.as_slice());
+        assert!(matches!(result, Err(Error::InvalidPaletteId(100))));
+    }
+}
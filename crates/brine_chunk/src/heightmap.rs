@@ -0,0 +1,137 @@
+//! Decoding the heightmaps sent along with chunks.
+//!
+//! A heightmap holds, for each column of a chunk, how far its highest block
+//! of some kind is above the bottom of the world. Servers send several; the
+//! client only keeps `MOTION_BLOCKING` (blocks that stop movement, and
+//! fluids), which is what things stand on.
+//!
+//! See <https://minecraft.wiki/w/Java_Edition_protocol/Chunk_format#Heightmaps_structure>.
+
+use std::fmt;
+
+use crate::{
+    decode::{Error, Result},
+    BlockState, ChunkSection, CHUNK_HEIGHT, CHUNK_MIN_Y, CHUNK_WIDTH, SECTION_HEIGHT,
+    SECTION_WIDTH, SECTION_Y_BASE,
+};
+
+/// Number of columns in a chunk.
+const COLUMNS: usize = CHUNK_WIDTH * CHUNK_WIDTH;
+
+/// Bits per entry: enough for every height from 0 to [`CHUNK_HEIGHT`].
+const BITS_PER_ENTRY: usize = (usize::BITS - CHUNK_HEIGHT.leading_zeros()) as usize;
+
+/// Entries per long. Entries don't span longs; the leftover bits are unused.
+const ENTRIES_PER_LONG: usize = 64 / BITS_PER_ENTRY;
+
+/// Number of longs in a packed heightmap.
+pub const PACKED_HEIGHTMAP_LEN: usize = COLUMNS.div_ceil(ENTRIES_PER_LONG);
+
+/// Height of every column of a chunk: the number of blocks from the bottom of
+/// the world to just above its highest block, or 0 if it has none. Columns
+/// are in Z-X-major order.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Heightmap(pub [u16; COLUMNS]);
+
+impl Heightmap {
+    /// Decodes a heightmap packed into longs as servers send it.
+    pub fn decode(packed: &[i64]) -> Result<Self> {
+        if packed.len() != PACKED_HEIGHTMAP_LEN {
+            return Err(Error::InvalidHeightmapLength(packed.len()));
+        }
+
+        let mask = (1 << BITS_PER_ENTRY) - 1;
+        let mut heights = [0; COLUMNS];
+        for (index, height) in heights.iter_mut().enumerate() {
+            let long = packed[index / ENTRIES_PER_LONG] as u64;
+            let shift = (index % ENTRIES_PER_LONG) * BITS_PER_ENTRY;
+            *height = ((long >> shift) & mask) as u16;
+        }
+        Ok(Self(heights))
+    }
+
+    /// Computes the heightmap of a chunk's `sections`, counting every block
+    /// but air. This is for chunks that come without one.
+    pub fn from_sections(sections: &[ChunkSection]) -> Self {
+        let mut heights = [0; COLUMNS];
+        for section in sections {
+            let min_height = (section.chunk_y - SECTION_Y_BASE) as u16 * SECTION_HEIGHT as u16;
+            for (x, y, z, block_state) in section.block_states.iter() {
+                if block_state == BlockState::AIR {
+                    continue;
+                }
+                let height = &mut heights[z as usize * SECTION_WIDTH + x as usize];
+                *height = (*height).max(min_height + u16::from(y) + 1);
+            }
+        }
+        Self(heights)
+    }
+
+    /// Returns the height of the column at the given chunk-relative X and Z.
+    #[inline]
+    pub fn get(&self, x: u8, z: u8) -> u16 {
+        self.0[z as usize * CHUNK_WIDTH + x as usize]
+    }
+
+    /// Returns the world Y just above the highest block of the column at the
+    /// given chunk-relative X and Z, where something standing on it has its
+    /// feet, or `None` if the column has no blocks.
+    pub fn surface_y(&self, x: u8, z: u8) -> Option<i32> {
+        let height = self.get(x, z);
+        (height > 0).then(|| CHUNK_MIN_Y as i32 + i32::from(height))
+    }
+}
+
+impl Default for Heightmap {
+    fn default() -> Self {
+        Self([0; COLUMNS])
+    }
+}
+
+impl fmt::Debug for Heightmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Heightmap").field(&"...").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BlockStates;
+
+    use super::*;
+
+    #[test]
+    fn packed_heights_do_not_span_longs() {
+        assert_eq!(BITS_PER_ENTRY, 9);
+        assert_eq!(PACKED_HEIGHTMAP_LEN, 37);
+
+        let mut packed = [0; PACKED_HEIGHTMAP_LEN];
+        // Column 6 ends the first long, and column 7 starts the second.
+        packed[0] = 130 << 54;
+        packed[1] = 384;
+        let heightmap = Heightmap::decode(&packed).unwrap();
+
+        assert_eq!(heightmap.get(6, 0), 130);
+        assert_eq!(heightmap.get(7, 0), 384);
+        assert_eq!(heightmap.surface_y(6, 0), Some(66));
+        assert_eq!(heightmap.surface_y(0, 0), None);
+
+        assert!(Heightmap::decode(&packed[1..]).is_err());
+    }
+
+    #[test]
+    fn sections_give_the_highest_block() {
+        let mut block_states = BlockStates::default();
+        block_states.0[BlockStates::xyz_to_index(1, 2, 3)] = BlockState(1);
+        block_states.0[BlockStates::xyz_to_index(1, 0, 3)] = BlockState(1);
+        let section = ChunkSection {
+            chunk_y: 4,
+            block_count: 2,
+            block_states,
+        };
+        let heightmap = Heightmap::from_sections(&[section]);
+
+        assert_eq!(heightmap.surface_y(1, 3), Some(67));
+        assert_eq!(heightmap.surface_y(3, 1), None);
+    }
+}
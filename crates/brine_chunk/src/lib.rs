@@ -5,9 +5,15 @@
 use std::fmt;
 
 pub mod decode;
+pub mod heightmap;
+pub mod light;
 pub mod palette;
+pub mod pos;
 
-pub use palette::{Palette, SectionPalette};
+pub use heightmap::Heightmap;
+pub use light::{ChunkLight, LightArray};
+pub use palette::{GlobalPalette, Palette, SectionPalette};
+pub use pos::{BlockPos, ChunkPos, ChunkRelative, SectionPos};
 
 pub const CHUNK_HEIGHT: usize = 384;
 pub const CHUNK_WIDTH: usize = 16;
@@ -25,6 +31,7 @@ pub const BLOCKS_PER_SECTION: usize = SECTION_HEIGHT * SECTION_WIDTH * SECTION_W
 /// is first loaded into the game), or it can represent a delta, in which case
 /// some information may be missing as noted in the fields' documentation.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chunk {
     /// Chunk coordinate (block coordinate divided by 16, rounded down).
     pub chunk_x: i32,
@@ -42,6 +49,12 @@ pub struct Chunk {
     ///
     /// If this is not the full data of a chunk, this is not included.
     pub biomes: Option<Box<Biomes>>,
+
+    /// Height of the highest motion-blocking block of each column (see
+    /// [`heightmap`]).
+    ///
+    /// Only set if the server sent it, which it doesn't for deltas.
+    pub heightmap: Option<Box<Heightmap>>,
     // TODO: block entities
 }
 
@@ -52,6 +65,7 @@ impl Chunk {
             chunk_z,
             sections: Vec::new(),
             biomes: Some(Box::new(Biomes::default())),
+            heightmap: None,
         }
     }
 
@@ -62,6 +76,25 @@ impl Chunk {
         }
     }
 
+    /// Returns the position of the chunk.
+    #[inline]
+    pub fn position(&self) -> ChunkPos {
+        ChunkPos::new(self.chunk_x, self.chunk_z)
+    }
+
+    /// Returns the block at `relative` in the chunk. Blocks in sections the
+    /// chunk doesn't have are air.
+    pub fn get_block(&self, relative: ChunkRelative) -> BlockState {
+        let section_y = relative.section_y();
+        self.sections
+            .iter()
+            .find(|section| i32::from(section.chunk_y) == section_y)
+            .map_or(BlockState::AIR, |section| {
+                let SectionKey { x, y, z } = relative.section_key();
+                section.block_states.get_block(x, y, z)
+            })
+    }
+
     /// Returns whether or not this contains the full data for a chunk or if
     /// it's just a delta.
     pub fn is_full(&self) -> bool {
@@ -71,6 +104,7 @@ impl Chunk {
 
 /// A [`ChunkSection`] is a 16x16x16 cubic section of a [`Chunk`].
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkSection {
     /// Chunk coordinate (block coordinate divided by 16, rounded down).
     pub chunk_y: i16,
@@ -105,6 +139,7 @@ impl ChunkSection {
 }
 
 /// A [`SectionKey`] is used to index a single block in a [`ChunkSection`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SectionKey {
     pub x: u8,
     pub y: u8,
@@ -235,6 +270,8 @@ impl<'a> Iterator for BlockIter<'a> {
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct BlockState(pub u32);
 
 impl BlockState {
@@ -249,6 +286,15 @@ impl BlockState {
 #[derive(Clone, PartialEq, Eq)]
 pub struct Biomes([BiomeId; SECTION_WIDTH * SECTION_WIDTH]);
 
+impl Biomes {
+    /// Returns the biome of the vertical slice at the given section-relative
+    /// X and Z coordinates.
+    #[inline]
+    pub fn get(&self, x: u8, z: u8) -> BiomeId {
+        self.0[(z as usize) * SECTION_WIDTH + (x as usize)]
+    }
+}
+
 impl Default for Biomes {
     fn default() -> Self {
         Self([BiomeId::VOID; SECTION_WIDTH * SECTION_WIDTH])
@@ -265,8 +311,71 @@ impl fmt::Debug for Biomes {
 ///
 /// See <https://minecraft.fandom.com/wiki/Biome/ID?oldid=1278248>
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct BiomeId(pub u16);
 
 impl BiomeId {
     pub const VOID: Self = Self(127);
 }
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    //! The block, biome and height grids are too large for serde's array impls, so
+    //! they are (de)serialized as sequences of the expected length.
+
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    impl Serialize for BlockStates {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.as_slice().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BlockStates {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let block_states = Vec::<BlockState>::deserialize(deserializer)?;
+            let len = block_states.len();
+            block_states
+                .try_into()
+                .map(Self)
+                .map_err(|_| D::Error::invalid_length(len, &"4096 block states"))
+        }
+    }
+
+    impl Serialize for Biomes {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.as_slice().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Biomes {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let biomes = Vec::<BiomeId>::deserialize(deserializer)?;
+            let len = biomes.len();
+            biomes
+                .try_into()
+                .map(Self)
+                .map_err(|_| D::Error::invalid_length(len, &"256 biomes"))
+        }
+    }
+
+    impl Serialize for Heightmap {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.as_slice().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Heightmap {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let heights = Vec::<u16>::deserialize(deserializer)?;
+            let len = heights.len();
+            heights
+                .try_into()
+                .map(Self)
+                .map_err(|_| D::Error::invalid_length(len, &"256 heights"))
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Decoding the light data sent along with chunks and in light updates.
+//!
+//! Light is stored for two more sections than the chunk has blocks in: one
+//! below the bottom of the world and one above the top. Light section `i` is
+//! chunk section `SECTION_Y_BASE - 1 + i`.
+//!
+//! See <https://minecraft.wiki/w/Java_Edition_protocol/Chunk_format#Light_data>.
+
+use std::{fmt, io};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::{
+    decode::{check_length, Result, VarIntRead},
+    BLOCKS_PER_SECTION, SECTIONS_PER_CHUNK, SECTION_WIDTH, SECTION_Y_BASE,
+};
+
+/// Number of sections light is stored for.
+pub const LIGHT_SECTIONS_PER_CHUNK: usize = SECTIONS_PER_CHUNK + 2;
+
+/// Length in bytes of a section's light array (a nibble per block).
+pub const LIGHT_ARRAY_LEN: usize = BLOCKS_PER_SECTION / 2;
+
+/// Light levels of every block in a section, 4 bits each, in the same
+/// Y-Z-X-major order as [`BlockStates`][crate::BlockStates].
+#[derive(Clone, PartialEq, Eq)]
+pub struct LightArray(pub Box<[u8; LIGHT_ARRAY_LEN]>);
+
+impl LightArray {
+    /// Returns the light level (0 to 15) of a block.
+    #[inline]
+    pub fn get(&self, x: u8, y: u8, z: u8) -> u8 {
+        let index = (y as usize) * SECTION_WIDTH * SECTION_WIDTH
+            + (z as usize) * SECTION_WIDTH
+            + (x as usize);
+        (self.0[index / 2] >> ((index % 2) * 4)) & 0xF
+    }
+}
+
+impl fmt::Debug for LightArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LightArray").field(&"...").finish()
+    }
+}
+
+/// The light data of a chunk, as sent by the server.
+///
+/// A set bit `i` in a mask stands for light section `i`. Sections set in
+/// neither the light mask nor the empty mask keep whatever light they had.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkLight {
+    /// Sections whose sky light is included.
+    pub sky_light_mask: u64,
+    /// Sections whose block light is included.
+    pub block_light_mask: u64,
+    /// Sections whose sky light is all zero.
+    pub empty_sky_light_mask: u64,
+    /// Sections whose block light is all zero.
+    pub empty_block_light_mask: u64,
+    /// Sky light of the sections in `sky_light_mask`, in increasing Y order.
+    pub sky_light: Vec<LightArray>,
+    /// Block light of the sections in `block_light_mask`, in increasing Y
+    /// order.
+    pub block_light: Vec<LightArray>,
+}
+
+impl ChunkLight {
+    /// Decodes light data: the four masks followed by the sky and block light
+    /// arrays.
+    pub fn decode(data: &mut impl io::Read) -> Result<Self> {
+        let sky_light_mask = decode_mask(data)?;
+        let block_light_mask = decode_mask(data)?;
+        let empty_sky_light_mask = decode_mask(data)?;
+        let empty_block_light_mask = decode_mask(data)?;
+        let sky_light = decode_arrays(data)?;
+        let block_light = decode_arrays(data)?;
+
+        Ok(Self {
+            sky_light_mask,
+            block_light_mask,
+            empty_sky_light_mask,
+            empty_block_light_mask,
+            sky_light,
+            block_light,
+        })
+    }
+
+    /// Returns the chunk section Y and sky light of every section included.
+    pub fn sky_light_sections(&self) -> impl Iterator<Item = (i16, &LightArray)> {
+        sections(self.sky_light_mask).zip(&self.sky_light)
+    }
+
+    /// Returns the chunk section Y and block light of every section included.
+    pub fn block_light_sections(&self) -> impl Iterator<Item = (i16, &LightArray)> {
+        sections(self.block_light_mask).zip(&self.block_light)
+    }
+}
+
+/// Returns the chunk section Y of every light section set in `mask`.
+fn sections(mask: u64) -> impl Iterator<Item = i16> {
+    (0..LIGHT_SECTIONS_PER_CHUNK)
+        .filter(move |&i| mask & (1 << i) != 0)
+        .map(|i| SECTION_Y_BASE - 1 + i as i16)
+}
+
+/// Decodes a bit set, keeping the bits that stand for light sections.
+fn decode_mask(data: &mut impl io::Read) -> Result<u64> {
+    let longs = check_length("light mask", data.read_var_i32()?.try_into()?, 1)?;
+
+    let mut mask = 0;
+    for _ in 0..longs {
+        mask = data.read_u64::<BigEndian>()?;
+    }
+    Ok(mask & ((1 << LIGHT_SECTIONS_PER_CHUNK) - 1))
+}
+
+fn decode_arrays(data: &mut impl io::Read) -> Result<Vec<LightArray>> {
+    let count = check_length(
+        "light array count",
+        data.read_var_i32()?.try_into()?,
+        LIGHT_SECTIONS_PER_CHUNK,
+    )?;
+
+    (0..count)
+        .map(|_| {
+            let length: usize = data.read_var_i32()?.try_into()?;
+            if length != LIGHT_ARRAY_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("light array of {length} bytes, expected {LIGHT_ARRAY_LEN}"),
+                )
+                .into());
+            }
+
+            let mut array = Box::new([0; LIGHT_ARRAY_LEN]);
+            data.read_exact(array.as_mut_slice())?;
+            Ok(LightArray(array))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn light_data_is_decoded() {
+        let mut data = Vec::new();
+        // Sky light mask: light sections 0 and 2.
+        data.extend([1, 0, 0, 0, 0, 0, 0, 0, 0b101]);
+        // Block light mask: none.
+        data.push(0);
+        // Empty sky light mask: light section 1.
+        data.extend([1, 0, 0, 0, 0, 0, 0, 0, 0b10]);
+        // Empty block light mask: none.
+        data.push(0);
+        // Two sky light arrays.
+        data.push(2);
+        for level in [0x0F, 0xF0] {
+            data.extend([0x80, 0x10]);
+            data.extend([level; LIGHT_ARRAY_LEN]);
+        }
+        // No block light arrays.
+        data.push(0);
+
+        let light = ChunkLight::decode(&mut data.as_slice()).unwrap();
+
+        assert_eq!(light.empty_sky_light_mask, 0b10);
+        let sky_light: Vec<_> = light
+            .sky_light_sections()
+            .map(|(section_y, array)| (section_y, array.get(0, 0, 0), array.get(1, 0, 0)))
+            .collect();
+        assert_eq!(
+            sky_light,
+            [(SECTION_Y_BASE - 1, 15, 0), (SECTION_Y_BASE + 1, 0, 15)]
+        );
+        assert_eq!(light.block_light_sections().count(), 0);
+    }
+
+    #[test]
+    fn short_light_array_is_rejected() {
+        // No masks, then one sky light array of a single byte.
+        let data = [0, 0, 0, 0, 1, 1, 0];
+
+        assert!(ChunkLight::decode(&mut data.as_slice()).is_err());
+    }
+}
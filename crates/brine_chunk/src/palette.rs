@@ -36,7 +36,7 @@ use std::{fmt, io};
 use tracing::trace;
 
 use crate::{
-    decode::{Result, VarIntRead},
+    decode::{check_length, Error, Result, VarIntRead},
     BlockState,
 };
 
@@ -45,6 +45,19 @@ pub trait Palette {
     fn id_to_block_state(&self, id: u32) -> Option<BlockState>;
 }
 
+/// The global palette, as far as chunk decoding is concerned: a
+/// [`BlockState`] is the global palette id itself, so every id maps to the
+/// block state with that id. Whether the id names a real block state is left
+/// to whoever looks it up.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalPalette;
+
+impl Palette for GlobalPalette {
+    fn id_to_block_state(&self, id: u32) -> Option<BlockState> {
+        Some(BlockState(id))
+    }
+}
+
 /// The palette of block states for a given [`ChunkSection`][crate::ChunkSection].
 ///
 /// See <https://wiki.vg/index.php?title=Chunk_Format&oldid=14901#Palettes>.
@@ -67,10 +80,19 @@ impl SectionPalette {
         let palette_length: usize = data.read_var_i32()?.try_into()?;
         trace!("palette_length: {}", palette_length);
 
+        // A section palette can't have more entries than its ids can index.
+        let palette_length = check_length(
+            "section palette",
+            palette_length,
+            1 << Self::MAX_BITS_PER_BLOCK,
+        )?;
+
         let mut id_to_block_state = Vec::with_capacity(palette_length);
         for _ in 0..palette_length {
             let expanded_id: u32 = data.read_var_i32()?.try_into()?;
-            let block_state = global_palette.id_to_block_state(expanded_id).unwrap();
+            let block_state = global_palette
+                .id_to_block_state(expanded_id)
+                .ok_or(Error::InvalidPaletteId(expanded_id))?;
             id_to_block_state.push(block_state);
         }
 
@@ -0,0 +1,376 @@
+//! Positions in the world: of blocks, and of the chunks and sections they are
+//! in.
+//!
+//! Chunks are [`CHUNK_WIDTH`] blocks wide and sections [`SECTION_HEIGHT`]
+//! blocks tall, so a block position splits into the chunk or section it is in
+//! and where it is within that. Splitting rounds towards negative infinity:
+//! block -1 is the last block of chunk -1, not a block of chunk 0.
+//!
+//! Each kind of position has its own type so they can't be mixed up, with
+//! conversions to and from [`glam`] vectors for math that doesn't care what
+//! the coordinates are of. The difference of two positions is a vector.
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+use glam::{IVec2, IVec3, Vec3};
+
+use crate::{SectionKey, CHUNK_WIDTH, SECTION_HEIGHT};
+
+const WIDTH: i32 = CHUNK_WIDTH as i32;
+const HEIGHT: i32 = SECTION_HEIGHT as i32;
+
+/// The position of a block in the world.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    #[inline]
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the position of the block that a point in the world is in.
+    #[inline]
+    pub fn containing(point: Vec3) -> Self {
+        point.floor().as_ivec3().into()
+    }
+
+    /// Returns the chunk the block is in.
+    #[inline]
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos::new(self.x.div_euclid(WIDTH), self.z.div_euclid(WIDTH))
+    }
+
+    /// Returns the section the block is in.
+    #[inline]
+    pub fn section(self) -> SectionPos {
+        SectionPos::new(
+            self.x.div_euclid(WIDTH),
+            self.y.div_euclid(HEIGHT),
+            self.z.div_euclid(WIDTH),
+        )
+    }
+
+    /// Returns where the block is within its chunk.
+    #[inline]
+    pub fn chunk_relative(self) -> ChunkRelative {
+        ChunkRelative::new(
+            self.x.rem_euclid(WIDTH) as u8,
+            self.y,
+            self.z.rem_euclid(WIDTH) as u8,
+        )
+    }
+
+    /// Returns where the block is within its section.
+    #[inline]
+    pub fn section_key(self) -> SectionKey {
+        self.chunk_relative().section_key()
+    }
+
+    /// Returns the position of the block's corner with the lowest coordinates.
+    #[inline]
+    pub fn as_vec3(self) -> Vec3 {
+        IVec3::from(self).as_vec3()
+    }
+}
+
+impl From<IVec3> for BlockPos {
+    #[inline]
+    fn from(position: IVec3) -> Self {
+        Self::new(position.x, position.y, position.z)
+    }
+}
+
+impl From<BlockPos> for IVec3 {
+    #[inline]
+    fn from(position: BlockPos) -> Self {
+        IVec3::new(position.x, position.y, position.z)
+    }
+}
+
+impl Add<IVec3> for BlockPos {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, offset: IVec3) -> Self {
+        (IVec3::from(self) + offset).into()
+    }
+}
+
+impl AddAssign<IVec3> for BlockPos {
+    #[inline]
+    fn add_assign(&mut self, offset: IVec3) {
+        *self = *self + offset;
+    }
+}
+
+impl Sub<IVec3> for BlockPos {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, offset: IVec3) -> Self {
+        (IVec3::from(self) - offset).into()
+    }
+}
+
+impl SubAssign<IVec3> for BlockPos {
+    #[inline]
+    fn sub_assign(&mut self, offset: IVec3) {
+        *self = *self - offset;
+    }
+}
+
+impl Sub for BlockPos {
+    type Output = IVec3;
+
+    #[inline]
+    fn sub(self, other: Self) -> IVec3 {
+        IVec3::from(self) - IVec3::from(other)
+    }
+}
+
+impl fmt::Display for BlockPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// The position of a chunk: the position of its blocks divided by
+/// [`CHUNK_WIDTH`], rounded down.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkPos {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl ChunkPos {
+    #[inline]
+    pub const fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+
+    /// Returns the chunk that a point in the world is in.
+    #[inline]
+    pub fn containing(point: Vec3) -> Self {
+        BlockPos::containing(point).chunk()
+    }
+
+    /// Returns the chunk's section at section Y `y`.
+    #[inline]
+    pub fn section(self, y: i32) -> SectionPos {
+        SectionPos::new(self.x, y, self.z)
+    }
+
+    /// Returns the position of the block at `relative` in the chunk.
+    #[inline]
+    pub fn block(self, relative: ChunkRelative) -> BlockPos {
+        BlockPos::new(
+            self.x * WIDTH + i32::from(relative.x),
+            relative.y,
+            self.z * WIDTH + i32::from(relative.z),
+        )
+    }
+
+    /// Returns the position of the chunk's block with the lowest X and Z at
+    /// Y = 0, where the chunk is placed in the world.
+    #[inline]
+    pub fn origin(self) -> BlockPos {
+        self.block(ChunkRelative::default())
+    }
+
+    /// Returns how many chunks apart two chunks are along the axis they are
+    /// furthest apart on.
+    #[inline]
+    pub fn chebyshev_distance(self, other: Self) -> u32 {
+        (other - self).abs().max_element() as u32
+    }
+}
+
+impl From<IVec2> for ChunkPos {
+    #[inline]
+    fn from(position: IVec2) -> Self {
+        Self::new(position.x, position.y)
+    }
+}
+
+impl From<ChunkPos> for IVec2 {
+    #[inline]
+    fn from(position: ChunkPos) -> Self {
+        IVec2::new(position.x, position.z)
+    }
+}
+
+impl Add<IVec2> for ChunkPos {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, offset: IVec2) -> Self {
+        (IVec2::from(self) + offset).into()
+    }
+}
+
+impl Sub for ChunkPos {
+    type Output = IVec2;
+
+    #[inline]
+    fn sub(self, other: Self) -> IVec2 {
+        IVec2::from(self) - IVec2::from(other)
+    }
+}
+
+impl fmt::Display for ChunkPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.z)
+    }
+}
+
+/// The position of a chunk section: the position of its blocks divided by
+/// [`CHUNK_WIDTH`] and [`SECTION_HEIGHT`], rounded down.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl SectionPos {
+    #[inline]
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the chunk the section is in.
+    #[inline]
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos::new(self.x, self.z)
+    }
+
+    /// Returns the position of the block at `key` in the section.
+    #[inline]
+    pub fn block(self, key: SectionKey) -> BlockPos {
+        self.origin() + IVec3::new(key.x.into(), key.y.into(), key.z.into())
+    }
+
+    /// Returns the position of the section's block with the lowest
+    /// coordinates.
+    #[inline]
+    pub fn origin(self) -> BlockPos {
+        BlockPos::new(self.x * WIDTH, self.y * HEIGHT, self.z * WIDTH)
+    }
+}
+
+impl From<IVec3> for SectionPos {
+    #[inline]
+    fn from(position: IVec3) -> Self {
+        Self::new(position.x, position.y, position.z)
+    }
+}
+
+impl From<SectionPos> for IVec3 {
+    #[inline]
+    fn from(position: SectionPos) -> Self {
+        IVec3::new(position.x, position.y, position.z)
+    }
+}
+
+impl Add<IVec3> for SectionPos {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, offset: IVec3) -> Self {
+        (IVec3::from(self) + offset).into()
+    }
+}
+
+impl Sub for SectionPos {
+    type Output = IVec3;
+
+    #[inline]
+    fn sub(self, other: Self) -> IVec3 {
+        IVec3::from(self) - IVec3::from(other)
+    }
+}
+
+impl fmt::Display for SectionPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// Where a block is within its chunk: X and Z from 0 to 15, and the block's
+/// own Y.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkRelative {
+    pub x: u8,
+    pub y: i32,
+    pub z: u8,
+}
+
+impl ChunkRelative {
+    #[inline]
+    pub const fn new(x: u8, y: i32, z: u8) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the section Y of the section the block is in.
+    #[inline]
+    pub fn section_y(self) -> i32 {
+        self.y.div_euclid(HEIGHT)
+    }
+
+    /// Returns where the block is within its section.
+    #[inline]
+    pub fn section_key(self) -> SectionKey {
+        SectionKey {
+            x: self.x,
+            y: self.y.rem_euclid(HEIGHT) as u8,
+            z: self.z,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_positions_round_down() {
+        let block = BlockPos::new(-1, -65, 16);
+        assert_eq!(block.chunk(), ChunkPos::new(-1, 1));
+        assert_eq!(block.section(), SectionPos::new(-1, -5, 1));
+        assert_eq!(block.chunk_relative(), ChunkRelative::new(15, -65, 0));
+
+        let key = block.section_key();
+        assert_eq!((key.x, key.y, key.z), (15, 15, 0));
+        assert_eq!(BlockPos::containing(Vec3::new(-0.5, -64.5, 16.5)), block);
+    }
+
+    #[test]
+    fn splitting_and_joining_positions_round_trips() {
+        for block in [
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(-17, 100, 31),
+            BlockPos::new(1_000_003, -64, -1_000_003),
+        ] {
+            assert_eq!(block.chunk().block(block.chunk_relative()), block);
+            assert_eq!(block.section().block(block.section_key()), block);
+            assert_eq!(block.section().chunk(), block.chunk());
+        }
+        assert_eq!(ChunkPos::new(2, -3).origin(), BlockPos::new(32, 0, -48));
+        assert_eq!(
+            ChunkPos::new(2, -3).chebyshev_distance(ChunkPos::new(-1, 1)),
+            4
+        );
+    }
+}
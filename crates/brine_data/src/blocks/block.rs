@@ -1,11 +1,11 @@
 use std::collections::HashMap;
 
-use minecraft_data_rs::models::block::BoundingBox;
 pub use minecraft_data_rs::models::block::Block as McBlock;
+use minecraft_data_rs::models::block::BoundingBox;
 
 use crate::Api;
 
-use super::{state::McBlockExt, BlockState};
+use super::{state::McBlockExt, BlockState, MapColor, RenderLayer, SoundGroup};
 
 pub(crate) type IndexType = u16;
 
@@ -36,7 +36,7 @@ where
 }
 
 /// A reference to a block in the [`Blocks`] data provider.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Block<'a> {
     pub id: IndexType,
     pub display_name: &'a str,
@@ -44,6 +44,17 @@ pub struct Block<'a> {
     pub transparent: bool,
     pub empty: bool,
     pub state: BlockState<'a>,
+
+    /// How hard the block is to dig, or `None` if it can't be dug (e.g.
+    /// bedrock).
+    pub hardness: Option<f32>,
+
+    /// Whether the block only drops anything, and digs at full speed, when
+    /// dug with the right tool.
+    pub needs_tool: bool,
+
+    /// Light level the block gives off, from 0 to 15.
+    pub light_emission: u8,
 }
 
 impl<'a> Block<'a> {
@@ -51,6 +62,33 @@ impl<'a> Block<'a> {
     pub fn is_air(&self) -> bool {
         self.name == "air" || self.name == "cave_air"
     }
+
+    /// Returns the sounds the block makes.
+    #[inline]
+    pub fn sound_group(&self) -> SoundGroup {
+        SoundGroup::for_block(self.name)
+    }
+
+    /// Returns the color the block has on maps.
+    #[inline]
+    pub fn map_color(&self) -> MapColor {
+        MapColor::for_block(self.name)
+    }
+
+    /// Returns how the block's texels are drawn.
+    #[inline]
+    pub fn render_layer(&self) -> RenderLayer {
+        RenderLayer::for_block(self.name, self.transparent)
+    }
+
+    /// Returns how many game ticks it takes to dig the block with bare hands
+    /// and no effects, or `None` if it can't be dug. Blocks that take `0`
+    /// break as soon as digging starts.
+    pub fn dig_ticks(&self) -> Option<u32> {
+        let hardness = self.hardness?;
+        let ticks_per_hardness = if self.needs_tool { 100.0 } else { 30.0 };
+        Some((hardness * ticks_per_hardness).ceil() as u32)
+    }
 }
 
 /// Provides access to Minecraft block data for a specific version.
@@ -100,6 +138,16 @@ impl Blocks {
         self.get_by_index_and_state_id(*index, None)
     }
 
+    /// Returns the id of the default state of the block with the given name,
+    /// or `None` if no such block exists.
+    #[inline]
+    pub fn default_state_id_by_name(&self, name: &str) -> Option<BlockStateId> {
+        let index = self.name_to_block.get(name)?;
+        let mc_block = self.blocks.get(*index as usize)?;
+
+        Some(BlockStateId(mc_block.default_state? as IndexType))
+    }
+
     /// Returns the [`Block`] associated with the given block state id, or
     /// `None` if no such block exists.
     #[inline]
@@ -169,6 +217,11 @@ impl Blocks {
             transparent: mc_block.transparent,
             empty: matches!(mc_block.bounding_box, BoundingBox::Empty),
             state,
+            hardness: mc_block
+                .hardness
+                .filter(|hardness| mc_block.diggable && *hardness >= 0.0),
+            needs_tool: mc_block.harvest_tools.is_some(),
+            light_emission: mc_block.emit_light,
         }
     }
 
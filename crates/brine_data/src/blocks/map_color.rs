@@ -0,0 +1,270 @@
+//! Block map colors.
+//!
+//! minecraft-data doesn't include the color a block has on maps, so it is
+//! derived from block names, following the map colors the vanilla block
+//! registry assigns.
+
+/// The color a block has on maps, as the ID of a vanilla base map color.
+///
+/// Maps draw each base color in four shades (see
+/// [`shaded`][Self::shaded]); which one depends on how the block's height
+/// compares with the block to its north.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapColor(pub u8);
+
+/// Dye colors, with their base map colors and those of their terracotta.
+/// Names that are the end of another come after it.
+const DYE_COLORS: [(&str, MapColor, MapColor); 16] = [
+    ("light_blue_", MapColor::LIGHT_BLUE, MapColor(38)),
+    ("light_gray_", MapColor::LIGHT_GRAY, MapColor(44)),
+    ("white_", MapColor::SNOW, MapColor(36)),
+    ("orange_", MapColor::ORANGE, MapColor(37)),
+    ("magenta_", MapColor(16), MapColor(39)),
+    ("yellow_", MapColor::YELLOW, MapColor(40)),
+    ("lime_", MapColor(19), MapColor(41)),
+    ("pink_", MapColor(20), MapColor(42)),
+    ("gray_", MapColor(21), MapColor(43)),
+    ("cyan_", MapColor(23), MapColor(45)),
+    ("purple_", MapColor::PURPLE, MapColor(46)),
+    ("blue_", MapColor(25), MapColor(47)),
+    ("brown_", MapColor::BROWN, MapColor(48)),
+    ("green_", MapColor(27), MapColor(49)),
+    ("red_", MapColor::RED, MapColor(50)),
+    ("black_", MapColor(29), MapColor(51)),
+];
+
+/// Blocks whose names start with a dye color and are colored by it.
+const DYED_BLOCKS: [&str; 9] = [
+    "wool",
+    "carpet",
+    "concrete",
+    "stained_glass",
+    "shulker_box",
+    "bed",
+    "banner",
+    "candle",
+    "glazed_terracotta",
+];
+
+/// Wood types, with the base map color of their planks.
+const WOOD_TYPES: [(&str, MapColor); 9] = [
+    ("dark_oak_", MapColor::BROWN),
+    ("pale_oak_", MapColor::QUARTZ),
+    ("oak_", MapColor::WOOD),
+    ("spruce_", MapColor::PODZOL),
+    ("birch_", MapColor::SAND),
+    ("jungle_", MapColor::DIRT),
+    ("acacia_", MapColor::ORANGE),
+    ("mangrove_", MapColor::RED),
+    ("cherry_", MapColor(36)),
+];
+
+impl MapColor {
+    /// Not drawn: air, glass, and blocks the map sees through.
+    pub const NONE: Self = Self(0);
+    pub const GRASS: Self = Self(1);
+    pub const SAND: Self = Self(2);
+    pub const WOOL: Self = Self(3);
+    pub const FIRE: Self = Self(4);
+    pub const ICE: Self = Self(5);
+    pub const METAL: Self = Self(6);
+    pub const PLANT: Self = Self(7);
+    pub const SNOW: Self = Self(8);
+    pub const CLAY: Self = Self(9);
+    pub const DIRT: Self = Self(10);
+    pub const STONE: Self = Self(11);
+    pub const WATER: Self = Self(12);
+    pub const WOOD: Self = Self(13);
+    pub const QUARTZ: Self = Self(14);
+    pub const ORANGE: Self = Self(15);
+    pub const LIGHT_BLUE: Self = Self(17);
+    pub const YELLOW: Self = Self(18);
+    pub const LIGHT_GRAY: Self = Self(22);
+    pub const PURPLE: Self = Self(24);
+    pub const BROWN: Self = Self(26);
+    pub const RED: Self = Self(28);
+    pub const GOLD: Self = Self(30);
+    pub const DIAMOND: Self = Self(31);
+    pub const LAPIS: Self = Self(32);
+    pub const EMERALD: Self = Self(33);
+    pub const PODZOL: Self = Self(34);
+    pub const NETHER: Self = Self(35);
+    pub const CRIMSON_NYLIUM: Self = Self(52);
+    pub const CRIMSON_STEM: Self = Self(53);
+    pub const WARPED_NYLIUM: Self = Self(55);
+    pub const WARPED_STEM: Self = Self(56);
+    pub const WARPED_WART_BLOCK: Self = Self(58);
+    pub const DEEPSLATE: Self = Self(59);
+    pub const RAW_IRON: Self = Self(60);
+    pub const GLOW_LICHEN: Self = Self(61);
+
+    /// Returns the map color of the block with the given name, e.g.
+    /// `grass_block`.
+    pub fn for_block(name: &str) -> Self {
+        let has = |part: &str| name.contains(part);
+
+        if let Some(color) = Self::for_dyed_block(name) {
+            return color;
+        }
+
+        if matches!(
+            name,
+            "air" | "cave_air" | "void_air" | "glass" | "glass_pane"
+        ) || has("torch")
+            || has("button")
+            || name.ends_with("rail")
+        {
+            Self::NONE
+        } else if matches!(
+            name,
+            "water" | "bubble_column" | "kelp" | "kelp_plant" | "seagrass" | "tall_seagrass"
+        ) {
+            Self::WATER
+        } else if matches!(name, "lava" | "fire" | "tnt" | "redstone_block") {
+            Self::FIRE
+        } else if name == "ice" || name.ends_with("_ice") {
+            Self::ICE
+        } else if has("snow") {
+            Self::SNOW
+        } else if matches!(name, "grass_block" | "slime_block") {
+            Self::GRASS
+        } else if has("leaves")
+            || has("sapling")
+            || has("vine")
+            || has("fern")
+            || has("grass")
+            || has("tulip")
+            || matches!(
+                name,
+                "dandelion"
+                    | "poppy"
+                    | "blue_orchid"
+                    | "allium"
+                    | "azure_bluet"
+                    | "oxeye_daisy"
+                    | "cornflower"
+                    | "lily_of_the_valley"
+                    | "sunflower"
+                    | "lilac"
+                    | "rose_bush"
+                    | "peony"
+                    | "lily_pad"
+                    | "cactus"
+                    | "sugar_cane"
+                    | "wheat"
+                    | "carrots"
+                    | "potatoes"
+                    | "beetroots"
+                    | "azalea"
+                    | "flowering_azalea"
+            )
+        {
+            Self::PLANT
+        } else if has("sand") || has("glowstone") || has("end_stone") || name == "bone_block" {
+            Self::SAND
+        } else if matches!(
+            name,
+            "dirt" | "coarse_dirt" | "rooted_dirt" | "farmland" | "dirt_path"
+        ) || has("granite")
+        {
+            Self::DIRT
+        } else if name == "clay" {
+            Self::CLAY
+        } else if name == "podzol" {
+            Self::PODZOL
+        } else if name == "mycelium" {
+            Self::PURPLE
+        } else if name == "terracotta" {
+            Self::ORANGE
+        } else if name == "crimson_nylium" {
+            Self::CRIMSON_NYLIUM
+        } else if name == "warped_nylium" {
+            Self::WARPED_NYLIUM
+        } else if name == "warped_wart_block" {
+            Self::WARPED_WART_BLOCK
+        } else if name.starts_with("crimson_") {
+            Self::CRIMSON_STEM
+        } else if name.starts_with("warped_") {
+            Self::WARPED_STEM
+        } else if name == "netherrack" || has("nether_brick") || name == "magma_block" {
+            Self::NETHER
+        } else if name == "gold_block" {
+            Self::GOLD
+        } else if name == "diamond_block" {
+            Self::DIAMOND
+        } else if name == "lapis_block" {
+            Self::LAPIS
+        } else if name == "emerald_block" {
+            Self::EMERALD
+        } else if name == "raw_iron_block" {
+            Self::RAW_IRON
+        } else if name == "glow_lichen" {
+            Self::GLOW_LICHEN
+        } else if matches!(
+            name,
+            "iron_block" | "iron_bars" | "iron_door" | "iron_trapdoor" | "anvil" | "cauldron"
+        ) {
+            Self::METAL
+        } else if has("quartz") || has("diorite") || name == "sea_lantern" {
+            Self::QUARTZ
+        } else if has("deepslate") {
+            Self::DEEPSLATE
+        } else if let Some(&(_, color)) = WOOD_TYPES.iter().find(|(wood, _)| name.starts_with(wood))
+        {
+            color
+        } else if matches!(
+            name,
+            "chest" | "trapped_chest" | "crafting_table" | "bookshelf" | "barrel" | "note_block"
+        ) {
+            Self::WOOD
+        } else {
+            Self::STONE
+        }
+    }
+
+    /// Returns the color of a block named after a dye color, e.g.
+    /// `red_wool`, or of its terracotta.
+    fn for_dyed_block(name: &str) -> Option<Self> {
+        let &(prefix, color, terracotta) = DYE_COLORS
+            .iter()
+            .find(|(prefix, _, _)| name.starts_with(prefix))?;
+        let block = &name[prefix.len()..];
+
+        if block == "terracotta" {
+            Some(terracotta)
+        } else if DYED_BLOCKS.contains(&block) || block == "concrete_powder" {
+            Some(color)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the map color ID of this base color in the given shade, from 0
+    /// to 3: darker, normal, brighter, darkest.
+    #[inline]
+    pub fn shaded(self, shade: u8) -> u8 {
+        (self.0 << 2) | (shade & 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_colors_by_name() {
+        assert_eq!(MapColor::for_block("air"), MapColor::NONE);
+        assert_eq!(MapColor::for_block("grass_block"), MapColor::GRASS);
+        assert_eq!(MapColor::for_block("oak_leaves"), MapColor::PLANT);
+        assert_eq!(MapColor::for_block("water"), MapColor::WATER);
+        assert_eq!(MapColor::for_block("sandstone"), MapColor::SAND);
+        assert_eq!(MapColor::for_block("dark_oak_planks"), MapColor::BROWN);
+        assert_eq!(MapColor::for_block("oak_planks"), MapColor::WOOD);
+        assert_eq!(MapColor::for_block("light_blue_wool"), MapColor::LIGHT_BLUE);
+        assert_eq!(MapColor::for_block("blue_wool"), MapColor(25));
+        assert_eq!(MapColor::for_block("red_terracotta"), MapColor(50));
+        assert_eq!(MapColor::for_block("stone_bricks"), MapColor::STONE);
+
+        assert_eq!(MapColor::GRASS.shaded(2), 6);
+    }
+}
@@ -3,7 +3,13 @@
 //! TODO: about block ids and block states.
 
 mod block;
+mod map_color;
+mod render;
+mod sound;
 mod state;
 
 pub use block::{Block, BlockId, BlockStateId, Blocks};
+pub use map_color::MapColor;
+pub use render::RenderLayer;
+pub use sound::SoundGroup;
 pub use state::{BlockState, StateValue};
@@ -0,0 +1,76 @@
+//! Block render layers.
+//!
+//! Neither minecraft-data nor the block models say how a block's texture is
+//! blended, so the layer is derived from the block's name and whether it is
+//! transparent, following the render types vanilla assigns.
+
+/// How a block's texels are drawn over what is behind them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderLayer {
+    /// Every texel is drawn.
+    #[default]
+    Solid,
+
+    /// Texels are either drawn or not at all, e.g. leaves, glass and plants.
+    /// Transparent blocks whose textures are fully opaque (slabs, stairs...)
+    /// are here too, which looks the same.
+    Cutout,
+
+    /// Texels are blended with what is behind them, e.g. water, ice and
+    /// stained glass.
+    Translucent,
+}
+
+/// Blocks drawn [`RenderLayer::Translucent`] besides stained glass.
+const TRANSLUCENT_BLOCKS: [&str; 8] = [
+    "water",
+    "bubble_column",
+    "ice",
+    "frosted_ice",
+    "slime_block",
+    "honey_block",
+    "tinted_glass",
+    "nether_portal",
+];
+
+impl RenderLayer {
+    /// Returns the render layer of the block with the given name, e.g.
+    /// `oak_leaves`, and transparency.
+    pub fn for_block(name: &str, transparent: bool) -> Self {
+        if TRANSLUCENT_BLOCKS.contains(&name) || name.contains("stained_glass") {
+            Self::Translucent
+        } else if transparent && name != "lava" {
+            Self::Cutout
+        } else {
+            Self::Solid
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_layers_by_name() {
+        assert_eq!(RenderLayer::for_block("stone", false), RenderLayer::Solid);
+        assert_eq!(RenderLayer::for_block("lava", true), RenderLayer::Solid);
+        assert_eq!(
+            RenderLayer::for_block("oak_leaves", true),
+            RenderLayer::Cutout
+        );
+        assert_eq!(RenderLayer::for_block("glass", true), RenderLayer::Cutout);
+        assert_eq!(
+            RenderLayer::for_block("red_stained_glass_pane", true),
+            RenderLayer::Translucent
+        );
+        assert_eq!(
+            RenderLayer::for_block("water", true),
+            RenderLayer::Translucent
+        );
+        assert_eq!(
+            RenderLayer::for_block("packed_ice", false),
+            RenderLayer::Solid
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Block sound groups.
+//!
+//! minecraft-data doesn't include the sounds blocks make, so they are derived
+//! from block names, following the sound types the vanilla block registry
+//! assigns.
+
+/// The sounds a block makes when broken, walked on, placed, hit while being
+/// mined, and landed on.
+///
+/// Sounds are named by their sound event, e.g. `block.stone.break`, which
+/// `sounds.json` maps to the sound files to play.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundGroup {
+    pub volume: f32,
+    pub pitch: f32,
+    pub break_sound: &'static str,
+    pub step_sound: &'static str,
+    pub place_sound: &'static str,
+    pub hit_sound: &'static str,
+    pub fall_sound: &'static str,
+}
+
+macro_rules! sound_group {
+    ($name:literal) => {
+        sound_group!($name, 1.0, 1.0)
+    };
+    ($name:literal, $volume:expr, $pitch:expr) => {
+        SoundGroup {
+            volume: $volume,
+            pitch: $pitch,
+            break_sound: concat!("block.", $name, ".break"),
+            step_sound: concat!("block.", $name, ".step"),
+            place_sound: concat!("block.", $name, ".place"),
+            hit_sound: concat!("block.", $name, ".hit"),
+            fall_sound: concat!("block.", $name, ".fall"),
+        }
+    };
+}
+
+/// Wood types whose blocks sound like [`SoundGroup::WOOD`].
+const WOOD_TYPES: [&str; 8] = [
+    "oak_",
+    "spruce_",
+    "birch_",
+    "jungle_",
+    "acacia_",
+    "dark_oak_",
+    "mangrove_",
+    "pale_oak_",
+];
+
+/// Blocks that sound like [`SoundGroup::WOOD`] regardless of their name.
+const WOODEN_BLOCKS: [&str; 10] = [
+    "chest",
+    "trapped_chest",
+    "crafting_table",
+    "bookshelf",
+    "chiseled_bookshelf",
+    "barrel",
+    "note_block",
+    "jukebox",
+    "composter",
+    "loom",
+];
+
+/// Blocks that sound like [`SoundGroup::METAL`].
+const METAL_BLOCKS: [&str; 12] = [
+    "iron_block",
+    "gold_block",
+    "diamond_block",
+    "emerald_block",
+    "netherite_block",
+    "iron_bars",
+    "iron_door",
+    "iron_trapdoor",
+    "hopper",
+    "cauldron",
+    "rail",
+    "bell",
+];
+
+impl SoundGroup {
+    pub const STONE: Self = sound_group!("stone");
+    pub const WOOD: Self = sound_group!("wood");
+    pub const GRAVEL: Self = sound_group!("gravel");
+    pub const GRASS: Self = sound_group!("grass");
+    pub const SAND: Self = sound_group!("sand");
+    pub const WOOL: Self = sound_group!("wool");
+    pub const METAL: Self = sound_group!("metal", 1.0, 1.5);
+    pub const SNOW: Self = sound_group!("snow");
+    pub const LADDER: Self = sound_group!("ladder");
+    pub const ANVIL: Self = sound_group!("anvil", 0.3, 1.0);
+    pub const SLIME_BLOCK: Self = sound_group!("slime_block");
+    pub const HONEY_BLOCK: Self = sound_group!("honey_block");
+    pub const NETHERRACK: Self = sound_group!("netherrack");
+    pub const NETHER_BRICKS: Self = sound_group!("nether_bricks");
+    pub const DEEPSLATE: Self = sound_group!("deepslate");
+    pub const COPPER: Self = sound_group!("copper");
+    pub const AMETHYST: Self = sound_group!("amethyst_block");
+    pub const NETHER_WOOD: Self = sound_group!("nether_wood");
+    pub const CHERRY_WOOD: Self = sound_group!("cherry_wood");
+    pub const BAMBOO_WOOD: Self = sound_group!("bamboo_wood");
+    pub const MOSS: Self = sound_group!("moss");
+    pub const MUD: Self = sound_group!("mud");
+
+    /// Glass has its own break and place sounds but otherwise sounds like
+    /// stone.
+    pub const GLASS: Self = Self {
+        break_sound: "block.glass.break",
+        place_sound: "block.glass.place",
+        ..Self::STONE
+    };
+
+    /// Returns the sound group of the block with the given name, e.g.
+    /// `oak_planks`.
+    pub fn for_block(name: &str) -> Self {
+        let has = |part: &str| name.contains(part);
+
+        if has("glass") || name == "ice" || name.ends_with("_ice") {
+            Self::GLASS
+        } else if has("wool") || (has("carpet") && !has("moss")) {
+            Self::WOOL
+        } else if has("concrete_powder") || (has("sand") && !has("sandstone")) {
+            Self::SAND
+        } else if has("moss") {
+            Self::MOSS
+        } else if has("mud") {
+            Self::MUD
+        } else if matches!(
+            name,
+            "dirt" | "coarse_dirt" | "rooted_dirt" | "farmland" | "clay" | "gravel"
+        ) || has("dirt_path")
+        {
+            Self::GRAVEL
+        } else if matches!(name, "grass_block" | "mycelium" | "podzol" | "hay_block")
+            || has("leaves")
+            || has("grass")
+            || has("fern")
+            || has("sapling")
+            || has("vine")
+        {
+            Self::GRASS
+        } else if has("snow") {
+            Self::SNOW
+        } else if name == "ladder" {
+            Self::LADDER
+        } else if has("anvil") {
+            Self::ANVIL
+        } else if name == "slime_block" {
+            Self::SLIME_BLOCK
+        } else if name == "honey_block" {
+            Self::HONEY_BLOCK
+        } else if name == "netherrack" || has("nylium") {
+            Self::NETHERRACK
+        } else if has("nether_brick") {
+            Self::NETHER_BRICKS
+        } else if has("deepslate") {
+            Self::DEEPSLATE
+        } else if has("copper") {
+            Self::COPPER
+        } else if has("amethyst") {
+            Self::AMETHYST
+        } else if name.starts_with("crimson_") || name.starts_with("warped_") {
+            Self::NETHER_WOOD
+        } else if name.starts_with("cherry_") {
+            Self::CHERRY_WOOD
+        } else if name.starts_with("bamboo_") {
+            Self::BAMBOO_WOOD
+        } else if WOOD_TYPES.iter().any(|wood| name.starts_with(wood))
+            || WOODEN_BLOCKS.contains(&name)
+        {
+            Self::WOOD
+        } else if METAL_BLOCKS.contains(&name) || name.ends_with("_rail") {
+            Self::METAL
+        } else {
+            Self::STONE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sound_groups_by_name() {
+        assert_eq!(SoundGroup::for_block("stone"), SoundGroup::STONE);
+        assert_eq!(SoundGroup::for_block("sandstone"), SoundGroup::STONE);
+        assert_eq!(SoundGroup::for_block("oak_planks"), SoundGroup::WOOD);
+        assert_eq!(SoundGroup::for_block("grass_block"), SoundGroup::GRASS);
+        assert_eq!(SoundGroup::for_block("red_sand"), SoundGroup::SAND);
+        assert_eq!(SoundGroup::for_block("moss_carpet"), SoundGroup::MOSS);
+        assert_eq!(
+            SoundGroup::for_block("white_stained_glass"),
+            SoundGroup::GLASS
+        );
+        assert_eq!(
+            SoundGroup::for_block("warped_planks"),
+            SoundGroup::NETHER_WOOD
+        );
+
+        assert_eq!(SoundGroup::GLASS.step_sound, "block.stone.step");
+        assert_eq!(SoundGroup::GLASS.break_sound, "block.glass.break");
+    }
+}
@@ -1,14 +1,16 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use bevy_ecs::prelude::Resource;
 
-use crate::{Api, Blocks, Version};
+use crate::{Api, Blocks, EntityTypes, Items, ServerRegistries, Version};
 
 /// Provides access to all Minecraft data for a specific version.
 ///
 /// This type is intended to be initialized once at program startup and accessed
-/// by reference thereafter. Construction is **not** an inexpensive operation,
-/// but access **is** an inexpensive operation.
+/// by reference thereafter. Construction is inexpensive: each data table is
+/// parsed the first time it is accessed, so tables that a program never uses
+/// are never loaded. Use [`preload`][Self::preload] to load everything up
+/// front, e.g., on a background thread.
 #[derive(Clone, Resource)]
 pub struct MinecraftData {
     inner: Arc<MinecraftDataInner>,
@@ -23,26 +25,69 @@ impl MinecraftData {
 
     /// Constructs Minecraft data for the specified [`Version`].
     pub fn for_version(version: impl Into<Version>) -> Self {
-        let version = version.into();
-        let api = Api::new(version.0.clone());
         Self {
             inner: Arc::new(MinecraftDataInner {
-                blocks: Blocks::from_api(&api),
-                version,
+                version: version.into(),
+                blocks: OnceLock::new(),
+                items: OnceLock::new(),
+                entity_types: OnceLock::new(),
+                server_registries: ServerRegistries::default(),
             }),
         }
     }
 
+    /// Returns the block table, loading it if this is the first access.
     pub fn blocks(&self) -> &Blocks {
-        &self.inner.blocks
+        self.inner
+            .blocks
+            .get_or_init(|| Blocks::from_api(&self.api()))
+    }
+
+    /// Returns the item table, loading it if this is the first access.
+    pub fn items(&self) -> &Items {
+        self.inner
+            .items
+            .get_or_init(|| Items::from_api(&self.api()))
+    }
+
+    /// Returns the entity type table, loading it if this is the first access.
+    pub fn entity_types(&self) -> &EntityTypes {
+        self.inner
+            .entity_types
+            .get_or_init(|| EntityTypes::from_api(&self.api()))
+    }
+
+    /// Returns the handle to the registries received from the server, which
+    /// take precedence over the bundled data where they overlap.
+    pub fn server_registries(&self) -> &ServerRegistries {
+        &self.inner.server_registries
     }
 
     pub fn version(&self) -> &Version {
         &self.inner.version
     }
+
+    /// Returns the protocol version number for this version of the game.
+    pub fn protocol_version(&self) -> i32 {
+        self.inner.version.version
+    }
+
+    /// Loads every data table now instead of on first access.
+    pub fn preload(&self) {
+        self.blocks();
+        self.items();
+        self.entity_types();
+    }
+
+    fn api(&self) -> Api {
+        Api::new(self.inner.version.0.clone())
+    }
 }
 
 struct MinecraftDataInner {
-    pub blocks: Blocks,
-    pub version: Version,
+    version: Version,
+    blocks: OnceLock<Blocks>,
+    items: OnceLock<Items>,
+    entity_types: OnceLock<EntityTypes>,
+    server_registries: ServerRegistries,
 }
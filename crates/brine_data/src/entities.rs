@@ -0,0 +1,117 @@
+//! Minecraft entity type data.
+
+use std::collections::HashMap;
+
+pub use minecraft_data_rs::models::entity::Entity as McEntity;
+
+use crate::Api;
+
+pub(crate) type IndexType = u16;
+
+/// Numeric entity type ID, as sent by the server when spawning an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityTypeId(pub IndexType);
+
+impl<T> From<T> for EntityTypeId
+where
+    T: Into<IndexType>,
+{
+    #[inline]
+    fn from(source: T) -> Self {
+        Self(source.into())
+    }
+}
+
+/// A reference to an entity type in the [`EntityTypes`] data provider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityType<'a> {
+    pub id: IndexType,
+    pub display_name: &'a str,
+    pub name: &'a str,
+    /// Width of the bounding box, in blocks.
+    pub width: f32,
+    /// Height of the bounding box, in blocks.
+    pub height: f32,
+}
+
+/// Provides access to Minecraft entity type data for a specific version.
+pub struct EntityTypes {
+    /// List of entity types by increasing [`EntityTypeId`].
+    entity_types: Vec<McEntity>,
+
+    /// Mapping from entity type id to entity type index.
+    id_to_entity_type: HashMap<IndexType, IndexType>,
+
+    /// Mapping from entity type name to entity type index.
+    name_to_entity_type: HashMap<String, IndexType>,
+}
+
+impl EntityTypes {
+    /// Returns the number of entity types in this version of Minecraft.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.entity_types.len()
+    }
+
+    /// Returns the [`EntityType`] with the given id, or `None` if no such
+    /// entity type exists.
+    #[inline]
+    pub fn get_by_id(&self, entity_type_id: EntityTypeId) -> Option<EntityType<'_>> {
+        let index = self.id_to_entity_type.get(&entity_type_id.0)?;
+
+        self.get_by_index(*index)
+    }
+
+    /// Returns the [`EntityType`] with the given name (e.g. `zombie`), or
+    /// `None` if no such entity type exists.
+    #[inline]
+    pub fn get_by_name(&self, name: &str) -> Option<EntityType<'_>> {
+        let index = self.name_to_entity_type.get(name)?;
+
+        self.get_by_index(*index)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = EntityType<'_>> + '_ {
+        self.entity_types
+            .iter()
+            .map(Self::entity_type_from_mc_entity)
+    }
+
+    #[inline]
+    fn get_by_index(&self, index: IndexType) -> Option<EntityType<'_>> {
+        self.entity_types
+            .get(index as usize)
+            .map(Self::entity_type_from_mc_entity)
+    }
+
+    fn entity_type_from_mc_entity(mc_entity: &McEntity) -> EntityType<'_> {
+        EntityType {
+            id: mc_entity.id as IndexType,
+            display_name: &mc_entity.display_name,
+            name: &mc_entity.name,
+            width: mc_entity.width,
+            height: mc_entity.height,
+        }
+    }
+
+    pub(crate) fn from_api(api: &Api) -> Self {
+        let entity_types = api.entities.entities_array().unwrap();
+
+        let mut id_to_entity_type = HashMap::with_capacity(entity_types.len());
+        let mut name_to_entity_type = HashMap::with_capacity(entity_types.len());
+
+        for (index, entity_type) in entity_types.iter().enumerate() {
+            let index = index as IndexType;
+
+            id_to_entity_type.insert(entity_type.id as IndexType, index);
+            name_to_entity_type.insert(entity_type.name.clone(), index);
+        }
+
+        Self {
+            entity_types,
+            id_to_entity_type,
+            name_to_entity_type,
+        }
+    }
+}
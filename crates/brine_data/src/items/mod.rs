@@ -0,0 +1,121 @@
+//! Minecraft item data.
+
+mod usage;
+
+use std::collections::HashMap;
+
+pub use minecraft_data_rs::models::item::Item as McItem;
+
+use crate::Api;
+
+pub use usage::UseAnimation;
+
+pub(crate) type IndexType = u16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ItemId(pub IndexType);
+
+impl<T> From<T> for ItemId
+where
+    T: Into<IndexType>,
+{
+    #[inline]
+    fn from(source: T) -> Self {
+        Self(source.into())
+    }
+}
+
+/// A reference to an item in the [`Items`] data provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item<'a> {
+    pub id: IndexType,
+    pub display_name: &'a str,
+    pub name: &'a str,
+    pub stack_size: u8,
+}
+
+impl Item<'_> {
+    /// Returns how the item is used while the use button is held down, or
+    /// `None` if using it has an immediate effect.
+    #[inline]
+    pub fn use_animation(&self) -> Option<UseAnimation> {
+        UseAnimation::for_item(self.name)
+    }
+}
+
+/// Provides access to Minecraft item data for a specific version.
+pub struct Items {
+    /// List of items by increasing [`ItemId`].
+    items: Vec<McItem>,
+
+    /// Mapping from item id to item index.
+    id_to_item: HashMap<IndexType, IndexType>,
+
+    /// Mapping from item name to item index.
+    name_to_item: HashMap<String, IndexType>,
+}
+
+impl Items {
+    /// Returns the number of unique items in this version of Minecraft.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns the [`Item`] with the given id, or `None` if no such item
+    /// exists.
+    #[inline]
+    pub fn get_by_id(&self, item_id: ItemId) -> Option<Item<'_>> {
+        let index = self.id_to_item.get(&item_id.0)?;
+
+        self.get_by_index(*index)
+    }
+
+    /// Returns the [`Item`] with the given name, or `None` if no such item
+    /// exists.
+    #[inline]
+    pub fn get_by_name(&self, name: &str) -> Option<Item<'_>> {
+        let index = self.name_to_item.get(name)?;
+
+        self.get_by_index(*index)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Item<'_>> + '_ {
+        self.items.iter().map(Self::item_from_mc_item)
+    }
+
+    #[inline]
+    fn get_by_index(&self, index: IndexType) -> Option<Item<'_>> {
+        self.items.get(index as usize).map(Self::item_from_mc_item)
+    }
+
+    fn item_from_mc_item(mc_item: &McItem) -> Item<'_> {
+        Item {
+            id: mc_item.id as IndexType,
+            display_name: &mc_item.display_name,
+            name: &mc_item.name,
+            stack_size: mc_item.stack_size,
+        }
+    }
+
+    pub(crate) fn from_api(api: &Api) -> Self {
+        let items = api.items.items_array().unwrap();
+
+        let mut id_to_item = HashMap::with_capacity(items.len());
+        let mut name_to_item = HashMap::with_capacity(items.len());
+
+        for (item_index, item) in items.iter().enumerate() {
+            let item_index = item_index as IndexType;
+
+            id_to_item.insert(item.id as IndexType, item_index);
+            name_to_item.insert(item.name.clone(), item_index);
+        }
+
+        Self {
+            items,
+            id_to_item,
+            name_to_item,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! What happens while an item is being used.
+//!
+//! minecraft-data doesn't say which items can be used over time (eaten, drawn,
+//! held up), so it is derived from item names, following the use animations
+//! the vanilla item registry assigns.
+
+/// How an item is used while the use button is held down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UseAnimation {
+    Eat,
+    Drink,
+    /// Held up to block attacks, like a shield.
+    Block,
+    /// Drawn, and shot when released.
+    Bow,
+    /// Loaded, and shot with the next use.
+    Crossbow,
+    /// Raised, and thrown when released, like a trident.
+    Spear,
+    /// Looked through.
+    Spyglass,
+}
+
+/// Foods other than the `cooked_*` ones, which are all eaten.
+const FOODS: [&str; 32] = [
+    "apple",
+    "golden_apple",
+    "enchanted_golden_apple",
+    "melon_slice",
+    "sweet_berries",
+    "glow_berries",
+    "chorus_fruit",
+    "carrot",
+    "golden_carrot",
+    "potato",
+    "baked_potato",
+    "poisonous_potato",
+    "beetroot",
+    "beetroot_soup",
+    "dried_kelp",
+    "beef",
+    "porkchop",
+    "mutton",
+    "chicken",
+    "rabbit",
+    "rabbit_stew",
+    "mushroom_stew",
+    "suspicious_stew",
+    "cod",
+    "salmon",
+    "tropical_fish",
+    "pufferfish",
+    "bread",
+    "cookie",
+    "pumpkin_pie",
+    "rotten_flesh",
+    "spider_eye",
+];
+
+impl UseAnimation {
+    /// Returns how the item with the given name, e.g. `bread`, is used, or
+    /// `None` if using it has an immediate effect (or none at all).
+    pub fn for_item(name: &str) -> Option<Self> {
+        match name {
+            "potion" | "milk_bucket" | "honey_bottle" | "ominous_bottle" => Some(Self::Drink),
+            "shield" => Some(Self::Block),
+            "bow" => Some(Self::Bow),
+            "crossbow" => Some(Self::Crossbow),
+            "trident" => Some(Self::Spear),
+            "spyglass" => Some(Self::Spyglass),
+            _ if name.starts_with("cooked_") || FOODS.contains(&name) => Some(Self::Eat),
+            _ => None,
+        }
+    }
+
+    /// Returns after how many ticks using the item finishes by itself, or
+    /// `None` if it lasts until the use button is released.
+    ///
+    /// Some items take more or less time than their animation's default (e.g.
+    /// dried kelp is eaten twice as fast); the server has the final say.
+    pub fn duration_ticks(self) -> Option<u32> {
+        match self {
+            Self::Eat | Self::Drink => Some(32),
+            Self::Block | Self::Bow | Self::Crossbow | Self::Spear | Self::Spyglass => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn use_animations_by_name() {
+        assert_eq!(UseAnimation::for_item("bread"), Some(UseAnimation::Eat));
+        assert_eq!(
+            UseAnimation::for_item("cooked_beef"),
+            Some(UseAnimation::Eat)
+        );
+        assert_eq!(UseAnimation::for_item("potion"), Some(UseAnimation::Drink));
+        assert_eq!(UseAnimation::for_item("shield"), Some(UseAnimation::Block));
+        assert_eq!(UseAnimation::for_item("bow"), Some(UseAnimation::Bow));
+        assert_eq!(UseAnimation::for_item("splash_potion"), None);
+        assert_eq!(UseAnimation::for_item("stone"), None);
+    }
+}
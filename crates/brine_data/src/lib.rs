@@ -3,10 +3,20 @@
 pub(crate) use minecraft_data_rs::api::Api;
 
 pub mod blocks;
+pub mod entities;
+pub mod items;
+#[cfg(feature = "registries")]
+pub mod registries;
+pub mod server_registries;
 
 mod data;
 mod version;
 
 pub use blocks::{BlockId, BlockState, BlockStateId, Blocks};
 pub use data::MinecraftData;
+pub use entities::{EntityType, EntityTypeId, EntityTypes};
+pub use items::{Item, ItemId, Items, UseAnimation};
+#[cfg(feature = "registries")]
+pub use registries::{block, entity, item};
+pub use server_registries::{DimensionType, ServerRegistries};
 pub use version::Version;
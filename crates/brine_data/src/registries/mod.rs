@@ -0,0 +1,51 @@
+//! Typed IDs for the blocks, items and entity types of one Minecraft version,
+//! so code can say `block::STONE` rather than a magic number.
+//!
+//! The IDs are generated from minecraft-data by
+//! `cargo xtask generate-registries --version <version>` into `generated.rs`,
+//! which is only compiled with the `registries` feature since it doesn't
+//! exist until the command has been run. The file isn't checked in, and
+//! nothing in the workspace enables the feature: it is opt-in, for tools and
+//! experiments pinned to one version. The IDs only hold for [`VERSION`]; look
+//! things up through [`Blocks`][crate::Blocks] and friends when the version
+//! isn't fixed, as the client does.
+
+mod generated;
+
+pub use generated::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::MinecraftData;
+
+    use super::*;
+
+    #[test]
+    fn generated_ids_match_the_data_tables() {
+        let data = MinecraftData::for_version(VERSION);
+
+        for kind in block::BlockKind::ALL {
+            let block = data.blocks().get_by_name(kind.name()).unwrap();
+            assert_eq!(block.id, kind.id().0, "{}", kind.name());
+            assert_eq!(
+                data.blocks().default_state_id_by_name(kind.name()),
+                Some(kind.default_state())
+            );
+        }
+        for kind in item::ItemKind::ALL {
+            assert_eq!(
+                data.items().get_by_name(kind.name()).unwrap().id,
+                kind.id().0
+            );
+        }
+        for kind in entity::EntityKind::ALL {
+            let entity_type = data.entity_types().get_by_name(kind.name()).unwrap();
+            assert_eq!(entity_type.id, kind.id().0);
+        }
+
+        assert_eq!(
+            block::BlockKind::from_name("stone").map(|kind| kind.default_state()),
+            Some(block::STONE)
+        );
+    }
+}
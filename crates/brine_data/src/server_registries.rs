@@ -0,0 +1,206 @@
+//! Registries received from the server at runtime.
+//!
+//! Since 1.20.2, servers send their dynamic registries (dimension types,
+//! biomes, damage types, ...) during the configuration phase, and data packs
+//! can add entries to them. An entry's network ID is its position in what the
+//! server sent, so it can't be taken from the bundled data.
+//!
+//! [`ServerRegistries`] keeps what the server sent. It is a handle to shared,
+//! locked state: every clone of it, including the one in
+//! [`MinecraftData`][crate::MinecraftData], sees what was set through any
+//! other, so the protocol backend can fill it in while the rest of the client
+//! reads it.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use bevy_ecs::prelude::Resource;
+
+/// Name of the registry of dimension types.
+pub const DIMENSION_TYPE: &str = "minecraft:dimension_type";
+
+/// Name of the registry of biomes.
+pub const BIOME: &str = "minecraft:worldgen/biome";
+
+/// The parts of a dimension type that decide the shape of its chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionType {
+    /// Lowest block Y coordinate. A multiple of 16.
+    pub min_y: i32,
+
+    /// Number of blocks from `min_y` to the top of the world. A multiple of
+    /// 16.
+    pub height: u32,
+
+    /// Whether the dimension has sky light.
+    pub has_skylight: bool,
+}
+
+impl DimensionType {
+    pub const OVERWORLD: Self = Self {
+        min_y: -64,
+        height: 384,
+        has_skylight: true,
+    };
+
+    pub const NETHER: Self = Self {
+        min_y: 0,
+        height: 256,
+        has_skylight: false,
+    };
+
+    pub const END: Self = Self {
+        min_y: 0,
+        height: 256,
+        has_skylight: false,
+    };
+
+    /// Returns the vanilla dimension type called `name`.
+    ///
+    /// Servers leave the data of vanilla entries out when the client says it
+    /// has the vanilla data pack, so these fill in for it.
+    pub fn vanilla(name: &str) -> Option<Self> {
+        match name {
+            "minecraft:overworld" | "minecraft:overworld_caves" => Some(Self::OVERWORLD),
+            "minecraft:the_nether" => Some(Self::NETHER),
+            "minecraft:the_end" => Some(Self::END),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of 16-block chunk sections in a column.
+    #[inline]
+    pub fn section_count(&self) -> usize {
+        self.height as usize / 16
+    }
+}
+
+/// Shared handle to the registries the server sent. See the
+/// [module documentation][self].
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ServerRegistries {
+    inner: Arc<RwLock<ServerRegistriesInner>>,
+}
+
+#[derive(Debug, Default)]
+struct ServerRegistriesInner {
+    /// Entry names of each registry, by network ID.
+    registries: HashMap<String, Vec<String>>,
+
+    /// Dimension types the server sent the data of, by name.
+    dimension_types: HashMap<String, DimensionType>,
+}
+
+impl ServerRegistries {
+    /// Replaces the entries of `registry` with `entries`, in network ID
+    /// order.
+    pub fn set_registry(&self, registry: impl Into<String>, entries: Vec<String>) {
+        let mut inner = self.inner.write().unwrap();
+        inner.registries.insert(registry.into(), entries);
+    }
+
+    /// Returns the entries of `registry` in network ID order, or `None` if
+    /// the server hasn't sent it.
+    pub fn entries(&self, registry: &str) -> Option<Vec<String>> {
+        self.inner.read().unwrap().registries.get(registry).cloned()
+    }
+
+    /// Returns the number of entries in `registry`, or `None` if the server
+    /// hasn't sent it.
+    pub fn entry_count(&self, registry: &str) -> Option<usize> {
+        self.inner
+            .read()
+            .unwrap()
+            .registries
+            .get(registry)
+            .map(Vec::len)
+    }
+
+    /// Returns the network ID of entry `name` of `registry`.
+    pub fn id(&self, registry: &str, name: &str) -> Option<u32> {
+        let inner = self.inner.read().unwrap();
+        let index = inner
+            .registries
+            .get(registry)?
+            .iter()
+            .position(|entry| entry == name)?;
+        Some(index as u32)
+    }
+
+    /// Returns the name of the entry of `registry` with network ID `id`.
+    pub fn name(&self, registry: &str, id: u32) -> Option<String> {
+        let inner = self.inner.read().unwrap();
+        inner.registries.get(registry)?.get(id as usize).cloned()
+    }
+
+    /// Records the data the server sent for dimension type `name`.
+    pub fn set_dimension_type(&self, name: impl Into<String>, dimension_type: DimensionType) {
+        let mut inner = self.inner.write().unwrap();
+        inner.dimension_types.insert(name.into(), dimension_type);
+    }
+
+    /// Returns dimension type `name`: as the server sent it, or else the
+    /// vanilla one of that name.
+    pub fn dimension_type(&self, name: &str) -> Option<DimensionType> {
+        let sent = self
+            .inner
+            .read()
+            .unwrap()
+            .dimension_types
+            .get(name)
+            .copied();
+        sent.or_else(|| DimensionType::vanilla(name))
+    }
+
+    /// Returns the dimension type with network ID `id`.
+    pub fn dimension_type_by_id(&self, id: u32) -> Option<DimensionType> {
+        self.dimension_type(&self.name(DIMENSION_TYPE, id)?)
+    }
+
+    /// Forgets everything the server sent, e.g. before connecting to another
+    /// server.
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.registries.clear();
+        inner.dimension_types.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_what_the_server_sent() {
+        let registries = ServerRegistries::default();
+        let handle = registries.clone();
+
+        handle.set_registry(
+            DIMENSION_TYPE,
+            vec![
+                String::from("minecraft:overworld"),
+                String::from("example:deep"),
+            ],
+        );
+        let deep = DimensionType {
+            min_y: -128,
+            height: 512,
+            has_skylight: true,
+        };
+        handle.set_dimension_type("example:deep", deep);
+
+        assert_eq!(registries.id(DIMENSION_TYPE, "example:deep"), Some(1));
+        assert_eq!(registries.dimension_type_by_id(1), Some(deep));
+        // Vanilla entries whose data was left out.
+        assert_eq!(
+            registries.dimension_type_by_id(0),
+            Some(DimensionType::OVERWORLD)
+        );
+        assert_eq!(registries.dimension_type_by_id(2), None);
+
+        registries.clear();
+        assert_eq!(handle.entry_count(DIMENSION_TYPE), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! Invariants that must hold for every Minecraft version the client can load,
+//! checked against the data minecraft-data-rs bundles. They are loose enough
+//! to survive minecraft-data fixing a block here and there, and tight enough
+//! to catch a refresh that drops, truncates or reorders a version's tables.
+
+use std::ops::RangeInclusive;
+
+use brine_data::{BlockId, BlockStateId, MinecraftData};
+
+struct Expected {
+    version: &'static str,
+    protocol: i32,
+    blocks: RangeInclusive<usize>,
+    states: RangeInclusive<usize>,
+    items: RangeInclusive<usize>,
+    entity_types: RangeInclusive<usize>,
+}
+
+const VERSIONS: [Expected; 3] = [
+    Expected {
+        version: "1.14.4",
+        protocol: 498,
+        blocks: 650..=700,
+        states: 11_000..=11_500,
+        items: 850..=900,
+        entity_types: 95..=110,
+    },
+    Expected {
+        version: "1.18.2",
+        protocol: 758,
+        blocks: 870..=920,
+        states: 20_000..=20_500,
+        items: 1_050..=1_150,
+        entity_types: 105..=120,
+    },
+    Expected {
+        version: "1.21.4",
+        protocol: 769,
+        blocks: 1_050..=1_150,
+        states: 26_000..=29_000,
+        items: 1_300..=1_450,
+        entity_types: 140..=160,
+    },
+];
+
+#[test]
+fn table_sizes_are_in_range() {
+    for expected in &VERSIONS {
+        let version = expected.version;
+        let data = MinecraftData::for_version(version);
+
+        assert_eq!(data.protocol_version(), expected.protocol, "{version}");
+        assert!(
+            expected.blocks.contains(&data.blocks().count()),
+            "{version} has {} blocks",
+            data.blocks().count()
+        );
+        assert!(
+            expected
+                .states
+                .contains(&data.blocks().state_id_to_block.len()),
+            "{version} has {} block states",
+            data.blocks().state_id_to_block.len()
+        );
+        assert!(
+            expected.items.contains(&data.items().count()),
+            "{version} has {} items",
+            data.items().count()
+        );
+        assert!(
+            expected.entity_types.contains(&data.entity_types().count()),
+            "{version} has {} entity types",
+            data.entity_types().count()
+        );
+    }
+}
+
+#[test]
+fn well_known_entries_resolve() {
+    for expected in &VERSIONS {
+        let version = expected.version;
+        let data = MinecraftData::for_version(version);
+        let blocks = data.blocks();
+
+        // Air and stone have had the first two state IDs since the flattening.
+        assert!(
+            blocks.get_by_state_id(BlockStateId(0)).unwrap().is_air(),
+            "{version}"
+        );
+        assert_eq!(
+            blocks.default_state_id_by_name("stone"),
+            Some(BlockStateId(1)),
+            "{version}"
+        );
+        let stone = blocks.get_by_state_id(BlockStateId(1)).unwrap();
+        assert_eq!(stone.name, "stone", "{version}");
+        assert_eq!(stone.display_name, "Stone", "{version}");
+        assert!(!stone.transparent, "{version}");
+        assert_eq!(blocks.get_by_id(BlockId(stone.id)).unwrap().name, "stone");
+
+        assert!(data.items().get_by_name("stone").is_some(), "{version}");
+        assert!(
+            data.items().get_by_name("diamond_pickaxe").is_some(),
+            "{version}"
+        );
+        assert!(
+            data.entity_types().get_by_name("zombie").is_some(),
+            "{version}"
+        );
+    }
+}
+
+#[test]
+fn every_state_belongs_to_exactly_one_block() {
+    for expected in &VERSIONS {
+        let version = expected.version;
+        let data = MinecraftData::for_version(version);
+        let blocks = data.blocks();
+
+        let mut next_state = 0;
+        for block in blocks.iter_blocks() {
+            let states: Vec<_> = blocks
+                .iter_states_for_block(BlockId(block.id))
+                .unwrap()
+                .collect();
+
+            // Each block's states follow on from the previous block's.
+            assert_eq!(
+                states.first().map(|(id, _)| id.0),
+                Some(next_state),
+                "{version}: {}",
+                block.name
+            );
+            for (state_id, state) in &states {
+                assert_eq!(state.name, block.name, "{version}: state {}", state_id.0);
+            }
+            next_state += states.len() as u16;
+
+            let default_state = blocks.default_state_id_by_name(block.name).unwrap();
+            assert!(
+                states.iter().any(|(id, _)| *id == default_state),
+                "{version}: default state of {} isn't one of its states",
+                block.name
+            );
+        }
+
+        assert_eq!(
+            next_state as usize,
+            blocks.state_id_to_block.len(),
+            "{version}"
+        );
+    }
+}
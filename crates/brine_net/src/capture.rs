@@ -0,0 +1,394 @@
+//! Recording the bytes received from a remote host, and replaying them.
+//!
+//! A capture holds every chunk of bytes read from the socket, tagged with the
+//! connection it was read on and when it was read. Nothing is decoded, so a
+//! capture replays through the same codec and plugins as a live session.
+//!
+//! Only the remote host's side is recorded. [`ReplayServer`] plays it back to
+//! a client without looking at what the client sends, so a replay only stays
+//! faithful while the client behaves as it did when recording (and the
+//! connection is not encrypted).
+//!
+//! # Format
+//!
+//! The file starts with [`MAGIC`] and a little-endian `u32` format version,
+//! followed by records of:
+//!
+//! * connection index (`u32`), counting up from zero in the order the
+//!   connections were made
+//! * microseconds since recording started (`u64`)
+//! * length (`u32`), followed by that many bytes
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    thread,
+    time::Duration,
+};
+
+use bevy::{log, platform::time::Instant};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use futures::AsyncRead;
+
+/// Bytes every capture file starts with.
+pub const MAGIC: &[u8; 8] = b"BRINECAP";
+
+const FORMAT_VERSION: u32 = 1;
+
+/// Records the bytes received over every connection made by a
+/// [`NetworkResource`][crate::NetworkResource] into one capture.
+///
+/// Clones write to the same capture.
+#[derive(Clone)]
+pub struct CaptureWriter {
+    inner: Arc<Mutex<CaptureWriterInner>>,
+}
+
+struct CaptureWriterInner {
+    writer: Box<dyn Write + Send>,
+    started: Instant,
+    connections: u32,
+    failed: bool,
+}
+
+impl CaptureWriter {
+    /// Creates (or truncates) a capture file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Self::new(BufWriter::new(file))
+    }
+
+    /// Starts a capture written to `writer`.
+    pub fn new(mut writer: impl Write + Send + 'static) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_u32::<LittleEndian>(FORMAT_VERSION)?;
+        writer.flush()?;
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(CaptureWriterInner {
+                writer: Box::new(writer),
+                started: Instant::now(),
+                connections: 0,
+                failed: false,
+            })),
+        })
+    }
+
+    /// Returns a recorder for the next connection.
+    pub(crate) fn begin_connection(&self) -> ConnectionRecorder {
+        let mut inner = self.inner.lock().unwrap();
+        let connection = inner.connections;
+        inner.connections += 1;
+
+        ConnectionRecorder {
+            capture: self.clone(),
+            connection,
+        }
+    }
+
+    fn record(&self, connection: u32, bytes: &[u8]) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.failed {
+            return;
+        }
+
+        let offset = inner.started.elapsed();
+        // Flushed every time so that the capture survives the app being
+        // killed.
+        let result = write_record(&mut inner.writer, connection, offset, bytes)
+            .and_then(|_| inner.writer.flush());
+
+        if let Err(err) = result {
+            log::error!("Failed to write capture, recording stopped: {}", err);
+            inner.failed = true;
+        }
+    }
+}
+
+fn write_record(
+    writer: &mut impl Write,
+    connection: u32,
+    offset: Duration,
+    bytes: &[u8],
+) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(connection)?;
+    writer.write_u64::<LittleEndian>(offset.as_micros() as u64)?;
+    writer.write_u32::<LittleEndian>(bytes.len() as u32)?;
+    writer.write_all(bytes)
+}
+
+/// Records the bytes received over one connection.
+pub(crate) struct ConnectionRecorder {
+    capture: CaptureWriter,
+    connection: u32,
+}
+
+/// Wraps the read half of a connection, recording everything read from it if
+/// a recorder is given.
+pub(crate) struct RecordingReader<S> {
+    inner: S,
+    recorder: Option<ConnectionRecorder>,
+}
+
+impl<S> RecordingReader<S> {
+    pub(crate) fn new(inner: S, recorder: Option<ConnectionRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for RecordingReader<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let (Poll::Ready(Ok(len)), Some(recorder)) = (&poll, &this.recorder) {
+            if *len > 0 {
+                recorder.capture.record(recorder.connection, &buf[..*len]);
+            }
+        }
+
+        poll
+    }
+}
+
+/// A capture read back from a file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Capture {
+    /// Reads made on each connection, in the order the connections were made.
+    pub connections: Vec<Vec<CapturedRead>>,
+}
+
+/// Bytes received in one read from the socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedRead {
+    /// Time since recording started.
+    pub offset: Duration,
+    pub bytes: Vec<u8>,
+}
+
+impl Capture {
+    /// Reads a capture file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Self::read_from(BufReader::new(file))
+    }
+
+    /// Reads a capture from `reader`.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a capture file"));
+        }
+
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported capture format version {version}"
+            )));
+        }
+
+        let mut capture = Self::default();
+
+        loop {
+            let connection = match reader.read_u32::<LittleEndian>() {
+                Ok(connection) => connection as usize,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            };
+            let offset = Duration::from_micros(reader.read_u64::<LittleEndian>()?);
+            let len = reader.read_u32::<LittleEndian>()? as usize;
+            let mut bytes = vec![0; len];
+            reader.read_exact(&mut bytes)?;
+
+            if connection >= capture.connections.len() {
+                capture.connections.resize_with(connection + 1, Vec::new);
+            }
+            capture.connections[connection].push(CapturedRead { offset, bytes });
+        }
+
+        Ok(capture)
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// A local TCP server that plays a [`Capture`] back to whoever connects.
+///
+/// Each accepted connection is served the next recorded connection. Once the
+/// client has sent something (its handshake), the recorded bytes are sent
+/// with the recorded timing divided by `speed`, and the server closes its
+/// side when they run out. Whatever the client sends is discarded.
+///
+/// The server runs on its own thread until every connection has been served.
+#[derive(Clone)]
+pub struct ReplayServer {
+    local_addr: SocketAddr,
+    finished: Arc<AtomicBool>,
+}
+
+impl ReplayServer {
+    /// Starts serving `capture` on an unused local port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` isn't positive.
+    pub fn start(capture: Capture, speed: f64) -> io::Result<Self> {
+        assert!(speed > 0.0, "replay speed must be positive");
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let local_addr = listener.local_addr()?;
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let server_finished = finished.clone();
+        thread::Builder::new()
+            .name(String::from("replay server"))
+            .spawn(move || {
+                let count = capture.connections.len();
+                for (index, reads) in capture.connections.into_iter().enumerate() {
+                    let stream = match listener.accept() {
+                        Ok((stream, _)) => stream,
+                        Err(err) => {
+                            log::error!("Replay server failed to accept a connection: {}", err);
+                            break;
+                        }
+                    };
+
+                    log::debug!("Replaying connection {} of {}", index + 1, count);
+                    let last = index + 1 == count;
+                    if let Err(err) = replay_connection(stream, &reads, speed, || {
+                        if last {
+                            server_finished.store(true, Ordering::Release);
+                        }
+                    }) {
+                        log::warn!("Replay of connection {} ended early: {}", index + 1, err);
+                    }
+                }
+
+                server_finished.store(true, Ordering::Release);
+            })?;
+
+        Ok(Self {
+            local_addr,
+            finished,
+        })
+    }
+
+    /// Address to connect to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Whether every recorded byte has been sent. The last connection is
+    /// closed right after this becomes true.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+}
+
+fn replay_connection(
+    mut stream: TcpStream,
+    reads: &[CapturedRead],
+    speed: f64,
+    on_sent: impl FnOnce(),
+) -> io::Result<()> {
+    // The client always speaks first, and its codec only knows how to decode
+    // the replies once it has sent its handshake.
+    let mut buf = [0; 4096];
+    if stream.read(&mut buf)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut drain = stream.try_clone()?;
+    let drainer =
+        thread::spawn(move || while matches!(drain.read(&mut buf), Ok(len) if len > 0) {});
+
+    let started = Instant::now();
+    let first_offset = reads.first().map_or(Duration::ZERO, |read| read.offset);
+
+    for read in reads {
+        let due = read.offset.saturating_sub(first_offset).div_f64(speed);
+        if let Some(wait) = due.checked_sub(started.elapsed()) {
+            thread::sleep(wait);
+        }
+        stream.write_all(&read.bytes)?;
+    }
+
+    on_sent();
+
+    // Closing with unread data would reset the connection and could lose what
+    // was just sent, so wait for the client to hang up first.
+    stream.shutdown(Shutdown::Write)?;
+    drainer.join().ok();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_read_back_by_connection() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.write_u32::<LittleEndian>(FORMAT_VERSION).unwrap();
+        write_record(&mut bytes, 0, Duration::from_millis(1), b"status").unwrap();
+        write_record(&mut bytes, 1, Duration::from_millis(5), b"login").unwrap();
+        write_record(&mut bytes, 1, Duration::from_millis(9), b"chunks").unwrap();
+
+        let capture = Capture::read_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(capture.connections.len(), 2);
+        assert_eq!(capture.connections[0][0].bytes, b"status");
+        assert_eq!(
+            capture.connections[1]
+                .iter()
+                .map(|read| (read.offset.as_millis(), read.bytes.as_slice()))
+                .collect::<Vec<_>>(),
+            [(5, b"login".as_slice()), (9, b"chunks".as_slice())]
+        );
+    }
+
+    #[test]
+    fn replay_server_waits_for_the_client_then_sends_everything() {
+        let read = |millis, bytes: &[u8]| CapturedRead {
+            offset: Duration::from_millis(millis),
+            bytes: bytes.to_vec(),
+        };
+        let capture = Capture {
+            connections: vec![vec![read(100, b"hello "), read(300, b"world")]],
+        };
+
+        let server = ReplayServer::start(capture, 100.0).unwrap();
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(b"handshake").unwrap();
+
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+
+        assert_eq!(received, b"hello world");
+        assert!(server.is_finished());
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_captures() {
+        let err = Capture::read_from(b"PNG\r\n\x1a\n\0\0\0\0".as_slice()).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
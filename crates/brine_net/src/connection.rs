@@ -1,12 +1,17 @@
-use std::{any::Any, fmt::Debug};
+use std::{any::Any, fmt::Debug, sync::Arc};
 
 use async_channel::{Receiver, Sender};
 use async_codec::{Decode, Encode, Framed, ReadFrameError, WriteFrameError};
-use async_net::TcpStream;
 use bevy::log;
 use futures::{FutureExt, SinkExt, StreamExt};
 
-use crate::{event::NetworkError, resource::NetworkResource, NetworkEvent};
+use crate::{
+    capture::{CaptureWriter, RecordingReader},
+    event::NetworkError,
+    resource::NetworkResource,
+    transport::{Transport, TransportReader, TransportWriter},
+    NetworkEvent,
+};
 
 /// Internal utility struct responsible for running
 pub(crate) struct Connection<Codec: Decode + Encode>
@@ -17,6 +22,8 @@ where
     network_event_sender: Sender<NetworkEvent<Codec>>,
     peerbound_packet_receiver: Receiver<<Codec as Encode>::Item>,
     selfbound_packet_sender: Sender<<Codec as Decode>::Item>,
+    capture: Option<CaptureWriter>,
+    transport: Arc<dyn Transport>,
 }
 
 impl<Codec> Connection<Codec>
@@ -32,6 +39,8 @@ where
             network_event_sender: net_resource.network_event_sender.clone(),
             peerbound_packet_receiver: net_resource.peerbound_packet_receiver.clone(),
             selfbound_packet_sender: net_resource.selfbound_packet_sender.clone(),
+            capture: net_resource.capture.clone(),
+            transport: net_resource.transport.clone(),
         }
     }
 
@@ -48,10 +57,14 @@ where
     pub(crate) async fn connect_and_run(self, peer_addr: String, codec: Codec) {
         log::debug!("Connecting to {} ...", &peer_addr);
 
-        let tcp_stream = match TcpStream::connect(peer_addr.clone()).await {
-            Ok(stream) => stream,
+        let (reader, writer) = match self.transport.connect(peer_addr.clone()).await {
+            Ok(halves) => halves,
             Err(err) => {
-                self.send_error(NetworkError::ConnectFailed(err)).await;
+                self.send_error(NetworkError::ConnectFailed {
+                    server_addr: peer_addr,
+                    source: err,
+                })
+                .await;
                 return;
             }
         };
@@ -60,8 +73,11 @@ where
 
         self.send_event(NetworkEvent::Connected).await;
 
-        let peerbound_future = self.run_peerbound(tcp_stream.clone(), codec.clone()).fuse();
-        let selfbound_future = self.run_selfbound(tcp_stream, codec).fuse();
+        let peerbound_future = self.run_peerbound(writer, codec.clone()).fuse();
+        // Connections that failed aren't part of the capture.
+        let recorder = self.capture.as_ref().map(CaptureWriter::begin_connection);
+        let reader = RecordingReader::new(reader, recorder);
+        let selfbound_future = self.run_selfbound(reader, codec).fuse();
 
         futures::pin_mut!(peerbound_future, selfbound_future);
         futures::select! {
@@ -80,10 +96,10 @@ where
 
     /// Run the half of the connection that encodes packets destined for the
     /// remote host.
-    async fn run_peerbound(&self, tcp_stream: TcpStream, codec: Codec) {
+    async fn run_peerbound(&self, writer: TransportWriter, codec: Codec) {
         log::trace!("peerbound writer task: starting");
 
-        let mut codec_writer = Framed::new(tcp_stream, codec);
+        let mut codec_writer = Framed::new(writer, codec);
 
         loop {
             let peerbound_packet = self.peerbound_packet_receiver.recv().await.unwrap();
@@ -104,10 +120,10 @@ where
 
     /// Runs the half of the connection that decodes packets destined for the
     /// local host.
-    async fn run_selfbound(&self, tcp_stream: TcpStream, codec: Codec) {
+    async fn run_selfbound(&self, reader: RecordingReader<TransportReader>, codec: Codec) {
         log::trace!("selfbound reader task: starting");
 
-        let mut codec_reader = Framed::new(tcp_stream, codec);
+        let mut codec_reader = Framed::new(reader, codec);
 
         loop {
             let selfbound_packet = codec_reader.next().await;
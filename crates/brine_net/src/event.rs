@@ -35,8 +35,18 @@ where
     #[error("there is already a connection established")]
     AlreadyConnected,
 
-    #[error("failed to connect to server: {0}")]
-    ConnectFailed(io::Error),
+    /// Connecting failed. With the [`TcpTransport`], the `source` of a host
+    /// none of whose addresses could be connected to holds a [`ConnectError`]
+    /// saying why each failed.
+    ///
+    /// [`TcpTransport`]: crate::transport::TcpTransport
+    /// [`ConnectError`]: crate::transport::ConnectError
+    #[error("failed to connect to {server_addr}: {source}")]
+    ConnectFailed {
+        server_addr: String,
+        #[source]
+        source: io::Error,
+    },
 
     #[error("an error occurred during transport: {0}")]
     TransportError(io::Error),
@@ -1,10 +1,14 @@
-//! Customizable two-way TCP networking for Bevy projects.
+//! Customizable two-way networking for Bevy projects, over TCP or any other
+//! [`Transport`][transport::Transport].
 //!
 //! This crate does not depend on any additional async runtime like Tokio; it
 //! uses the same runtime provided by [`bevy::tasks`].
 //!
 //! It is based heavily on the [`async_codec`] crate.
 //!
+//! Datagrams that aren't part of a connection, like multicast announcements,
+//! can be received with a `udp::UdpListener` (not in the browser).
+//!
 //! # Usage
 //!
 //! Using this crate starts with defining your **codec**, or how your protocol
@@ -113,10 +117,14 @@ mod plugin;
 mod resource;
 mod system_param;
 
+pub mod capture;
 pub mod codec;
+pub mod transport;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod udp;
 
 pub use async_codec::{Decode, DecodeResult, Encode, EncodeResult};
 
 pub use event::{NetworkError, NetworkEvent};
-pub use plugin::{CodecReader, CodecWriter, NetworkPlugin};
-pub use resource::NetworkResource;
+pub use plugin::{CodecReader, CodecWriter, NetworkPlugin, NetworkSystems};
+pub use resource::{NetworkResource, NetworkStats};
@@ -53,6 +53,10 @@ pub type CodecWriter<'w, Codec> = system_param::CodecWriter<'w, <Codec as Encode
 ///
 /// The plugin expects no resources to exist.
 ///
+/// # Ordering
+///
+/// The plugin's systems run in the [`NetworkSystems`] sets.
+///
 /// [`EventReader`]: bevy::ecs::event::EventReader
 pub struct NetworkPlugin<Codec> {
     _phantom: PhantomData<Codec>,
@@ -66,6 +70,21 @@ impl<Codec> Default for NetworkPlugin<Codec> {
     }
 }
 
+/// System sets in which [`NetworkPlugin`] moves packets between the app and
+/// the connection.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkSystems {
+    /// Received packets and network events are made available to
+    /// [`CodecReader`]s and event readers. Runs in `PreUpdate`, so packets
+    /// can be read anywhere in `Update`.
+    Receive,
+
+    /// Packets written with [`CodecWriter`]s are handed to the connection.
+    /// Runs in `PostUpdate`, so packets written anywhere in `Update` are sent
+    /// in the same frame.
+    Send,
+}
+
 type CodecReadEvent<Codec> = Read<<Codec as Decode>::Item, Codec>;
 type CodecWriteEvent<Codec> = Write<<Codec as Encode>::Item, Codec>;
 
@@ -86,9 +105,18 @@ where
         let net_resource = NetworkResource::<Codec>::new(task_pool);
         app.insert_resource(net_resource);
 
-        app.add_systems(PreUpdate, Self::send_network_events);
-        app.add_systems(PreUpdate, Self::send_packets_to_codec_reader);
-        app.add_systems(PostUpdate, Self::receive_packets_from_codec_writer);
+        app.add_systems(
+            PreUpdate,
+            (
+                Self::send_network_events,
+                Self::send_packets_to_codec_reader,
+            )
+                .in_set(NetworkSystems::Receive),
+        );
+        app.add_systems(
+            PostUpdate,
+            Self::receive_packets_from_codec_writer.in_set(NetworkSystems::Send),
+        );
     }
 }
 
@@ -122,10 +150,11 @@ where
     /// them through an [`EventWriter`] so they can be read by the
     /// appropriate [`CodecReader`].
     fn send_packets_to_codec_reader(
-        net_resource: Res<NetworkResource<Codec>>,
+        mut net_resource: ResMut<NetworkResource<Codec>>,
         mut event_writer: MessageWriter<CodecReadEvent<Codec>>,
     ) {
         while let Ok(packet) = net_resource.selfbound_packet_receiver.try_recv() {
+            net_resource.stats.packets_received += 1;
             event_writer.write(Read(packet, PhantomData));
         }
     }
@@ -134,9 +163,12 @@ where
     /// forwards them to the internal channel to be encoded and sent to the
     /// remote host.
     fn receive_packets_from_codec_writer(
-        net_resource: Res<NetworkResource<Codec>>,
+        mut net_resource: ResMut<NetworkResource<Codec>>,
         mut messages: ResMut<Messages<CodecWriteEvent<Codec>>>,
     ) {
+        net_resource.stats.packets_sent += messages.len() as u64;
+
+        let net_resource = &*net_resource;
         net_resource.task_pool.scope(|scope| {
             scope.spawn(async {
                 for packet in messages.drain() {
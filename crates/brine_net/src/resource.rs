@@ -1,6 +1,6 @@
 //! Resources exposed by this crate.
 
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 
 use async_channel::{unbounded, Receiver, Sender};
 use async_codec::{Decode, Encode};
@@ -10,12 +10,24 @@ use bevy::{
 };
 
 use crate::{
+    capture::CaptureWriter,
     connection::Connection,
     event::{NetworkError, NetworkEvent},
+    transport::{DefaultTransport, Transport},
 };
 
-/// Resource that provides a TCP connection that encodes and decodes
-/// packets as specified by the given codec.
+/// Counters describing the traffic over the current connection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkStats {
+    /// Number of packets received from the remote host.
+    pub packets_received: u64,
+
+    /// Number of packets sent to the remote host.
+    pub packets_sent: u64,
+}
+
+/// Resource that provides a connection (over TCP unless another [`Transport`]
+/// is set) that encodes and decodes packets as specified by the given codec.
 #[derive(Resource)]
 pub struct NetworkResource<Codec: Decode + Encode>
 where
@@ -25,6 +37,9 @@ where
     pub(crate) codec: Codec,
     pub(crate) task_pool: TaskPool,
     pub(crate) connection_task: Option<Task<()>>,
+    pub(crate) stats: NetworkStats,
+    pub(crate) capture: Option<CaptureWriter>,
+    pub(crate) transport: Arc<dyn Transport>,
 
     /// Used by background tasks to produce [`NetworkEvent`]s.
     pub(crate) network_event_sender: Sender<NetworkEvent<Codec>>,
@@ -66,6 +81,9 @@ where
             codec: Default::default(),
             task_pool,
             connection_task: None,
+            stats: NetworkStats::default(),
+            capture: None,
+            transport: Arc::new(DefaultTransport::default()),
             network_event_sender,
             network_event_receiver,
             peerbound_packet_sender,
@@ -82,16 +100,50 @@ where
         &self.codec
     }
 
+    /// Returns whether or not a connection is established or being
+    /// established.
+    pub fn is_connected(&self) -> bool {
+        self.connection_task.is_some()
+    }
+
+    /// Returns traffic counters for the current (or most recent) connection.
+    pub fn stats(&self) -> NetworkStats {
+        self.stats
+    }
+
+    /// Records the bytes received over this and every later connection into
+    /// `capture`, replacing any capture that was already being recorded.
+    ///
+    /// See [`capture`][crate::capture].
+    pub fn record_to(&mut self, capture: CaptureWriter) {
+        self.capture = Some(capture);
+    }
+
+    /// Opens this and every later connection with `transport` instead of
+    /// the [`DefaultTransport`][crate::transport::DefaultTransport].
+    /// Connections already open are unaffected.
+    pub fn set_transport(&mut self, transport: impl Transport) {
+        self.transport = Arc::new(transport);
+    }
+
     /// Establish a connection with a server that speaks this codec.
     ///
-    /// The server address argument can be a `<hostname>:<port>` pair or an
-    /// `<ip_addr>:<port>` pair (or anything that can be successfully resolved
-    /// to one or more IP addresses with
-    /// [`ToSocketAddrs`][std::net::ToSocketAddrs]).
+    /// With the default [`TcpTransport`], the server address argument can be a
+    /// `<hostname>:<port>` pair or an `<ip_addr>:<port>` pair (or anything
+    /// that can be successfully resolved to one or more IP addresses with
+    /// [`ToSocketAddrs`][std::net::ToSocketAddrs]), each of which is tried
+    /// until one connects. Other transports take other addresses, e.g. the
+    /// `ws://` URLs of [`WebSocketTransport`].
+    ///
+    /// [`TcpTransport`]: crate::transport::TcpTransport
+    /// [`WebSocketTransport`]: crate::transport::WebSocketTransport
     ///
     /// If any error occurs in the process of establishing the connection or
     /// while the connection is active, it will be delivered as a
     /// [`NetworkEvent`][crate::NetworkEvent].
+    ///
+    /// Packets written while there was no connection are discarded rather
+    /// than sent to the new server.
     pub fn connect(&mut self, server_addr: String) {
         if self.connection_task.is_some() {
             self.task_pool.scope(|scope| {
@@ -103,6 +155,9 @@ where
                 });
             });
         } else {
+            self.stats = NetworkStats::default();
+            while self.peerbound_packet_receiver.try_recv().is_ok() {}
+
             let connection = Connection::new(self);
 
             let codec = self.codec.clone();
@@ -111,4 +166,16 @@ where
             }));
         }
     }
+
+    /// Closes the current connection, if any.
+    ///
+    /// Unlike a connection closed by the remote host, this does not produce a
+    /// [`NetworkEvent::Disconnected`][crate::NetworkEvent::Disconnected].
+    /// Received packets that haven't been read yet are discarded.
+    pub fn disconnect(&mut self) {
+        // Dropping the task cancels it, which closes the socket.
+        if self.connection_task.take().is_some() {
+            while self.selfbound_packet_receiver.try_recv().is_ok() {}
+        }
+    }
 }
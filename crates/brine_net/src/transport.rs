@@ -0,0 +1,366 @@
+//! How connections reach the remote host.
+//!
+//! A [`Transport`] opens a byte stream to an address, which the codec then
+//! runs over. [`TcpTransport`] (the default) connects straight to the server;
+//! [`WebSocketTransport`] goes through a WebSocket proxy that forwards binary
+//! messages to and from the server, which also works where raw sockets aren't
+//! available.
+//!
+//! In the browser (`wasm32`) there are no raw sockets, so there is no
+//! [`TcpTransport`] and [`WebSocketTransport`] is the default, using the
+//! browser's own WebSockets.
+//!
+//! See [`NetworkResource::set_transport`][crate::NetworkResource::set_transport].
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fmt, net::SocketAddr, time::Duration};
+use std::{io, sync::Arc};
+
+#[cfg(not(target_arch = "wasm32"))]
+use async_io::Timer;
+#[cfg(not(target_arch = "wasm32"))]
+use async_net::TcpStream;
+#[cfg(not(target_arch = "wasm32"))]
+use async_tungstenite::{ByteReader, ByteWriter};
+use bevy::tasks::BoxedFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::AsyncReadExt;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::{
+    future::{self, Either},
+    stream::FuturesUnordered,
+    StreamExt, TryStreamExt,
+};
+use futures::{io::BufWriter, AsyncRead, AsyncWrite};
+
+/// The half of a connection that bytes from the remote host are read from.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// The half of a connection that bytes from the remote host are read from.
+#[cfg(target_arch = "wasm32")]
+pub type TransportReader = Box<dyn AsyncRead + Unpin>;
+
+/// The half of a connection that bytes for the remote host are written to.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// The half of a connection that bytes for the remote host are written to.
+#[cfg(target_arch = "wasm32")]
+pub type TransportWriter = Box<dyn AsyncWrite + Unpin>;
+
+/// The transport connections are opened with unless another one is set.
+#[cfg(not(target_arch = "wasm32"))]
+pub type DefaultTransport = TcpTransport;
+
+/// The transport connections are opened with unless another one is set.
+#[cfg(target_arch = "wasm32")]
+pub type DefaultTransport = WebSocketTransport;
+
+/// A way of opening a byte stream to a remote host.
+pub trait Transport: Send + Sync + 'static {
+    /// Connects to `addr`, whose form depends on the transport.
+    fn connect(
+        &self,
+        addr: String,
+    ) -> BoxedFuture<'static, io::Result<(TransportReader, TransportWriter)>>;
+}
+
+impl Transport for Arc<dyn Transport> {
+    fn connect(
+        &self,
+        addr: String,
+    ) -> BoxedFuture<'static, io::Result<(TransportReader, TransportWriter)>> {
+        (**self).connect(addr)
+    }
+}
+
+/// Connects over TCP to a `<hostname>:<port>` or `<ip_addr>:<port>` address.
+///
+/// Every address the host resolves to is tried, IPv6 first and then
+/// alternating between IPv4 and IPv6. An attempt that hasn't connected within
+/// [`CONNECTION_ATTEMPT_DELAY`] doesn't hold up the next one, which starts
+/// alongside it ("Happy Eyeballs", RFC 8305); the first to connect is used.
+/// If none do, the error is a [`ConnectError`] listing why each failed.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TcpTransport;
+
+/// How long a TCP connection attempt runs on its own before the next address
+/// is tried alongside it.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Transport for TcpTransport {
+    fn connect(
+        &self,
+        addr: String,
+    ) -> BoxedFuture<'static, io::Result<(TransportReader, TransportWriter)>> {
+        Box::pin(async move {
+            let stream = connect_tcp(&addr).await?;
+            let reader: TransportReader = Box::new(stream.clone());
+            let writer: TransportWriter = Box::new(stream);
+            Ok((reader, writer))
+        })
+    }
+}
+
+/// Connects to whichever address `addr` resolves to accepts first, as
+/// [`TcpTransport`] describes.
+#[cfg(not(target_arch = "wasm32"))]
+async fn connect_tcp(addr: &str) -> io::Result<TcpStream> {
+    let addrs = async_net::resolve(addr).await?;
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "the host did not resolve to any addresses",
+        ));
+    }
+
+    connect_to_any(interleave_families(addrs))
+        .await
+        .map_err(|attempts| {
+            let kind = attempts
+                .last()
+                .map_or(io::ErrorKind::NotFound, |(_, err)| err.kind());
+            io::Error::new(kind, ConnectError { attempts })
+        })
+}
+
+/// Why none of the addresses a host resolved to could be connected to.
+///
+/// [`TcpTransport`] returns it inside an [`io::Error`], so it is the
+/// [`source`][std::error::Error::source] of a
+/// [`NetworkError::ConnectFailed`][crate::NetworkError::ConnectFailed].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct ConnectError {
+    /// Each address that was tried, and why connecting to it failed, in the
+    /// order they failed.
+    pub attempts: Vec<(SocketAddr, io::Error)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (addr, err)) in self.attempts.iter().enumerate() {
+            let separator = if index == 0 {
+                "no address could be connected to: "
+            } else {
+                ", "
+            };
+            write!(f, "{separator}{addr} ({err})")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::error::Error for ConnectError {}
+
+/// Orders addresses IPv6 first and then alternating between the families, so
+/// that one that doesn't work only delays connecting by one attempt.
+#[cfg(not(target_arch = "wasm32"))]
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+
+    let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+    let (mut v6, mut v4) = (v6.into_iter(), v4.into_iter());
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => return ordered,
+            (first, second) => ordered.extend(first.into_iter().chain(second)),
+        }
+    }
+}
+
+/// Connects to the first of `addrs` that accepts, starting the next attempt
+/// whenever one fails or [`CONNECTION_ATTEMPT_DELAY`] passes without any
+/// connecting. Returns each address's error if none do.
+#[cfg(not(target_arch = "wasm32"))]
+async fn connect_to_any(addrs: Vec<SocketAddr>) -> Result<TcpStream, Vec<(SocketAddr, io::Error)>> {
+    let mut addrs = addrs.into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut failures = Vec::new();
+
+    let mut start_next = |attempts: &mut FuturesUnordered<_>| {
+        if let Some(addr) = addrs.next() {
+            attempts.push(async move { TcpStream::connect(addr).await.map_err(|err| (addr, err)) });
+        }
+    };
+
+    start_next(&mut attempts);
+    while !attempts.is_empty() {
+        let finished =
+            match future::select(attempts.next(), Timer::after(CONNECTION_ATTEMPT_DELAY)).await {
+                Either::Left((finished, _)) => finished,
+                Either::Right(_) => None,
+            };
+
+        match finished {
+            Some(Ok(stream)) => return Ok(stream),
+            Some(Err(failure)) => {
+                failures.push(failure);
+                start_next(&mut attempts);
+            }
+            None => start_next(&mut attempts),
+        }
+    }
+
+    Err(failures)
+}
+
+/// Connects to a WebSocket proxy at a `ws://<host>[:<port>][/<path>]` URL.
+///
+/// Everything written is sent as binary messages, one per flush; the payloads
+/// of binary messages received are read back in order, and other messages are
+/// ignored. TLS (`wss://`) isn't supported. The proxy is reached the way
+/// [`TcpTransport`] reaches servers.
+///
+/// In the browser, the connection is the browser's own WebSocket, so `wss://`
+/// URLs work too, and text messages are read back as their UTF-8 bytes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WebSocketTransport;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Transport for WebSocketTransport {
+    fn connect(
+        &self,
+        addr: String,
+    ) -> BoxedFuture<'static, io::Result<(TransportReader, TransportWriter)>> {
+        Box::pin(async move {
+            let host = websocket_host(&addr).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("`{addr}` is not a ws:// URL"),
+                )
+            })?;
+            let stream = connect_tcp(&host).await?;
+            let (websocket, _) = async_tungstenite::client_async(addr.as_str(), stream)
+                .await
+                .map_err(io::Error::other)?;
+
+            let (sender, receiver) = websocket.split();
+            let messages = receiver.try_filter(|message| future::ready(message.is_binary()));
+            let reader: TransportReader = Box::new(ByteReader::new(messages));
+            let writer: TransportWriter = Box::new(BufWriter::new(ByteWriter::new(sender)));
+            Ok((reader, writer))
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Transport for WebSocketTransport {
+    fn connect(
+        &self,
+        addr: String,
+    ) -> BoxedFuture<'static, io::Result<(TransportReader, TransportWriter)>> {
+        Box::pin(async move {
+            let (_, websocket) = ws_stream_wasm::WsMeta::connect(&addr, None)
+                .await
+                .map_err(|err| io::Error::other(err.to_string()))?;
+
+            let (reader, writer) = websocket.into_io().split();
+            let reader: TransportReader = Box::new(reader);
+            let writer: TransportWriter = Box::new(BufWriter::new(writer));
+            Ok((reader, writer))
+        })
+    }
+}
+
+/// Returns the `host:port` a `ws://` URL points at, with the default port if
+/// it has none.
+#[cfg(not(target_arch = "wasm32"))]
+fn websocket_host(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("ws://")?;
+    let authority = rest.split(['/', '?']).next()?;
+    if authority.is_empty() {
+        return None;
+    }
+
+    let has_port = match authority.rfind(']') {
+        Some(end) => authority[end..].contains(':'),
+        None => authority.contains(':'),
+    };
+    if has_port {
+        Some(authority.to_string())
+    } else {
+        Some(format!("{authority}:80"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v4(last: u8) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, last], 25565))
+    }
+
+    fn v6(last: u16) -> SocketAddr {
+        SocketAddr::from(([0xfd00, 0, 0, 0, 0, 0, 0, last], 25565))
+    }
+
+    #[test]
+    fn addresses_alternate_families_starting_with_ipv6() {
+        assert_eq!(
+            interleave_families(vec![v4(1), v4(2), v4(3), v6(1), v6(2)]),
+            [v6(1), v4(1), v6(2), v4(2), v4(3)]
+        );
+        assert_eq!(interleave_families(vec![v4(1), v4(2)]), [v4(1), v4(2)]);
+    }
+
+    /// Returns a local address nothing is listening on.
+    fn closed_addr() -> SocketAddr {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    #[test]
+    fn falls_back_to_an_address_that_accepts() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap();
+
+        let stream = async_io::block_on(connect_to_any(vec![closed_addr(), open])).unwrap();
+
+        assert_eq!(stream.peer_addr().unwrap(), open);
+    }
+
+    #[test]
+    fn reports_every_failed_address() {
+        let addrs = vec![closed_addr(), closed_addr()];
+
+        let attempts = async_io::block_on(connect_to_any(addrs.clone())).unwrap_err();
+        let err = ConnectError { attempts };
+
+        assert_eq!(err.attempts.len(), 2);
+        let message = err.to_string();
+        assert!(addrs.iter().all(|addr| message.contains(&addr.to_string())));
+    }
+
+    #[test]
+    fn finds_the_host_of_websocket_urls() {
+        assert_eq!(
+            websocket_host("ws://proxy.example:8080/mc"),
+            Some(String::from("proxy.example:8080"))
+        );
+        assert_eq!(
+            websocket_host("ws://proxy.example?server=a"),
+            Some(String::from("proxy.example:80"))
+        );
+        assert_eq!(
+            websocket_host("ws://[::1]/"),
+            Some(String::from("[::1]:80"))
+        );
+        assert_eq!(
+            websocket_host("ws://[::1]:25565"),
+            Some(String::from("[::1]:25565"))
+        );
+        assert_eq!(websocket_host("wss://proxy.example"), None);
+        assert_eq!(websocket_host("proxy.example:8080"), None);
+        assert_eq!(websocket_host("ws:///path"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Receiving UDP datagrams in the background, e.g. multicast announcements.
+//!
+//! Unlike the TCP side of this crate there is no codec; a [`UdpListener`]
+//! hands out raw [`Datagram`]s and leaves parsing them to the caller.
+
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    thread,
+    time::Duration,
+};
+
+use async_channel::{unbounded, Receiver, Sender};
+use bevy::log;
+use socket2::{Domain, Protocol, Socket, Type};
+
+/// Largest datagram a [`UdpListener`] receives in full; longer ones are
+/// truncated.
+pub const MAX_DATAGRAM_LEN: usize = 4096;
+
+/// How often the background thread checks whether its listener is gone.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A datagram received by a [`UdpListener`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datagram {
+    /// Address the datagram was sent from.
+    pub source: SocketAddr,
+    pub data: Vec<u8>,
+}
+
+/// A UDP socket that receives datagrams on a background thread.
+///
+/// The socket is bound with `SO_REUSEADDR`, so other programs (and other
+/// listeners) can listen on the same port, as is usual for multicast. The
+/// socket is closed shortly after the listener is dropped.
+pub struct UdpListener {
+    local_addr: SocketAddr,
+    receiver: Receiver<Datagram>,
+}
+
+impl UdpListener {
+    /// Listens on `addr`.
+    pub fn bind(addr: SocketAddrV4) -> io::Result<Self> {
+        let socket = bind_reusable(addr)?;
+        Self::spawn(socket.into())
+    }
+
+    /// Listens on `port` of every interface, and joins the multicast `group`
+    /// on the default interface.
+    pub fn bind_multicast_v4(group: Ipv4Addr, port: u16) -> io::Result<Self> {
+        let socket = bind_reusable(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port))?;
+        socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)?;
+        Self::spawn(socket.into())
+    }
+
+    fn spawn(socket: UdpSocket) -> io::Result<Self> {
+        let local_addr = socket.local_addr()?;
+        // Wakes the thread up now and then to notice the listener is gone.
+        socket.set_read_timeout(Some(SHUTDOWN_POLL_INTERVAL))?;
+
+        let (sender, receiver) = unbounded();
+        thread::Builder::new()
+            .name(format!("udp listener {local_addr}"))
+            .spawn(move || receive(socket, sender))?;
+
+        Ok(Self {
+            local_addr,
+            receiver,
+        })
+    }
+
+    /// Address the socket is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the next datagram received, if any.
+    pub fn try_recv(&self) -> Option<Datagram> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+fn bind_reusable(addr: SocketAddrV4) -> io::Result<Socket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::V4(addr).into())?;
+    Ok(socket)
+}
+
+fn receive(socket: UdpSocket, sender: Sender<Datagram>) {
+    let mut buf = [0; MAX_DATAGRAM_LEN];
+
+    while !sender.is_closed() {
+        match socket.recv_from(&mut buf) {
+            Ok((len, source)) => {
+                let datagram = Datagram {
+                    source,
+                    data: buf[..len].to_vec(),
+                };
+                if sender.try_send(datagram).is_err() {
+                    break;
+                }
+            }
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            // Other errors are reported per datagram (e.g. one that didn't fit
+            // the buffer on Windows); the socket is still usable.
+            Err(err) => log::debug!("Failed to receive a datagram: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn receives_datagrams_in_the_background() {
+        let listener = UdpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.send_to(b"hello", listener.local_addr()).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let datagram = loop {
+            if let Some(datagram) = listener.try_recv() {
+                break datagram;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for the datagram"
+            );
+            thread::sleep(Duration::from_millis(5));
+        };
+
+        assert_eq!(datagram.source, sender.local_addr().unwrap());
+        assert_eq!(datagram.data, b"hello");
+    }
+}
@@ -0,0 +1,160 @@
+//! Buffered channels for events that must not be lost.
+//!
+//! Bevy keeps a message for two frames, then drops it whether or not every
+//! reader has seen it. That's fine for most events, but a system that stalls
+//! (skipped by a run condition, or waiting on assets) can miss a burst of
+//! [`ChunkData`][crate::event::clientbound::ChunkData] entirely, leaving holes
+//! in the world.
+//!
+//! A [`MessageBufferPlugin`][crate::MessageBufferPlugin] copies every message
+//! of its type into a [`MessageBuffer`] each frame, where it stays until a
+//! consumer takes it. The plugin reads with a cursor of its own, so the
+//! messages are still there for regular readers. Buffers are unbounded by default, so nothing is lost; a
+//! bounded buffer drops its oldest messages instead and counts them. The
+//! number of pending and dropped messages is published as diagnostics.
+
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::{
+    diagnostic::DiagnosticPath,
+    ecs::{message::Message, resource::Resource},
+};
+
+/// Messages of type `T` waiting for a consumer.
+///
+/// Registered by a [`MessageBufferPlugin`][crate::MessageBufferPlugin], which
+/// copies messages here without taking them out of
+/// [`Messages<T>`][bevy::ecs::message::Messages]. Consumers that must not
+/// miss a message should [`drain`](Self::drain) the buffer rather than use a
+/// [`MessageReader`][bevy::ecs::message::MessageReader].
+#[derive(Resource, Debug)]
+pub struct MessageBuffer<T: Message> {
+    messages: VecDeque<T>,
+    capacity: Option<usize>,
+    received: u64,
+    dropped: u64,
+}
+
+impl<T: Message> MessageBuffer<T> {
+    /// Creates an empty buffer that holds at most `capacity` messages, or any
+    /// number of them if `None`.
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            messages: VecDeque::new(),
+            capacity,
+            received: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Adds a message, dropping the oldest pending one if the buffer is full.
+    ///
+    /// Returns `true` if a message was dropped.
+    pub fn push(&mut self, message: T) -> bool {
+        self.received += 1;
+
+        let full = self
+            .capacity
+            .is_some_and(|capacity| self.messages.len() >= capacity);
+        if full {
+            self.dropped += 1;
+            if self.messages.pop_front().is_none() {
+                // Zero capacity: drop the new message itself.
+                return true;
+            }
+        }
+
+        self.messages.push_back(message);
+        full
+    }
+
+    /// Removes and returns the pending messages, oldest first.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.messages.drain(..)
+    }
+
+    /// Returns the number of messages waiting for a consumer.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of messages ever added to the buffer.
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Returns the number of messages dropped because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl<T: Message> Default for MessageBuffer<T> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Diagnostic paths of a [`MessageBuffer`].
+///
+/// A buffer named `chunk_data` publishes `brine/buffer/chunk_data/pending`
+/// and `brine/buffer/chunk_data/dropped`.
+#[derive(Resource, Debug, Clone)]
+pub struct MessageBufferDiagnostics<T: Message> {
+    name: String,
+
+    /// Messages waiting for a consumer, measured each frame after the buffer
+    /// is filled.
+    pub pending: DiagnosticPath,
+
+    /// Messages dropped since startup.
+    pub dropped: DiagnosticPath,
+
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Message> MessageBufferDiagnostics<T> {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pending: DiagnosticPath::new(format!("brine/buffer/{name}/pending")),
+            dropped: DiagnosticPath::new(format!("brine/buffer/{name}/dropped")),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::message::Message;
+
+    use super::*;
+
+    #[derive(Message, Debug, PartialEq)]
+    struct Chunk(u32);
+
+    #[test]
+    fn full_buffer_drops_oldest() {
+        let mut buffer = MessageBuffer::new(Some(2));
+        assert!(!buffer.push(Chunk(1)));
+        assert!(!buffer.push(Chunk(2)));
+        assert!(buffer.push(Chunk(3)));
+
+        assert_eq!(buffer.received(), 3);
+        assert_eq!(buffer.dropped(), 1);
+        assert_eq!(buffer.drain().collect::<Vec<_>>(), [Chunk(2), Chunk(3)]);
+        assert!(buffer.is_empty());
+    }
+}
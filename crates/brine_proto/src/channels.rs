@@ -0,0 +1,43 @@
+//! Registry of custom payload (plugin message) channels.
+
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::Resource;
+
+/// Channel the client uses to identify itself to the server.
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+
+/// Channels the client wants to receive
+/// [`PluginMessage`][crate::event::clientbound::PluginMessage]s for.
+///
+/// Custom payloads on any other channel are dropped by the backend. The
+/// backend also announces registered channels to the server on
+/// `minecraft:register` (and removed ones on `minecraft:unregister`), which is
+/// how server-side plugins learn what the client understands.
+///
+/// Channel names are namespaced identifiers, e.g. `bungeecord:main`.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct PluginChannels {
+    channels: BTreeSet<String>,
+}
+
+impl PluginChannels {
+    /// Starts listening on `channel`. Returns `false` if already registered.
+    pub fn register(&mut self, channel: impl Into<String>) -> bool {
+        self.channels.insert(channel.into())
+    }
+
+    /// Stops listening on `channel`. Returns `false` if it wasn't registered.
+    pub fn unregister(&mut self, channel: &str) -> bool {
+        self.channels.remove(channel)
+    }
+
+    pub fn is_registered(&self, channel: &str) -> bool {
+        self.channels.contains(channel)
+    }
+
+    /// Iterates over the registered channels in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.channels.iter().map(String::as_str)
+    }
+}
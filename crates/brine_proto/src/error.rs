@@ -0,0 +1,62 @@
+//! Errors reported to the user.
+//!
+//! Crates keep their own typed errors (a decode error knows the packet id and
+//! protocol state, a chunk error knows the chunk's coordinates). When one of
+//! those ends up affecting the player, whoever handled it sends a
+//! [`ClientError`] summarizing it, so that the UI can show what happened and
+//! what to do about it without depending on every crate's error types.
+
+use bevy_ecs::prelude::Message;
+
+/// Something went wrong that the user should know about.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct ClientError {
+    pub kind: ClientErrorKind,
+
+    /// Human-readable description of the error, including its context.
+    pub message: String,
+}
+
+impl ClientError {
+    pub fn new(kind: ClientErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Returns a suggestion of what the user can do about the error.
+    pub fn hint(&self) -> &'static str {
+        self.kind.hint()
+    }
+}
+
+/// What a [`ClientError`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientErrorKind {
+    /// The client couldn't connect to the server.
+    Connect,
+
+    /// The connection to the server failed after it was established.
+    Connection,
+
+    /// The server sent something the client couldn't decode, or the client
+    /// couldn't encode something it meant to send.
+    Protocol,
+
+    /// A chunk sent by the server couldn't be decoded.
+    Chunk { chunk_x: i32, chunk_z: i32 },
+}
+
+impl ClientErrorKind {
+    pub fn hint(self) -> &'static str {
+        match self {
+            Self::Connect => "Check the server address and that the server is running.",
+            Self::Connection => "Check your network connection, then reconnect.",
+            Self::Protocol => {
+                "The server may be running an unsupported version or sending bad data."
+            }
+            Self::Chunk { .. } => "Part of the world may be missing until you reconnect.",
+        }
+    }
+}
@@ -7,14 +7,394 @@
 //! the actual Minecraft protocol defined at <https://wiki.vg/Protocol>. This
 //! API is much more high-level, and the "back-end" is concerned with speaking
 //! the actual protocol and converting to and from this higher-level API.
+//!
+//! Not every backend sends every event; a backend that doesn't understand
+//! part of the protocol simply never sends the events for it.
+//!
+//! # Versioning
+//!
+//! The events form a schema identified by [`SCHEMA_VERSION`]. With the `serde`
+//! feature, every event can be serialized, e.g. to record a session or to
+//! send it to another process; wrap events in [`Versioned`] so that the
+//! reader can tell whether it understands them.
+//!
+//! The version is bumped whenever an existing event changes shape: a field
+//! or variant is added, removed or renamed, or its type or meaning changes.
+//! Adding a new event doesn't bump it, since no existing reader can have
+//! depended on it.
+//!
+//! ## History
+//!
+//! 1. The first versioned schema: login, disconnect, transfer and
+//!    reconfiguration, plugin messages, chunks, block changes and break
+//!    progress, the world border, chat, the inventory, entities, the player
+//!    list, health and status effects.
+//! 2. [`clientbound::InventoryContents`] and [`clientbound::InventorySlot`]
+//!    carry the inventory's state ID, which clicks must echo.
+
+use std::fmt;
 
+pub use bevy::math::{DVec3, IVec3, Vec3};
+pub use brine_chunk::BlockState;
 pub use uuid::Uuid;
 
+/// Version of the event schema defined by this module. See the
+/// [module documentation][self].
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// An event tagged with the [`SCHEMA_VERSION`] it was written with.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Versioned<E> {
+    pub schema_version: u32,
+    pub event: E,
+}
+
+impl<E> Versioned<E> {
+    /// Tags `event` with the current schema version.
+    pub fn new(event: E) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            event,
+        }
+    }
+
+    /// Returns the event if it was written with the current schema version.
+    pub fn into_current(self) -> Result<E, SchemaVersionMismatch> {
+        if self.schema_version == SCHEMA_VERSION {
+            Ok(self.event)
+        } else {
+            Err(SchemaVersionMismatch {
+                found: self.schema_version,
+            })
+        }
+    }
+}
+
+/// A [`Versioned`] event was written with a different schema version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaVersionMismatch {
+    pub found: u32,
+}
+
+impl fmt::Display for SchemaVersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "event schema version {} is not the supported version {}",
+            self.found, SCHEMA_VERSION
+        )
+    }
+}
+
+impl std::error::Error for SchemaVersionMismatch {}
+
+/// A stack of items in an inventory slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItemStack {
+    /// Numeric item ID, as used by the registry of the server's version.
+    pub item_id: i32,
+
+    /// Number of items in the stack.
+    pub count: i32,
+}
+
+/// One of the player's hands.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hand {
+    /// The hand holding the selected hotbar item.
+    #[default]
+    Main,
+    /// The hand holding the item in the off-hand slot.
+    Off,
+}
+
+/// One of the six faces of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockFace {
+    /// Facing -Y.
+    Down,
+    /// Facing +Y.
+    Up,
+    /// Facing -Z.
+    North,
+    /// Facing +Z.
+    South,
+    /// Facing -X.
+    West,
+    /// Facing +X.
+    East,
+}
+
+impl BlockFace {
+    /// Returns the face pointing along `normal`, a unit vector along one
+    /// axis, or `None` for any other vector.
+    pub fn from_normal(normal: IVec3) -> Option<Self> {
+        match normal.to_array() {
+            [0, -1, 0] => Some(Self::Down),
+            [0, 1, 0] => Some(Self::Up),
+            [0, 0, -1] => Some(Self::North),
+            [0, 0, 1] => Some(Self::South),
+            [-1, 0, 0] => Some(Self::West),
+            [1, 0, 0] => Some(Self::East),
+            _ => None,
+        }
+    }
+
+    /// Returns the unit vector pointing out of the face.
+    pub fn normal(self) -> IVec3 {
+        match self {
+            Self::Down => IVec3::NEG_Y,
+            Self::Up => IVec3::Y,
+            Self::North => IVec3::NEG_Z,
+            Self::South => IVec3::Z,
+            Self::West => IVec3::NEG_X,
+            Self::East => IVec3::X,
+        }
+    }
+}
+
+/// A stage of digging a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DigStatus {
+    /// The player started digging. Blocks that break instantly (e.g. in
+    /// creative mode) break right away.
+    Start,
+    /// The player stopped digging before the block broke.
+    Cancel,
+    /// The player dug long enough for the block to break.
+    Finish,
+}
+
+/// What can go in one slot of a crafting recipe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ingredient {
+    /// Any of these items, by numeric item ID.
+    Items(Vec<i32>),
+
+    /// Any item with this tag, e.g. `minecraft:planks`.
+    Tag(String),
+}
+
+/// How a crafting recipe's ingredients are laid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecipeShape {
+    /// The ingredients form a `width` by `height` pattern, row by row, which
+    /// may go anywhere in the crafting grid. `None` is an empty cell.
+    Shaped {
+        width: u8,
+        height: u8,
+        ingredients: Vec<Option<Ingredient>>,
+    },
+
+    /// The ingredients can go anywhere in the crafting grid.
+    Shapeless { ingredients: Vec<Ingredient> },
+}
+
+impl RecipeShape {
+    /// Returns whether the recipe can be crafted in a square crafting grid of
+    /// the given size, e.g. `2` for the player's inventory.
+    pub fn fits_in(&self, grid_size: u8) -> bool {
+        match self {
+            Self::Shaped { width, height, .. } => *width <= grid_size && *height <= grid_size,
+            Self::Shapeless { ingredients } => {
+                ingredients.len() <= usize::from(grid_size) * usize::from(grid_size)
+            }
+        }
+    }
+}
+
+/// A crafting recipe the player has unlocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recipe {
+    /// ID the server uses for the recipe in this session.
+    pub id: i32,
+    pub shape: RecipeShape,
+    pub result: ItemStack,
+}
+
+/// How a click in a container window moves items around.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClickMode {
+    /// Picks up or puts down the stack (left button) or half of it (right
+    /// button).
+    #[default]
+    Pickup,
+    /// Moves the stack to the other part of the window, as with shift-click.
+    QuickMove,
+    /// Swaps the slot with the hotbar slot given by the button (`0..9`), or
+    /// with the off-hand (`40`).
+    Swap,
+    /// Copies the stack onto the cursor in creative mode.
+    Clone,
+    /// Drops one item (button `0`) or the whole stack (button `1`).
+    Throw,
+    /// One step of dragging items across slots.
+    QuickCraft,
+    /// Collects items matching the cursor's, as with double-click.
+    PickupAll,
+}
+
+/// What kind of container a window shows, which decides how many slots it
+/// has before the player's inventory and how they are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContainerKind {
+    /// A chest, barrel or ender chest: `rows` rows of nine slots.
+    Generic {
+        rows: u8,
+    },
+    /// A dispenser or dropper: three rows of three slots.
+    Dispenser,
+    /// A crafting table: the result, then a 3x3 grid.
+    Crafting,
+    /// A furnace: the ingredient, the fuel and the result.
+    Furnace,
+    BlastFurnace,
+    Smoker,
+    /// Five slots in a row.
+    Hopper,
+    /// Three rows of nine slots.
+    ShulkerBox,
+    /// Any other kind, by its ID in the `minecraft:menu` registry.
+    Other(i32),
+}
+
+impl ContainerKind {
+    /// Returns the number of slots the container itself has, before the
+    /// player's inventory, or `None` for [`Other`][Self::Other] kinds.
+    pub fn slot_count(self) -> Option<usize> {
+        match self {
+            Self::Generic { rows } => Some(usize::from(rows) * 9),
+            Self::Dispenser => Some(9),
+            Self::Crafting => Some(10),
+            Self::Furnace | Self::BlastFurnace | Self::Smoker => Some(3),
+            Self::Hopper => Some(5),
+            Self::ShulkerBox => Some(27),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// Returns whether every slot of the container simply holds items, so
+    /// clicking them works the same as in a chest.
+    pub fn is_storage(self) -> bool {
+        matches!(
+            self,
+            Self::Generic { .. } | Self::Dispenser | Self::Hopper | Self::ShulkerBox
+        )
+    }
+}
+
+/// A marker drawn on a map, such as a player, a banner or an explorer map's
+/// target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapIcon {
+    /// Numeric icon type, as used by the `minecraft:map_decoration_type`
+    /// registry of the server's version.
+    pub kind: i32,
+
+    /// Position on the map, from -128 (left or top edge) to 127 (right or
+    /// bottom edge); twice the map's pixel coordinates.
+    pub x: i8,
+    pub z: i8,
+
+    /// Rotation in sixteenths of a full turn (`0..16`), clockwise from north.
+    pub direction: u8,
+
+    /// Label shown under the icon, e.g. a named banner's name.
+    pub label: Option<String>,
+}
+
+/// A rectangle of a map's 128x128 pixels that changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapPatch {
+    /// Top left pixel of the rectangle.
+    pub x: u8,
+    pub z: u8,
+
+    pub width: u8,
+    pub height: u8,
+
+    /// Map colors of the rectangle's pixels, row by row: a base color in the
+    /// upper six bits and a shade in the lower two.
+    pub colors: Vec<u8>,
+}
+
+/// A game mode, which decides what the player can do.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameMode {
+    #[default]
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+/// How hard the server's world is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// What the server allows the player to do, regardless of game mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Abilities {
+    /// The player takes no damage, including from falling.
+    pub invulnerable: bool,
+
+    /// The player is flying.
+    pub flying: bool,
+
+    /// The player may start flying.
+    pub may_fly: bool,
+
+    /// Blocks break as soon as the player starts digging, as in creative
+    /// mode.
+    pub instant_break: bool,
+
+    /// Flying speed, `0.05` by default.
+    pub flying_speed: f32,
+
+    /// Walking speed, `0.1` by default; also affects the field of view.
+    pub walking_speed: f32,
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Self {
+            invulnerable: false,
+            flying: false,
+            may_fly: false,
+            instant_break: false,
+            flying_speed: 0.05,
+            walking_speed: 0.1,
+        }
+    }
+}
+
 pub mod serverbound {
     //! Definitions for all serverbound events.
 
     #[allow(unused)]
     use super::clientbound;
+    use super::{BlockFace, ClickMode, DVec3, DigStatus, Hand, IVec3, ItemStack, Vec3};
     use bevy_ecs::prelude::Message;
 
     /// Initiates login for the given user on the given server.
@@ -26,6 +406,7 @@ pub mod serverbound {
     /// * [`clientbound::LoginSuccess`]
     /// * [`clientbound::Disconnect`]
     #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Login {
         /// Hostname or IP address of the server.
         pub server: String,
@@ -34,16 +415,321 @@ pub mod serverbound {
         pub username: String,
     }
 
+    /// Sends a custom payload to the server on the given channel.
+    ///
+    /// Messages can only be delivered during the configuration and play
+    /// phases; the backend drops them at other times.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::PluginMessage`]
+    /// * [`PluginChannels`][crate::PluginChannels]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PluginMessage {
+        /// Namespaced channel name, e.g. `minecraft:brand`.
+        pub channel: String,
+
+        /// Raw payload; its format is defined by the channel.
+        pub data: Vec<u8>,
+    }
+
+    /// Selects the hotbar slot (`0..9`) whose item the player holds.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::HotbarSlotSelected`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SelectHotbarSlot {
+        pub slot: u8,
+    }
+
+    /// Sends a chat message, or runs a command if the message starts with
+    /// `/`.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::ChatMessage`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SendChatMessage {
+        pub message: String,
+    }
+
+    /// Asks the server to respawn the player after dying.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::HealthUpdated`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Respawn;
+
+    /// Tells the server that the player started or stopped flying.
+    ///
+    /// Only has an effect while the player's abilities allow flight.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::AbilitiesChanged`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SetFlying {
+        pub flying: bool,
+    }
+
+    /// Tells the server that the player started or stopped sneaking.
+    ///
+    /// The server crouches the player and stops them from walking off edges
+    /// while sneaking, but keeps them standing while flying or swimming.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SetSneaking {
+        pub sneaking: bool,
+    }
+
+    /// Tells the server that the player started or stopped sprinting.
+    ///
+    /// Sprinting in water makes the player swim.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SetSprinting {
+        pub sprinting: bool,
+    }
+
+    /// Tells the server that the player started gliding with the elytra they
+    /// wear.
+    ///
+    /// There is no event for stopping: the server stops the glide itself once
+    /// the player lands, enters water or takes the elytra off.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct StartGliding;
+
+    /// Tells the server where the player moved and where they are looking.
+    ///
+    /// Angles are as in [`clientbound::EntitySpawned`]. Relative teleports
+    /// are resolved against the latest position and angles sent.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::PlayerTeleported`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct MovePlayer {
+        /// World position of the player's feet.
+        pub position: DVec3,
+
+        pub yaw: f32,
+        pub pitch: f32,
+        pub on_ground: bool,
+    }
+
+    /// Uses the item held in the given hand, e.g. to start eating, drawing a
+    /// bow, or raising a shield, or to throw a snowball.
+    ///
+    /// Items that are used over time keep being used until they finish (e.g.
+    /// the food is eaten) or until [`ReleaseUseItem`] is sent.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct UseItem {
+        pub hand: Hand,
+
+        /// Direction the player is looking, in degrees.
+        pub yaw: f32,
+        pub pitch: f32,
+    }
+
+    /// Stops using the item started with [`UseItem`], e.g. to shoot an arrow
+    /// or lower a shield.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ReleaseUseItem;
+
+    /// Swaps the items in the main hand and the off-hand.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::InventorySlot`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SwapHands;
+
+    /// Digs the block at `position`, or stops digging it.
+    ///
+    /// `sequence` comes from [`ActionSequence`][crate::ActionSequence]; the
+    /// server acknowledges it once it has sent the block changes the action
+    /// caused.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::BlockChanged`]
+    /// * [`clientbound::BlockChangesAcknowledged`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DigBlock {
+        pub position: IVec3,
+
+        /// The face of the block the player is looking at.
+        pub face: BlockFace,
+
+        pub status: DigStatus,
+        pub sequence: i32,
+    }
+
+    /// Uses the item in the given hand on a face of the block at `position`,
+    /// e.g. to place a block against it.
+    ///
+    /// `sequence` comes from [`ActionSequence`][crate::ActionSequence], as
+    /// for [`DigBlock`].
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::BlockChanged`]
+    /// * [`clientbound::BlockChangesAcknowledged`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlaceBlock {
+        pub hand: Hand,
+
+        /// The block that was clicked, not where the new block goes.
+        pub position: IVec3,
+        pub face: BlockFace,
+
+        /// Where on the block the player clicked, from `0.0` to `1.0` on each
+        /// axis.
+        pub cursor: Vec3,
+
+        pub sequence: i32,
+    }
+
+    /// Asks the server to move the ingredients of a recipe from the inventory
+    /// into the crafting grid of the given window (`0` for the player's
+    /// inventory).
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::RecipesAdded`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlaceRecipe {
+        pub window_id: i32,
+        pub recipe_id: i32,
+
+        /// Fill the grid with as many sets of ingredients as possible, as with
+        /// shift-click.
+        pub make_all: bool,
+    }
+
+    /// Clicks a slot in a container window (`0` for the player's inventory).
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::InventoryContents`]
+    /// * [`clientbound::ContainerContents`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ClickContainer {
+        pub window_id: i32,
+
+        /// The last state ID the server sent for the window.
+        pub state_id: i32,
+
+        /// The clicked slot, or `-999` for outside the window.
+        pub slot: i16,
+        pub button: i8,
+        pub mode: ClickMode,
+
+        /// The slots the client expects the click to change, with their new
+        /// contents. The server corrects any it disagrees with.
+        pub changed_slots: Vec<(i16, Option<ItemStack>)>,
+
+        /// The item the client expects on the cursor after the click.
+        pub carried_item: Option<ItemStack>,
+    }
+
+    /// Closes a container window (`0` for the player's inventory), returning
+    /// the items in its crafting grid to the inventory.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct CloseContainer {
+        pub window_id: i32,
+    }
+
+    /// Moves the vehicle the player is riding and controlling, e.g. a boat or
+    /// a saddled horse.
+    ///
+    /// Angles are as in [`clientbound::EntitySpawned`].
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::PassengersSet`]
+    /// * [`clientbound::VehicleMoved`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct MoveVehicle {
+        /// World position of the vehicle's feet.
+        pub position: DVec3,
+
+        pub yaw: f32,
+        pub pitch: f32,
+    }
+
+    /// Tells the server which movement inputs the player is holding while
+    /// riding a vehicle. Sneaking dismounts.
+    ///
+    /// Only needs to be sent when the inputs change.
+    ///
+    /// # See also
+    ///
+    /// * [`clientbound::PassengersSet`]
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SteerVehicle {
+        pub forward: bool,
+        pub backward: bool,
+        pub left: bool,
+        pub right: bool,
+        pub jump: bool,
+        pub sneak: bool,
+    }
+
     pub(crate) fn add_events(app: &mut bevy::app::App) {
         app.add_message::<Login>();
+        app.add_message::<PluginMessage>();
+        app.add_message::<SelectHotbarSlot>();
+        app.add_message::<SendChatMessage>();
+        app.add_message::<Respawn>();
+        app.add_message::<SetFlying>();
+        app.add_message::<SetSneaking>();
+        app.add_message::<SetSprinting>();
+        app.add_message::<StartGliding>();
+        app.add_message::<MovePlayer>();
+        app.add_message::<UseItem>();
+        app.add_message::<ReleaseUseItem>();
+        app.add_message::<SwapHands>();
+        app.add_message::<DigBlock>();
+        app.add_message::<PlaceBlock>();
+        app.add_message::<PlaceRecipe>();
+        app.add_message::<ClickContainer>();
+        app.add_message::<CloseContainer>();
+        app.add_message::<MoveVehicle>();
+        app.add_message::<SteerVehicle>();
     }
 }
 
 pub mod clientbound {
     //! Definitions for all clientbound events.
 
+    use std::time::Duration;
+
     #[allow(unused)]
     use super::serverbound;
+    use super::{
+        Abilities, BlockState, ContainerKind, DVec3, Difficulty, GameMode, IVec3, ItemStack,
+        MapIcon, MapPatch, Recipe,
+    };
     use bevy_ecs::prelude::Message;
 
     /// Notifies the client that they have successfully logged in to the server.
@@ -52,6 +738,7 @@ pub mod clientbound {
     ///
     /// * [`serverbound::Login`]
     #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct LoginSuccess {
         /// UUID assigned by the server to this client.
         pub uuid: uuid::Uuid,
@@ -60,6 +747,16 @@ pub mod clientbound {
         pub username: String,
     }
 
+    /// Notifies the client that it has joined the world, or rejoined it after
+    /// reconfiguration.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct JoinedGame {
+        /// ID the server uses for the player's own entity, e.g. in
+        /// [`EntityEffectAdded`].
+        pub entity_id: i32,
+    }
+
     /// Notifies the client they have been disconnected from the server.
     ///
     /// This could happen for a number of reasons:
@@ -69,20 +766,729 @@ pub mod clientbound {
     /// * Generic networking error.
     /// * etc...
     #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Disconnect {
         /// Human-readable reason for why the disconnect occurred.
         pub reason: String,
+
+        /// Who ended the session, which decides whether it is worth trying
+        /// again.
+        pub kind: DisconnectKind,
+    }
+
+    /// Why a [`Disconnect`] happened.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum DisconnectKind {
+        /// The server ended the session on purpose: it refused the login or
+        /// kicked the player.
+        Kicked,
+
+        /// The connection couldn't be established, or was lost. Trying again
+        /// later may succeed.
+        Network,
+
+        /// The client can't play on the server, e.g. because the server's
+        /// protocol version is not supported.
+        Incompatible,
+    }
+
+    /// Notifies the client that the server has handed it off to another
+    /// server.
+    ///
+    /// The backend reconnects on its own and sends [`LoginSuccess`] again once
+    /// it has logged in there, or [`Disconnect`] if that fails. Everything
+    /// received from the previous server should be discarded.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Transfer {
+        /// Address (`host:port`) of the server being transferred to.
+        pub server: String,
+    }
+
+    /// Notifies the client that the server has sent it back to the
+    /// configuration phase mid-game, e.g. to change registries or resource
+    /// packs.
+    ///
+    /// The world should be discarded; the server sends it again once
+    /// configuration has finished.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Reconfigure;
+
+    /// Notifies the client of how long a message took to reach the server and
+    /// come back, measured by the backend at regular intervals while playing.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PingMeasured {
+        pub round_trip: Duration,
+    }
+
+    /// A custom payload received from the server on a channel registered in
+    /// [`PluginChannels`][crate::PluginChannels].
+    ///
+    /// # See also
+    ///
+    /// * [`serverbound::PluginMessage`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PluginMessage {
+        /// Namespaced channel name, e.g. `minecraft:brand`.
+        pub channel: String,
+
+        /// Raw payload; its format is defined by the channel.
+        pub data: Vec<u8>,
     }
 
     /// Contains data relating to a 16x256x16 chunk of the Minecraft world.
     #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ChunkData {
         pub chunk_data: brine_chunk::Chunk,
     }
 
+    /// Notifies the client that a block in a loaded chunk has changed.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BlockChanged {
+        /// World position of the block.
+        pub position: IVec3,
+
+        /// The block's new state.
+        pub block_state: BlockState,
+    }
+
+    /// Notifies the client that the server has handled every action up to
+    /// and including the given [`ActionSequence`][crate::ActionSequence]
+    /// number, and has sent the block changes they caused.
+    ///
+    /// Blocks the client changed in anticipation of those actions that the
+    /// server didn't confirm with a [`BlockChanged`] should be put back.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BlockChangesAcknowledged {
+        pub sequence: i32,
+    }
+
+    /// Notifies the client of the progress of an entity breaking a block.
+    ///
+    /// The server does not send these for the client's own digging, so the
+    /// client is expected to track that progress locally.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BlockBreakProgress {
+        /// ID of the entity that is breaking the block.
+        pub entity_id: i32,
+
+        /// World position of the block being broken.
+        pub position: IVec3,
+
+        /// Destroy stage in the range `0..=9`, or `None` if the entity has
+        /// stopped breaking the block.
+        pub stage: Option<u8>,
+    }
+
+    /// Changes to the world border.
+    ///
+    /// Coordinates and diameters are in blocks.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum WorldBorderUpdate {
+        /// Sets every property of the border at once. Sent on join and after
+        /// respawning in another dimension.
+        Initialize {
+            center_x: f64,
+            center_z: f64,
+            old_diameter: f64,
+            new_diameter: f64,
+            /// Time over which the diameter changes from `old_diameter` to
+            /// `new_diameter`; zero if it doesn't change.
+            lerp_millis: i64,
+            /// Distance from the world origin beyond which portals won't
+            /// teleport.
+            portal_teleport_boundary: i32,
+            warning_blocks: i32,
+            warning_seconds: i32,
+        },
+        Center {
+            x: f64,
+            z: f64,
+        },
+        /// Starts moving the border from `old_diameter` to `new_diameter`.
+        LerpSize {
+            old_diameter: f64,
+            new_diameter: f64,
+            lerp_millis: i64,
+        },
+        /// Sets the diameter immediately.
+        Size {
+            diameter: f64,
+        },
+        /// A moving border warns players this many seconds before it reaches
+        /// them.
+        WarningDelay {
+            seconds: i32,
+        },
+        /// Players closer than this many blocks to the border are warned.
+        WarningDistance {
+            blocks: i32,
+        },
+    }
+
+    /// Updates a map item's image or icons.
+    ///
+    /// The first update of a map the client is shown covers all of it; later
+    /// ones only carry what changed.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct MapData {
+        /// ID of the map, as stored in the `map_id` component of the map
+        /// items showing it.
+        pub map_id: i32,
+
+        /// Zoom level in the range `0..=4`; each pixel covers `2^scale`
+        /// blocks.
+        pub scale: u8,
+
+        /// Whether the map has been locked in a cartography table and no
+        /// longer changes.
+        pub locked: bool,
+
+        /// The map's icons, replacing the previous ones, or `None` if they
+        /// didn't change.
+        pub icons: Option<Vec<MapIcon>>,
+
+        /// The pixels that changed, if any.
+        pub patch: Option<MapPatch>,
+    }
+
+    /// Notifies the client that the server has changed which hotbar slot
+    /// (`0..9`) is selected.
+    ///
+    /// # See also
+    ///
+    /// * [`serverbound::SelectHotbarSlot`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HotbarSlotSelected {
+        pub slot: u8,
+    }
+
+    /// Replaces the entire contents of the player's inventory.
+    ///
+    /// Items are indexed by player inventory slot: `0` is the crafting output,
+    /// `1..5` the crafting grid, `5..9` armor, `9..36` the main inventory,
+    /// `36..45` the hotbar and `45` the offhand.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct InventoryContents {
+        /// State ID to echo in [`serverbound::ClickContainer`].
+        pub state_id: i32,
+        pub items: Vec<Option<ItemStack>>,
+    }
+
+    /// Replaces the item in one slot of the player's inventory.
+    ///
+    /// Slots are numbered as in [`InventoryContents`].
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct InventorySlot {
+        /// New state ID to echo in [`serverbound::ClickContainer`], or `None`
+        /// if the update doesn't change it.
+        pub state_id: Option<i32>,
+        pub slot: u16,
+        pub item: Option<ItemStack>,
+    }
+
+    /// Notifies the client of the item the player is holding with the cursor
+    /// in an open inventory, or `None` if the cursor is empty.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct CursorItem {
+        pub item: Option<ItemStack>,
+    }
+
+    /// Adds crafting recipes to the player's recipe book, or replaces the
+    /// whole book if `replace` is set.
+    ///
+    /// # See also
+    ///
+    /// * [`serverbound::PlaceRecipe`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct RecipesAdded {
+        pub recipes: Vec<Recipe>,
+        pub replace: bool,
+    }
+
+    /// Removes recipes from the player's recipe book.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct RecipesRemoved {
+        pub ids: Vec<i32>,
+    }
+
+    /// Notifies the client that the server sends recipes in a form the
+    /// backend can't read, so the recipe book stays empty and recipes can't
+    /// be placed. Sent on entering the Play state.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct RecipeBookUnavailable {
+        /// Why, for showing to the player.
+        pub reason: String,
+    }
+
+    /// Opens a container window, e.g. because the player opened a chest. Its
+    /// contents follow in a [`ContainerContents`].
+    ///
+    /// # See also
+    ///
+    /// * [`serverbound::CloseContainer`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ContainerOpened {
+        pub window_id: i32,
+        pub kind: ContainerKind,
+        pub title: String,
+    }
+
+    /// Replaces the contents of an open container window: the container's
+    /// own slots, then the player's main inventory and hotbar.
+    ///
+    /// The player's own inventory window (`0`) is sent as
+    /// [`InventoryContents`] instead.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ContainerContents {
+        pub window_id: i32,
+
+        /// State ID to echo in [`serverbound::ClickContainer`].
+        pub state_id: i32,
+        pub items: Vec<Option<ItemStack>>,
+    }
+
+    /// Sets one slot of an open container window.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ContainerSlot {
+        pub window_id: i32,
+
+        /// State ID to echo in [`serverbound::ClickContainer`].
+        pub state_id: i32,
+        pub slot: u16,
+        pub item: Option<ItemStack>,
+    }
+
+    /// Sets a property of an open container window, e.g. a furnace's cooking
+    /// progress. What the properties mean depends on the
+    /// [`ContainerKind`].
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ContainerProperty {
+        pub window_id: i32,
+        pub property: i16,
+        pub value: i16,
+    }
+
+    /// Closes a container window, e.g. because the chest was broken.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ContainerClosed {
+        pub window_id: i32,
+    }
+
+    /// A chat message, system message or action bar text to show to the
+    /// player.
+    ///
+    /// # See also
+    ///
+    /// * [`serverbound::SendChatMessage`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ChatMessage {
+        /// Plain text of the message, without formatting.
+        pub text: String,
+
+        pub kind: ChatKind,
+    }
+
+    /// Where a [`ChatMessage`] comes from and where it's shown.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ChatKind {
+        /// Sent by the player with this UUID; shown in chat.
+        Player { sender: uuid::Uuid },
+
+        /// Sent by the server itself; shown in chat.
+        System,
+
+        /// Shown above the hotbar, replacing the previous action bar text.
+        ActionBar,
+    }
+
+    /// Notifies the client that an entity has come into view.
+    ///
+    /// Angles are in degrees. A yaw of 0 faces south (+Z) and increases
+    /// clockwise when viewed from above; a positive pitch looks down.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntitySpawned {
+        /// ID the server uses for this entity in later events.
+        pub entity_id: i32,
+
+        pub uuid: uuid::Uuid,
+
+        /// Numeric entity type, as used by the registry of the server's
+        /// version.
+        pub entity_type: i32,
+
+        /// World position of the entity's feet.
+        pub position: DVec3,
+
+        pub yaw: f32,
+        pub pitch: f32,
+        pub head_yaw: f32,
+    }
+
+    /// How an entity's position changed.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum EntityPosition {
+        /// The entity moved by this offset.
+        Relative(DVec3),
+
+        /// The entity is now at this world position.
+        Absolute(DVec3),
+    }
+
+    /// Notifies the client that an entity has moved or turned.
+    ///
+    /// Fields that didn't change are `None`. Angles are as in
+    /// [`EntitySpawned`].
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityMoved {
+        pub entity_id: i32,
+        pub position: Option<EntityPosition>,
+        pub yaw: Option<f32>,
+        pub pitch: Option<f32>,
+        pub head_yaw: Option<f32>,
+    }
+
+    /// Notifies the client of an entity's velocity, in blocks per tick.
+    ///
+    /// The client is expected to keep moving the entity at this velocity
+    /// between [`EntityMoved`] events.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityVelocity {
+        pub entity_id: i32,
+        pub velocity: DVec3,
+    }
+
+    /// Notifies the client of the item an entity shows: what a dropped item
+    /// is, what's in an item frame, or what a thrown item looks like. Sent
+    /// after the entity is spawned and whenever the item changes.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityItemSet {
+        pub entity_id: i32,
+
+        /// The item, or `None` for an empty item frame.
+        pub item: Option<ItemStack>,
+    }
+
+    /// Notifies the client that an entity's custom name (from a name tag,
+    /// or given by the server) changed, or whether it is always shown did.
+    /// Only what changed is set.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityCustomName {
+        pub entity_id: i32,
+
+        /// The new custom name, or `Some(None)` if it was removed.
+        pub name: Option<Option<String>>,
+
+        /// Whether the name is shown above the entity at all times, rather
+        /// than only when the player looks at it.
+        pub visible: Option<bool>,
+    }
+
+    /// Notifies the client that entities have gone out of view or been
+    /// removed from the world.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntitiesRemoved {
+        pub entity_ids: Vec<i32>,
+    }
+
+    /// Moves the local player, e.g. when joining the world, respawning or
+    /// being teleported. The client should move the camera there.
+    ///
+    /// The backend resolves positions the server gave relative to the
+    /// player's previous one and confirms the teleport itself.
+    ///
+    /// Angles are as in [`EntitySpawned`].
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlayerTeleported {
+        /// World position of the player's feet.
+        pub position: DVec3,
+
+        pub yaw: f32,
+        pub pitch: f32,
+    }
+
+    /// Notifies the client of the entities riding a vehicle, replacing any
+    /// it had before. An empty list means nothing rides it anymore.
+    ///
+    /// The first passenger controls the vehicle, if it can be controlled.
+    ///
+    /// # See also
+    ///
+    /// * [`serverbound::SteerVehicle`]
+    /// * [`serverbound::MoveVehicle`]
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PassengersSet {
+        pub vehicle_id: i32,
+        pub passenger_ids: Vec<i32>,
+    }
+
+    /// Moves the vehicle the player is controlling back to where the server
+    /// has it, after it rejected a [`serverbound::MoveVehicle`].
+    ///
+    /// Angles are as in [`EntitySpawned`].
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VehicleMoved {
+        pub position: DVec3,
+        pub yaw: f32,
+        pub pitch: f32,
+    }
+
+    /// Notifies the client that a player has joined the player list.
+    ///
+    /// Player entities are matched to their player list entry by UUID.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlayerAdded {
+        pub uuid: uuid::Uuid,
+
+        /// The name of the player's profile.
+        pub name: String,
+    }
+
+    /// Notifies the client that the name shown for a player has changed.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlayerDisplayName {
+        pub uuid: uuid::Uuid,
+
+        /// Plain text of the display name, or `None` to show the profile name.
+        pub display_name: Option<String>,
+    }
+
+    /// Notifies the client of a player's latency as measured by the server,
+    /// from how long the player's client takes to answer keep-alives.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlayerLatency {
+        pub uuid: uuid::Uuid,
+
+        /// Latency in milliseconds.
+        pub latency_ms: i32,
+    }
+
+    /// Notifies the client that players have left the player list.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PlayersRemoved {
+        pub uuids: Vec<uuid::Uuid>,
+    }
+
+    /// Notifies the client of the player's health and hunger.
+    ///
+    /// A `health` of zero or less means the player has died and can
+    /// [`serverbound::Respawn`].
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HealthUpdated {
+        /// Health in half hearts, from `0.0` to the player's maximum (normally
+        /// `20.0`).
+        pub health: f32,
+
+        /// Food level, from `0` to `20`.
+        pub food: i32,
+
+        pub saturation: f32,
+    }
+
+    /// Notifies the client of the player's game mode, on joining and whenever
+    /// it changes.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct GameModeChanged {
+        pub game_mode: GameMode,
+    }
+
+    /// Notifies the client of the world's difficulty.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DifficultyChanged {
+        pub difficulty: Difficulty,
+
+        /// Whether the difficulty can no longer be changed.
+        pub locked: bool,
+    }
+
+    /// Notifies the client of what the player is allowed to do. Sent on
+    /// joining, and whenever the game mode or the abilities change.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct AbilitiesChanged {
+        pub abilities: Abilities,
+    }
+
+    /// Notifies the client that an entity (possibly the player) has taken
+    /// damage.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityDamaged {
+        pub entity_id: i32,
+    }
+
+    /// Notifies the client that an entity (possibly the player) has gained a
+    /// status effect, or that one of its effects has changed.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityEffectAdded {
+        pub entity_id: i32,
+
+        /// Numeric effect ID, as used by the registry of the server's version.
+        pub effect_id: i32,
+
+        /// Level of the effect minus one, e.g. `1` for Speed II.
+        pub amplifier: i32,
+
+        /// Remaining duration in ticks, or `None` if the effect lasts forever.
+        pub duration: Option<i32>,
+
+        /// Whether the effect comes from a beacon or conduit.
+        pub ambient: bool,
+        pub show_particles: bool,
+        pub show_icon: bool,
+    }
+
+    /// Notifies the client that an entity has lost a status effect.
+    #[derive(Debug, Clone, PartialEq, Message)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct EntityEffectRemoved {
+        pub entity_id: i32,
+        pub effect_id: i32,
+    }
+
     pub(crate) fn add_events(app: &mut bevy::app::App) {
         app.add_message::<LoginSuccess>();
+        app.add_message::<JoinedGame>();
         app.add_message::<Disconnect>();
+        app.add_message::<Transfer>();
+        app.add_message::<Reconfigure>();
+        app.add_message::<PingMeasured>();
+        app.add_message::<PluginMessage>();
         app.add_message::<ChunkData>();
+        app.add_message::<BlockChanged>();
+        app.add_message::<BlockChangesAcknowledged>();
+        app.add_message::<BlockBreakProgress>();
+        app.add_message::<WorldBorderUpdate>();
+        app.add_message::<MapData>();
+        app.add_message::<HotbarSlotSelected>();
+        app.add_message::<InventoryContents>();
+        app.add_message::<InventorySlot>();
+        app.add_message::<CursorItem>();
+        app.add_message::<RecipesAdded>();
+        app.add_message::<RecipesRemoved>();
+        app.add_message::<RecipeBookUnavailable>();
+        app.add_message::<ContainerOpened>();
+        app.add_message::<ContainerContents>();
+        app.add_message::<ContainerSlot>();
+        app.add_message::<ContainerProperty>();
+        app.add_message::<ContainerClosed>();
+        app.add_message::<ChatMessage>();
+        app.add_message::<EntitySpawned>();
+        app.add_message::<EntityMoved>();
+        app.add_message::<EntityVelocity>();
+        app.add_message::<EntityItemSet>();
+        app.add_message::<EntityCustomName>();
+        app.add_message::<EntitiesRemoved>();
+        app.add_message::<PlayerTeleported>();
+        app.add_message::<PassengersSet>();
+        app.add_message::<VehicleMoved>();
+        app.add_message::<PlayerAdded>();
+        app.add_message::<PlayerDisplayName>();
+        app.add_message::<PlayerLatency>();
+        app.add_message::<PlayersRemoved>();
+        app.add_message::<HealthUpdated>();
+        app.add_message::<GameModeChanged>();
+        app.add_message::<DifficultyChanged>();
+        app.add_message::<AbilitiesChanged>();
+        app.add_message::<EntityDamaged>();
+        app.add_message::<EntityEffectAdded>();
+        app.add_message::<EntityEffectRemoved>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_schema_versions_are_rejected() {
+        let event = clientbound::Reconfigure;
+
+        assert_eq!(Versioned::new(event.clone()).into_current(), Ok(event));
+        let old = Versioned {
+            schema_version: 0,
+            event: clientbound::Reconfigure,
+        };
+        assert_eq!(old.into_current(), Err(SchemaVersionMismatch { found: 0 }));
+    }
+
+    #[test]
+    fn block_faces_round_trip_through_normals() {
+        for face in [
+            BlockFace::Down,
+            BlockFace::Up,
+            BlockFace::North,
+            BlockFace::South,
+            BlockFace::West,
+            BlockFace::East,
+        ] {
+            assert_eq!(BlockFace::from_normal(face.normal()), Some(face));
+        }
+        assert_eq!(BlockFace::from_normal(IVec3::ZERO), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn events_round_trip_through_json() {
+        let mut chunk_data = brine_chunk::Chunk::empty(3, -7);
+        let mut section = brine_chunk::ChunkSection::empty(-4);
+        section.block_states.0[42] = BlockState(9);
+        section.block_count = 1;
+        chunk_data.sections.push(section);
+        let event = Versioned::new(clientbound::ChunkData { chunk_data });
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<Versioned<_>>(&json).unwrap(), event);
+
+        let event = Versioned::new(clientbound::ChatMessage {
+            text: String::from("hi"),
+            kind: clientbound::ChatKind::Player {
+                sender: Uuid::from_u128(1),
+            },
+        });
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<Versioned<_>>(&json).unwrap(), event);
     }
 }
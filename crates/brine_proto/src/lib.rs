@@ -1,6 +1,20 @@
 //! High-level client-server API definition.
 
+pub mod buffer;
+pub mod channels;
+pub mod error;
 pub mod event;
+pub mod login;
 mod plugin;
+pub mod sequence;
+pub mod sets;
+pub mod tick;
 
-pub use plugin::{AlwaysSuccessfulLoginPlugin, ProtocolPlugin};
+pub use buffer::MessageBuffer;
+pub use channels::PluginChannels;
+pub use error::{ClientError, ClientErrorKind};
+pub use login::{LoginState, LoginTransition};
+pub use plugin::{AlwaysSuccessfulLoginPlugin, MessageBufferPlugin, ProtocolPlugin};
+pub use sequence::ActionSequence;
+pub use sets::BrineSystems;
+pub use tick::TICKS_PER_SECOND;
@@ -0,0 +1,259 @@
+//! The login state machine, as a graph of states and the triggers that move
+//! between them.
+//!
+//! Backends don't set [`LoginState`] directly. They report what happened as a
+//! [`LoginTrigger`] through [`LoginTransitions`], which looks the next state
+//! up in [`LoginState::TRANSITIONS`], ignores triggers the current state has
+//! no edge for, and sends a [`LoginTransition`] for every transition it
+//! makes.
+//!
+//! The current state is the [`State<LoginState>`] resource. Code that needs
+//! to run on a transition can read [`LoginTransition`]s, or use Bevy's
+//! [`OnEnter`], [`OnExit`] and [`OnTransition`] schedules.
+//!
+//! ```text
+//! Idle --LoginRequested--> StatusAwaitingConnect --Connected--> StatusAwaitingResponse
+//!   --StatusReceived--> StatusAwaitingDisconnect --StatusClosed--> LoginAwaitingConnect
+//!   --Connected--> LoginAwaitingSuccess --LoginSucceeded--> Play
+//! Idle --LoginRequestedPinned--> LoginAwaitingConnect
+//! Play --Transferred--> Transferring --TransferConnecting--> LoginAwaitingConnect
+//! ```
+//!
+//! Failures (`ConnectFailed`, `Incompatible`, `Kicked`, `ConnectionLost`)
+//! return to `Idle` from the states they can happen in.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// Where a connection is in the login process.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, States, Default)]
+pub enum LoginState {
+    /// Not connected, waiting for a
+    /// [`Login`][crate::event::serverbound::Login].
+    #[default]
+    Idle,
+
+    /// Connecting to the server to ask for its protocol version.
+    StatusAwaitingConnect,
+
+    /// Sent the status request, waiting for the server's status.
+    StatusAwaitingResponse,
+
+    /// Waiting for the server to close the status connection.
+    StatusAwaitingDisconnect,
+
+    /// Connecting to the server to log in.
+    LoginAwaitingConnect,
+
+    /// Sent Login Start, waiting for Login Success.
+    LoginAwaitingSuccess,
+
+    /// Logged in. Configuration phases happen within this state.
+    Play,
+
+    /// Disconnected from the old server; connecting to the new one next frame
+    /// so that nothing queued for the old server gets sent to the new one.
+    Transferring,
+}
+
+/// Something that happened to a connection, which may move the
+/// [`LoginState`] along.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LoginTrigger {
+    /// A `Login` message asked to log in.
+    LoginRequested,
+
+    /// A `Login` message asked to log in, with a protocol version pinned so
+    /// that protocol discovery is skipped.
+    LoginRequestedPinned,
+
+    /// The connection to the server was established.
+    Connected,
+
+    /// The connection to the server couldn't be established.
+    ConnectFailed,
+
+    /// The server sent its status, with a protocol version the client speaks.
+    StatusReceived,
+
+    /// The server speaks a protocol version the client doesn't.
+    Incompatible,
+
+    /// The server closed the status connection.
+    StatusClosed,
+
+    /// The server accepted the login.
+    LoginSucceeded,
+
+    /// The server refused the login or kicked the client.
+    Kicked,
+
+    /// The connection was lost.
+    ConnectionLost,
+
+    /// The server sent the client to another server.
+    Transferred,
+
+    /// Started connecting to the server the client was transferred to.
+    TransferConnecting,
+}
+
+/// A transition of the [`LoginState`], and what caused it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Message)]
+pub struct LoginTransition {
+    pub from: LoginState,
+    pub to: LoginState,
+    pub trigger: LoginTrigger,
+}
+
+impl LoginState {
+    /// Every edge of the state graph, as `(from, trigger, to)`.
+    pub const TRANSITIONS: &'static [(Self, LoginTrigger, Self)] = {
+        use LoginState::*;
+        use LoginTrigger::*;
+        &[
+            (Idle, LoginRequested, StatusAwaitingConnect),
+            (Idle, LoginRequestedPinned, LoginAwaitingConnect),
+            (StatusAwaitingConnect, Connected, StatusAwaitingResponse),
+            (StatusAwaitingConnect, ConnectFailed, Idle),
+            (
+                StatusAwaitingResponse,
+                StatusReceived,
+                StatusAwaitingDisconnect,
+            ),
+            (StatusAwaitingResponse, Incompatible, Idle),
+            (StatusAwaitingDisconnect, StatusClosed, LoginAwaitingConnect),
+            (LoginAwaitingConnect, Connected, LoginAwaitingSuccess),
+            (LoginAwaitingConnect, ConnectFailed, Idle),
+            (LoginAwaitingSuccess, LoginSucceeded, Play),
+            (LoginAwaitingSuccess, Kicked, Idle),
+            (LoginAwaitingSuccess, Incompatible, Idle),
+            (Play, Kicked, Idle),
+            (Play, ConnectionLost, Idle),
+            (Play, Transferred, Transferring),
+            (Transferring, TransferConnecting, LoginAwaitingConnect),
+        ]
+    };
+
+    /// Returns the state `trigger` moves this state to, or `None` if it
+    /// doesn't apply here.
+    pub fn next(self, trigger: LoginTrigger) -> Option<Self> {
+        Self::TRANSITIONS
+            .iter()
+            .find(|&&(from, edge, _)| from == self && edge == trigger)
+            .map(|&(_, _, to)| to)
+    }
+
+    /// Returns the triggers that apply in this state.
+    pub fn triggers(self) -> impl Iterator<Item = LoginTrigger> {
+        Self::TRANSITIONS
+            .iter()
+            .filter(move |&&(from, _, _)| from == self)
+            .map(|&(_, trigger, _)| trigger)
+    }
+
+    /// Whether a login is underway: neither idle nor playing.
+    pub fn is_connecting(self) -> bool {
+        !matches!(self, Self::Idle | Self::Play)
+    }
+
+    /// A short description of the state, for showing connection progress.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Idle => "Not connected",
+            Self::StatusAwaitingConnect => "Connecting",
+            Self::StatusAwaitingResponse => "Requesting server status",
+            Self::StatusAwaitingDisconnect => "Finishing status request",
+            Self::LoginAwaitingConnect => "Connecting to log in",
+            Self::LoginAwaitingSuccess => "Logging in",
+            Self::Play => "Playing",
+            Self::Transferring => "Transferring",
+        }
+    }
+}
+
+/// Moves the [`LoginState`] along the state graph.
+///
+/// Only the first transition in a frame is taken, since the state only
+/// changes between frames; later triggers are judged against a state that is
+/// about to change, so they're ignored.
+#[derive(SystemParam)]
+pub struct LoginTransitions<'w> {
+    state: Res<'w, State<LoginState>>,
+    next_state: ResMut<'w, NextState<LoginState>>,
+    transitions: MessageWriter<'w, LoginTransition>,
+}
+
+impl LoginTransitions<'_> {
+    /// Returns the current state.
+    pub fn state(&self) -> LoginState {
+        *self.state.get()
+    }
+
+    /// Takes the transition for `trigger` from the current state, if there
+    /// is one. Returns the new state.
+    pub fn fire(&mut self, trigger: LoginTrigger) -> Option<LoginState> {
+        let from = self.state();
+
+        if let NextState::Pending(pending) = *self.next_state {
+            debug!(
+                "Ignoring {:?}: already moving from {:?} to {:?}",
+                trigger, from, pending
+            );
+            return None;
+        }
+
+        let Some(to) = from.next(trigger) else {
+            warn!("Ignoring {:?} in login state {:?}", trigger, from);
+            return None;
+        };
+
+        debug!("Login state {:?} -> {:?} ({:?})", from, to, trigger);
+        self.next_state.set(to);
+        self.transitions
+            .write(LoginTransition { from, to, trigger });
+        Some(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn login_goes_through_status_then_login_to_play() {
+        use LoginTrigger::*;
+
+        let mut state = LoginState::Idle;
+        let mut states = vec![state];
+        for trigger in [
+            LoginRequested,
+            Connected,
+            StatusReceived,
+            StatusClosed,
+            Connected,
+            LoginSucceeded,
+        ] {
+            state = state.next(trigger).unwrap();
+            states.push(state);
+        }
+
+        assert_eq!(
+            states,
+            [
+                LoginState::Idle,
+                LoginState::StatusAwaitingConnect,
+                LoginState::StatusAwaitingResponse,
+                LoginState::StatusAwaitingDisconnect,
+                LoginState::LoginAwaitingConnect,
+                LoginState::LoginAwaitingSuccess,
+                LoginState::Play,
+            ]
+        );
+
+        assert_eq!(LoginState::Play.next(Connected), None);
+        assert_eq!(
+            LoginState::Play.next(Transferred),
+            Some(LoginState::Transferring)
+        );
+    }
+}
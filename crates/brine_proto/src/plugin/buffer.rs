@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    diagnostic::{Diagnostic, Diagnostics, RegisterDiagnostic},
+    ecs::message::Message,
+    prelude::*,
+};
+
+use crate::{
+    buffer::{MessageBuffer, MessageBufferDiagnostics},
+    BrineSystems,
+};
+
+/// Plugin that keeps messages of type `T` in a [`MessageBuffer`] until they
+/// are consumed, rather than letting them expire after two frames.
+///
+/// Every frame, after [`BrineSystems::ProtocolDecode`] and before
+/// [`BrineSystems::WorldUpdate`], the plugin copies the messages it hasn't
+/// seen yet into the buffer. It reads them with its own cursor and leaves
+/// [`Messages<T>`] alone, so regular readers still see every message. See the
+/// [`buffer`][crate::buffer] module.
+///
+/// # Events
+///
+/// The plugin does not register any events.
+///
+/// The plugin reads the following events:
+///
+/// * `T`
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`MessageBuffer<T>`]
+/// * [`MessageBufferDiagnostics<T>`], whose paths are registered as
+///   diagnostics
+///
+/// The plugin expects [`Messages<T>`] to exist.
+pub struct MessageBufferPlugin<T: Message> {
+    name: &'static str,
+    capacity: Option<usize>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Message> MessageBufferPlugin<T> {
+    /// Creates a plugin for an unbounded buffer, publishing diagnostics under
+    /// `name`.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            capacity: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Bounds the buffer to `capacity` messages, past which the oldest are
+    /// dropped.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+}
+
+impl<T: Message + Clone> Plugin for MessageBufferPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let diagnostics = MessageBufferDiagnostics::<T>::new(self.name);
+
+        app.register_diagnostic(Diagnostic::new(diagnostics.pending.clone()))
+            .register_diagnostic(Diagnostic::new(diagnostics.dropped.clone()))
+            .insert_resource(MessageBuffer::<T>::new(self.capacity))
+            .insert_resource(diagnostics)
+            .add_systems(
+                Update,
+                (fill_buffer::<T>, measure_buffer::<T>)
+                    .chain()
+                    .after(BrineSystems::ProtocolDecode)
+                    .before(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn fill_buffer<T: Message + Clone>(
+    mut messages: MessageReader<T>,
+    mut buffer: ResMut<MessageBuffer<T>>,
+    paths: Res<MessageBufferDiagnostics<T>>,
+) {
+    let mut dropped = 0;
+    for message in messages.read() {
+        if buffer.push(message.clone()) {
+            dropped += 1;
+        }
+    }
+
+    if dropped > 0 {
+        warn!(
+            "The {} buffer is full; dropped {} messages ({} total)",
+            paths.name(),
+            dropped,
+            buffer.dropped()
+        );
+    }
+}
+
+fn measure_buffer<T: Message>(
+    buffer: Res<MessageBuffer<T>>,
+    paths: Res<MessageBufferDiagnostics<T>>,
+    mut diagnostics: Diagnostics,
+) {
+    diagnostics.add_measurement(&paths.pending, || buffer.len() as f64);
+    diagnostics.add_measurement(&paths.dropped, || buffer.dropped() as f64);
+}
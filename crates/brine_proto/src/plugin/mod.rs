@@ -1,7 +1,9 @@
 //! Plugins exported from this crate.
 
+mod buffer;
 mod protocol;
 mod successful_login;
 
+pub use buffer::MessageBufferPlugin;
 pub use protocol::ProtocolPlugin;
 pub use successful_login::AlwaysSuccessfulLoginPlugin;
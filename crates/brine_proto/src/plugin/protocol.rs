@@ -1,6 +1,11 @@
-use bevy::app::{App, Plugin};
+use bevy::{
+    app::{App, Plugin},
+    time::{Fixed, Time},
+};
 
-use crate::event;
+use crate::{
+    event, sets, ActionSequence, ClientError, LoginTransition, PluginChannels, TICKS_PER_SECOND,
+};
 
 /// Protocol "front-end" plugin.
 ///
@@ -13,6 +18,8 @@ use crate::event;
 ///
 /// * [`event::clientbound::*`][event::clientbound]
 /// * [`event::serverbound::*`][event::serverbound]
+/// * [`ClientError`]
+/// * [`LoginTransition`]
 ///
 /// The plugin does not react to any events.
 ///
@@ -20,14 +27,35 @@ use crate::event;
 ///
 /// # Resources
 ///
-/// The plugin registers no resources.
+/// The plugin registers the following resources:
+///
+/// * [`PluginChannels`]
+/// * [`ActionSequence`]
 ///
 /// The plugin expects no resources to exist.
+///
+/// # Ordering
+///
+/// The plugin orders the [`BrineSystems`][crate::BrineSystems] sets within
+/// `Update`. See the [`sets`][crate::sets] module.
+///
+/// # Ticks
+///
+/// The plugin sets the [`Time<Fixed>`] timestep to [`TICKS_PER_SECOND`], so
+/// `FixedUpdate` runs once per game tick. See the [`tick`][crate::tick]
+/// module.
 pub struct ProtocolPlugin;
 
 impl Plugin for ProtocolPlugin {
     fn build(&self, app: &mut App) {
         event::serverbound::add_events(app);
         event::clientbound::add_events(app);
+        app.add_message::<ClientError>();
+        app.add_message::<LoginTransition>();
+        sets::configure_sets(app);
+
+        app.init_resource::<PluginChannels>()
+            .init_resource::<ActionSequence>()
+            .insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND));
     }
 }
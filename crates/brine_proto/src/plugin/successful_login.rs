@@ -1,6 +1,9 @@
 use bevy::{ecs::schedule::IntoScheduleConfigs, prelude::*};
 
-use crate::event::{clientbound::LoginSuccess, serverbound::Login, Uuid};
+use crate::{
+    event::{clientbound::LoginSuccess, serverbound::Login, Uuid},
+    BrineSystems,
+};
 
 /// A plugin that responds immediately with success to the first login request.
 ///
@@ -26,7 +29,12 @@ pub struct AlwaysSuccessfulLoginPlugin;
 impl Plugin for AlwaysSuccessfulLoginPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<ServerState>();
-        app.add_systems(Update, handle_login.run_if(in_state(ServerState::Login)));
+        app.add_systems(
+            Update,
+            handle_login
+                .run_if(in_state(ServerState::Login))
+                .in_set(BrineSystems::ProtocolDecode),
+        );
     }
 }
 
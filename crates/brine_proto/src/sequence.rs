@@ -0,0 +1,23 @@
+//! Sequence numbers for actions that may change blocks.
+
+use bevy_ecs::prelude::Resource;
+
+/// Source of the sequence numbers attached to actions that may change blocks
+/// (digging, placing, using items).
+///
+/// The server acknowledges each sequence number once it has handled the
+/// action (see
+/// [`BlockChangesAcknowledged`][crate::event::clientbound::BlockChangesAcknowledged]),
+/// after having sent any block changes the action caused. The client and the
+/// backend draw from the same counter so that the client can tell which of
+/// its predicted block changes an acknowledgement covers.
+#[derive(Resource, Debug, Default)]
+pub struct ActionSequence(i32);
+
+impl ActionSequence {
+    /// Returns the sequence number for the next action.
+    pub fn next(&mut self) -> i32 {
+        self.0 = self.0.wrapping_add(1);
+        self.0
+    }
+}
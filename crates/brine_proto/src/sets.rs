@@ -0,0 +1,52 @@
+//! System sets that order brine's systems within a frame.
+//!
+//! A frame moves data through the client in one direction:
+//!
+//! 1. `PreUpdate`: `brine_net` hands received packets to the app
+//!    (`brine_net::NetworkSystems::Receive`).
+//! 2. `Update`, in the order of [`BrineSystems`]:
+//!    1. [`ProtocolDecode`](BrineSystems::ProtocolDecode): the backend turns
+//!       packets into [`clientbound`][crate::event::clientbound] events.
+//!    2. [`WorldUpdate`](BrineSystems::WorldUpdate): the client applies those
+//!       events to its world (entities, players, health, ...).
+//!    3. [`ProtocolEncode`](BrineSystems::ProtocolEncode): the backend turns
+//!       [`serverbound`][crate::event::serverbound] events into packets.
+//!    4. [`MeshBuild`](BrineSystems::MeshBuild): chunk meshes are built and
+//!       added to the world.
+//!    5. [`RenderPrep`](BrineSystems::RenderPrep): textures, models and other
+//!       visuals are brought up to date.
+//! 3. `PostUpdate`: `brine_net` sends the packets written this frame
+//!    (`brine_net::NetworkSystems::Send`).
+//!
+//! Following this order, a packet's effects show up in the frame it arrives,
+//! and a reply leaves in the frame it is written. Systems outside these sets
+//! run in no particular order relative to them.
+
+use bevy::prelude::*;
+
+/// Stages of the client's `Update` schedule, which
+/// [`ProtocolPlugin`][crate::ProtocolPlugin] runs in declaration order.
+///
+/// See the [module documentation][self].
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BrineSystems {
+    ProtocolDecode,
+    WorldUpdate,
+    ProtocolEncode,
+    MeshBuild,
+    RenderPrep,
+}
+
+pub(crate) fn configure_sets(app: &mut App) {
+    app.configure_sets(
+        Update,
+        (
+            BrineSystems::ProtocolDecode,
+            BrineSystems::WorldUpdate,
+            BrineSystems::ProtocolEncode,
+            BrineSystems::MeshBuild,
+            BrineSystems::RenderPrep,
+        )
+            .chain(),
+    );
+}
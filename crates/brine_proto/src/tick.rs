@@ -0,0 +1,14 @@
+//! The game tick.
+//!
+//! Minecraft's game logic runs at a fixed rate of [`TICKS_PER_SECOND`],
+//! independent of the frame rate. [`ProtocolPlugin`][crate::ProtocolPlugin]
+//! sets Bevy's fixed timestep to match, so systems that implement per-tick
+//! logic (sending movement or tick-end packets, counting down durations given
+//! in ticks, physics) belong in [`FixedUpdate`][bevy::app::FixedUpdate],
+//! which runs exactly once per tick however fast frames are rendered.
+//!
+//! Reading incoming events stays in `Update`: messages only live for two
+//! frames, and several frames can pass between two ticks.
+
+/// Rate at which game ticks happen, in ticks per second.
+pub const TICKS_PER_SECOND: f64 = 20.0;
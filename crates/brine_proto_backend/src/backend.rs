@@ -0,0 +1,121 @@
+//! The interface between [`ProtocolBackendPlugin`] and a protocol
+//! implementation.
+//!
+//! A backend translates between the wire protocol and the high-level
+//! [`brine_proto`] events. Downstream crates only ever see those events (and
+//! [`ProtocolCodec`] for connection stats), so a backend can be swapped out by
+//! changing the cargo feature that selects [`ActiveBackend`].
+//!
+//! [`ProtocolBackendPlugin`]: crate::ProtocolBackendPlugin
+//! [`ProtocolCodec`]: crate::ProtocolCodec
+
+use std::{any::Any, time::Duration};
+
+use bevy::app::App;
+
+use brine_net::{Decode, Encode};
+
+use crate::{
+    chunks::{ChunkData, ChunkExtras},
+    status::{ServerStatus, StatusError},
+};
+
+/// A protocol implementation.
+///
+/// Each adapter registers the systems for one area of the protocol. Adapters
+/// for areas with no [`brine_proto`] events yet default to doing nothing.
+pub trait ProtocolBackend: Send + Sync + 'static {
+    /// The wire codec run by [`NetworkPlugin`][brine_net::NetworkPlugin].
+    type Codec: Decode + Encode + Default + Clone + Unpin + Any + Send + Sync;
+
+    /// Name of the backend, for logs.
+    const NAME: &'static str;
+
+    /// Asks the server at `server_addr` (`host:port`) for its status over a
+    /// connection of its own, waiting at most `timeout` for each step. See
+    /// [`status`][crate::status].
+    fn query_status(server_addr: &str, timeout: Duration) -> Result<ServerStatus, StatusError>;
+
+    /// Returns the undecoded chunk data of a chunk packet from the overworld.
+    /// See [`chunks`][crate::chunks].
+    fn chunk_data(packet: &<Self::Codec as Decode>::Item) -> Option<ChunkData<&[u8]>>;
+
+    /// Returns the block entities and light of a chunk packet, or the light
+    /// of a light update.
+    fn chunk_extras(packet: &<Self::Codec as Decode>::Item) -> Option<ChunkExtras>;
+
+    /// Handles [`Login`][brine_proto::event::serverbound::Login] and everything
+    /// needed to get into and stay in the play state, sending
+    /// [`LoginSuccess`][brine_proto::event::clientbound::LoginSuccess],
+    /// [`PlayerTeleported`][brine_proto::event::clientbound::PlayerTeleported],
+    /// [`PingMeasured`][brine_proto::event::clientbound::PingMeasured]
+    /// and [`Disconnect`][brine_proto::event::clientbound::Disconnect], and
+    /// handles [`MovePlayer`][brine_proto::event::serverbound::MovePlayer].
+    fn build_login(app: &mut App);
+
+    /// Sends [`ChunkData`][brine_proto::event::clientbound::ChunkData].
+    fn build_chunks(app: &mut App);
+
+    /// Sends world events such as
+    /// [`BlockChanged`][brine_proto::event::clientbound::BlockChanged],
+    /// [`BlockChangesAcknowledged`][brine_proto::event::clientbound::BlockChangesAcknowledged],
+    /// [`BlockBreakProgress`][brine_proto::event::clientbound::BlockBreakProgress]
+    /// and [`MapData`][brine_proto::event::clientbound::MapData], and handles
+    /// [`DigBlock`][brine_proto::event::serverbound::DigBlock] and
+    /// [`PlaceBlock`][brine_proto::event::serverbound::PlaceBlock].
+    fn build_world(_app: &mut App) {}
+
+    /// Sends entity, passenger, player list (including latency), health, game
+    /// mode and ability events, and handles
+    /// [`Respawn`][brine_proto::event::serverbound::Respawn],
+    /// [`SetFlying`][brine_proto::event::serverbound::SetFlying],
+    /// [`SetSneaking`][brine_proto::event::serverbound::SetSneaking],
+    /// [`SetSprinting`][brine_proto::event::serverbound::SetSprinting],
+    /// [`StartGliding`][brine_proto::event::serverbound::StartGliding],
+    /// [`SteerVehicle`][brine_proto::event::serverbound::SteerVehicle] and
+    /// [`MoveVehicle`][brine_proto::event::serverbound::MoveVehicle].
+    fn build_entities(_app: &mut App) {}
+
+    /// Sends and receives chat messages.
+    fn build_chat(_app: &mut App) {}
+
+    /// Sends the player's inventory, container windows, the cursor item and
+    /// the hotbar selection, and handles
+    /// [`SelectHotbarSlot`][brine_proto::event::serverbound::SelectHotbarSlot],
+    /// [`UseItem`][brine_proto::event::serverbound::UseItem],
+    /// [`ReleaseUseItem`][brine_proto::event::serverbound::ReleaseUseItem],
+    /// [`SwapHands`][brine_proto::event::serverbound::SwapHands],
+    /// [`ClickContainer`][brine_proto::event::serverbound::ClickContainer] and
+    /// [`CloseContainer`][brine_proto::event::serverbound::CloseContainer].
+    /// Also sends the recipe book as
+    /// [`RecipesAdded`][brine_proto::event::clientbound::RecipesAdded] and
+    /// [`RecipesRemoved`][brine_proto::event::clientbound::RecipesRemoved], and
+    /// handles [`PlaceRecipe`][brine_proto::event::serverbound::PlaceRecipe].
+    fn build_inventory(_app: &mut App) {}
+
+    /// Sends [`serverbound::PluginMessage`]s and sends
+    /// [`clientbound::PluginMessage`]s for channels registered in
+    /// [`PluginChannels`][brine_proto::PluginChannels].
+    ///
+    /// [`serverbound::PluginMessage`]: brine_proto::event::serverbound::PluginMessage
+    /// [`clientbound::PluginMessage`]: brine_proto::event::clientbound::PluginMessage
+    fn build_plugin_messages(_app: &mut App) {}
+
+    /// Registers all of the adapters.
+    fn build(app: &mut App) {
+        Self::build_login(app);
+        Self::build_chunks(app);
+        Self::build_world(app);
+        Self::build_entities(app);
+        Self::build_chat(app);
+        Self::build_inventory(app);
+        Self::build_plugin_messages(app);
+    }
+}
+
+/// The backend selected by cargo features.
+#[cfg(feature = "stevenarella")]
+pub type ActiveBackend = crate::backend_stevenarella::StevenarellaBackend;
+
+#[cfg(not(feature = "stevenarella"))]
+compile_error!("brine_proto_backend needs a backend; enable the `stevenarella` feature");
@@ -0,0 +1,149 @@
+//! Translation of the player's sneaking, sprinting and gliding into packets.
+//!
+//! These go out as Entity Action packets, which carry the player's own entity
+//! ID. Since 1.21.2 sneaking is no longer an action but the sneak flag of the
+//! Player Input packet, and the remaining actions are numbered differently;
+//! both changes came with Player Input, so they follow the same shim.
+
+use bevy::prelude::*;
+use steven_protocol::protocol::VarInt;
+
+use brine_net::{CodecWriter, NetworkResource};
+use brine_proto::{
+    event::serverbound::{SetSneaking, SetSprinting, StartGliding},
+    BrineSystems,
+};
+
+use crate::{codec::MinecraftProtocolState, version::shims_for};
+
+use super::{
+    codec::{packet, Packet, ProtocolCodec},
+    dispatch::{packet_kind, PacketHandlerAppExt},
+    vehicles::INPUT_SNEAK,
+};
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<LocalEntityId>()
+        .add_packet_handler([packet_kind!(PlayClientboundLogin)], remember_entity_id)
+        .add_systems(
+            Update,
+            send_entity_actions.in_set(BrineSystems::ProtocolEncode),
+        );
+}
+
+/// The local player's entity ID, from the last Login (play) packet.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct LocalEntityId(i32);
+
+/// The actions of the Entity Action packet that the client sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntityAction {
+    StartSneaking,
+    StopSneaking,
+    StartSprinting,
+    StopSprinting,
+    StartGliding,
+}
+
+impl EntityAction {
+    /// Returns the action's ID, or `None` for sneaking once it moved to the
+    /// Player Input packet (1.21.2+).
+    fn id(self, player_input: bool) -> Option<i32> {
+        let id = match (self, player_input) {
+            (Self::StartSneaking | Self::StopSneaking, true) => return None,
+            (Self::StartSneaking, false) => 0,
+            (Self::StopSneaking, false) => 1,
+            (Self::StartSprinting, false) => 3,
+            (Self::StopSprinting, false) => 4,
+            (Self::StartGliding, false) => 8,
+            (Self::StartSprinting, true) => 1,
+            (Self::StopSprinting, true) => 2,
+            (Self::StartGliding, true) => 6,
+        };
+        Some(id)
+    }
+}
+
+/// Handler that keeps the local player's entity ID for the Entity Action
+/// packets.
+fn remember_entity_id(InRef(packet): InRef<Packet>, mut entity_id: ResMut<LocalEntityId>) {
+    if let Packet::Known(packet::Packet::PlayClientboundLogin(login)) = packet {
+        entity_id.0 = login.entityId;
+    }
+}
+
+/// System that tells the server when the player starts or stops sneaking or
+/// sprinting, and when they start gliding.
+fn send_entity_actions(
+    mut sneaking_events: MessageReader<SetSneaking>,
+    mut sprinting_events: MessageReader<SetSprinting>,
+    mut gliding_events: MessageReader<StartGliding>,
+    entity_id: Res<LocalEntityId>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    // Only the latest state matters.
+    let sneaking = sneaking_events.read().last().map(|event| event.sneaking);
+    let sprinting = sprinting_events.read().last().map(|event| event.sprinting);
+    let gliding = gliding_events.read().count() > 0;
+
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        return;
+    }
+    let player_input = shims_for(net_resource.codec().protocol_version()).player_input;
+
+    let actions = [
+        sneaking.map(|sneaking| {
+            if sneaking {
+                EntityAction::StartSneaking
+            } else {
+                EntityAction::StopSneaking
+            }
+        }),
+        sprinting.map(|sprinting| {
+            if sprinting {
+                EntityAction::StartSprinting
+            } else {
+                EntityAction::StopSprinting
+            }
+        }),
+        gliding.then_some(EntityAction::StartGliding),
+    ];
+
+    if let (Some(sneaking), true) = (sneaking, player_input) {
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundPlayerInput(
+            Box::new(packet::play::serverbound::PlayerInput {
+                inputs: if sneaking { INPUT_SNEAK } else { 0 },
+            }),
+        )));
+    }
+
+    for action in actions.into_iter().flatten() {
+        let Some(action_id) = action.id(player_input) else {
+            continue;
+        };
+        debug!("Entity action: {:?}", action);
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundEntityAction(
+            Box::new(packet::play::serverbound::EntityAction {
+                entityId: VarInt(entity_id.0),
+                actionId: VarInt(action_id),
+                jumpBoost: VarInt(0),
+            }),
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sneaking_moves_to_player_input() {
+        assert_eq!(EntityAction::StartSneaking.id(false), Some(0));
+        assert_eq!(EntityAction::StartSneaking.id(true), None);
+        assert_eq!(EntityAction::StopSprinting.id(false), Some(4));
+        assert_eq!(EntityAction::StopSprinting.id(true), Some(2));
+        assert_eq!(EntityAction::StartGliding.id(false), Some(8));
+        assert_eq!(EntityAction::StartGliding.id(true), Some(6));
+    }
+}
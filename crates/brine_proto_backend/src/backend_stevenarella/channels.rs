@@ -0,0 +1,155 @@
+//! Translation between custom payload packets and [`PluginMessage`] events.
+//!
+//! [`PluginMessage`]: brine_proto::event::clientbound::PluginMessage
+
+use bevy::prelude::*;
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{event, BrineSystems, PluginChannels};
+
+use crate::codec::MinecraftProtocolState;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+/// Channels used to tell the server which channels the client listens on.
+/// Payloads are the channel names separated by NUL bytes.
+const REGISTER_CHANNEL: &str = "minecraft:register";
+const UNREGISTER_CHANNEL: &str = "minecraft:unregister";
+
+/// Channels announced to the server on the current connection.
+#[derive(Resource, Default)]
+struct AnnouncedChannels(Vec<String>);
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<AnnouncedChannels>();
+    app.add_systems(
+        Update,
+        (
+            receive_plugin_messages.in_set(BrineSystems::ProtocolDecode),
+            (announce_channels, send_plugin_messages)
+                .chain()
+                .in_set(BrineSystems::ProtocolEncode),
+        ),
+    );
+}
+
+/// System that forwards custom payloads on registered channels.
+fn receive_plugin_messages(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut plugin_message_events: MessageWriter<event::clientbound::PluginMessage>,
+    channels: Res<PluginChannels>,
+) {
+    for packet in packet_reader.iter() {
+        let (channel, data) = match packet {
+            Packet::Known(packet::Packet::ConfigurationClientboundCustomPayload(payload)) => {
+                (&payload.channel, &payload.data)
+            }
+            Packet::Known(packet::Packet::PlayClientboundCustomPayload(payload)) => {
+                (&payload.channel, &payload.data)
+            }
+            _ => continue,
+        };
+
+        if channels.is_registered(channel) {
+            plugin_message_events.write(event::clientbound::PluginMessage {
+                channel: channel.clone(),
+                data: data.clone(),
+            });
+        } else {
+            trace!(
+                "Ignoring plugin message on unregistered channel {}",
+                channel
+            );
+        }
+    }
+}
+
+/// System that keeps the server informed of the registered channels.
+///
+/// Everything is (re-)announced when play starts, since that's either a new
+/// connection or the end of a reconfiguration.
+fn announce_channels(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut plugin_message_events: MessageWriter<event::serverbound::PluginMessage>,
+    mut announced: ResMut<AnnouncedChannels>,
+    channels: Res<PluginChannels>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    let joined = packet_reader.iter().any(|packet| {
+        matches!(
+            packet,
+            Packet::Known(packet::Packet::PlayClientboundLogin(_))
+        )
+    });
+    if joined {
+        announced.0.clear();
+    } else if !channels.is_changed()
+        || net_resource.codec().protocol_state() != MinecraftProtocolState::Play
+    {
+        return;
+    }
+
+    let added: Vec<_> = channels
+        .iter()
+        .filter(|channel| !announced.0.iter().any(|a| a == channel))
+        .map(String::from)
+        .collect();
+    let removed: Vec<_> = announced
+        .0
+        .iter()
+        .filter(|channel| !channels.is_registered(channel))
+        .cloned()
+        .collect();
+
+    if !added.is_empty() {
+        debug!("Registering plugin channels: {}", added.join(", "));
+        plugin_message_events.write(event::serverbound::PluginMessage {
+            channel: REGISTER_CHANNEL.to_string(),
+            data: added.join("\0").into_bytes(),
+        });
+    }
+    if !removed.is_empty() {
+        debug!("Unregistering plugin channels: {}", removed.join(", "));
+        plugin_message_events.write(event::serverbound::PluginMessage {
+            channel: UNREGISTER_CHANNEL.to_string(),
+            data: removed.join("\0").into_bytes(),
+        });
+    }
+
+    announced.0 = channels.iter().map(String::from).collect();
+}
+
+/// System that sends plugin messages as custom payloads for the current phase.
+fn send_plugin_messages(
+    mut plugin_message_events: MessageReader<event::serverbound::PluginMessage>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    let protocol_state = net_resource.codec().protocol_state();
+
+    for message in plugin_message_events.read() {
+        let channel = message.channel.clone();
+        let data = message.data.clone();
+
+        let payload = match protocol_state {
+            MinecraftProtocolState::Configuration => {
+                packet::Packet::ConfigurationServerboundCustomPayload(Box::new(
+                    packet::configuration::serverbound::CustomPayload { channel, data },
+                ))
+            }
+            MinecraftProtocolState::Play => packet::Packet::PlayServerboundCustomPayload(Box::new(
+                packet::play::serverbound::CustomPayload { channel, data },
+            )),
+            state => {
+                warn!(
+                    "Dropping plugin message on {} sent during {:?}",
+                    message.channel, state
+                );
+                continue;
+            }
+        };
+
+        trace!("Sending plugin message on {}", message.channel);
+        packet_writer.send(Packet::Known(payload));
+    }
+}
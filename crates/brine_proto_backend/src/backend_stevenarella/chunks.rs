@@ -1,43 +1,49 @@
 use bevy::prelude::*;
 use byteorder::{BigEndian, ReadBytesExt};
-use std::io::Cursor;
+use std::io::{self, Cursor};
 
 use brine_chunk::{
     decode::{Result, VarIntRead},
     palette::SectionPalette,
-    BlockState, Chunk, Palette, SECTIONS_PER_CHUNK,
+    Chunk, CHUNK_MIN_Y, SECTIONS_PER_CHUNK, SECTION_HEIGHT,
 };
+use brine_data::{DimensionType, ServerRegistries};
 use brine_net::CodecReader;
-use brine_proto::event;
+use brine_proto::{event, BrineSystems, ClientError, ClientErrorKind};
 
-use super::codec::{packet, Packet, ProtocolCodec};
+use crate::chunks::{ChunkData, ChunkExtras};
 
-/// A dummy palette for testing that performs no translation.
-pub struct DummyPalette;
-
-impl Palette for DummyPalette {
-    fn id_to_block_state(&self, id: u32) -> Option<brine_chunk::BlockState> {
-        Some(BlockState(id))
-    }
-}
+use super::{
+    codec::{packet, Error, Packet, ProtocolCodec, Serializable},
+    players::{
+        invalid_data, read_nbt_length, read_nbt_string, skip_nbt, TAG_COMPOUND, TAG_END,
+        TAG_LONG_ARRAY,
+    },
+    registries::CurrentDimension,
+};
 
-/// Common representation of the different versions of ChunkData packets.
-pub struct ChunkData<T> {
-    pub chunk_x: i32,
-    pub chunk_z: i32,
-    pub full_chunk: bool,
-    pub bitmask: u32,
-    pub data: T,
-}
+/// Name of the heightmap the client keeps. See [`brine_chunk::heightmap`].
+const MOTION_BLOCKING: &str = "MOTION_BLOCKING";
 
 impl<'d> ChunkData<&'d [u8]> {
-    pub fn from_packet(packet: &'d Packet) -> Option<Self> {
+    /// Returns the chunk data of a chunk packet from the overworld.
+    pub(crate) fn from_packet(packet: &'d Packet) -> Option<Self> {
+        Self::from_packet_in(packet, &DimensionType::OVERWORLD)
+    }
+
+    /// Returns the chunk data of a chunk packet from a dimension of type
+    /// `dimension`, whose sections start at its `min_y`.
+    pub(crate) fn from_packet_in(packet: &'d Packet, dimension: &DimensionType) -> Option<Self> {
         match packet {
             Packet::Known(packet::Packet::PlayClientboundMapChunk(map_chunk)) => {
                 let chunk_bytes = map_chunk.chunkData.data.as_slice();
 
-                let bitmask = match compute_section_bitmask(chunk_bytes) {
-                    Ok(mask) => mask,
+                let first_section = first_section(dimension).unwrap_or(0);
+                let max_sections = dimension
+                    .section_count()
+                    .min(SECTIONS_PER_CHUNK - first_section as usize);
+                let bitmask = match compute_section_bitmask(chunk_bytes, max_sections) {
+                    Ok(mask) => mask << first_section,
                     Err(err) => {
                         warn!("Failed to parse chunk data bitmask: {}", err);
                         return None;
@@ -52,12 +58,23 @@ impl<'d> ChunkData<&'d [u8]> {
                     bitmask.count_ones()
                 );
 
+                let mut heightmaps = Vec::new();
+                let heightmap = match map_chunk.heightmaps.write_to(&mut heightmaps) {
+                    Ok(()) => read_motion_blocking(&heightmaps).map_err(|err| err.to_string()),
+                    Err(err) => Err(format!("{:?}", err)),
+                };
+                let heightmap = heightmap.unwrap_or_else(|err| {
+                    warn!("Failed to read chunk heightmaps: {}", err);
+                    None
+                });
+
                 Some(Self {
                     chunk_x: map_chunk.x,
                     chunk_z: map_chunk.z,
                     full_chunk: true,
                     bitmask,
                     data: chunk_bytes,
+                    heightmap,
                 })
             }
             _ => None,
@@ -65,18 +82,104 @@ impl<'d> ChunkData<&'d [u8]> {
     }
 }
 
-impl<T: AsRef<[u8]>> ChunkData<T> {
-    pub fn decode(&self) -> Result<Chunk> {
-        let mut buf = self.data.as_ref();
-        Chunk::decode(
-            self.chunk_x,
-            self.chunk_z,
-            self.full_chunk,
-            self.bitmask,
-            &DummyPalette,
-            &mut buf,
-        )
+/// Encodes the light data fields, which chunk packets and light updates
+/// share.
+macro_rules! encode_light_data {
+    ($packet:expr) => {{
+        let mut light = Vec::new();
+        $packet
+            .skyLightMask
+            .write_to(&mut light)
+            .and_then(|()| $packet.blockLightMask.write_to(&mut light))
+            .and_then(|()| $packet.emptySkyLightMask.write_to(&mut light))
+            .and_then(|()| $packet.emptyBlockLightMask.write_to(&mut light))
+            .and_then(|()| $packet.skyLight.write_to(&mut light))
+            .and_then(|()| $packet.blockLight.write_to(&mut light))
+            .map(|()| light)
+    }};
+}
+
+impl ChunkExtras {
+    /// Returns the block entities and light of a chunk packet, or the light
+    /// of a light update.
+    pub(crate) fn from_packet(packet: &Packet) -> Option<Self> {
+        let extras = match packet {
+            Packet::Known(packet::Packet::PlayClientboundMapChunk(map_chunk)) => {
+                let mut block_entities = Vec::new();
+                map_chunk
+                    .blockEntities
+                    .write_to(&mut block_entities)
+                    .and_then(|()| encode_light_data!(map_chunk))
+                    .map(|light| Self {
+                        chunk_x: map_chunk.x,
+                        chunk_z: map_chunk.z,
+                        block_entities: Some(block_entities),
+                        light,
+                    })
+            }
+            Packet::Known(packet::Packet::PlayClientboundUpdateLight(update_light)) => {
+                encode_light_data!(update_light).map(|light| Self {
+                    chunk_x: update_light.chunkX.0,
+                    chunk_z: update_light.chunkZ.0,
+                    block_entities: None,
+                    light,
+                })
+            }
+            _ => return None,
+        };
+
+        extras
+            .map_err(|err: Error| {
+                warn!(
+                    "Failed to re-encode chunk light or block entities: {:?}",
+                    err
+                )
+            })
+            .ok()
+    }
+}
+
+/// Returns the packed `MOTION_BLOCKING` heightmap from the network NBT
+/// compound of heightmaps a chunk comes with, if it has one.
+fn read_motion_blocking(heightmaps: &[u8]) -> io::Result<Option<Vec<i64>>> {
+    let mut reader = Cursor::new(heightmaps);
+    match reader.read_u8()? {
+        TAG_COMPOUND => {}
+        TAG_END => return Ok(None),
+        _ => return Err(invalid_data("heightmaps aren't a compound")),
+    }
+
+    let mut motion_blocking = None;
+    loop {
+        let tag = reader.read_u8()?;
+        if tag == TAG_END {
+            break;
+        }
+        let name = read_nbt_string(&mut reader)?;
+        if name == MOTION_BLOCKING && tag == TAG_LONG_ARRAY {
+            let length = read_nbt_length(&mut reader)?;
+            let longs = (0..length)
+                .map(|_| reader.read_i64::<BigEndian>())
+                .collect::<io::Result<_>>()?;
+            motion_blocking = Some(longs);
+        } else {
+            skip_nbt(&mut reader, tag)?;
+        }
     }
+    Ok(motion_blocking)
+}
+
+/// Returns which of the client's chunk sections holds the lowest section of
+/// a chunk in `dimension`, or `None` if the dimension reaches outside the
+/// client's (overworld-sized) chunks, in which case its sections are placed
+/// from the bottom.
+pub(crate) fn first_section(dimension: &DimensionType) -> Option<u32> {
+    let offset = dimension.min_y - i32::from(CHUNK_MIN_Y);
+    let first = offset.div_euclid(SECTION_HEIGHT as i32);
+    let fits = offset >= 0
+        && offset % SECTION_HEIGHT as i32 == 0
+        && first as usize + dimension.section_count() <= SECTIONS_PER_CHUNK;
+    fits.then_some(first as u32)
 }
 
 pub fn get_chunk_from_packet(packet: &Packet) -> Result<Option<Chunk>> {
@@ -88,28 +191,66 @@ pub fn get_chunk_from_packet(packet: &Packet) -> Result<Option<Chunk>> {
 }
 
 pub(crate) fn build(app: &mut App) {
-    app.add_systems(Update, handle_chunk_data);
+    app.init_resource::<ServerRegistries>()
+        .init_resource::<CurrentDimension>()
+        .add_systems(
+            Update,
+            handle_chunk_data.in_set(BrineSystems::ProtocolDecode),
+        );
 }
 
 /// System that listens for ChunkData packets and sends ChunkData events to the
 /// client application.
 fn handle_chunk_data(
     mut packet_reader: CodecReader<ProtocolCodec>,
+    registries: Res<ServerRegistries>,
+    mut dimension: ResMut<CurrentDimension>,
     mut chunk_events: MessageWriter<event::clientbound::ChunkData>,
+    mut client_errors: MessageWriter<ClientError>,
 ) {
     for packet in packet_reader.iter() {
-        match get_chunk_from_packet(packet) {
-            Ok(Some(chunk_data)) => {
+        // Done here rather than in a handler of its own so that chunks after
+        // a Respawn in the same frame get the new dimension's shape.
+        if dimension.update(packet, &registries) {
+            if first_section(&dimension.0).is_none() {
+                warn!(
+                    "Dimension {:?} doesn't fit in the client's chunks; placing its sections from the bottom",
+                    dimension.0
+                );
+            }
+            continue;
+        }
+
+        let Some(chunk_data) = ChunkData::from_packet_in(packet, &dimension.0) else {
+            continue;
+        };
+
+        match chunk_data.decode() {
+            Ok(chunk_data) => {
                 trace!("Chunk: {:?}", chunk_data);
                 chunk_events.write(event::clientbound::ChunkData { chunk_data });
             }
-            Err(e) => error!("{}", e),
-            _ => {}
+            Err(err) => {
+                let message = format!(
+                    "Failed to decode chunk ({}, {}): {}",
+                    chunk_data.chunk_x, chunk_data.chunk_z, err
+                );
+                error!("{}", message);
+                client_errors.write(ClientError::new(
+                    ClientErrorKind::Chunk {
+                        chunk_x: chunk_data.chunk_x,
+                        chunk_z: chunk_data.chunk_z,
+                    },
+                    message,
+                ));
+            }
         }
     }
 }
 
-fn compute_section_bitmask(chunk_bytes: &[u8]) -> Result<u32> {
+/// Returns the bitmask of the sections in `chunk_bytes`, of which there are
+/// at most `max_sections`.
+fn compute_section_bitmask(chunk_bytes: &[u8], max_sections: usize) -> Result<u32> {
     let mut cursor = Cursor::new(chunk_bytes);
     let mut bitmask: u32 = 0;
     let mut section_index: u32 = 0;
@@ -146,7 +287,7 @@ fn compute_section_bitmask(chunk_bytes: &[u8]) -> Result<u32> {
         }
         section_index += 1;
 
-        if section_index >= SECTIONS_PER_CHUNK as u32 {
+        if section_index >= max_sections as u32 {
             break;
         }
     }
@@ -161,3 +302,48 @@ fn compute_section_bitmask(chunk_bytes: &[u8]) -> Result<u32> {
 
     Ok(bitmask)
 }
+
+#[cfg(test)]
+mod tests {
+    use byteorder::WriteBytesExt;
+
+    use super::*;
+
+    fn long_array(name: &str, longs: &[i64]) -> Vec<u8> {
+        let mut data = vec![TAG_LONG_ARRAY];
+        data.write_u16::<BigEndian>(name.len() as u16).unwrap();
+        data.extend(name.as_bytes());
+        data.write_i32::<BigEndian>(longs.len() as i32).unwrap();
+        for long in longs {
+            data.write_i64::<BigEndian>(*long).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn motion_blocking_is_picked_out_of_the_heightmaps() {
+        let mut heightmaps = vec![TAG_COMPOUND];
+        heightmaps.extend(long_array("WORLD_SURFACE", &[1, 2]));
+        heightmaps.extend(long_array(MOTION_BLOCKING, &[3, 4, 5]));
+        heightmaps.push(TAG_END);
+
+        assert_eq!(
+            read_motion_blocking(&heightmaps).unwrap(),
+            Some(vec![3, 4, 5])
+        );
+        assert_eq!(read_motion_blocking(&[TAG_END]).unwrap(), None);
+    }
+
+    #[test]
+    fn sections_start_at_the_dimension_min_y() {
+        assert_eq!(first_section(&DimensionType::OVERWORLD), Some(0));
+        assert_eq!(first_section(&DimensionType::NETHER), Some(4));
+
+        let deep = DimensionType {
+            min_y: -128,
+            height: 512,
+            has_skylight: true,
+        };
+        assert_eq!(first_section(&deep), None);
+    }
+}
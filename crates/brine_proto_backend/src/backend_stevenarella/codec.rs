@@ -14,16 +14,26 @@ pub use steven_protocol::protocol::{packet, Direction, Error, PacketType, Serial
 
 use brine_net::{Decode, DecodeResult, Encode, EncodeResult};
 
-use crate::codec::{
-    IntoDecodeResult, IntoEncodeResult, MinecraftClientCodec, MinecraftProtocolState,
-    UnknownPacket, HANDSHAKE_LOGIN_NEXT, HANDSHAKE_STATUS_NEXT,
+use crate::{
+    codec::{
+        CodecLimits, IntoDecodeResult, IntoEncodeResult, MinecraftClientCodec,
+        MinecraftProtocolState, ProtocolAnomaly, UnknownPacket, HANDSHAKE_LOGIN_NEXT,
+        HANDSHAKE_STATUS_NEXT, HANDSHAKE_TRANSFER_NEXT,
+    },
+    version::shims_for,
 };
 
+use super::legacy::LegacyKick;
+
 /// Packet representation used by this implementation of the protocol codec.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Packet {
     Known(packet::Packet),
     Unknown(UnknownPacket),
+
+    /// A kick from a server older than 1.7. See the [`legacy`][super::legacy]
+    /// module.
+    Legacy(LegacyKick),
 }
 
 impl From<packet::Packet> for Packet {
@@ -56,6 +66,53 @@ impl From<State> for MinecraftProtocolState {
     }
 }
 
+/// Errors that can occur while decoding a packet frame.
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    /// The buffer ends before the frame does. Not an error as such: decoding
+    /// should be retried once more bytes have arrived.
+    #[error("incomplete packet")]
+    Incomplete,
+
+    #[error("packet length {length} is outside of 0..={max}")]
+    PacketTooLong { length: i32, max: usize },
+
+    #[error("compressed packet claims a length of {length}, outside of 0..={max}")]
+    DecompressedTooLong { length: i32, max: usize },
+
+    #[error("packet decompresses to more than its claimed {length} bytes")]
+    DecompressionOverrun { length: usize },
+
+    #[error("invalid compressed packet data: {0}")]
+    Compression(#[source] io::Error),
+
+    /// The frame is complete, but its header couldn't be read.
+    #[error("malformed packet frame: {0:?}")]
+    Frame(Error),
+
+    /// The frame is fine, but the packet in it couldn't be decoded.
+    #[error(
+        "failed to decode packet 0x{packet_id:02X} in state {state:?} ({direction:?}): {error:?}"
+    )]
+    Packet {
+        packet_id: i32,
+        state: MinecraftProtocolState,
+        direction: Direction,
+        error: Error,
+    },
+}
+
+impl From<Error> for DecodeError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IOError(io_error) if io_error.kind() == io::ErrorKind::UnexpectedEof => {
+                Self::Incomplete
+            }
+            error => Self::Frame(error),
+        }
+    }
+}
+
 /// Implementation of the Minecraft protocol using the [`steven_protocol`] crate.
 ///
 /// [`steven_protocol`]: <https://github.com/iceiix/stevenarella/tree/master/protocol>
@@ -144,47 +201,94 @@ impl MinecraftCodec {
         protocol_state: MinecraftProtocolState,
         direction: Direction,
         compression_threshold: Option<i32>,
+        limits: CodecLimits,
         buf: impl AsRef<[u8]>,
-    ) -> Result<(usize, Packet), Error> {
-        let buf = buf.as_ref();
+    ) -> Result<(usize, Packet), DecodeError> {
+        Self::decode_frame(
+            protocol_version,
+            protocol_state,
+            direction,
+            compression_threshold,
+            limits,
+            buf.as_ref(),
+        )
+        .map(|(length, packet, _)| (length, packet))
+    }
 
+    /// Like [`decode_packet`](Self::decode_packet), also returning the
+    /// anomaly if the packet's definition left some of it unread.
+    fn decode_frame(
+        protocol_version: i32,
+        protocol_state: MinecraftProtocolState,
+        direction: Direction,
+        compression_threshold: Option<i32>,
+        limits: CodecLimits,
+        buf: &[u8],
+    ) -> Result<(usize, Packet, Option<ProtocolAnomaly>), DecodeError> {
         // Use a cursor so we can track how many bytes we've read
         // (VarInts have variable length).
         let mut cursor = Cursor::new(buf);
 
         // First field is the packet length in bytes. Note that this number does
         // **not** include the bytes used for the length field.
-        let length = VarInt::read_from(&mut cursor)?.0 as usize;
+        let length = VarInt::read_from(&mut cursor)?.0;
+        let length = match usize::try_from(length) {
+            Ok(length) if length <= limits.max_packet_length => length,
+            _ => {
+                return Err(DecodeError::PacketTooLong {
+                    length,
+                    max: limits.max_packet_length,
+                })
+            }
+        };
         // Take note of how many bytes the `length` field took up.
         let length_length = cursor.position() as usize;
 
         // Ensure that there's enough data in the buffer to read the rest of the packet.
         let total_packet_bytes = length_length + length;
         if buf.len() < total_packet_bytes {
-            return Err(Error::IOError(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "Not enough bytes in buffer",
-            )));
+            return Err(DecodeError::Incomplete);
         }
 
-        // The rest of the packet is the actual packet data.
+        // The rest of the packet is the actual packet data. From here on the
+        // whole frame is in the buffer, so running out of bytes is an error.
         let packet_body = &buf[length_length..length_length + length];
 
         let mut body_bytes: Cow<[u8]> = Cow::Borrowed(packet_body);
 
         if compression_threshold.is_some() {
             let mut body_cursor = Cursor::new(packet_body);
-            let data_length = VarInt::read_from(&mut body_cursor)?.0 as usize;
+            let data_length = VarInt::read_from(&mut body_cursor)
+                .map_err(DecodeError::Frame)?
+                .0;
+            let data_length = match usize::try_from(data_length) {
+                Ok(data_length) if data_length <= limits.max_decompressed_length => data_length,
+                _ => {
+                    return Err(DecodeError::DecompressedTooLong {
+                        length: data_length,
+                        max: limits.max_decompressed_length,
+                    })
+                }
+            };
             let remaining = &packet_body[body_cursor.position() as usize..];
 
             if data_length == 0 {
                 body_bytes = Cow::Borrowed(remaining);
             } else {
-                let mut decoder = ZlibDecoder::new(remaining);
+                // Stop a stream that inflates past its claimed length (a zip
+                // bomb) one byte over it.
+                let mut decoder = ZlibDecoder::new(remaining).take(data_length as u64 + 1);
                 let mut data = Vec::with_capacity(data_length);
-                decoder.read_to_end(&mut data)?;
-
-                if data_length != 0 && data.len() != data_length {
+                decoder
+                    .read_to_end(&mut data)
+                    .map_err(DecodeError::Compression)?;
+
+                if data.len() > data_length {
+                    return Err(DecodeError::DecompressionOverrun {
+                        length: data_length,
+                    });
+                }
+                if data.len() != data_length {
                     log::warn!(
                         "Decompressed packet length mismatch (expected {}, got {})",
                         data_length,
@@ -197,22 +301,32 @@ impl MinecraftCodec {
         }
 
         let mut id_cursor = Cursor::new(body_bytes.as_ref());
-        let packet_id = VarInt::read_from(&mut id_cursor)?.0;
+        let packet_id = VarInt::read_from(&mut id_cursor)
+            .map_err(DecodeError::Frame)?
+            .0;
         let data_start = id_cursor.position() as usize;
         let data_slice = &body_bytes.as_ref()[data_start..];
 
         Self::trace_packet_body(packet_id, protocol_state, direction, body_bytes.as_ref());
         Self::dump_packet_to_file(packet_id, protocol_state, direction, body_bytes.as_ref());
 
-        let packet = Self::decode_packet_with_id(
+        let (packet, consumed) = Self::decode_body(
             protocol_version,
             protocol_state,
             direction,
+            limits,
             packet_id,
             data_slice,
         )?;
+        let anomaly = consumed.map(|consumed| ProtocolAnomaly {
+            protocol_version,
+            state: protocol_state,
+            packet_id,
+            consumed,
+            body: Vec::from(data_slice),
+        });
 
-        Ok((total_packet_bytes, packet))
+        Ok((total_packet_bytes, packet, anomaly))
     }
 
     /// Decodes packet contents from a byte slice. Byte slice must be exactly
@@ -221,10 +335,39 @@ impl MinecraftCodec {
         protocol_version: i32,
         protocol_state: MinecraftProtocolState,
         direction: Direction,
+        limits: CodecLimits,
         packet_id: i32,
         buf: impl AsRef<[u8]>,
-    ) -> Result<Packet, Error> {
-        let buf = buf.as_ref();
+    ) -> Result<Packet, DecodeError> {
+        Self::decode_body(
+            protocol_version,
+            protocol_state,
+            direction,
+            limits,
+            packet_id,
+            buf.as_ref(),
+        )
+        .map(|(packet, _)| packet)
+    }
+
+    /// Like [`decode_packet_with_id`](Self::decode_packet_with_id), also
+    /// returning how much of `buf` was read if a known packet's definition
+    /// didn't read all of it.
+    fn decode_body(
+        protocol_version: i32,
+        protocol_state: MinecraftProtocolState,
+        direction: Direction,
+        limits: CodecLimits,
+        packet_id: i32,
+        buf: &[u8],
+    ) -> Result<(Packet, Option<usize>), DecodeError> {
+        let _span = log::info_span!(
+            "packet_decode",
+            packet_id,
+            state = ?protocol_state,
+            len = buf.len()
+        )
+        .entered();
 
         log::debug!(
             "Decoding packet id=0x{:02X} state={:?} dir={:?} ({} bytes)",
@@ -234,78 +377,83 @@ impl MinecraftCodec {
             buf.len()
         );
 
-        // Temporary workaround: the generated DeclareRecipes (0x7e) parser for protocol 769
-        // leaves unread bytes, which stalls the play stream. Treat it as unknown so we can
-        // keep consuming packets until the upstream spec is fixed.
+        // Parsers for several large packets (DeclareRecipes and some metadata
+        // packets) are incomplete in the generated tables for some versions.
+        // Skipping them keeps the stream aligned so chunk packets can still be
+        // decoded.
         if protocol_state == MinecraftProtocolState::Play
             && direction == Direction::Clientbound
-            && packet_id == 0x7e
-            && protocol_version >= 769
+            && shims_for(protocol_version)
+                .skipped_play_packets
+                .contains(&packet_id)
         {
-            return Ok(Packet::Unknown(UnknownPacket {
+            let packet = Packet::Unknown(UnknownPacket {
                 packet_id,
                 body: Vec::from(buf),
-            }));
-        }
-        // Parsers for several large metadata packets are incomplete in the generated 1.21.4 tables.
-        // Skipping them keeps the stream aligned so chunk packets can still be decoded.
-        if protocol_state == MinecraftProtocolState::Play
-            && direction == Direction::Clientbound
-            && matches!(packet_id, 0x11 | 0x40 | 0x42 | 0x44 | 0x46 | 0x50)
-            && protocol_version >= 769
-        {
-            return Ok(Packet::Unknown(UnknownPacket {
-                packet_id,
-                body: Vec::from(buf),
-            }));
+            });
+            return Ok((packet, None));
         }
 
         let mut cursor = Cursor::new(buf);
 
-        let packet = packet::packet_by_id(
-            protocol_version,
-            protocol_state.into(),
-            direction,
-            packet_id,
-            &mut cursor,
-        )
+        // Array lengths are read deep inside the generated packet definitions,
+        // which can't be passed the limits, so they're set for the thread.
+        let packet = packet::with_max_array_length(limits.max_array_length, || {
+            packet::packet_by_id(
+                protocol_version,
+                protocol_state.into(),
+                direction,
+                packet_id,
+                &mut cursor,
+            )
+        })
         .map(|maybe_packet| match maybe_packet {
             Some(packet) => Packet::Known(packet),
             None => Packet::Unknown(UnknownPacket {
                 packet_id,
                 body: Vec::from(buf),
             }),
+        })
+        .map_err(|error| DecodeError::Packet {
+            packet_id,
+            state: protocol_state,
+            direction,
+            error,
         })?;
 
         // All of the data should have been read but older packet definitions
         // don't necessarily include newly added trailing fields. Don't crash
-        // in that case; just log the mismatch so we know something was skipped.
+        // in that case; just log the mismatch so we know something was skipped,
+        // and hand it back for strict mode.
         let consumed = cursor.position() as usize;
         let total = buf.len();
-        if consumed != total {
-            match &packet {
-                Packet::Known(_) => log::warn!(
-                    "Decoded packet id={} state={:?} dir={:?} consumed {} of {} bytes (protocol {})",
+        let under_read = match &packet {
+            Packet::Known(_) if consumed != total => {
+                log::warn!(
+                    "Decoded packet id={} state={:?} dir={:?} read {} of {} bytes (protocol {})",
                     packet_id,
                     protocol_state,
                     direction,
                     consumed,
                     total,
                     protocol_version
-                ),
-                Packet::Unknown(_) => {
-                    log::debug!(
-                        "Unknown packet id={} state={:?} dir={:?} left {} unread bytes",
-                        packet_id,
-                        protocol_state,
-                        direction,
-                        total.saturating_sub(consumed)
-                    );
-                }
+                );
+                Some(consumed)
             }
-        }
+            Packet::Unknown(_) if consumed != total => {
+                log::debug!(
+                    "Unknown packet id={} state={:?} dir={:?} left {} unread bytes",
+                    packet_id,
+                    protocol_state,
+                    direction,
+                    total.saturating_sub(consumed)
+                );
+                None
+            }
+            _ => None,
+        };
 
-        Ok(packet)
+        Ok((packet, under_read))
     }
 
     pub fn encode_packet(
@@ -359,6 +507,10 @@ impl MinecraftCodec {
                 "Attempted to encode unknown packet: {:?}",
                 packet
             ))),
+            Packet::Legacy(kick) => Err(Error::Err(format!(
+                "Attempted to encode legacy kick: {:?}",
+                kick
+            ))),
         }
     }
 
@@ -403,16 +555,14 @@ impl MinecraftCodec {
     }
 }
 
-impl<T> IntoDecodeResult for Result<(usize, T), Error> {
+impl<T> IntoDecodeResult for Result<(usize, T), DecodeError> {
     type Item = T;
-    type Error = Error;
+    type Error = DecodeError;
 
     fn into_decode_result(self) -> (usize, DecodeResult<Self::Item, Self::Error>) {
         match self {
             Ok((length, item)) => (length, DecodeResult::Ok(item)),
-            Err(Error::IOError(io_error)) if io_error.kind() == io::ErrorKind::UnexpectedEof => {
-                (0, DecodeResult::UnexpectedEnd)
-            }
+            Err(DecodeError::Incomplete) => (0, DecodeResult::UnexpectedEnd),
             Err(err) => (0, DecodeResult::Err(err)),
         }
     }
@@ -424,8 +574,15 @@ impl IntoEncodeResult for Result<usize, Error> {
     fn into_encode_result(self, buflen: usize) -> EncodeResult<Self::Error> {
         match self {
             Ok(length) => EncodeResult::Ok(length),
-            Err(Error::IOError(io_error)) if io_error.kind() == io::ErrorKind::UnexpectedEof => {
-                EncodeResult::Overflow(buflen * 2)
+            // Running out of room mid-write is `WriteZero`, and the length
+            // check before writing the body is `UnexpectedEof`.
+            Err(Error::IOError(io_error))
+                if matches!(
+                    io_error.kind(),
+                    io::ErrorKind::UnexpectedEof | io::ErrorKind::WriteZero
+                ) =>
+            {
+                EncodeResult::Overflow(buflen.max(1) * 2)
             }
             Err(err) => EncodeResult::Err(err),
         }
@@ -448,9 +605,12 @@ impl MinecraftClientCodec<MinecraftCodec> {
             Packet::Known(packet::Packet::HandshakingServerboundSetProtocol(handshake)) => {
                 // Each handshake starts a fresh connection, so compression gets reset.
                 self.set_compression_threshold(None);
+                self.reset_compression_stats();
                 if let Some(next_state) = match handshake.nextState.0 {
                     HANDSHAKE_STATUS_NEXT => Some(MinecraftProtocolState::Status),
-                    HANDSHAKE_LOGIN_NEXT => Some(MinecraftProtocolState::Login),
+                    HANDSHAKE_LOGIN_NEXT | HANDSHAKE_TRANSFER_NEXT => {
+                        Some(MinecraftProtocolState::Login)
+                    }
                     i => {
                         log::error!("Invalid next state in Handshake packet: {}", i);
                         None
@@ -508,18 +668,41 @@ impl MinecraftClientCodec<MinecraftCodec> {
 
 impl Decode for MinecraftClientCodec<MinecraftCodec> {
     type Item = Packet;
-    type Error = Error;
+    type Error = DecodeError;
 
-    fn decode(&mut self, buf: &mut [u8]) -> (usize, DecodeResult<Packet, Error>) {
-        let result = MinecraftCodec::decode_packet(
+    fn decode(&mut self, buf: &mut [u8]) -> (usize, DecodeResult<Packet, DecodeError>) {
+        if let Some(result) = LegacyKick::decode(self.protocol_state(), buf) {
+            if let Ok((length, _)) = result {
+                self.record_inbound_bytes(length);
+            }
+            return result
+                .map(|(length, kick)| (length, Packet::Legacy(kick)))
+                .into_decode_result();
+        }
+
+        let result = MinecraftCodec::decode_frame(
             self.protocol_version(),
             self.protocol_state(),
             Direction::Clientbound,
             self.compression_threshold(),
+            self.limits(),
             buf,
-        );
-
-        if let Ok((_, ref packet)) = result {
+        )
+        .map(|(length, packet, anomaly)| {
+            if let Some(anomaly) = anomaly.filter(|_| self.strict_decoding()) {
+                self.push_anomaly(anomaly);
+            }
+            (length, packet)
+        });
+
+        if let Ok((length, ref packet)) = result {
+            self.record_inbound_bytes(length);
+            // Measured before the packet can turn compression on.
+            if self.compression_threshold().is_some() {
+                if let Some((compressed, uncompressed)) = compressed_sizes(&buf[..length]) {
+                    self.record_inbound_compression(compressed, uncompressed);
+                }
+            }
             self.react_to_packet(packet);
         }
 
@@ -536,24 +719,60 @@ impl Encode for MinecraftClientCodec<MinecraftCodec> {
 
         let len = buf.len();
 
-        MinecraftCodec::encode_packet(
+        // Disabling compression sends everything as if it were under a
+        // threshold no packet reaches.
+        let compression_threshold = self.compression_threshold().map(|threshold| {
+            if self.outbound_compression_disabled() {
+                i32::MAX
+            } else {
+                threshold
+            }
+        });
+
+        let result = MinecraftCodec::encode_packet(
             self.protocol_version(),
             packet,
-            buf,
-            self.compression_threshold(),
-        )
-        .into_encode_result(len)
+            &mut *buf,
+            compression_threshold,
+        );
+
+        if let Ok(length) = result {
+            self.record_outbound_bytes(length);
+            if compression_threshold.is_some() {
+                if let Some((compressed, uncompressed)) = compressed_sizes(&buf[..length]) {
+                    self.record_outbound_compression(compressed, uncompressed);
+                }
+            }
+        }
+
+        result.into_encode_result(len)
     }
 }
 
+/// Returns the compressed and decompressed sizes of the packet in `frame`, a
+/// whole frame sent with compression enabled, or `None` if the packet was
+/// sent uncompressed.
+fn compressed_sizes(frame: &[u8]) -> Option<(usize, usize)> {
+    let mut cursor = Cursor::new(frame);
+    VarInt::read_from(&mut cursor).ok()?;
+    let data_length = VarInt::read_from(&mut cursor).ok()?.0;
+    let data_length = usize::try_from(data_length).ok().filter(|n| *n > 0)?;
+
+    let compressed = frame.len() - cursor.position() as usize;
+    Some((compressed, data_length))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    use std::{fmt::Debug, marker::PhantomData};
+
     use async_codec::Framed;
     use futures::{sink::SinkExt, stream::StreamExt};
+    use proptest::prelude::*;
 
-    use crate::codec::MinecraftClientCodec;
+    use crate::{codec::MinecraftClientCodec, version::get_protocol_version};
 
     async fn roundtrip(state: MinecraftProtocolState, packet: packet::Packet) {
         let mut encoded = Vec::<u8>::new();
@@ -591,4 +810,297 @@ mod test {
     fn metadata_size() {
         assert!(std::mem::size_of::<steven_protocol::types::Metadata>() > 0);
     }
+
+    fn protocol_version() -> i32 {
+        get_protocol_version("1.21.4").unwrap()
+    }
+
+    /// Writes `value` and reads it back, checking that all of it is read.
+    fn round_trip_value<T: Serializable + Debug>(value: &T) -> Result<T, TestCaseError> {
+        let mut buf = Vec::new();
+        value.write_to(&mut buf).unwrap();
+
+        let mut cursor = Cursor::new(buf.as_slice());
+        let decoded = T::read_from(&mut cursor).unwrap();
+        prop_assert_eq!(cursor.position() as usize, buf.len(), "{:?}", value);
+
+        Ok(decoded)
+    }
+
+    /// Encodes `packet` as a frame and decodes it back.
+    fn round_trip_packet(
+        state: MinecraftProtocolState,
+        direction: Direction,
+        compression_threshold: Option<i32>,
+        packet: packet::Packet,
+    ) -> Result<(), TestCaseError> {
+        let packet = Packet::from(packet);
+        let mut buf = vec![0; 4096];
+        let length = MinecraftCodec::encode_packet(
+            protocol_version(),
+            &packet,
+            &mut buf,
+            compression_threshold,
+        )
+        .unwrap();
+
+        let (decoded_length, decoded) = MinecraftCodec::decode_packet(
+            protocol_version(),
+            state,
+            direction,
+            compression_threshold,
+            CodecLimits::default(),
+            &buf[..length],
+        )
+        .unwrap();
+        prop_assert_eq!(decoded_length, length);
+        prop_assert_eq!(decoded, packet);
+
+        Ok(())
+    }
+
+    #[test]
+    fn outbound_compression_is_counted_unless_disabled() {
+        let packet = Packet::from(packet::Packet::PlayClientboundKeepAlive(Box::new(
+            packet::play::clientbound::KeepAlive { keepAliveId: 0 },
+        )));
+        let mut codec = MinecraftClientCodec::<MinecraftCodec>::new(MinecraftProtocolState::Play);
+        codec.set_compression_threshold(Some(0));
+        let mut buf = vec![0; 64];
+
+        let EncodeResult::Ok(length) = codec.encode(&packet, &mut buf) else {
+            panic!("failed to encode");
+        };
+        let stats = codec.outbound_compression();
+        assert_eq!(stats.packets, 1);
+        // Packet ID and the keep-alive ID.
+        assert_eq!(stats.uncompressed_bytes, 9);
+        // Everything after the packet length and the uncompressed length.
+        assert_eq!(stats.compressed_bytes as usize, length - 2);
+        // Every byte sent counts toward the traffic, compressed or not.
+        assert_eq!(codec.outbound_bytes(), length as u64);
+
+        codec.set_outbound_compression_disabled(true);
+        let EncodeResult::Ok(length) = codec.encode(&packet, &mut buf) else {
+            panic!("failed to encode");
+        };
+        assert_eq!(codec.outbound_compression(), stats);
+        assert_eq!(compressed_sizes(&buf[..length]), None);
+
+        let (_, decoded) = MinecraftCodec::decode_packet(
+            protocol_version(),
+            MinecraftProtocolState::Play,
+            Direction::Clientbound,
+            Some(0),
+            CodecLimits::default(),
+            &buf[..length],
+        )
+        .unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn under_read_packets_are_anomalies_when_strict() {
+        let packet = packet::Packet::PlayClientboundKeepAlive(Box::new(
+            packet::play::clientbound::KeepAlive { keepAliveId: 0 },
+        ));
+        let mut body = Vec::new();
+        MinecraftCodec::encode_packet_id_and_data(protocol_version(), &packet, &mut body).unwrap();
+        body.push(0xAB);
+        let mut frame = Vec::new();
+        VarInt(body.len() as i32).write_to(&mut frame).unwrap();
+        frame.extend_from_slice(&body);
+
+        let mut codec = MinecraftClientCodec::<MinecraftCodec>::new(MinecraftProtocolState::Play);
+        let (_, result) = codec.decode(&mut frame.clone());
+        assert!(matches!(result, DecodeResult::Ok(Packet::Known(_))));
+        assert!(codec.take_anomalies().is_empty());
+
+        codec.set_strict_decoding(true);
+        let (length, result) = codec.decode(&mut frame);
+        assert_eq!(length, frame.len());
+        assert!(matches!(result, DecodeResult::Ok(Packet::Known(_))));
+        let anomalies = codec.take_anomalies();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].consumed, 8);
+        assert_eq!(anomalies[0].body, body[1..]);
+    }
+
+    #[test]
+    fn packets_over_the_limit_are_rejected() {
+        let packet = Packet::from(packet::Packet::PlayClientboundKeepAlive(Box::new(
+            packet::play::clientbound::KeepAlive { keepAliveId: 0 },
+        )));
+        let mut buf = vec![0; 64];
+        let length =
+            MinecraftCodec::encode_packet(protocol_version(), &packet, &mut buf, None).unwrap();
+
+        let limits = CodecLimits {
+            max_packet_length: 4,
+            ..Default::default()
+        };
+        let result = MinecraftCodec::decode_packet(
+            protocol_version(),
+            MinecraftProtocolState::Play,
+            Direction::Clientbound,
+            None,
+            limits,
+            &buf[..length],
+        );
+        assert!(
+            matches!(result, Err(DecodeError::PacketTooLong { max: 4, .. })),
+            "{:?}",
+            result
+        );
+    }
+
+    fn compression_threshold() -> impl Strategy<Value = Option<i32>> {
+        prop_oneof![Just(None), Just(Some(0)), Just(Some(256))]
+    }
+
+    proptest! {
+        #[test]
+        fn var_int_round_trips(value: i32) {
+            prop_assert_eq!(round_trip_value(&VarInt(value))?.0, value);
+        }
+
+        #[test]
+        fn counted_array_round_trips(values: Vec<i32>) {
+            let array = packet::CountedArray::<i32, VarInt> {
+                values,
+                _phantom: PhantomData,
+            };
+            prop_assert_eq!(round_trip_value(&array)?, array);
+        }
+
+        #[test]
+        fn option_flag_round_trips(value: Option<String>) {
+            let option = packet::OptionFlag { value };
+            prop_assert_eq!(round_trip_value(&option)?, option);
+        }
+
+        #[test]
+        fn prefixed_bytes_round_trip(data: Vec<u8>) {
+            let bytes = packet::PrefixedBytes::<VarInt> {
+                data,
+                _phantom: PhantomData,
+            };
+            prop_assert_eq!(round_trip_value(&bytes)?, bytes);
+        }
+
+        #[test]
+        fn truncated_values_are_unexpected_eof(
+            data in prop::collection::vec(any::<u8>(), 1..64),
+            cut: prop::sample::Index,
+        ) {
+            let bytes = packet::PrefixedBytes::<VarInt> {
+                data,
+                _phantom: PhantomData,
+            };
+            let mut buf = Vec::new();
+            bytes.write_to(&mut buf).unwrap();
+            buf.truncate(cut.index(buf.len()));
+
+            let result = packet::PrefixedBytes::<VarInt>::read_from(&mut Cursor::new(buf));
+            prop_assert!(
+                matches!(
+                    result,
+                    Err(Error::IOError(ref err)) if err.kind() == io::ErrorKind::UnexpectedEof
+                ),
+                "{:?}",
+                result
+            );
+        }
+
+        #[test]
+        fn keep_alive_round_trips(
+            keep_alive_id: i64,
+            compression_threshold in compression_threshold(),
+        ) {
+            round_trip_packet(
+                MinecraftProtocolState::Play,
+                Direction::Clientbound,
+                compression_threshold,
+                packet::Packet::PlayClientboundKeepAlive(Box::new(
+                    packet::play::clientbound::KeepAlive { keepAliveId: keep_alive_id },
+                )),
+            )?;
+        }
+
+        #[test]
+        fn login_start_round_trips(
+            username in "[A-Za-z0-9_]{1,16}",
+            compression_threshold in compression_threshold(),
+        ) {
+            round_trip_packet(
+                MinecraftProtocolState::Login,
+                Direction::Serverbound,
+                compression_threshold,
+                packet::Packet::LoginServerboundLoginStart(Box::new(
+                    packet::login::serverbound::LoginStart {
+                        username,
+                        ..Default::default()
+                    },
+                )),
+            )?;
+        }
+
+        #[test]
+        fn teleport_confirm_round_trips(
+            teleport_id: i32,
+            compression_threshold in compression_threshold(),
+        ) {
+            round_trip_packet(
+                MinecraftProtocolState::Play,
+                Direction::Serverbound,
+                compression_threshold,
+                packet::Packet::PlayServerboundTeleportConfirm(Box::new(
+                    packet::play::serverbound::TeleportConfirm {
+                        teleportId: VarInt(teleport_id),
+                    },
+                )),
+            )?;
+        }
+
+        #[test]
+        fn small_buffers_overflow(keep_alive_id: i64, buffer_length in 0usize..8) {
+            let packet = Packet::from(packet::Packet::PlayClientboundKeepAlive(Box::new(
+                packet::play::clientbound::KeepAlive { keepAliveId: keep_alive_id },
+            )));
+            let result = MinecraftCodec::encode_packet(
+                protocol_version(),
+                &packet,
+                vec![0; buffer_length],
+                None,
+            )
+            .into_encode_result(buffer_length);
+            prop_assert!(
+                matches!(result, EncodeResult::Overflow(needed) if needed > buffer_length),
+                "{:?}",
+                result
+            );
+        }
+
+        #[test]
+        fn truncated_frames_are_unexpected_end(keep_alive_id: i64, cut: prop::sample::Index) {
+            let packet = Packet::from(packet::Packet::PlayClientboundKeepAlive(Box::new(
+                packet::play::clientbound::KeepAlive { keepAliveId: keep_alive_id },
+            )));
+            let mut buf = vec![0; 64];
+            let length =
+                MinecraftCodec::encode_packet(protocol_version(), &packet, &mut buf, None).unwrap();
+
+            let (consumed, result) = MinecraftCodec::decode_packet(
+                protocol_version(),
+                MinecraftProtocolState::Play,
+                Direction::Clientbound,
+                None,
+                CodecLimits::default(),
+                &buf[..cut.index(length)],
+            )
+            .into_decode_result();
+            prop_assert_eq!(consumed, 0);
+            prop_assert!(matches!(result, DecodeResult::UnexpectedEnd), "{:?}", result);
+        }
+    }
 }
@@ -0,0 +1,176 @@
+//! Dispatch of received packets to the handlers registered for them.
+//!
+//! Most packets matter to a single handler, so rather than every system
+//! scanning every packet with a `match`, handlers register for the packets
+//! they handle with [`PacketHandlerAppExt::add_packet_handler`]. The
+//! dispatcher reads the received packets once per frame, in
+//! [`BrineSystems::ProtocolDecode`], and runs the handlers registered for each
+//! packet in the order they were added. Handlers are one-shot systems that
+//! take the packet as [`InRef<Packet>`], so they can use any system params.
+//!
+//! Packets are told apart by their variant of stevenarella's packet enum,
+//! which belongs to a single protocol state and direction (see
+//! [`packet_kind!`]).
+
+use std::mem::Discriminant;
+
+use bevy::{
+    ecs::system::{SystemId, SystemState},
+    platform::collections::HashMap,
+    prelude::*,
+};
+
+use brine_net::CodecReader;
+use brine_proto::BrineSystems;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+/// Which packet a handler is for.
+pub(crate) type PacketKind = Discriminant<packet::Packet>;
+
+/// Returns the [`PacketKind`] of a variant of stevenarella's packet enum,
+/// e.g. `packet_kind!(PlayClientboundUpdateHealth)`.
+macro_rules! packet_kind {
+    ($variant:ident) => {
+        ::std::mem::discriminant(
+            &$crate::backend_stevenarella::codec::packet::Packet::$variant(Default::default()),
+        )
+    };
+}
+
+pub(crate) use packet_kind;
+
+type PacketHandler = SystemId<InRef<'static, Packet>>;
+
+/// Handlers registered for each kind of packet.
+#[derive(Resource, Default)]
+struct PacketHandlers(HashMap<PacketKind, Vec<PacketHandler>>);
+
+impl PacketHandlers {
+    fn get(&self, packet: &Packet) -> &[PacketHandler] {
+        let handlers = match packet {
+            Packet::Known(packet) => self.0.get(&std::mem::discriminant(packet)),
+            Packet::Unknown(_) | Packet::Legacy(_) => None,
+        };
+        handlers.map_or(&[], Vec::as_slice)
+    }
+}
+
+pub(crate) trait PacketHandlerAppExt {
+    /// Runs `handler` on every received packet of each of `kinds`.
+    fn add_packet_handler<M>(
+        &mut self,
+        kinds: impl IntoIterator<Item = PacketKind>,
+        handler: impl IntoSystem<InRef<'static, Packet>, (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl PacketHandlerAppExt for App {
+    fn add_packet_handler<M>(
+        &mut self,
+        kinds: impl IntoIterator<Item = PacketKind>,
+        handler: impl IntoSystem<InRef<'static, Packet>, (), M> + 'static,
+    ) -> &mut Self {
+        let world = self.world_mut();
+        let handler = world.register_system(handler);
+        let mut handlers = world.get_resource_or_init::<PacketHandlers>();
+        for kind in kinds {
+            handlers.0.entry(kind).or_default().push(handler);
+        }
+        self
+    }
+}
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<PacketHandlers>().add_systems(
+        Update,
+        dispatch_packets.in_set(BrineSystems::ProtocolDecode),
+    );
+}
+
+/// System that runs the handlers registered for each received packet.
+fn dispatch_packets(
+    world: &mut World,
+    packet_reader: &mut SystemState<CodecReader<'static, 'static, ProtocolCodec>>,
+) {
+    world.resource_scope(|world, handlers: Mut<PacketHandlers>| {
+        // Handlers need the world, so the packets they handle are taken out
+        // of the reader first.
+        let packets: Vec<Packet> = packet_reader
+            .get_mut(world)
+            .iter()
+            .filter(|packet| !handlers.get(packet).is_empty())
+            .cloned()
+            .collect();
+
+        run_handlers(world, &handlers, &packets);
+    });
+}
+
+fn run_handlers(world: &mut World, handlers: &PacketHandlers, packets: &[Packet]) {
+    for packet in packets {
+        for handler in handlers.get(packet) {
+            if let Err(err) = world.run_system_with(*handler, packet) {
+                error!("Packet handler failed: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Handled(Vec<(&'static str, i64)>);
+
+    fn keep_alive_id(packet: &Packet) -> i64 {
+        match packet {
+            Packet::Known(packet::Packet::PlayClientboundKeepAlive(keep_alive)) => {
+                keep_alive.keepAliveId
+            }
+            _ => panic!("handler got the wrong packet: {:?}", packet),
+        }
+    }
+
+    fn first(InRef(packet): InRef<Packet>, mut handled: ResMut<Handled>) {
+        handled.0.push(("first", keep_alive_id(packet)));
+    }
+
+    fn second(InRef(packet): InRef<Packet>, mut handled: ResMut<Handled>) {
+        handled.0.push(("second", keep_alive_id(packet)));
+    }
+
+    #[test]
+    fn handlers_run_once_per_packet_of_their_kinds() {
+        let mut app = App::new();
+        app.init_resource::<Handled>()
+            .add_packet_handler([packet_kind!(PlayClientboundKeepAlive)], first)
+            .add_packet_handler(
+                [
+                    packet_kind!(PlayClientboundKeepAlive),
+                    packet_kind!(PlayClientboundUpdateHealth),
+                ],
+                second,
+            );
+
+        let keep_alive = |id| {
+            Packet::Known(packet::Packet::PlayClientboundKeepAlive(Box::new(
+                packet::play::clientbound::KeepAlive { keepAliveId: id },
+            )))
+        };
+        let packets = [
+            keep_alive(1),
+            Packet::Known(packet::Packet::PlayClientboundTags(Default::default())),
+            keep_alive(2),
+        ];
+        let world = app.world_mut();
+        let handlers = world.remove_resource::<PacketHandlers>().unwrap();
+        run_handlers(world, &handlers, &packets);
+
+        assert_eq!(
+            app.world().resource::<Handled>().0,
+            [("first", 1), ("second", 1), ("first", 2), ("second", 2)]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Translation of entity spawn, movement, removal and status effect packets
+//! into [`brine_proto`] events.
+
+use bevy::prelude::*;
+
+use brine_net::CodecReader;
+use brine_proto::event::{
+    clientbound::{
+        EntitiesRemoved, EntityEffectAdded, EntityEffectRemoved, EntityMoved, EntityPosition,
+        EntitySpawned, EntityVelocity, JoinedGame,
+    },
+    DVec3, Uuid,
+};
+use brine_proto::BrineSystems;
+use steven_protocol::protocol::Serializable;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+/// Relative moves are sent in units of 1/4096 of a block.
+const RELATIVE_MOVE_SCALE: f64 = 4096.0;
+
+/// Velocities are sent in units of 1/8000 of a block per tick.
+const VELOCITY_SCALE: f64 = 8000.0;
+
+const EFFECT_AMBIENT: i8 = 0x01;
+const EFFECT_SHOW_PARTICLES: i8 = 0x02;
+const EFFECT_SHOW_ICON: i8 = 0x04;
+
+/// Effect duration that means the effect never runs out.
+const INFINITE_DURATION: i32 = -1;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (handle_entity_packets, handle_effect_packets).in_set(BrineSystems::ProtocolDecode),
+    );
+}
+
+/// Converts a protocol angle (1/256 of a full turn) to degrees.
+fn angle(angle: i8) -> f32 {
+    f32::from(angle) * 360.0 / 256.0
+}
+
+fn relative(dx: i16, dy: i16, dz: i16) -> Option<EntityPosition> {
+    Some(EntityPosition::Relative(
+        DVec3::new(f64::from(dx), f64::from(dy), f64::from(dz)) / RELATIVE_MOVE_SCALE,
+    ))
+}
+
+/// System that listens for entity packets and sends the corresponding events
+/// to the client application.
+fn handle_entity_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut spawned_events: MessageWriter<EntitySpawned>,
+    mut moved_events: MessageWriter<EntityMoved>,
+    mut velocity_events: MessageWriter<EntityVelocity>,
+    mut removed_events: MessageWriter<EntitiesRemoved>,
+) {
+    for packet in packet_reader.iter() {
+        let moved = match packet {
+            Packet::Known(packet::Packet::PlayClientboundSpawnEntity(spawn)) => {
+                let mut uuid_bytes = Vec::with_capacity(16);
+                spawn.objectUUID.write_to(&mut uuid_bytes).unwrap();
+
+                spawned_events.write(EntitySpawned {
+                    entity_id: spawn.entityId.0,
+                    uuid: Uuid::from_bytes(uuid_bytes.try_into().unwrap()),
+                    entity_type: spawn.ty.0,
+                    position: DVec3::new(spawn.x, spawn.y, spawn.z),
+                    yaw: angle(spawn.yaw),
+                    pitch: angle(spawn.pitch),
+                    head_yaw: angle(spawn.headPitch),
+                });
+                continue;
+            }
+            Packet::Known(packet::Packet::PlayClientboundEntityDestroy(destroy)) => {
+                removed_events.write(EntitiesRemoved {
+                    entity_ids: destroy.entityIds.data.iter().map(|id| id.0).collect(),
+                });
+                continue;
+            }
+            Packet::Known(packet::Packet::PlayClientboundEntityVelocity(velocity)) => {
+                velocity_events.write(EntityVelocity {
+                    entity_id: velocity.entityId.0,
+                    velocity: DVec3::new(
+                        f64::from(velocity.velocityX),
+                        f64::from(velocity.velocityY),
+                        f64::from(velocity.velocityZ),
+                    ) / VELOCITY_SCALE,
+                });
+                continue;
+            }
+            Packet::Known(packet::Packet::PlayClientboundRelEntityMove(moved)) => EntityMoved {
+                entity_id: moved.entityId.0,
+                position: relative(moved.dX, moved.dY, moved.dZ),
+                yaw: None,
+                pitch: None,
+                head_yaw: None,
+            },
+            Packet::Known(packet::Packet::PlayClientboundEntityMoveLook(moved)) => EntityMoved {
+                entity_id: moved.entityId.0,
+                position: relative(moved.dX, moved.dY, moved.dZ),
+                yaw: Some(angle(moved.yaw)),
+                pitch: Some(angle(moved.pitch)),
+                head_yaw: None,
+            },
+            Packet::Known(packet::Packet::PlayClientboundEntityLook(look)) => EntityMoved {
+                entity_id: look.entityId.0,
+                position: None,
+                yaw: Some(angle(look.yaw)),
+                pitch: Some(angle(look.pitch)),
+                head_yaw: None,
+            },
+            Packet::Known(packet::Packet::PlayClientboundEntityHeadRotation(head)) => EntityMoved {
+                entity_id: head.entityId.0,
+                position: None,
+                yaw: None,
+                pitch: None,
+                head_yaw: Some(angle(head.headYaw)),
+            },
+            Packet::Known(packet::Packet::PlayClientboundSyncEntityPosition(sync)) => EntityMoved {
+                entity_id: sync.entityId.0,
+                position: Some(EntityPosition::Absolute(DVec3::new(sync.x, sync.y, sync.z))),
+                yaw: Some(sync.yaw),
+                pitch: Some(sync.pitch),
+                head_yaw: None,
+            },
+            _ => continue,
+        };
+
+        moved_events.write(moved);
+    }
+}
+
+/// System that listens for the Login and status effect packets and sends the
+/// corresponding events to the client application.
+fn handle_effect_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut joined_events: MessageWriter<JoinedGame>,
+    mut added_events: MessageWriter<EntityEffectAdded>,
+    mut removed_events: MessageWriter<EntityEffectRemoved>,
+) {
+    for packet in packet_reader.iter() {
+        match packet {
+            Packet::Known(packet::Packet::PlayClientboundLogin(login)) => {
+                joined_events.write(JoinedGame {
+                    entity_id: login.entityId,
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundEntityEffect(effect)) => {
+                added_events.write(EntityEffectAdded {
+                    entity_id: effect.entityId.0,
+                    effect_id: effect.effectId.0,
+                    amplifier: effect.amplifier.0,
+                    duration: (effect.duration.0 != INFINITE_DURATION).then_some(effect.duration.0),
+                    ambient: effect.flags & EFFECT_AMBIENT != 0,
+                    show_particles: effect.flags & EFFECT_SHOW_PARTICLES != 0,
+                    show_icon: effect.flags & EFFECT_SHOW_ICON != 0,
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundRemoveEntityEffect(effect)) => {
+                removed_events.write(EntityEffectRemoved {
+                    entity_id: effect.entityId.0,
+                    effect_id: effect.effectId.0,
+                });
+            }
+            _ => {}
+        }
+    }
+}
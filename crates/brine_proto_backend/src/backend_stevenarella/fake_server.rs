@@ -0,0 +1,300 @@
+//! A fake Minecraft server for driving the backend in tests.
+//!
+//! [`FakeServer`] listens on a local TCP port and plays the server's side of
+//! one login from a thread: it answers the status request made for protocol
+//! discovery, accepts the login, skips straight through configuration, and
+//! then sends a keep-alive and a single chunk once in play. It checks each
+//! packet it expects from the client along the way, and fails if the client
+//! sends them out of order or stops responding.
+//!
+//! The connection stays open until the server is joined, so the client
+//! doesn't see it as lost while a test is still looking at what it received.
+
+use std::{
+    io::{Cursor, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use byteorder::{BigEndian, WriteBytesExt};
+use steven_protocol::protocol::VarInt;
+
+use crate::codec::{
+    CodecLimits, MinecraftProtocolState, UnknownPacket, HANDSHAKE_LOGIN_NEXT,
+    HANDSHAKE_STATUS_NEXT, HANDSHAKE_TRANSFER_NEXT,
+};
+
+use super::codec::{packet, Direction, Error, MinecraftCodec, Packet, Serializable};
+
+/// ID of the keep-alive the server sends once in play.
+pub(crate) const KEEP_ALIVE_ID: i64 = 0x0B41_4E45;
+
+/// Block state every block of the chunk the server sends is set to.
+pub(crate) const CHUNK_BLOCK_STATE: u32 = 1;
+
+/// How long to wait for the client before giving up.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A server that speaks just enough of the protocol to log a client in.
+pub(crate) struct FakeServer {
+    addr: String,
+    thread: JoinHandle<Result<Connection, Error>>,
+}
+
+impl FakeServer {
+    /// Starts listening on a free local port, pretending to speak
+    /// `protocol_version`.
+    pub(crate) fn start(protocol_version: i32) -> Result<Self, Error> {
+        Self::start_with(protocol_version, true)
+    }
+
+    /// Like [`start`][Self::start], for a client that skips protocol
+    /// discovery and connects only to log in.
+    pub(crate) fn start_without_status(protocol_version: i32) -> Result<Self, Error> {
+        Self::start_with(protocol_version, false)
+    }
+
+    fn start_with(protocol_version: i32, expect_status: bool) -> Result<Self, Error> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?.to_string();
+
+        let thread = thread::spawn(move || serve(listener, protocol_version, expect_status));
+
+        Ok(Self { addr, thread })
+    }
+
+    /// The address to log in to.
+    pub(crate) fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Whether the server is done, having either seen the whole login or
+    /// failed.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Waits for the server to finish and returns how the login went.
+    pub(crate) fn join(self) -> Result<(), Error> {
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(Error::Err(String::from("fake server panicked"))))
+            .map(drop)
+    }
+}
+
+fn serve(
+    listener: TcpListener,
+    protocol_version: i32,
+    expect_status: bool,
+) -> Result<Connection, Error> {
+    if expect_status {
+        serve_status(&listener, protocol_version)?;
+    }
+
+    // Login.
+    let mut connection = Connection::accept(&listener, protocol_version)?;
+    connection.expect_handshake(HANDSHAKE_LOGIN_NEXT)?;
+    connection.expect("login start", |packet| {
+        matches!(packet, packet::Packet::LoginServerboundLoginStart(_))
+    })?;
+    connection.send(packet::Packet::LoginClientboundSuccess(Box::new(
+        packet::login::clientbound::Success {
+            username: String::from("Player"),
+            ..Default::default()
+        },
+    )))?;
+    connection.expect("login acknowledgement", |packet| {
+        matches!(packet, packet::Packet::LoginServerboundLoginAcknowledged(_))
+    })?;
+    connection.state = MinecraftProtocolState::Configuration;
+
+    // Configuration, with nothing to configure.
+    connection.send(packet::Packet::ConfigurationClientboundFinishConfiguration(
+        Box::new(packet::configuration::clientbound::FinishConfiguration {}),
+    ))?;
+    connection.expect("finish configuration", |packet| {
+        matches!(
+            packet,
+            packet::Packet::ConfigurationServerboundFinishConfiguration(_)
+        )
+    })?;
+    connection.state = MinecraftProtocolState::Play;
+
+    // Play.
+    connection.send(packet::Packet::PlayClientboundKeepAlive(Box::new(
+        packet::play::clientbound::KeepAlive {
+            keepAliveId: KEEP_ALIVE_ID,
+        },
+    )))?;
+
+    let mut map_chunk = packet::play::clientbound::MapChunk {
+        x: 0,
+        z: 0,
+        ..Default::default()
+    };
+    map_chunk.chunkData.data = chunk_data()?;
+    connection.send(packet::Packet::PlayClientboundMapChunk(Box::new(map_chunk)))?;
+
+    connection.expect("keep-alive response", |packet| {
+        matches!(
+            packet,
+            packet::Packet::PlayServerboundKeepAlive(keep_alive)
+                if keep_alive.keepAliveId == KEEP_ALIVE_ID
+        )
+    })?;
+
+    Ok(connection)
+}
+
+/// Answers the status request of the client's protocol discovery.
+fn serve_status(listener: &TcpListener, protocol_version: i32) -> Result<(), Error> {
+    let mut connection = Connection::accept(listener, protocol_version)?;
+    connection.expect_handshake(HANDSHAKE_STATUS_NEXT)?;
+    connection.expect("status request", |packet| {
+        matches!(packet, packet::Packet::StatusServerboundPingStart(_))
+    })?;
+    connection.send(packet::Packet::StatusClientboundServerInfo(Box::new(
+        packet::status::clientbound::ServerInfo {
+            response: status_response(protocol_version),
+        },
+    )))?;
+    connection.expect("status ping", |packet| {
+        matches!(packet, packet::Packet::StatusServerboundPing(_))
+    })?;
+    Ok(())
+}
+
+fn status_response(protocol_version: i32) -> String {
+    format!(
+        r#"{{"version":{{"name":"brine","protocol":{protocol_version}}},"players":{{"max":1,"online":0}},"description":{{"text":"brine fake server"}}}}"#
+    )
+}
+
+/// Returns the data of a chunk with a single section at the bottom, filled
+/// with [`CHUNK_BLOCK_STATE`].
+fn chunk_data() -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+
+    // Block count and bits per block.
+    data.write_i16::<BigEndian>(16 * 16 * 16)?;
+    data.write_u8(4)?;
+
+    // A palette of one block state, which every block points to.
+    VarInt(1).write_to(&mut data)?;
+    VarInt(CHUNK_BLOCK_STATE as i32).write_to(&mut data)?;
+
+    let longs = 16 * 16 * 16 * 4 / 64;
+    VarInt(longs).write_to(&mut data)?;
+    for _ in 0..longs {
+        data.write_u64::<BigEndian>(0)?;
+    }
+
+    Ok(data)
+}
+
+/// The server's end of a connection, which hasn't enabled compression.
+struct Connection {
+    stream: TcpStream,
+    protocol_version: i32,
+    state: MinecraftProtocolState,
+}
+
+impl Connection {
+    fn accept(listener: &TcpListener, protocol_version: i32) -> Result<Self, Error> {
+        let (stream, _) = listener.accept()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+        Ok(Self {
+            stream,
+            protocol_version,
+            state: MinecraftProtocolState::Handshaking,
+        })
+    }
+
+    fn send(&mut self, packet: packet::Packet) -> Result<(), Error> {
+        let mut body = Vec::new();
+        MinecraftCodec::encode_packet_id_and_data(self.protocol_version, &packet, &mut body)?;
+
+        let mut frame = Vec::new();
+        VarInt(body.len() as i32).write_to(&mut frame)?;
+        frame.extend_from_slice(&body);
+        self.stream.write_all(&frame)?;
+
+        Ok(())
+    }
+
+    /// Reads the next packet. Packets the tables can't decode come back as
+    /// [`Packet::Unknown`].
+    fn receive(&mut self) -> Result<Packet, Error> {
+        let length = VarInt::read_from(&mut self.stream)?.0 as usize;
+        let mut body = vec![0; length];
+        self.stream.read_exact(&mut body)?;
+
+        let mut cursor = Cursor::new(body.as_slice());
+        let packet_id = VarInt::read_from(&mut cursor)?.0;
+        let data = &body[cursor.position() as usize..];
+
+        Ok(MinecraftCodec::decode_packet_with_id(
+            self.protocol_version,
+            self.state,
+            Direction::Serverbound,
+            CodecLimits::default(),
+            packet_id,
+            data,
+        )
+        .unwrap_or_else(|_| {
+            Packet::Unknown(UnknownPacket {
+                packet_id,
+                body: Vec::from(data),
+            })
+        }))
+    }
+
+    /// Reads packets until one matches `is_expected`, skipping any others the
+    /// client sends in the meantime.
+    fn expect(
+        &mut self,
+        description: &str,
+        is_expected: impl Fn(&packet::Packet) -> bool,
+    ) -> Result<packet::Packet, Error> {
+        loop {
+            match self.receive() {
+                Ok(Packet::Known(packet)) if is_expected(&packet) => return Ok(packet),
+                Ok(_) => {}
+                Err(err) => {
+                    return Err(Error::Err(format!(
+                        "expected {description} in state {:?}: {err:?}",
+                        self.state
+                    )))
+                }
+            }
+        }
+    }
+
+    fn expect_handshake(&mut self, next_state: i32) -> Result<(), Error> {
+        let handshake = self.expect("handshake", |packet| {
+            matches!(packet, packet::Packet::HandshakingServerboundSetProtocol(_))
+        })?;
+
+        let packet::Packet::HandshakingServerboundSetProtocol(handshake) = handshake else {
+            unreachable!();
+        };
+
+        self.state = match handshake.nextState.0 {
+            HANDSHAKE_STATUS_NEXT => MinecraftProtocolState::Status,
+            HANDSHAKE_LOGIN_NEXT | HANDSHAKE_TRANSFER_NEXT => MinecraftProtocolState::Login,
+            _ => MinecraftProtocolState::Handshaking,
+        };
+
+        if handshake.nextState.0 != next_state {
+            return Err(Error::Err(format!(
+                "expected a handshake to state {}, got {}",
+                next_state, handshake.nextState.0
+            )));
+        }
+
+        Ok(())
+    }
+}
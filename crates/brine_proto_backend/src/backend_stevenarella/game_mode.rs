@@ -0,0 +1,159 @@
+//! Translation of game mode, difficulty and player ability packets between
+//! packets and [`brine_proto`] events.
+
+use bevy::prelude::*;
+
+use brine_net::{CodecWriter, NetworkResource};
+use brine_proto::{
+    event::{
+        clientbound::{AbilitiesChanged, DifficultyChanged, GameModeChanged},
+        serverbound::SetFlying,
+        Abilities, Difficulty, GameMode,
+    },
+    BrineSystems,
+};
+
+use crate::codec::MinecraftProtocolState;
+
+use super::{
+    codec::{packet, Packet, ProtocolCodec},
+    dispatch::{packet_kind, PacketHandlerAppExt},
+};
+
+/// Game Event reason that changes the player's game mode.
+const GAME_EVENT_CHANGE_GAME_MODE: u8 = 3;
+
+const ABILITY_INVULNERABLE: i8 = 0x01;
+const ABILITY_FLYING: i8 = 0x02;
+const ABILITY_MAY_FLY: i8 = 0x04;
+const ABILITY_INSTANT_BREAK: i8 = 0x08;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_packet_handler(
+        [
+            packet_kind!(PlayClientboundLogin),
+            packet_kind!(PlayClientboundRespawn),
+            packet_kind!(PlayClientboundGameStateChange),
+        ],
+        handle_game_mode,
+    )
+    .add_packet_handler([packet_kind!(PlayClientboundDifficulty)], handle_difficulty)
+    .add_packet_handler([packet_kind!(PlayClientboundAbilities)], handle_abilities)
+    .add_systems(Update, send_flying.in_set(BrineSystems::ProtocolEncode));
+}
+
+fn game_mode(id: i32) -> Option<GameMode> {
+    match id {
+        0 => Some(GameMode::Survival),
+        1 => Some(GameMode::Creative),
+        2 => Some(GameMode::Adventure),
+        3 => Some(GameMode::Spectator),
+        _ => None,
+    }
+}
+
+fn difficulty(id: u8) -> Option<Difficulty> {
+    match id {
+        0 => Some(Difficulty::Peaceful),
+        1 => Some(Difficulty::Easy),
+        2 => Some(Difficulty::Normal),
+        3 => Some(Difficulty::Hard),
+        _ => None,
+    }
+}
+
+/// Handler that sends the game mode from the Login, Respawn and Game Event
+/// packets to the client application.
+fn handle_game_mode(
+    InRef(packet): InRef<Packet>,
+    mut game_mode_events: MessageWriter<GameModeChanged>,
+) {
+    let game_mode_id = match packet {
+        Packet::Known(packet::Packet::PlayClientboundLogin(login)) => {
+            i32::from(login.worldState.gamemode)
+        }
+        Packet::Known(packet::Packet::PlayClientboundRespawn(respawn)) => {
+            i32::from(respawn.worldState.gamemode)
+        }
+        Packet::Known(packet::Packet::PlayClientboundGameStateChange(game_event))
+            if game_event.reason == GAME_EVENT_CHANGE_GAME_MODE =>
+        {
+            game_event.gameMode as i32
+        }
+        _ => return,
+    };
+
+    match game_mode(game_mode_id) {
+        Some(game_mode) => {
+            game_mode_events.write(GameModeChanged { game_mode });
+        }
+        None => warn!("Server sent unknown game mode {}", game_mode_id),
+    }
+}
+
+/// Handler that sends the difficulty to the client application.
+fn handle_difficulty(
+    InRef(packet): InRef<Packet>,
+    mut difficulty_events: MessageWriter<DifficultyChanged>,
+) {
+    let Packet::Known(packet::Packet::PlayClientboundDifficulty(change)) = packet else {
+        return;
+    };
+
+    match difficulty(change.difficulty) {
+        Some(difficulty) => {
+            difficulty_events.write(DifficultyChanged {
+                difficulty,
+                locked: change.difficultyLocked,
+            });
+        }
+        None => warn!("Server sent unknown difficulty {}", change.difficulty),
+    }
+}
+
+/// Handler that sends the player's abilities to the client application.
+fn handle_abilities(
+    InRef(packet): InRef<Packet>,
+    mut abilities_events: MessageWriter<AbilitiesChanged>,
+) {
+    let Packet::Known(packet::Packet::PlayClientboundAbilities(abilities)) = packet else {
+        return;
+    };
+
+    let flags = abilities.flags;
+    abilities_events.write(AbilitiesChanged {
+        abilities: Abilities {
+            invulnerable: flags & ABILITY_INVULNERABLE != 0,
+            flying: flags & ABILITY_FLYING != 0,
+            may_fly: flags & ABILITY_MAY_FLY != 0,
+            instant_break: flags & ABILITY_INSTANT_BREAK != 0,
+            flying_speed: abilities.flyingSpeed,
+            walking_speed: abilities.walkingSpeed,
+        },
+    });
+}
+
+/// System that tells the server when the player starts or stops flying.
+///
+/// The serverbound Player Abilities packet only carries the flying flag; the
+/// server ignores every other bit.
+fn send_flying(
+    mut flying_events: MessageReader<SetFlying>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    // Only the latest state matters.
+    let Some(set_flying) = flying_events.read().last() else {
+        return;
+    };
+
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        return;
+    }
+
+    debug!("Flying: {}", set_flying.flying);
+    let flags = if set_flying.flying { ABILITY_FLYING } else { 0 };
+    packet_writer.send(Packet::Known(packet::Packet::PlayServerboundAbilities(
+        Box::new(packet::play::serverbound::Abilities { flags }),
+    )));
+}
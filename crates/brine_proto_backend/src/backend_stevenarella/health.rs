@@ -0,0 +1,76 @@
+//! Translation of health, damage and respawn packets between the server and
+//! [`brine_proto`] events.
+
+use bevy::prelude::*;
+
+use brine_net::{CodecWriter, NetworkResource};
+use brine_proto::{event, BrineSystems};
+use steven_protocol::protocol::VarInt;
+
+use crate::codec::MinecraftProtocolState;
+
+use super::{
+    codec::{packet, Packet, ProtocolCodec},
+    dispatch::{packet_kind, PacketHandlerAppExt},
+};
+
+/// Client Command action that respawns the player.
+const PERFORM_RESPAWN: i32 = 0;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_packet_handler(
+        [packet_kind!(PlayClientboundUpdateHealth)],
+        handle_update_health,
+    )
+    .add_packet_handler([packet_kind!(PlayClientboundDamageEvent)], handle_damage)
+    .add_systems(Update, send_respawn.in_set(BrineSystems::ProtocolEncode));
+}
+
+/// Handler that sends the player's health on to the client application.
+fn handle_update_health(
+    InRef(packet): InRef<Packet>,
+    mut health_events: MessageWriter<event::clientbound::HealthUpdated>,
+) {
+    if let Packet::Known(packet::Packet::PlayClientboundUpdateHealth(health)) = packet {
+        health_events.write(event::clientbound::HealthUpdated {
+            health: health.health,
+            food: health.food.0,
+            saturation: health.foodSaturation,
+        });
+    }
+}
+
+/// Handler that tells the client application an entity was hurt.
+fn handle_damage(
+    InRef(packet): InRef<Packet>,
+    mut damaged_events: MessageWriter<event::clientbound::EntityDamaged>,
+) {
+    if let Packet::Known(packet::Packet::PlayClientboundDamageEvent(damage)) = packet {
+        damaged_events.write(event::clientbound::EntityDamaged {
+            entity_id: damage.entityId.0,
+        });
+    }
+}
+
+/// System that asks the server to respawn the player.
+fn send_respawn(
+    mut respawn_events: MessageReader<event::serverbound::Respawn>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    // Asking more than once per frame doesn't respawn any sooner.
+    if respawn_events.read().count() == 0 {
+        return;
+    }
+
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        return;
+    }
+
+    debug!("Requesting respawn");
+    packet_writer.send(Packet::Known(packet::Packet::PlayServerboundClientCommand(
+        Box::new(packet::play::serverbound::ClientCommand {
+            actionId: VarInt(PERFORM_RESPAWN),
+        }),
+    )));
+}
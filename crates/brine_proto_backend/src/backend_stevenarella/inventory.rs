@@ -0,0 +1,351 @@
+//! Translation of the player's inventory, container windows and hotbar
+//! selection between packets and [`brine_proto`] events.
+
+use bevy::prelude::*;
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{
+    event::{self, ClickMode, ContainerKind, Hand, ItemStack},
+    ActionSequence, BrineSystems,
+};
+use steven_protocol::{
+    item::Stack,
+    protocol::{LenPrefixed, VarInt},
+    shared::Position,
+};
+
+use crate::codec::MinecraftProtocolState;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+/// Window ID of the player's own inventory.
+const PLAYER_INVENTORY_WINDOW: i32 = 0;
+
+/// Window ID and slot of Set Container Slot packets that set the item on the
+/// cursor.
+const CURSOR_WINDOW: i32 = -1;
+const CURSOR_SLOT: i16 = -1;
+
+/// Player Action statuses that don't involve a block.
+const ACTION_RELEASE_USE_ITEM: i32 = 5;
+const ACTION_SWAP_HANDS: i32 = 6;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            handle_inventory_packets.in_set(BrineSystems::ProtocolDecode),
+            (
+                send_selected_hotbar_slot,
+                send_item_use,
+                send_container_clicks,
+            )
+                .in_set(BrineSystems::ProtocolEncode),
+        ),
+    );
+}
+
+fn item_stack(slot: &Option<Stack>) -> Option<ItemStack> {
+    slot.as_ref()
+        .filter(|stack| stack.count > 0)
+        .map(|stack| ItemStack {
+            item_id: stack.id as i32,
+            count: stack.count as i32,
+        })
+}
+
+fn stack(item: &Option<ItemStack>) -> Option<Stack> {
+    item.map(|item| Stack {
+        id: item.item_id as isize,
+        count: item.count as isize,
+        ..Default::default()
+    })
+}
+
+fn click_mode(mode: ClickMode) -> i32 {
+    match mode {
+        ClickMode::Pickup => 0,
+        ClickMode::QuickMove => 1,
+        ClickMode::Swap => 2,
+        ClickMode::Clone => 3,
+        ClickMode::Throw => 4,
+        ClickMode::QuickCraft => 5,
+        ClickMode::PickupAll => 6,
+    }
+}
+
+/// Converts an ID in the `minecraft:menu` registry into a container kind.
+fn container_kind(menu_id: i32) -> ContainerKind {
+    match menu_id {
+        0..=5 => ContainerKind::Generic {
+            rows: menu_id as u8 + 1,
+        },
+        6 => ContainerKind::Dispenser,
+        10 => ContainerKind::BlastFurnace,
+        12 => ContainerKind::Crafting,
+        14 => ContainerKind::Furnace,
+        16 => ContainerKind::Hopper,
+        20 => ContainerKind::ShulkerBox,
+        22 => ContainerKind::Smoker,
+        _ => ContainerKind::Other(menu_id),
+    }
+}
+
+/// Converts an index into the player's inventory storage, as used by
+/// `SetPlayerInventory`, into a player inventory window slot.
+fn storage_index_to_window_slot(index: i32) -> Option<u16> {
+    let slot = match index {
+        // Hotbar.
+        0..=8 => index + 36,
+        // Main inventory.
+        9..=35 => index,
+        // Armor, feet first; the window lists it head first.
+        36..=39 => 8 - (index - 36),
+        // Offhand.
+        40 => 45,
+        _ => return None,
+    };
+
+    Some(slot as u16)
+}
+
+/// System that listens for inventory, container and held item packets and
+/// sends the corresponding events to the client application.
+#[allow(clippy::too_many_arguments)]
+fn handle_inventory_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut hotbar_events: MessageWriter<event::clientbound::HotbarSlotSelected>,
+    mut contents_events: MessageWriter<event::clientbound::InventoryContents>,
+    mut slot_events: MessageWriter<event::clientbound::InventorySlot>,
+    mut cursor_events: MessageWriter<event::clientbound::CursorItem>,
+    mut opened_events: MessageWriter<event::clientbound::ContainerOpened>,
+    mut container_contents_events: MessageWriter<event::clientbound::ContainerContents>,
+    mut container_slot_events: MessageWriter<event::clientbound::ContainerSlot>,
+    mut property_events: MessageWriter<event::clientbound::ContainerProperty>,
+    mut closed_events: MessageWriter<event::clientbound::ContainerClosed>,
+) {
+    for packet in packet_reader.iter() {
+        match packet {
+            Packet::Known(packet::Packet::PlayClientboundHeldItemSlot(held)) => {
+                let Ok(slot) = u8::try_from(held.slot.0) else {
+                    warn!("Server selected invalid hotbar slot {}", held.slot.0);
+                    continue;
+                };
+                hotbar_events.write(event::clientbound::HotbarSlotSelected { slot });
+            }
+            Packet::Known(packet::Packet::PlayClientboundOpenWindow(open)) => {
+                opened_events.write(event::clientbound::ContainerOpened {
+                    window_id: open.windowId.0,
+                    kind: container_kind(open.inventoryType.0),
+                    title: open.windowTitle.to_string(),
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundWindowItems(window)) => {
+                let items = window.items.data.iter().map(item_stack).collect();
+                if window.windowId.0 == PLAYER_INVENTORY_WINDOW {
+                    contents_events.write(event::clientbound::InventoryContents {
+                        state_id: window.stateId.0,
+                        items,
+                    });
+                } else {
+                    container_contents_events.write(event::clientbound::ContainerContents {
+                        window_id: window.windowId.0,
+                        state_id: window.stateId.0,
+                        items,
+                    });
+                }
+                cursor_events.write(event::clientbound::CursorItem {
+                    item: item_stack(&window.carriedItem),
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundSetSlot(set_slot))
+                if set_slot.windowId.0 == CURSOR_WINDOW && set_slot.slot == CURSOR_SLOT =>
+            {
+                cursor_events.write(event::clientbound::CursorItem {
+                    item: item_stack(&set_slot.item),
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundSetSlot(set_slot)) => {
+                let Ok(slot) = u16::try_from(set_slot.slot) else {
+                    continue;
+                };
+                if set_slot.windowId.0 == PLAYER_INVENTORY_WINDOW {
+                    slot_events.write(event::clientbound::InventorySlot {
+                        state_id: Some(set_slot.stateId.0),
+                        slot,
+                        item: item_stack(&set_slot.item),
+                    });
+                } else {
+                    container_slot_events.write(event::clientbound::ContainerSlot {
+                        window_id: set_slot.windowId.0,
+                        state_id: set_slot.stateId.0,
+                        slot,
+                        item: item_stack(&set_slot.item),
+                    });
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundSetCursorItem(cursor)) => {
+                cursor_events.write(event::clientbound::CursorItem {
+                    item: item_stack(&cursor.contents),
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundCraftProgressBar(progress)) => {
+                property_events.write(event::clientbound::ContainerProperty {
+                    window_id: progress.windowId.0,
+                    property: progress.property,
+                    value: progress.value,
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundCloseWindow(close)) => {
+                closed_events.write(event::clientbound::ContainerClosed {
+                    window_id: close.windowId.0,
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundSetPlayerInventory(set_slot)) => {
+                let Some(slot) = storage_index_to_window_slot(set_slot.slotId.0) else {
+                    warn!("Unknown player inventory slot {}", set_slot.slotId.0);
+                    continue;
+                };
+                slot_events.write(event::clientbound::InventorySlot {
+                    state_id: None,
+                    slot,
+                    item: item_stack(&set_slot.contents),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// System that tells the server when the player selects a hotbar slot.
+fn send_selected_hotbar_slot(
+    mut select_events: MessageReader<event::serverbound::SelectHotbarSlot>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    // Only the latest selection matters.
+    let Some(select) = select_events.read().last() else {
+        return;
+    };
+
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        return;
+    }
+
+    debug!("Selecting hotbar slot {}", select.slot);
+    packet_writer.send(Packet::Known(packet::Packet::PlayServerboundHeldItemSlot(
+        Box::new(packet::play::serverbound::HeldItemSlot {
+            slotId: i16::from(select.slot),
+        }),
+    )));
+}
+
+/// System that tells the server when the player uses an item, stops using it,
+/// or swaps the items in their hands.
+fn send_item_use(
+    mut use_events: MessageReader<event::serverbound::UseItem>,
+    mut release_events: MessageReader<event::serverbound::ReleaseUseItem>,
+    mut swap_events: MessageReader<event::serverbound::SwapHands>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    mut sequence: ResMut<ActionSequence>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        use_events.clear();
+        release_events.clear();
+        swap_events.clear();
+        return;
+    }
+
+    for _ in swap_events.read() {
+        debug!("Swapping hands");
+        send_player_action(&mut packet_writer, ACTION_SWAP_HANDS);
+    }
+
+    for use_item in use_events.read() {
+        debug!("Using item in {:?} hand", use_item.hand);
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundUseItem(
+            Box::new(packet::play::serverbound::UseItem {
+                hand: VarInt(match use_item.hand {
+                    Hand::Main => 0,
+                    Hand::Off => 1,
+                }),
+                sequence: VarInt(sequence.next()),
+                rotation: packet::Vec2f {
+                    x: use_item.yaw,
+                    y: use_item.pitch,
+                },
+            }),
+        )));
+    }
+
+    // Releasing more than once per frame has no further effect.
+    if release_events.read().count() > 0 {
+        debug!("Releasing used item");
+        send_player_action(&mut packet_writer, ACTION_RELEASE_USE_ITEM);
+    }
+}
+
+/// Sends a Player Action that doesn't involve a block, so its position, face
+/// and sequence number are ignored.
+fn send_player_action(packet_writer: &mut CodecWriter<ProtocolCodec>, status: i32) {
+    packet_writer.send(Packet::Known(packet::Packet::PlayServerboundBlockDig(
+        Box::new(packet::play::serverbound::BlockDig {
+            status: VarInt(status),
+            location: Position::new(0, 0, 0),
+            face: 0,
+            sequence: VarInt(0),
+        }),
+    )));
+}
+
+/// System that sends the player's clicks in container windows, and closes
+/// them.
+fn send_container_clicks(
+    mut click_events: MessageReader<event::serverbound::ClickContainer>,
+    mut close_events: MessageReader<event::serverbound::CloseContainer>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        click_events.clear();
+        close_events.clear();
+        return;
+    }
+
+    for click in click_events.read() {
+        debug!(
+            "Clicking slot {} of window {} ({:?}, button {})",
+            click.slot, click.window_id, click.mode, click.button
+        );
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundWindowClick(
+            Box::new(packet::play::serverbound::WindowClick {
+                windowId: VarInt(click.window_id),
+                stateId: VarInt(click.state_id),
+                slot: click.slot,
+                mouseButton: click.button,
+                mode: VarInt(click_mode(click.mode)),
+                changedSlots: LenPrefixed::new(
+                    click
+                        .changed_slots
+                        .iter()
+                        .map(|(location, item)| packet::WindowClickChangedSlot {
+                            location: *location,
+                            item: stack(item),
+                        })
+                        .collect(),
+                ),
+                cursorItem: stack(&click.carried_item),
+            }),
+        )));
+    }
+
+    for close in close_events.read() {
+        debug!("Closing window {}", close.window_id);
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundCloseWindow(
+            Box::new(packet::play::serverbound::CloseWindow {
+                windowId: VarInt(close.window_id),
+            }),
+        )));
+    }
+}
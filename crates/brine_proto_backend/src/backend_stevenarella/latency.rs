@@ -0,0 +1,142 @@
+//! Measurement of the round trip time to the server.
+//!
+//! Keep-alives are sent by the server, which measures the latency it reports
+//! in the player list from them; the client can't tell how long its answers
+//! took to arrive. While playing, the backend therefore sends its own Ping
+//! Request every [`PING_INTERVAL`] and times the matching Pong Response,
+//! sending each measurement on as [`PingMeasured`].
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{platform::time::Instant, prelude::*};
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{event::clientbound::PingMeasured, BrineSystems};
+
+use crate::codec::MinecraftProtocolState;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+/// How often to measure the round trip time.
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Pings left unanswered for this long are given up on.
+const PING_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<PendingPings>().add_systems(
+        Update,
+        (
+            handle_ping_responses.in_set(BrineSystems::ProtocolDecode),
+            send_ping_requests.in_set(BrineSystems::ProtocolEncode),
+        ),
+    );
+}
+
+/// Ping Requests sent and not answered yet, oldest first.
+#[derive(Resource, Debug, Default)]
+struct PendingPings {
+    next_id: i64,
+    sent: VecDeque<(i64, Instant)>,
+
+    /// When the latest ping was sent, answered or not.
+    last_sent: Option<Instant>,
+}
+
+impl PendingPings {
+    /// Returns the ID of a ping sent at `now`.
+    fn send(&mut self, now: Instant) -> i64 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.sent
+            .retain(|(_, sent)| now.saturating_duration_since(*sent) < PING_TIMEOUT);
+        self.sent.push_back((id, now));
+        self.last_sent = Some(now);
+        id
+    }
+
+    /// Returns when the ping with `id` was sent, and forgets it along with
+    /// any older ones, which are lost.
+    fn answer(&mut self, id: i64) -> Option<Instant> {
+        let index = self.sent.iter().position(|(sent_id, _)| *sent_id == id)?;
+        let (_, sent) = self.sent.drain(..=index).last()?;
+        Some(sent)
+    }
+}
+
+/// System that sends a Ping Request every [`PING_INTERVAL`] while playing.
+fn send_ping_requests(
+    mut pending: ResMut<PendingPings>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        *pending = PendingPings::default();
+        return;
+    }
+
+    let now = Instant::now();
+    if pending
+        .last_sent
+        .is_some_and(|sent| now.saturating_duration_since(sent) < PING_INTERVAL)
+    {
+        return;
+    }
+
+    let id = pending.send(now);
+    packet_writer.send(Packet::Known(packet::Packet::PlayServerboundPingRequest(
+        Box::new(packet::play::serverbound::PingRequest { id }),
+    )));
+}
+
+/// System that listens for Pong Response packets and sends PingMeasured
+/// events to the client application.
+fn handle_ping_responses(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut pending: ResMut<PendingPings>,
+    mut measured_events: MessageWriter<PingMeasured>,
+) {
+    for packet in packet_reader.iter() {
+        if let Packet::Known(packet::Packet::PlayClientboundPingResponse(response)) = packet {
+            let Some(sent) = pending.answer(response.id) else {
+                debug!("Ignoring Pong Response to unknown ping {}", response.id);
+                continue;
+            };
+            measured_events.write(PingMeasured {
+                round_trip: sent.elapsed(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answering_a_ping_forgets_older_ones() {
+        let start = Instant::now();
+        let mut pending = PendingPings::default();
+
+        let first = pending.send(start);
+        let second = pending.send(start + PING_INTERVAL);
+        let third = pending.send(start + PING_INTERVAL * 2);
+
+        assert_eq!(pending.answer(second), Some(start + PING_INTERVAL));
+        assert_eq!(pending.answer(first), None);
+        assert_eq!(pending.answer(third), Some(start + PING_INTERVAL * 2));
+        assert!(pending.sent.is_empty());
+    }
+
+    #[test]
+    fn unanswered_pings_time_out() {
+        let start = Instant::now();
+        let mut pending = PendingPings::default();
+
+        let lost = pending.send(start);
+        pending.send(start + PING_TIMEOUT);
+
+        assert_eq!(pending.answer(lost), None);
+        assert_eq!(pending.sent.len(), 1);
+    }
+}
@@ -0,0 +1,196 @@
+//! Responses from servers older than 1.7.
+//!
+//! Before 1.7 reworked the protocol, servers kicked clients, and answered the
+//! server list ping (`0xFE`), with a `0xFF` packet: a big-endian `u16` length
+//! in UTF-16 code units, then the message in UTF-16BE. Such servers send one
+//! in response to a modern handshake too. Decoded as a modern frame it makes
+//! no sense, so the codec recognizes it first and hands it on as
+//! [`Packet::Legacy`][super::codec::Packet::Legacy], which the login turns
+//! into a [`Disconnect`][brine_proto::event::clientbound::Disconnect] saying
+//! which version the server needs.
+//!
+//! See <https://wiki.vg/Server_List_Ping#1.6> and
+//! <https://wiki.vg/Protocol_History#1.7.2>.
+
+use crate::{codec::MinecraftProtocolState, version::supported_versions_list};
+
+use super::codec::DecodeError;
+
+/// ID of the legacy kick packet.
+pub const LEGACY_KICK_ID: u8 = 0xFF;
+
+/// A kick (or server list ping response) from a server older than 1.7.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyKick {
+    pub message: String,
+}
+
+/// What a legacy server list ping response says about the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LegacyStatus {
+    /// From 1.4 to 1.6: `§1`, then protocol version, version name, message of
+    /// the day, players online and maximum players, separated by NULs.
+    Versioned {
+        protocol_version: i32,
+        version_name: String,
+        motd: String,
+    },
+
+    /// From Beta 1.8 to 1.3: message of the day, players online and maximum
+    /// players, separated by `§`. The version isn't included.
+    Unversioned { motd: String },
+}
+
+impl LegacyKick {
+    /// Decodes a legacy kick from the start of `buf`, if it holds one rather
+    /// than a modern frame. Only servers in the handshake's Status or Login
+    /// state can be legacy ones.
+    ///
+    /// The first byte of a modern frame starts its length, and a length whose
+    /// first byte is `0xFF` has a non-zero second byte, so `0xFF 0x00` can
+    /// only be a legacy kick of fewer than 256 code units, which they all
+    /// are in practice.
+    pub fn decode(
+        state: MinecraftProtocolState,
+        buf: &[u8],
+    ) -> Option<Result<(usize, Self), DecodeError>> {
+        if !matches!(
+            state,
+            MinecraftProtocolState::Status | MinecraftProtocolState::Login
+        ) {
+            return None;
+        }
+
+        match buf {
+            [LEGACY_KICK_ID, 0x00, ..] => {}
+            [LEGACY_KICK_ID] => return Some(Err(DecodeError::Incomplete)),
+            _ => return None,
+        }
+        if buf.len() < 3 {
+            return Some(Err(DecodeError::Incomplete));
+        }
+
+        let units = u16::from_be_bytes([buf[1], buf[2]]) as usize;
+        let length = 3 + 2 * units;
+        let Some(bytes) = buf.get(3..length) else {
+            return Some(Err(DecodeError::Incomplete));
+        };
+
+        let units = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+        let message = char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+
+        Some(Ok((length, Self { message })))
+    }
+
+    /// Parses the message as a server list ping response, if it is one.
+    pub fn status(&self) -> Option<LegacyStatus> {
+        if let Some(fields) = self.message.strip_prefix("§1\0") {
+            let mut fields = fields.split('\0');
+            let protocol_version = fields.next()?.parse().ok()?;
+            let version_name = fields.next()?.to_string();
+            let motd = fields.next()?.to_string();
+            return Some(LegacyStatus::Versioned {
+                protocol_version,
+                version_name,
+                motd,
+            });
+        }
+
+        let mut fields = self.message.rsplitn(3, '§');
+        let max_players = fields.next()?;
+        let players_online = fields.next()?;
+        let motd = fields.next()?;
+        if max_players.parse::<u32>().is_err() || players_online.parse::<u32>().is_err() {
+            return None;
+        }
+        Some(LegacyStatus::Unversioned {
+            motd: motd.to_string(),
+        })
+    }
+
+    /// A reason to disconnect with, naming the version the server needs when
+    /// it's known.
+    pub fn disconnect_reason(&self) -> String {
+        let server = match self.status() {
+            Some(LegacyStatus::Versioned {
+                protocol_version,
+                version_name,
+                ..
+            }) => format!(
+                "Server requires Minecraft {} (legacy protocol {})",
+                version_name, protocol_version
+            ),
+            Some(LegacyStatus::Unversioned { .. }) => {
+                String::from("Server requires a Minecraft version from Beta 1.8 to 1.3")
+            }
+            None => format!(
+                "Server requires a Minecraft version older than 1.7 (it said \"{}\")",
+                self.message
+            ),
+        };
+
+        format!("{}; brine supports {}", server, supported_versions_list())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(message: &str) -> Vec<u8> {
+        let units = message.encode_utf16().collect::<Vec<_>>();
+        let mut buf = vec![LEGACY_KICK_ID];
+        buf.extend((units.len() as u16).to_be_bytes());
+        buf.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+        buf
+    }
+
+    #[test]
+    fn legacy_ping_response_names_the_version() {
+        let buf = encode("§1\078\01.6.4\0A Minecraft Server\00\020");
+
+        let (length, kick) = LegacyKick::decode(MinecraftProtocolState::Status, &buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(length, buf.len());
+        assert_eq!(
+            kick.status(),
+            Some(LegacyStatus::Versioned {
+                protocol_version: 78,
+                version_name: String::from("1.6.4"),
+                motd: String::from("A Minecraft Server"),
+            })
+        );
+        assert!(kick
+            .disconnect_reason()
+            .starts_with("Server requires Minecraft 1.6.4 (legacy protocol 78); brine supports"));
+
+        let beta = LegacyKick {
+            message: String::from("A Minecraft Server§0§20"),
+        };
+        assert_eq!(
+            beta.status(),
+            Some(LegacyStatus::Unversioned {
+                motd: String::from("A Minecraft Server")
+            })
+        );
+    }
+
+    #[test]
+    fn modern_frames_are_not_legacy_kicks() {
+        // A 255-byte frame: its length is 0xFF 0x01.
+        let modern = [0xFF, 0x01, 0x00];
+        assert!(LegacyKick::decode(MinecraftProtocolState::Status, &modern).is_none());
+
+        let buf = encode("Outdated client!");
+        assert!(LegacyKick::decode(MinecraftProtocolState::Play, &buf).is_none());
+        assert!(matches!(
+            LegacyKick::decode(MinecraftProtocolState::Login, &buf[..5]),
+            Some(Err(DecodeError::Incomplete))
+        ));
+    }
+}
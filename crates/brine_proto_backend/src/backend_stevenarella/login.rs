@@ -1,7 +1,9 @@
 //! Implementation of the Minecraft protocol login handshake.
 //!
 //! This is driven by only a single message from the user's point of view:
-//! [`Login`]. These systems handle all of the login logic.
+//! [`Login`]. These systems handle all of the login logic, moving the
+//! [`LoginState`] along by firing [`LoginTrigger`]s; the state graph itself
+//! is in [`brine_proto::login`].
 //!
 //! # The Login Process
 //!
@@ -16,6 +18,10 @@
 //!   5. S -> C: Status Pong
 //!   6. Server disconnects
 //!
+//!   Skipped when the protocol version is forced and
+//!   [`without_status_ping`][crate::ProtocolBackendPlugin::without_status_ping]
+//!   is used.
+//!
 //! * Login (unauthenticated)
 //!   1. Client connects
 //!   2. C -> S: Handshake with Next State set to 2 (Login)
@@ -26,6 +32,15 @@
 //!   * Periodic KeepAlive packets
 //!   * Other play packets
 //!
+//! During Play the server may also:
+//!
+//! * Send StartConfiguration, which the client acknowledges before going
+//!   through the configuration phase again. The world is resent afterwards.
+//! * Send Transfer (during Play or configuration), after which the client
+//!   disconnects and logs in to the given server with a Handshake whose Next
+//!   State is 3 (Transfer). Protocol discovery is skipped; the new server is
+//!   expected to speak the same version.
+//!
 //! See these pages for reference:
 //!
 //! * <https://wiki.vg/Protocol#Handshaking>
@@ -36,38 +51,36 @@ use bevy::{ecs::schedule::IntoScheduleConfigs, prelude::*};
 use steven_protocol::protocol::{Serializable, VarInt};
 
 use brine_net::{CodecReader, CodecWriter, NetworkError, NetworkEvent, NetworkResource};
-use brine_proto::event::{
-    clientbound::{Disconnect, LoginSuccess},
-    serverbound::Login,
-    Uuid,
+use brine_proto::{
+    channels::BRAND_CHANNEL,
+    event::{
+        clientbound::{Disconnect, DisconnectKind, LoginSuccess, Reconfigure, Transfer},
+        serverbound::{Login, PluginMessage},
+        Uuid,
+    },
+    login::{LoginState, LoginTransitions, LoginTrigger},
+    BrineSystems,
 };
 
-use crate::codec::{HANDSHAKE_LOGIN_NEXT, HANDSHAKE_STATUS_NEXT};
-
-use super::codec::{packet, Packet, ProtocolCodec};
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, States, Default)]
-enum LoginState {
-    #[default]
-    Idle,
-
-    // Phase 1
-    StatusAwaitingConnect,
-    StatusAwaitingResponse,
-    StatusAwaitingDisconnect,
-
-    // Phase 2
-    LoginAwaitingConnect,
-    LoginAwaitingSuccess,
+use crate::{
+    codec::{CodecLimits, HANDSHAKE_LOGIN_NEXT, HANDSHAKE_STATUS_NEXT, HANDSHAKE_TRANSFER_NEXT},
+    configuration::ConfigurationPolicy,
+    version::{shims_for, supported_version, supported_versions_list},
+    ProtocolOverride,
+};
 
-    Play,
-}
+use super::{
+    codec::{packet, Packet, ProtocolCodec},
+    legacy::LegacyKick,
+};
 
 /// Keeps data around that is needed by systems occurring later in the state machine.
 #[derive(Resource)]
 struct LoginResource {
     username: String,
     server_addr: String,
+    /// Whether `server_addr` came from a Transfer packet.
+    transferred: bool,
 }
 
 #[derive(Resource, Default)]
@@ -78,45 +91,27 @@ struct ConfigurationState {
     finished: bool,
 }
 
-#[derive(Resource)]
-struct DebugPacketCounter {
-    seen: usize,
-    log_after_config: bool,
-    unknown_logged: usize,
-}
-
-#[derive(Resource, Default)]
-struct TickEndState {
-    last_sent_seconds: f64,
-}
-
 #[derive(Resource, Default)]
 struct BrandState {
     sent_brand: bool,
 }
 
-impl Default for DebugPacketCounter {
-    fn default() -> Self {
-        Self {
-            seen: 0,
-            // Log a handful of packets as soon as we hit play so we can see
-            // whether chunk traffic is flowing.
-            log_after_config: false,
-            unknown_logged: 0,
-        }
-    }
-}
-
 pub(crate) fn build(app: &mut App) {
     app.init_state::<LoginState>();
     app.init_resource::<ConfigurationState>();
-    app.init_resource::<DebugPacketCounter>();
-    app.init_resource::<TickEndState>();
     app.init_resource::<BrandState>();
 
+    app.add_systems(
+        Update,
+        apply_codec_limits
+            .run_if(resource_changed::<CodecLimits>)
+            .in_set(BrineSystems::ProtocolDecode),
+    );
+
     protocol_discovery::build(app);
     login::build(app);
     play::build(app);
+    transfer::build(app);
 }
 
 fn make_handshake_packet(protocol_version: i32, next_state: i32) -> Packet {
@@ -130,33 +125,58 @@ fn make_handshake_packet(protocol_version: i32, next_state: i32) -> Packet {
     )))
 }
 
+/// Applies the configured [`CodecLimits`] to the codec, which shares them
+/// with any running connection.
+fn apply_codec_limits(limits: Res<CodecLimits>, net_resource: Res<NetworkResource<ProtocolCodec>>) {
+    net_resource.codec().set_limits(*limits);
+}
+
 /// System that listens for any connection failure event and emits a LoginFailure event.
 fn handle_connection_error(
     mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
     mut login_failure_events: MessageWriter<Disconnect>,
-    mut login_state: ResMut<NextState<LoginState>>,
+    mut login_transitions: LoginTransitions,
 ) {
     for event in network_events.read() {
-        if let NetworkEvent::Error(NetworkError::ConnectFailed(io_error)) = event {
-            error!("Connection failed: {}", io_error);
+        if let NetworkEvent::Error(NetworkError::ConnectFailed {
+            server_addr,
+            source,
+        }) = event
+        {
+            error!("Connection to {} failed: {}", server_addr, source);
 
             login_failure_events.write(Disconnect {
-                reason: format!("Connection failed: {}", io_error),
+                reason: format!("Connection to {} failed: {}", server_addr, source),
+                kind: DisconnectKind::Network,
             });
 
-            login_state.set(LoginState::Idle);
+            login_transitions.fire(LoginTrigger::ConnectFailed);
             break;
         }
     }
 }
 
+/// Reports a kick from a server older than 1.7, which closes the connection
+/// after sending it.
+fn on_legacy_kick(kick: &LegacyKick, disconnect_events: &mut MessageWriter<Disconnect>) {
+    let reason = kick.disconnect_reason();
+    error!("{}", reason);
+
+    disconnect_events.write(Disconnect {
+        reason,
+        kind: DisconnectKind::Incompatible,
+    });
+}
+
 mod protocol_discovery {
     use super::*;
 
     pub(crate) fn build(app: &mut App) {
         app.add_systems(
             Update,
-            await_login_event_then_connect.run_if(in_state(LoginState::Idle)),
+            await_login_event_then_connect
+                .run_if(in_state(LoginState::Idle))
+                .in_set(BrineSystems::ProtocolDecode),
         );
         app.add_systems(
             Update,
@@ -164,51 +184,79 @@ mod protocol_discovery {
                 handle_connection_error,
                 await_connect_then_send_handshake_and_status_request,
             )
-                .run_if(in_state(LoginState::StatusAwaitingConnect)),
+                .run_if(in_state(LoginState::StatusAwaitingConnect))
+                .in_set(BrineSystems::ProtocolDecode),
         );
         app.add_systems(
             Update,
             await_response_then_send_status_ping
-                .run_if(in_state(LoginState::StatusAwaitingResponse)),
+                .run_if(in_state(LoginState::StatusAwaitingResponse))
+                .in_set(BrineSystems::ProtocolDecode),
         );
         app.add_systems(
             Update,
             await_disconnect_then_connect_for_login
-                .run_if(in_state(LoginState::StatusAwaitingDisconnect)),
+                .run_if(in_state(LoginState::StatusAwaitingDisconnect))
+                .in_set(BrineSystems::ProtocolDecode),
         );
     }
 
     fn await_login_event_then_connect(
         mut login_events: MessageReader<Login>,
-        mut login_state: ResMut<NextState<LoginState>>,
+        mut login_transitions: LoginTransitions,
         mut net_resource: ResMut<NetworkResource<ProtocolCodec>>,
+        protocol_override: Option<Res<ProtocolOverride>>,
         mut commands: Commands,
     ) {
         if let Some(login) = login_events.read().last() {
             info!("Logging in to server {}", login.server);
 
-            debug!("Connecting to server for protocol discovery.");
-            net_resource.connect(login.server.clone());
-
             commands.insert_resource(LoginResource {
                 username: login.username.clone(),
                 server_addr: login.server.clone(),
+                transferred: false,
             });
 
-            login_state.set(LoginState::StatusAwaitingConnect);
+            match protocol_override.as_deref() {
+                Some(protocol_override) if protocol_override.skip_status => {
+                    debug!(
+                        "Skipping protocol discovery; connecting to server for login with protocol version {}.",
+                        protocol_override.version
+                    );
+                    net_resource
+                        .codec()
+                        .set_protocol_version(protocol_override.version);
+                    net_resource.connect(login.server.clone());
+
+                    login_transitions.fire(LoginTrigger::LoginRequestedPinned);
+                }
+                _ => {
+                    debug!("Connecting to server for protocol discovery.");
+                    net_resource.connect(login.server.clone());
+
+                    login_transitions.fire(LoginTrigger::LoginRequested);
+                }
+            }
         }
     }
 
     fn await_connect_then_send_handshake_and_status_request(
         mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
         mut packet_writer: CodecWriter<ProtocolCodec>,
-        mut login_state: ResMut<NextState<LoginState>>,
+        mut login_transitions: LoginTransitions,
         net_resource: Res<NetworkResource<ProtocolCodec>>,
+        protocol_override: Option<Res<ProtocolOverride>>,
     ) {
         for event in network_events.read() {
             if let NetworkEvent::Connected = event {
                 debug!("Connection established. Sending Handshake and StatusRequest packets.");
 
+                if let Some(protocol_override) = protocol_override.as_deref() {
+                    net_resource
+                        .codec()
+                        .set_protocol_version(protocol_override.version);
+                }
+
                 let handshake = make_handshake_packet(
                     net_resource.codec().protocol_version(),
                     HANDSHAKE_STATUS_NEXT,
@@ -221,7 +269,7 @@ mod protocol_discovery {
                 ));
                 packet_writer.send(status_request);
 
-                login_state.set(LoginState::StatusAwaitingResponse);
+                login_transitions.fire(LoginTrigger::Connected);
                 break;
             }
         }
@@ -230,11 +278,19 @@ mod protocol_discovery {
     fn await_response_then_send_status_ping(
         mut packet_reader: CodecReader<ProtocolCodec>,
         mut packet_writer: CodecWriter<ProtocolCodec>,
-        mut login_state: ResMut<NextState<LoginState>>,
+        mut login_transitions: LoginTransitions,
+        mut login_failure_events: MessageWriter<Disconnect>,
         net_resource: Res<NetworkResource<ProtocolCodec>>,
+        protocol_override: Option<Res<ProtocolOverride>>,
     ) {
         for packet in packet_reader.iter() {
-            if let Packet::Known(packet::Packet::StatusClientboundServerInfo(_)) = packet {
+            if let Packet::Legacy(kick) = packet {
+                on_legacy_kick(kick, &mut login_failure_events);
+                login_transitions.fire(LoginTrigger::Incompatible);
+                break;
+            }
+
+            if let Packet::Known(packet::Packet::StatusClientboundServerInfo(info)) = packet {
                 // The codec will have already switched its internal protocol
                 // version in response to decoding the StatusResponse packet,
                 // so just read it from there.
@@ -245,13 +301,50 @@ mod protocol_discovery {
                     protocol_version
                 );
 
+                // Ping either way so that the server closes the connection.
                 debug!("Sending StatusPing.");
                 let status_ping = Packet::Known(packet::Packet::StatusServerboundPing(Box::new(
                     packet::status::serverbound::Ping { time: 0 },
                 )));
                 packet_writer.send(status_ping);
 
-                login_state.set(LoginState::StatusAwaitingDisconnect);
+                if let Some(protocol_override) = protocol_override.as_deref() {
+                    if protocol_override.version != protocol_version {
+                        warn!(
+                            "Server uses protocol version {}; using {} as requested",
+                            protocol_version, protocol_override.version
+                        );
+                    }
+                    net_resource
+                        .codec()
+                        .set_protocol_version(protocol_override.version);
+                } else if let Some(supported) = supported_version(protocol_version) {
+                    info!("Server uses Minecraft {}", supported.label());
+                } else {
+                    // Name the version the server runs, as far as it says.
+                    let version_name = serde_json::from_str::<serde_json::Value>(&info.response)
+                        .ok()
+                        .and_then(|status| status["version"]["name"].as_str().map(String::from));
+                    let server = match version_name {
+                        Some(name) => format!(
+                            "Server requires Minecraft {} (protocol {})",
+                            name, protocol_version
+                        ),
+                        None => format!("Server uses protocol version {}", protocol_version),
+                    };
+                    let reason =
+                        format!("{}; brine supports {}", server, supported_versions_list());
+                    error!("{}", reason);
+                    login_failure_events.write(Disconnect {
+                        reason,
+                        kind: DisconnectKind::Incompatible,
+                    });
+
+                    login_transitions.fire(LoginTrigger::Incompatible);
+                    break;
+                }
+
+                login_transitions.fire(LoginTrigger::StatusReceived);
                 break;
             }
         }
@@ -259,7 +352,7 @@ mod protocol_discovery {
 
     fn await_disconnect_then_connect_for_login(
         mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
-        mut login_state: ResMut<NextState<LoginState>>,
+        mut login_transitions: LoginTransitions,
         mut net_resource: ResMut<NetworkResource<ProtocolCodec>>,
         login_resource: Res<LoginResource>,
     ) {
@@ -269,7 +362,7 @@ mod protocol_discovery {
                 debug!("Connecting to server for login.");
                 net_resource.connect(login_resource.server_addr.clone());
 
-                login_state.set(LoginState::LoginAwaitingConnect);
+                login_transitions.fire(LoginTrigger::StatusClosed);
             }
         }
     }
@@ -286,11 +379,14 @@ mod login {
                 handle_connection_error,
                 await_connect_then_send_handshake_and_login_start,
             )
-                .run_if(in_state(LoginState::LoginAwaitingConnect)),
+                .run_if(in_state(LoginState::LoginAwaitingConnect))
+                .in_set(BrineSystems::ProtocolDecode),
         );
         app.add_systems(
             Update,
-            await_login_success.run_if(in_state(LoginState::LoginAwaitingSuccess)),
+            await_login_success
+                .run_if(in_state(LoginState::LoginAwaitingSuccess))
+                .in_set(BrineSystems::ProtocolDecode),
         );
     }
 
@@ -308,7 +404,7 @@ mod login {
     fn await_connect_then_send_handshake_and_login_start(
         mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
         mut packet_writer: CodecWriter<ProtocolCodec>,
-        mut login_state: ResMut<NextState<LoginState>>,
+        mut login_transitions: LoginTransitions,
         login_resource: Res<LoginResource>,
         net_resource: Res<NetworkResource<ProtocolCodec>>,
     ) {
@@ -318,7 +414,12 @@ mod login {
 
                 let protocol_version = net_resource.codec().protocol_version();
 
-                let handshake = make_handshake_packet(protocol_version, HANDSHAKE_LOGIN_NEXT);
+                let next_state = if login_resource.transferred {
+                    HANDSHAKE_TRANSFER_NEXT
+                } else {
+                    HANDSHAKE_LOGIN_NEXT
+                };
+                let handshake = make_handshake_packet(protocol_version, next_state);
                 trace!("{:#?}", &handshake);
                 packet_writer.send(handshake);
 
@@ -327,7 +428,7 @@ mod login {
                 trace!("{:#?}", &login_start);
                 packet_writer.send(login_start);
 
-                login_state.set(LoginState::LoginAwaitingSuccess);
+                login_transitions.fire(LoginTrigger::Connected);
                 break;
             }
         }
@@ -340,14 +441,14 @@ mod login {
         mut packet_writer: CodecWriter<ProtocolCodec>,
         mut login_success_events: MessageWriter<LoginSuccess>,
         mut disconnect_events: MessageWriter<Disconnect>,
-        mut login_state: ResMut<NextState<LoginState>>,
+        mut login_transitions: LoginTransitions,
     ) {
         let mut on_login_success = |username: String, uuid: Uuid| {
             info!("Successfully logged in to server.");
 
             login_success_events.write(LoginSuccess { username, uuid });
 
-            login_state.set(LoginState::Play);
+            login_transitions.fire(LoginTrigger::LoginSucceeded);
         };
 
         for packet in packet_reader.iter() {
@@ -371,9 +472,18 @@ mod login {
                     let message = format!("Login disconnect: {}", login_disconnect.reason);
                     error!("{}", &message);
 
-                    disconnect_events.write(Disconnect { reason: message });
+                    disconnect_events.write(Disconnect {
+                        reason: message,
+                        kind: DisconnectKind::Kicked,
+                    });
 
-                    login_state.set(LoginState::Idle);
+                    login_transitions.fire(LoginTrigger::Kicked);
+                    break;
+                }
+
+                Packet::Legacy(kick) => {
+                    on_legacy_kick(kick, &mut disconnect_events);
+                    login_transitions.fire(LoginTrigger::Incompatible);
                     break;
                 }
 
@@ -392,16 +502,18 @@ mod play {
             (
                 respond_to_keep_alive_packets,
                 handle_configuration_start,
-                respond_to_position_packets,
                 respond_to_chunk_batch_packets,
                 respond_to_cookie_requests,
-                debug_log_incoming_packets,
                 log_network_events,
-                send_tick_end,
                 send_brand_message,
                 handle_disconnect,
             )
-                .run_if(in_state(LoginState::Play)),
+                .run_if(in_state(LoginState::Play))
+                .in_set(BrineSystems::ProtocolDecode),
+        );
+        app.add_systems(
+            FixedUpdate,
+            send_tick_end.run_if(in_state(LoginState::Play)),
         );
     }
 
@@ -409,15 +521,19 @@ mod play {
         mut packet_reader: CodecReader<ProtocolCodec>,
         mut packet_writer: CodecWriter<ProtocolCodec>,
         mut config_state: ResMut<ConfigurationState>,
+        mut reconfigure_events: MessageWriter<Reconfigure>,
+        net_resource: Res<NetworkResource<ProtocolCodec>>,
+        policy: Res<ConfigurationPolicy>,
     ) {
+        let information = &policy.client_information;
         let send_config_settings = |writer: &mut CodecWriter<ProtocolCodec>| {
             let settings = Packet::Known(packet::Packet::ConfigurationServerboundSettings(
                 Box::new(packet::configuration::serverbound::Settings {
-                    locale: "en_us".to_string(),
-                    viewDistance: 12,
+                    locale: information.locale.clone(),
+                    viewDistance: information.view_distance,
                     chatFlags: VarInt(0),
                     chatColors: true,
-                    skinParts: 0x7F,
+                    skinParts: information.skin_parts,
                     mainHand: VarInt(1), // 0=left,1=right
                     enableTextFiltering: false,
                     enableServerListing: true,
@@ -430,11 +546,11 @@ mod play {
         let send_play_settings = |writer: &mut CodecWriter<ProtocolCodec>| {
             let settings = Packet::Known(packet::Packet::PlayServerboundSettings(Box::new(
                 packet::play::serverbound::Settings {
-                    locale: "en_us".to_string(),
-                    viewDistance: 12,
+                    locale: information.locale.clone(),
+                    viewDistance: information.view_distance,
                     chatFlags: VarInt(0),
                     chatColors: true,
-                    skinParts: 0x7F,
+                    skinParts: information.skin_parts,
                     mainHand: VarInt(1), // 0=left,1=right
                     enableTextFiltering: false,
                     enableServerListing: true,
@@ -456,8 +572,16 @@ mod play {
 
         for packet in packet_reader.iter() {
             if let Packet::Known(packet::Packet::PlayClientboundStartConfiguration(_)) = packet {
+                // The server waits for this before sending any configuration
+                // packets. The codec has already switched to Configuration.
+                debug!("StartConfiguration received; acknowledging and entering config phase");
+                let ack = Packet::Known(packet::Packet::PlayServerboundConfigurationAcknowledged(
+                    Box::new(packet::play::serverbound::ConfigurationAcknowledged {}),
+                ));
+                packet_writer.send(ack);
+                reconfigure_events.write(Reconfigure);
+
                 // Send default client settings expected during configuration, then finish configuration.
-                debug!("StartConfiguration received; entering config phase");
 
                 config_state.sent_settings = false;
                 config_state.finished = false;
@@ -473,15 +597,20 @@ mod play {
             {
                 ensure_config_settings(&mut packet_writer, &mut config_state);
 
+                let mut packs = select_known_packs.packs.clone();
+                packs.values.retain(|pack| {
+                    policy
+                        .known_packs
+                        .claims(&pack.namespace, &pack.id, &pack.version)
+                });
                 debug!(
-                    "SelectKnownPacks received with {} packs; echoing selection",
-                    select_known_packs.packs.values.len()
+                    "SelectKnownPacks received with {} packs; claiming {}",
+                    select_known_packs.packs.values.len(),
+                    packs.values.len()
                 );
                 let select_known_packs =
                     Packet::Known(packet::Packet::ConfigurationServerboundSelectKnownPacks(
-                        Box::new(packet::configuration::serverbound::SelectKnownPacks {
-                            packs: select_known_packs.packs.clone(),
-                        }),
+                        Box::new(packet::configuration::serverbound::SelectKnownPacks { packs }),
                     ));
                 packet_writer.send(select_known_packs);
                 continue;
@@ -533,10 +662,12 @@ mod play {
                 send_play_settings(&mut packet_writer);
 
                 // Notify the server that the client finished loading into the play state.
-                let player_loaded = Packet::Known(packet::Packet::PlayServerboundPlayerLoaded(
-                    Box::new(packet::play::serverbound::PlayerLoaded {}),
-                ));
-                packet_writer.send(player_loaded);
+                if shims_for(net_resource.codec().protocol_version()).player_loaded {
+                    let player_loaded = Packet::Known(packet::Packet::PlayServerboundPlayerLoaded(
+                        Box::new(packet::play::serverbound::PlayerLoaded {}),
+                    ));
+                    packet_writer.send(player_loaded);
+                }
                 break;
             }
 
@@ -553,90 +684,6 @@ mod play {
         }
     }
 
-    fn debug_log_incoming_packets(
-        mut packet_reader: CodecReader<ProtocolCodec>,
-        mut counter: ResMut<DebugPacketCounter>,
-    ) {
-        const MAX_UNKNOWN_LOGS: usize = 64;
-        const MAX_GENERIC_LOGS: usize = 64;
-
-        for packet in packet_reader.iter() {
-            if let Packet::Known(packet::Packet::ConfigurationClientboundFinishConfiguration(_)) =
-                &packet
-            {
-                counter.log_after_config = true;
-                counter.seen = 0;
-                debug!("Configuration finished; enabling play-phase packet logging");
-                continue;
-            }
-
-            if let Packet::Unknown(unknown) = &packet {
-                if counter.unknown_logged < MAX_UNKNOWN_LOGS {
-                    debug!(
-                        "Unknown inbound packet id=0x{:02X} ({} bytes): {:?}",
-                        unknown.packet_id,
-                        unknown.body.len(),
-                        unknown
-                    );
-                    counter.unknown_logged += 1;
-                } else if counter.unknown_logged == MAX_UNKNOWN_LOGS {
-                    debug!(
-                        "Suppressing further unknown packet logs after {} entries",
-                        MAX_UNKNOWN_LOGS
-                    );
-                    counter.unknown_logged += 1;
-                }
-            }
-
-            // Always surface chunk-related packets to help debugging world loading.
-            match packet {
-                Packet::Known(packet::Packet::PlayClientboundMapChunk(_))
-                | Packet::Known(packet::Packet::PlayClientboundChunkBatchStart(_))
-                | Packet::Known(packet::Packet::PlayClientboundChunkBatchFinished(_)) => {
-                    debug!("Incoming packet: {:?}", packet);
-                    continue;
-                }
-                Packet::Known(packet::Packet::PlayClientboundPosition(pos)) => {
-                    debug!(
-                        "PlayClientboundPosition teleport_id={}, pos=({}, {}, {}), angles=({}, {})",
-                        pos.teleportId.0, pos.x, pos.y, pos.z, pos.yaw, pos.pitch
-                    );
-                    continue;
-                }
-                Packet::Known(packet::Packet::PlayClientboundKeepAlive(_)) => {
-                    debug!("Incoming packet: {:?}", packet);
-                    continue;
-                }
-                Packet::Known(packet::Packet::PlayClientboundDeclareRecipes(recipes)) => {
-                    debug!(
-                        "DeclareRecipes packet: {} recipes, {} stonecutter entries",
-                        recipes.recipes.values.len(),
-                        recipes.stoneCutterRecipes.values.len()
-                    );
-                    continue;
-                }
-                Packet::Known(packet::Packet::PlayClientboundTags(tags)) => {
-                    debug!("Tags packet: {} tag groups", tags.tags.values.len());
-                    continue;
-                }
-                _ => {
-                    // Keep a small rolling log of generic packets once in play to avoid drowning
-                    // in recipe/tag spam.
-                    if counter.log_after_config && counter.seen < MAX_GENERIC_LOGS {
-                        debug!("Incoming packet: {:?}", packet);
-                        counter.seen += 1;
-                    } else if counter.log_after_config && counter.seen == MAX_GENERIC_LOGS {
-                        debug!(
-                            "Packet debug log limit ({}) reached; suppressing further generic logs",
-                            MAX_GENERIC_LOGS
-                        );
-                        counter.seen += 1;
-                    }
-                }
-            }
-        }
-    }
-
     fn log_network_events(mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>) {
         for event in network_events.read() {
             match event {
@@ -649,7 +696,7 @@ mod play {
 
     fn send_brand_message(
         mut packet_reader: CodecReader<ProtocolCodec>,
-        mut packet_writer: CodecWriter<ProtocolCodec>,
+        mut plugin_message_events: MessageWriter<PluginMessage>,
         mut brand_state: ResMut<BrandState>,
     ) {
         for packet in packet_reader.iter() {
@@ -663,77 +710,33 @@ mod play {
                     }
                     data.extend_from_slice(brand.as_bytes());
 
-                    let payload = Packet::Known(packet::Packet::PlayServerboundCustomPayload(
-                        Box::new(packet::play::serverbound::CustomPayload {
-                            channel: "minecraft:brand".to_string(),
-                            data,
-                        }),
-                    ));
-                    packet_writer.send(payload);
+                    plugin_message_events.write(PluginMessage {
+                        channel: BRAND_CHANNEL.to_string(),
+                        data,
+                    });
                     brand_state.sent_brand = true;
-                    debug!("Sent brand plugin message (minecraft:brand=brine)");
+                    debug!("Sent brand plugin message ({}={})", BRAND_CHANNEL, brand);
                 }
             }
         }
     }
 
+    /// System that ends each client tick with a TickEnd, as vanilla does.
+    ///
+    /// Runs in `FixedUpdate`, once per game tick.
     fn send_tick_end(
         mut packet_writer: CodecWriter<ProtocolCodec>,
-        time: Res<Time>,
         config_state: Res<ConfigurationState>,
-        mut tick_state: ResMut<TickEndState>,
+        net_resource: Res<NetworkResource<ProtocolCodec>>,
     ) {
-        if !config_state.finished {
+        if !config_state.finished || !shims_for(net_resource.codec().protocol_version()).tick_end {
             return;
         }
 
-        // Send a periodic TickEnd to keep the server's tick stream moving.
-        let now = time.elapsed_secs_f64();
-        if now - tick_state.last_sent_seconds > 1.0 {
-            let tick_end = Packet::Known(packet::Packet::PlayServerboundTickEnd(Box::new(
-                packet::play::serverbound::TickEnd {},
-            )));
-            packet_writer.send(tick_end);
-            tick_state.last_sent_seconds = now;
-            debug!("Sent PlayServerboundTickEnd");
-        }
-    }
-
-    fn respond_to_position_packets(
-        mut packet_reader: CodecReader<ProtocolCodec>,
-        mut packet_writer: CodecWriter<ProtocolCodec>,
-    ) {
-        for packet in packet_reader.iter() {
-            match packet {
-                Packet::Known(packet::Packet::PlayClientboundPosition(pos)) => {
-                    let confirm = Packet::Known(packet::Packet::PlayServerboundTeleportConfirm(
-                        Box::new(packet::play::serverbound::TeleportConfirm {
-                            teleportId: pos.teleportId,
-                        }),
-                    ));
-                    packet_writer.send(confirm);
-
-                    // Echo the server's suggested position and angles to finish the teleport.
-                    let movement = Packet::Known(packet::Packet::PlayServerboundPositionLook(
-                        Box::new(packet::play::serverbound::PositionLook {
-                            x: pos.x,
-                            y: pos.y,
-                            z: pos.z,
-                            yaw: pos.yaw,
-                            pitch: pos.pitch,
-                            flags: 0,
-                        }),
-                    ));
-                    packet_writer.send(movement);
-
-                    debug!(
-                        "Position packet received: teleport_id={}, pos=({}, {}, {}), angles=({}, {})",
-                        pos.teleportId.0, pos.x, pos.y, pos.z, pos.yaw, pos.pitch
-                    );
-                }
-                _ => {}
-            }
-        }
+        let tick_end = Packet::Known(packet::Packet::PlayServerboundTickEnd(Box::new(
+            packet::play::serverbound::TickEnd {},
+        )));
+        packet_writer.send(tick_end);
     }
 
     fn respond_to_chunk_batch_packets(
@@ -791,6 +794,11 @@ mod play {
                         },
                     )))
                 }
+                Packet::Known(packet::Packet::PlayClientboundPing(ping)) => {
+                    Packet::Known(packet::Packet::PlayServerboundPong(Box::new(
+                        packet::play::serverbound::Pong { id: ping.id },
+                    )))
+                }
 
                 _ => continue,
             };
@@ -824,24 +832,268 @@ mod play {
         }
     }
 
+    /// System that ends the session when the server kicks the client or the
+    /// connection is lost, so that the next Login starts over.
     fn handle_disconnect(
         mut packet_reader: CodecReader<ProtocolCodec>,
+        mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
         mut disconnect_events: MessageWriter<Disconnect>,
+        mut login_transitions: LoginTransitions,
+        mut net_resource: ResMut<NetworkResource<ProtocolCodec>>,
+        mut config_state: ResMut<ConfigurationState>,
+        mut brand_state: ResMut<BrandState>,
     ) {
+        let mut disconnect = None;
         for packet in packet_reader.iter() {
-            match packet {
-                Packet::Known(packet::Packet::PlayClientboundKickDisconnect(disconnect)) => {
-                    let reason = format!("{:?}", disconnect.reason);
-                    debug!("Play disconnect: {}", &reason);
-                    disconnect_events.write(Disconnect { reason });
+            let reason = match packet {
+                Packet::Known(packet::Packet::PlayClientboundKickDisconnect(kick)) => {
+                    format!("{:?}", kick.reason)
                 }
-                Packet::Known(packet::Packet::ConfigurationClientboundDisconnect(disconnect)) => {
-                    let reason = format!("{:?}", disconnect.reason);
-                    debug!("Configuration disconnect: {}", &reason);
-                    disconnect_events.write(Disconnect { reason });
+                Packet::Known(packet::Packet::ConfigurationClientboundDisconnect(kick)) => {
+                    format!("{:?}", kick.reason)
                 }
-                _ => {}
+                _ => continue,
+            };
+            debug!("Kicked: {}", &reason);
+            disconnect = Some(Disconnect {
+                reason,
+                kind: DisconnectKind::Kicked,
+            });
+            break;
+        }
+
+        // The server closes the connection right after kicking the client, so
+        // only report a lost connection if there was no kick.
+        let connection_lost = network_events
+            .read()
+            .any(|event| matches!(event, NetworkEvent::Disconnected));
+        if disconnect.is_none() && connection_lost {
+            disconnect = Some(Disconnect {
+                reason: String::from("Connection lost"),
+                kind: DisconnectKind::Network,
+            });
+        }
+
+        let Some(disconnect) = disconnect else {
+            return;
+        };
+
+        net_resource.disconnect();
+        *config_state = ConfigurationState::default();
+        *brand_state = BrandState::default();
+
+        let trigger = match disconnect.kind {
+            DisconnectKind::Kicked => LoginTrigger::Kicked,
+            _ => LoginTrigger::ConnectionLost,
+        };
+        disconnect_events.write(disconnect);
+        login_transitions.fire(trigger);
+    }
+}
+
+mod transfer {
+    use super::*;
+
+    pub(crate) fn build(app: &mut App) {
+        app.add_systems(
+            Update,
+            handle_transfer_packets
+                .run_if(in_state(LoginState::Play))
+                .in_set(BrineSystems::ProtocolDecode),
+        );
+        app.add_systems(
+            Update,
+            connect_to_transfer_target
+                .run_if(in_state(LoginState::Transferring))
+                .in_set(BrineSystems::ProtocolDecode),
+        );
+    }
+
+    /// System that listens for Transfer packets, drops the current connection,
+    /// and resets everything tied to it.
+    fn handle_transfer_packets(
+        mut packet_reader: CodecReader<ProtocolCodec>,
+        mut transfer_events: MessageWriter<Transfer>,
+        mut login_transitions: LoginTransitions,
+        mut net_resource: ResMut<NetworkResource<ProtocolCodec>>,
+        mut login_resource: ResMut<LoginResource>,
+        mut config_state: ResMut<ConfigurationState>,
+        mut brand_state: ResMut<BrandState>,
+    ) {
+        for packet in packet_reader.iter() {
+            let (host, port) = match packet {
+                Packet::Known(packet::Packet::ConfigurationClientboundTransfer(transfer)) => {
+                    (&transfer.host, transfer.port.0)
+                }
+                Packet::Known(packet::Packet::PlayClientboundTransfer(transfer)) => {
+                    (&transfer.host, transfer.port.0)
+                }
+                _ => continue,
+            };
+
+            let server_addr = format!("{}:{}", host, port);
+            info!("Server transferred us to {}", server_addr);
+
+            net_resource.disconnect();
+            *config_state = ConfigurationState::default();
+            *brand_state = BrandState::default();
+
+            login_resource.server_addr = server_addr.clone();
+            login_resource.transferred = true;
+
+            transfer_events.write(Transfer {
+                server: server_addr,
+            });
+            login_transitions.fire(LoginTrigger::Transferred);
+            break;
+        }
+    }
+
+    fn connect_to_transfer_target(
+        mut login_transitions: LoginTransitions,
+        mut net_resource: ResMut<NetworkResource<ProtocolCodec>>,
+        login_resource: Res<LoginResource>,
+    ) {
+        debug!("Connecting to {} for login.", login_resource.server_addr);
+        net_resource.connect(login_resource.server_addr.clone());
+        login_transitions.fire(LoginTrigger::TransferConnecting);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use bevy::state::app::StatesPlugin;
+    use brine_proto::{event::clientbound::ChunkData, LoginTransition, ProtocolPlugin};
+
+    use crate::{
+        backend_stevenarella::fake_server::{FakeServer, CHUNK_BLOCK_STATE},
+        codec::ProtocolAnomaly,
+        version::get_protocol_version,
+        ProtocolBackendPlugin,
+    };
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(20);
+
+    /// Logs in to `server` until the server has seen the whole exchange and a
+    /// chunk has arrived. Returns the states the client went through and the
+    /// chunks it received.
+    fn log_in(
+        server: FakeServer,
+        backend: ProtocolBackendPlugin,
+    ) -> (Vec<LoginState>, Vec<ChunkData>) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, ProtocolPlugin, backend));
+        app.world_mut()
+            .resource_mut::<Messages<Login>>()
+            .write(Login {
+                server: server.addr().to_string(),
+                username: String::from("Player"),
+            });
+
+        let mut server = Some(server);
+        let mut logged_in = false;
+        let mut chunks = Vec::new();
+        let mut states = Vec::new();
+        let deadline = Instant::now() + TIMEOUT;
+        while server.is_some() || !logged_in || chunks.is_empty() {
+            assert!(Instant::now() < deadline, "timed out logging in");
+
+            app.update();
+
+            let world = app.world_mut();
+            if let Some(disconnect) = world.resource_mut::<Messages<Disconnect>>().drain().next() {
+                panic!("disconnected: {}", disconnect.reason);
             }
+            if let Some(anomaly) = world
+                .resource_mut::<Messages<ProtocolAnomaly>>()
+                .drain()
+                .next()
+            {
+                panic!("{}", anomaly);
+            }
+            logged_in |= world
+                .resource_mut::<Messages<LoginSuccess>>()
+                .drain()
+                .count()
+                > 0;
+            chunks.extend(world.resource_mut::<Messages<ChunkData>>().drain());
+            states.extend(
+                world
+                    .resource_mut::<Messages<LoginTransition>>()
+                    .drain()
+                    .map(|transition| transition.to),
+            );
+
+            // The server checks the client's side of the exchange.
+            if server.as_ref().is_some_and(FakeServer::is_finished) {
+                server.take().unwrap().join().unwrap();
+            }
+
+            thread::sleep(Duration::from_millis(5));
         }
+
+        assert_eq!(
+            *app.world().resource::<State<LoginState>>().get(),
+            LoginState::Play
+        );
+
+        (states, chunks)
+    }
+
+    #[test]
+    fn logs_in_to_fake_server() {
+        let server = FakeServer::start(get_protocol_version("1.21.4").unwrap()).unwrap();
+
+        let (states, chunks) = log_in(
+            server,
+            ProtocolBackendPlugin::default().with_strict_decoding(),
+        );
+
+        assert_eq!(
+            states,
+            [
+                LoginState::StatusAwaitingConnect,
+                LoginState::StatusAwaitingResponse,
+                LoginState::StatusAwaitingDisconnect,
+                LoginState::LoginAwaitingConnect,
+                LoginState::LoginAwaitingSuccess,
+                LoginState::Play,
+            ]
+        );
+
+        let chunk = &chunks[0].chunk_data;
+        assert_eq!((chunk.chunk_x, chunk.chunk_z), (0, 0));
+        assert_eq!(
+            chunk.sections[0].get_block((1u8, 2, 3)).unwrap().0,
+            CHUNK_BLOCK_STATE
+        );
+    }
+
+    #[test]
+    fn pinned_protocol_version_skips_status_ping() {
+        let protocol_version = get_protocol_version("1.21.4").unwrap();
+        let server = FakeServer::start_without_status(protocol_version).unwrap();
+
+        let backend = ProtocolBackendPlugin::default()
+            .with_strict_decoding()
+            .with_protocol_version(protocol_version)
+            .without_status_ping();
+        let (states, _) = log_in(server, backend);
+
+        assert_eq!(
+            states,
+            [
+                LoginState::LoginAwaitingConnect,
+                LoginState::LoginAwaitingSuccess,
+                LoginState::Play,
+            ]
+        );
     }
 }
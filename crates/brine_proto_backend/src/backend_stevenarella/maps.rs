@@ -0,0 +1,172 @@
+//! Translation of the Map Data packet into [`brine_proto`] events.
+//!
+//! The packet's icons and pixel patch are both optional, the patch's height
+//! only being present when its width is non-zero, which the generated parsers
+//! don't follow. Like Player Info Update, the packet is passed through
+//! undecoded (see [`PacketShims::map_data`][crate::version::PacketShims::map_data])
+//! and parsed here.
+
+use std::io::{self, Cursor, Read};
+
+use bevy::prelude::*;
+use byteorder::ReadBytesExt;
+
+use brine_net::{CodecReader, NetworkResource};
+use brine_proto::{
+    event::{clientbound::MapData, MapIcon, MapPatch},
+    BrineSystems,
+};
+
+use crate::version::shims_for;
+
+use super::{
+    codec::{Packet, ProtocolCodec},
+    players::{invalid_data, read_text_component, read_varint},
+};
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        handle_map_packets.in_set(BrineSystems::ProtocolDecode),
+    );
+}
+
+/// System that listens for Map Data packets and sends [`MapData`] events to
+/// the client application.
+fn handle_map_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut map_events: MessageWriter<MapData>,
+) {
+    let shims = shims_for(net_resource.codec().protocol_version());
+
+    for packet in packet_reader.iter() {
+        let Packet::Unknown(unknown) = packet else {
+            continue;
+        };
+        if unknown.packet_id != shims.map_data {
+            continue;
+        }
+
+        match parse_map_data(&unknown.body) {
+            Ok(map_data) => {
+                map_events.write(map_data);
+            }
+            Err(e) => warn!("Failed to parse Map Data: {}", e),
+        }
+    }
+}
+
+fn parse_map_data(body: &[u8]) -> io::Result<MapData> {
+    let mut reader = Cursor::new(body);
+
+    let map_id = read_varint(&mut reader)? as i32;
+    let scale = reader.read_u8()?;
+    let locked = reader.read_u8()? != 0;
+
+    let icons = if reader.read_u8()? != 0 {
+        let count = read_varint(&mut reader)?;
+        let icons = (0..count)
+            .map(|_| read_icon(&mut reader))
+            .collect::<io::Result<_>>()?;
+        Some(icons)
+    } else {
+        None
+    };
+
+    let width = reader.read_u8()?;
+    let patch = if width > 0 {
+        let height = reader.read_u8()?;
+        let x = reader.read_u8()?;
+        let z = reader.read_u8()?;
+
+        let length = read_varint(&mut reader)?;
+        if length != u64::from(width) * u64::from(height) {
+            return Err(invalid_data("map patch size doesn't match its colors"));
+        }
+        let mut colors = vec![0; length as usize];
+        reader.read_exact(&mut colors)?;
+
+        Some(MapPatch {
+            x,
+            z,
+            width,
+            height,
+            colors,
+        })
+    } else {
+        None
+    };
+
+    Ok(MapData {
+        map_id,
+        scale,
+        locked,
+        icons,
+        patch,
+    })
+}
+
+fn read_icon(reader: &mut Cursor<&[u8]>) -> io::Result<MapIcon> {
+    let kind = read_varint(reader)? as i32;
+    let x = reader.read_i8()?;
+    let z = reader.read_i8()?;
+    let direction = reader.read_u8()? & 0x0f;
+    let label = if reader.read_u8()? != 0 {
+        Some(read_text_component(reader)?)
+    } else {
+        None
+    };
+
+    Ok(MapIcon {
+        kind,
+        x,
+        z,
+        direction,
+        label,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_map_data() {
+        // Map 5, scale 2, unlocked, one labelled icon, a 2x1 patch at (3, 4).
+        let mut body = vec![5, 2, 0, 1, 1, 9, 0xf0, 16, 4, 1, 8, 0, 4];
+        body.extend_from_slice(b"Home");
+        body.extend_from_slice(&[2, 1, 3, 4, 2, 0x22, 0x23]);
+
+        assert_eq!(
+            parse_map_data(&body).unwrap(),
+            MapData {
+                map_id: 5,
+                scale: 2,
+                locked: false,
+                icons: Some(vec![MapIcon {
+                    kind: 9,
+                    x: -16,
+                    z: 16,
+                    direction: 4,
+                    label: Some(String::from("Home")),
+                }]),
+                patch: Some(MapPatch {
+                    x: 3,
+                    z: 4,
+                    width: 2,
+                    height: 1,
+                    colors: vec![0x22, 0x23],
+                }),
+            }
+        );
+
+        // Only the lock changed: no icons, no patch.
+        let locked = parse_map_data(&[5, 2, 1, 0, 0]).unwrap();
+        assert!(locked.locked);
+        assert_eq!((locked.icons, locked.patch), (None, None));
+
+        // A patch whose colors are cut short.
+        assert!(parse_map_data(&[5, 2, 0, 0, 2, 2, 0, 0, 3, 1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! Translation of the Set Entity Metadata packet into [`brine_proto`] events.
+//!
+//! The generated parsers predate the metadata value types of the supported
+//! versions, so the packet is passed through undecoded (see
+//! [`PacketShims::entity_metadata`][crate::version::PacketShims::entity_metadata])
+//! and parsed here. Two things are read: an entity's custom name and whether
+//! it is always shown, which every entity has at the same indices, and the
+//! first item in the metadata, which is the item of dropped items, item
+//! frames and thrown items. Other values before the item are skipped, and
+//! nothing after it is read.
+
+use std::io::{self, Cursor};
+
+use bevy::prelude::*;
+use byteorder::ReadBytesExt;
+
+use brine_net::{CodecReader, NetworkResource};
+use brine_proto::{
+    event::{
+        clientbound::{EntityCustomName, EntityItemSet},
+        ItemStack,
+    },
+    BrineSystems,
+};
+
+use crate::version::shims_for;
+
+use super::{
+    codec::{Packet, ProtocolCodec},
+    players::{invalid_data, read_string, read_text_component, read_varint, skip, skip_nbt},
+};
+
+/// Index that ends the metadata.
+const END_OF_METADATA: u8 = 0xff;
+
+/// Index of an entity's custom name.
+const INDEX_CUSTOM_NAME: u8 = 2;
+
+/// Index of whether an entity's custom name is always shown.
+const INDEX_CUSTOM_NAME_VISIBLE: u8 = 3;
+
+// Metadata value types (1.20.5 through 1.21.4).
+const TYPE_BYTE: u64 = 0;
+const TYPE_VARINT: u64 = 1;
+const TYPE_VARLONG: u64 = 2;
+const TYPE_FLOAT: u64 = 3;
+const TYPE_STRING: u64 = 4;
+const TYPE_TEXT_COMPONENT: u64 = 5;
+const TYPE_OPTIONAL_TEXT_COMPONENT: u64 = 6;
+const TYPE_SLOT: u64 = 7;
+const TYPE_BOOLEAN: u64 = 8;
+const TYPE_ROTATIONS: u64 = 9;
+const TYPE_POSITION: u64 = 10;
+const TYPE_OPTIONAL_POSITION: u64 = 11;
+const TYPE_DIRECTION: u64 = 12;
+const TYPE_OPTIONAL_UUID: u64 = 13;
+const TYPE_BLOCK_STATE: u64 = 14;
+const TYPE_OPTIONAL_BLOCK_STATE: u64 = 15;
+const TYPE_NBT: u64 = 16;
+const TYPE_VILLAGER_DATA: u64 = 19;
+const TYPE_OPTIONAL_VARINT: u64 = 20;
+const TYPE_POSE: u64 = 21;
+const TYPE_CAT_VARIANT: u64 = 22;
+const TYPE_FROG_VARIANT: u64 = 24;
+const TYPE_SNIFFER_STATE: u64 = 27;
+const TYPE_ARMADILLO_STATE: u64 = 28;
+const TYPE_VECTOR3: u64 = 29;
+const TYPE_QUATERNION: u64 = 30;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        handle_metadata_packets.in_set(BrineSystems::ProtocolDecode),
+    );
+}
+
+/// System that listens for Set Entity Metadata packets and sends
+/// [`EntityItemSet`] and [`EntityCustomName`] events to the client
+/// application.
+fn handle_metadata_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut item_events: MessageWriter<EntityItemSet>,
+    mut name_events: MessageWriter<EntityCustomName>,
+) {
+    let shims = shims_for(net_resource.codec().protocol_version());
+
+    for packet in packet_reader.iter() {
+        let Packet::Unknown(unknown) = packet else {
+            continue;
+        };
+        if unknown.packet_id != shims.entity_metadata {
+            continue;
+        }
+
+        // Whatever was read before a value that can't be is still sent.
+        let mut metadata = EntityMetadata::default();
+        if let Err(e) = parse_metadata(&unknown.body, &mut metadata) {
+            debug!("Stopped parsing Set Entity Metadata: {}", e);
+        }
+
+        let entity_id = metadata.entity_id;
+        if let Some(item) = metadata.item {
+            item_events.write(EntityItemSet { entity_id, item });
+        }
+        if metadata.custom_name.is_some() || metadata.custom_name_visible.is_some() {
+            name_events.write(EntityCustomName {
+                entity_id,
+                name: metadata.custom_name,
+                visible: metadata.custom_name_visible,
+            });
+        }
+    }
+}
+
+/// The values read from Set Entity Metadata. Each is `None` if the packet
+/// didn't change it.
+#[derive(Debug, Default, PartialEq)]
+struct EntityMetadata {
+    entity_id: i32,
+    item: Option<Option<ItemStack>>,
+    custom_name: Option<Option<String>>,
+    custom_name_visible: Option<bool>,
+}
+
+/// Reads Set Entity Metadata into `metadata`, up to and including the first
+/// item.
+fn parse_metadata(body: &[u8], metadata: &mut EntityMetadata) -> io::Result<()> {
+    let mut reader = Cursor::new(body);
+    metadata.entity_id = read_varint(&mut reader)? as i32;
+
+    loop {
+        let index = reader.read_u8()?;
+        if index == END_OF_METADATA {
+            return Ok(());
+        }
+
+        let value_type = read_varint(&mut reader)?;
+        match (index, value_type) {
+            (_, TYPE_SLOT) => {
+                metadata.item = Some(read_slot(&mut reader)?);
+                return Ok(());
+            }
+            (INDEX_CUSTOM_NAME, TYPE_OPTIONAL_TEXT_COMPONENT) => {
+                let name = if reader.read_u8()? != 0 {
+                    Some(read_text_component(&mut reader)?)
+                } else {
+                    None
+                };
+                metadata.custom_name = Some(name);
+            }
+            (INDEX_CUSTOM_NAME_VISIBLE, TYPE_BOOLEAN) => {
+                metadata.custom_name_visible = Some(reader.read_u8()? != 0);
+            }
+            _ => skip_value(&mut reader, value_type)?,
+        }
+    }
+}
+
+/// Reads an item stack's count and item, leaving its data components unread.
+fn read_slot(reader: &mut Cursor<&[u8]>) -> io::Result<Option<ItemStack>> {
+    let count = read_varint(reader)? as i32;
+    if count <= 0 {
+        return Ok(None);
+    }
+    let item_id = read_varint(reader)? as i32;
+    Ok(Some(ItemStack { item_id, count }))
+}
+
+/// Skips a metadata value of type `value_type`. Fails for the types that
+/// aren't worth a parser (particles and registry variants), which no entity
+/// with an item has before it.
+fn skip_value(reader: &mut Cursor<&[u8]>, value_type: u64) -> io::Result<()> {
+    match value_type {
+        TYPE_BYTE | TYPE_BOOLEAN => skip(reader, 1),
+        TYPE_VARINT
+        | TYPE_DIRECTION
+        | TYPE_BLOCK_STATE
+        | TYPE_OPTIONAL_BLOCK_STATE
+        | TYPE_OPTIONAL_VARINT
+        | TYPE_POSE
+        | TYPE_CAT_VARIANT
+        | TYPE_FROG_VARIANT
+        | TYPE_SNIFFER_STATE
+        | TYPE_ARMADILLO_STATE => read_varint(reader).map(drop),
+        TYPE_VARLONG => skip_varlong(reader),
+        TYPE_FLOAT => skip(reader, 4),
+        TYPE_STRING => read_string(reader).map(drop),
+        TYPE_TEXT_COMPONENT => read_text_component(reader).map(drop),
+        TYPE_OPTIONAL_TEXT_COMPONENT => {
+            if reader.read_u8()? != 0 {
+                read_text_component(reader)?;
+            }
+            Ok(())
+        }
+        TYPE_ROTATIONS | TYPE_VECTOR3 => skip(reader, 12),
+        TYPE_POSITION => skip(reader, 8),
+        TYPE_OPTIONAL_POSITION => skip_optional(reader, 8),
+        TYPE_OPTIONAL_UUID => skip_optional(reader, 16),
+        TYPE_NBT => {
+            let tag = reader.read_u8()?;
+            skip_nbt(reader, tag)
+        }
+        TYPE_VILLAGER_DATA => {
+            for _ in 0..3 {
+                read_varint(reader)?;
+            }
+            Ok(())
+        }
+        TYPE_QUATERNION => skip(reader, 16),
+        _ => Err(invalid_data("unsupported metadata type")),
+    }
+}
+
+/// Skips a value of `length` bytes that is preceded by whether it's present.
+fn skip_optional(reader: &mut Cursor<&[u8]>, length: u64) -> io::Result<()> {
+    if reader.read_u8()? != 0 {
+        skip(reader, length)?;
+    }
+    Ok(())
+}
+
+fn skip_varlong(reader: &mut Cursor<&[u8]>) -> io::Result<()> {
+    for _ in 0..10 {
+        if reader.read_u8()? & 0x80 == 0 {
+            return Ok(());
+        }
+    }
+    Err(invalid_data("VarLong is too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &[u8]) -> EntityMetadata {
+        let mut metadata = EntityMetadata::default();
+        parse_metadata(body, &mut metadata).unwrap();
+        metadata
+    }
+
+    #[test]
+    fn parses_entity_item() {
+        // Entity 300: flags byte, air ticks, no custom name, then 3 diamonds
+        // (item 900) with one data component.
+        let body = [
+            0xac, 0x02, 0, 0, 0, 1, 1, 0xac, 0x02, 2, 6, 0, 8, 7, 3, 0x84, 0x07, 1, 0, 5,
+        ];
+        assert_eq!(
+            parse(&body),
+            EntityMetadata {
+                entity_id: 300,
+                item: Some(Some(ItemStack {
+                    item_id: 900,
+                    count: 3,
+                })),
+                custom_name: Some(None),
+                custom_name_visible: None,
+            }
+        );
+
+        // An empty item frame.
+        assert_eq!(parse(&[7, 8, 7, 0, 0xff]).item, Some(None));
+
+        // Only the pose changed.
+        assert_eq!(
+            parse(&[7, 6, 21, 1, 0xff]),
+            EntityMetadata {
+                entity_id: 7,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_custom_name() {
+        // Entity 7 named "Bob" (a string tag), shown, then its pose.
+        let body = [
+            7, 2, 6, 1, 8, 0, 3, b'B', b'o', b'b', 3, 8, 1, 6, 21, 0, 0xff,
+        ];
+        assert_eq!(
+            parse(&body),
+            EntityMetadata {
+                entity_id: 7,
+                item: None,
+                custom_name: Some(Some(String::from("Bob"))),
+                custom_name_visible: Some(true),
+            }
+        );
+    }
+}
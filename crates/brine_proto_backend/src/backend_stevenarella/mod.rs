@@ -1,13 +1,101 @@
 //! Implementation of the Minecraft codec using stevenarella's protocol crate as
 //! the backend.
 
+mod actions;
+mod channels;
 pub mod chunks;
 pub mod codec;
+mod dispatch;
+mod entities;
+#[cfg(test)]
+pub(crate) mod fake_server;
+mod game_mode;
+mod health;
+mod inventory;
+mod latency;
+pub mod legacy;
 mod login;
+mod maps;
+mod metadata;
+mod packet_log;
+mod players;
+mod recipes;
+mod registries;
+mod resource_packs;
+mod status;
+mod teleport;
+mod unknown_packets;
+mod vehicles;
+mod world;
+
+use std::time::Duration;
+
+use bevy::app::App;
+
+use crate::{
+    chunks::{ChunkData, ChunkExtras},
+    status::{ServerStatus, StatusError},
+    ProtocolBackend,
+};
 
 pub use codec::ProtocolCodec;
 
-pub(crate) fn build(app: &mut bevy::app::App) {
-    chunks::build(app);
-    login::build(app);
+/// Backend built on stevenarella's generated packet definitions.
+pub struct StevenarellaBackend;
+
+impl ProtocolBackend for StevenarellaBackend {
+    type Codec = ProtocolCodec;
+
+    const NAME: &'static str = "stevenarella";
+
+    fn query_status(server_addr: &str, timeout: Duration) -> Result<ServerStatus, StatusError> {
+        Ok(status::query_status(server_addr, timeout)?)
+    }
+
+    fn chunk_data(packet: &codec::Packet) -> Option<ChunkData<&[u8]>> {
+        ChunkData::from_packet(packet)
+    }
+
+    fn chunk_extras(packet: &codec::Packet) -> Option<ChunkExtras> {
+        ChunkExtras::from_packet(packet)
+    }
+
+    fn build_login(app: &mut App) {
+        dispatch::build(app);
+        login::build(app);
+        registries::build(app);
+        resource_packs::build(app);
+        teleport::build(app);
+        latency::build(app);
+        packet_log::build(app);
+        unknown_packets::build(app);
+    }
+
+    fn build_chunks(app: &mut App) {
+        chunks::build(app);
+    }
+
+    fn build_world(app: &mut App) {
+        world::build(app);
+        maps::build(app);
+    }
+
+    fn build_entities(app: &mut App) {
+        entities::build(app);
+        metadata::build(app);
+        players::build(app);
+        health::build(app);
+        game_mode::build(app);
+        vehicles::build(app);
+        actions::build(app);
+    }
+
+    fn build_inventory(app: &mut App) {
+        inventory::build(app);
+        recipes::build(app);
+    }
+
+    fn build_plugin_messages(app: &mut App) {
+        channels::build(app);
+    }
 }
@@ -0,0 +1,161 @@
+//! Entries for the [packet log](crate::packet_log).
+//!
+//! Packets are named after their variants in stevenarella's packet enum,
+//! which start with the state and direction the packet belongs to (e.g.
+//! `PlayClientboundKeepAlive` is the `KeepAlive` packet received in `Play`).
+
+use bevy::prelude::*;
+
+use brine_net::{CodecReader, NetworkResource};
+use brine_proto::BrineSystems;
+
+use crate::{
+    codec::MinecraftProtocolState,
+    packet_log::{
+        summarize, timestamp_ms, PacketLog, PacketLogConfig, PacketLogEntry, MAX_SUMMARY_LENGTH,
+    },
+};
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+/// Longest variant name in the packet enum, with room to spare.
+const MAX_VARIANT_LENGTH: usize = 128;
+
+const STATES: [&str; 5] = ["Handshaking", "Status", "Login", "Configuration", "Play"];
+const DIRECTIONS: [&str; 2] = ["Clientbound", "Serverbound"];
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        log_packets
+            .run_if(|log: Res<PacketLog>| log.is_open())
+            .in_set(BrineSystems::ProtocolDecode),
+    );
+}
+
+fn log_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    config: Res<PacketLogConfig>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut log: ResMut<PacketLog>,
+) {
+    for packet in packet_reader.iter() {
+        let entry = describe(&packet, net_resource.codec().protocol_state());
+        if config.logs(&entry.name) {
+            log.write(&entry);
+        }
+    }
+}
+
+/// Returns the log entry for a received `packet`. Unknown packets are put in
+/// `state`, the codec's.
+fn describe(packet: &Packet, state: MinecraftProtocolState) -> PacketLogEntry {
+    let known = match packet {
+        Packet::Known(known) => known,
+        Packet::Unknown(unknown) => {
+            return PacketLogEntry {
+                timestamp_ms: timestamp_ms(),
+                state: format!("{:?}", state),
+                direction: String::from("Clientbound"),
+                name: String::from("Unknown"),
+                id: Some(unknown.packet_id),
+                summary: format!("{} bytes", unknown.body.len()),
+            };
+        }
+        Packet::Legacy(kick) => {
+            return PacketLogEntry {
+                timestamp_ms: timestamp_ms(),
+                state: format!("{:?}", state),
+                direction: String::from("Clientbound"),
+                name: String::from("LegacyKick"),
+                id: None,
+                summary: kick.message.clone(),
+            };
+        }
+    };
+
+    let variant = summarize(known, MAX_VARIANT_LENGTH);
+    let variant = variant
+        .split_once('(')
+        .map_or(variant.as_str(), |(name, _)| name);
+    let (state, direction, name) = split_variant(variant);
+
+    PacketLogEntry {
+        timestamp_ms: timestamp_ms(),
+        state: state.to_string(),
+        direction: direction.to_string(),
+        name: name.to_string(),
+        id: None,
+        summary: summarize_fields(known),
+    }
+}
+
+/// Splits a packet enum variant into state, direction and packet name.
+fn split_variant(variant: &str) -> (&str, &str, &str) {
+    let split = STATES.iter().find_map(|state| {
+        let rest = variant.strip_prefix(state)?;
+        DIRECTIONS.iter().find_map(|direction| {
+            let name = rest.strip_prefix(direction)?;
+            Some((*state, *direction, name))
+        })
+    });
+    split.unwrap_or(("", "", variant))
+}
+
+/// Returns a summary of the fields of `packet`. Packets with long lists get
+/// their lengths instead.
+fn summarize_fields(packet: &packet::Packet) -> String {
+    match packet {
+        // Since 1.21.2 this only carries stonecutter recipes and item
+        // property sets; crafting recipes come in Recipe Book Add.
+        packet::Packet::PlayClientboundDeclareRecipes(recipes) => format!(
+            "{} recipes, {} stonecutter entries",
+            recipes.recipes.values.len(),
+            recipes.stoneCutterRecipes.values.len()
+        ),
+        packet::Packet::PlayClientboundTags(tags) => {
+            format!("{} tag groups", tags.tags.values.len())
+        }
+        _ => {
+            // Without the variant's parentheses.
+            let summary = summarize(packet, MAX_VARIANT_LENGTH + MAX_SUMMARY_LENGTH);
+            let fields = summary
+                .split_once('(')
+                .map_or(summary.as_str(), |(_, fields)| fields);
+            let fields = fields.strip_suffix(')').unwrap_or(fields);
+            summarize(&format_args!("{}", fields), MAX_SUMMARY_LENGTH)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets_are_named_after_their_variants() {
+        assert_eq!(
+            split_variant("PlayClientboundKeepAlive"),
+            ("Play", "Clientbound", "KeepAlive")
+        );
+        assert_eq!(
+            split_variant("ConfigurationServerboundFinishConfiguration"),
+            ("Configuration", "Serverbound", "FinishConfiguration")
+        );
+        assert_eq!(split_variant("Odd"), ("", "", "Odd"));
+    }
+
+    #[test]
+    fn entries_summarize_fields() {
+        let packet = Packet::from(packet::Packet::PlayClientboundKeepAlive(Box::new(
+            packet::play::clientbound::KeepAlive { keepAliveId: 7 },
+        )));
+        let entry = describe(&packet, MinecraftProtocolState::Play);
+
+        assert_eq!(entry.state, "Play");
+        assert_eq!(entry.direction, "Clientbound");
+        assert_eq!(entry.name, "KeepAlive");
+        assert_eq!(entry.id, None);
+        assert_eq!(entry.summary, "KeepAlive { keepAliveId: 7 }");
+    }
+}
@@ -0,0 +1,394 @@
+//! Translation of the player list packets into [`brine_proto`] events.
+//!
+//! Player Info Update is driven by a set of action flags that decides which
+//! fields every entry has, which the generated packet tables can't describe.
+//! The codec passes it through undecoded (see
+//! [`PacketShims::player_info_update`][crate::version::PacketShims::player_info_update])
+//! and it is parsed here instead.
+
+use std::io::{self, Cursor};
+
+use bevy::prelude::*;
+use byteorder::{BigEndian, ReadBytesExt};
+
+use brine_net::{CodecReader, NetworkResource};
+use brine_proto::event::{
+    clientbound::{PlayerAdded, PlayerDisplayName, PlayerLatency, PlayersRemoved},
+    Uuid,
+};
+use brine_proto::BrineSystems;
+use steven_protocol::protocol::Serializable;
+
+use crate::version::shims_for;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+const ADD_PLAYER: u8 = 0x01;
+const INITIALIZE_CHAT: u8 = 0x02;
+const UPDATE_GAME_MODE: u8 = 0x04;
+const UPDATE_LISTED: u8 = 0x08;
+const UPDATE_LATENCY: u8 = 0x10;
+const UPDATE_DISPLAY_NAME: u8 = 0x20;
+const UPDATE_LIST_PRIORITY: u8 = 0x40;
+const UPDATE_HAT: u8 = 0x80;
+
+pub(super) const TAG_END: u8 = 0;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+pub(super) const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+pub(super) const TAG_LONG_ARRAY: u8 = 12;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        handle_player_list_packets.in_set(BrineSystems::ProtocolDecode),
+    );
+}
+
+/// What a Player Info Update entry changed about one player.
+#[derive(Debug, Default, PartialEq)]
+struct PlayerInfoEntry {
+    uuid: Uuid,
+
+    /// Profile name, if the entry adds the player.
+    name: Option<String>,
+
+    /// New display name, if the entry updates it; the inner `None` clears it.
+    display_name: Option<Option<String>>,
+
+    /// New latency in milliseconds, if the entry updates it.
+    latency_ms: Option<i32>,
+}
+
+/// System that listens for the player list packets and sends the
+/// corresponding events to the client application.
+fn handle_player_list_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut added_events: MessageWriter<PlayerAdded>,
+    mut display_name_events: MessageWriter<PlayerDisplayName>,
+    mut latency_events: MessageWriter<PlayerLatency>,
+    mut removed_events: MessageWriter<PlayersRemoved>,
+) {
+    let shims = shims_for(net_resource.codec().protocol_version());
+
+    for packet in packet_reader.iter() {
+        match packet {
+            Packet::Unknown(unknown) if unknown.packet_id == shims.player_info_update => {
+                let entries = match parse_player_info_update(&unknown.body) {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        warn!("Failed to parse Player Info Update: {}", e);
+                        continue;
+                    }
+                };
+
+                for entry in entries {
+                    if let Some(name) = entry.name {
+                        added_events.write(PlayerAdded {
+                            uuid: entry.uuid,
+                            name,
+                        });
+                    }
+                    if let Some(display_name) = entry.display_name {
+                        display_name_events.write(PlayerDisplayName {
+                            uuid: entry.uuid,
+                            display_name,
+                        });
+                    }
+                    if let Some(latency_ms) = entry.latency_ms {
+                        latency_events.write(PlayerLatency {
+                            uuid: entry.uuid,
+                            latency_ms,
+                        });
+                    }
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundPlayerRemove(remove)) => {
+                let uuids = remove
+                    .players
+                    .data
+                    .iter()
+                    .map(|uuid| {
+                        let mut uuid_bytes = Vec::with_capacity(16);
+                        uuid.write_to(&mut uuid_bytes).unwrap();
+                        Uuid::from_bytes(uuid_bytes.try_into().unwrap())
+                    })
+                    .collect();
+                removed_events.write(PlayersRemoved { uuids });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_player_info_update(body: &[u8]) -> io::Result<Vec<PlayerInfoEntry>> {
+    let mut reader = Cursor::new(body);
+
+    let actions = reader.read_u8()?;
+    let count = read_varint(&mut reader)?;
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let mut entry = PlayerInfoEntry {
+            uuid: Uuid::from_u128(reader.read_u128::<BigEndian>()?),
+            ..default()
+        };
+
+        if actions & ADD_PLAYER != 0 {
+            entry.name = Some(read_string(&mut reader)?);
+            let properties = read_varint(&mut reader)?;
+            for _ in 0..properties {
+                read_string(&mut reader)?;
+                read_string(&mut reader)?;
+                if reader.read_u8()? != 0 {
+                    read_string(&mut reader)?;
+                }
+            }
+        }
+        if actions & INITIALIZE_CHAT != 0 && reader.read_u8()? != 0 {
+            // Session ID and key expiry, then the public key and its signature.
+            skip(&mut reader, 16 + 8)?;
+            let key_length = read_varint(&mut reader)?;
+            skip(&mut reader, key_length)?;
+            let signature_length = read_varint(&mut reader)?;
+            skip(&mut reader, signature_length)?;
+        }
+        if actions & UPDATE_GAME_MODE != 0 {
+            read_varint(&mut reader)?;
+        }
+        if actions & UPDATE_LISTED != 0 {
+            reader.read_u8()?;
+        }
+        if actions & UPDATE_LATENCY != 0 {
+            entry.latency_ms = Some(read_varint(&mut reader)? as i32);
+        }
+        if actions & UPDATE_DISPLAY_NAME != 0 {
+            entry.display_name = Some(if reader.read_u8()? != 0 {
+                Some(read_text_component(&mut reader)?)
+            } else {
+                None
+            });
+        }
+        if actions & UPDATE_LIST_PRIORITY != 0 {
+            read_varint(&mut reader)?;
+        }
+        if actions & UPDATE_HAT != 0 {
+            reader.read_u8()?;
+        }
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+pub(super) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads a VarInt as an unsigned length or count.
+pub(super) fn read_varint(reader: &mut Cursor<&[u8]>) -> io::Result<u64> {
+    let mut value = 0u32;
+    for i in 0..5 {
+        let byte = reader.read_u8()?;
+        value |= u32::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(u64::from(value));
+        }
+    }
+    Err(invalid_data("VarInt is too long"))
+}
+
+pub(super) fn skip(reader: &mut Cursor<&[u8]>, length: u64) -> io::Result<()> {
+    let position = reader.position() + length;
+    if position > reader.get_ref().len() as u64 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    reader.set_position(position);
+    Ok(())
+}
+
+fn read_bytes(reader: &mut Cursor<&[u8]>, length: u64) -> io::Result<Vec<u8>> {
+    let start = reader.position() as usize;
+    skip(reader, length)?;
+    Ok(reader.get_ref()[start..reader.position() as usize].to_vec())
+}
+
+pub(super) fn read_string(reader: &mut Cursor<&[u8]>) -> io::Result<String> {
+    let length = read_varint(reader)?;
+    String::from_utf8(read_bytes(reader, length)?).map_err(|_| invalid_data("invalid string"))
+}
+
+pub(super) fn read_nbt_string(reader: &mut Cursor<&[u8]>) -> io::Result<String> {
+    let length = reader.read_u16::<BigEndian>()?;
+    // NBT uses modified UTF-8, which only differs for NUL and characters
+    // outside the BMP; those aren't worth a decoder for a name tag.
+    Ok(String::from_utf8_lossy(&read_bytes(reader, u64::from(length))?).into_owned())
+}
+
+pub(super) fn read_nbt_length(reader: &mut Cursor<&[u8]>) -> io::Result<u64> {
+    Ok(reader.read_i32::<BigEndian>()?.max(0) as u64)
+}
+
+/// Reads a text component sent as network NBT and returns its plain text,
+/// ignoring styling.
+pub(super) fn read_text_component(reader: &mut Cursor<&[u8]>) -> io::Result<String> {
+    let tag = reader.read_u8()?;
+    let mut text = String::new();
+    append_component_text(reader, tag, &mut text)?;
+    Ok(text)
+}
+
+fn append_component_text(reader: &mut Cursor<&[u8]>, tag: u8, text: &mut String) -> io::Result<()> {
+    match tag {
+        TAG_STRING => text.push_str(&read_nbt_string(reader)?),
+        TAG_COMPOUND => {
+            let mut own_text = String::new();
+            let mut extra_text = String::new();
+            loop {
+                let field_tag = reader.read_u8()?;
+                if field_tag == TAG_END {
+                    break;
+                }
+                let name = read_nbt_string(reader)?;
+                match (name.as_str(), field_tag) {
+                    ("text", TAG_STRING) => own_text = read_nbt_string(reader)?,
+                    ("extra", TAG_LIST) => {
+                        let element_tag = reader.read_u8()?;
+                        for _ in 0..read_nbt_length(reader)? {
+                            append_component_text(reader, element_tag, &mut extra_text)?;
+                        }
+                    }
+                    _ => skip_nbt(reader, field_tag)?,
+                }
+            }
+            text.push_str(&own_text);
+            text.push_str(&extra_text);
+        }
+        _ => skip_nbt(reader, tag)?,
+    }
+    Ok(())
+}
+
+/// Skips the payload of an NBT tag of type `tag`.
+pub(super) fn skip_nbt(reader: &mut Cursor<&[u8]>, tag: u8) -> io::Result<()> {
+    match tag {
+        TAG_END => {}
+        1 => skip(reader, 1)?,
+        2 => skip(reader, 2)?,
+        3 | 5 => skip(reader, 4)?,
+        4 | 6 => skip(reader, 8)?,
+        TAG_BYTE_ARRAY => {
+            let length = read_nbt_length(reader)?;
+            skip(reader, length)?;
+        }
+        TAG_STRING => {
+            let length = reader.read_u16::<BigEndian>()?;
+            skip(reader, u64::from(length))?;
+        }
+        TAG_LIST => {
+            let element_tag = reader.read_u8()?;
+            for _ in 0..read_nbt_length(reader)? {
+                skip_nbt(reader, element_tag)?;
+            }
+        }
+        TAG_COMPOUND => loop {
+            let field_tag = reader.read_u8()?;
+            if field_tag == TAG_END {
+                break;
+            }
+            let name_length = reader.read_u16::<BigEndian>()?;
+            skip(reader, u64::from(name_length))?;
+            skip_nbt(reader, field_tag)?;
+        },
+        TAG_INT_ARRAY => {
+            let length = read_nbt_length(reader)?;
+            skip(reader, length * 4)?;
+        }
+        TAG_LONG_ARRAY => {
+            let length = read_nbt_length(reader)?;
+            skip(reader, length * 8)?;
+        }
+        _ => return Err(invalid_data("unknown NBT tag")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nbt_string(value: &str) -> Vec<u8> {
+        let mut bytes = (value.len() as u16).to_be_bytes().to_vec();
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn parses_added_player_with_display_name() {
+        let uuid = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+
+        let mut body = vec![ADD_PLAYER | UPDATE_LISTED | UPDATE_DISPLAY_NAME, 1];
+        body.extend_from_slice(uuid.as_bytes());
+        // Name and one signed property.
+        body.extend_from_slice(b"\x05Steve\x01\x08textures\x01x\x01\x01y");
+        // Listed.
+        body.push(1);
+        // Display name: {"color": "red", "text": "The ", "extra": ["Steve"]}.
+        body.extend_from_slice(&[1, TAG_COMPOUND, TAG_STRING]);
+        body.extend(nbt_string("color"));
+        body.extend(nbt_string("red"));
+        body.push(TAG_LIST);
+        body.extend(nbt_string("extra"));
+        body.push(TAG_STRING);
+        body.extend_from_slice(&1i32.to_be_bytes());
+        body.extend(nbt_string("Steve"));
+        body.push(TAG_STRING);
+        body.extend(nbt_string("text"));
+        body.extend(nbt_string("The "));
+        body.push(TAG_END);
+
+        let entries = parse_player_info_update(&body).unwrap();
+        assert_eq!(
+            entries,
+            vec![PlayerInfoEntry {
+                uuid,
+                name: Some(String::from("Steve")),
+                display_name: Some(Some(String::from("The Steve"))),
+                latency_ms: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_latency_update() {
+        let uuid = Uuid::from_u128(7);
+
+        let mut body = vec![UPDATE_LATENCY, 1];
+        body.extend_from_slice(uuid.as_bytes());
+        // 300 ms.
+        body.extend_from_slice(&[0xac, 0x02]);
+
+        let entries = parse_player_info_update(&body).unwrap();
+        assert_eq!(
+            entries,
+            vec![PlayerInfoEntry {
+                uuid,
+                latency_ms: Some(300),
+                ..default()
+            }]
+        );
+    }
+
+    #[test]
+    fn truncated_update_is_an_error() {
+        let mut body = vec![ADD_PLAYER, 1];
+        body.extend_from_slice(&[0; 16]);
+        body.extend_from_slice(b"\x05Ste");
+
+        assert!(parse_player_info_update(&body).is_err());
+    }
+}
@@ -0,0 +1,452 @@
+//! Translation of the recipe book packets into [`brine_proto`] events.
+//!
+//! Since 1.21.2, unlocked recipes arrive in Recipe Book Add as displays: the
+//! ingredients and result as the recipe book shows them, rather than the
+//! recipes themselves. Like Player Info Update, the packet is passed through
+//! undecoded (see
+//! [`PacketShims::recipe_book_add`][crate::version::PacketShims::recipe_book_add])
+//! and parsed here. Only crafting recipes are forwarded; furnace, stonecutter
+//! and smithing displays are read and dropped.
+//!
+//! Older versions declare every recipe in Declare Recipes and place them by
+//! name, neither of which the backend reads or writes, so on those the
+//! client is told with [`RecipeBookUnavailable`] rather than left with an
+//! empty recipe book.
+
+use std::io::{self, Cursor};
+
+use bevy::prelude::*;
+use byteorder::{BigEndian, ReadBytesExt};
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{
+    event::{
+        clientbound::{RecipeBookUnavailable, RecipesAdded, RecipesRemoved},
+        serverbound::PlaceRecipe,
+        Ingredient, ItemStack, Recipe, RecipeShape,
+    },
+    login::LoginState,
+    BrineSystems,
+};
+use steven_protocol::protocol::VarInt;
+
+use crate::{
+    codec::MinecraftProtocolState,
+    version::{shims_for, supported_version},
+};
+
+use super::{
+    codec::{packet, Packet, ProtocolCodec},
+    players::{invalid_data, read_string, read_varint},
+};
+
+const DISPLAY_SHAPELESS: u64 = 0;
+const DISPLAY_SHAPED: u64 = 1;
+const DISPLAY_FURNACE: u64 = 2;
+const DISPLAY_STONECUTTER: u64 = 3;
+const DISPLAY_SMITHING: u64 = 4;
+
+const SLOT_EMPTY: u64 = 0;
+const SLOT_ANY_FUEL: u64 = 1;
+const SLOT_ITEM: u64 = 2;
+const SLOT_ITEM_STACK: u64 = 3;
+const SLOT_TAG: u64 = 4;
+const SLOT_SMITHING_TRIM: u64 = 5;
+const SLOT_WITH_REMAINDER: u64 = 6;
+const SLOT_COMPOSITE: u64 = 7;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            handle_recipe_packets.in_set(BrineSystems::ProtocolDecode),
+            send_place_recipe.in_set(BrineSystems::ProtocolEncode),
+        ),
+    )
+    .add_systems(OnEnter(LoginState::Play), report_unavailable_recipe_book);
+}
+
+/// System that tells the client when the server's version sends recipes in a
+/// format the backend doesn't read.
+fn report_unavailable_recipe_book(
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut unavailable_events: MessageWriter<RecipeBookUnavailable>,
+) {
+    let protocol_version = net_resource.codec().protocol_version();
+    if shims_for(protocol_version).recipe_book_add.is_some() {
+        return;
+    }
+
+    let version = supported_version(protocol_version).map_or_else(
+        || protocol_version.to_string(),
+        |supported| supported.label(),
+    );
+    let reason = format!("Recipes can't be read from Minecraft {version} servers");
+    warn!("{}; crafting is unavailable", reason);
+    unavailable_events.write(RecipeBookUnavailable { reason });
+}
+
+/// How the recipe book shows one slot of a recipe.
+#[derive(Debug, PartialEq)]
+enum SlotDisplay {
+    Empty,
+    Stack(ItemStack),
+    Tag(String),
+    /// Any one of several displays, e.g. the items of an ingredient.
+    Composite(Vec<SlotDisplay>),
+    /// Something crafting recipes don't use, like furnace fuel.
+    Other,
+}
+
+impl SlotDisplay {
+    fn ingredient(self) -> Option<Ingredient> {
+        match self {
+            Self::Empty | Self::Other => None,
+            Self::Stack(stack) => Some(Ingredient::Items(vec![stack.item_id])),
+            Self::Tag(tag) => Some(Ingredient::Tag(tag)),
+            Self::Composite(displays) => {
+                let items: Vec<i32> = displays
+                    .iter()
+                    .filter_map(|display| match display {
+                        Self::Stack(stack) => Some(stack.item_id),
+                        _ => None,
+                    })
+                    .collect();
+                if items.is_empty() {
+                    displays.into_iter().find_map(Self::ingredient)
+                } else {
+                    Some(Ingredient::Items(items))
+                }
+            }
+        }
+    }
+
+    fn result(self) -> Option<ItemStack> {
+        match self {
+            Self::Stack(stack) => Some(stack),
+            Self::Composite(displays) => displays.into_iter().find_map(Self::result),
+            _ => None,
+        }
+    }
+}
+
+/// System that listens for the recipe book packets and sends the
+/// corresponding events to the client application.
+fn handle_recipe_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut added_events: MessageWriter<RecipesAdded>,
+    mut removed_events: MessageWriter<RecipesRemoved>,
+) {
+    let shims = shims_for(net_resource.codec().protocol_version());
+
+    for packet in packet_reader.iter() {
+        let Packet::Unknown(unknown) = packet else {
+            continue;
+        };
+
+        if Some(unknown.packet_id) == shims.recipe_book_add {
+            match parse_recipe_book_add(&unknown.body) {
+                Ok(added) => {
+                    debug!("Recipe book: {} crafting recipes", added.recipes.len());
+                    added_events.write(added);
+                }
+                Err(e) => warn!("Failed to parse Recipe Book Add: {}", e),
+            }
+        } else if Some(unknown.packet_id) == shims.recipe_book_remove {
+            match parse_recipe_book_remove(&unknown.body) {
+                Ok(ids) => {
+                    removed_events.write(RecipesRemoved { ids });
+                }
+                Err(e) => warn!("Failed to parse Recipe Book Remove: {}", e),
+            }
+        }
+    }
+}
+
+/// System that asks the server to fill a crafting grid with a recipe.
+fn send_place_recipe(
+    mut place_events: MessageReader<PlaceRecipe>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        place_events.clear();
+        return;
+    }
+
+    for place in place_events.read() {
+        debug!(
+            "Placing recipe {} in window {}",
+            place.recipe_id, place.window_id
+        );
+        packet_writer.send(Packet::Known(
+            packet::Packet::PlayServerboundCraftRecipeRequest(Box::new(
+                packet::play::serverbound::CraftRecipeRequest {
+                    windowId: VarInt(place.window_id),
+                    recipeId: VarInt(place.recipe_id),
+                    makeAll: place.make_all,
+                },
+            )),
+        ));
+    }
+}
+
+fn parse_recipe_book_add(body: &[u8]) -> io::Result<RecipesAdded> {
+    let mut reader = Cursor::new(body);
+
+    let mut recipes = Vec::new();
+    for _ in 0..read_varint(&mut reader)? {
+        let id = read_id(&mut reader)?;
+        let shape_and_result = read_recipe_display(&mut reader)?;
+
+        // Group and category.
+        read_varint(&mut reader)?;
+        read_varint(&mut reader)?;
+
+        // The items that unlock crafting the recipe, as ID sets.
+        if reader.read_u8()? != 0 {
+            for _ in 0..read_varint(&mut reader)? {
+                read_id_set(&mut reader)?;
+            }
+        }
+
+        // Flags: show a notification, highlight as new.
+        reader.read_u8()?;
+
+        if let Some((shape, result)) = shape_and_result {
+            recipes.push(Recipe { id, shape, result });
+        }
+    }
+
+    let replace = reader.read_u8()? != 0;
+
+    Ok(RecipesAdded { recipes, replace })
+}
+
+fn parse_recipe_book_remove(body: &[u8]) -> io::Result<Vec<i32>> {
+    let mut reader = Cursor::new(body);
+    (0..read_varint(&mut reader)?)
+        .map(|_| read_id(&mut reader))
+        .collect()
+}
+
+/// Reads a VarInt that holds an ID rather than a length.
+fn read_id(reader: &mut Cursor<&[u8]>) -> io::Result<i32> {
+    Ok(read_varint(reader)? as i32)
+}
+
+/// Reads an ID set, which is either a tag name or a list of registry IDs.
+fn read_id_set(reader: &mut Cursor<&[u8]>) -> io::Result<()> {
+    match read_varint(reader)? {
+        0 => {
+            read_string(reader)?;
+        }
+        length_plus_one => {
+            for _ in 1..length_plus_one {
+                read_varint(reader)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a recipe display, returning the shape and result of crafting
+/// recipes and `None` for every other kind.
+fn read_recipe_display(reader: &mut Cursor<&[u8]>) -> io::Result<Option<(RecipeShape, ItemStack)>> {
+    let shape = match read_varint(reader)? {
+        DISPLAY_SHAPELESS => {
+            let ingredients = read_slot_displays(reader)?
+                .into_iter()
+                .filter_map(SlotDisplay::ingredient)
+                .collect();
+            RecipeShape::Shapeless { ingredients }
+        }
+        DISPLAY_SHAPED => {
+            let width = u8::try_from(read_varint(reader)?)
+                .map_err(|_| invalid_data("recipe is too wide"))?;
+            let height = u8::try_from(read_varint(reader)?)
+                .map_err(|_| invalid_data("recipe is too tall"))?;
+            let ingredients = read_slot_displays(reader)?
+                .into_iter()
+                .map(SlotDisplay::ingredient)
+                .collect();
+            RecipeShape::Shaped {
+                width,
+                height,
+                ingredients,
+            }
+        }
+        DISPLAY_FURNACE => {
+            // Ingredient, fuel, result and station, then the cooking time and
+            // experience.
+            for _ in 0..4 {
+                read_slot_display(reader)?;
+            }
+            read_varint(reader)?;
+            reader.read_f32::<BigEndian>()?;
+            return Ok(None);
+        }
+        DISPLAY_STONECUTTER => {
+            // Ingredient, result and station.
+            for _ in 0..3 {
+                read_slot_display(reader)?;
+            }
+            return Ok(None);
+        }
+        DISPLAY_SMITHING => {
+            // Template, base, addition, result and station.
+            for _ in 0..5 {
+                read_slot_display(reader)?;
+            }
+            return Ok(None);
+        }
+        _ => return Err(invalid_data("unknown recipe display")),
+    };
+
+    let result = read_slot_display(reader)?.result();
+
+    // The crafting station: a crafting table, which the grid size already
+    // implies.
+    read_slot_display(reader)?;
+
+    Ok(result.map(|result| (shape, result)))
+}
+
+fn read_slot_displays(reader: &mut Cursor<&[u8]>) -> io::Result<Vec<SlotDisplay>> {
+    (0..read_varint(reader)?)
+        .map(|_| read_slot_display(reader))
+        .collect()
+}
+
+fn read_slot_display(reader: &mut Cursor<&[u8]>) -> io::Result<SlotDisplay> {
+    let display = match read_varint(reader)? {
+        SLOT_EMPTY => SlotDisplay::Empty,
+        SLOT_ANY_FUEL => SlotDisplay::Other,
+        SLOT_ITEM => SlotDisplay::Stack(ItemStack {
+            item_id: read_id(reader)?,
+            count: 1,
+        }),
+        SLOT_ITEM_STACK => read_slot(reader)?,
+        SLOT_TAG => SlotDisplay::Tag(read_string(reader)?),
+        SLOT_SMITHING_TRIM => {
+            // Base, material and pattern.
+            for _ in 0..3 {
+                read_slot_display(reader)?;
+            }
+            SlotDisplay::Other
+        }
+        SLOT_WITH_REMAINDER => {
+            let input = read_slot_display(reader)?;
+            read_slot_display(reader)?;
+            input
+        }
+        SLOT_COMPOSITE => SlotDisplay::Composite(read_slot_displays(reader)?),
+        _ => return Err(invalid_data("unknown slot display")),
+    };
+    Ok(display)
+}
+
+/// Reads an item stack as sent in slots. Stacks with data components (e.g. a
+/// named or enchanted result) can't be skipped without decoding every
+/// component, and are an error.
+fn read_slot(reader: &mut Cursor<&[u8]>) -> io::Result<SlotDisplay> {
+    let count = read_id(reader)?;
+    if count <= 0 {
+        return Ok(SlotDisplay::Empty);
+    }
+
+    let item_id = read_id(reader)?;
+    let added_components = read_varint(reader)?;
+    let removed_components = read_varint(reader)?;
+    if added_components > 0 {
+        return Err(invalid_data("item stack has data components"));
+    }
+    for _ in 0..removed_components {
+        read_varint(reader)?;
+    }
+
+    Ok(SlotDisplay::Stack(ItemStack { item_id, count }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRAFTING_TABLE: u8 = 0x7a;
+
+    #[test]
+    fn parses_crafting_recipes() {
+        let mut body = vec![3];
+
+        // Recipe 1: two planks, shaped 1x2, making four sticks.
+        body.extend_from_slice(&[1, DISPLAY_SHAPED as u8, 1, 2, 2]);
+        body.extend_from_slice(&[SLOT_TAG as u8, 16]);
+        body.extend_from_slice(b"minecraft:planks");
+        body.extend_from_slice(&[SLOT_EMPTY as u8]);
+        body.extend_from_slice(&[SLOT_ITEM_STACK as u8, 4, 0x20, 0, 0]);
+        body.extend_from_slice(&[SLOT_ITEM as u8, CRAFTING_TABLE]);
+        // Group, category, no unlocking items, flags.
+        body.extend_from_slice(&[0, 1, 0, 0]);
+
+        // Recipe 2: smelting, which is dropped.
+        body.extend_from_slice(&[2, DISPLAY_FURNACE as u8]);
+        body.extend_from_slice(&[SLOT_ITEM as u8, 5, SLOT_ANY_FUEL as u8]);
+        body.extend_from_slice(&[SLOT_ITEM as u8, 6, SLOT_ITEM as u8, 7, 200, 1]);
+        body.extend_from_slice(&0.1f32.to_be_bytes());
+        body.extend_from_slice(&[0, 2, 0, 0]);
+
+        // Recipe 3: shapeless, with either of two items, unlocked by item 9.
+        body.extend_from_slice(&[3, DISPLAY_SHAPELESS as u8, 1]);
+        body.extend_from_slice(&[SLOT_COMPOSITE as u8, 2, SLOT_ITEM as u8, 8]);
+        body.extend_from_slice(&[SLOT_ITEM as u8, 9]);
+        body.extend_from_slice(&[SLOT_ITEM as u8, 10]);
+        body.extend_from_slice(&[SLOT_ITEM as u8, CRAFTING_TABLE]);
+        body.extend_from_slice(&[0, 1, 1, 1, 2, 9, 0]);
+
+        // Replace.
+        body.push(1);
+
+        let added = parse_recipe_book_add(&body).unwrap();
+        assert!(added.replace);
+        assert_eq!(
+            added.recipes,
+            vec![
+                Recipe {
+                    id: 1,
+                    shape: RecipeShape::Shaped {
+                        width: 1,
+                        height: 2,
+                        ingredients: vec![
+                            Some(Ingredient::Tag(String::from("minecraft:planks"))),
+                            None,
+                        ],
+                    },
+                    result: ItemStack {
+                        item_id: 0x20,
+                        count: 4,
+                    },
+                },
+                Recipe {
+                    id: 3,
+                    shape: RecipeShape::Shapeless {
+                        ingredients: vec![Ingredient::Items(vec![8, 9])],
+                    },
+                    result: ItemStack {
+                        item_id: 10,
+                        count: 1,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_removed_recipes() {
+        assert_eq!(
+            parse_recipe_book_remove(&[2, 1, 0x80, 0x01]).unwrap(),
+            vec![1, 128]
+        );
+        assert!(parse_recipe_book_remove(&[2, 1]).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Recording the registries the server sends during configuration into
+//! [`ServerRegistries`], and tracking which dimension the player is in so
+//! that chunks are decoded in its shape.
+//!
+//! Both are forgotten whenever the client logs in, is transferred or is
+//! reconfigured, since the server sends them again afterwards and the next
+//! server's may differ.
+//!
+//! The [`ServerRegistries`] resource is a handle; the client app replaces it
+//! with the one in its [`MinecraftData`][brine_data::MinecraftData] so that
+//! what is recorded here is seen there.
+
+use std::io::{self, Cursor};
+
+use bevy::prelude::*;
+use byteorder::{BigEndian, ReadBytesExt};
+
+use brine_data::{server_registries::DIMENSION_TYPE, DimensionType, ServerRegistries};
+use brine_proto::{
+    event::{
+        clientbound::{Reconfigure, Transfer},
+        serverbound::Login,
+    },
+    BrineSystems,
+};
+
+use super::{
+    codec::{packet, Packet, Serializable},
+    dispatch::{packet_kind, PacketHandlerAppExt},
+    players::{invalid_data, read_nbt_string, skip_nbt, TAG_COMPOUND, TAG_END},
+};
+
+const TAG_BYTE: u8 = 1;
+const TAG_INT: u8 = 3;
+
+/// The type of the dimension the player is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub(crate) struct CurrentDimension(pub DimensionType);
+
+impl Default for CurrentDimension {
+    fn default() -> Self {
+        Self(DimensionType::OVERWORLD)
+    }
+}
+
+impl CurrentDimension {
+    /// Switches to the dimension a Login or Respawn packet puts the player
+    /// in. Returns whether `packet` was one.
+    pub(crate) fn update(&mut self, packet: &Packet, registries: &ServerRegistries) -> bool {
+        let world_state = match packet {
+            Packet::Known(packet::Packet::PlayClientboundLogin(login)) => &login.worldState,
+            Packet::Known(packet::Packet::PlayClientboundRespawn(respawn)) => &respawn.worldState,
+            _ => return false,
+        };
+
+        let id = world_state.dimension.0 as u32;
+        match registries.dimension_type_by_id(id) {
+            Some(dimension_type) => {
+                debug!("Entering {} ({:?})", world_state.name, dimension_type);
+                self.0 = dimension_type;
+            }
+            None => warn!(
+                "Entering {} of unknown dimension type {}; assuming the overworld's shape",
+                world_state.name, id
+            ),
+        }
+        true
+    }
+}
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<ServerRegistries>()
+        .init_resource::<CurrentDimension>()
+        .add_packet_handler(
+            [packet_kind!(ConfigurationClientboundRegistryData)],
+            handle_registry_data,
+        )
+        .add_systems(
+            Update,
+            forget_server_registries.in_set(BrineSystems::WorldUpdate),
+        );
+}
+
+/// System that forgets the registries and dimension of the last server when
+/// logging in again, being transferred or being reconfigured.
+fn forget_server_registries(
+    mut login_events: MessageReader<Login>,
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    registries: Res<ServerRegistries>,
+    mut dimension: ResMut<CurrentDimension>,
+) {
+    let logged_in = login_events.read().count() > 0;
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if logged_in || transferred || reconfigured {
+        debug!("Forgetting the server's registries");
+        registries.clear();
+        *dimension = CurrentDimension::default();
+    }
+}
+
+/// Handler that records a registry the server sent.
+fn handle_registry_data(InRef(packet): InRef<Packet>, registries: Res<ServerRegistries>) {
+    let Packet::Known(packet::Packet::ConfigurationClientboundRegistryData(registry_data)) = packet
+    else {
+        return;
+    };
+
+    let registry = &registry_data.id;
+    let entries = &registry_data.entries.values;
+    debug!("Registry {} has {} entries", registry, entries.len());
+    registries.set_registry(
+        registry.clone(),
+        entries.iter().map(|entry| entry.key.clone()).collect(),
+    );
+
+    if registry != DIMENSION_TYPE {
+        return;
+    }
+    for entry in entries {
+        // Left out for vanilla entries when the client has the vanilla data
+        // pack.
+        let Some(value) = &entry.value.value else {
+            continue;
+        };
+
+        let mut nbt = Vec::new();
+        let dimension_type = match value.write_to(&mut nbt) {
+            Ok(()) => read_dimension_type(&nbt).map_err(|err| err.to_string()),
+            Err(err) => Err(format!("{:?}", err)),
+        };
+        match dimension_type {
+            Ok(dimension_type) => registries.set_dimension_type(entry.key.clone(), dimension_type),
+            Err(err) => warn!("Failed to read dimension type {}: {}", entry.key, err),
+        }
+    }
+}
+
+/// Reads the fields of a dimension type that decide the shape of its chunks
+/// from its network NBT compound.
+fn read_dimension_type(nbt: &[u8]) -> io::Result<DimensionType> {
+    let mut reader = Cursor::new(nbt);
+    if reader.read_u8()? != TAG_COMPOUND {
+        return Err(invalid_data("dimension type isn't a compound"));
+    }
+
+    let (mut min_y, mut height, mut has_skylight) = (None, None, None);
+    loop {
+        let tag = reader.read_u8()?;
+        if tag == TAG_END {
+            break;
+        }
+        match (read_nbt_string(&mut reader)?.as_str(), tag) {
+            ("min_y", TAG_INT) => min_y = Some(reader.read_i32::<BigEndian>()?),
+            ("height", TAG_INT) => height = Some(reader.read_i32::<BigEndian>()?),
+            ("has_skylight", TAG_BYTE) => has_skylight = Some(reader.read_u8()? != 0),
+            _ => skip_nbt(&mut reader, tag)?,
+        }
+    }
+
+    match (min_y, height) {
+        (Some(min_y), Some(height)) if height > 0 => Ok(DimensionType {
+            min_y,
+            height: height as u32,
+            has_skylight: has_skylight.unwrap_or(true),
+        }),
+        _ => Err(invalid_data("dimension type has no min_y or height")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::WriteBytesExt;
+
+    use super::*;
+
+    const TAG_STRING: u8 = 8;
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.write_u16::<BigEndian>(value.len() as u16).unwrap();
+        data.extend(value.as_bytes());
+        data
+    }
+
+    fn named(tag: u8, name: &str) -> Vec<u8> {
+        let mut data = vec![tag];
+        data.extend(string(name));
+        data
+    }
+
+    #[test]
+    fn dimension_type_shape_is_read_from_nbt() {
+        let mut nbt = vec![TAG_COMPOUND];
+        nbt.extend(named(TAG_BYTE, "has_skylight"));
+        nbt.push(0);
+        nbt.extend(named(TAG_STRING, "effects"));
+        nbt.extend(string("minecraft:the_nether"));
+        nbt.extend(named(TAG_INT, "min_y"));
+        nbt.write_i32::<BigEndian>(-32).unwrap();
+        nbt.extend(named(TAG_INT, "height"));
+        nbt.write_i32::<BigEndian>(320).unwrap();
+        nbt.push(TAG_END);
+
+        assert_eq!(
+            read_dimension_type(&nbt).unwrap(),
+            DimensionType {
+                min_y: -32,
+                height: 320,
+                has_skylight: false,
+            }
+        );
+        assert!(read_dimension_type(&[TAG_COMPOUND, TAG_END]).is_err());
+    }
+
+    #[test]
+    fn reconfiguring_forgets_the_server_registries() {
+        let mut app = App::new();
+        app.add_message::<Login>()
+            .add_message::<Transfer>()
+            .add_message::<Reconfigure>()
+            .init_resource::<ServerRegistries>()
+            .insert_resource(CurrentDimension(DimensionType::NETHER))
+            .add_systems(Update, forget_server_registries);
+
+        let registries = app.world().resource::<ServerRegistries>().clone();
+        registries.set_registry(DIMENSION_TYPE, vec![String::from("example:deep")]);
+        app.update();
+        assert_eq!(registries.entry_count(DIMENSION_TYPE), Some(1));
+
+        app.world_mut().write_message(Reconfigure);
+        app.update();
+        assert_eq!(registries.entry_count(DIMENSION_TYPE), None);
+        assert_eq!(
+            *app.world().resource::<CurrentDimension>(),
+            CurrentDimension::default()
+        );
+    }
+}
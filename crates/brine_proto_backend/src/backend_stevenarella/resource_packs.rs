@@ -0,0 +1,83 @@
+//! Answers to the resource packs servers send, as the
+//! [`ConfigurationPolicy`] says.
+
+use bevy::prelude::*;
+use steven_protocol::protocol::VarInt;
+
+use brine_net::CodecWriter;
+
+use crate::configuration::ConfigurationPolicy;
+
+use super::{
+    codec::{packet, Packet, ProtocolCodec},
+    dispatch::{packet_kind, PacketHandlerAppExt},
+};
+
+// Possible values for the `result` field of the Resource Pack Response packet.
+const RESOURCE_PACK_LOADED: i32 = 0;
+const RESOURCE_PACK_DECLINED: i32 = 1;
+const RESOURCE_PACK_ACCEPTED: i32 = 3;
+const RESOURCE_PACK_DOWNLOADED: i32 = 4;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_packet_handler(
+        [
+            packet_kind!(ConfigurationClientboundAddResourcePack),
+            packet_kind!(PlayClientboundAddResourcePack),
+        ],
+        respond_to_resource_packs,
+    );
+}
+
+/// Handler that accepts or declines a resource pack. Accepted packs go
+/// through the responses a vanilla client sends while downloading and
+/// loading them, so the server sees them as loaded.
+fn respond_to_resource_packs(
+    InRef(packet): InRef<Packet>,
+    policy: Res<ConfigurationPolicy>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+) {
+    let (pack, in_play) = match packet {
+        Packet::Known(packet::Packet::ConfigurationClientboundAddResourcePack(pack)) => {
+            (pack, false)
+        }
+        Packet::Known(packet::Packet::PlayClientboundAddResourcePack(pack)) => (pack, true),
+        _ => return,
+    };
+
+    let accepted = policy.resource_packs.accepts(pack.forced);
+    info!(
+        "{} resource pack {} (required: {})",
+        if accepted { "Accepting" } else { "Declining" },
+        pack.url,
+        pack.forced
+    );
+
+    let results: &[i32] = if accepted {
+        &[
+            RESOURCE_PACK_ACCEPTED,
+            RESOURCE_PACK_DOWNLOADED,
+            RESOURCE_PACK_LOADED,
+        ]
+    } else {
+        &[RESOURCE_PACK_DECLINED]
+    };
+    for &result in results {
+        let response = if in_play {
+            packet::Packet::PlayServerboundResourcePackReceive(Box::new(
+                packet::play::serverbound::ResourcePackReceive {
+                    uuid: pack.uuid,
+                    result: VarInt(result),
+                },
+            ))
+        } else {
+            packet::Packet::ConfigurationServerboundResourcePackReceive(Box::new(
+                packet::configuration::serverbound::ResourcePackReceive {
+                    uuid: pack.uuid,
+                    result: VarInt(result),
+                },
+            ))
+        };
+        packet_writer.send(Packet::Known(response));
+    }
+}
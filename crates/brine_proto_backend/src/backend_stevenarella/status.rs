@@ -0,0 +1,249 @@
+//! Querying a server's status with stevenarella's packets. See the
+//! [`status`][crate::status] module.
+
+use std::{
+    io::{Cursor, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+use steven_protocol::protocol::VarInt;
+
+use crate::{
+    codec::{CodecLimits, MinecraftProtocolState, HANDSHAKE_STATUS_NEXT},
+    status::{ServerStatus, StatusError},
+    version::SUPPORTED_VERSIONS,
+};
+
+use super::{
+    codec::{packet, Direction, Error, MinecraftCodec, Packet, Serializable},
+    legacy::{LegacyKick, LEGACY_KICK_ID},
+};
+
+/// Protocol version sent in the handshake. Servers answer status requests
+/// whatever the version, and report their own.
+const STATUS_PROTOCOL_VERSION: i32 = -1;
+
+impl From<Error> for StatusError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IOError(io_error) => Self::Io(io_error),
+            Error::Err(message) => Self::Protocol(message),
+            error => Self::Protocol(format!("{error:?}")),
+        }
+    }
+}
+
+/// Asks the server at `server_addr` (`host:port`) for its status, waiting at
+/// most `timeout` for each step.
+pub(crate) fn query_status(server_addr: &str, timeout: Duration) -> Result<ServerStatus, Error> {
+    let addr = server_addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::Err(format!("{server_addr} did not resolve to an address")))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    send(
+        &mut stream,
+        packet::Packet::HandshakingServerboundSetProtocol(Box::new(
+            packet::handshake::serverbound::SetProtocol {
+                protocolVersion: VarInt(STATUS_PROTOCOL_VERSION),
+                nextState: VarInt(HANDSHAKE_STATUS_NEXT),
+                ..Default::default()
+            },
+        )),
+    )?;
+    send(
+        &mut stream,
+        packet::Packet::StatusServerboundPingStart(Box::new(
+            packet::status::serverbound::PingStart::default(),
+        )),
+    )?;
+
+    let response = match receive(&mut stream)? {
+        packet::Packet::StatusClientboundServerInfo(info) => info.response,
+        other => return Err(unexpected("status response", &other)),
+    };
+
+    let sent = Instant::now();
+    send(
+        &mut stream,
+        packet::Packet::StatusServerboundPing(Box::new(packet::status::serverbound::Ping {
+            time: 0,
+        })),
+    )?;
+    match receive(&mut stream)? {
+        packet::Packet::StatusClientboundPing(_) => {}
+        other => return Err(unexpected("pong", &other)),
+    }
+    let latency = sent.elapsed();
+
+    parse_status(&response, latency)
+}
+
+fn unexpected(expected: &str, packet: &packet::Packet) -> Error {
+    Error::Err(format!("expected a {expected}, got {packet:?}"))
+}
+
+fn send(stream: &mut TcpStream, packet: packet::Packet) -> Result<(), Error> {
+    // Any protocol version encodes the status packets the same way.
+    let mut body = Vec::new();
+    MinecraftCodec::encode_packet_id_and_data(
+        SUPPORTED_VERSIONS[0].protocol_version,
+        &packet,
+        &mut body,
+    )?;
+
+    let mut frame = Vec::new();
+    VarInt(body.len() as i32).write_to(&mut frame)?;
+    frame.extend_from_slice(&body);
+    stream.write_all(&frame)?;
+
+    Ok(())
+}
+
+fn receive(stream: &mut TcpStream) -> Result<packet::Packet, Error> {
+    let limits = CodecLimits::default();
+
+    // Every frame has at least a length and a packet ID.
+    let mut head = [0; 2];
+    stream.read_exact(&mut head)?;
+    if head == [LEGACY_KICK_ID, 0x00] {
+        return Err(Error::Err(receive_legacy_kick(stream)?.disconnect_reason()));
+    }
+    let mut reader = head.as_slice().chain(stream);
+
+    let length = VarInt::read_from(&mut reader)?.0;
+    let length = match usize::try_from(length) {
+        Ok(length) if length <= limits.max_packet_length => length,
+        _ => return Err(Error::Err(format!("invalid packet length {length}"))),
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let mut cursor = Cursor::new(body.as_slice());
+    let packet_id = VarInt::read_from(&mut cursor)?.0;
+    let data = &body[cursor.position() as usize..];
+
+    let packet = MinecraftCodec::decode_packet_with_id(
+        SUPPORTED_VERSIONS[0].protocol_version,
+        MinecraftProtocolState::Status,
+        Direction::Clientbound,
+        limits,
+        packet_id,
+        data,
+    )
+    .map_err(|err| Error::Err(err.to_string()))?;
+
+    match packet {
+        Packet::Known(packet) => Ok(packet),
+        Packet::Unknown(unknown) => Err(Error::Err(format!(
+            "unknown status packet 0x{:02X}",
+            unknown.packet_id
+        ))),
+        Packet::Legacy(kick) => Err(Error::Err(kick.disconnect_reason())),
+    }
+}
+
+/// Reads the rest of a legacy kick, after its ID and the high byte of its
+/// length.
+fn receive_legacy_kick(stream: &mut TcpStream) -> Result<LegacyKick, Error> {
+    let mut units = [0; 1];
+    stream.read_exact(&mut units)?;
+
+    let mut buf = vec![LEGACY_KICK_ID, 0x00, units[0]];
+    buf.resize(buf.len() + 2 * units[0] as usize, 0);
+    stream.read_exact(&mut buf[3..])?;
+
+    match LegacyKick::decode(MinecraftProtocolState::Status, &buf) {
+        Some(Ok((_, kick))) => Ok(kick),
+        _ => Err(Error::Err(String::from("malformed legacy kick"))),
+    }
+}
+
+/// Parses the JSON of a status response.
+fn parse_status(response: &str, latency: Duration) -> Result<ServerStatus, Error> {
+    let invalid = || Error::Err(format!("malformed status response: {response}"));
+
+    let status: Value = serde_json::from_str(response).map_err(|_| invalid())?;
+    let version = status.get("version").ok_or_else(invalid)?;
+    let players = status.get("players");
+
+    Ok(ServerStatus {
+        version_name: version
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        protocol_version: version
+            .get("protocol")
+            .and_then(Value::as_i64)
+            .ok_or_else(invalid)? as i32,
+        players_online: players
+            .and_then(|players| players.get("online"))
+            .and_then(Value::as_i64)
+            .unwrap_or_default(),
+        players_max: players
+            .and_then(|players| players.get("max"))
+            .and_then(Value::as_i64)
+            .unwrap_or_default(),
+        description: status
+            .get("description")
+            .map(plain_text)
+            .unwrap_or_default(),
+        latency,
+    })
+}
+
+/// Flattens a JSON text component (a string, an array, or an object with
+/// `text` and `extra`) into its plain text, dropping formatting.
+fn plain_text(component: &Value) -> String {
+    match component {
+        Value::String(text) => text.clone(),
+        Value::Array(components) => components.iter().map(plain_text).collect(),
+        Value::Object(object) => {
+            let mut text = object
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if let Some(extra) = object.get("extra") {
+                text.push_str(&plain_text(extra));
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_response_is_parsed() {
+        let response = r#"{
+            "version": {"name": "1.21.4", "protocol": 769},
+            "players": {"max": 20, "online": 3},
+            "description": {"text": "A ", "extra": [{"text": "Minecraft", "bold": true}, " Server"]}
+        }"#;
+
+        let status = parse_status(response, Duration::from_millis(12)).unwrap();
+
+        assert_eq!(
+            status,
+            ServerStatus {
+                version_name: String::from("1.21.4"),
+                protocol_version: 769,
+                players_online: 3,
+                players_max: 20,
+                description: String::from("A Minecraft Server"),
+                latency: Duration::from_millis(12),
+            }
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Confirmation of the server's Synchronize Player Position packets, and the
+//! player's own movement.
+//!
+//! The server moves the player with a position packet whose coordinates and
+//! angles may each be relative to where it thinks the player is: where the
+//! last teleport put the player, or wherever the client said it moved since
+//! ([`MovePlayer`]). [`PlayerPose`] keeps track of it to resolve the relative
+//! parts. Each teleport is confirmed with its ID and the resolved position,
+//! and sent on to the client application as [`PlayerTeleported`].
+
+use bevy::{math::DVec3, prelude::*};
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{
+    event::{clientbound::PlayerTeleported, serverbound::MovePlayer},
+    BrineSystems,
+};
+
+use crate::{codec::MinecraftProtocolState, version::shims_for};
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+const RELATIVE_X: u32 = 0x01;
+const RELATIVE_Y: u32 = 0x02;
+const RELATIVE_Z: u32 = 0x04;
+const RELATIVE_YAW: u32 = 0x08;
+const RELATIVE_PITCH: u32 = 0x10;
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<PlayerPose>();
+    app.add_systems(
+        Update,
+        (
+            respond_to_position_packets.in_set(BrineSystems::ProtocolDecode),
+            send_player_moves.in_set(BrineSystems::ProtocolEncode),
+        ),
+    );
+}
+
+/// Where the server thinks the player is.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+struct PlayerPose {
+    position: DVec3,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl PlayerPose {
+    /// Moves to the given position and angles, adding those marked relative
+    /// in `flags` to the current ones.
+    fn teleport(&mut self, flags: u32, position: DVec3, yaw: f32, pitch: f32) {
+        let resolve = |flag: u32, current: f64, value: f64| {
+            if flags & flag != 0 {
+                current + value
+            } else {
+                value
+            }
+        };
+        self.position = DVec3::new(
+            resolve(RELATIVE_X, self.position.x, position.x),
+            resolve(RELATIVE_Y, self.position.y, position.y),
+            resolve(RELATIVE_Z, self.position.z, position.z),
+        );
+        self.yaw = resolve(RELATIVE_YAW, self.yaw.into(), yaw.into()) as f32;
+        self.pitch =
+            (resolve(RELATIVE_PITCH, self.pitch.into(), pitch.into()) as f32).clamp(-90.0, 90.0);
+    }
+}
+
+/// System that confirms position packets and sends [`PlayerTeleported`]
+/// events to the client application.
+fn respond_to_position_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut pose: ResMut<PlayerPose>,
+    mut teleported_events: MessageWriter<PlayerTeleported>,
+) {
+    let shims = shims_for(net_resource.codec().protocol_version());
+
+    for packet in packet_reader.iter() {
+        let Packet::Known(packet::Packet::PlayClientboundPosition(pos)) = packet else {
+            continue;
+        };
+
+        // Since 1.21.2 the flags are a 32-bit field that also marks the
+        // velocity as relative; the position and angle bits are unchanged.
+        let flags = pos.flags as u32;
+        pose.teleport(flags, DVec3::new(pos.x, pos.y, pos.z), pos.yaw, pos.pitch);
+
+        let confirm = Packet::Known(packet::Packet::PlayServerboundTeleportConfirm(Box::new(
+            packet::play::serverbound::TeleportConfirm {
+                teleportId: pos.teleportId,
+            },
+        )));
+        packet_writer.send(confirm);
+
+        // Report arriving where the server put the player, as vanilla does.
+        let movement = Packet::Known(packet::Packet::PlayServerboundPositionLook(Box::new(
+            packet::play::serverbound::PositionLook {
+                x: pose.position.x,
+                y: pose.position.y,
+                z: pose.position.z,
+                yaw: pose.yaw,
+                pitch: pose.pitch,
+                flags: shims.movement_flags(false, false),
+            },
+        )));
+        packet_writer.send(movement);
+
+        debug!(
+            "Teleported: id={}, flags={:#x}, pos={}, angles=({}, {})",
+            pos.teleportId.0, flags, pose.position, pose.yaw, pose.pitch
+        );
+        teleported_events.write(PlayerTeleported {
+            position: pose.position,
+            yaw: pose.yaw,
+            pitch: pose.pitch,
+        });
+    }
+}
+
+/// System that sends where the player moved and looked.
+fn send_player_moves(
+    mut move_events: MessageReader<MovePlayer>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut pose: ResMut<PlayerPose>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        move_events.clear();
+        return;
+    }
+
+    let shims = shims_for(net_resource.codec().protocol_version());
+
+    for moved in move_events.read() {
+        pose.position = moved.position;
+        pose.yaw = moved.yaw;
+        pose.pitch = moved.pitch.clamp(-90.0, 90.0);
+
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundPositionLook(
+            Box::new(packet::play::serverbound::PositionLook {
+                x: pose.position.x,
+                y: pose.position.y,
+                z: pose.position.z,
+                yaw: pose.yaw,
+                pitch: pose.pitch,
+                flags: shims.movement_flags(moved.on_ground, false),
+            }),
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_teleports() {
+        let mut pose = PlayerPose::default();
+
+        pose.teleport(0, DVec3::new(10.0, 64.0, -5.0), 90.0, 10.0);
+        assert_eq!(
+            pose,
+            PlayerPose {
+                position: DVec3::new(10.0, 64.0, -5.0),
+                yaw: 90.0,
+                pitch: 10.0,
+            }
+        );
+
+        // Up two blocks and turned around, keeping X and Z and the pitch;
+        // a velocity bit (1.21.2+) changes nothing here.
+        pose.teleport(
+            RELATIVE_X | RELATIVE_Y | RELATIVE_Z | RELATIVE_YAW | RELATIVE_PITCH | 0x20,
+            DVec3::new(0.0, 2.0, 0.0),
+            180.0,
+            0.0,
+        );
+        assert_eq!(pose.position, DVec3::new(10.0, 66.0, -5.0));
+        assert_eq!((pose.yaw, pose.pitch), (270.0, 10.0));
+
+        // Pitch stays within straight up and straight down.
+        pose.teleport(RELATIVE_PITCH, DVec3::ZERO, 0.0, 85.0);
+        assert_eq!(pose.pitch, 90.0);
+    }
+}
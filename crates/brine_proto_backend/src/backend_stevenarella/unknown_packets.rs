@@ -0,0 +1,240 @@
+//! Statistics on the packets missing from the generated packet tables.
+//!
+//! Packets the tables don't define decode as [`Packet::Unknown`]. The backend
+//! counts them by protocol state and ID for the whole session, along with how
+//! large they were, and logs a report sorted by count whenever the connection
+//! ends and when the app exits, so the definitions worth adding first are the
+//! ones at the top. The report is also saved as JSON if
+//! [`PacketLogConfig::unknown_report`] names a file.
+
+use std::{fmt, fs::File, io::BufWriter};
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::Serialize;
+
+use brine_net::{CodecReader, NetworkEvent, NetworkResource};
+use brine_proto::BrineSystems;
+
+use crate::{codec::MinecraftProtocolState, packet_log::PacketLogConfig};
+
+use super::codec::{Packet, ProtocolCodec};
+
+/// Number of size buckets. The first holds packets under 16 bytes, each
+/// other one sizes up to four times as large as the one before, and the last
+/// everything larger.
+const SIZE_BUCKETS: usize = 6;
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<UnknownPackets>()
+        .add_systems(
+            Update,
+            record_unknown_packets.in_set(BrineSystems::ProtocolDecode),
+        )
+        .add_systems(Last, report_unknown_packets);
+}
+
+/// What was seen of one kind of unknown packet.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+struct UnknownPacketStats {
+    count: u64,
+    total_bytes: u64,
+    largest: usize,
+
+    /// Number of packets in each size bucket (see [`SIZE_BUCKETS`]).
+    sizes: [u64; SIZE_BUCKETS],
+}
+
+impl UnknownPacketStats {
+    fn record(&mut self, length: usize) {
+        self.count += 1;
+        self.total_bytes += length as u64;
+        self.largest = self.largest.max(length);
+        self.sizes[size_bucket(length)] += 1;
+    }
+}
+
+/// Unknown packets seen this session, by state and ID.
+#[derive(Resource, Debug, Default)]
+struct UnknownPackets {
+    seen: HashMap<(MinecraftProtocolState, i32), UnknownPacketStats>,
+
+    /// Number of packets counted when the report was last made.
+    reported: u64,
+}
+
+impl UnknownPackets {
+    fn total(&self) -> u64 {
+        self.seen.values().map(|stats| stats.count).sum()
+    }
+
+    /// Returns the report rows, most common first.
+    fn report(&self) -> Vec<ReportRow> {
+        let mut rows: Vec<_> = self
+            .seen
+            .iter()
+            .map(|((state, id), stats)| ReportRow {
+                state: format!("{:?}", state),
+                id: *id,
+                stats: stats.clone(),
+            })
+            .collect();
+        rows.sort_by(|a, b| (b.stats.count, &a.state, a.id).cmp(&(a.stats.count, &b.state, b.id)));
+        rows
+    }
+}
+
+/// One kind of unknown packet in the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct ReportRow {
+    state: String,
+    id: i32,
+    #[serde(flatten)]
+    stats: UnknownPacketStats,
+}
+
+impl fmt::Display for ReportRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} 0x{:02X}: {} packets, {} bytes, largest {} bytes, sizes",
+            self.state, self.id, self.stats.count, self.stats.total_bytes, self.stats.largest
+        )?;
+        let sizes: Vec<_> = self
+            .stats
+            .sizes
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| format!("{}: {}", size_bucket_label(bucket), count))
+            .collect();
+        write!(f, " {}", sizes.join(", "))
+    }
+}
+
+/// Returns the size bucket a packet of `length` bytes goes in.
+fn size_bucket(length: usize) -> usize {
+    let mut bucket = 0;
+    let mut limit = 16;
+    while length >= limit && bucket < SIZE_BUCKETS - 1 {
+        bucket += 1;
+        limit *= 4;
+    }
+    bucket
+}
+
+/// Returns the range of sizes in `bucket`, in bytes.
+fn size_bucket_label(bucket: usize) -> String {
+    let start = if bucket == 0 { 0 } else { 4 << (2 * bucket) };
+    if bucket == SIZE_BUCKETS - 1 {
+        format!("{}+", start)
+    } else {
+        format!("{}-{}", start, (16 << (2 * bucket)) - 1)
+    }
+}
+
+fn record_unknown_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut unknown_packets: ResMut<UnknownPackets>,
+) {
+    for packet in packet_reader.iter() {
+        if let Packet::Unknown(unknown) = &packet {
+            let state = net_resource.codec().protocol_state();
+            unknown_packets
+                .seen
+                .entry((state, unknown.packet_id))
+                .or_default()
+                .record(unknown.body.len());
+        }
+    }
+}
+
+/// Logs and saves the report after the connection ends or before the app
+/// exits, if there are unknown packets it doesn't have yet.
+fn report_unknown_packets(
+    mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
+    mut app_exit_events: MessageReader<AppExit>,
+    config: Res<PacketLogConfig>,
+    mut unknown_packets: ResMut<UnknownPackets>,
+) {
+    let disconnected = network_events
+        .read()
+        .any(|event| matches!(event, NetworkEvent::Disconnected));
+    let exiting = app_exit_events.read().count() > 0;
+
+    let total = unknown_packets.total();
+    if !(disconnected || exiting) || total == unknown_packets.reported {
+        return;
+    }
+    unknown_packets.reported = total;
+
+    let rows = unknown_packets.report();
+    let mut report = format!(
+        "{} unknown packets of {} kinds this session:",
+        total,
+        rows.len()
+    );
+    for row in &rows {
+        report.push_str(&format!("\n  {}", row));
+    }
+    info!("{}", report);
+
+    let Some(path) = &config.unknown_report else {
+        return;
+    };
+    let result = File::create(path)
+        .map_err(serde_json::Error::io)
+        .and_then(|file| serde_json::to_writer_pretty(BufWriter::new(file), &rows));
+    match result {
+        Ok(()) => info!("Saved the unknown packet report to {}", path.display()),
+        Err(err) => warn!(
+            "Failed to save the unknown packet report to {}: {}",
+            path.display(),
+            err
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_go_in_growing_buckets() {
+        assert_eq!(size_bucket(0), 0);
+        assert_eq!(size_bucket(15), 0);
+        assert_eq!(size_bucket(16), 1);
+        assert_eq!(size_bucket(63), 1);
+        assert_eq!(size_bucket(64), 2);
+        assert_eq!(size_bucket(1 << 20), SIZE_BUCKETS - 1);
+
+        assert_eq!(size_bucket_label(0), "0-15");
+        assert_eq!(size_bucket_label(1), "16-63");
+        assert_eq!(size_bucket_label(2), "64-255");
+        assert_eq!(size_bucket_label(SIZE_BUCKETS - 1), "4096+");
+    }
+
+    #[test]
+    fn report_lists_the_most_common_first() {
+        let mut unknown_packets = UnknownPackets::default();
+        let mut record = |state, id, length| {
+            unknown_packets
+                .seen
+                .entry((state, id))
+                .or_default()
+                .record(length);
+        };
+        record(MinecraftProtocolState::Configuration, 0x0E, 100);
+        record(MinecraftProtocolState::Play, 0x2A, 8);
+        record(MinecraftProtocolState::Play, 0x2A, 20);
+
+        let rows = unknown_packets.report();
+        assert_eq!(rows[0].id, 0x2A);
+        assert_eq!(
+            rows[0].to_string(),
+            "Play 0x2A: 2 packets, 28 bytes, largest 20 bytes, sizes 0-15: 1, 16-63: 1"
+        );
+        assert_eq!(rows[1].state, "Configuration");
+        assert_eq!(unknown_packets.total(), 3);
+    }
+}
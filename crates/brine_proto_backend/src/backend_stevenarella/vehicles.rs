@@ -0,0 +1,175 @@
+//! Translation of passenger and vehicle packets between packets and
+//! [`brine_proto`] events.
+
+use bevy::prelude::*;
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{
+    event::{
+        clientbound::{PassengersSet, VehicleMoved},
+        serverbound::{MoveVehicle, SteerVehicle},
+        DVec3,
+    },
+    BrineSystems,
+};
+
+use crate::{codec::MinecraftProtocolState, version::shims_for};
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+const INPUT_FORWARD: u8 = 0x01;
+const INPUT_BACKWARD: u8 = 0x02;
+const INPUT_LEFT: u8 = 0x04;
+const INPUT_RIGHT: u8 = 0x08;
+const INPUT_JUMP: u8 = 0x10;
+pub(super) const INPUT_SNEAK: u8 = 0x20;
+
+const STEER_JUMP: u8 = 0x01;
+const STEER_UNMOUNT: u8 = 0x02;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            handle_vehicle_packets.in_set(BrineSystems::ProtocolDecode),
+            (send_steering, send_vehicle_moves).in_set(BrineSystems::ProtocolEncode),
+        ),
+    );
+}
+
+/// System that listens for the Set Passengers and Move Vehicle packets and
+/// sends the corresponding events to the client application.
+fn handle_vehicle_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut passengers_events: MessageWriter<PassengersSet>,
+    mut vehicle_events: MessageWriter<VehicleMoved>,
+) {
+    for packet in packet_reader.iter() {
+        match packet {
+            Packet::Known(packet::Packet::PlayClientboundSetPassengers(passengers)) => {
+                passengers_events.write(PassengersSet {
+                    vehicle_id: passengers.entityId.0,
+                    passenger_ids: passengers.passengers.data.iter().map(|id| id.0).collect(),
+                });
+            }
+            Packet::Known(packet::Packet::PlayClientboundVehicleMove(moved)) => {
+                vehicle_events.write(VehicleMoved {
+                    position: DVec3::new(moved.x, moved.y, moved.z),
+                    yaw: moved.yaw,
+                    pitch: moved.pitch,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Encodes the inputs as the flags of the Player Input packet (1.21.2+).
+fn player_input_flags(steer: &SteerVehicle) -> u8 {
+    [
+        (steer.forward, INPUT_FORWARD),
+        (steer.backward, INPUT_BACKWARD),
+        (steer.left, INPUT_LEFT),
+        (steer.right, INPUT_RIGHT),
+        (steer.jump, INPUT_JUMP),
+        (steer.sneak, INPUT_SNEAK),
+    ]
+    .into_iter()
+    .filter(|(held, _)| *held)
+    .fold(0, |flags, (_, flag)| flags | flag)
+}
+
+/// Encodes the inputs as the sideways and forward axes and the flags of the
+/// Steer Vehicle packet. Positive sideways is to the left.
+fn steer_vehicle_axes(steer: &SteerVehicle) -> (f32, f32, u8) {
+    let axis = |positive: bool, negative: bool| f32::from(i8::from(positive) - i8::from(negative));
+    let mut flags = 0;
+    if steer.jump {
+        flags |= STEER_JUMP;
+    }
+    if steer.sneak {
+        flags |= STEER_UNMOUNT;
+    }
+    (
+        axis(steer.left, steer.right),
+        axis(steer.forward, steer.backward),
+        flags,
+    )
+}
+
+/// System that tells the server which inputs the player is holding.
+fn send_steering(
+    mut steer_events: MessageReader<SteerVehicle>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    // Only the latest inputs matter.
+    let Some(steer) = steer_events.read().last() else {
+        return;
+    };
+
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        return;
+    }
+
+    let packet = if shims_for(net_resource.codec().protocol_version()).player_input {
+        packet::Packet::PlayServerboundPlayerInput(Box::new(
+            packet::play::serverbound::PlayerInput {
+                inputs: player_input_flags(steer),
+            },
+        ))
+    } else {
+        let (sideways, forward, jump) = steer_vehicle_axes(steer);
+        packet::Packet::PlayServerboundSteerVehicle(Box::new(
+            packet::play::serverbound::SteerVehicle {
+                sideways,
+                forward,
+                jump,
+            },
+        ))
+    };
+    packet_writer.send(Packet::Known(packet));
+}
+
+/// System that sends where the player moved the vehicle they control.
+fn send_vehicle_moves(
+    mut move_events: MessageReader<MoveVehicle>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        move_events.clear();
+        return;
+    }
+
+    for moved in move_events.read() {
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundVehicleMove(
+            Box::new(packet::play::serverbound::VehicleMove {
+                x: moved.position.x,
+                y: moved.position.y,
+                z: moved.position.z,
+                yaw: moved.yaw,
+                pitch: moved.pitch,
+            }),
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_steering() {
+        let steer = SteerVehicle {
+            forward: true,
+            right: true,
+            sneak: true,
+            ..default()
+        };
+
+        assert_eq!(player_input_flags(&steer), 0x29);
+        assert_eq!(steer_vehicle_axes(&steer), (-1.0, 1.0, STEER_UNMOUNT));
+        assert_eq!(steer_vehicle_axes(&SteerVehicle::default()), (0.0, 0.0, 0));
+    }
+}
@@ -0,0 +1,219 @@
+//! Translation of world-related play packets (block updates, break progress,
+//! world border, etc.) into [`brine_proto`] events, and of the player's
+//! digging and block placement into packets.
+
+use bevy::prelude::*;
+
+use brine_net::{CodecReader, CodecWriter, NetworkResource};
+use brine_proto::{
+    event::{self, clientbound::WorldBorderUpdate, BlockFace, BlockState, DigStatus, Hand, IVec3},
+    BrineSystems,
+};
+use steven_protocol::{protocol::VarInt, shared::Position};
+
+use crate::codec::MinecraftProtocolState;
+
+use super::codec::{packet, Packet, ProtocolCodec};
+
+pub(crate) fn build(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            handle_block_change,
+            handle_block_change_acknowledgements,
+            handle_block_break_animation,
+            handle_world_border_packets,
+        )
+            .in_set(BrineSystems::ProtocolDecode),
+    )
+    .add_systems(
+        Update,
+        send_block_interactions.in_set(BrineSystems::ProtocolEncode),
+    );
+}
+
+fn face_id(face: BlockFace) -> i32 {
+    match face {
+        BlockFace::Down => 0,
+        BlockFace::Up => 1,
+        BlockFace::North => 2,
+        BlockFace::South => 3,
+        BlockFace::West => 4,
+        BlockFace::East => 5,
+    }
+}
+
+fn dig_status_id(status: DigStatus) -> i32 {
+    match status {
+        DigStatus::Start => 0,
+        DigStatus::Cancel => 1,
+        DigStatus::Finish => 2,
+    }
+}
+
+fn position(position: IVec3) -> Position {
+    Position::new(position.x, position.y, position.z)
+}
+
+/// System that tells the server when the player digs or places blocks.
+fn send_block_interactions(
+    mut dig_events: MessageReader<event::serverbound::DigBlock>,
+    mut place_events: MessageReader<event::serverbound::PlaceBlock>,
+    mut packet_writer: CodecWriter<ProtocolCodec>,
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+) {
+    if net_resource.codec().protocol_state() != MinecraftProtocolState::Play {
+        dig_events.clear();
+        place_events.clear();
+        return;
+    }
+
+    for dig in dig_events.read() {
+        debug!("{:?} digging block at {}", dig.status, dig.position);
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundBlockDig(
+            Box::new(packet::play::serverbound::BlockDig {
+                status: VarInt(dig_status_id(dig.status)),
+                location: position(dig.position),
+                face: face_id(dig.face) as i8,
+                sequence: VarInt(dig.sequence),
+            }),
+        )));
+    }
+
+    for place in place_events.read() {
+        debug!(
+            "Using {:?} hand on {:?} face of block at {}",
+            place.hand, place.face, place.position
+        );
+        packet_writer.send(Packet::Known(packet::Packet::PlayServerboundBlockPlace(
+            Box::new(packet::play::serverbound::BlockPlace {
+                hand: VarInt(match place.hand {
+                    Hand::Main => 0,
+                    Hand::Off => 1,
+                }),
+                location: position(place.position),
+                direction: VarInt(face_id(place.face)),
+                cursorX: place.cursor.x,
+                cursorY: place.cursor.y,
+                cursorZ: place.cursor.z,
+                insideBlock: false,
+                worldBorderHit: false,
+                sequence: VarInt(place.sequence),
+            }),
+        )));
+    }
+}
+
+/// System that listens for BlockChange packets and sends BlockChanged events
+/// to the client application.
+fn handle_block_change(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut changed_events: MessageWriter<event::clientbound::BlockChanged>,
+) {
+    for packet in packet_reader.iter() {
+        if let Packet::Known(packet::Packet::PlayClientboundBlockChange(change)) = packet {
+            let location = &change.location;
+
+            changed_events.write(event::clientbound::BlockChanged {
+                position: IVec3::new(location.x, location.y, location.z),
+                block_state: BlockState(change.ty.0 as u32),
+            });
+        }
+    }
+}
+
+/// System that listens for AcknowledgePlayerDigging packets and sends
+/// BlockChangesAcknowledged events to the client application.
+fn handle_block_change_acknowledgements(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut acknowledged_events: MessageWriter<event::clientbound::BlockChangesAcknowledged>,
+) {
+    for packet in packet_reader.iter() {
+        if let Packet::Known(packet::Packet::PlayClientboundAcknowledgePlayerDigging(ack)) = packet
+        {
+            acknowledged_events.write(event::clientbound::BlockChangesAcknowledged {
+                sequence: ack.sequenceId.0,
+            });
+        }
+    }
+}
+
+/// System that listens for BlockBreakAnimation packets and sends
+/// BlockBreakProgress events to the client application.
+fn handle_block_break_animation(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut break_events: MessageWriter<event::clientbound::BlockBreakProgress>,
+) {
+    for packet in packet_reader.iter() {
+        if let Packet::Known(packet::Packet::PlayClientboundBlockBreakAnimation(animation)) = packet
+        {
+            let location = &animation.location;
+
+            // Any stage outside of 0..=9 means the entity stopped digging.
+            let stage = u8::try_from(animation.destroyStage)
+                .ok()
+                .filter(|stage| *stage <= 9);
+
+            break_events.write(event::clientbound::BlockBreakProgress {
+                entity_id: animation.entityId.0,
+                position: IVec3::new(location.x, location.y, location.z),
+                stage,
+            });
+        }
+    }
+}
+
+/// System that listens for the world border packets and sends
+/// WorldBorderUpdate events to the client application.
+fn handle_world_border_packets(
+    mut packet_reader: CodecReader<ProtocolCodec>,
+    mut border_events: MessageWriter<WorldBorderUpdate>,
+) {
+    for packet in packet_reader.iter() {
+        let update = match packet {
+            Packet::Known(packet::Packet::PlayClientboundInitializeWorldBorder(border)) => {
+                WorldBorderUpdate::Initialize {
+                    center_x: border.x,
+                    center_z: border.z,
+                    old_diameter: border.oldDiameter,
+                    new_diameter: border.newDiameter,
+                    lerp_millis: i64::from(border.speed.0),
+                    portal_teleport_boundary: border.portalTeleportBoundary.0,
+                    warning_blocks: border.warningBlocks.0,
+                    warning_seconds: border.warningTime.0,
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundWorldBorderCenter(center)) => {
+                WorldBorderUpdate::Center {
+                    x: center.x,
+                    z: center.z,
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundWorldBorderLerpSize(lerp)) => {
+                WorldBorderUpdate::LerpSize {
+                    old_diameter: lerp.oldDiameter,
+                    new_diameter: lerp.newDiameter,
+                    lerp_millis: i64::from(lerp.speed.0),
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundWorldBorderSize(size)) => {
+                WorldBorderUpdate::Size {
+                    diameter: size.diameter,
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundWorldBorderWarningDelay(delay)) => {
+                WorldBorderUpdate::WarningDelay {
+                    seconds: delay.warningTime.0,
+                }
+            }
+            Packet::Known(packet::Packet::PlayClientboundWorldBorderWarningReach(reach)) => {
+                WorldBorderUpdate::WarningDistance {
+                    blocks: reach.warningBlocks.0,
+                }
+            }
+            _ => continue,
+        };
+
+        border_events.write(update);
+    }
+}
@@ -0,0 +1,84 @@
+//! Chunks as the server sent them, before they are decoded.
+//!
+//! The backend decodes chunk packets into
+//! [`ChunkData`][brine_proto::event::clientbound::ChunkData] events itself.
+//! Tools that save chunks to files (e.g. `chunktool save`) want the packet's
+//! parts as they came over the wire instead, so that they can be decoded
+//! again later without a server. The [`ActiveBackend`] knows how they are
+//! laid out in its packets; [`chunk_data`] and [`chunk_extras`] ask it for
+//! them.
+
+use bevy::log::warn;
+
+use brine_chunk::{decode::Result, Chunk, GlobalPalette, Heightmap};
+use brine_net::Decode;
+
+use crate::{ActiveBackend, ProtocolBackend, ProtocolCodec};
+
+/// A packet of the [`ActiveBackend`]'s codec.
+pub type Packet = <ProtocolCodec as Decode>::Item;
+
+/// Common representation of the different versions of ChunkData packets.
+pub struct ChunkData<T> {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub full_chunk: bool,
+    pub bitmask: u32,
+    pub data: T,
+
+    /// The packed `MOTION_BLOCKING` heightmap, if the packet has one.
+    pub heightmap: Option<Vec<i64>>,
+}
+
+impl<T: AsRef<[u8]>> ChunkData<T> {
+    /// Decodes the chunk. A heightmap that doesn't decode is left out rather
+    /// than failing the chunk.
+    pub fn decode(&self) -> Result<Chunk> {
+        let mut buf = self.data.as_ref();
+        let mut chunk = Chunk::decode(
+            self.chunk_x,
+            self.chunk_z,
+            self.full_chunk,
+            self.bitmask,
+            &GlobalPalette,
+            &mut buf,
+        )?;
+
+        if let Some(packed) = &self.heightmap {
+            match Heightmap::decode(packed) {
+                Ok(heightmap) => chunk.heightmap = Some(Box::new(heightmap)),
+                Err(err) => warn!(
+                    "Failed to decode heightmap of chunk ({}, {}): {}",
+                    self.chunk_x, self.chunk_z, err
+                ),
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+/// The parts of a chunk that aren't decoded into a [`Chunk`], kept
+/// protocol-encoded: its block entities and its light.
+pub struct ChunkExtras {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+
+    /// The block entity array (a VarInt count, then the entries with their
+    /// NBT), if the packet has one. Light updates don't.
+    pub block_entities: Option<Vec<u8>>,
+
+    /// The light data, as decoded by [`brine_chunk::ChunkLight::decode`].
+    pub light: Vec<u8>,
+}
+
+/// Returns the undecoded chunk data of a chunk packet from the overworld, or
+/// `None` if `packet` isn't one.
+pub fn chunk_data(packet: &Packet) -> Option<ChunkData<&[u8]>> {
+    ActiveBackend::chunk_data(packet)
+}
+
+/// Returns the block entities and light of a chunk packet, or the light of a
+/// light update, or `None` if `packet` is neither.
+pub fn chunk_extras(packet: &Packet) -> Option<ChunkExtras> {
+    ActiveBackend::chunk_extras(packet)
+}
@@ -0,0 +1,268 @@
+//! An async client for use without a Bevy app.
+//!
+//! [`Connection`] runs the [`ProtocolBackendPlugin`] (codec, login state
+//! machine and all) in a private app that it updates whenever one of its
+//! futures is polled, and hands out the high-level events of
+//! [`brine_proto::event`]. CLI bots and tests get the same protocol handling
+//! as the client without setting up an app of their own.
+//!
+//! The futures don't depend on a particular executor.
+//!
+//! ```no_run
+//! use brine_proto_backend::client::{ClientboundEvent, Connection};
+//!
+//! # async fn run() -> Result<(), brine_proto_backend::client::Error> {
+//! let mut connection = Connection::login("localhost:25565", "Bot").await?;
+//!
+//! while let Some(event) = connection.recv().await {
+//!     if let ClientboundEvent::ChunkData(chunk) = event {
+//!         println!("chunk {} {}", chunk.chunk_data.chunk_x, chunk.chunk_data.chunk_z);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::VecDeque, time::Duration};
+
+use async_io::Timer;
+use bevy::{ecs::message::Message, prelude::*, state::app::StatesPlugin};
+use thiserror::Error;
+
+use brine_proto::{
+    event::{clientbound, serverbound::Login},
+    ProtocolPlugin,
+};
+
+use crate::ProtocolBackendPlugin;
+
+/// How long to wait between updates of the connection's app while waiting for
+/// events.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("disconnected before logging in: {0}")]
+    Disconnected(String),
+}
+
+macro_rules! clientbound_events {
+    ($($event:ident),* $(,)?) => {
+        /// An event received from the server.
+        ///
+        /// See [`brine_proto::event::clientbound`] for what each one means.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum ClientboundEvent {
+            $($event(clientbound::$event),)*
+        }
+
+        $(
+            impl From<clientbound::$event> for ClientboundEvent {
+                fn from(event: clientbound::$event) -> Self {
+                    Self::$event(event)
+                }
+            }
+        )*
+
+        fn add_collectors(app: &mut App) {
+            $(app.add_systems(Last, collect_events::<clientbound::$event>);)*
+        }
+    };
+}
+
+clientbound_events!(
+    LoginSuccess,
+    JoinedGame,
+    Disconnect,
+    Transfer,
+    Reconfigure,
+    PluginMessage,
+    ChunkData,
+    BlockBreakProgress,
+    WorldBorderUpdate,
+    HotbarSlotSelected,
+    InventoryContents,
+    InventorySlot,
+    EntitySpawned,
+    EntityMoved,
+    EntitiesRemoved,
+    PlayerAdded,
+    PlayerDisplayName,
+    PlayersRemoved,
+    HealthUpdated,
+    EntityDamaged,
+    EntityEffectAdded,
+    EntityEffectRemoved,
+);
+
+/// Events received by the app and not yet handed out.
+#[derive(Resource, Default)]
+struct Received(VecDeque<ClientboundEvent>);
+
+fn collect_events<E>(mut events: MessageReader<E>, mut received: ResMut<Received>)
+where
+    E: Message + Clone + Into<ClientboundEvent>,
+{
+    received.0.extend(events.read().cloned().map(Into::into));
+}
+
+/// A connection to a server, logged in and in play.
+///
+/// Events of different types that arrive in the same update are handed out
+/// grouped by type; events of one type keep their order.
+pub struct Connection {
+    app: App,
+    disconnected: bool,
+}
+
+impl Connection {
+    /// Connects to the server at `server_addr` (`host:port`) and logs in as
+    /// `username`, with a default [`ProtocolBackendPlugin`].
+    pub async fn login(
+        server_addr: impl Into<String>,
+        username: impl Into<String>,
+    ) -> Result<Self, Error> {
+        Self::login_with(ProtocolBackendPlugin::default(), server_addr, username).await
+    }
+
+    /// Connects to the server at `server_addr` (`host:port`) and logs in as
+    /// `username`, speaking the protocol through `backend`.
+    ///
+    /// Resolves once the server has accepted the login. Its
+    /// [`LoginSuccess`][clientbound::LoginSuccess] is the first event
+    /// [`recv`][Self::recv] returns.
+    pub async fn login_with(
+        backend: ProtocolBackendPlugin,
+        server_addr: impl Into<String>,
+        username: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, ProtocolPlugin, backend))
+            .init_resource::<Received>();
+        add_collectors(&mut app);
+        app.finish();
+        app.cleanup();
+
+        app.world_mut().write_message(Login {
+            server: server_addr.into(),
+            username: username.into(),
+        });
+
+        let mut connection = Self {
+            app,
+            disconnected: false,
+        };
+
+        loop {
+            connection.update().await;
+
+            for event in &connection.received().0 {
+                match event {
+                    ClientboundEvent::LoginSuccess(_) => return Ok(connection),
+                    ClientboundEvent::Disconnect(disconnect) => {
+                        return Err(Error::Disconnected(disconnect.reason.clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Sends a serverbound event (one of
+    /// [`brine_proto::event::serverbound`]) to the server.
+    pub async fn send<E: Message>(&mut self, event: E) {
+        self.app.world_mut().write_message(event);
+        self.update().await;
+    }
+
+    /// Waits for the next event from the server.
+    ///
+    /// Returns `None` once the [`Disconnect`][clientbound::Disconnect] event
+    /// has been returned.
+    pub async fn recv(&mut self) -> Option<ClientboundEvent> {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            if self.disconnected {
+                return None;
+            }
+            self.update().await;
+        }
+    }
+
+    /// Returns the next event that has already been received, if any.
+    pub fn try_recv(&mut self) -> Option<ClientboundEvent> {
+        let event = self
+            .app
+            .world_mut()
+            .resource_mut::<Received>()
+            .0
+            .pop_front()?;
+        if let ClientboundEvent::Disconnect(_) = event {
+            self.disconnected = true;
+        }
+        Some(event)
+    }
+
+    /// The app the connection runs in, for access to the backend's resources.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    fn received(&self) -> &Received {
+        self.app.world().resource::<Received>()
+    }
+
+    /// Waits a moment for the connection to make progress, then updates the
+    /// app.
+    async fn update(&mut self) {
+        Timer::after(UPDATE_INTERVAL).await;
+        self.app.update();
+    }
+}
+
+#[cfg(all(test, feature = "stevenarella"))]
+mod tests {
+    use std::thread;
+
+    use futures::executor::block_on;
+
+    use crate::{backend_stevenarella::fake_server::FakeServer, version::get_protocol_version};
+
+    use super::*;
+
+    #[test]
+    fn logs_in_and_receives_a_chunk() {
+        let server = FakeServer::start(get_protocol_version("1.21.4").unwrap()).unwrap();
+
+        let mut connection = block_on(async {
+            let mut connection = Connection::login(server.addr(), "Player").await.unwrap();
+
+            assert!(matches!(
+                connection.recv().await,
+                Some(ClientboundEvent::LoginSuccess(login)) if login.username == "Player"
+            ));
+            loop {
+                match connection.recv().await {
+                    Some(ClientboundEvent::ChunkData(chunk)) => {
+                        assert_eq!((chunk.chunk_data.chunk_x, chunk.chunk_data.chunk_z), (0, 0));
+                        break;
+                    }
+                    Some(ClientboundEvent::Disconnect(disconnect)) => {
+                        panic!("disconnected: {}", disconnect.reason)
+                    }
+                    _ => {}
+                }
+            }
+            connection
+        });
+
+        // The server checks the client's side of the exchange.
+        while !server.is_finished() {
+            connection.app_mut().update();
+            thread::sleep(UPDATE_INTERVAL);
+        }
+        server.join().unwrap();
+    }
+}
@@ -5,12 +5,12 @@ use std::{
     marker::PhantomData,
     ops::Deref,
     sync::{
-        atomic::{AtomicI32, AtomicU8, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, AtomicUsize, Ordering},
+        Arc, Mutex,
     },
 };
 
-use bevy::prelude::Resource;
+use bevy::prelude::{Message, Resource};
 
 use brine_net::{DecodeResult, EncodeResult};
 
@@ -19,6 +19,18 @@ use crate::version::get_protocol_version;
 // Possible values for the `next` field in the Handshake packet.
 pub const HANDSHAKE_STATUS_NEXT: i32 = 1;
 pub const HANDSHAKE_LOGIN_NEXT: i32 = 2;
+/// Login after being sent by another server with a Transfer packet (1.20.5+).
+pub const HANDSHAKE_TRANSFER_NEXT: i32 = 3;
+
+/// Default for [`CodecLimits::max_packet_length`]. Vanilla uses the same
+/// limit: the most a three-byte VarInt can hold.
+pub const MAX_PACKET_LENGTH: usize = (1 << 21) - 1;
+
+/// Default for [`CodecLimits::max_decompressed_length`].
+pub const MAX_DECOMPRESSED_LENGTH: usize = 1 << 23;
+
+/// Default for [`CodecLimits::max_array_length`].
+pub const MAX_ARRAY_LENGTH: usize = 1 << 20;
 
 /// A protocol version has to be sent in the Handshake packet, even when
 /// attempting to discover the protocol version of the server. This is the value
@@ -37,6 +49,123 @@ pub enum MinecraftProtocolState {
     Play,
 }
 
+/// Limits on the sizes a server can claim in the packets it sends.
+///
+/// Sizes come from the network, so they are checked before anything is
+/// allocated based on them. Packets that exceed a limit fail to decode with a
+/// protocol error, which ends the connection.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodecLimits {
+    /// Largest packet accepted, in bytes, not counting its length prefix.
+    pub max_packet_length: usize,
+
+    /// Largest size a compressed packet may claim to decompress to, in bytes.
+    pub max_decompressed_length: usize,
+
+    /// Largest number of elements a length-prefixed array in a packet may
+    /// claim. For byte arrays, this is a number of bytes.
+    pub max_array_length: usize,
+}
+
+impl Default for CodecLimits {
+    fn default() -> Self {
+        Self {
+            max_packet_length: MAX_PACKET_LENGTH,
+            max_decompressed_length: MAX_DECOMPRESSED_LENGTH,
+            max_array_length: MAX_ARRAY_LENGTH,
+        }
+    }
+}
+
+/// A received packet whose definition didn't read all of it, most likely
+/// because the definition is missing fields the server's version added.
+///
+/// The codec reports these when strict decoding is on (see
+/// [`CodecState::set_strict_decoding`]); otherwise they are only logged.
+/// Either way, the packet is still decoded and handled.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolAnomaly {
+    pub protocol_version: i32,
+    pub state: MinecraftProtocolState,
+    pub packet_id: i32,
+
+    /// Number of bytes the definition read.
+    pub consumed: usize,
+
+    /// The packet's data, after its ID.
+    pub body: Vec<u8>,
+}
+
+impl fmt::Display for ProtocolAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "packet 0x{:02X} in {:?} (protocol {}) read {} of {} bytes: {}",
+            self.packet_id,
+            self.state,
+            self.protocol_version,
+            self.consumed,
+            self.body.len(),
+            hex_dump(&self.body)
+        )
+    }
+}
+
+/// Totals for the packets sent or received compressed since the last
+/// handshake. Packets under the compression threshold are not counted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionStats {
+    pub packets: u64,
+
+    /// Bytes the packets took up compressed, not counting their length
+    /// prefixes.
+    pub compressed_bytes: u64,
+
+    /// Bytes the packets took up once decompressed.
+    pub uncompressed_bytes: u64,
+}
+
+impl CompressionStats {
+    /// Returns how many times smaller compression made the packets, or `None`
+    /// until a packet has been compressed.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.compressed_bytes > 0)
+            .then(|| self.uncompressed_bytes as f64 / self.compressed_bytes as f64)
+    }
+}
+
+/// [`CompressionStats`] that can be updated from the connection tasks.
+#[derive(Default)]
+struct CompressionCounters {
+    packets: AtomicU64,
+    compressed_bytes: AtomicU64,
+    uncompressed_bytes: AtomicU64,
+}
+
+impl CompressionCounters {
+    fn record(&self, compressed_bytes: usize, uncompressed_bytes: usize) {
+        self.packets.fetch_add(1, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed_bytes as u64, Ordering::Relaxed);
+        self.uncompressed_bytes
+            .fetch_add(uncompressed_bytes as u64, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> CompressionStats {
+        CompressionStats {
+            packets: self.packets.load(Ordering::Relaxed),
+            compressed_bytes: self.compressed_bytes.load(Ordering::Relaxed),
+            uncompressed_bytes: self.uncompressed_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        self.packets.store(0, Ordering::Relaxed);
+        self.compressed_bytes.store(0, Ordering::Relaxed);
+        self.uncompressed_bytes.store(0, Ordering::Relaxed);
+    }
+}
+
 /// Thin wrapper around some concrete implementation of the Minecraft protocol.
 #[derive(Resource)]
 pub struct MinecraftClientCodec<Backend> {
@@ -80,6 +209,22 @@ pub struct CodecState {
     protocol_version: AtomicI32,
     /// Compression threshold negotiated via the SetCompression packet.
     compression_threshold: AtomicI32,
+    /// Whether to send every packet uncompressed, even once compression is
+    /// negotiated.
+    outbound_compression_disabled: AtomicBool,
+    inbound_compression: CompressionCounters,
+    outbound_compression: CompressionCounters,
+    /// Bytes received and sent, length prefixes included, over every
+    /// connection.
+    inbound_bytes: AtomicU64,
+    outbound_bytes: AtomicU64,
+    /// Whether to report [`ProtocolAnomaly`]s, and those not yet taken.
+    strict_decoding: AtomicBool,
+    anomalies: Mutex<Vec<ProtocolAnomaly>>,
+    /// See [`CodecLimits`].
+    max_packet_length: AtomicUsize,
+    max_decompressed_length: AtomicUsize,
+    max_array_length: AtomicUsize,
 }
 
 impl Default for CodecState {
@@ -90,6 +235,16 @@ impl Default for CodecState {
                 get_protocol_version(DEFAULT_PROTOCOL_VERSION_STRING).unwrap(),
             ),
             compression_threshold: AtomicI32::new(-1),
+            outbound_compression_disabled: AtomicBool::new(false),
+            inbound_compression: CompressionCounters::default(),
+            outbound_compression: CompressionCounters::default(),
+            inbound_bytes: AtomicU64::new(0),
+            outbound_bytes: AtomicU64::new(0),
+            strict_decoding: AtomicBool::new(false),
+            anomalies: Mutex::default(),
+            max_packet_length: AtomicUsize::new(MAX_PACKET_LENGTH),
+            max_decompressed_length: AtomicUsize::new(MAX_DECOMPRESSED_LENGTH),
+            max_array_length: AtomicUsize::new(MAX_ARRAY_LENGTH),
         }
     }
 }
@@ -144,6 +299,103 @@ impl CodecState {
         let value = threshold.unwrap_or(-1);
         self.compression_threshold.store(value, Ordering::Relaxed);
     }
+
+    /// Returns whether packets are sent uncompressed even when they reach the
+    /// compression threshold.
+    pub fn outbound_compression_disabled(&self) -> bool {
+        self.outbound_compression_disabled.load(Ordering::Relaxed)
+    }
+
+    /// Sends every packet uncompressed, or stops doing so. Packets keep the
+    /// compressed framing once compression is negotiated, which servers accept
+    /// whatever their threshold.
+    pub fn set_outbound_compression_disabled(&self, disabled: bool) {
+        self.outbound_compression_disabled
+            .store(disabled, Ordering::Relaxed);
+    }
+
+    /// Returns the totals for the packets received compressed.
+    pub fn inbound_compression(&self) -> CompressionStats {
+        self.inbound_compression.load()
+    }
+
+    /// Returns the totals for the packets sent compressed.
+    pub fn outbound_compression(&self) -> CompressionStats {
+        self.outbound_compression.load()
+    }
+
+    pub fn record_inbound_compression(&self, compressed_bytes: usize, uncompressed_bytes: usize) {
+        self.inbound_compression
+            .record(compressed_bytes, uncompressed_bytes);
+    }
+
+    pub fn record_outbound_compression(&self, compressed_bytes: usize, uncompressed_bytes: usize) {
+        self.outbound_compression
+            .record(compressed_bytes, uncompressed_bytes);
+    }
+
+    /// Returns how many bytes of packets have been received, over every
+    /// connection. Unlike the compression totals, it is never reset.
+    pub fn inbound_bytes(&self) -> u64 {
+        self.inbound_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Like [`inbound_bytes`][Self::inbound_bytes], for the packets sent.
+    pub fn outbound_bytes(&self) -> u64 {
+        self.outbound_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn record_inbound_bytes(&self, bytes: usize) {
+        self.inbound_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_outbound_bytes(&self, bytes: usize) {
+        self.outbound_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Returns whether decoded packets that weren't read in full are reported
+    /// as [`ProtocolAnomaly`]s.
+    pub fn strict_decoding(&self) -> bool {
+        self.strict_decoding.load(Ordering::Relaxed)
+    }
+
+    pub fn set_strict_decoding(&self, strict: bool) {
+        self.strict_decoding.store(strict, Ordering::Relaxed);
+    }
+
+    pub fn push_anomaly(&self, anomaly: ProtocolAnomaly) {
+        self.anomalies.lock().unwrap().push(anomaly);
+    }
+
+    /// Returns the anomalies found since this was last called.
+    pub fn take_anomalies(&self) -> Vec<ProtocolAnomaly> {
+        std::mem::take(&mut *self.anomalies.lock().unwrap())
+    }
+
+    /// Forgets the compression totals, for a new connection.
+    pub fn reset_compression_stats(&self) {
+        self.inbound_compression.reset();
+        self.outbound_compression.reset();
+    }
+
+    pub fn limits(&self) -> CodecLimits {
+        CodecLimits {
+            max_packet_length: self.max_packet_length.load(Ordering::Relaxed),
+            max_decompressed_length: self.max_decompressed_length.load(Ordering::Relaxed),
+            max_array_length: self.max_array_length.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn set_limits(&self, limits: CodecLimits) {
+        self.max_packet_length
+            .store(limits.max_packet_length, Ordering::Relaxed);
+        self.max_decompressed_length
+            .store(limits.max_decompressed_length, Ordering::Relaxed);
+        self.max_array_length
+            .store(limits.max_array_length, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,140 @@
+//! What the client tells the server during the configuration phase.
+//!
+//! The [`ConfigurationPolicy`] resource decides which of the server's known
+//! packs the client claims to have, how it answers resource pack offers, and
+//! the client information it reports. It is read whenever the server
+//! configures the client, so changes take effect at the next login or
+//! reconfiguration.
+
+use bevy::prelude::*;
+
+/// Every skin part shown: cape, jacket, both sleeves, both trouser legs and
+/// hat.
+pub const ALL_SKIN_PARTS: u8 = 0x7F;
+
+/// How the client answers the server during the configuration phase.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigurationPolicy {
+    /// Which of the data packs the server offers the client claims to have.
+    pub known_packs: KnownPacks,
+
+    /// How to answer the server's resource packs.
+    pub resource_packs: ResourcePackPolicy,
+
+    /// What the client reports about itself.
+    pub client_information: ClientInformation,
+}
+
+/// Which of the data packs offered in Select Known Packs the client claims.
+///
+/// The server leaves out the registry data that claimed packs contain, so
+/// claiming fewer makes it send more.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum KnownPacks {
+    /// Every pack the server offers.
+    #[default]
+    All,
+
+    /// Only these, of the packs the server offers. Empty claims none, so the
+    /// server sends all of its registry data.
+    Only(Vec<KnownPack>),
+}
+
+impl KnownPacks {
+    /// Returns whether the client claims the pack `namespace:id` at
+    /// `version`.
+    pub fn claims(&self, namespace: &str, id: &str, version: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(packs) => packs.iter().any(|pack| {
+                pack.namespace == namespace && pack.id == id && pack.version == version
+            }),
+        }
+    }
+}
+
+/// A data pack, e.g. `minecraft:core` at version `1.21.4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownPack {
+    pub namespace: String,
+    pub id: String,
+    pub version: String,
+}
+
+/// How to answer the resource packs a server sends, whether while
+/// configuring or in play.
+///
+/// The client doesn't download server resource packs, so accepting one only
+/// tells the server it was loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ResourcePackPolicy {
+    /// Decline every pack. Servers that require theirs disconnect the client.
+    #[default]
+    Decline,
+
+    /// Accept the packs the server requires, and decline the rest.
+    AcceptRequired,
+
+    /// Accept every pack.
+    Accept,
+}
+
+impl ResourcePackPolicy {
+    /// Returns whether to accept a pack, which the server may require.
+    pub fn accepts(self, required: bool) -> bool {
+        match self {
+            Self::Decline => false,
+            Self::AcceptRequired => required,
+            Self::Accept => true,
+        }
+    }
+}
+
+/// What the client reports about itself in Client Information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInformation {
+    /// Language, e.g. `en_us`.
+    pub locale: String,
+
+    /// Render distance, in chunks.
+    pub view_distance: i8,
+
+    /// Bits of the skin parts shown (see [`ALL_SKIN_PARTS`]).
+    pub skin_parts: u8,
+}
+
+impl Default for ClientInformation {
+    fn default() -> Self {
+        Self {
+            locale: String::from("en_us"),
+            view_distance: 12,
+            skin_parts: ALL_SKIN_PARTS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_listed_packs_are_claimed() {
+        let core = KnownPack {
+            namespace: String::from("minecraft"),
+            id: String::from("core"),
+            version: String::from("1.21.4"),
+        };
+        assert!(KnownPacks::All.claims("minecraft", "core", "1.21.4"));
+        assert!(KnownPacks::Only(vec![core.clone()]).claims("minecraft", "core", "1.21.4"));
+        assert!(!KnownPacks::Only(vec![core]).claims("minecraft", "core", "1.21.1"));
+        assert!(!KnownPacks::Only(Vec::new()).claims("minecraft", "core", "1.21.4"));
+    }
+
+    #[test]
+    fn required_packs_can_be_accepted_alone() {
+        assert!(!ResourcePackPolicy::Decline.accepts(true));
+        assert!(ResourcePackPolicy::AcceptRequired.accepts(true));
+        assert!(!ResourcePackPolicy::AcceptRequired.accepts(false));
+        assert!(ResourcePackPolicy::Accept.accepts(false));
+    }
+}
@@ -1,11 +1,23 @@
 //! Low-level client-server protocol implementation.
 
+pub mod backend;
+pub mod chunks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
 pub mod codec;
+pub mod configuration;
+pub mod packet_log;
 mod plugin;
+pub mod status;
 pub mod version;
 
+#[cfg(feature = "stevenarella")]
 pub mod backend_stevenarella;
 
-pub(crate) use backend_stevenarella as backend;
+pub(crate) use plugin::ProtocolOverride;
 
+pub use backend::{ActiveBackend, ProtocolBackend};
 pub use plugin::ProtocolBackendPlugin;
+
+/// The codec of the [`ActiveBackend`].
+pub type ProtocolCodec = <ActiveBackend as ProtocolBackend>::Codec;
@@ -0,0 +1,196 @@
+//! Logging of received packets to a file, one JSON object per line.
+//!
+//! The log is off unless [`PacketLogConfig::path`] is set. Each line is a
+//! [`PacketLogEntry`]; the packets logged can be narrowed down by name with
+//! [`PacketLogConfig::include`] and [`PacketLogConfig::exclude`]. The backend
+//! decides what the names and summaries are.
+
+use std::{
+    fmt::{self, Debug, Write as _},
+    fs::{File, OpenOptions},
+    io::{self, LineWriter, Write as _},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use brine_proto::BrineSystems;
+
+/// Longest [`PacketLogEntry::summary`], in bytes.
+pub const MAX_SUMMARY_LENGTH: usize = 256;
+
+/// Where to log packets, and which ones.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PacketLogConfig {
+    /// File to append the log to. Nothing is logged without one.
+    pub path: Option<PathBuf>,
+
+    /// Names of the packets to log (e.g. `KeepAlive`), or all of them if
+    /// empty.
+    pub include: Vec<String>,
+
+    /// Names of the packets not to log, even if included.
+    pub exclude: Vec<String>,
+
+    /// File to save the backend's report on the packets it couldn't decode
+    /// to, when the connection ends or the app exits. The report is logged
+    /// either way.
+    pub unknown_report: Option<PathBuf>,
+}
+
+impl PacketLogConfig {
+    /// Returns whether packets named `name` are logged, provided there is a
+    /// file to log them to.
+    pub fn logs(&self, name: &str) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|n| n == name);
+        included && !self.exclude.iter().any(|n| n == name)
+    }
+}
+
+/// One line of the packet log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PacketLogEntry {
+    /// When the packet was logged, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+
+    /// Protocol state the packet belongs to (e.g. `Play`).
+    pub state: String,
+
+    /// `Clientbound` or `Serverbound`.
+    pub direction: String,
+
+    /// Name of the packet within its state and direction (e.g. `KeepAlive`).
+    pub name: String,
+
+    /// ID of a packet the backend doesn't know, which it names `Unknown`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+
+    /// The packet's fields, cut short at [`MAX_SUMMARY_LENGTH`].
+    pub summary: String,
+}
+
+/// The open packet log file, if any.
+#[derive(Resource, Default)]
+pub(crate) struct PacketLog {
+    file: Option<LineWriter<File>>,
+}
+
+impl PacketLog {
+    pub(crate) fn is_open(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Appends `entry` to the log, closing it if that fails.
+    pub(crate) fn write(&mut self, entry: &PacketLogEntry) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+
+        let result = serde_json::to_writer(&mut *file, entry)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(file));
+        if let Err(err) = result {
+            warn!("Failed to write packet log, closing it: {}", err);
+            self.file = None;
+        }
+    }
+}
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<PacketLogConfig>()
+        .init_resource::<PacketLog>()
+        .add_systems(
+            Update,
+            open_packet_log
+                .run_if(resource_changed::<PacketLogConfig>)
+                .before(BrineSystems::ProtocolDecode),
+        );
+}
+
+/// Opens the file the [`PacketLogConfig`] names, closing any other.
+fn open_packet_log(config: Res<PacketLogConfig>, mut log: ResMut<PacketLog>) {
+    log.file = config.path.as_ref().and_then(|path| {
+        let file = OpenOptions::new().create(true).append(true).open(path);
+        match file {
+            Ok(file) => {
+                info!("Logging packets to {}", path.display());
+                Some(LineWriter::new(file))
+            }
+            Err(err) => {
+                warn!("Failed to open packet log {}: {}", path.display(), err);
+                None
+            }
+        }
+    });
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+pub(crate) fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Returns `value`'s debug output, cut short with an ellipsis after
+/// `max_length` bytes without formatting the rest.
+pub fn summarize(value: &impl Debug, max_length: usize) -> String {
+    /// Writer that refuses to go past its limit.
+    struct Bounded {
+        out: String,
+        max_length: usize,
+    }
+
+    impl fmt::Write for Bounded {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let room = self.max_length - self.out.len();
+            if s.len() <= room {
+                self.out.push_str(s);
+                return Ok(());
+            }
+
+            let end = (0..=room)
+                .rev()
+                .find(|i| s.is_char_boundary(*i))
+                .unwrap_or(0);
+            self.out.push_str(&s[..end]);
+            Err(fmt::Error)
+        }
+    }
+
+    let mut bounded = Bounded {
+        out: String::new(),
+        max_length,
+    };
+    if write!(bounded, "{:?}", value).is_err() {
+        bounded.out.push('…');
+    }
+    bounded.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_are_cut_at_a_char_boundary() {
+        assert_eq!(summarize(&"short", 16), "\"short\"");
+        assert_eq!(summarize(&[1, 2, 3, 4], 5), "[1, 2…");
+        assert_eq!(summarize(&"héllo", 3), "\"h…");
+    }
+
+    #[test]
+    fn excluded_packets_are_not_logged() {
+        let mut config = PacketLogConfig::default();
+        assert!(config.logs("KeepAlive"));
+
+        config.include = vec![String::from("KeepAlive"), String::from("MapChunk")];
+        config.exclude = vec![String::from("MapChunk")];
+        assert!(config.logs("KeepAlive"));
+        assert!(!config.logs("MapChunk"));
+        assert!(!config.logs("Tags"));
+    }
+}
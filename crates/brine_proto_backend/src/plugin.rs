@@ -1,16 +1,37 @@
 //! Plugins exported by this crate.
 
-use bevy::prelude::*;
+use std::sync::Arc;
 
-use brine_net::{NetworkEvent, NetworkPlugin};
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
 
-use crate::backend::{self, ProtocolCodec};
+use brine_net::{
+    capture::CaptureWriter, transport::Transport, NetworkError, NetworkEvent, NetworkPlugin,
+    NetworkResource,
+};
+use brine_proto::{BrineSystems, ClientError, ClientErrorKind};
+
+use crate::{
+    codec::{CodecLimits, ProtocolAnomaly},
+    configuration::ConfigurationPolicy,
+    packet_log,
+    version::{supported_version, supported_versions_list},
+    ActiveBackend, ProtocolBackend, ProtocolCodec,
+};
 
 /// Minecraft protocol implementation plugin.
 ///
+/// The protocol is implemented by the [`ActiveBackend`], chosen with cargo
+/// features. See [`ProtocolBackend`].
+///
 /// # Events
 ///
-/// The plugin does not register any events.
+/// The plugin registers the following events:
+///
+/// * [`ProtocolAnomaly`], only sent with
+///   [`with_strict_decoding`][Self::with_strict_decoding]
 ///
 /// The plugin acts on the following events:
 ///
@@ -19,27 +40,323 @@ use crate::backend::{self, ProtocolCodec};
 /// The plugin sends the following events:
 ///
 /// * [`brine_proto::event::clientbound::*`][brine_proto::event::clientbound]
+/// * [`ClientError`], for network errors and chunks that fail to decode
 ///
 /// # Resources
 ///
 /// The plugin registers a [`NetworkPlugin`] which provides things. See its
 /// documentation.
-pub struct ProtocolBackendPlugin;
+///
+/// The plugin also registers the [`CodecLimits`] resource, which the backend
+/// applies to its codec whenever it changes. Set it with
+/// [`with_limits`][Self::with_limits].
+///
+/// What the client answers while the server configures it (known packs,
+/// resource packs, and the locale, view distance and skin parts it reports)
+/// comes from the [`ConfigurationPolicy`] resource. Set it with
+/// [`with_configuration_policy`][Self::with_configuration_policy].
+///
+/// Received packets are logged to a file as JSON lines when the
+/// [`PacketLogConfig`][crate::packet_log::PacketLogConfig] resource names one.
+///
+/// Everything received from servers can be recorded for replay with
+/// [`with_capture`][Self::with_capture], and servers can be reached through
+/// something other than TCP (e.g., a WebSocket proxy) with
+/// [`with_transport`][Self::with_transport].
+///
+/// The plugin publishes the following diagnostics about compression, while it
+/// is enabled:
+///
+/// * [`COMPRESSION_THRESHOLD`][Self::COMPRESSION_THRESHOLD]
+/// * [`INBOUND_COMPRESSION_RATIO`][Self::INBOUND_COMPRESSION_RATIO]
+/// * [`OUTBOUND_COMPRESSION_RATIO`][Self::OUTBOUND_COMPRESSION_RATIO]
+///
+/// and about traffic, always:
+///
+/// * [`INBOUND_THROUGHPUT`][Self::INBOUND_THROUGHPUT]
+/// * [`OUTBOUND_THROUGHPUT`][Self::OUTBOUND_THROUGHPUT]
+///
+/// To find out whether a server mishandles compressed packets, the client can
+/// send everything uncompressed with
+/// [`without_outbound_compression`][Self::without_outbound_compression].
+///
+/// # Ordering
+///
+/// Systems that turn packets into clientbound events run in
+/// [`BrineSystems::ProtocolDecode`], and systems that turn serverbound events
+/// into packets in [`BrineSystems::ProtocolEncode`]. Per-tick packets are sent
+/// from `FixedUpdate`.
+///
+/// # Protocol versions
+///
+/// The protocol version is normally discovered from the server's status
+/// response. Servers using a version outside of
+/// [`SUPPORTED_VERSIONS`][crate::version::SUPPORTED_VERSIONS] are refused with
+/// a [`Disconnect`][brine_proto::event::clientbound::Disconnect] listing the
+/// supported ones, unless a version is forced with
+/// [`with_protocol_version`][Self::with_protocol_version]. A forced version
+/// also lets [`without_status_ping`][Self::without_status_ping] skip the
+/// status connection and go straight to login.
+#[derive(Default)]
+pub struct ProtocolBackendPlugin {
+    protocol_override: Option<i32>,
+    skip_status: bool,
+    limits: CodecLimits,
+    configuration_policy: ConfigurationPolicy,
+    capture: Option<CaptureWriter>,
+    transport: Option<Arc<dyn Transport>>,
+    outbound_compression_disabled: bool,
+    strict_decoding: bool,
+}
+
+impl ProtocolBackendPlugin {
+    /// Compression threshold the server set, in bytes.
+    pub const COMPRESSION_THRESHOLD: DiagnosticPath =
+        DiagnosticPath::const_new("brine/compression/threshold");
+
+    /// How many times smaller compression made the packets received
+    /// compressed since the handshake. See
+    /// [`CompressionStats::ratio`][crate::codec::CompressionStats::ratio].
+    pub const INBOUND_COMPRESSION_RATIO: DiagnosticPath =
+        DiagnosticPath::const_new("brine/compression/inbound_ratio");
+
+    /// Like [`INBOUND_COMPRESSION_RATIO`][Self::INBOUND_COMPRESSION_RATIO],
+    /// for the packets sent.
+    pub const OUTBOUND_COMPRESSION_RATIO: DiagnosticPath =
+        DiagnosticPath::const_new("brine/compression/outbound_ratio");
+
+    /// Kibibytes of packets received per second, as sent over the wire.
+    pub const INBOUND_THROUGHPUT: DiagnosticPath =
+        DiagnosticPath::const_new("brine/network/inbound_throughput");
+
+    /// Like [`INBOUND_THROUGHPUT`][Self::INBOUND_THROUGHPUT], for the packets
+    /// sent.
+    pub const OUTBOUND_THROUGHPUT: DiagnosticPath =
+        DiagnosticPath::const_new("brine/network/outbound_throughput");
+
+    /// Always speaks `protocol_version`, whatever the server advertises.
+    pub fn with_protocol_version(mut self, protocol_version: i32) -> Self {
+        self.protocol_override = Some(protocol_version);
+        self
+    }
+
+    /// Logs in without asking the server for its status first, saving a
+    /// connection per login and staying clear of servers that rate-limit
+    /// status pings. Only applies along with
+    /// [`with_protocol_version`][Self::with_protocol_version], since the
+    /// status is where the protocol version comes from otherwise.
+    pub fn without_status_ping(mut self) -> Self {
+        self.skip_status = true;
+        self
+    }
+
+    /// Decodes packets with `limits` instead of the defaults.
+    pub fn with_limits(mut self, limits: CodecLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Answers the server during the configuration phase as `policy` says,
+    /// instead of with the defaults.
+    pub fn with_configuration_policy(mut self, policy: ConfigurationPolicy) -> Self {
+        self.configuration_policy = policy;
+        self
+    }
+
+    /// Records the bytes received from every server into `capture`, so that
+    /// the session can be replayed with a
+    /// [`ReplayServer`][brine_net::capture::ReplayServer].
+    pub fn with_capture(mut self, capture: CaptureWriter) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Connects to servers with `transport`, so that the server addresses
+    /// given to [`Login`][brine_proto::event::serverbound::Login] are the
+    /// transport's (e.g., `ws://` URLs for a
+    /// [`WebSocketTransport`][brine_net::transport::WebSocketTransport]).
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Sends every packet uncompressed, however large, once the server turns
+    /// compression on. Servers accept these whatever their threshold, so this
+    /// is for diagnosing servers that compress or decompress oddly.
+    pub fn without_outbound_compression(mut self) -> Self {
+        self.outbound_compression_disabled = true;
+        self
+    }
+
+    /// Sends a [`ProtocolAnomaly`] for every received packet that its
+    /// definition doesn't read in full, instead of only logging it. This is
+    /// for development: tests can fail on these to catch definitions that
+    /// are missing fields.
+    pub fn with_strict_decoding(mut self) -> Self {
+        self.strict_decoding = true;
+        self
+    }
+}
+
+/// Protocol version forced by [`ProtocolBackendPlugin::with_protocol_version`].
+#[derive(Resource, Debug, Clone, Copy)]
+pub(crate) struct ProtocolOverride {
+    pub version: i32,
+
+    /// Whether to skip protocol discovery. See
+    /// [`ProtocolBackendPlugin::without_status_ping`].
+    pub skip_status: bool,
+}
 
 impl Plugin for ProtocolBackendPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(NetworkPlugin::<ProtocolCodec>::default());
 
-        app.add_systems(Update, log_network_errors);
+        if let Some(capture) = &self.capture {
+            app.world_mut()
+                .resource_mut::<NetworkResource<ProtocolCodec>>()
+                .record_to(capture.clone());
+        }
+        if let Some(transport) = &self.transport {
+            app.world_mut()
+                .resource_mut::<NetworkResource<ProtocolCodec>>()
+                .set_transport(transport.clone());
+        }
+        if self.outbound_compression_disabled {
+            app.world()
+                .resource::<NetworkResource<ProtocolCodec>>()
+                .codec()
+                .set_outbound_compression_disabled(true);
+        }
+        if self.strict_decoding {
+            app.world()
+                .resource::<NetworkResource<ProtocolCodec>>()
+                .codec()
+                .set_strict_decoding(true);
+        }
 
-        backend::build(app);
+        if let Some(protocol_version) = self.protocol_override {
+            if supported_version(protocol_version).is_none() {
+                warn!(
+                    "Forcing unsupported protocol version {}; supported versions are {}",
+                    protocol_version,
+                    supported_versions_list()
+                );
+            }
+            app.insert_resource(ProtocolOverride {
+                version: protocol_version,
+                skip_status: self.skip_status,
+            });
+        } else if self.skip_status {
+            warn!("Not skipping the status ping: no protocol version is forced");
+        }
+
+        app.insert_resource(self.limits);
+        app.insert_resource(self.configuration_policy.clone());
+        packet_log::build(app);
+
+        app.register_diagnostic(Diagnostic::new(Self::COMPRESSION_THRESHOLD).with_suffix(" B"));
+        for path in [
+            Self::INBOUND_COMPRESSION_RATIO,
+            Self::OUTBOUND_COMPRESSION_RATIO,
+        ] {
+            app.register_diagnostic(Diagnostic::new(path).with_suffix("x"));
+        }
+        for path in [Self::INBOUND_THROUGHPUT, Self::OUTBOUND_THROUGHPUT] {
+            app.register_diagnostic(Diagnostic::new(path).with_suffix(" KiB/s"));
+        }
+
+        app.add_message::<ProtocolAnomaly>().add_systems(
+            Update,
+            (
+                log_network_errors,
+                send_protocol_anomalies,
+                measure_compression,
+                measure_throughput,
+            )
+                .in_set(BrineSystems::ProtocolDecode),
+        );
+
+        debug!("Using the {} protocol backend", ActiveBackend::NAME);
+        ActiveBackend::build(app);
+    }
+}
+
+fn send_protocol_anomalies(
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut anomalies: MessageWriter<ProtocolAnomaly>,
+) {
+    for anomaly in net_resource.codec().take_anomalies() {
+        warn!("Protocol anomaly: {}", anomaly);
+        anomalies.write(anomaly);
+    }
+}
+
+fn measure_compression(
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    mut diagnostics: Diagnostics,
+) {
+    let codec = net_resource.codec();
+    let Some(threshold) = codec.compression_threshold() else {
+        return;
+    };
+
+    diagnostics.add_measurement(&ProtocolBackendPlugin::COMPRESSION_THRESHOLD, || {
+        f64::from(threshold)
+    });
+    if let Some(ratio) = codec.inbound_compression().ratio() {
+        diagnostics.add_measurement(&ProtocolBackendPlugin::INBOUND_COMPRESSION_RATIO, || ratio);
+    }
+    if let Some(ratio) = codec.outbound_compression().ratio() {
+        diagnostics.add_measurement(&ProtocolBackendPlugin::OUTBOUND_COMPRESSION_RATIO, || ratio);
+    }
+}
+
+/// Measures the bytes sent and received since the last frame, against the
+/// totals it saw then.
+fn measure_throughput(
+    net_resource: Res<NetworkResource<ProtocolCodec>>,
+    time: Res<Time<Real>>,
+    mut last_totals: Local<Option<(u64, u64)>>,
+    mut diagnostics: Diagnostics,
+) {
+    let codec = net_resource.codec();
+    let totals = (codec.inbound_bytes(), codec.outbound_bytes());
+    let Some((last_inbound, last_outbound)) = last_totals.replace(totals) else {
+        return;
+    };
+
+    let seconds = time.delta_secs_f64();
+    if seconds <= 0.0 {
+        return;
     }
+    let kib_per_second = |bytes: u64| bytes as f64 / 1024.0 / seconds;
+    diagnostics.add_measurement(&ProtocolBackendPlugin::INBOUND_THROUGHPUT, || {
+        kib_per_second(totals.0 - last_inbound)
+    });
+    diagnostics.add_measurement(&ProtocolBackendPlugin::OUTBOUND_THROUGHPUT, || {
+        kib_per_second(totals.1 - last_outbound)
+    });
 }
 
-fn log_network_errors(mut event_reader: MessageReader<NetworkEvent<ProtocolCodec>>) {
+fn log_network_errors(
+    mut event_reader: MessageReader<NetworkEvent<ProtocolCodec>>,
+    mut client_errors: MessageWriter<ClientError>,
+) {
     for event in event_reader.read() {
         if let NetworkEvent::Error(network_error) = event {
             warn!("Network error: {}", network_error);
+
+            let kind = match network_error {
+                // Only happens if the backend itself misbehaves.
+                NetworkError::AlreadyConnected => continue,
+                NetworkError::ConnectFailed { .. } => ClientErrorKind::Connect,
+                NetworkError::TransportError(_) => ClientErrorKind::Connection,
+                NetworkError::EncodeError(_) | NetworkError::DecodeError(_) => {
+                    ClientErrorKind::Protocol
+                }
+            };
+            client_errors.write(ClientError::new(kind, network_error.to_string()));
         }
     }
 }
@@ -0,0 +1,45 @@
+//! Querying a server's status, as the multiplayer server list does.
+//!
+//! This is a standalone blocking request over its own connection; it doesn't
+//! need an app or the [`ProtocolBackendPlugin`][crate::ProtocolBackendPlugin].
+//! The [`ActiveBackend`] speaks the protocol.
+
+use std::{io, time::Duration};
+
+use crate::{ActiveBackend, ProtocolBackend};
+
+/// What a server reports about itself in response to a status request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    /// Name of the server's version, e.g. `1.21.4` or `Paper 1.21.4`.
+    pub version_name: String,
+
+    /// Protocol version the server speaks.
+    pub protocol_version: i32,
+
+    pub players_online: i64,
+    pub players_max: i64,
+
+    /// Plain text of the server's message of the day.
+    pub description: String,
+
+    /// Round trip time of the status ping.
+    pub latency: Duration,
+}
+
+/// Errors that can occur while querying a server's status.
+#[derive(Debug, thiserror::Error)]
+pub enum StatusError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    /// The server answered with something other than a status.
+    #[error("{0}")]
+    Protocol(String),
+}
+
+/// Asks the server at `server_addr` (`host:port`) for its status, waiting at
+/// most `timeout` for each step.
+pub fn query_status(server_addr: &str, timeout: Duration) -> Result<ServerStatus, StatusError> {
+    ActiveBackend::query_status(server_addr, timeout)
+}
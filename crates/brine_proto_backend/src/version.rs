@@ -1,4 +1,5 @@
-//! Conversion between Minecraft versions and protocol version numbers.
+//! Conversion between Minecraft versions and protocol version numbers, and the
+//! registry of protocol versions the backend can actually speak.
 
 macro_rules! protocol_versions {
     (
@@ -102,9 +103,202 @@ pub const fn get_protocol_version(version_string: &str) -> Option<i32> {
     get_protocol_version_internal(version_string)
 }
 
+/// Parses a protocol version given either as a Minecraft version (`1.21.4`)
+/// or as a raw protocol number (`769`).
+pub fn parse_protocol_version(value: &str) -> Option<i32> {
+    get_protocol_version(value).or_else(|| value.parse().ok())
+}
+
+/// A protocol version the backend has packet tables and handlers for.
+#[derive(Debug)]
+pub struct SupportedVersion {
+    pub protocol_version: i32,
+    /// Minecraft releases that use this protocol version, oldest first.
+    pub minecraft_versions: &'static [&'static str],
+    pub shims: PacketShims,
+}
+
+impl SupportedVersion {
+    /// A human-readable label, e.g. `1.21.2-1.21.3 (768)`.
+    pub fn label(&self) -> String {
+        let versions = match self.minecraft_versions {
+            [] => String::from("?"),
+            [only] => only.to_string(),
+            [first, .., last] => format!("{first}-{last}"),
+        };
+        format!("{versions} ({})", self.protocol_version)
+    }
+}
+
+/// Differences between protocol versions that the packet handlers have to
+/// account for beyond what the packet tables already describe.
+#[derive(Debug)]
+pub struct PacketShims {
+    /// Clientbound play packet IDs whose generated parsers are known to leave
+    /// bytes unread. They are passed through as unknown packets instead so the
+    /// stream stays aligned.
+    pub skipped_play_packets: &'static [i32],
+    /// Clientbound play packet ID of Player Info Update. Its entries depend on
+    /// action flags the generated parsers can't follow, so it is always in
+    /// `skipped_play_packets` and parsed by hand.
+    pub player_info_update: i32,
+    /// Clientbound play packet IDs of Recipe Book Add and Recipe Book Remove
+    /// (1.21.2+), which are parsed by hand like Player Info Update. Older
+    /// versions send recipes in a format the backend doesn't read, and the
+    /// client is sent `RecipeBookUnavailable` instead.
+    pub recipe_book_add: Option<i32>,
+    pub recipe_book_remove: Option<i32>,
+    /// Clientbound play packet ID of Map Data, whose optional parts the
+    /// generated parsers don't follow. Always in `skipped_play_packets` and
+    /// parsed by hand.
+    pub map_data: i32,
+    /// Clientbound play packet ID of Set Entity Metadata. The generated
+    /// parsers predate the metadata types of these versions, so it is always
+    /// in `skipped_play_packets` and parsed by hand.
+    pub entity_metadata: i32,
+    /// Whether the client sends Player Loaded after configuration (1.21.4+).
+    pub player_loaded: bool,
+    /// Whether the client sends Client Tick End (1.21.2+).
+    pub tick_end: bool,
+    /// Whether riding inputs are sent as Player Input flags (1.21.2+) rather
+    /// than as Steer Vehicle.
+    pub player_input: bool,
+    /// Whether the movement packets' flags byte has a horizontal collision bit
+    /// next to on-ground (1.21.2+). Older versions send a plain on-ground bool.
+    pub movement_collision_flag: bool,
+}
+
+impl PacketShims {
+    /// Encodes the trailing flags byte of the serverbound movement packets.
+    pub fn movement_flags(&self, on_ground: bool, horizontal_collision: bool) -> u8 {
+        let mut flags = u8::from(on_ground);
+        if self.movement_collision_flag && horizontal_collision {
+            flags |= 0x02;
+        }
+        flags
+    }
+}
+
+/// Every protocol version the backend supports, newest first.
+pub const SUPPORTED_VERSIONS: &[SupportedVersion] = &[
+    SupportedVersion {
+        protocol_version: 769,
+        minecraft_versions: &["1.21.4"],
+        shims: PacketShims {
+            skipped_play_packets: &[0x11, 0x2d, 0x40, 0x42, 0x44, 0x45, 0x46, 0x50, 0x5d, 0x7e],
+            player_info_update: 0x40,
+            recipe_book_add: Some(0x44),
+            recipe_book_remove: Some(0x45),
+            map_data: 0x2d,
+            entity_metadata: 0x5d,
+            player_loaded: true,
+            tick_end: true,
+            player_input: true,
+            movement_collision_flag: true,
+        },
+    },
+    SupportedVersion {
+        protocol_version: 768,
+        minecraft_versions: &["1.21.2", "1.21.3"],
+        shims: PacketShims {
+            skipped_play_packets: &[0x2d, 0x40, 0x44, 0x45, 0x5d],
+            player_info_update: 0x40,
+            recipe_book_add: Some(0x44),
+            recipe_book_remove: Some(0x45),
+            map_data: 0x2d,
+            entity_metadata: 0x5d,
+            player_loaded: false,
+            tick_end: true,
+            player_input: true,
+            movement_collision_flag: true,
+        },
+    },
+    SupportedVersion {
+        protocol_version: 767,
+        minecraft_versions: &["1.21", "1.21.1"],
+        shims: PacketShims {
+            skipped_play_packets: &[0x2c, 0x3e, 0x58],
+            player_info_update: 0x3e,
+            recipe_book_add: None,
+            recipe_book_remove: None,
+            map_data: 0x2c,
+            entity_metadata: 0x58,
+            player_loaded: false,
+            tick_end: false,
+            player_input: false,
+            movement_collision_flag: false,
+        },
+    },
+];
+
+/// Looks up `protocol_version` in [`SUPPORTED_VERSIONS`].
+pub fn supported_version(protocol_version: i32) -> Option<&'static SupportedVersion> {
+    SUPPORTED_VERSIONS
+        .iter()
+        .find(|supported| supported.protocol_version == protocol_version)
+}
+
+/// The shims for `protocol_version`, falling back to those of the newest
+/// supported version for unsupported ones (e.g. when forced with an override).
+pub fn shims_for(protocol_version: i32) -> &'static PacketShims {
+    &supported_version(protocol_version)
+        .unwrap_or(&SUPPORTED_VERSIONS[0])
+        .shims
+}
+
+/// Comma-separated labels of all supported versions, for error messages.
+pub fn supported_versions_list() -> String {
+    SUPPORTED_VERSIONS
+        .iter()
+        .map(SupportedVersion::label)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 #[test]
 fn test() {
     assert_eq!(get_protocol_version("1.21.10"), Some(769));
     assert_eq!(get_protocol_version("foo"), None);
 }
+
+#[cfg(test)]
+#[test]
+fn supported_versions() {
+    assert_eq!(parse_protocol_version("1.21.4"), Some(769));
+    assert_eq!(parse_protocol_version("768"), Some(768));
+    assert_eq!(parse_protocol_version("foo"), None);
+
+    assert!(supported_version(769).is_some());
+    assert!(supported_version(47).is_none());
+    for supported in SUPPORTED_VERSIONS {
+        for version in supported.minecraft_versions {
+            assert_eq!(
+                get_protocol_version(version),
+                Some(supported.protocol_version)
+            );
+        }
+        assert!(supported
+            .shims
+            .skipped_play_packets
+            .contains(&supported.shims.player_info_update));
+        for hand_parsed_packet in [
+            Some(supported.shims.map_data),
+            Some(supported.shims.entity_metadata),
+            supported.shims.recipe_book_add,
+            supported.shims.recipe_book_remove,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            assert!(supported
+                .shims
+                .skipped_play_packets
+                .contains(&hand_parsed_packet));
+        }
+    }
+
+    assert_eq!(shims_for(767).movement_flags(true, true), 0x01);
+    assert_eq!(shims_for(769).movement_flags(true, true), 0x03);
+    assert!(supported_versions_list().starts_with("1.21.4 (769), 1.21.2-1.21.3 (768)"));
+}
@@ -0,0 +1,360 @@
+//! Text that floats in the world, always facing the camera, like name tags.
+//!
+//! Each [`WorldText`] is drawn as a UI node that follows the projection of its
+//! anchor point, so it is always upright and readable. Its glyphs come from
+//! the [`BitmapFont`], one image node each, spaced like vanilla. Its size
+//! still follows perspective: a font pixel is a ninth of the projected height
+//! of [`WorldText::line_height`].
+
+use bevy::{
+    platform::collections::HashMap, prelude::*, transform::TransformSystems, ui::UiTransform,
+};
+
+use crate::font::{BitmapFont, GLYPH_SIZE, LINE_HEIGHT};
+
+/// How far before [`WorldText::max_distance`] text starts fading out, in
+/// blocks.
+const FADE_DISTANCE: f32 = 8.0;
+
+/// Opacity of see-through text that is behind something, as in vanilla.
+const SEE_THROUGH_ALPHA: f32 = 32.0 / 255.0;
+
+/// Text whose lines are shorter than this many pixels isn't drawn.
+const MIN_FONT_SIZE: f32 = 2.0;
+
+/// Line heights are rounded to this many pixels so that glyphs aren't resized
+/// for every tiny movement.
+const FONT_SIZE_STEP: f32 = 0.5;
+
+/// Text drawn above an entity, facing the camera.
+///
+/// The bottom center of the text is at the entity's [`GlobalTransform`]
+/// translation plus [`offset`](Self::offset).
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct WorldText {
+    pub text: String,
+
+    /// World space offset of the text from the entity.
+    pub offset: Vec3,
+
+    /// Height of the text in blocks.
+    pub line_height: f32,
+
+    pub color: Color,
+    pub background: Color,
+
+    /// Distance from the camera, in blocks, beyond which the text is hidden.
+    pub max_distance: f32,
+
+    /// Whether the text stays faintly visible while [`WorldTextOccluded`].
+    /// Otherwise it is hidden.
+    pub see_through: bool,
+}
+
+impl WorldText {
+    /// Text styled like a vanilla name tag: white on a translucent black
+    /// background, visible from up to 64 blocks away and through walls.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            offset: Vec3::ZERO,
+            line_height: 0.225,
+            color: Color::WHITE,
+            background: Color::srgba(0.0, 0.0, 0.0, 0.25),
+            max_distance: 64.0,
+            see_through: true,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn with_see_through(mut self, see_through: bool) -> Self {
+        self.see_through = see_through;
+        self
+    }
+
+    /// Returns how opaque the text is at `distance` blocks from the camera,
+    /// from `0.0` (hidden) to `1.0`.
+    pub fn opacity(&self, distance: f32, occluded: bool) -> f32 {
+        let fade = ((self.max_distance - distance) / FADE_DISTANCE).clamp(0.0, 1.0);
+
+        match (occluded, self.see_through) {
+            (false, _) => fade,
+            (true, true) => fade * SEE_THROUGH_ALPHA,
+            (true, false) => 0.0,
+        }
+    }
+}
+
+/// Whether something is between the camera and a [`WorldText`].
+///
+/// Text can't tell what is in the way on its own; whatever knows about the
+/// world (e.g. a raycast through the loaded chunks) keeps this up to date.
+/// Without it, text is never occluded.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WorldTextOccluded(pub bool);
+
+/// The UI node that draws the [`WorldText`] of `source`.
+#[derive(Component, Debug)]
+struct WorldTextLabel {
+    source: Entity,
+
+    /// The text the label's glyphs spell out.
+    text: String,
+
+    /// Size of a font pixel, in UI pixels.
+    pixel_size: f32,
+}
+
+/// A glyph of a [`WorldTextLabel`], sized in font pixels.
+#[derive(Component, Debug)]
+struct LabelGlyph {
+    width: f32,
+    advance: f32,
+}
+
+/// Labels by the entity whose text they draw.
+#[derive(Resource, Debug, Default)]
+struct WorldTextLabels(HashMap<Entity, Entity>);
+
+/// Plugin that draws [`WorldText`] for the 3D camera.
+///
+/// Text is drawn once the [`BitmapFont`] is loaded.
+pub struct BillboardPlugin;
+
+impl Plugin for BillboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldTextLabels>().add_systems(
+            PostUpdate,
+            (spawn_labels, despawn_labels, update_labels, resize_glyphs)
+                .chain()
+                .after(TransformSystems::Propagate)
+                .run_if(resource_exists::<BitmapFont>),
+        );
+    }
+}
+
+fn spawn_labels(
+    mut commands: Commands,
+    mut labels: ResMut<WorldTextLabels>,
+    texts: Query<(Entity, &WorldText)>,
+) {
+    for (source, text) in texts.iter() {
+        if labels.0.contains_key(&source) {
+            continue;
+        }
+
+        let label = commands
+            .spawn((
+                Name::new("World text"),
+                WorldTextLabel {
+                    source,
+                    text: String::new(),
+                    pixel_size: 0.0,
+                },
+                BackgroundColor(text.background),
+                Node {
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                UiTransform {
+                    translation: Val2::percent(-50.0, -100.0),
+                    ..default()
+                },
+                ZIndex::default(),
+                Visibility::Hidden,
+            ))
+            .id();
+        labels.0.insert(source, label);
+    }
+}
+
+fn despawn_labels(
+    mut commands: Commands,
+    mut labels: ResMut<WorldTextLabels>,
+    texts: Query<(), With<WorldText>>,
+) {
+    labels.0.retain(|source, label| {
+        let keep = texts.contains(*source);
+        if !keep {
+            commands.entity(*label).despawn();
+        }
+        keep
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn update_labels(
+    mut commands: Commands,
+    font: Res<BitmapFont>,
+    ui_scale: Res<UiScale>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    texts: Query<(&WorldText, &GlobalTransform, Option<&WorldTextOccluded>)>,
+    mut labels: Query<(
+        Entity,
+        &mut WorldTextLabel,
+        &mut Node,
+        &mut BackgroundColor,
+        &mut ZIndex,
+        &mut Visibility,
+        Option<&Children>,
+    )>,
+    mut glyphs: Query<&mut ImageNode, With<LabelGlyph>>,
+) {
+    let camera = cameras.single().ok();
+
+    for (entity, mut label, mut node, mut background, mut z_index, mut visibility, children) in
+        labels.iter_mut()
+    {
+        let Ok((world_text, transform, occluded)) = texts.get(label.source) else {
+            continue;
+        };
+
+        let anchor = transform.translation() + world_text.offset;
+        let layout = camera.and_then(|(camera, camera_transform)| {
+            let distance = camera_transform.translation().distance(anchor);
+            let occluded = occluded.is_some_and(|occluded| occluded.0);
+            let opacity = world_text.opacity(distance, occluded);
+            if opacity <= 0.0 {
+                return None;
+            }
+
+            let bottom = camera.world_to_viewport(camera_transform, anchor).ok()?;
+            let top = camera
+                .world_to_viewport(
+                    camera_transform,
+                    anchor + camera_transform.up() * world_text.line_height,
+                )
+                .ok()?;
+            let font_size = ((bottom.distance(top) / ui_scale.0 / FONT_SIZE_STEP).round()
+                * FONT_SIZE_STEP)
+                .max(0.0);
+            (font_size >= MIN_FONT_SIZE).then_some((
+                bottom / ui_scale.0,
+                font_size,
+                distance,
+                opacity,
+            ))
+        });
+
+        let Some((position, font_size, distance, opacity)) = layout else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        visibility.set_if_neq(Visibility::Inherited);
+
+        // Vanilla pads the background by a font pixel on every side but the
+        // bottom, where the line's spare pixel already is. The glyphs' own
+        // spacing pads the right by one more.
+        let pixel_size = font_size / LINE_HEIGHT;
+        let padding = UiRect::new(
+            Val::Px(pixel_size),
+            Val::Px(pixel_size),
+            Val::Px(pixel_size),
+            Val::Px(0.0),
+        );
+        if node.left != Val::Px(position.x)
+            || node.top != Val::Px(position.y)
+            || node.padding != padding
+        {
+            node.left = Val::Px(position.x);
+            node.top = Val::Px(position.y);
+            node.padding = padding;
+        }
+        if label.pixel_size != pixel_size {
+            label.pixel_size = pixel_size;
+        }
+
+        let color = world_text
+            .color
+            .with_alpha(world_text.color.alpha() * opacity);
+        if label.text != world_text.text {
+            label.text.clone_from(&world_text.text);
+            let mut label = commands.entity(entity);
+            label.despawn_related::<Children>();
+            label.with_children(|label| {
+                for character in world_text.text.chars() {
+                    let glyph = font.glyph(character);
+                    label.spawn((
+                        LabelGlyph {
+                            width: glyph.width,
+                            advance: glyph.advance,
+                        },
+                        ImageNode::new(font.texture.clone())
+                            .with_rect(glyph.rect)
+                            .with_color(color),
+                        Node::default(),
+                    ));
+                }
+            });
+        } else {
+            for child in children.into_iter().flatten() {
+                if let Ok(mut image) = glyphs.get_mut(*child) {
+                    if image.color != color {
+                        image.color = color;
+                    }
+                }
+            }
+        }
+
+        background.set_if_neq(BackgroundColor(
+            world_text
+                .background
+                .with_alpha(world_text.background.alpha() * opacity),
+        ));
+        // Nearer text is drawn on top.
+        z_index.set_if_neq(ZIndex(-(distance * 16.0) as i32));
+    }
+}
+
+/// Sizes glyphs by their label's font pixel, for new glyphs and labels whose
+/// text got bigger or smaller.
+fn resize_glyphs(
+    labels: Query<Ref<WorldTextLabel>>,
+    mut glyphs: Query<(&LabelGlyph, &ChildOf, &mut Node), Without<WorldTextLabel>>,
+) {
+    for (glyph, child_of, mut node) in glyphs.iter_mut() {
+        let Ok(label) = labels.get(child_of.parent()) else {
+            continue;
+        };
+        if !label.is_changed() && !node.is_added() {
+            continue;
+        }
+
+        let pixel_size = label.pixel_size;
+        node.width = Val::Px(glyph.width * pixel_size);
+        node.height = Val::Px(GLYPH_SIZE * pixel_size);
+        node.margin.right = Val::Px((glyph.advance - glyph.width) * pixel_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opacity_fades_out_before_max_distance() {
+        let text = WorldText::new("Steve");
+        assert_eq!(text.opacity(10.0, false), 1.0);
+        assert_eq!(text.opacity(60.0, false), 0.5);
+        assert_eq!(text.opacity(64.0, false), 0.0);
+        assert_eq!(text.opacity(100.0, false), 0.0);
+    }
+
+    #[test]
+    fn occluded_text_is_faint_only_if_see_through() {
+        let text = WorldText::new("Steve");
+        assert_eq!(text.opacity(10.0, true), SEE_THROUGH_ALPHA);
+        assert_eq!(text.with_see_through(false).opacity(10.0, true), 0.0);
+    }
+}
@@ -0,0 +1,368 @@
+//! The Minecraft bitmap font, for text drawn in the world.
+//!
+//! Glyphs come from `font/ascii.png`, a 16 by 16 grid of cells holding the
+//! printable ASCII characters at their code points. Like vanilla, a glyph is
+//! as wide as its rightmost column with an opaque pixel and is followed by a
+//! pixel of spacing, and a space is 4 pixels wide. Characters outside
+//! printable ASCII are drawn as `?`.
+//!
+//! Sizes are in font pixels: a glyph cell is 8 pixels square whatever the
+//! resolution of the texture, and a line is [`LINE_HEIGHT`] pixels tall.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::{ImageLoaderSettings, ImageSampler},
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+use brine_asset::MinecraftAssets;
+
+/// Height of a line of text, in font pixels.
+pub const LINE_HEIGHT: f32 = 9.0;
+
+/// Size of a glyph cell, in font pixels.
+pub const GLYPH_SIZE: f32 = 8.0;
+
+/// Number of glyph cells along each side of the texture.
+const GRID_SIZE: u32 = 16;
+
+/// How far a space advances, in font pixels.
+const SPACE_ADVANCE: f32 = 4.0;
+
+/// The glyph drawn for characters the font doesn't have.
+const MISSING_GLYPH: char = '?';
+
+/// Where a glyph is in the font texture and how much room it takes up.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    /// Rectangle of the glyph's pixels in the font texture, in texels.
+    pub rect: Rect,
+
+    /// Width of the glyph, in font pixels.
+    pub width: f32,
+
+    /// How far the next glyph starts from this one, in font pixels.
+    pub advance: f32,
+}
+
+/// The Minecraft font, once its texture is loaded.
+#[derive(Resource, Debug, Clone)]
+pub struct BitmapFont {
+    pub texture: Handle<Image>,
+
+    /// Material for text meshes: unlit, cut out, and tinted by the meshes'
+    /// vertex colors.
+    pub material: Handle<StandardMaterial>,
+
+    /// Size of the font texture, in texels.
+    texture_size: UVec2,
+
+    /// Whether each texel of the font texture is drawn, row by row.
+    opaque: Vec<bool>,
+
+    /// Glyphs by code point, for the 128 ASCII characters.
+    glyphs: Vec<Glyph>,
+}
+
+impl BitmapFont {
+    /// Measures the glyphs in `texture`, the font texture.
+    pub fn new(image: &Image, texture: Handle<Image>, material: Handle<StandardMaterial>) -> Self {
+        let cell = image.width() / GRID_SIZE;
+        let scale = GLYPH_SIZE / cell as f32;
+        let opaque: Vec<bool> = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                image
+                    .get_color_at(x, y)
+                    .is_ok_and(|color| color.alpha() > 0.0)
+            })
+            .collect();
+        let is_opaque = |x: u32, y: u32| opaque[(y * image.width() + x) as usize];
+
+        let glyphs = (0..128)
+            .map(|code| {
+                let left = code % GRID_SIZE * cell;
+                let top = code / GRID_SIZE * cell;
+                let columns = (0..cell)
+                    .rev()
+                    .find(|column| (0..cell).any(|row| is_opaque(left + column, top + row)))
+                    .map_or(0, |column| column + 1);
+
+                let width = (columns as f32 * scale + 0.5).floor();
+                Glyph {
+                    rect: Rect::new(
+                        left as f32,
+                        top as f32,
+                        (left + columns) as f32,
+                        (top + cell) as f32,
+                    ),
+                    width,
+                    advance: if code == u32::from(' ') {
+                        SPACE_ADVANCE
+                    } else {
+                        width + 1.0
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            texture,
+            material,
+            texture_size: image.size(),
+            opaque,
+            glyphs,
+        }
+    }
+
+    /// Returns the number of texels per font pixel in the font texture.
+    pub fn texels_per_pixel(&self) -> u32 {
+        (self.texture_size.x / GRID_SIZE / GLYPH_SIZE as u32).max(1)
+    }
+
+    /// Returns whether the texel at `position` in the font texture is drawn.
+    pub fn is_opaque(&self, position: UVec2) -> bool {
+        position.cmplt(self.texture_size).all()
+            && self.opaque[(position.y * self.texture_size.x + position.x) as usize]
+    }
+
+    /// Returns the glyph drawn for `character`.
+    pub fn glyph(&self, character: char) -> Glyph {
+        let printable = (' '..='~').contains(&character);
+        let character = if printable { character } else { MISSING_GLYPH };
+        self.glyphs[character as usize]
+    }
+
+    /// Returns how wide `text` is, in font pixels, counting the spacing after
+    /// its last glyph like vanilla.
+    pub fn width(&self, text: &str) -> f32 {
+        text.chars()
+            .map(|character| self.glyph(character).advance)
+            .sum()
+    }
+
+    /// Builds a mesh of `lines` of text facing +Z, each centered on X = 0,
+    /// with the top of the first line at Y = 0 and `line_height` font pixels
+    /// between the tops of lines. Positions are scaled by `pixel_size`, and
+    /// vertices are colored `color`.
+    pub fn text_mesh(
+        &self,
+        lines: &[String],
+        line_height: f32,
+        pixel_size: f32,
+        color: Color,
+    ) -> Mesh {
+        let color = color.to_linear().to_f32_array();
+
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+
+        for (line_index, line) in lines.iter().enumerate() {
+            let top = -(line_index as f32) * line_height;
+            let mut x = -self.width(line) / 2.0;
+
+            for character in line.chars() {
+                let glyph = self.glyph(character);
+                if glyph.width > 0.0 {
+                    let [left, right] = [x, x + glyph.width].map(|x| x * pixel_size);
+                    let [bottom, top] = [top - GLYPH_SIZE, top].map(|y| y * pixel_size);
+                    let min = glyph.rect.min / self.texture_size.as_vec2();
+                    let max = glyph.rect.max / self.texture_size.as_vec2();
+
+                    let start = positions.len() as u32;
+                    positions.extend([
+                        [left, bottom, 0.0],
+                        [right, bottom, 0.0],
+                        [right, top, 0.0],
+                        [left, top, 0.0],
+                    ]);
+                    uvs.extend([
+                        [min.x, max.y],
+                        [max.x, max.y],
+                        [max.x, min.y],
+                        [min.x, min.y],
+                    ]);
+                    indices.extend([0, 1, 2, 0, 2, 3].map(|index| start + index));
+                }
+                x += glyph.advance;
+            }
+        }
+
+        let vertex_count = positions.len();
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; vertex_count])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![color; vertex_count])
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// Text drawn flat in the world with the Minecraft font, like the text on a
+/// sign: in the entity's XY plane facing +Z, each line centered on its origin,
+/// with the top of the first line at the origin.
+///
+/// The text's mesh goes on the entity itself, so text gets an entity of its
+/// own (e.g. a child of the sign).
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct FlatText {
+    pub lines: Vec<String>,
+    pub color: Color,
+
+    /// Size of a font pixel, in blocks.
+    pub pixel_size: f32,
+
+    /// Distance between the tops of lines, in font pixels.
+    pub line_height: f32,
+}
+
+impl FlatText {
+    /// Text sized and spaced like the text on a sign.
+    pub fn sign(lines: Vec<String>, color: Color) -> Self {
+        Self {
+            lines,
+            color,
+            pixel_size: 1.0 / 96.0,
+            line_height: 10.0,
+        }
+    }
+}
+
+/// The handle of the font texture while it loads.
+#[derive(Resource, Debug)]
+struct FontTexture(Handle<Image>);
+
+/// Plugin that loads the [`BitmapFont`] and draws [`FlatText`].
+///
+/// Waits for [`MinecraftAssets`] before loading the font.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`BitmapFont`], once its texture is loaded
+pub struct FontPlugin;
+
+impl Plugin for FontPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                load_font_texture.run_if(resource_added::<MinecraftAssets>),
+                create_font
+                    .run_if(resource_exists::<FontTexture>.and(not(resource_exists::<BitmapFont>))),
+                update_flat_text_meshes.run_if(resource_exists::<BitmapFont>),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn load_font_texture(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+) {
+    let path = mc_assets.get_any_texture_path("font/ascii");
+    let texture = asset_server.load_with_settings(path, |settings: &mut ImageLoaderSettings| {
+        settings.sampler = ImageSampler::nearest();
+    });
+    commands.insert_resource(FontTexture(texture));
+}
+
+fn create_font(
+    mut commands: Commands,
+    texture: Res<FontTexture>,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(image) = images.get(&texture.0) else {
+        return;
+    };
+
+    let material = materials.add(StandardMaterial {
+        base_color_texture: Some(texture.0.clone()),
+        alpha_mode: AlphaMode::Mask(0.5),
+        unlit: true,
+        ..default()
+    });
+    commands.insert_resource(BitmapFont::new(image, texture.0.clone(), material));
+    commands.remove_resource::<FontTexture>();
+}
+
+/// Rebuilds the meshes of changed text, and of all text once the font loads.
+fn update_flat_text_meshes(
+    mut commands: Commands,
+    font: Res<BitmapFont>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    texts: Query<(Entity, Ref<FlatText>)>,
+) {
+    for (entity, text) in texts.iter() {
+        if !text.is_changed() && !font.is_added() {
+            continue;
+        }
+
+        let mesh = font.text_mesh(&text.lines, text.line_height, text.pixel_size, text.color);
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(font.material.clone()),
+        ));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    /// A font texture with `cell` texel cells, where `A` is 5 texels wide and
+    /// `i` 1 texel wide.
+    pub(crate) fn font(cell: u32) -> BitmapFont {
+        let size = GRID_SIZE * cell;
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        for (character, width) in [('A', 5), ('i', 1)] {
+            let code = character as u32;
+            let (left, top) = (code % GRID_SIZE * cell, code / GRID_SIZE * cell);
+            for column in 0..width * cell / 8 {
+                image
+                    .set_color_at(left + column, top + cell / 2, Color::WHITE)
+                    .unwrap();
+            }
+        }
+        BitmapFont::new(&image, Handle::default(), Handle::default())
+    }
+
+    #[test]
+    fn glyphs_are_as_wide_as_their_pixels_plus_spacing() {
+        for cell in [8, 16] {
+            let font = font(cell);
+            assert_eq!(font.glyph('A').width, 5.0);
+            assert_eq!(font.glyph('A').advance, 6.0);
+            assert_eq!(font.glyph('i').advance, 2.0);
+            assert_eq!(font.glyph(' ').advance, SPACE_ADVANCE);
+            assert_eq!(font.width("Ai A"), 6.0 + 2.0 + 4.0 + 6.0);
+        }
+    }
+
+    #[test]
+    fn missing_characters_are_question_marks() {
+        let font = font(8);
+        assert_eq!(font.glyph('é'), font.glyph('?'));
+        assert_eq!(font.glyph('\n'), font.glyph('?'));
+    }
+}
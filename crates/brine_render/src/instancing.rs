@@ -0,0 +1,464 @@
+//! Drawing many copies of the same textured mesh in one draw call.
+//!
+//! Servers can spawn thousands of identical things, like item frames on a
+//! wall, and drawing each of them on its own costs a draw call apiece. An
+//! entity with an [`InstancedMesh`] is drawn instead as one instance of its
+//! mesh and texture: every frame, the [`InstancingPlugin`] gathers the
+//! transforms of all visible entities that share a mesh and texture into one
+//! batch, and draws each batch with a single instanced draw call.
+//!
+//! Instances are unlit, like the chunks, and texels with less than half
+//! opacity are cut out. The meshes need positions and UVs.
+
+use bevy::{
+    asset::embedded_asset,
+    camera::visibility::VisibilitySystems,
+    core_pipeline::core_3d::Transparent3d,
+    ecs::system::{
+        lifetimeless::{Read, SRes},
+        SystemParamItem,
+    },
+    pbr::{MeshPipeline, MeshPipelineKey, SetMeshViewBindGroup, SetMeshViewBindingArrayBindGroup},
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{allocator::MeshAllocator, RenderMesh, RenderMeshBufferInfo},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+            RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d},
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, Buffer,
+            BufferInitDescriptor, BufferUsages, FilterMode, PipelineCache,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
+            SpecializedMeshPipeline, SpecializedMeshPipelineError, SpecializedMeshPipelines,
+            TextureSampleType, VertexAttribute, VertexFormat, VertexStepMode,
+        },
+        renderer::RenderDevice,
+        sync_world::MainEntity,
+        texture::GpuImage,
+        view::ExtractedView,
+        Render, RenderApp, RenderStartup, RenderSystems,
+    },
+    transform::TransformSystems,
+};
+use bevy_mesh::{MeshVertexBufferLayoutRef, VertexBufferLayout};
+
+const SHADER_PATH: &str = "embedded://brine_render/instancing.wgsl";
+
+/// The first shader location of the per-instance attributes, after those the
+/// mesh pipeline gives mesh attributes.
+const INSTANCE_SHADER_LOCATION: u32 = 8;
+
+/// Draws an entity as one instance of a mesh and texture that many entities
+/// share.
+///
+/// The instance is placed by the entity's [`GlobalTransform`], and isn't
+/// drawn while the entity is hidden.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+#[require(Transform, Visibility)]
+pub struct InstancedMesh {
+    pub mesh: Handle<Mesh>,
+    pub texture: Handle<Image>,
+}
+
+/// The instances of one [`InstancedMesh`] to draw this frame, as the
+/// world-from-local matrix of each.
+#[derive(Component, Debug, Clone, ExtractComponent)]
+struct InstanceBatch {
+    mesh: Handle<Mesh>,
+    texture: Handle<Image>,
+    instances: Vec<Mat4>,
+}
+
+/// The entity of each [`InstancedMesh`]'s batch.
+#[derive(Resource, Debug, Default)]
+struct InstanceBatches(HashMap<InstancedMesh, Entity>);
+
+/// Plugin that draws entities with an [`InstancedMesh`] in batches.
+///
+/// # Ordering
+///
+/// Instances are gathered into batches in [`PostUpdate`], once transforms and
+/// visibility have been propagated for the frame.
+pub struct InstancingPlugin;
+
+impl Plugin for InstancingPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "instancing.wgsl");
+
+        app.init_resource::<InstanceBatches>()
+            .add_plugins(ExtractComponentPlugin::<InstanceBatch>::default())
+            .add_systems(
+                PostUpdate,
+                batch_instances
+                    .after(TransformSystems::Propagate)
+                    .after(VisibilitySystems::VisibilityPropagate),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_command::<Transparent3d, DrawInstances>()
+            .init_resource::<SpecializedMeshPipelines<InstancingPipeline>>()
+            .add_systems(RenderStartup, init_instancing_pipeline)
+            .add_systems(
+                Render,
+                (
+                    queue_instance_batches.in_set(RenderSystems::QueueMeshes),
+                    prepare_instance_batches.in_set(RenderSystems::PrepareBindGroups),
+                ),
+            );
+    }
+}
+
+fn batch_instances(
+    mut commands: Commands,
+    mut batches: ResMut<InstanceBatches>,
+    instances: Query<(&InstancedMesh, &GlobalTransform, &InheritedVisibility)>,
+    mut batch_entities: Query<&mut InstanceBatch>,
+) {
+    let mut grouped: HashMap<&InstancedMesh, Vec<Mat4>> = HashMap::default();
+    for (instanced_mesh, transform, visibility) in instances.iter() {
+        if visibility.get() {
+            grouped
+                .entry(instanced_mesh)
+                .or_default()
+                .push(Mat4::from(transform.affine()));
+        }
+    }
+
+    batches.0.retain(|instanced_mesh, batch| {
+        let keep = grouped.contains_key(instanced_mesh);
+        if !keep {
+            commands.entity(*batch).despawn();
+        }
+        keep
+    });
+
+    for (instanced_mesh, instances) in grouped {
+        let existing = batches.0.get(instanced_mesh).copied();
+        if let Some(mut batch) = existing.and_then(|batch| batch_entities.get_mut(batch).ok()) {
+            batch.instances = instances;
+            continue;
+        }
+
+        let batch = commands
+            .spawn((
+                Name::new("Instance Batch"),
+                InstanceBatch {
+                    mesh: instanced_mesh.mesh.clone(),
+                    texture: instanced_mesh.texture.clone(),
+                    instances,
+                },
+            ))
+            .id();
+        batches.0.insert(instanced_mesh.clone(), batch);
+    }
+}
+
+/*
+  ____                _
+ |  _ \ ___ _ __   __| | ___ _ __
+ | |_) / _ \ '_ \ / _` |/ _ \ '__|
+ |  _ <  __/ | | | (_| |  __/ |
+ |_| \_\___|_| |_|\__,_|\___|_|
+*/
+
+/// The mesh pipeline, with the instance attributes as a second vertex buffer
+/// and the batch's texture in place of the mesh bind group.
+#[derive(Resource)]
+struct InstancingPipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+    texture_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+fn init_instancing_pipeline(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mesh_pipeline: Res<MeshPipeline>,
+    render_device: Res<RenderDevice>,
+) {
+    let texture_layout = render_device.create_bind_group_layout(
+        "instance_texture_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    );
+
+    // Block and entity textures are pixel art.
+    let sampler = render_device.create_sampler(&SamplerDescriptor {
+        label: Some("instance_texture_sampler"),
+        mag_filter: FilterMode::Nearest,
+        min_filter: FilterMode::Nearest,
+        ..default()
+    });
+
+    commands.insert_resource(InstancingPipeline {
+        shader: asset_server.load(SHADER_PATH),
+        mesh_pipeline: mesh_pipeline.clone(),
+        texture_layout,
+        sampler,
+    });
+}
+
+impl SpecializedMeshPipeline for InstancingPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+
+        // Instances carry their own transform, so the mesh bind group is
+        // replaced by the texture.
+        descriptor.layout.truncate(2);
+        descriptor.layout.push(self.texture_layout.clone());
+
+        let columns = (0..4).map(|column| VertexAttribute {
+            format: VertexFormat::Float32x4,
+            offset: column as u64 * VertexFormat::Float32x4.size(),
+            shader_location: INSTANCE_SHADER_LOCATION + column,
+        });
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: size_of::<Mat4>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: columns.collect(),
+        });
+
+        descriptor.vertex.shader = self.shader.clone();
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader = self.shader.clone();
+        }
+
+        Ok(descriptor)
+    }
+}
+
+/// An [`InstanceBatch`]'s instances and texture, ready to draw.
+#[derive(Component)]
+struct PreparedInstanceBatch {
+    mesh: AssetId<Mesh>,
+    buffer: Buffer,
+    count: u32,
+    bind_group: BindGroup,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_instance_batches(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    instancing_pipeline: Res<InstancingPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<InstancingPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    batches: Query<(Entity, &MainEntity, &InstanceBatch)>,
+    mut phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(&ExtractedView, &Msaa)>,
+) {
+    let draw_instances = draw_functions.read().id::<DrawInstances>();
+
+    for (view, msaa) in views.iter() {
+        let Some(phase) = phases.get_mut(&view.retained_view_entity) else {
+            continue;
+        };
+
+        let view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr);
+        let rangefinder = view.rangefinder3d();
+
+        for (entity, main_entity, batch) in batches.iter() {
+            let Some(mesh) = meshes.get(batch.mesh.id()) else {
+                continue;
+            };
+
+            let key =
+                view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let pipeline = match pipelines.specialize(
+                &pipeline_cache,
+                &instancing_pipeline,
+                key,
+                &mesh.layout,
+            ) {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("Can't draw instances of mesh {}: {}", batch.mesh.id(), err);
+                    continue;
+                }
+            };
+
+            // Batches are spread all over, so none is nearer than another.
+            phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function: draw_instances,
+                distance: rangefinder.distance_translation(&Vec3::ZERO),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::None,
+                indexed: mesh.indexed(),
+            });
+        }
+    }
+}
+
+fn prepare_instance_batches(
+    mut commands: Commands,
+    batches: Query<(Entity, &InstanceBatch)>,
+    instancing_pipeline: Res<InstancingPipeline>,
+    images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+) {
+    for (entity, batch) in batches.iter() {
+        let Some(image) = images.get(batch.texture.id()) else {
+            continue;
+        };
+
+        let contents: Vec<u8> = batch
+            .instances
+            .iter()
+            .flat_map(Mat4::to_cols_array)
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("instance_buffer"),
+            contents: &contents,
+            usage: BufferUsages::VERTEX,
+        });
+
+        let bind_group = render_device.create_bind_group(
+            "instance_texture_bind_group",
+            &instancing_pipeline.texture_layout,
+            &BindGroupEntries::sequential((&image.texture_view, &instancing_pipeline.sampler)),
+        );
+
+        commands.entity(entity).insert(PreparedInstanceBatch {
+            mesh: batch.mesh.id(),
+            buffer,
+            count: batch.instances.len() as u32,
+            bind_group,
+        });
+    }
+}
+
+type DrawInstances = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshViewBindingArrayBindGroup<1>,
+    DrawInstanceBatch,
+);
+
+struct DrawInstanceBatch;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawInstanceBatch {
+    type Param = (SRes<RenderAssets<RenderMesh>>, SRes<MeshAllocator>);
+    type ViewQuery = ();
+    type ItemQuery = Read<PreparedInstanceBatch>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        batch: Option<&'w PreparedInstanceBatch>,
+        (meshes, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let Some(batch) = batch else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(mesh) = meshes.into_inner().get(batch.mesh) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertices) = mesh_allocator.mesh_vertex_slice(&batch.mesh) else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_bind_group(2, &batch.bind_group, &[]);
+        pass.set_vertex_buffer(0, vertices.buffer.slice(..));
+        pass.set_vertex_buffer(1, batch.buffer.slice(..));
+
+        match &mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed {
+                index_format,
+                count,
+            } => {
+                let Some(indices) = mesh_allocator.mesh_index_slice(&batch.mesh) else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(indices.buffer.slice(..), *index_format);
+                pass.draw_indexed(
+                    indices.range.start..(indices.range.start + count),
+                    vertices.range.start as i32,
+                    0..batch.count,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertices.range, 0..batch.count);
+            }
+        }
+
+        RenderCommandResult::Success
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(visibility: InheritedVisibility, x: f32) -> impl Bundle {
+        (
+            InstancedMesh {
+                mesh: Handle::default(),
+                texture: Handle::default(),
+            },
+            GlobalTransform::from_xyz(x, 0.0, 0.0),
+            visibility,
+        )
+    }
+
+    #[test]
+    fn visible_instances_share_a_batch() {
+        let mut app = App::new();
+        app.init_resource::<InstanceBatches>()
+            .add_systems(Update, batch_instances);
+
+        let first = app
+            .world_mut()
+            .spawn(instance(InheritedVisibility::VISIBLE, 1.0))
+            .id();
+        let second = app
+            .world_mut()
+            .spawn(instance(InheritedVisibility::VISIBLE, 2.0))
+            .id();
+        let hidden = app
+            .world_mut()
+            .spawn(instance(InheritedVisibility::HIDDEN, 3.0))
+            .id();
+        app.update();
+
+        let mut batches = app.world_mut().query::<&InstanceBatch>();
+        let batch = batches.single(app.world()).unwrap();
+        let mut xs: Vec<f32> = batch.instances.iter().map(|m| m.w_axis.x).collect();
+        xs.sort_by(f32::total_cmp);
+        assert_eq!(xs, [1.0, 2.0]);
+
+        for entity in [first, second, hidden] {
+            app.world_mut().despawn(entity);
+        }
+        app.update();
+
+        assert_eq!(batches.iter(app.world()).count(), 0);
+        assert!(app.world().resource::<InstanceBatches>().0.is_empty());
+    }
+}
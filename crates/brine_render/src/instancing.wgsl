@@ -0,0 +1,47 @@
+// Shader of instance batches: each instance's world-from-local matrix comes
+// in as four per-instance vertex attributes, and the batch's texture is drawn
+// unlit, cutting out texels with less than half opacity.
+
+#import bevy_pbr::mesh_view_bindings::view
+
+@group(2) @binding(0) var instance_texture: texture_2d<f32>;
+@group(2) @binding(1) var instance_sampler: sampler;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+
+    @location(8) world_from_local_0: vec4<f32>,
+    @location(9) world_from_local_1: vec4<f32>,
+    @location(10) world_from_local_2: vec4<f32>,
+    @location(11) world_from_local_3: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let world_from_local = mat4x4<f32>(
+        vertex.world_from_local_0,
+        vertex.world_from_local_1,
+        vertex.world_from_local_2,
+        vertex.world_from_local_3,
+    );
+
+    var out: VertexOutput;
+    out.clip_position = view.clip_from_world * world_from_local * vec4<f32>(vertex.position, 1.0);
+    out.uv = vertex.uv;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(instance_texture, instance_sampler, in.uv);
+    if color.a < 0.5 {
+        discard;
+    }
+    return color;
+}
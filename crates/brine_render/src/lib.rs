@@ -1,2 +1,6 @@
+pub mod billboard;
 pub mod chunk;
+pub mod font;
+pub mod instancing;
 pub mod texture;
+pub mod ui_text;
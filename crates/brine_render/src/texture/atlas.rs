@@ -46,6 +46,7 @@ impl TextureAtlas {
         T: IntoIterator<Item = (TextureKey, &'a Handle<Image>)>,
     {
         let textures: Vec<(TextureKey, &Handle<Image>)> = textures.into_iter().collect();
+        let _span = info_span!("atlas_stitch", textures = textures.len()).entered();
 
         debug!("Stitching texture atlas with {} textures", textures.len());
 
@@ -2,6 +2,7 @@ use bevy::{prelude::*, render::renderer::RenderDevice};
 use std::collections::HashMap;
 
 use brine_asset::TextureKey;
+use brine_proto::BrineSystems;
 
 use crate::texture::{PendingAtlas, TextureAtlas};
 
@@ -189,7 +190,10 @@ impl Plugin for TextureManagerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TextureManager>();
         app.init_asset::<TextureAtlas>();
-        app.add_systems(Update, stitch_pending_atlases);
+        app.add_systems(
+            Update,
+            stitch_pending_atlases.in_set(BrineSystems::RenderPrep),
+        );
     }
 }
 
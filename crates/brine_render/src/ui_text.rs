@@ -0,0 +1,471 @@
+//! UI text drawn with the Minecraft font, like chat and the HUD.
+//!
+//! Text can hold legacy formatting codes: `§` followed by a color (`0`-`9`,
+//! `a`-`f`), which also resets the style, or by `k` (obfuscated), `l` (bold),
+//! `m` (strikethrough), `n` (underlined), `o` (italic) or `r` (reset). Unknown
+//! codes are dropped, like vanilla.
+//!
+//! Each [`MinecraftText`] is drawn from the [`BitmapFont`] texture into an
+//! image of its own, pixel for pixel, then shown as an [`ImageNode`]. As in
+//! vanilla, bold glyphs are drawn twice a pixel apart and take a pixel more,
+//! italic glyphs lean a pixel each way, obfuscated glyphs are swapped for
+//! random glyphs of the same width every frame, and the shadow is drawn a
+//! pixel down and right at a quarter of the brightness.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    ui::UiSystems,
+};
+
+use crate::font::{BitmapFont, GLYPH_SIZE, LINE_HEIGHT};
+
+/// Starts a formatting code.
+pub const FORMATTING_PREFIX: char = '§';
+
+/// The colors of the color formatting codes `0` to `f`.
+const FORMATTING_COLORS: [u32; 16] = [
+    0x000000, 0x0000AA, 0x00AA00, 0x00AAAA, 0xAA0000, 0xAA00AA, 0xFFAA00, 0xAAAAAA, 0x555555,
+    0x5555FF, 0x55FF55, 0x55FFFF, 0xFF5555, 0xFF55FF, 0xFFFF55, 0xFFFFFF,
+];
+
+/// How text is styled by formatting codes.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// Color from a color code, or `None` for the text's own color.
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub strikethrough: bool,
+    pub obfuscated: bool,
+}
+
+impl TextStyle {
+    /// Applies the formatting code `code`, the character after the `§`.
+    /// Returns `false` if there is no such code.
+    pub fn apply(&mut self, code: char) -> bool {
+        let code = code.to_ascii_lowercase();
+        if let Some(index) = code.to_digit(16) {
+            let [_, red, green, blue] = FORMATTING_COLORS[index as usize].to_be_bytes();
+            *self = Self {
+                color: Some(Color::srgb_u8(red, green, blue)),
+                ..default()
+            };
+            return true;
+        }
+
+        match code {
+            'k' => self.obfuscated = true,
+            'l' => self.bold = true,
+            'm' => self.strikethrough = true,
+            'n' => self.underlined = true,
+            'o' => self.italic = true,
+            'r' => *self = Self::default(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// A run of text in one [`TextStyle`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+/// Splits `text` into runs of styled text, taking out its formatting codes.
+pub fn parse_formatting(text: &str) -> Vec<TextRun> {
+    let mut runs: Vec<TextRun> = Vec::new();
+    let mut style = TextStyle::default();
+    let mut characters = text.chars();
+
+    while let Some(character) = characters.next() {
+        if character == FORMATTING_PREFIX {
+            if let Some(code) = characters.next() {
+                style.apply(code);
+            }
+            continue;
+        }
+
+        match runs.last_mut() {
+            Some(run) if run.style == style => run.text.push(character),
+            _ => runs.push(TextRun {
+                text: character.to_string(),
+                style,
+            }),
+        }
+    }
+
+    runs
+}
+
+/// Text drawn in the UI with the Minecraft font, with
+/// [formatting codes](self).
+///
+/// Sets the width and height of the entity's [`Node`] to those of the text,
+/// so padding goes outside the text only with [`BoxSizing::ContentBox`].
+#[derive(Component, Debug, Clone, PartialEq)]
+#[require(Node)]
+pub struct MinecraftText {
+    pub text: String,
+
+    /// Color of text without a color code.
+    pub color: Color,
+
+    pub shadow: bool,
+
+    /// How lines are aligned with each other. [`Justify::Justified`] lines
+    /// are aligned left.
+    pub justify: Justify,
+
+    /// Size of a font pixel, in UI pixels.
+    pub scale: f32,
+}
+
+impl MinecraftText {
+    /// White text with a shadow, at a GUI scale of 2.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: Color::WHITE,
+            shadow: true,
+            justify: Justify::Left,
+            scale: 2.0,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_shadow(mut self, shadow: bool) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
+    pub fn with_justify(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Whether a [`MinecraftText`] has obfuscated glyphs to swap every frame.
+#[derive(Component, Debug)]
+struct Obfuscated;
+
+/// Plugin that draws [`MinecraftText`].
+///
+/// Text is drawn once the [`BitmapFont`] is loaded.
+pub struct UiTextPlugin;
+
+impl Plugin for UiTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            draw_text
+                .before(UiSystems::Layout)
+                .run_if(resource_exists::<BitmapFont>),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_text(
+    mut commands: Commands,
+    font: Res<BitmapFont>,
+    mut images: ResMut<Assets<Image>>,
+    mut rng: Local<fastrand::Rng>,
+    mut texts: Query<(
+        Entity,
+        Ref<MinecraftText>,
+        &mut Node,
+        Option<&ImageNode>,
+        Has<Obfuscated>,
+    )>,
+) {
+    for (entity, text, mut node, image_node, obfuscated) in texts.iter_mut() {
+        if !text.is_changed() && !font.is_added() && !obfuscated {
+            continue;
+        }
+
+        let runs = parse_formatting(&text.text);
+        let (image, size) = rasterize(&font, &text, &runs, &mut rng);
+        node.width = Val::Px(size.x * text.scale);
+        node.height = Val::Px(size.y * text.scale);
+
+        match image_node {
+            Some(image_node) => {
+                if let Some(existing) = images.get_mut(&image_node.image) {
+                    *existing = image;
+                }
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(ImageNode::new(images.add(image)));
+            }
+        }
+
+        if runs.iter().any(|run| run.style.obfuscated) != obfuscated {
+            if obfuscated {
+                commands.entity(entity).remove::<Obfuscated>();
+            } else {
+                commands.entity(entity).insert(Obfuscated);
+            }
+        }
+    }
+}
+
+/// A glyph placed on a line.
+struct PlacedGlyph {
+    character: char,
+    style: TextStyle,
+    /// Left edge, in font pixels from the start of the line.
+    x: f32,
+    advance: f32,
+}
+
+/// Draws `runs` of `text` into a new image, returning it and the size of the
+/// text in font pixels.
+fn rasterize(
+    font: &BitmapFont,
+    text: &MinecraftText,
+    runs: &[TextRun],
+    rng: &mut fastrand::Rng,
+) -> (Image, Vec2) {
+    let mut lines = vec![Vec::new()];
+    let mut x = 0.0;
+    for run in runs {
+        for character in run.text.chars() {
+            if character == '\n' {
+                lines.push(Vec::new());
+                x = 0.0;
+                continue;
+            }
+
+            let character = if run.style.obfuscated {
+                obfuscate(font, character, rng)
+            } else {
+                character
+            };
+            let advance = font.glyph(character).advance + f32::from(u8::from(run.style.bold));
+            lines.last_mut().unwrap().push(PlacedGlyph {
+                character,
+                style: run.style,
+                x,
+                advance,
+            });
+            x += advance;
+        }
+    }
+
+    let line_width =
+        |line: &[PlacedGlyph]| line.last().map_or(0.0, |glyph| glyph.x + glyph.advance);
+    let width = lines
+        .iter()
+        .map(|line| line_width(line))
+        .fold(0.0, f32::max);
+    let size = Vec2::new(width, lines.len() as f32 * LINE_HEIGHT);
+
+    let mut canvas = Canvas::new(font, size);
+    let passes = if text.shadow { 2 } else { 1 };
+    for pass in (0..passes).rev() {
+        let shadow = pass == 1;
+        let offset = if shadow { 1.0 } else { 0.0 };
+
+        for (index, line) in lines.iter().enumerate() {
+            let left = match text.justify {
+                Justify::Center => ((width - line_width(line)) / 2.0).floor(),
+                Justify::Right => width - line_width(line),
+                _ => 0.0,
+            };
+            let top = index as f32 * LINE_HEIGHT + offset;
+
+            for glyph in line {
+                let color = glyph.style.color.unwrap_or(text.color);
+                let color = if shadow {
+                    let Srgba {
+                        red, green, blue, ..
+                    } = color.to_srgba();
+                    Color::srgb(red / 4.0, green / 4.0, blue / 4.0)
+                } else {
+                    color
+                }
+                .with_alpha(text.color.alpha());
+
+                let x = left + glyph.x + offset;
+                canvas.glyph(glyph.character, Vec2::new(x, top), glyph.style, color);
+                if glyph.style.bold {
+                    canvas.glyph(glyph.character, Vec2::new(x + 1.0, top), glyph.style, color);
+                }
+                if glyph.style.strikethrough {
+                    canvas.line(x - 1.0, x + glyph.advance, top + 3.5, color);
+                }
+                if glyph.style.underlined {
+                    canvas.line(x - 1.0, x + glyph.advance, top + GLYPH_SIZE, color);
+                }
+            }
+        }
+    }
+
+    (canvas.into_image(), size)
+}
+
+/// Returns a random glyph as wide as `character`'s, or `character` itself if
+/// no other glyph is.
+fn obfuscate(font: &BitmapFont, character: char, rng: &mut fastrand::Rng) -> char {
+    let advance = font.glyph(character).advance;
+    let candidates: Vec<char> = ('!'..='~')
+        .filter(|candidate| font.glyph(*candidate).advance == advance)
+        .collect();
+    if candidates.is_empty() {
+        character
+    } else {
+        candidates[rng.usize(..candidates.len())]
+    }
+}
+
+/// Texels of text being drawn, at the resolution of the font texture.
+struct Canvas<'a> {
+    font: &'a BitmapFont,
+    texels_per_pixel: u32,
+    size: UVec2,
+    data: Vec<u8>,
+}
+
+impl<'a> Canvas<'a> {
+    /// A transparent canvas `size` font pixels big.
+    fn new(font: &'a BitmapFont, size: Vec2) -> Self {
+        let texels_per_pixel = font.texels_per_pixel();
+        let size = (size * texels_per_pixel as f32).as_uvec2().max(UVec2::ONE);
+        Self {
+            font,
+            texels_per_pixel,
+            size,
+            data: vec![0; (size.x * size.y * 4) as usize],
+        }
+    }
+
+    fn set(&mut self, position: IVec2, color: [u8; 4]) {
+        if position.cmpge(IVec2::ZERO).all() && position.as_uvec2().cmplt(self.size).all() {
+            let index = ((position.y as u32 * self.size.x + position.x as u32) * 4) as usize;
+            self.data[index..index + 4].copy_from_slice(&color);
+        }
+    }
+
+    /// Draws `character` with its top left corner at `position`, in font
+    /// pixels.
+    fn glyph(&mut self, character: char, position: Vec2, style: TextStyle, color: Color) {
+        let color = color.to_srgba().to_u8_array();
+        let rect = self.font.glyph(character).rect;
+        let scale = self.texels_per_pixel as f32;
+        let origin = (position * scale).as_ivec2();
+
+        for row in 0..rect.height() as u32 {
+            // Vanilla leans italic glyphs from a pixel right at the top to a
+            // pixel left at the bottom.
+            let lean = if style.italic {
+                ((1.0 - 0.25 * (row as f32 + 0.5) / scale) * scale).round() as i32
+            } else {
+                0
+            };
+
+            for column in 0..rect.width() as u32 {
+                let texel = rect.min.as_uvec2() + UVec2::new(column, row);
+                if self.font.is_opaque(texel) {
+                    let offset = IVec2::new(column as i32 + lean, row as i32);
+                    self.set(origin + offset, color);
+                }
+            }
+        }
+    }
+
+    /// Draws a line a pixel thick from `left` to `right` with its top at
+    /// `top`, in font pixels.
+    fn line(&mut self, left: f32, right: f32, top: f32, color: Color) {
+        let color = color.to_srgba().to_u8_array();
+        let scale = self.texels_per_pixel as f32;
+        let [left, right, top, bottom] =
+            [left, right, top, top + 1.0].map(|value| (value * scale).floor() as i32);
+
+        for y in top..bottom {
+            for x in left..right {
+                self.set(IVec2::new(x, y), color);
+            }
+        }
+    }
+
+    fn into_image(self) -> Image {
+        let mut image = Image::new(
+            Extent3d {
+                width: self.size.x,
+                height: self.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.sampler = ImageSampler::nearest();
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::tests::font;
+
+    #[test]
+    fn formatting_codes_style_runs() {
+        let runs = parse_formatting("§cRed §lbold§r plain§z§");
+
+        let red = Color::srgb_u8(0xFF, 0x55, 0x55);
+        assert_eq!(
+            runs,
+            [
+                TextRun {
+                    text: String::from("Red "),
+                    style: TextStyle {
+                        color: Some(red),
+                        ..default()
+                    },
+                },
+                TextRun {
+                    text: String::from("bold"),
+                    style: TextStyle {
+                        color: Some(red),
+                        bold: true,
+                        ..default()
+                    },
+                },
+                TextRun {
+                    text: String::from(" plain"),
+                    style: TextStyle::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bold_glyphs_take_a_pixel_more() {
+        let font = font(16);
+        let mut rng = fastrand::Rng::with_seed(0);
+        let text = MinecraftText::new("§lA§rA\nAi");
+
+        let (image, size) = rasterize(&font, &text, &parse_formatting(&text.text), &mut rng);
+        assert_eq!(size, Vec2::new(7.0 + 6.0, 2.0 * LINE_HEIGHT));
+        assert_eq!(image.width(), 26);
+    }
+}
@@ -0,0 +1,191 @@
+//! Bringing newly built chunks into view gradually.
+//!
+//! Chunks are built a few at a time while the world loads, and each one pops
+//! into place in a single frame. With the [`ChunkAppearancePlugin`], each new
+//! [`BuiltChunk`] instead rises into place from below or fades in over
+//! [`APPEAR_DURATION`], as the [`ChunkAppearance`] resource says. Chunks that
+//! are [`Rebuilt`] (remeshed because a block changed, or the mesh settings
+//! did) appear at once, since the chunk they replace was already in view.
+//!
+//! Fading draws the chunk's sections with alpha blending until they are
+//! opaque, so it costs more than rising while chunks load. Fluids share their
+//! materials between chunks, so they aren't faded and appear at once.
+
+use std::time::Duration;
+
+use bevy::{pbr::MeshMaterial3d, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use brine_proto::BrineSystems;
+
+use crate::chunk_builder::component::{BuiltChunk, BuiltChunkSection, Rebuilt};
+
+/// How long a chunk takes to appear.
+pub const APPEAR_DURATION: Duration = Duration::from_millis(600);
+
+/// How far below its place a rising chunk starts, in blocks.
+const RISE_DEPTH: f32 = 16.0;
+
+/// How the [`ChunkAppearancePlugin`] brings new chunks into view.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkAppearance {
+    /// All at once, as soon as they are built.
+    Instant,
+
+    /// Rising into place from below.
+    #[default]
+    Rise,
+
+    /// Fading in from transparent.
+    Fade,
+}
+
+/// Component of a [`BuiltChunk`] that is still appearing.
+#[derive(Component, Debug)]
+pub struct Appearing {
+    appearance: ChunkAppearance,
+    timer: Timer,
+
+    /// Where the chunk rises to.
+    rest_y: f32,
+
+    /// The materials of the chunk's sections, with the alpha mode each had
+    /// before fading, to go back to.
+    materials: Vec<(Handle<StandardMaterial>, AlphaMode)>,
+}
+
+impl Appearing {
+    /// Returns how far the chunk has come into view, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        ease_out(self.timer.fraction())
+    }
+}
+
+/// Starts fast and settles gently into place.
+fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Plugin that animates new [`BuiltChunk`]s into view.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`ChunkAppearance`], unless it was already inserted
+///
+/// # Ordering
+///
+/// Chunks are animated in [`BrineSystems::RenderPrep`], after the chunk
+/// builder plugins have spawned them in [`BrineSystems::MeshBuild`].
+pub struct ChunkAppearancePlugin;
+
+impl Plugin for ChunkAppearancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkAppearance>().add_systems(
+            Update,
+            (start_appearing, animate_appearing)
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+fn start_appearing(
+    appearance: Res<ChunkAppearance>,
+    mut chunks: Query<(Entity, &mut Transform, &Children), (Added<BuiltChunk>, Without<Rebuilt>)>,
+    sections: Query<&MeshMaterial3d<StandardMaterial>, With<BuiltChunkSection>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    if *appearance == ChunkAppearance::Instant {
+        return;
+    }
+
+    for (entity, mut transform, children) in chunks.iter_mut() {
+        let mut appearing = Appearing {
+            appearance: *appearance,
+            timer: Timer::new(APPEAR_DURATION, TimerMode::Once),
+            rest_y: transform.translation.y,
+            materials: Vec::new(),
+        };
+
+        // Out of sight before the first frame is drawn.
+        match *appearance {
+            ChunkAppearance::Instant => {}
+            ChunkAppearance::Rise => transform.translation.y -= RISE_DEPTH,
+            ChunkAppearance::Fade => {
+                for material in sections.iter_many(children) {
+                    let Some(standard) = materials.get_mut(&material.0) else {
+                        continue;
+                    };
+                    appearing
+                        .materials
+                        .push((material.0.clone(), standard.alpha_mode));
+                    standard.alpha_mode = AlphaMode::Blend;
+                    standard.base_color = standard.base_color.with_alpha(0.0);
+                }
+            }
+        }
+
+        commands.entity(entity).insert(appearing);
+    }
+}
+
+fn animate_appearing(
+    time: Res<Time>,
+    mut chunks: Query<(Entity, &mut Appearing, &mut Transform)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    for (entity, mut appearing, mut transform) in chunks.iter_mut() {
+        let finished = appearing.timer.tick(time.delta()).is_finished();
+        let progress = appearing.progress();
+
+        match appearing.appearance {
+            ChunkAppearance::Instant => {}
+            ChunkAppearance::Rise => {
+                transform.translation.y = appearing.rest_y - RISE_DEPTH * (1.0 - progress);
+            }
+            ChunkAppearance::Fade => {
+                for (handle, alpha_mode) in &appearing.materials {
+                    let Some(material) = materials.get_mut(handle) else {
+                        continue;
+                    };
+                    material.base_color = material.base_color.with_alpha(progress);
+                    if finished {
+                        material.alpha_mode = *alpha_mode;
+                    }
+                }
+            }
+        }
+
+        if finished {
+            commands.entity(entity).remove::<Appearing>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appearing_eases_into_place() {
+        assert_eq!(ease_out(0.0), 0.0);
+        assert_eq!(ease_out(1.0), 1.0);
+        assert!(ease_out(0.5) > 0.5);
+
+        let mut appearing = Appearing {
+            appearance: ChunkAppearance::Rise,
+            timer: Timer::new(APPEAR_DURATION, TimerMode::Once),
+            rest_y: 0.0,
+            materials: Vec::new(),
+        };
+        appearing.timer.tick(APPEAR_DURATION / 2);
+        assert!(appearing.progress() > 0.5 && appearing.progress() < 1.0);
+        appearing.timer.tick(APPEAR_DURATION);
+        assert_eq!(appearing.progress(), 1.0);
+    }
+}
@@ -96,6 +96,30 @@ impl ChunkBuilder for GreedyQuadsChunkBuilder {
     }
 }
 
+/// Returns the face of the block at `voxel` (in section coordinates) facing
+/// `axis`, as the visible faces builder makes it.
+pub(crate) fn unit_face(voxel: [u8; 3], axis: Axis) -> VoxelFace {
+    let face = RIGHT_HANDED_Y_UP_CONFIG
+        .faces
+        .iter()
+        .find(|face| BlockMeshBuilder::get_axis(face) == axis)
+        .unwrap();
+    let quad = UnorientedQuad {
+        minimum: voxel.map(u32::from),
+        width: 1,
+        height: 1,
+    };
+
+    VoxelFace {
+        voxel,
+        axis,
+        positions: face.quad_mesh_positions(&quad, 1.0),
+        tex_coords: face.tex_coords(RIGHT_HANDED_Y_UP_CONFIG.u_flip_face, true, &quad),
+        indices: face.quad_mesh_indices(0).map(|i| i as u8),
+        ..Default::default()
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq)]
 struct BlockState(brine_chunk::BlockState);
 
@@ -150,6 +174,8 @@ impl BlockMeshBuilder {
     where
         F: FnOnce(&BlockMeshBuilder) -> BlockMeshOutput,
     {
+        let _span = debug_span!("mesh_build_section", chunk_y = chunk_section.chunk_y).entered();
+
         for (x, y, z, block_state) in chunk_section.block_states.iter() {
             let index = self
                 .shape
@@ -188,6 +214,7 @@ impl BlockMeshBuilder {
                 positions,
                 tex_coords,
                 indices,
+                ..Default::default()
             });
         });
 
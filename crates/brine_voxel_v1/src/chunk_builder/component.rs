@@ -1,8 +1,9 @@
 use std::fmt;
 
 use bevy::{prelude::*, tasks::Task};
+use brine_chunk::{ChunkPos, SectionPos};
 
-use crate::mesh::VoxelMesh;
+use crate::{fluid::FluidMesh, mesh::VoxelMesh, occlusion::SectionVisibility};
 
 use super::ChunkBuilderType;
 
@@ -13,20 +14,43 @@ pub struct PendingMeshAtlas {
     /// Handle to the atlas layout.
     pub layout: Handle<TextureAtlasLayout>,
 
+    /// Handle to the emissive atlas with the same layout, if any of the
+    /// textures glow.
+    pub emissive_texture: Option<Handle<Image>>,
+
     /// List of weak texture handles, one for each face in the [`VoxelMesh`].
     pub face_textures: Vec<Handle<Image>>,
 }
 
+/// What a builder task makes of a chunk: the chunk itself, then the voxel
+/// meshes, fluid meshes and visibility of its sections.
+pub type BuiltMeshes = (
+    brine_chunk::Chunk,
+    Vec<VoxelMesh>,
+    Vec<Vec<FluidMesh>>,
+    Vec<SectionVisibility>,
+);
+
 #[derive(Component, Default)]
 pub struct PendingChunk {
     pub builder: ChunkBuilderType,
 
-    pub task: Option<Task<(brine_chunk::Chunk, Vec<VoxelMesh>)>>,
+    pub task: Option<Task<BuiltMeshes>>,
 
     pub chunk_data: Option<brine_chunk::Chunk>,
     pub voxel_meshes: Option<Vec<VoxelMesh>>,
 
+    /// The fluid meshes of each section, if fluids are meshed separately.
+    pub fluid_meshes: Option<Vec<Vec<FluidMesh>>>,
+
+    /// The visibility of each section, if opaque blocks are known.
+    pub visibility: Option<Vec<SectionVisibility>>,
+
     pub texture_atlases: Option<Vec<PendingMeshAtlas>>,
+
+    /// Whether the chunk is being remeshed rather than built from newly
+    /// received chunk data.
+    pub rebuilt: bool,
 }
 
 impl PendingChunk {
@@ -43,6 +67,10 @@ impl PendingChunk {
 #[derive(Component)]
 pub struct ChunkSection(pub brine_chunk::ChunkSection);
 
+/// Component that stores the biome data for a built chunk.
+#[derive(Component)]
+pub struct ChunkBiomes(pub Box<brine_chunk::Biomes>);
+
 /// Component that signifies a built chunk.
 ///
 /// Typically has one or more children with [`BuiltChunkSection`] components.
@@ -53,12 +81,35 @@ pub struct BuiltChunk {
     pub chunk_z: i32,
 }
 
+impl BuiltChunk {
+    #[inline]
+    pub fn position(&self) -> ChunkPos {
+        ChunkPos::new(self.chunk_x, self.chunk_z)
+    }
+}
+
 impl fmt::Display for BuiltChunk {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Chunk ({}, {})", self.chunk_x, self.chunk_z)
     }
 }
 
+/// Marker component that asks for a [`BuiltChunk`] to be rebuilt from its
+/// stored chunk data, e.g. because the block models or textures it uses have
+/// changed.
+///
+/// The chunk is despawned and meshed again by the same chunk builder (or the
+/// [`ActiveChunkBuilder`][super::ActiveChunkBuilder], if there is one), so it
+/// disappears until its new meshes and atlases are ready.
+#[derive(Debug, Default, Component)]
+pub struct RemeshChunk;
+
+/// Marker component for a [`BuiltChunk`] that replaced an earlier build of
+/// the same chunk data (see [`RemeshChunk`]), rather than being built from
+/// chunk data newly received from the server.
+#[derive(Debug, Default, Component)]
+pub struct Rebuilt;
+
 /// Component that signifies a built chunk section.
 #[derive(Debug, Default, Component)]
 pub struct BuiltChunkSection {
@@ -78,6 +129,9 @@ pub struct BuiltChunkBundle {
     pub name: Name,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub inherited_visibility: InheritedVisibility,
+    pub view_visibility: ViewVisibility,
 }
 
 impl BuiltChunkBundle {
@@ -89,16 +143,16 @@ impl BuiltChunkBundle {
         };
 
         let name = Name::new(built_chunk.to_string());
+        let transform = Transform::from_translation(built_chunk.position().origin().as_vec3());
 
         Self {
             built_chunk,
             name,
-            transform: Transform::from_translation(Vec3::new(
-                (chunk_x * 16) as f32,
-                0.0,
-                (chunk_z * 16) as f32,
-            )),
+            transform,
             global_transform: Default::default(),
+            visibility: Visibility::Inherited,
+            inherited_visibility: InheritedVisibility::default(),
+            view_visibility: ViewVisibility::default(),
         }
     }
 }
@@ -123,9 +177,11 @@ impl BuiltChunkSectionBundle {
         Self {
             built_chunk_section,
             name,
-            transform: Transform::from_translation(Vec3::new(0.0, (section_y * 16) as f32, 0.0)),
+            transform: Transform::from_translation(
+                SectionPos::new(0, section_y.into(), 0).origin().as_vec3(),
+            ),
             global_transform: GlobalTransform::default(),
-            visibility: Visibility::Visible,
+            visibility: Visibility::Inherited,
             inherited_visibility: InheritedVisibility::default(),
             view_visibility: ViewVisibility::default(),
         }
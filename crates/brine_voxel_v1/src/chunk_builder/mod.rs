@@ -15,9 +15,10 @@ mod plugin;
 
 use crate::mesh::VoxelMesh;
 
+pub(crate) use self::block_mesh::unit_face;
 pub use self::block_mesh::{GreedyQuadsChunkBuilder, VisibleFacesChunkBuilder};
 pub use naive_blocks::NaiveBlocksChunkBuilder;
-pub use plugin::ChunkBuilderPlugin;
+pub use plugin::{ActiveChunkBuilder, ChunkBuilderPlugin};
 
 /// A trait for types that can turn a [`Chunk`] into [`VoxelMesh`]es.
 pub trait ChunkBuilder: Sized {
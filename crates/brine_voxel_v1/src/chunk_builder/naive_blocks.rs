@@ -71,6 +71,7 @@ impl NaiveBlocksChunkBuilder {
                     positions,
                     tex_coords,
                     indices: indices.map(|i| (i as usize - vertex_index) as u8),
+                    ..Default::default()
                 });
             }
         } else {
@@ -1,7 +1,9 @@
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::{any::Any, marker::PhantomData};
 
-use bevy::{pbr::MeshMaterial3d, prelude::*, tasks::AsyncComputeTaskPool};
+use bevy::{
+    ecs::system::SystemParam, pbr::MeshMaterial3d, prelude::*, tasks::AsyncComputeTaskPool,
+};
 use bevy_image::{TextureAtlasLayout, TextureAtlasSources};
 use bevy_mesh::Mesh3d;
 use futures_lite::future;
@@ -9,42 +11,83 @@ use futures_lite::future;
 use brine_asset::{api::BlockFace, MinecraftAssets};
 use brine_chunk::ChunkSection;
 use brine_data::BlockStateId;
-use brine_proto::event;
+use brine_proto::{
+    event::{self, clientbound::ChunkData},
+    BrineSystems, MessageBuffer, MessageBufferPlugin,
+};
 
 use crate::chunk_builder::component::PendingChunk;
+use crate::emissive::{tag_emission, EmissiveTexture};
+use crate::fluid::{FluidMaterials, FluidMesh, FluidStates};
 use crate::mesh::VoxelMesh;
+use crate::occlusion::{OpaqueBlocks, SectionVisibility};
+use crate::quality::{
+    add_faces_behind_cutouts, occlude_vertices, BlockRenderTable, LeavesQuality, MeshQuality,
+};
 use crate::texture::BlockTextures;
+use crate::waving::{Waving, WavingBlocks};
 
-use super::component::{ChunkSection as ChunkSectionComponent, PendingMeshAtlas};
+use super::component::{ChunkBiomes, ChunkSection as ChunkSectionComponent, PendingMeshAtlas};
 
 use super::{
-    component::{BuiltChunkBundle, BuiltChunkSectionBundle},
-    ChunkBuilder,
+    component::{BuiltChunk, BuiltChunkBundle, BuiltChunkSectionBundle, Rebuilt, RemeshChunk},
+    ChunkBuilder, ChunkBuilderType,
 };
 
+/// Which of several [`ChunkBuilderPlugin`]s builds chunks.
+///
+/// Without this resource, every chunk builder plugin builds every chunk. With
+/// it, only the plugin whose builder is of this type builds and remeshes
+/// chunks, and when it changes, the new builder remeshes the chunks the others
+/// built.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveChunkBuilder(pub ChunkBuilderType);
+
 /// Plugin that asynchronously generates renderable entities from chunk data.
 ///
 /// The [`ChunkBuilderPlugin`] listens for [`ChunkData`] events from the backend
 /// and spawns a task to run a particular [`ChunkBuilder`]. When the task
-/// completes, the plugin adds the result to the game world.
+/// completes, the plugin adds the result to the game world. Its systems run
+/// in [`BrineSystems::MeshBuild`].
+///
+/// If the [`FluidStates`] resource exists (see
+/// [`FluidPlugin`][crate::fluid::FluidPlugin]), fluids are left out of the
+/// chunk given to the builder and meshed separately, and each section's fluid
+/// meshes are added as children of the section once the [`FluidMaterials`]
+/// exist.
 ///
-/// [`ChunkData`]: brine_proto::event::clientbound::ChunkData
+/// If the [`WavingBlocks`] resource exists (see
+/// [`WavingPlugin`][crate::waving::WavingPlugin]), faces are tagged with how
+/// they wave, and meshes with waving faces are marked [`Waving`].
+///
+/// If the [`OpaqueBlocks`] resource exists (see
+/// [`OcclusionCullingPlugin`][crate::occlusion::OcclusionCullingPlugin]), each
+/// section gets a [`SectionVisibility`] saying which of its faces see each
+/// other, and the [`MeshQuality`] (see
+/// [`MeshQualityPlugin`][crate::quality::MeshQualityPlugin]) can add ambient
+/// occlusion and fancy leaves.
+///
+/// If the [`BlockRenderTable`] resource exists (see
+/// [`MeshQualityPlugin`][crate::quality::MeshQualityPlugin]), faces are tagged
+/// with the light level they glow with, and sections with glowing faces or
+/// emissive overlays get an emissive texture (see [`emissive`][crate::emissive]).
+///
+/// If the [`ActiveChunkBuilder`] resource exists, the plugin only builds
+/// chunks while its builder is the active one.
 pub struct ChunkBuilderPlugin<T: ChunkBuilder> {
     shared: bool,
     _phantom: PhantomData<T>,
 }
 
 impl<T: ChunkBuilder> ChunkBuilderPlugin<T> {
-    /// For (potentially premature) performance reasons, the default behavior of
-    /// the [`ChunkBuilderPlugin`] is to consume `ChunkData` events (i.e.,
-    /// [`Messages::drain()`]) so they can be moved into the builder task rather
-    /// than cloned.
-    ///
-    /// [`Messages::drain()`]: bevy::prelude::Messages::drain
+    /// The default behavior of the [`ChunkBuilderPlugin`] is to consume
+    /// `ChunkData` events from a [`MessageBuffer`] (i.e.,
+    /// [`MessageBuffer::drain()`]). It adds a [`MessageBufferPlugin`] for them,
+    /// so chunks are never lost however long they wait for the builder.
     ///
     /// This constructor allows multiple chunk builder plugins to exist
     /// simultaneously without them clobbering each other. It forces the plugin
-    /// to use a regular old [`EventReader`] rather than draining the events.
+    /// to use a regular old [`EventReader`] rather than draining the buffer.
     pub fn shared() -> Self {
         Self {
             shared: true,
@@ -67,15 +110,44 @@ where
     T: ChunkBuilder + Default + Send + Sync + 'static,
 {
     fn build(&self, app: &mut App) {
+        if !self.shared && !app.is_plugin_added::<MessageBufferPlugin<ChunkData>>() {
+            app.add_plugins(MessageBufferPlugin::<ChunkData>::new("chunk_data"));
+        }
+
+        // Clear out the old world before building chunks for the new one.
         if self.shared {
-            app.add_systems(Update, Self::builder_task_spawn_shared);
+            app.add_systems(
+                Update,
+                (
+                    Self::despawn_chunks_on_reset,
+                    Self::builder_task_spawn_shared.run_if(Self::is_active),
+                )
+                    .chain()
+                    .in_set(BrineSystems::MeshBuild),
+            );
         } else {
-            app.add_systems(Update, Self::builder_task_spawn_unique);
+            app.add_systems(
+                Update,
+                (
+                    Self::despawn_chunks_on_reset,
+                    Self::builder_task_spawn_unique.run_if(Self::is_active),
+                )
+                    .chain()
+                    .in_set(BrineSystems::MeshBuild),
+            );
         }
 
+        // Meshes can finish building before the assets are loaded; they wait
+        // in their pending chunks until texture atlases can be made.
         app.add_systems(
             Update,
-            (Self::receive_built_meshes, Self::add_built_chunks_to_world),
+            (
+                Self::take_over_chunks.run_if(resource_changed::<ActiveChunkBuilder>),
+                Self::remesh_chunks,
+                Self::receive_built_meshes.run_if(resource_exists::<MinecraftAssets>),
+                Self::add_built_chunks_to_world,
+            )
+                .in_set(BrineSystems::MeshBuild),
         );
     }
 }
@@ -84,7 +156,12 @@ impl<T> ChunkBuilderPlugin<T>
 where
     T: ChunkBuilder + Default + Any + Send + Sync + 'static,
 {
-    fn builder_task_spawn(chunk_event: event::clientbound::ChunkData, commands: &mut Commands) {
+    fn builder_task_spawn(
+        chunk_event: ChunkData,
+        resources: BuildData,
+        rebuilt: bool,
+        commands: &mut Commands,
+    ) {
         let chunk = chunk_event.chunk_data;
         if !chunk.is_full() {
             return;
@@ -97,12 +174,50 @@ where
 
         let task_pool = AsyncComputeTaskPool::get();
         let task = task_pool.spawn(async move {
-            let built = T::default().build_chunk(&chunk);
-            (chunk, built)
+            let _span = info_span!("mesh_build", builder = T::TYPE.0, chunk_x, chunk_z).entered();
+            let BuildData {
+                fluids,
+                waving,
+                opaque,
+                quality,
+                render_table,
+            } = resources;
+            let without_fluids = fluids.as_ref().map(|fluids| fluids.without_fluids(&chunk));
+            let solid = without_fluids.as_ref().unwrap_or(&chunk);
+            let mut built = T::default().build_chunk(solid);
+            if let Some(render_table) = &render_table {
+                tag_emission(render_table, &chunk, &mut built);
+            }
+            if let (Some(quality), Some(opaque)) = (quality, &opaque) {
+                let render_table = render_table.unwrap_or_default();
+                if quality.leaves == LeavesQuality::Fancy {
+                    add_faces_behind_cutouts(opaque, &render_table, solid, &mut built);
+                }
+                if quality.ambient_occlusion {
+                    occlude_vertices(opaque, &render_table, solid, &mut built);
+                }
+            }
+            if let Some(waving) = waving {
+                waving.tag(&chunk, &mut built);
+            }
+            let fluid_meshes = fluids
+                .map(|fluids| fluids.build_chunk(&chunk))
+                .unwrap_or_default();
+            let visibility = opaque
+                .map(|opaque| {
+                    chunk
+                        .sections
+                        .iter()
+                        .map(|section| opaque.section_visibility(section))
+                        .collect()
+                })
+                .unwrap_or_default();
+            (chunk, built, fluid_meshes, visibility)
         });
 
         let mut pending_chunk = PendingChunk::new(T::TYPE);
         pending_chunk.task = Some(task);
+        pending_chunk.rebuilt = rebuilt;
 
         commands.spawn((
             pending_chunk,
@@ -126,14 +241,17 @@ where
         // Texture handles, one for each face in the mesh.
         let mut face_textures: Vec<Handle<Image>> = Vec::with_capacity(mesh.faces.len());
 
+        // What glows of each texture that does.
+        let mut emissive: HashMap<Handle<Image>, EmissiveTexture> = Default::default();
+
         // Cached mapping from block state id to texture handle.
         let mut handle_cache: HashMap<(BlockStateId, BlockFace), Handle<Image>> =
             Default::default();
 
-        for face in mesh.faces.iter() {
-            let [x, y, z] = face.voxel;
+        for voxel_face in mesh.faces.iter() {
+            let [x, y, z] = voxel_face.voxel;
 
-            let face = face.axis.into();
+            let face = voxel_face.axis.into();
 
             let block_state_id = chunk_section.get_block((x, y, z)).unwrap();
             let block_state_id = BlockStateId(block_state_id.0 as u16);
@@ -144,7 +262,19 @@ where
                     let strong_handle = match mc_assets
                         .get_texture_path_for_block_state_and_face(block_state_id, face)
                     {
-                        Some(path) => asset_server.load(path),
+                        Some(path) => {
+                            let handle = asset_server.load(path.clone());
+                            let overlay =
+                                texture_builder.get_or_load_overlay(&path, asset_server, |path| {
+                                    mc_assets.get_emissive_overlay_path(path)
+                                });
+                            if let Some(overlay) = overlay {
+                                emissive
+                                    .entry(handle.clone())
+                                    .or_insert(EmissiveTexture::Overlay(overlay));
+                            }
+                            handle
+                        }
                         None => {
                             debug!("No texture for {:?}:{:?}", block_state_id, face);
                             texture_builder.placeholder_texture.clone()
@@ -160,6 +290,11 @@ where
                 Entry::Occupied(entry) => entry.get().clone(),
             };
 
+            // Blocks that give off light glow as a whole, overlay or not.
+            if voxel_face.emission > 0 {
+                emissive.insert(handle.clone(), EmissiveTexture::Whole);
+            }
+
             face_textures.push(handle);
         }
 
@@ -167,24 +302,33 @@ where
         // debug!("face_textures: {:#?}", &face_textures);
         // debug!("handle_cache: {:#?}", &handle_cache);
 
-        let (atlas_texture, layout) = texture_builder.create_texture_atlas_with_textures(
-            texture_handles.into_iter(),
-            textures,
-            atlas_layouts,
-        );
+        let (atlas_texture, layout, emissive_texture) = texture_builder
+            .create_texture_atlas_with_emissive(
+                texture_handles.into_iter(),
+                emissive,
+                textures,
+                atlas_layouts,
+            );
 
         PendingMeshAtlas {
             texture: atlas_texture,
             layout,
+            emissive_texture,
             face_textures,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn add_built_chunk_to_world(
         chunk_data: brine_chunk::Chunk,
         voxel_meshes: Vec<VoxelMesh>,
-        atlas_data: Vec<(&TextureAtlasLayout, &TextureAtlasSources, Handle<Image>)>,
+        fluid_meshes: Vec<Vec<FluidMesh>>,
+        visibility: Vec<SectionVisibility>,
+        atlas_data: Vec<AtlasData<'_>>,
         face_textures: Vec<Vec<Handle<Image>>>,
+        fluid_materials: Option<&FluidMaterials>,
+        alpha_mode: AlphaMode,
+        rebuilt: bool,
         meshes: &mut Assets<Mesh>,
         materials: &mut Assets<StandardMaterial>,
         commands: &mut Commands,
@@ -193,34 +337,82 @@ where
             "Adding chunk ({}, {}) to world",
             chunk_data.chunk_x, chunk_data.chunk_z
         );
-        commands
-            .spawn(BuiltChunkBundle::new(
-                T::TYPE,
-                chunk_data.chunk_x,
-                chunk_data.chunk_z,
-            ))
+        let mut chunk_entity = commands.spawn(BuiltChunkBundle::new(
+            T::TYPE,
+            chunk_data.chunk_x,
+            chunk_data.chunk_z,
+        ));
+
+        if let Some(biomes) = chunk_data.biomes {
+            chunk_entity.insert(ChunkBiomes(biomes));
+        }
+        if rebuilt {
+            chunk_entity.insert(Rebuilt);
+        }
+
+        // Chunks built without fluid states have no fluid meshes.
+        let mut fluid_meshes = fluid_meshes.into_iter();
+        // Nor do chunks built without opaque blocks have section visibility.
+        let mut visibility = visibility.into_iter();
+
+        chunk_entity
             .with_children(move |parent| {
-                for (((section, mut mesh), (layout, sources, texture_handle)), face_textures) in
-                    chunk_data
-                        .sections
-                        .into_iter()
-                        .zip(voxel_meshes.into_iter())
-                        .zip(atlas_data.into_iter())
-                        .zip(face_textures.into_iter())
+                for (
+                    ((section, mut mesh), (layout, sources, texture_handle, emissive)),
+                    face_textures,
+                ) in chunk_data
+                    .sections
+                    .into_iter()
+                    .zip(voxel_meshes.into_iter())
+                    .zip(atlas_data.into_iter())
+                    .zip(face_textures.into_iter())
                 {
                     mesh.adjust_tex_coords(layout, sources, &face_textures);
-
-                    parent
-                        .spawn((
-                            BuiltChunkSectionBundle::new(T::TYPE, section.chunk_y),
-                            Mesh3d(meshes.add(mesh.to_render_mesh())),
-                            MeshMaterial3d(materials.add(StandardMaterial {
-                                base_color_texture: Some(texture_handle.clone()),
-                                unlit: true,
-                                ..Default::default()
-                            })),
-                        ))
-                        .insert(ChunkSectionComponent(section));
+                    let section_fluids = fluid_meshes.next().unwrap_or_default();
+                    let waves = mesh.waves();
+
+                    let mut section_entity = parent.spawn((
+                        BuiltChunkSectionBundle::new(T::TYPE, section.chunk_y),
+                        Mesh3d(meshes.add(mesh.to_render_mesh())),
+                        MeshMaterial3d(materials.add(StandardMaterial {
+                            base_color_texture: Some(texture_handle.clone()),
+                            emissive: if emissive.is_some() {
+                                LinearRgba::WHITE
+                            } else {
+                                LinearRgba::BLACK
+                            },
+                            emissive_texture: emissive,
+                            unlit: true,
+                            alpha_mode,
+                            ..Default::default()
+                        })),
+                    ));
+                    if waves {
+                        section_entity.insert(Waving);
+                    }
+                    if let Some(visibility) = visibility.next() {
+                        section_entity.insert(visibility);
+                    }
+                    section_entity
+                        .insert(ChunkSectionComponent(section))
+                        .with_children(|section_parent| {
+                            for fluid in section_fluids {
+                                let Some(material) = fluid_materials
+                                    .and_then(|materials| materials.get(fluid.texture))
+                                else {
+                                    continue;
+                                };
+                                let mut fluid_entity = section_parent.spawn((
+                                    Name::new(format!("Fluid ({:?})", fluid.texture)),
+                                    Mesh3d(meshes.add(fluid.mesh.to_render_mesh())),
+                                    MeshMaterial3d(material),
+                                    Transform::default(),
+                                ));
+                                if fluid.mesh.waves() {
+                                    fluid_entity.insert(Waving);
+                                }
+                            }
+                        });
                 }
             })
             .id()
@@ -235,21 +427,122 @@ where
             |___/
     */
 
+    /// Despawns this builder's chunks when the server transfers the client
+    /// elsewhere or restarts configuration, as it resends the world either way.
+    fn despawn_chunks_on_reset(
+        mut transfer_events: MessageReader<event::clientbound::Transfer>,
+        mut reconfigure_events: MessageReader<event::clientbound::Reconfigure>,
+        built_chunks: Query<(Entity, &BuiltChunk)>,
+        pending_chunks: Query<(Entity, &PendingChunk)>,
+        mut commands: Commands,
+    ) {
+        let transferred = transfer_events.read().count() > 0;
+        let reconfigured = reconfigure_events.read().count() > 0;
+        if !transferred && !reconfigured {
+            return;
+        }
+
+        let mut despawned = 0;
+        for (entity, built_chunk) in &built_chunks {
+            if built_chunk.builder == T::TYPE {
+                commands.entity(entity).despawn();
+                despawned += 1;
+            }
+        }
+        for (entity, pending_chunk) in &pending_chunks {
+            if pending_chunk.builder == T::TYPE {
+                commands.entity(entity).despawn();
+            }
+        }
+
+        debug!("World reset; despawned {} chunks", despawned);
+    }
+
+    /// Run condition for building new chunks: whether this builder is the
+    /// [`ActiveChunkBuilder`], if there is one.
+    fn is_active(active: Option<Res<ActiveChunkBuilder>>) -> bool {
+        active.is_none_or(|active| active.0 == T::TYPE)
+    }
+
+    /// Whether this builder remeshes a chunk built by `builder`.
+    fn remeshes(builder: ChunkBuilderType, active: Option<&ActiveChunkBuilder>) -> bool {
+        match active {
+            Some(active) => active.0 == T::TYPE,
+            None => builder == T::TYPE,
+        }
+    }
+
+    /// Remeshes the chunks the other builders built once this builder becomes
+    /// the active one.
+    fn take_over_chunks(
+        active: Res<ActiveChunkBuilder>,
+        chunks: Query<(Entity, &BuiltChunk)>,
+        mut commands: Commands,
+    ) {
+        if active.0 != T::TYPE {
+            return;
+        }
+
+        for (entity, built_chunk) in chunks.iter() {
+            if built_chunk.builder != T::TYPE {
+                commands.entity(entity).insert(RemeshChunk);
+            }
+        }
+    }
+
+    /// Rebuilds chunks marked with [`RemeshChunk`] from the chunk data stored
+    /// on their sections.
+    fn remesh_chunks(
+        chunks: Query<(Entity, &BuiltChunk, &Children, Option<&ChunkBiomes>), With<RemeshChunk>>,
+        sections: Query<&ChunkSectionComponent>,
+        active: Option<Res<ActiveChunkBuilder>>,
+        resources: BuildResources,
+        mut commands: Commands,
+    ) {
+        for (entity, built_chunk, children, biomes) in chunks.iter() {
+            if !Self::remeshes(built_chunk.builder, active.as_deref()) {
+                continue;
+            }
+
+            let mut chunk_data =
+                brine_chunk::Chunk::empty(built_chunk.chunk_x, built_chunk.chunk_z);
+            chunk_data.sections = sections
+                .iter_many(children)
+                .map(|section| section.0.clone())
+                .collect();
+            chunk_data.sections.sort_by_key(|section| section.chunk_y);
+            if let Some(biomes) = biomes {
+                chunk_data.biomes = Some(biomes.0.clone());
+            }
+
+            debug!("Remeshing {}", built_chunk);
+            commands.entity(entity).despawn();
+            Self::builder_task_spawn(
+                ChunkData { chunk_data },
+                resources.get(),
+                true,
+                &mut commands,
+            );
+        }
+    }
+
     fn builder_task_spawn_unique(
-        mut chunk_events: ResMut<Messages<event::clientbound::ChunkData>>,
+        mut chunk_events: ResMut<MessageBuffer<ChunkData>>,
+        resources: BuildResources,
         mut commands: Commands,
     ) {
         for chunk_event in chunk_events.drain() {
-            Self::builder_task_spawn(chunk_event, &mut commands);
+            Self::builder_task_spawn(chunk_event, resources.get(), false, &mut commands);
         }
     }
 
     fn builder_task_spawn_shared(
-        mut chunk_events: MessageReader<event::clientbound::ChunkData>,
+        mut chunk_events: MessageReader<ChunkData>,
+        resources: BuildResources,
         mut commands: Commands,
     ) {
         for chunk_event in chunk_events.read() {
-            Self::builder_task_spawn(chunk_event.clone(), &mut commands);
+            Self::builder_task_spawn(chunk_event.clone(), resources.get(), false, &mut commands);
         }
     }
 
@@ -273,7 +566,9 @@ where
             }
 
             if let Some(task) = pending_chunk.task.as_mut() {
-                if let Some((chunk, voxel_meshes)) = future::block_on(future::poll_once(task)) {
+                if let Some((chunk, voxel_meshes, fluid_meshes, visibility)) =
+                    future::block_on(future::poll_once(task))
+                {
                     debug!(
                         "Received meshes for Chunk ({}, {})",
                         chunk.chunk_x, chunk.chunk_z
@@ -297,6 +592,8 @@ where
 
                     pending_chunk.chunk_data = Some(chunk);
                     pending_chunk.voxel_meshes = Some(voxel_meshes);
+                    pending_chunk.fluid_meshes = Some(fluid_meshes);
+                    pending_chunk.visibility = Some(visibility);
                     pending_chunk.texture_atlases = Some(texture_atlases);
                     pending_chunk.task = None;
                 }
@@ -304,9 +601,12 @@ where
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn add_built_chunks_to_world(
         atlas_layouts: Res<Assets<TextureAtlasLayout>>,
         block_textures: Res<BlockTextures>,
+        fluid_materials: Option<Res<FluidMaterials>>,
+        quality: Option<Res<MeshQuality>>,
         mut chunks_with_pending_atlases: Query<(Entity, &mut PendingChunk)>,
         mut meshes: ResMut<Assets<Mesh>>,
         mut materials: ResMut<Assets<StandardMaterial>>,
@@ -338,7 +638,21 @@ where
                         break;
                     }
                 };
-                atlas_data.push((layout, sources, pending_atlas.texture.clone()));
+                atlas_data.push((
+                    layout,
+                    sources,
+                    pending_atlas.texture.clone(),
+                    pending_atlas.emissive_texture.clone(),
+                ));
+            }
+
+            // Fluids wait for their materials.
+            let has_fluids = pending_chunk
+                .fluid_meshes
+                .as_ref()
+                .is_some_and(|fluid_meshes| fluid_meshes.iter().any(|meshes| !meshes.is_empty()));
+            if has_fluids && fluid_materials.is_none() {
+                ready = false;
             }
 
             if !ready {
@@ -355,6 +669,8 @@ where
 
             let chunk = pending_chunk.chunk_data.take().unwrap();
             let voxel_meshes = pending_chunk.voxel_meshes.take().unwrap();
+            let fluid_meshes = pending_chunk.fluid_meshes.take().unwrap_or_default();
+            let visibility = pending_chunk.visibility.take().unwrap_or_default();
 
             debug!(
                 "Received all texture atlases for Chunk ({}, {})",
@@ -364,8 +680,13 @@ where
             Self::add_built_chunk_to_world(
                 chunk,
                 voxel_meshes,
+                fluid_meshes,
+                visibility,
                 atlas_data,
                 face_textures,
+                fluid_materials.as_deref(),
+                quality.map_or(AlphaMode::Opaque, |quality| quality.alpha_mode()),
+                pending_chunk.rebuilt,
                 &mut *meshes,
                 &mut *materials,
                 &mut commands,
@@ -375,3 +696,42 @@ where
         }
     }
 }
+
+/// A section's atlas layout, sources, texture and emissive texture, if any.
+type AtlasData<'a> = (
+    &'a TextureAtlasLayout,
+    &'a TextureAtlasSources,
+    Handle<Image>,
+    Option<Handle<Image>>,
+);
+
+/// The optional resources that change what a builder task makes of a chunk.
+#[derive(SystemParam)]
+struct BuildResources<'w> {
+    fluids: Option<Res<'w, FluidStates>>,
+    waving: Option<Res<'w, WavingBlocks>>,
+    opaque: Option<Res<'w, OpaqueBlocks>>,
+    quality: Option<Res<'w, MeshQuality>>,
+    render_table: Option<Res<'w, BlockRenderTable>>,
+}
+
+impl BuildResources<'_> {
+    fn get(&self) -> BuildData {
+        BuildData {
+            fluids: self.fluids.as_deref().cloned(),
+            waving: self.waving.as_deref().cloned(),
+            opaque: self.opaque.as_deref().cloned(),
+            quality: self.quality.as_deref().copied(),
+            render_table: self.render_table.as_deref().cloned(),
+        }
+    }
+}
+
+/// Cheap copies of the [`BuildResources`] for a builder task to own.
+struct BuildData {
+    fluids: Option<FluidStates>,
+    waving: Option<WavingBlocks>,
+    opaque: Option<OpaqueBlocks>,
+    quality: Option<MeshQuality>,
+    render_table: Option<BlockRenderTable>,
+}
@@ -0,0 +1,92 @@
+//! Parts of blocks that glow.
+//!
+//! A face glows in two ways. Faces of blocks that give off light, like
+//! glowstone and fire, glow as a whole. And resource packs can give any
+//! texture an emissive overlay: a texture of the same name with `_e` added
+//! (e.g. `block/diamond_ore_e.png`) whose opaque texels glow on top of it
+//! (see `MinecraftAssets::get_emissive_overlay_path`).
+//!
+//! While a chunk is baked, each face is tagged with the light level of its
+//! block, which goes into the mesh as the [`ATTRIBUTE_EMISSION`] of its
+//! vertices. Each section's texture atlas gets an emissive atlas with the same
+//! layout, holding whatever of each texture glows, which goes into the
+//! section's material as its emissive texture.
+//!
+//! Chunks are drawn unlit, so nothing is dimmer than a glowing face yet: the
+//! emission is there for lighting and bloom to tell glowing texels apart.
+
+use bevy::{mesh::MeshVertexAttribute, prelude::*, render::render_resource::VertexFormat};
+
+use brine_chunk::Chunk;
+
+use crate::{mesh::VoxelMesh, quality::BlockRenderTable};
+
+/// How brightly each vertex glows, from 0 to 1: the light level of its
+/// block, over 15.
+pub const ATTRIBUTE_EMISSION: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_Emission", 416_118_803, VertexFormat::Float32);
+
+/// What of a texture glows, in its place in the emissive atlas.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EmissiveTexture {
+    /// The whole texture, for blocks that give off light.
+    Whole,
+
+    /// The opaque texels of an overlay as big as the texture.
+    Overlay(Handle<Image>),
+}
+
+/// Tags the faces of the meshes built for each of the chunk's sections with
+/// the light level of their block.
+pub fn tag_emission(render_table: &BlockRenderTable, chunk: &Chunk, meshes: &mut [VoxelMesh]) {
+    for (section, mesh) in chunk.sections.iter().zip(meshes.iter_mut()) {
+        for face in mesh.faces.iter_mut() {
+            let [x, y, z] = face.voxel;
+            face.emission = render_table
+                .get(section.block_states.get_block(x, y, z))
+                .light_emission;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_asset::BlockRenderProperties;
+    use brine_chunk::{BlockState, BlockStates, ChunkSection};
+
+    use crate::mesh::VoxelFace;
+
+    use super::*;
+
+    #[test]
+    fn faces_take_the_light_level_of_their_block() {
+        let glowstone = BlockState(1);
+        let render_table: BlockRenderTable = [
+            BlockRenderProperties::default(),
+            BlockRenderProperties {
+                light_emission: 15,
+                ..Default::default()
+            },
+        ]
+        .into_iter()
+        .collect();
+
+        let mut section = ChunkSection::empty(0);
+        section.block_states.0[BlockStates::xyz_to_index(1, 2, 3)] = glowstone;
+        let chunk = Chunk {
+            sections: vec![section],
+            ..Chunk::empty(0, 0)
+        };
+        let face = |voxel| VoxelFace {
+            voxel,
+            ..Default::default()
+        };
+        let mut meshes = vec![VoxelMesh {
+            faces: vec![face([1, 2, 3]), face([0, 0, 0])],
+        }];
+
+        tag_emission(&render_table, &chunk, &mut meshes);
+        assert_eq!(meshes[0].faces[0].emission, 15);
+        assert_eq!(meshes[0].faces[1].emission, 0);
+    }
+}
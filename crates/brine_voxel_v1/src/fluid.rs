@@ -0,0 +1,585 @@
+//! Meshing and drawing water and lava.
+//!
+//! Fluid blocks have no block model quads, so a [`ChunkBuilder`] would bake
+//! them as placeholder cubes. Instead, the [`ChunkBuilderPlugin`] takes them
+//! out of the chunk before handing it to its builder, and meshes them here.
+//!
+//! A fluid's surface is as high as its level says ([`Fluid::height`]), and
+//! sloped: each corner of the surface is the average height of the columns of
+//! the same fluid around it, or a full block if any of them has the same fluid
+//! above. Faces against the same fluid are left out, as are the sides and
+//! bottom against any other block. The still texture goes on the top and
+//! bottom, and the flowing texture on the sides; both are animated by the
+//! [`FluidPlugin`].
+//!
+//! Not handled yet:
+//!
+//! * waterlogged blocks, which are drawn without their water
+//! * neighbouring chunks, so surfaces don't slope across chunk borders, and
+//!   sides facing another chunk are never drawn
+//! * the direction of the flow, which vanilla shows on sloped surfaces
+//! * biome water colors, and the darker shading of the sides
+//! * the frame lists and timings in `.mcmeta` files; every frame of a texture
+//!   is shown in order, for [`FluidTexture::frame_ticks`] each
+//!
+//! [`ChunkBuilder`]: crate::ChunkBuilder
+//! [`ChunkBuilderPlugin`]: crate::ChunkBuilderPlugin
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use bevy::{math::Affine2, prelude::*};
+
+use brine_asset::MinecraftAssets;
+use brine_chunk::{BlockState, Chunk, ChunkSection, SectionPos, SECTION_WIDTH};
+use brine_data::{BlockId, MinecraftData};
+use brine_proto::BrineSystems;
+
+use crate::{
+    mesh::{Axis, VoxelFace, VoxelMesh},
+    waving::WaveClass,
+};
+
+/// Water's color in most biomes; the textures are grey and tinted with it.
+pub const WATER_COLOR: Color = Color::srgb(0.247, 0.463, 0.894);
+
+/// Length of a game tick, which texture animations are timed in.
+const TICK: Duration = Duration::from_millis(50);
+
+/// Fraction of the flowing texture's width drawn on a side face. The texture
+/// is twice as large as the others, like in vanilla.
+const FLOW_TEXTURE_SCALE: f32 = 0.5;
+
+/// The two kinds of fluid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FluidKind {
+    Water,
+    Lava,
+}
+
+impl FluidKind {
+    /// Returns the name of the fluid's block.
+    pub fn block_name(self) -> &'static str {
+        match self {
+            Self::Water => "water",
+            Self::Lava => "lava",
+        }
+    }
+
+    /// Returns the textures of the fluid when still and when flowing.
+    pub fn textures(self) -> (FluidTexture, FluidTexture) {
+        match self {
+            Self::Water => (FluidTexture::WaterStill, FluidTexture::WaterFlow),
+            Self::Lava => (FluidTexture::LavaStill, FluidTexture::LavaFlow),
+        }
+    }
+}
+
+/// A block of fluid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fluid {
+    pub kind: FluidKind,
+
+    /// The `level` block state: 0 for a source, 1 to 7 as the fluid spreads
+    /// out and gets lower, and 8 or more for fluid falling from above.
+    pub level: u8,
+}
+
+impl Fluid {
+    /// Returns the height of the fluid's surface above the bottom of its
+    /// block, when there isn't the same fluid above it.
+    pub fn height(self) -> f32 {
+        if self.level == 0 || self.level >= 8 {
+            8.0 / 9.0
+        } else {
+            f32::from(8 - self.level) / 9.0
+        }
+    }
+}
+
+/// The textures drawn on fluids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FluidTexture {
+    WaterStill,
+    WaterFlow,
+    LavaStill,
+    LavaFlow,
+}
+
+impl FluidTexture {
+    pub const ALL: [Self; 4] = [
+        Self::WaterStill,
+        Self::WaterFlow,
+        Self::LavaStill,
+        Self::LavaFlow,
+    ];
+
+    /// Returns the name of the texture, as in the block models.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::WaterStill => "block/water_still",
+            Self::WaterFlow => "block/water_flow",
+            Self::LavaStill => "block/lava_still",
+            Self::LavaFlow => "block/lava_flow",
+        }
+    }
+
+    /// Returns how many game ticks each frame of the animation is shown.
+    pub fn frame_ticks(self) -> u32 {
+        match self {
+            Self::LavaFlow => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// The faces of one section's fluids that have the same texture.
+#[derive(Debug, Clone)]
+pub struct FluidMesh {
+    pub texture: FluidTexture,
+    pub mesh: VoxelMesh,
+}
+
+/// Which block states are fluids, and how high.
+///
+/// Holds every state of water and lava, with its fluid and level. A chunk's
+/// builder task uses it off the main thread to take the fluids out of the
+/// chunk before the solid blocks are meshed, then to mesh them on their own,
+/// so each task owns a clone; the map behind it is shared, not copied.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct FluidStates {
+    states: Arc<HashMap<BlockState, Fluid>>,
+}
+
+impl FromIterator<(BlockState, Fluid)> for FluidStates {
+    fn from_iter<I: IntoIterator<Item = (BlockState, Fluid)>>(iter: I) -> Self {
+        Self {
+            states: Arc::new(iter.into_iter().collect()),
+        }
+    }
+}
+
+impl FluidStates {
+    /// Finds the states of the water and lava blocks.
+    pub fn from_data(mc_data: &MinecraftData) -> Self {
+        let blocks = mc_data.blocks();
+        [FluidKind::Water, FluidKind::Lava]
+            .into_iter()
+            .filter_map(|kind| {
+                let block = blocks.get_by_name(kind.block_name())?;
+                let states = blocks.iter_states_for_block(BlockId(block.id))?;
+                Some(states.map(move |(state_id, block)| {
+                    let level = block
+                        .state
+                        .get("level")
+                        .and_then(|level| level.as_int())
+                        .unwrap_or(0);
+                    let fluid = Fluid {
+                        kind,
+                        level: level as u8,
+                    };
+                    (BlockState(u32::from(state_id.0)), fluid)
+                }))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the fluid of a block state, if it is one.
+    #[inline]
+    pub fn get(&self, block_state: BlockState) -> Option<Fluid> {
+        self.states.get(&block_state).copied()
+    }
+
+    /// Returns a copy of `chunk` with air in place of its fluids, for the
+    /// chunk builders.
+    pub fn without_fluids(&self, chunk: &Chunk) -> Chunk {
+        let mut chunk = chunk.clone();
+        for section in chunk.sections.iter_mut() {
+            for block_state in section.block_states.0.iter_mut() {
+                if self.states.contains_key(block_state) {
+                    *block_state = BlockState::AIR;
+                    section.block_count = section.block_count.saturating_sub(1);
+                }
+            }
+        }
+        chunk
+    }
+
+    /// Meshes the fluids of each of the chunk's sections, returning one list
+    /// of meshes (one per texture) per section.
+    pub fn build_chunk(&self, chunk: &Chunk) -> Vec<Vec<FluidMesh>> {
+        chunk
+            .sections
+            .iter()
+            .map(|section| self.build_chunk_section(chunk, section))
+            .collect()
+    }
+
+    fn build_chunk_section(&self, chunk: &Chunk, section: &ChunkSection) -> Vec<FluidMesh> {
+        let mut meshes: Vec<FluidMesh> = Vec::new();
+
+        // The block at a position relative to the section; `None` outside of
+        // the chunk horizontally. Missing sections are air.
+        let block = |x: i32, y: i32, z: i32| -> Option<BlockState> {
+            let width = SECTION_WIDTH as i32;
+            if !(0..width).contains(&x) || !(0..width).contains(&z) {
+                return None;
+            }
+            let position =
+                SectionPos::new(0, section.chunk_y.into(), 0).origin() + IVec3::new(x, y, z);
+            Some(chunk.get_block(position.chunk_relative()))
+        };
+        let fluid = |x, y, z| block(x, y, z).and_then(|block_state| self.get(block_state));
+
+        for (x, y, z, block_state) in section.block_states.iter() {
+            let Some(this) = self.get(block_state) else {
+                continue;
+            };
+            let (x, y, z) = (x as i32, y as i32, z as i32);
+
+            let same_fluid = |x, y, z| fluid(x, y, z).is_some_and(|other| other.kind == this.kind);
+            let hidden_by = |axis: Axis| {
+                let [dx, dy, dz] = axis.normal().map(i32::from);
+                let (x, y, z) = (x + dx, y + dy, z + dz);
+                match (block(x, y, z), axis) {
+                    // The surface is lower than the block above, so it shows
+                    // unless the fluid goes on up.
+                    (_, Axis::YPos) => same_fluid(x, y, z),
+                    (None, _) => true,
+                    (Some(block_state), _) => block_state != BlockState::AIR,
+                }
+            };
+
+            // Heights of the surface's corners, by their X and Z offsets.
+            let corner_heights = if same_fluid(x, y + 1, z) {
+                [[1.0; 2]; 2]
+            } else {
+                [0, 1].map(|dx| {
+                    [0, 1].map(|dz| {
+                        corner_height(x + dx, z + dz, |x, z| {
+                            let fluid = fluid(x, y, z).filter(|fluid| fluid.kind == this.kind)?;
+                            Some(if same_fluid(x, y + 1, z) {
+                                1.0
+                            } else {
+                                fluid.height()
+                            })
+                        })
+                    })
+                })
+            };
+
+            let (still, flow) = this.kind.textures();
+            for axis in AXES {
+                if hidden_by(axis) {
+                    continue;
+                }
+                let texture = match axis {
+                    Axis::YPos | Axis::YNeg => still,
+                    _ => flow,
+                };
+                let mut face = fluid_face([x as u8, y as u8, z as u8], axis, corner_heights);
+                if this.kind == FluidKind::Water {
+                    face.wave = WaveClass::Water;
+                }
+                match meshes.iter_mut().find(|mesh| mesh.texture == texture) {
+                    Some(mesh) => mesh.mesh.faces.push(face),
+                    None => meshes.push(FluidMesh {
+                        texture,
+                        mesh: VoxelMesh { faces: vec![face] },
+                    }),
+                }
+            }
+        }
+
+        meshes
+    }
+}
+
+const AXES: [Axis; 6] = [
+    Axis::XPos,
+    Axis::XNeg,
+    Axis::YPos,
+    Axis::YNeg,
+    Axis::ZPos,
+    Axis::ZNeg,
+];
+
+/// Returns the height of the surface at the corner between the four columns
+/// around (`x`, `z`), given each column's height if it has the same fluid.
+fn corner_height(x: i32, z: i32, column_height: impl Fn(i32, i32) -> Option<f32>) -> f32 {
+    let heights = [(x - 1, z - 1), (x, z - 1), (x - 1, z), (x, z)]
+        .map(|(x, z)| column_height(x, z))
+        .into_iter()
+        .flatten();
+
+    let mut total = 0.0;
+    let mut count = 0;
+    for height in heights {
+        if height >= 1.0 {
+            return 1.0;
+        }
+        total += height;
+        count += 1;
+    }
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+/// Builds one face of the fluid at `voxel`, whose surface has the given
+/// corner heights.
+fn fluid_face(voxel: [u8; 3], axis: Axis, corner_heights: [[f32; 2]; 2]) -> VoxelFace {
+    let [x, y, z] = voxel.map(f32::from);
+    let corner = |dx: usize, dy: f32, dz: usize| [x + dx as f32, y + dy, z + dz as f32];
+    let height = |dx: usize, dz: usize| corner_heights[dx][dz];
+
+    // Vertices 0 and 1 are one edge of the face, and 2 and 3 the other.
+    let (positions, tex_coords) = match axis {
+        Axis::YPos | Axis::YNeg => {
+            let top = axis == Axis::YPos;
+            let dy = |dx, dz| if top { height(dx, dz) } else { 0.0 };
+            (
+                [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dz)| corner(dx, dy(dx, dz), dz)),
+                [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]],
+            )
+        }
+        _ => {
+            let ((ax, az), (bx, bz)) = match axis {
+                Axis::XPos => ((1, 0), (1, 1)),
+                Axis::XNeg => ((0, 0), (0, 1)),
+                Axis::ZPos => ((0, 1), (1, 1)),
+                _ => ((0, 0), (1, 0)),
+            };
+            let (a, b) = (height(ax, az), height(bx, bz));
+            let v = |height: f32| (1.0 - height) * FLOW_TEXTURE_SCALE;
+            (
+                [
+                    corner(ax, 0.0, az),
+                    corner(bx, 0.0, bz),
+                    corner(ax, a, az),
+                    corner(bx, b, bz),
+                ],
+                [
+                    [0.0, FLOW_TEXTURE_SCALE],
+                    [FLOW_TEXTURE_SCALE, FLOW_TEXTURE_SCALE],
+                    [0.0, v(a)],
+                    [FLOW_TEXTURE_SCALE, v(b)],
+                ],
+            )
+        }
+    };
+
+    VoxelFace {
+        voxel,
+        axis,
+        positions,
+        tex_coords,
+        // Fluids are drawn from both sides, so the winding doesn't matter.
+        indices: [0, 1, 2, 1, 3, 2],
+        ..Default::default()
+    }
+}
+
+/// The materials of the fluid textures, animated by the [`FluidPlugin`].
+#[derive(Resource)]
+pub struct FluidMaterials {
+    textures: HashMap<FluidTexture, (Handle<Image>, Handle<StandardMaterial>)>,
+}
+
+impl FluidMaterials {
+    /// Returns the material for a fluid texture.
+    pub fn get(&self, texture: FluidTexture) -> Option<Handle<StandardMaterial>> {
+        self.textures
+            .get(&texture)
+            .map(|(_, material)| material.clone())
+    }
+}
+
+/// Plugin that finds the [`FluidStates`] in the Minecraft data and makes the
+/// [`FluidMaterials`] for the [`ChunkBuilderPlugin`][crate::ChunkBuilderPlugin]
+/// to mesh and draw fluids with. Without it, fluids are meshed like any other
+/// block.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`FluidStates`], once the `MinecraftData` resource exists
+/// * [`FluidMaterials`], once the `MinecraftAssets` resource exists
+///
+/// # Ordering
+///
+/// The textures are animated in [`BrineSystems::MeshBuild`].
+pub struct FluidPlugin;
+
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                insert_fluid_states.run_if(resource_added::<MinecraftData>),
+                load_fluid_materials.run_if(resource_added::<MinecraftAssets>),
+                animate_fluid_textures.run_if(resource_exists::<FluidMaterials>),
+            )
+                .chain()
+                .in_set(BrineSystems::MeshBuild),
+        );
+    }
+}
+
+fn insert_fluid_states(mc_data: Res<MinecraftData>, mut commands: Commands) {
+    commands.insert_resource(FluidStates::from_data(&mc_data));
+}
+
+fn load_fluid_materials(
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let textures = FluidTexture::ALL
+        .into_iter()
+        .map(|texture| {
+            let image: Handle<Image> =
+                asset_server.load(mc_assets.get_any_texture_path(texture.name()));
+            let (base_color, alpha_mode) = match texture {
+                FluidTexture::WaterStill | FluidTexture::WaterFlow => {
+                    (WATER_COLOR, AlphaMode::Blend)
+                }
+                FluidTexture::LavaStill | FluidTexture::LavaFlow => {
+                    (Color::WHITE, AlphaMode::Opaque)
+                }
+            };
+            let material = materials.add(StandardMaterial {
+                base_color,
+                base_color_texture: Some(image.clone()),
+                alpha_mode,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            });
+            (texture, (image, material))
+        })
+        .collect();
+
+    commands.insert_resource(FluidMaterials { textures });
+}
+
+/// Shows the current frame of each texture, which are stacked vertically in
+/// the image.
+fn animate_fluid_textures(
+    time: Res<Time>,
+    fluid_materials: Res<FluidMaterials>,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let ticks = (time.elapsed().as_millis() / TICK.as_millis()) as u32;
+
+    for (texture, (image, material)) in fluid_materials.textures.iter() {
+        let Some(image) = images.get(image) else {
+            continue;
+        };
+        let size = image.size();
+        let frames = (size.y / size.x.max(1)).max(1);
+        let frame = (ticks / texture.frame_ticks()) % frames;
+
+        let uv_transform = Affine2::from_scale_angle_translation(
+            Vec2::new(1.0, 1.0 / frames as f32),
+            0.0,
+            Vec2::new(0.0, frame as f32 / frames as f32),
+        );
+
+        // Only touch the material when the frame changes, since that makes
+        // it be prepared again.
+        let changed = materials
+            .get(material)
+            .is_some_and(|material| material.uv_transform != uv_transform);
+        if changed {
+            if let Some(material) = materials.get_mut(material) {
+                material.uv_transform = uv_transform;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: BlockState = BlockState(1);
+    const FLOWING: BlockState = BlockState(2);
+
+    fn fluids() -> FluidStates {
+        [(SOURCE, 0), (FLOWING, 4)]
+            .into_iter()
+            .map(|(block_state, level)| {
+                let fluid = Fluid {
+                    kind: FluidKind::Water,
+                    level,
+                };
+                (block_state, fluid)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn levels_give_heights() {
+        let height = |level| {
+            Fluid {
+                kind: FluidKind::Lava,
+                level,
+            }
+            .height()
+        };
+        assert_eq!(height(0), 8.0 / 9.0);
+        assert_eq!(height(1), 7.0 / 9.0);
+        assert_eq!(height(7), 1.0 / 9.0);
+        assert_eq!(height(8), 8.0 / 9.0);
+        assert_eq!(height(15), 8.0 / 9.0);
+    }
+
+    #[test]
+    fn surfaces_slope_between_levels() {
+        let mut section = ChunkSection::empty(0);
+        section.block_states.0[brine_chunk::BlockStates::xyz_to_index(0, 0, 0)] = SOURCE;
+        section.block_states.0[brine_chunk::BlockStates::xyz_to_index(1, 0, 0)] = FLOWING;
+        section.block_count = 2;
+        let chunk = Chunk {
+            sections: vec![section],
+            ..Chunk::empty(0, 0)
+        };
+
+        let fluids = fluids();
+        assert_eq!(fluids.without_fluids(&chunk).sections[0].block_count, 0);
+
+        let meshes = fluids.build_chunk(&chunk);
+        assert_eq!(meshes.len(), 1);
+        let faces = |texture| {
+            meshes[0]
+                .iter()
+                .find(|mesh| mesh.texture == texture)
+                .map_or(0, |mesh| mesh.mesh.faces.len())
+        };
+        // Tops and bottoms, then the sides facing air inside the chunk.
+        assert_eq!(faces(FluidTexture::WaterStill), 4);
+        assert_eq!(faces(FluidTexture::WaterFlow), 3);
+
+        let still = meshes[0]
+            .iter()
+            .find(|mesh| mesh.texture == FluidTexture::WaterStill)
+            .unwrap();
+        let top = still
+            .mesh
+            .faces
+            .iter()
+            .find(|face| face.voxel == [1, 0, 0] && face.axis == Axis::YPos)
+            .unwrap();
+        let source = 8.0 / 9.0;
+        let between = (source + 4.0 / 9.0) / 2.0;
+        let heights = top.positions.map(|[_, y, _]| y);
+        assert_eq!(heights, [between, 4.0 / 9.0, between, 4.0 / 9.0]);
+    }
+}
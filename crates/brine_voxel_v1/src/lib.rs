@@ -2,17 +2,29 @@
 //!
 //! Currently all that is implemented is two different [chunk builders]
 //! (["visible faces"] and ["naive blocks"]) that generate meshes from chunk
-//! data. The former is implemented using the [`block-mesh`] crate.
+//! data. The former is implemented using the [`block-mesh`] crate. Water and
+//! lava are meshed on their own (see [`fluid`]), and leaves, plants and water
+//! can sway (see [`waving`]). Sections hidden behind solid rock aren't drawn
+//! (see [`occlusion`]), faces can be shaded with ambient occlusion (see
+//! [`quality`]), and glowing faces are told apart (see [`emissive`]). New
+//! chunks can rise or fade into view rather than pop in (see [`appearance`]).
 //!
 //! [chunk builders]: ChunkBuilder
 //! ["visible faces"]: VisibleFacesChunkBuilder
 //! ["naive blocks"]: NaiveBlocksChunkBuilder
 //! [`block-mesh`]: <https://github.com/bonsairobo/block-mesh-rs>
 
+pub mod appearance;
 pub mod chunk_builder;
+pub mod emissive;
+pub mod fluid;
 pub mod mesh;
+pub mod occlusion;
+pub mod quality;
 pub mod texture;
+pub mod waving;
 
 pub use chunk_builder::{
-    ChunkBuilder, ChunkBuilderPlugin, NaiveBlocksChunkBuilder, VisibleFacesChunkBuilder,
+    ActiveChunkBuilder, ChunkBuilder, ChunkBuilderPlugin, NaiveBlocksChunkBuilder,
+    VisibleFacesChunkBuilder,
 };
@@ -8,6 +8,12 @@ use bevy_image::{TextureAtlasLayout, TextureAtlasSources};
 use bevy_mesh::Indices;
 use brine_asset::BlockFace;
 
+use crate::{emissive::ATTRIBUTE_EMISSION, waving::WaveClass};
+
+/// How bright a vertex is with each number of blocks occluding it, for
+/// ambient occlusion.
+const OCCLUSION_BRIGHTNESS: [f32; 4] = [1.0, 0.8, 0.65, 0.5];
+
 /// The six sides of a voxel.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u8)]
@@ -80,6 +86,18 @@ pub struct VoxelFace {
     /// These describe how to draw the face using two triangles.
     /// Each entry is an index into the `positions` array.
     pub indices: [u8; 6],
+
+    /// How the face moves when drawn with a
+    /// [`WavingMaterial`][crate::waving::WavingMaterial].
+    pub wave: WaveClass,
+
+    /// How many of the blocks around each vertex darken it, from 0 to 3. See
+    /// [`quality`][crate::quality].
+    pub occlusion: [u8; 4],
+
+    /// Light level the face glows with, from 0 to 15. See
+    /// [`emissive`][crate::emissive].
+    pub emission: u8,
 }
 
 impl VoxelMesh {
@@ -109,9 +127,58 @@ impl VoxelMesh {
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
         mesh.insert_indices(Indices::U32(indices));
 
+        // The wave class and weight of each vertex, for the waving shader.
+        if self.waves() {
+            let waves: Vec<[f32; 2]> = self
+                .faces
+                .iter()
+                .flat_map(|face| {
+                    let bottom = f32::from(face.voxel[1]);
+                    let class = f32::from(face.wave as u8);
+                    face.positions
+                        .map(|[_, y, _]| [class, face.wave.weight(y - bottom)])
+                })
+                .collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, waves);
+        }
+
+        // Vertex colors darken the texture where blocks occlude it.
+        if self.faces.iter().any(|face| face.occlusion != [0; 4]) {
+            let colors: Vec<[f32; 4]> = self
+                .faces
+                .iter()
+                .flat_map(|face| {
+                    face.occlusion.map(|occluders| {
+                        let brightness = OCCLUSION_BRIGHTNESS[usize::from(occluders.min(3))];
+                        [brightness, brightness, brightness, 1.0]
+                    })
+                })
+                .collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+
+        if self.glows() {
+            let emission: Vec<f32> = self
+                .faces
+                .iter()
+                .flat_map(|face| [f32::from(face.emission) / 15.0; 4])
+                .collect();
+            mesh.insert_attribute(ATTRIBUTE_EMISSION, emission);
+        }
+
         mesh
     }
 
+    /// Returns whether any of the faces glow.
+    pub fn glows(&self) -> bool {
+        self.faces.iter().any(|face| face.emission > 0)
+    }
+
+    /// Returns whether any of the faces wave.
+    pub fn waves(&self) -> bool {
+        self.faces.iter().any(|face| face.wave != WaveClass::None)
+    }
+
     pub fn adjust_tex_coords(
         &mut self,
         layout: &TextureAtlasLayout,
@@ -0,0 +1,406 @@
+//! Hiding chunk sections that the camera can't see into, like vanilla's cave
+//! culling.
+//!
+//! While a chunk is meshed, each of its sections gets a [`SectionVisibility`]:
+//! which of its six faces can be seen from which others through the section,
+//! found by flood filling the blocks that aren't opaque (see
+//! [`OpaqueBlocks`]). The [`OcclusionCullingPlugin`] then searches outwards
+//! from the camera's section, entering a neighbour only if the way into the
+//! current section connects to the face towards it, and never turning back
+//! towards the camera. Sections the search doesn't reach are hidden. Most of
+//! the caves and solid rock under the player are never drawn this way.
+//!
+//! Which faces of the sections are actually in view is left to Bevy's frustum
+//! culling.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
+
+use bevy::prelude::*;
+
+use brine_chunk::{
+    BlockState, ChunkSection, BLOCKS_PER_SECTION, SECTIONS_PER_CHUNK, SECTION_WIDTH, SECTION_Y_BASE,
+};
+use brine_data::MinecraftData;
+use brine_proto::BrineSystems;
+
+use crate::{
+    chunk_builder::component::{BuiltChunk, BuiltChunkSection},
+    mesh::Axis,
+};
+
+const AXES: [Axis; 6] = [
+    Axis::XPos,
+    Axis::XNeg,
+    Axis::YPos,
+    Axis::YNeg,
+    Axis::ZPos,
+    Axis::ZNeg,
+];
+
+/// Sections with fewer opaque blocks than this can be seen through from every
+/// face, as they can't wall anything off.
+const MIN_OPAQUE_TO_OCCLUDE: usize = SECTION_WIDTH * SECTION_WIDTH;
+
+/// Which block states can't be seen through.
+///
+/// A flag per state ID, quick enough to check for every block when a chunk's
+/// builder task works out each section's [`SectionVisibility`] (and when it
+/// adds ambient occlusion and fancy leaves). The task can't reach resources,
+/// so it is handed a clone of the table, which shares the flags.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct OpaqueBlocks {
+    /// Whether each block state is opaque, by state ID.
+    states: Arc<Vec<bool>>,
+}
+
+impl FromIterator<BlockState> for OpaqueBlocks {
+    fn from_iter<I: IntoIterator<Item = BlockState>>(iter: I) -> Self {
+        let mut states = Vec::new();
+        for block_state in iter {
+            let index = block_state.0 as usize;
+            if states.len() <= index {
+                states.resize(index + 1, false);
+            }
+            states[index] = true;
+        }
+        Self {
+            states: Arc::new(states),
+        }
+    }
+}
+
+impl OpaqueBlocks {
+    /// Finds the states of the blocks that are neither air nor transparent.
+    pub fn from_data(mc_data: &MinecraftData) -> Self {
+        mc_data
+            .blocks()
+            .iter_states()
+            .enumerate()
+            .filter(|(_, block)| !block.is_air() && !block.transparent)
+            .map(|(state_id, _)| BlockState(state_id as u32))
+            .collect()
+    }
+
+    /// Returns whether a block state is opaque.
+    #[inline]
+    pub fn is_opaque(&self, block_state: BlockState) -> bool {
+        self.states
+            .get(block_state.0 as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Works out which faces of `section` can be seen from which others.
+    pub fn section_visibility(&self, section: &ChunkSection) -> SectionVisibility {
+        let opaque: Vec<bool> = section
+            .block_states
+            .0
+            .iter()
+            .map(|block_state| self.is_opaque(*block_state))
+            .collect();
+        SectionVisibility::flood_fill(&opaque)
+    }
+}
+
+/// Which faces of a chunk section can be seen from which others, through
+/// blocks that aren't opaque.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionVisibility(u64);
+
+impl Default for SectionVisibility {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl SectionVisibility {
+    /// Every face can be seen from every other, as in an empty section.
+    pub const ALL: Self = Self((1 << 36) - 1);
+
+    /// No face can be seen from any other, as in a solid section.
+    pub const NONE: Self = Self(0);
+
+    /// Returns whether face `to` can be seen from face `from`.
+    #[inline]
+    pub fn connects(self, from: Axis, to: Axis) -> bool {
+        self.0 & Self::bit(from, to) != 0
+    }
+
+    fn connect(&mut self, from: Axis, to: Axis) {
+        self.0 |= Self::bit(from, to) | Self::bit(to, from);
+    }
+
+    fn bit(from: Axis, to: Axis) -> u64 {
+        1 << (from as u64 * 6 + to as u64)
+    }
+
+    /// Flood fills each region of cells that aren't `opaque` (in block state
+    /// order), connecting every pair of faces the region touches.
+    fn flood_fill(opaque: &[bool]) -> Self {
+        if opaque.iter().filter(|opaque| **opaque).count() < MIN_OPAQUE_TO_OCCLUDE {
+            return Self::ALL;
+        }
+
+        let mut visibility = Self::NONE;
+        let mut filled = opaque.to_vec();
+        let mut stack = Vec::new();
+
+        for start in 0..BLOCKS_PER_SECTION {
+            if filled[start] {
+                continue;
+            }
+            filled[start] = true;
+            stack.push(start);
+
+            let mut faces = Vec::with_capacity(AXES.len());
+            while let Some(index) = stack.pop() {
+                let (x, y, z) = brine_chunk::BlockStates::index_to_xyz(index);
+                let cell = IVec3::new(x as i32, y as i32, z as i32);
+                for axis in AXES {
+                    let next = cell + normal(axis);
+                    let inside = next.cmpge(IVec3::ZERO).all()
+                        && next.cmplt(IVec3::splat(SECTION_WIDTH as i32)).all();
+                    if !inside {
+                        if !faces.contains(&axis) {
+                            faces.push(axis);
+                        }
+                        continue;
+                    }
+                    let next = brine_chunk::BlockStates::xyz_to_index(
+                        next.x as u8,
+                        next.y as u8,
+                        next.z as u8,
+                    );
+                    if !filled[next] {
+                        filled[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+
+            for &from in faces.iter() {
+                for &to in faces.iter() {
+                    visibility.connect(from, to);
+                }
+            }
+        }
+
+        visibility
+    }
+}
+
+fn normal(axis: Axis) -> IVec3 {
+    IVec3::from_array(axis.normal().map(i32::from))
+}
+
+fn opposite(axis: Axis) -> Axis {
+    match axis {
+        Axis::XPos => Axis::XNeg,
+        Axis::XNeg => Axis::XPos,
+        Axis::YPos => Axis::YNeg,
+        Axis::YNeg => Axis::YPos,
+        Axis::ZPos => Axis::ZNeg,
+        Axis::ZNeg => Axis::ZPos,
+    }
+}
+
+/// Returns the sections that can be seen from section `start`, given the
+/// visibility of each loaded section (`None` for sections that aren't).
+pub fn visible_sections(
+    start: IVec3,
+    visibility: impl Fn(IVec3) -> Option<SectionVisibility>,
+) -> HashSet<IVec3> {
+    let mut visible = HashSet::from([start]);
+
+    // Each section is searched with the face it was entered through, and the
+    // directions travelled to get there, none of which are turned back on.
+    let mut queue = VecDeque::from([(start, None, 0u8)]);
+    while let Some((section, entered, travelled)) = queue.pop_front() {
+        let Some(section_visibility) = visibility(section) else {
+            continue;
+        };
+        for axis in AXES {
+            let back = opposite(axis);
+            if travelled & (1 << back as u8) != 0 {
+                continue;
+            }
+            if entered.is_some_and(|entered| !section_visibility.connects(entered, axis)) {
+                continue;
+            }
+            let next = section + normal(axis);
+            if visibility(next).is_none() || !visible.insert(next) {
+                continue;
+            }
+            queue.push_back((next, Some(back), travelled | (1 << axis as u8)));
+        }
+    }
+
+    visible
+}
+
+/// Plugin that finds the [`OpaqueBlocks`] for the
+/// [`ChunkBuilderPlugin`][crate::ChunkBuilderPlugin] to give sections their
+/// [`SectionVisibility`], and hides the sections that can't be seen from the
+/// camera.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`OpaqueBlocks`], once the `MinecraftData` resource exists
+///
+/// # Ordering
+///
+/// Sections are hidden after [`BrineSystems::MeshBuild`], once the frame's
+/// new sections are in.
+pub struct OcclusionCullingPlugin;
+
+impl Plugin for OcclusionCullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                insert_opaque_blocks
+                    .run_if(resource_added::<MinecraftData>)
+                    .in_set(BrineSystems::MeshBuild),
+                cull_occluded_sections.after(BrineSystems::MeshBuild),
+            ),
+        );
+    }
+}
+
+fn insert_opaque_blocks(mc_data: Res<MinecraftData>, mut commands: Commands) {
+    commands.insert_resource(OpaqueBlocks::from_data(&mc_data));
+}
+
+/// Hides the sections that can't be seen from the camera's section, whenever
+/// the camera moves to another section or sections come and go. The others
+/// inherit their chunk's visibility, so distant chunks stay hidden.
+fn cull_occluded_sections(
+    mut last_start: Local<Option<IVec3>>,
+    cameras: Query<&Transform, With<Camera3d>>,
+    chunks: Query<&BuiltChunk>,
+    mut sections: Query<(
+        &ChildOf,
+        &BuiltChunkSection,
+        &SectionVisibility,
+        &mut Visibility,
+    )>,
+    added: Query<(), Added<SectionVisibility>>,
+    mut removed: RemovedComponents<SectionVisibility>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let section_size = SECTION_WIDTH as f32;
+    let start = (camera.translation / section_size).floor().as_ivec3();
+    let sections_changed = !added.is_empty() || removed.read().count() > 0;
+    if *last_start == Some(start) && !sections_changed {
+        return;
+    }
+    *last_start = Some(start);
+
+    let columns: HashSet<IVec2> = chunks
+        .iter()
+        .map(|chunk| IVec2::new(chunk.chunk_x, chunk.chunk_z))
+        .collect();
+    let mut positions = HashMap::new();
+    for (parent, section, section_visibility, _) in sections.iter() {
+        if let Ok(chunk) = chunks.get(parent.parent()) {
+            let position = IVec3::new(chunk.chunk_x, section.section_y as i32, chunk.chunk_z);
+            positions.insert(position, *section_visibility);
+        }
+    }
+
+    let min_y = SECTION_Y_BASE as i32;
+    let max_y = min_y + SECTIONS_PER_CHUNK as i32 - 1;
+    let visibility = |position: IVec3| {
+        let loaded = columns.contains(&position.xz()) && (min_y..=max_y).contains(&position.y);
+        // Sections that were left out of their chunk are empty.
+        loaded.then(|| positions.get(&position).copied().unwrap_or_default())
+    };
+
+    // From outside of the loaded world, there's nothing to search from.
+    let visible = visibility(start)
+        .is_some()
+        .then(|| visible_sections(start, visibility));
+
+    for (parent, section, _, mut section_visibility) in sections.iter_mut() {
+        let Ok(chunk) = chunks.get(parent.parent()) else {
+            continue;
+        };
+        let position = IVec3::new(chunk.chunk_x, section.section_y as i32, chunk.chunk_z);
+        let shown = visible
+            .as_ref()
+            .is_none_or(|visible| visible.contains(&position));
+        let wanted = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        section_visibility.set_if_neq(wanted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_chunk::BlockStates;
+
+    use super::*;
+
+    /// A section with a floor of opaque blocks at `y`.
+    fn floor_at(y: u8) -> SectionVisibility {
+        let mut opaque = vec![false; BLOCKS_PER_SECTION];
+        for x in 0..16 {
+            for z in 0..16 {
+                opaque[BlockStates::xyz_to_index(x, y, z)] = true;
+            }
+        }
+        SectionVisibility::flood_fill(&opaque)
+    }
+
+    #[test]
+    fn floors_part_the_top_from_the_bottom() {
+        let visibility = floor_at(8);
+        assert!(!visibility.connects(Axis::YPos, Axis::YNeg));
+        assert!(visibility.connects(Axis::YPos, Axis::XNeg));
+        assert!(visibility.connects(Axis::YNeg, Axis::ZPos));
+        assert!(visibility.connects(Axis::XPos, Axis::XNeg));
+
+        // On the bottom layer, the floor covers the whole bottom face.
+        let visibility = floor_at(0);
+        assert!(!visibility.connects(Axis::YNeg, Axis::YNeg));
+        assert!(visibility.connects(Axis::YPos, Axis::XPos));
+
+        assert_eq!(
+            SectionVisibility::flood_fill(&[false; BLOCKS_PER_SECTION]),
+            SectionVisibility::ALL
+        );
+    }
+
+    #[test]
+    fn search_stops_at_floors() {
+        let floor = floor_at(8);
+        let column = |position: IVec3| {
+            (position.xz() == IVec2::ZERO && (0..4).contains(&position.y)).then_some(
+                if position.y == 2 {
+                    floor
+                } else {
+                    SectionVisibility::ALL
+                },
+            )
+        };
+
+        let visible = visible_sections(IVec3::ZERO, column);
+        let expected = HashSet::from([IVec3::ZERO, IVec3::Y, IVec3::new(0, 2, 0)]);
+        assert_eq!(visible, expected);
+
+        let visible = visible_sections(IVec3::new(0, 3, 0), column);
+        let expected = HashSet::from([IVec3::new(0, 3, 0), IVec3::new(0, 2, 0)]);
+        assert_eq!(visible, expected);
+    }
+}
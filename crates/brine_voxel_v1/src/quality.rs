@@ -0,0 +1,407 @@
+//! Chunk mesh extras that trade meshing time for looks: ambient occlusion and
+//! fancy leaves.
+//!
+//! With ambient occlusion, each vertex of a block face is darkened by the
+//! opaque blocks in front of the face around it, as in vanilla's smooth
+//! lighting (without light levels). Greedy quads are merged whatever their
+//! occlusion, so it is only exact with the visible faces builder.
+//!
+//! Fast leaves are drawn solid, like every other block. Fancy leaves (and
+//! glass, and everything else that can be seen through) have their
+//! transparent texels cut out, so the faces the chunk builders cull against
+//! them are put back.
+//!
+//! Both need the [`OpaqueBlocks`] (see
+//! [`OcclusionCullingPlugin`][crate::occlusion::OcclusionCullingPlugin]), and
+//! respect the [`BlockRenderTable`] once the assets are loaded: blocks whose
+//! models turn ambient occlusion off or that give off light aren't darkened,
+//! and faces between two of the same translucent block stay culled.
+
+use std::{collections::HashSet, sync::Arc};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use brine_asset::{BlockRenderProperties, MinecraftAssets};
+use brine_chunk::{BlockState, Chunk, ChunkSection, SECTION_HEIGHT, SECTION_WIDTH};
+use brine_data::{blocks::RenderLayer, BlockStateId, MinecraftData};
+use brine_proto::BrineSystems;
+
+use crate::{
+    chunk_builder::{
+        component::{BuiltChunk, RemeshChunk},
+        unit_face,
+    },
+    mesh::{Axis, VoxelFace, VoxelMesh},
+    occlusion::OpaqueBlocks,
+};
+
+const AXES: [Axis; 6] = [
+    Axis::XPos,
+    Axis::XNeg,
+    Axis::YPos,
+    Axis::YNeg,
+    Axis::ZPos,
+    Axis::ZNeg,
+];
+
+const SECTION_SIZE: IVec3 = IVec3::new(
+    SECTION_WIDTH as i32,
+    SECTION_HEIGHT as i32,
+    SECTION_WIDTH as i32,
+);
+
+/// How leaves and other blocks that can be seen through are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeavesQuality {
+    /// Solid, with the faces between them culled.
+    Fast,
+
+    /// With their transparent texels cut out.
+    #[default]
+    Fancy,
+}
+
+/// Which extras the [`ChunkBuilderPlugin`][crate::ChunkBuilderPlugin] meshes
+/// chunks with.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MeshQuality {
+    pub ambient_occlusion: bool,
+    pub leaves: LeavesQuality,
+}
+
+impl Default for MeshQuality {
+    fn default() -> Self {
+        Self {
+            ambient_occlusion: true,
+            leaves: LeavesQuality::Fancy,
+        }
+    }
+}
+
+impl MeshQuality {
+    /// The alpha mode of the chunk section materials.
+    pub fn alpha_mode(&self) -> AlphaMode {
+        match self.leaves {
+            LeavesQuality::Fast => AlphaMode::Opaque,
+            LeavesQuality::Fancy => AlphaMode::Mask(0.5),
+        }
+    }
+}
+
+/// How each block state is drawn, by state ID (see
+/// [`MinecraftAssets::block_render_properties`]).
+///
+/// Working these out goes through each state's baked models, so it's done
+/// once for every state when the assets load rather than per face. Builder
+/// tasks read it for glowing faces, cutouts and ambient occlusion; each gets
+/// a clone that points at the same table.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct BlockRenderTable {
+    states: Arc<Vec<BlockRenderProperties>>,
+}
+
+impl FromIterator<BlockRenderProperties> for BlockRenderTable {
+    fn from_iter<I: IntoIterator<Item = BlockRenderProperties>>(iter: I) -> Self {
+        Self {
+            states: Arc::new(iter.into_iter().collect()),
+        }
+    }
+}
+
+impl BlockRenderTable {
+    /// Looks up how every block state is drawn.
+    pub fn from_assets(mc_data: &MinecraftData, mc_assets: &MinecraftAssets) -> Self {
+        (0..mc_data.blocks().state_id_to_block.len())
+            .map(|state_id| {
+                mc_assets
+                    .block_render_properties(mc_data, BlockStateId(state_id as u16))
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Returns how a block state is drawn, or the defaults for unknown states.
+    #[inline]
+    pub fn get(&self, block_state: BlockState) -> BlockRenderProperties {
+        self.states
+            .get(block_state.0 as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Plugin that rebuilds every chunk when the [`MeshQuality`] changes, and
+/// finds the [`BlockRenderTable`].
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`MeshQuality`], unless it was already inserted
+/// * [`BlockRenderTable`], once the [`MinecraftAssets`] resource exists
+///
+/// # Ordering
+///
+/// Chunks are marked for rebuilding in [`BrineSystems::MeshBuild`].
+pub struct MeshQualityPlugin;
+
+impl Plugin for MeshQualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshQuality>().add_systems(
+            Update,
+            (
+                insert_block_render_table.run_if(resource_added::<MinecraftAssets>),
+                remesh_chunks_on_change.run_if(resource_changed::<MeshQuality>),
+            )
+                .in_set(BrineSystems::MeshBuild),
+        );
+    }
+}
+
+fn insert_block_render_table(
+    mc_data: Res<MinecraftData>,
+    mc_assets: Res<MinecraftAssets>,
+    mut commands: Commands,
+) {
+    commands.insert_resource(BlockRenderTable::from_assets(&mc_data, &mc_assets));
+}
+
+fn remesh_chunks_on_change(
+    quality: Res<MeshQuality>,
+    chunks: Query<Entity, With<BuiltChunk>>,
+    mut commands: Commands,
+) {
+    if quality.is_added() {
+        return;
+    }
+
+    debug!("Mesh quality changed to {:?}, remeshing", *quality);
+    for entity in chunks.iter() {
+        commands.entity(entity).insert(RemeshChunk);
+    }
+}
+
+/// Adds the faces of each section's blocks that face blocks that can be seen
+/// through, which the chunk builders cull, except between two of the same
+/// translucent block (e.g. water or stained glass).
+pub fn add_faces_behind_cutouts(
+    opaque: &OpaqueBlocks,
+    render_table: &BlockRenderTable,
+    chunk: &Chunk,
+    meshes: &mut [VoxelMesh],
+) {
+    for (section, mesh) in chunk.sections.iter().zip(meshes.iter_mut()) {
+        let existing: HashSet<([u8; 3], Axis)> = mesh
+            .faces
+            .iter()
+            .map(|face| (face.voxel, face.axis))
+            .collect();
+
+        for (x, y, z, block_state) in section.block_states.iter() {
+            if block_state == BlockState::AIR {
+                continue;
+            }
+
+            let translucent =
+                render_table.get(block_state).render_layer == RenderLayer::Translucent;
+            let voxel = IVec3::new(x.into(), y.into(), z.into());
+            for axis in AXES {
+                let Some(neighbor) = block_in_section(section, voxel + normal(axis)) else {
+                    continue;
+                };
+                let see_through = neighbor != BlockState::AIR
+                    && !opaque.is_opaque(neighbor)
+                    && !(translucent && neighbor == block_state);
+                if see_through && !existing.contains(&([x, y, z], axis)) {
+                    mesh.faces.push(unit_face([x, y, z], axis));
+                }
+            }
+        }
+    }
+}
+
+/// Sets how occluded each vertex of the faces of each section's mesh is,
+/// leaving the faces of blocks without smooth lighting unoccluded.
+pub fn occlude_vertices(
+    opaque: &OpaqueBlocks,
+    render_table: &BlockRenderTable,
+    chunk: &Chunk,
+    meshes: &mut [VoxelMesh],
+) {
+    for (section, mesh) in chunk.sections.iter().zip(meshes.iter_mut()) {
+        let opaque_at = |position: IVec3| {
+            block_in_chunk(chunk, section, position)
+                .is_some_and(|block_state| opaque.is_opaque(block_state))
+        };
+        for face in mesh.faces.iter_mut() {
+            let [x, y, z] = face.voxel;
+            let block_state = section.block_states.get_block(x, y, z);
+            if render_table.get(block_state).smooth_lighting() {
+                face.occlusion = vertex_occlusion(face, opaque_at);
+            }
+        }
+    }
+}
+
+/// Counts the opaque blocks around each vertex of `face`, in the layer in
+/// front of it: the two beside the vertex and the one diagonal to it, or three
+/// if both beside it are, as the diagonal one can't be seen then.
+fn vertex_occlusion(face: &VoxelFace, opaque_at: impl Fn(IVec3) -> bool) -> [u8; 4] {
+    let normal = normal(face.axis);
+    let tangent_mask = IVec3::ONE - normal.abs();
+    let [u, v] = tangents(face.axis);
+
+    let voxel = IVec3::from_array(face.voxel.map(i32::from));
+    let front_layer = (voxel + normal) * normal.abs();
+    let center = face.positions.iter().copied().map(Vec3::from).sum::<Vec3>() / 4.0;
+
+    face.positions.map(|position| {
+        let position = Vec3::from(position);
+        // Which way the vertex is from the middle of the face, along it.
+        let toward = (position - center).signum().as_ivec3() * tangent_mask;
+        // The block in front of the face whose corner the vertex is.
+        let in_front =
+            (position - toward.as_vec3() * 0.5).floor().as_ivec3() * tangent_mask + front_layer;
+
+        let side_u = opaque_at(in_front + u * toward.dot(u));
+        let side_v = opaque_at(in_front + v * toward.dot(v));
+        let corner = opaque_at(in_front + toward);
+        if side_u && side_v {
+            3
+        } else {
+            u8::from(side_u) + u8::from(side_v) + u8::from(corner)
+        }
+    })
+}
+
+/// Returns the block at `position` (in section coordinates) if it's in the
+/// section.
+fn block_in_section(section: &ChunkSection, position: IVec3) -> Option<BlockState> {
+    let in_section = position.cmpge(IVec3::ZERO).all() && position.cmplt(SECTION_SIZE).all();
+    in_section.then(|| {
+        section
+            .block_states
+            .get_block(position.x as u8, position.y as u8, position.z as u8)
+    })
+}
+
+/// Returns the block at `position` (in the coordinates of `section`) if it's
+/// in the chunk.
+fn block_in_chunk(chunk: &Chunk, section: &ChunkSection, position: IVec3) -> Option<BlockState> {
+    let height = SECTION_HEIGHT as i32;
+    let chunk_y = section.chunk_y + position.y.div_euclid(height) as i16;
+    let position = position.with_y(position.y.rem_euclid(height));
+    if chunk_y == section.chunk_y {
+        block_in_section(section, position)
+    } else {
+        let neighbor = chunk
+            .sections
+            .iter()
+            .find(|section| section.chunk_y == chunk_y)?;
+        block_in_section(neighbor, position)
+    }
+}
+
+fn normal(axis: Axis) -> IVec3 {
+    IVec3::from_array(axis.normal().map(i32::from))
+}
+
+/// The two directions along a face facing `axis`.
+fn tangents(axis: Axis) -> [IVec3; 2] {
+    match axis {
+        Axis::XPos | Axis::XNeg => [IVec3::Y, IVec3::Z],
+        Axis::YPos | Axis::YNeg => [IVec3::X, IVec3::Z],
+        Axis::ZPos | Axis::ZNeg => [IVec3::X, IVec3::Y],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_chunk::BlockStates;
+
+    use crate::VisibleFacesChunkBuilder;
+
+    use super::*;
+
+    fn chunk_with(blocks: &[([u8; 3], BlockState)]) -> Chunk {
+        let mut section = ChunkSection::empty(0);
+        for ([x, y, z], block_state) in blocks {
+            section.block_states.0[BlockStates::xyz_to_index(*x, *y, *z)] = *block_state;
+        }
+        Chunk {
+            sections: vec![section],
+            ..Chunk::empty(0, 0)
+        }
+    }
+
+    #[test]
+    fn walls_darken_the_vertices_next_to_them() {
+        let stone = BlockState(1);
+        let opaque: OpaqueBlocks = [stone].into_iter().collect();
+        let chunk = chunk_with(&[([1, 0, 1], stone), ([2, 1, 1], stone)]);
+
+        let mut meshes = vec![VoxelMesh {
+            faces: vec![unit_face([1, 0, 1], Axis::YPos)],
+        }];
+        occlude_vertices(&opaque, &BlockRenderTable::default(), &chunk, &mut meshes);
+
+        let face = &meshes[0].faces[0];
+        for (position, occlusion) in face.positions.iter().zip(face.occlusion) {
+            let expected = if position[0] == 2.0 { 1 } else { 0 };
+            assert_eq!(occlusion, expected, "vertex at {position:?}");
+        }
+
+        // Unless the block gives off light.
+        let glowing = BlockRenderProperties {
+            light_emission: 15,
+            ..default()
+        };
+        let render_table = [BlockRenderProperties::default(), glowing]
+            .into_iter()
+            .collect();
+        let mut meshes = vec![VoxelMesh {
+            faces: vec![unit_face([1, 0, 1], Axis::YPos)],
+        }];
+        occlude_vertices(&opaque, &render_table, &chunk, &mut meshes);
+        assert_eq!(meshes[0].faces[0].occlusion, [0; 4]);
+    }
+
+    #[test]
+    fn faces_behind_see_through_blocks_are_kept() {
+        let leaves = BlockState(2);
+        let opaque = OpaqueBlocks::default();
+        let chunk = chunk_with(&[([0, 0, 0], leaves), ([1, 0, 0], leaves)]);
+
+        let mut meshes = VisibleFacesChunkBuilder::build_chunk(&chunk);
+        assert_eq!(meshes[0].faces.len(), 10);
+
+        add_faces_behind_cutouts(&opaque, &BlockRenderTable::default(), &chunk, &mut meshes);
+        let faces = &meshes[0].faces;
+        assert_eq!(faces.len(), 12);
+        assert!(faces
+            .iter()
+            .any(|face| face.voxel == [0, 0, 0] && face.axis == Axis::XPos));
+        assert!(faces
+            .iter()
+            .any(|face| face.voxel == [1, 0, 0] && face.axis == Axis::XNeg));
+    }
+
+    #[test]
+    fn faces_between_the_same_translucent_block_stay_culled() {
+        let glass = BlockState(2);
+        let opaque = OpaqueBlocks::default();
+        let chunk = chunk_with(&[([0, 0, 0], glass), ([1, 0, 0], glass)]);
+        let translucent = BlockRenderProperties {
+            render_layer: RenderLayer::Translucent,
+            ..default()
+        };
+        let render_table = [translucent; 3].into_iter().collect();
+
+        let mut meshes = VisibleFacesChunkBuilder::build_chunk(&chunk);
+        add_faces_behind_cutouts(&opaque, &render_table, &chunk, &mut meshes);
+        assert_eq!(meshes[0].faces.len(), 10);
+    }
+}
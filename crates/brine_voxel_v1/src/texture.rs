@@ -1,12 +1,19 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+};
 
 use bevy::{
-    asset::{AssetPath, LoadState},
+    asset::{AssetPath, LoadState, RenderAssetUsages},
     prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
 use bevy_image::{TextureAtlasBuilder, TextureAtlasLayout, TextureAtlasSources};
 
 use brine_data::blocks::BlockStateId;
+use brine_proto::BrineSystems;
+
+use crate::emissive::EmissiveTexture;
 
 const PLACEHOLDER_PATH: &str = "placeholder.png";
 
@@ -19,14 +26,75 @@ struct PendingAtlas {
 
     /// Handle to the atlas layout that will be populated once building finishes.
     layout: Handle<TextureAtlasLayout>,
+
+    /// What glows of each of the `textures` that glow.
+    emissive: HashMap<Handle<Image>, EmissiveTexture>,
+
+    /// Strong handle that we will populate with the emissive atlas, if any of
+    /// the textures glow.
+    emissive_texture: Option<Handle<Image>>,
 }
 
 impl PendingAtlas {
     fn all_textures_loaded(&self, asset_server: &AssetServer) -> bool {
+        let overlays = self
+            .emissive
+            .values()
+            .filter_map(|emissive| match emissive {
+                EmissiveTexture::Overlay(overlay) => Some(overlay),
+                EmissiveTexture::Whole => None,
+            });
+
         self.textures
             .iter()
+            .chain(overlays)
             .all(|handle| matches!(asset_server.get_load_state(handle), Some(LoadState::Loaded)))
     }
+
+    /// Draws what glows of each texture in its place in the atlas, into an
+    /// image as big as the atlas.
+    fn build_emissive_atlas(
+        &self,
+        layout: &TextureAtlasLayout,
+        sources: &TextureAtlasSources,
+        textures: &Assets<Image>,
+    ) -> Image {
+        let mut atlas = Image::new_fill(
+            Extent3d {
+                width: layout.size.x,
+                height: layout.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+
+        for (texture, emissive) in self.emissive.iter() {
+            let Some(rect) = sources.texture_rect(layout, texture.id()) else {
+                continue;
+            };
+            let source = match emissive {
+                EmissiveTexture::Whole => textures.get(texture),
+                EmissiveTexture::Overlay(overlay) => textures.get(overlay),
+            };
+            let Some(source) = source.filter(|source| source.size() == rect.size()) else {
+                debug!("Emissive texture doesn't fit {:?}", texture);
+                continue;
+            };
+
+            for y in 0..rect.height() {
+                for x in 0..rect.width() {
+                    if let Ok(color) = source.get_color_at(x, y) {
+                        let _ = atlas.set_color_at(rect.min.x + x, rect.min.y + y, color);
+                    }
+                }
+            }
+        }
+
+        atlas
+    }
 }
 
 #[derive(Resource, Default)]
@@ -43,6 +111,9 @@ pub struct BlockTextures {
 
     /// Sources describing how textures map into a built atlas, keyed by the atlas handle.
     atlas_sources: HashMap<Handle<Image>, TextureAtlasSources>,
+
+    /// Map from texture path to the texture's emissive overlay, if it has one.
+    emissive_overlays: HashMap<PathBuf, Option<Handle<Image>>>,
 }
 
 impl BlockTextures {
@@ -80,8 +151,32 @@ impl BlockTextures {
         atlas_images: &mut Assets<Image>,
         atlas_layouts: &mut Assets<TextureAtlasLayout>,
     ) -> (Handle<Image>, Handle<TextureAtlasLayout>) {
+        let (atlas_texture, layout, _) = self.create_texture_atlas_with_emissive(
+            textures,
+            HashMap::new(),
+            atlas_images,
+            atlas_layouts,
+        );
+        (atlas_texture, layout)
+    }
+
+    /// Like [`Self::create_texture_atlas_with_textures`], but also returns a
+    /// handle to an emissive atlas with the same layout, holding what glows
+    /// of each of the `emissive` textures, unless there are none.
+    pub fn create_texture_atlas_with_emissive(
+        &mut self,
+        textures: impl IntoIterator<Item = Handle<Image>>,
+        emissive: HashMap<Handle<Image>, EmissiveTexture>,
+        atlas_images: &mut Assets<Image>,
+        atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> (
+        Handle<Image>,
+        Handle<TextureAtlasLayout>,
+        Option<Handle<Image>>,
+    ) {
         let atlas_texture = atlas_images.reserve_handle();
         let layout = atlas_layouts.reserve_handle();
+        let emissive_texture = (!emissive.is_empty()).then(|| atlas_images.reserve_handle());
 
         // The vended handle needs to be strong so that the atlas isn't dropped
         // as soon as it is added to the `Assets` in `finish_texture_atlases`.
@@ -91,9 +186,32 @@ impl BlockTextures {
             textures: textures.into_iter().collect(),
             atlas_texture: atlas_texture.clone(),
             layout: layout.clone(),
+            emissive,
+            emissive_texture: emissive_texture.clone(),
         });
 
-        (atlas_texture, layout)
+        (atlas_texture, layout, emissive_texture)
+    }
+
+    /// Returns a handle to the emissive overlay of the texture at
+    /// `texture_path` (see [`emissive`][crate::emissive]), or `None` if it has
+    /// none.
+    ///
+    /// The first time, the `get_path` closure is used to find the overlay,
+    /// which the `asset_server` loads.
+    pub fn get_or_load_overlay<F>(
+        &mut self,
+        texture_path: &Path,
+        asset_server: &AssetServer,
+        get_path: F,
+    ) -> Option<Handle<Image>>
+    where
+        F: FnOnce(&Path) -> Option<PathBuf>,
+    {
+        self.emissive_overlays
+            .entry(texture_path.to_path_buf())
+            .or_insert_with(|| get_path(texture_path).map(|path| asset_server.load(path)))
+            .clone()
     }
 
     /// Returns a handle to a [`TextureAtlas`] that includes the textures for
@@ -174,6 +292,13 @@ impl BlockTextures {
 
                 match builder.build() {
                     Ok((layout, sources, image)) => {
+                        if let Some(emissive_texture) = pending_atlas.emissive_texture.as_ref() {
+                            let emissive =
+                                pending_atlas.build_emissive_atlas(&layout, &sources, textures);
+                            if let Err(err) = textures.insert(emissive_texture.id(), emissive) {
+                                error!("Failed to insert emissive atlas image: {err}");
+                            }
+                        }
                         if let Err(err) = atlas_layouts.insert(pending_atlas.layout.id(), layout) {
                             error!("Failed to insert texture atlas layout: {err}");
                         }
@@ -207,7 +332,10 @@ impl Plugin for TextureBuilderPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<BlockTextures>();
         app.add_systems(Startup, Self::load_placeholder_texture);
-        app.add_systems(Update, Self::finish_texture_atlases);
+        app.add_systems(
+            Update,
+            Self::finish_texture_atlases.in_set(BrineSystems::MeshBuild),
+        );
     }
 }
 
@@ -0,0 +1,379 @@
+//! Swaying leaves and plants, and rippling water.
+//!
+//! While a chunk is baked, each face is tagged with the [`WaveClass`] of its
+//! block (see [`WavingBlocks`]), which goes into the mesh as its second set of
+//! UVs: the class, and how much each vertex moves. Faces of plants are rooted
+//! at the bottom of their block and sway more towards the top; leaves sway as
+//! a whole, and water bobs at its surface.
+//!
+//! The [`WavingPlugin`] draws meshes with such faces with a [`WavingMaterial`],
+//! a [`StandardMaterial`] whose vertex shader moves the tagged vertices over
+//! time, if the [`WavingSettings`] say so. Vertices move by their position in
+//! the world, so the faces of neighbouring blocks move together.
+
+use std::{collections::HashMap, sync::Arc};
+
+use bevy::{
+    asset::embedded_asset,
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::AsBindGroup,
+    shader::ShaderRef,
+};
+use serde::{Deserialize, Serialize};
+
+use brine_chunk::{BlockState, Chunk};
+use brine_data::{BlockId, MinecraftData};
+use brine_proto::BrineSystems;
+
+use crate::mesh::VoxelMesh;
+
+const SHADER_PATH: &str = "embedded://brine_voxel_v1/waving.wgsl";
+
+/// Blocks that sway like plants, besides saplings.
+const PLANTS: &[&str] = &[
+    "short_grass",
+    "tall_grass",
+    "fern",
+    "large_fern",
+    "dead_bush",
+    "seagrass",
+    "tall_seagrass",
+    "kelp",
+    "kelp_plant",
+    "vine",
+    "dandelion",
+    "poppy",
+    "blue_orchid",
+    "allium",
+    "azure_bluet",
+    "red_tulip",
+    "orange_tulip",
+    "white_tulip",
+    "pink_tulip",
+    "oxeye_daisy",
+    "cornflower",
+    "lily_of_the_valley",
+    "sunflower",
+    "lilac",
+    "rose_bush",
+    "peony",
+    "wheat",
+    "carrots",
+    "potatoes",
+    "beetroots",
+];
+
+/// How a face moves in a [`WavingMaterial`]. The shader tells the classes
+/// apart by their number.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum WaveClass {
+    /// Doesn't move.
+    #[default]
+    None = 0,
+    Leaves = 1,
+    Plant = 2,
+    Water = 3,
+}
+
+impl WaveClass {
+    /// Returns the class of the block named `name`. Water isn't a block
+    /// class; fluids are tagged when they are meshed.
+    pub fn for_block(name: &str) -> Self {
+        if name.ends_with("_leaves") {
+            Self::Leaves
+        } else if name.ends_with("_sapling") || PLANTS.contains(&name) {
+            Self::Plant
+        } else {
+            Self::None
+        }
+    }
+
+    /// Returns how much a vertex `height` above the bottom of its block
+    /// moves, from 0 to 1.
+    pub fn weight(self, height: f32) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::Leaves => 1.0,
+            Self::Plant => height.clamp(0.0, 1.0),
+            // Only the surface moves, not the water below other water.
+            Self::Water if height > 0.0 && height < 1.0 => 1.0,
+            Self::Water => 0.0,
+        }
+    }
+}
+
+/// Which block states wave, and how.
+///
+/// Only the states of leaves, plants and water are kept, with their
+/// [`WaveClass`]; every other state doesn't wave. [`tag`](Self::tag) runs at
+/// the end of each chunk's builder task, which takes a clone along when it is
+/// spawned; clones share one map.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct WavingBlocks {
+    states: Arc<HashMap<BlockState, WaveClass>>,
+}
+
+impl FromIterator<(BlockState, WaveClass)> for WavingBlocks {
+    fn from_iter<I: IntoIterator<Item = (BlockState, WaveClass)>>(iter: I) -> Self {
+        Self {
+            states: Arc::new(iter.into_iter().collect()),
+        }
+    }
+}
+
+impl WavingBlocks {
+    /// Finds the states of the blocks that wave.
+    pub fn from_data(mc_data: &MinecraftData) -> Self {
+        let blocks = mc_data.blocks();
+        blocks
+            .iter_blocks()
+            .map(|block| (block.id, WaveClass::for_block(block.name)))
+            .filter(|(_, class)| *class != WaveClass::None)
+            .filter_map(|(block_id, class)| {
+                let states = blocks.iter_states_for_block(BlockId(block_id))?;
+                Some(states.map(move |(state_id, _)| (BlockState(u32::from(state_id.0)), class)))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the class of a block state.
+    #[inline]
+    pub fn get(&self, block_state: BlockState) -> WaveClass {
+        self.states.get(&block_state).copied().unwrap_or_default()
+    }
+
+    /// Tags the faces of the meshes built for each of the chunk's sections
+    /// with the class of their block.
+    pub fn tag(&self, chunk: &Chunk, meshes: &mut [VoxelMesh]) {
+        for (section, mesh) in chunk.sections.iter().zip(meshes.iter_mut()) {
+            for face in mesh.faces.iter_mut() {
+                let [x, y, z] = face.voxel;
+                face.wave = self.get(section.block_states.get_block(x, y, z));
+            }
+        }
+    }
+}
+
+/// Whether and how much the [`WavingPlugin`] moves leaves, plants and water.
+#[derive(Resource, Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WavingSettings {
+    pub enabled: bool,
+
+    /// How far things move, relative to the default.
+    pub strength: f32,
+}
+
+impl Default for WavingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strength: 1.0,
+        }
+    }
+}
+
+/// Marker component for chunk meshes with faces that wave, which the
+/// [`WavingPlugin`] draws with a [`WavingMaterial`].
+#[derive(Component, Debug, Default)]
+pub struct Waving;
+
+/// Extension of the [`StandardMaterial`] that moves vertices by their
+/// [`WaveClass`].
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct WavingExtension {
+    #[uniform(100)]
+    pub strength: f32,
+}
+
+impl MaterialExtension for WavingExtension {
+    fn vertex_shader() -> ShaderRef {
+        SHADER_PATH.into()
+    }
+}
+
+pub type WavingMaterial = ExtendedMaterial<StandardMaterial, WavingExtension>;
+
+/// The [`WavingMaterial`] made for each standard material, shared by all the
+/// meshes that had it.
+#[derive(Resource, Default)]
+struct WavingMaterials(HashMap<AssetId<StandardMaterial>, Handle<WavingMaterial>>);
+
+/// The standard material a [`Waving`] mesh had before it was given a
+/// [`WavingMaterial`], to go back to.
+#[derive(Component)]
+struct StandardBase(Handle<StandardMaterial>);
+
+/// Plugin that draws [`Waving`] chunk meshes with a [`WavingMaterial`] while
+/// the [`WavingSettings`] enable it, and finds the [`WavingBlocks`] for the
+/// [`ChunkBuilderPlugin`][crate::ChunkBuilderPlugin] to tag faces with.
+///
+/// Changes to the standard materials, such as animated textures, are copied
+/// over to the waving ones.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`WavingSettings`], unless they were already loaded
+/// * [`WavingBlocks`], once the `MinecraftData` resource exists
+///
+/// # Ordering
+///
+/// Materials are swapped in [`BrineSystems::MeshBuild`].
+pub struct WavingPlugin;
+
+impl Plugin for WavingPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "waving.wgsl");
+
+        app.add_plugins(MaterialPlugin::<WavingMaterial>::default())
+            .init_resource::<WavingSettings>()
+            .init_resource::<WavingMaterials>()
+            .add_systems(
+                Update,
+                (
+                    insert_waving_blocks.run_if(resource_added::<MinecraftData>),
+                    sync_waving_materials,
+                    swap_chunk_materials,
+                    update_strength.run_if(resource_changed::<WavingSettings>),
+                )
+                    .chain()
+                    .in_set(BrineSystems::MeshBuild),
+            );
+    }
+}
+
+fn insert_waving_blocks(mc_data: Res<MinecraftData>, mut commands: Commands) {
+    commands.insert_resource(WavingBlocks::from_data(&mc_data));
+}
+
+/// Copies changed standard materials to their waving copies, and forgets the
+/// copies of those no longer used.
+fn sync_waving_materials(
+    mut events: MessageReader<AssetEvent<StandardMaterial>>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut waving_materials: ResMut<WavingMaterials>,
+    mut materials: ResMut<Assets<WavingMaterial>>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Modified { id } => {
+                let Some(handle) = waving_materials.0.get(&id) else {
+                    continue;
+                };
+                if let (Some(base), Some(material)) =
+                    (standard_materials.get(id), materials.get_mut(handle))
+                {
+                    material.base = base.clone();
+                }
+            }
+            AssetEvent::Unused { id } | AssetEvent::Removed { id } => {
+                waving_materials.0.remove(&id);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn swap_chunk_materials(
+    settings: Res<WavingSettings>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut waving_materials: ResMut<WavingMaterials>,
+    mut materials: ResMut<Assets<WavingMaterial>>,
+    standard: Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<Waving>>,
+    waving: Query<(Entity, &StandardBase)>,
+    mut commands: Commands,
+) {
+    if !settings.enabled {
+        for (entity, base) in waving.iter() {
+            commands
+                .entity(entity)
+                .remove::<(MeshMaterial3d<WavingMaterial>, StandardBase)>()
+                .insert(MeshMaterial3d(base.0.clone()));
+        }
+        return;
+    }
+
+    for (entity, standard_material) in standard.iter() {
+        let Some(base) = standard_materials.get(&standard_material.0) else {
+            continue;
+        };
+        let material = waving_materials
+            .0
+            .entry(standard_material.0.id())
+            .or_insert_with(|| {
+                materials.add(WavingMaterial {
+                    base: base.clone(),
+                    extension: WavingExtension {
+                        strength: settings.strength,
+                    },
+                })
+            })
+            .clone();
+
+        commands
+            .entity(entity)
+            .remove::<MeshMaterial3d<StandardMaterial>>()
+            .insert((
+                MeshMaterial3d(material),
+                StandardBase(standard_material.0.clone()),
+            ));
+    }
+}
+
+fn update_strength(settings: Res<WavingSettings>, mut materials: ResMut<Assets<WavingMaterial>>) {
+    for (_, material) in materials.iter_mut() {
+        material.extension.strength = settings.strength;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_chunk::{BlockStates, ChunkSection};
+
+    use crate::mesh::VoxelFace;
+
+    use super::*;
+
+    #[test]
+    fn faces_take_the_class_of_their_block() {
+        assert_eq!(WaveClass::for_block("oak_leaves"), WaveClass::Leaves);
+        assert_eq!(WaveClass::for_block("birch_sapling"), WaveClass::Plant);
+        assert_eq!(WaveClass::for_block("short_grass"), WaveClass::Plant);
+        assert_eq!(WaveClass::for_block("grass_block"), WaveClass::None);
+
+        let leaves = BlockState(7);
+        let waving: WavingBlocks = [(leaves, WaveClass::Leaves)].into_iter().collect();
+
+        let mut section = ChunkSection::empty(0);
+        section.block_states.0[BlockStates::xyz_to_index(1, 2, 3)] = leaves;
+        let chunk = Chunk {
+            sections: vec![section],
+            ..Chunk::empty(0, 0)
+        };
+        let face = |voxel| VoxelFace {
+            voxel,
+            ..Default::default()
+        };
+        let mut meshes = vec![VoxelMesh {
+            faces: vec![face([1, 2, 3]), face([0, 0, 0])],
+        }];
+
+        waving.tag(&chunk, &mut meshes);
+        assert_eq!(meshes[0].faces[0].wave, WaveClass::Leaves);
+        assert_eq!(meshes[0].faces[1].wave, WaveClass::None);
+    }
+
+    #[test]
+    fn plants_are_rooted_and_water_moves_at_the_surface() {
+        assert_eq!(WaveClass::Plant.weight(0.0), 0.0);
+        assert_eq!(WaveClass::Plant.weight(1.0), 1.0);
+        assert_eq!(WaveClass::Water.weight(8.0 / 9.0), 1.0);
+        assert_eq!(WaveClass::Water.weight(1.0), 0.0);
+        assert_eq!(WaveClass::Water.weight(0.0), 0.0);
+    }
+}
@@ -0,0 +1,83 @@
+// Vertex shader of the `WavingMaterial`: the standard mesh vertex shader,
+// moving each vertex by the wave class and weight in its second set of UVs.
+
+#import bevy_pbr::{
+    forward_io::{Vertex, VertexOutput},
+    mesh_functions,
+    mesh_view_bindings::globals,
+    view_transformations::position_world_to_clip,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> strength: f32;
+
+const LEAVES: u32 = 1u;
+const PLANT: u32 = 2u;
+const WATER: u32 = 3u;
+
+// Returns how far a vertex at `position` moves.
+fn wave(position: vec3<f32>, class: u32, weight: f32) -> vec3<f32> {
+    let time = globals.time;
+    let phase = position.x * 0.7 + position.z * 0.5 + position.y * 0.3;
+    let amount = weight * strength;
+
+    switch class {
+        case LEAVES: {
+            return vec3(sin(time * 1.6 + phase), 0.0, cos(time * 1.3 + phase)) * 0.04 * amount;
+        }
+        case PLANT: {
+            return vec3(sin(time * 2.0 + phase), 0.0, cos(time * 1.7 + phase)) * 0.1 * amount;
+        }
+        case WATER: {
+            // Only ever lower the surface, so it doesn't poke through blocks.
+            return vec3(0.0, (sin(time * 1.8 + phase) - 1.0) * 0.05 * amount, 0.0);
+        }
+        default: {
+            return vec3(0.0);
+        }
+    }
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    var world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0),
+    );
+#ifdef VERTEX_UVS_B
+    let class = u32(round(vertex.uv_b.x));
+    world_position += vec4(wave(world_position.xyz, class, vertex.uv_b.y), 0.0);
+#endif
+    out.world_position = world_position;
+    out.position = position_world_to_clip(world_position.xyz);
+
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(
+        vertex.normal,
+        vertex.instance_index,
+    );
+#endif
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
+        world_from_local,
+        vertex.tangent,
+        vertex.instance_index,
+    );
+#endif
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+    return out;
+}
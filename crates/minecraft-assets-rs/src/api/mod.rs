@@ -10,7 +10,8 @@
 //!
 //! Resources can be enumerated and loaded using the [`ResourceProvider`] trait.
 //! This crate provides the [`FileSystemResourceProvider`] as a convenient
-//! implementation of this trait.
+//! implementation of this trait, and the [`LayeredResourceProvider`] to stack
+//! resource packs over one another.
 //!
 //! ## Asset Pack
 //!
@@ -25,7 +26,8 @@ mod resource;
 
 pub use asset_pack::AssetPack;
 pub use provider::{
-    EnumerateResources, FileSystemResourceProvider, LoadResource, ResourceProvider,
+    EnumerateResources, FileSystemResourceProvider, LayeredResourceProvider, LoadResource,
+    ResourceProvider,
 };
 pub use resolve::ModelResolver;
 pub use resource::{
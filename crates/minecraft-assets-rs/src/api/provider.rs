@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     fs, io,
     path::{Path, PathBuf},
 };
@@ -86,6 +87,83 @@ impl LoadResource for FileSystemResourceProvider {
     }
 }
 
+/// A [`ResourceProvider`] that stacks several providers, like resource packs
+/// layered over the vanilla assets.
+///
+/// Layers are ordered from lowest to highest priority: a resource is loaded
+/// from the last layer that has it, and enumerating resources lists the
+/// resources of every layer once.
+pub struct LayeredResourceProvider {
+    layers: Vec<Box<dyn ResourceProvider>>,
+}
+
+impl LayeredResourceProvider {
+    /// Returns a new provider with the given layers, lowest priority first.
+    pub fn new(layers: Vec<Box<dyn ResourceProvider>>) -> Self {
+        Self { layers }
+    }
+
+    /// Returns a new provider that layers the given root directories (each
+    /// containing an `assets/` directory), lowest priority first.
+    pub fn from_roots<I>(roots: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        Self::new(
+            roots
+                .into_iter()
+                .map(|root| {
+                    Box::new(FileSystemResourceProvider::new(root)) as Box<dyn ResourceProvider>
+                })
+                .collect(),
+        )
+    }
+}
+
+impl EnumerateResources for LayeredResourceProvider {
+    fn enumerate_resources(
+        &self,
+        namespace: &str,
+        kind: ResourceKind,
+    ) -> Result<Vec<ResourceIdentifier<'static>>, io::Error> {
+        let mut seen = HashSet::new();
+        let mut resources = Vec::new();
+
+        for layer in self.layers.iter() {
+            let layer_resources = match layer.enumerate_resources(namespace, kind) {
+                Ok(layer_resources) => layer_resources,
+                // Packs usually only have some kinds of resources.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+
+            for id in layer_resources {
+                if seen.insert(id.clone()) {
+                    resources.push(id);
+                }
+            }
+        }
+
+        Ok(resources)
+    }
+}
+
+impl LoadResource for LayeredResourceProvider {
+    fn load_resource(&self, id: &ResourceIdentifier) -> Result<Vec<u8>, io::Error> {
+        let mut not_found = None;
+
+        for layer in self.layers.iter().rev() {
+            match layer.load_resource(id) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => not_found = Some(err),
+                result => return result,
+            }
+        }
+
+        Err(not_found.unwrap_or_else(|| io::Error::from(io::ErrorKind::NotFound)))
+    }
+}
+
 /*
     dMP dMMMMMMP dMMMMMP dMMMMb
    amr    dMP   dMP     dMP.dMP
@@ -216,3 +294,70 @@ impl Iterator for ResourceIter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory under the system temp directory.
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "minecraft-assets-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn write_model(root: &Path, name: &str, contents: &str) {
+        let path = ResourcePath::for_resource(root, &ResourceIdentifier::block_model(name));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn higher_layers_override_lower_ones() {
+        let vanilla = temp_root("vanilla");
+        let pack = temp_root("pack");
+        write_model(&vanilla, "stone", "vanilla stone");
+        write_model(&vanilla, "dirt", "vanilla dirt");
+        write_model(&pack, "stone", "pack stone");
+
+        let provider = LayeredResourceProvider::from_roots([&vanilla, &pack]);
+
+        let load = |name| {
+            let bytes = provider
+                .load_resource(&ResourceIdentifier::block_model(name))
+                .unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+        assert_eq!(load("stone"), "pack stone");
+        assert_eq!(load("dirt"), "vanilla dirt");
+
+        let missing = provider.load_resource(&ResourceIdentifier::block_model("gravel"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let mut models = provider
+            .enumerate_resources("minecraft", ResourceKind::BlockModel)
+            .unwrap();
+        models.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(
+            models,
+            vec![
+                ResourceIdentifier::block_model("dirt"),
+                ResourceIdentifier::block_model("stone"),
+            ]
+        );
+
+        // The pack has no textures at all.
+        assert!(provider
+            .enumerate_resources("minecraft", ResourceKind::Texture)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(vanilla).unwrap();
+        fs::remove_dir_all(pack).unwrap();
+    }
+}
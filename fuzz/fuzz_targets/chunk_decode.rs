@@ -0,0 +1,27 @@
+//! Decodes arbitrary bytes as the data of a full chunk.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use brine_chunk::{BlockState, Chunk, Palette};
+
+/// A global palette with a plausible number of block states.
+struct FuzzPalette;
+
+impl Palette for FuzzPalette {
+    fn id_to_block_state(&self, id: u32) -> Option<BlockState> {
+        (id < 1000).then_some(BlockState(id))
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    // The first four bytes are the primary bit mask.
+    if data.len() < 4 {
+        return;
+    }
+    let (bit_mask, mut buf) = data.split_at(4);
+    let primary_bit_mask = u32::from_be_bytes(bit_mask.try_into().unwrap());
+
+    let _ = Chunk::decode(0, 0, true, primary_bit_mask, &FuzzPalette, &mut buf);
+});
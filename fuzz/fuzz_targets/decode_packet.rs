@@ -0,0 +1,47 @@
+//! Decodes arbitrary bytes as a packet frame, in every protocol state and
+//! direction, with and without compression.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use brine_proto_backend::{
+    backend_stevenarella::codec::{Direction, MinecraftCodec},
+    codec::{CodecLimits, MinecraftProtocolState},
+    version::get_protocol_version,
+};
+
+const PROTOCOL_VERSION: i32 = match get_protocol_version("1.21.4") {
+    Some(protocol_version) => protocol_version,
+    None => panic!("unknown version"),
+};
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte picks how to decode the rest.
+    let Some((&selector, buf)) = data.split_first() else {
+        return;
+    };
+
+    let state = match selector % 5 {
+        0 => MinecraftProtocolState::Handshaking,
+        1 => MinecraftProtocolState::Status,
+        2 => MinecraftProtocolState::Login,
+        3 => MinecraftProtocolState::Configuration,
+        _ => MinecraftProtocolState::Play,
+    };
+    let direction = if selector & 0x08 == 0 {
+        Direction::Clientbound
+    } else {
+        Direction::Serverbound
+    };
+    let compression_threshold = (selector & 0x10 != 0).then_some(256);
+
+    let _ = MinecraftCodec::decode_packet(
+        PROTOCOL_VERSION,
+        state,
+        direction,
+        compression_threshold,
+        CodecLimits::default(),
+        buf,
+    );
+});
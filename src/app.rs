@@ -0,0 +1,771 @@
+//! Assembling a Brine app.
+//!
+//! [`BrineAppBuilder`] puts together the plugins the `brine` binary runs, so
+//! that its subcommands, the utility binaries and tests set apps up the same
+//! way. Other Bevy apps can embed a Minecraft world view by adding
+//! [`BrineClientPlugins`] themselves.
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+#[cfg(target_arch = "wasm32")]
+use bevy::asset::AssetMetaCheck;
+use bevy::{
+    app::PluginGroupBuilder,
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    log::{Level, LogPlugin},
+    prelude::*,
+    render::{
+        render_resource::WgpuFeatures,
+        settings::{RenderCreation, WgpuSettings},
+        RenderPlugin,
+    },
+    state::app::StatesPlugin,
+};
+use bevy_flycam::{FlyCam, NoCameraPlayerPlugin};
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use brine_data::MinecraftData;
+use brine_net::{capture::CaptureWriter, transport::Transport};
+use brine_proto::{AlwaysSuccessfulLoginPlugin, BrineSystems, ProtocolPlugin};
+use brine_proto_backend::{configuration::ConfigurationPolicy, ProtocolBackendPlugin};
+use brine_render::{
+    billboard::BillboardPlugin, font::FontPlugin, instancing::InstancingPlugin,
+    ui_text::UiTextPlugin,
+};
+use brine_voxel_v1::{
+    appearance::ChunkAppearancePlugin,
+    chunk_builder::{
+        component::BuiltChunkSection, ChunkBuilderPlugin, GreedyQuadsChunkBuilder,
+        VisibleFacesChunkBuilder,
+    },
+    fluid::FluidPlugin,
+    occlusion::OcclusionCullingPlugin,
+    quality::MeshQualityPlugin,
+    texture::TextureBuilderPlugin,
+    waving::WavingPlugin,
+};
+
+#[cfg(feature = "scripting")]
+use crate::scripting::{Script, ScriptingPlugin};
+use crate::{
+    block_interaction::BlockInteractionPlugin,
+    block_update::BlockUpdatePlugin,
+    container::ContainerPlugin,
+    crafting::CraftingPlugin,
+    debug::{
+        DebugHudPlugin, DebugOverlaysPlugin, DebugWireframePlugin, MemoryDiagnosticsPlugin,
+        PerformanceOverlayPlugin,
+    },
+    dropped_item::DroppedItemPlugin,
+    entity::EntityPlugin,
+    error::ClientErrorPlugin,
+    first_person::FirstPersonPlugin,
+    flight::FlightPlugin,
+    game_mode::GameModePlugin,
+    gamepad::GamepadPlugin,
+    graphics::GraphicsPlugin,
+    ground::GroundPlugin,
+    health::HealthPlugin,
+    hot_reload::AssetHotReloadPlugin,
+    hotbar::HotbarPlugin,
+    idle::{IdleBehavior, IdlePlugin},
+    input::InputMapPlugin,
+    item_frame::ItemFramePlugin,
+    item_use::ItemUsePlugin,
+    latency::LatencyPlugin,
+    loading::AssetLoadingPlugin,
+    login::{LoginPlugin, ReconnectPolicy},
+    map::MapPlugin,
+    map_overlay::MapOverlayPlugin,
+    mob_model::MobModelPlugin,
+    name_tag::NameTagPlugin,
+    player::LocalPlayerPlugin,
+    player_list::PlayerListPlugin,
+    pose::PosePlugin,
+    screenshot::ScreenshotCapturePlugin,
+    server::{ChunkServing, ServeChunksFromDirectoryPlugin},
+    settings::{Settings, SettingsPlugin, DEFAULT_SETTINGS_PATH},
+    sound::BlockSoundPlugin,
+    special_block::SpecialBlockPlugin,
+    status_effect::StatusEffectPlugin,
+    submerged::SubmergedPlugin,
+    synthetic::{SyntheticWorld, SyntheticWorldPlugin},
+    targeting::TargetingPlugin,
+    vehicle::VehiclePlugin,
+    world_border::WorldBorderPlugin,
+    world_diff::WorldDiffPlugin,
+    DEFAULT_LOG_FILTER,
+};
+pub const DEFAULT_PORT: &str = "25565";
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_SERVER: &str = "localhost:25565";
+/// The browser can only reach servers through a WebSocket proxy (see
+/// [`WebSocketTransport`][brine_net::transport::WebSocketTransport]), so by
+/// default it looks for one on the same machine.
+#[cfg(target_arch = "wasm32")]
+pub const DEFAULT_SERVER: &str = "ws://localhost:25566";
+pub const DEFAULT_USERNAME: &str = "user";
+
+/// Minecraft version whose data and assets are loaded.
+pub const MINECRAFT_VERSION: &str = "1.21.4";
+
+/// The vanilla assets directory (contains `assets/`, `data/` and
+/// `pack.mcmeta`).
+pub const DEFAULT_ASSETS_PATH: &str = "assets/1.21.4";
+pub const DEFAULT_BAKE_CACHE_PATH: &str = "cache/baked_assets_1.21.4.bin";
+
+/// Where the world comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldSource {
+    /// Log in to a server at this address (`host:port`).
+    Server(String),
+
+    /// Serve chunks from a directory of chunk files, with no server.
+    ChunkDirectory(PathBuf),
+
+    /// Generate chunks, with no server. See [`SyntheticWorldPlugin`].
+    Synthetic(SyntheticWorld),
+}
+
+/// Configuration of [`BrineClientPlugins`] and [`BrineConnectionPlugins`].
+#[derive(Clone)]
+pub struct BrineClientConfig {
+    pub world_source: WorldSource,
+
+    /// How chunks are sent when serving them from a directory. See
+    /// [`ServeChunksFromDirectoryPlugin::with_serving`].
+    pub chunk_serving: ChunkServing,
+
+    /// Username to log in with.
+    pub username: String,
+
+    /// Protocol version to speak whatever the server advertises. See
+    /// [`ProtocolBackendPlugin::with_protocol_version`].
+    pub protocol_version: Option<i32>,
+
+    /// Whether to log in without a status ping when `protocol_version` is
+    /// set. See [`ProtocolBackendPlugin::without_status_ping`].
+    pub skip_status_ping: bool,
+
+    /// Capture to record everything received from the server into. See
+    /// [`ProtocolBackendPlugin::with_capture`].
+    pub capture: Option<CaptureWriter>,
+
+    /// How to reach the server, if not over TCP. See
+    /// [`ProtocolBackendPlugin::with_transport`].
+    pub transport: Option<Arc<dyn Transport>>,
+
+    /// Whether to send every packet uncompressed. See
+    /// [`ProtocolBackendPlugin::without_outbound_compression`].
+    pub outbound_compression_disabled: bool,
+
+    /// Whether to report packets their definitions don't read in full. See
+    /// [`ProtocolBackendPlugin::with_strict_decoding`].
+    pub strict_decoding: bool,
+
+    /// How to answer the server while it configures the client. See
+    /// [`ProtocolBackendPlugin::with_configuration_policy`].
+    pub configuration_policy: ConfigurationPolicy,
+
+    /// Whether to exit the app when the server disconnects the client.
+    pub exit_on_disconnect: bool,
+
+    /// How to log in again when the connection is lost, if at all. See
+    /// [`LoginPlugin::with_reconnect`].
+    pub reconnect: Option<ReconnectPolicy>,
+
+    /// Settings file (key bindings, etc.), created on first change.
+    pub settings_path: PathBuf,
+
+    /// The vanilla assets directory.
+    pub assets_path: PathBuf,
+
+    /// Where to cache the baked assets, if anywhere.
+    pub bake_cache_path: Option<PathBuf>,
+
+    /// Resource packs to layer over the vanilla assets. See
+    /// [`AssetLoadingPlugin::with_resource_packs`].
+    pub resource_packs: Vec<PathBuf>,
+}
+
+impl Default for BrineClientConfig {
+    fn default() -> Self {
+        Self {
+            world_source: WorldSource::Server(String::from(DEFAULT_SERVER)),
+            chunk_serving: ChunkServing::default(),
+            username: String::from(DEFAULT_USERNAME),
+            protocol_version: None,
+            skip_status_ping: false,
+            capture: None,
+            transport: None,
+            outbound_compression_disabled: false,
+            strict_decoding: false,
+            configuration_policy: ConfigurationPolicy::default(),
+            exit_on_disconnect: false,
+            reconnect: None,
+            settings_path: PathBuf::from(DEFAULT_SETTINGS_PATH),
+            assets_path: PathBuf::from(DEFAULT_ASSETS_PATH),
+            bake_cache_path: Some(PathBuf::from(DEFAULT_BAKE_CACHE_PATH)),
+            resource_packs: Vec::new(),
+        }
+    }
+}
+
+/// Everything needed to show a Minecraft world in a Bevy app: the connection
+/// to the server, the protocol, asset loading, chunk meshing, and the world
+/// view with its camera, HUD and input handling.
+///
+/// The group expects the app to already have Bevy's [`DefaultPlugins`] (or
+/// an equivalent set with windowing, assets and rendering).
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use brine::app::{BrineClientConfig, BrineClientPlugins, WorldSource};
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(BrineClientPlugins::new(BrineClientConfig {
+///         world_source: WorldSource::Server(String::from("localhost:25565")),
+///         ..default()
+///     }))
+///     .run();
+/// ```
+#[derive(Clone, Default)]
+pub struct BrineClientPlugins {
+    config: BrineClientConfig,
+}
+
+impl BrineClientPlugins {
+    pub fn new(config: BrineClientConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl PluginGroup for BrineClientPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let mut config = self.config;
+
+        // The server only sends the chunks within the render distance.
+        let graphics = Settings::load(&config.settings_path)
+            .map(|settings| settings.graphics)
+            .unwrap_or_default();
+        config.configuration_policy.client_information.view_distance = graphics.view_distance();
+
+        let mut asset_loading = AssetLoadingPlugin::new(config.assets_path.clone())
+            .with_resource_packs(config.resource_packs.clone());
+        if let Some(bake_cache_path) = &config.bake_cache_path {
+            asset_loading = asset_loading.with_bake_cache(bake_cache_path.clone());
+        }
+
+        let mut group = PluginGroupBuilder::start::<Self>()
+            .add(SettingsPlugin::new(config.settings_path.clone()))
+            .add(InputMapPlugin)
+            .add_group(BrineConnectionPlugins::new(config))
+            // The assets are indexed in the background while a loading screen
+            // is shown.
+            .add(asset_loading)
+            .add(TextureBuilderPlugin)
+            .add(FluidPlugin)
+            .add(WavingPlugin)
+            .add(OcclusionCullingPlugin)
+            .add(MeshQualityPlugin)
+            .add(ChunkAppearancePlugin)
+            .add(MinecraftWorldViewerPlugin);
+
+        if cfg!(feature = "hot_reload") {
+            group = group.add(AssetHotReloadPlugin);
+        }
+
+        group
+    }
+}
+
+/// The part of [`BrineClientPlugins`] that doesn't need a window: logging in
+/// (or serving chunks from a directory), the protocol, and the Minecraft
+/// data. The [`BrineClientConfig`] fields about settings and assets are
+/// ignored.
+#[derive(Clone, Default)]
+pub struct BrineConnectionPlugins {
+    config: BrineClientConfig,
+}
+
+impl BrineConnectionPlugins {
+    pub fn new(config: BrineClientConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl PluginGroup for BrineConnectionPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let config = self.config;
+
+        let mut group = PluginGroupBuilder::start::<Self>().add(ProtocolPlugin);
+
+        match config.world_source {
+            WorldSource::ChunkDirectory(chunk_dir) => {
+                group = group.add(AlwaysSuccessfulLoginPlugin).add(
+                    ServeChunksFromDirectoryPlugin::new(chunk_dir)
+                        .with_serving(config.chunk_serving),
+                );
+            }
+            WorldSource::Synthetic(world) => {
+                group = group
+                    .add(AlwaysSuccessfulLoginPlugin)
+                    .add(SyntheticWorldPlugin::new(world));
+            }
+            WorldSource::Server(server) => {
+                let mut backend = ProtocolBackendPlugin::default();
+                if let Some(protocol_version) = config.protocol_version {
+                    backend = backend.with_protocol_version(protocol_version);
+                }
+                if config.skip_status_ping {
+                    backend = backend.without_status_ping();
+                }
+                if let Some(capture) = config.capture {
+                    backend = backend.with_capture(capture);
+                }
+                if let Some(transport) = config.transport {
+                    backend = backend.with_transport(transport);
+                }
+                if config.outbound_compression_disabled {
+                    backend = backend.without_outbound_compression();
+                }
+                if config.strict_decoding {
+                    backend = backend.with_strict_decoding();
+                }
+                backend = backend.with_configuration_policy(config.configuration_policy);
+
+                let mut login = LoginPlugin::new(server, config.username);
+                if config.exit_on_disconnect {
+                    login = login.exit_on_disconnect();
+                }
+                if let Some(policy) = config.reconnect {
+                    login = login.with_reconnect(policy);
+                }
+
+                group = group.add(backend).add(login);
+            }
+        }
+
+        group.add(MinecraftDataPlugin)
+    }
+}
+
+/// Inserts the [`MinecraftData`] for [`MINECRAFT_VERSION`], and its
+/// [`ServerRegistries`][brine_data::ServerRegistries] handle for the backend
+/// to record the server's registries into.
+struct MinecraftDataPlugin;
+
+impl Plugin for MinecraftDataPlugin {
+    fn build(&self, app: &mut App) {
+        let mc_data = MinecraftData::for_version(MINECRAFT_VERSION);
+        app.insert_resource(mc_data.server_registries().clone())
+            .insert_resource(mc_data);
+    }
+}
+
+/// Builds an [`App`] running the Brine client.
+///
+/// By default the app opens a window and renders the world of the server at
+/// [`DEFAULT_SERVER`] with [`BrineClientPlugins`]. [`headless`][Self::headless]
+/// apps have no window, and only keep track of what the server sends.
+///
+/// ```no_run
+/// use brine::app::BrineAppBuilder;
+///
+/// BrineAppBuilder::new()
+///     .headless()
+///     .with_server("localhost:25565")
+///     .exit_on_disconnect()
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone, Default)]
+pub struct BrineAppBuilder {
+    config: BrineClientConfig,
+    headless: bool,
+    idle: Option<IdleBehavior>,
+    #[cfg(feature = "scripting")]
+    script: Option<Script>,
+    debug: bool,
+    profile: bool,
+    world_diff_stream: Option<SocketAddr>,
+}
+
+impl BrineAppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from `config` rather than the defaults.
+    pub fn with_config(mut self, config: BrineClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Runs without a window, assets or rendering: the app logs in and keeps
+    /// track of entities and players, for bots, replays and tests.
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
+
+    /// Keeps a headless app's player looking active as `behavior` says, so
+    /// anti-AFK plugins don't kick it. See [`IdlePlugin`]. Apps with a window
+    /// ignore it.
+    pub fn with_idle_behavior(mut self, behavior: IdleBehavior) -> Self {
+        self.idle = Some(behavior);
+        self
+    }
+
+    /// Runs `script` against a headless app. See [`ScriptingPlugin`]. Apps
+    /// with a window ignore it.
+    #[cfg(feature = "scripting")]
+    pub fn with_script(mut self, script: Script) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    /// Logs in to the server at `server` (`host:port`).
+    pub fn with_server(mut self, server: impl Into<String>) -> Self {
+        self.config.world_source = WorldSource::Server(server.into());
+        self
+    }
+
+    /// Serves chunks from a directory of chunk files instead of connecting to
+    /// a server.
+    pub fn with_chunk_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.world_source = WorldSource::ChunkDirectory(path.into());
+        self
+    }
+
+    /// Generates `world` instead of connecting to a server.
+    pub fn with_synthetic_world(mut self, world: SyntheticWorld) -> Self {
+        self.config.world_source = WorldSource::Synthetic(world);
+        self
+    }
+
+    /// Sends the chunks from the chunk directory in the order, at the rate
+    /// and for as long as `serving` says. See
+    /// [`ServeChunksFromDirectoryPlugin::with_serving`].
+    pub fn with_chunk_serving(mut self, serving: ChunkServing) -> Self {
+        self.config.chunk_serving = serving;
+        self
+    }
+
+    /// Logs in as `username`.
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.config.username = username.into();
+        self
+    }
+
+    /// Always speaks `protocol_version`, whatever the server advertises. See
+    /// [`ProtocolBackendPlugin::with_protocol_version`].
+    pub fn with_protocol_version(mut self, protocol_version: i32) -> Self {
+        self.config.protocol_version = Some(protocol_version);
+        self
+    }
+
+    /// Logs in without asking the server for its status first, when the
+    /// protocol version is forced. See
+    /// [`ProtocolBackendPlugin::without_status_ping`].
+    pub fn without_status_ping(mut self) -> Self {
+        self.config.skip_status_ping = true;
+        self
+    }
+
+    /// Records everything received from the server into `capture`. See
+    /// [`ProtocolBackendPlugin::with_capture`].
+    pub fn with_capture(mut self, capture: CaptureWriter) -> Self {
+        self.config.capture = Some(capture);
+        self
+    }
+
+    /// Reaches the server with `transport` instead of TCP, e.g. a
+    /// [`WebSocketTransport`][brine_net::transport::WebSocketTransport] for a
+    /// `ws://` server address. See [`ProtocolBackendPlugin::with_transport`].
+    pub fn with_transport(mut self, transport: impl Transport) -> Self {
+        self.config.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sends every packet to the server uncompressed. See
+    /// [`ProtocolBackendPlugin::without_outbound_compression`].
+    pub fn without_outbound_compression(mut self) -> Self {
+        self.config.outbound_compression_disabled = true;
+        self
+    }
+
+    /// Reports packets their definitions don't read in full as
+    /// [`ProtocolAnomaly`][brine_proto_backend::codec::ProtocolAnomaly]
+    /// events. See [`ProtocolBackendPlugin::with_strict_decoding`].
+    pub fn with_strict_decoding(mut self) -> Self {
+        self.config.strict_decoding = true;
+        self
+    }
+
+    /// Answers the server as `policy` says while it configures the client.
+    /// See [`ProtocolBackendPlugin::with_configuration_policy`].
+    pub fn with_configuration_policy(mut self, policy: ConfigurationPolicy) -> Self {
+        self.config.configuration_policy = policy;
+        self
+    }
+
+    /// Exits the app when the server disconnects the client.
+    pub fn exit_on_disconnect(mut self) -> Self {
+        self.config.exit_on_disconnect = true;
+        self
+    }
+
+    /// Logs in again according to `policy` when the connection is lost.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.config.reconnect = Some(policy);
+        self
+    }
+
+    /// Loads and saves settings at `path` instead of [`DEFAULT_SETTINGS_PATH`].
+    /// Headless apps have no settings.
+    pub fn with_settings(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.settings_path = path.into();
+        self
+    }
+
+    /// Layers resource packs over the vanilla assets. See
+    /// [`AssetLoadingPlugin::with_resource_packs`].
+    pub fn with_resource_packs(mut self, resource_packs: Vec<PathBuf>) -> Self {
+        self.config.resource_packs = resource_packs;
+        self
+    }
+
+    /// Adds the inspector, wireframes, overlays, debug HUD and performance
+    /// overlay, and memory and frame time diagnostics.
+    pub fn with_debug_tools(mut self) -> Self {
+        self.debug = true;
+        self
+    }
+
+    /// Streams a [`WorldDiff`][crate::world_diff::WorldDiff] of every tick as
+    /// JSON lines to whatever connects to `addr`.
+    pub fn with_world_diff_stream(mut self, addr: SocketAddr) -> Self {
+        self.world_diff_stream = Some(addr);
+        self
+    }
+
+    /// Sets up a profiling session: quieter logging and frame time
+    /// diagnostics.
+    pub fn with_profiling(mut self) -> Self {
+        self.profile = true;
+        self
+    }
+
+    pub fn build(self) -> App {
+        let mut app = App::new();
+
+        // Per-packet debug logging dominates the frame time, so profiling runs
+        // only log at info and above, as do headless apps, which would
+        // otherwise drown out whatever they print.
+        let log_level = if self.profile || self.headless {
+            Level::INFO
+        } else {
+            Level::DEBUG
+        };
+        let log_plugin = LogPlugin {
+            level: log_level,
+            filter: String::from(DEFAULT_LOG_FILTER),
+            ..default()
+        };
+
+        if self.headless {
+            app.add_plugins((MinimalPlugins, StatesPlugin, log_plugin));
+        } else {
+            let mut default_plugins = DefaultPlugins.set(log_plugin);
+            // Web servers don't have the `.meta` files Bevy would otherwise
+            // ask for alongside every asset, and the canvas should fill the
+            // page.
+            #[cfg(target_arch = "wasm32")]
+            {
+                default_plugins = default_plugins
+                    .set(AssetPlugin {
+                        meta_check: AssetMetaCheck::Never,
+                        ..default()
+                    })
+                    .set(WindowPlugin {
+                        primary_window: Some(Window {
+                            fit_canvas_to_parent: true,
+                            ..default()
+                        }),
+                        ..default()
+                    });
+            }
+            if self.debug {
+                default_plugins = default_plugins.set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings {
+                        features: WgpuFeatures::POLYGON_MODE_LINE,
+                        ..default()
+                    }),
+                    ..default()
+                });
+            }
+            app.add_plugins(default_plugins);
+        }
+
+        if self.profile && !cfg!(feature = "trace") {
+            warn!(
+                "Profiling without tracing spans; rebuild with \
+                 `cargo run --release --features trace_chrome -- --profile` \
+                 (or `trace_tracy`) to record them"
+            );
+        }
+
+        if self.headless {
+            app.add_plugins((
+                BrineConnectionPlugins::new(self.config),
+                EntityPlugin,
+                PlayerListPlugin,
+                LatencyPlugin,
+                GameModePlugin,
+            ));
+            if let Some(behavior) = self.idle {
+                app.add_plugins(IdlePlugin::default().with_behavior(behavior));
+            }
+            #[cfg(feature = "scripting")]
+            if let Some(script) = self.script {
+                app.add_plugins(ScriptingPlugin::new(script));
+            }
+        } else {
+            app.add_plugins(BrineClientPlugins::new(self.config));
+        }
+
+        if let Some(addr) = self.world_diff_stream {
+            app.add_plugins(WorldDiffPlugin::default().with_stream(addr));
+        }
+
+        // Debugging, diagnostics, and utility plugins.
+
+        if self.debug && !self.headless {
+            app.add_plugins((
+                WorldInspectorPlugin::new(),
+                DebugWireframePlugin,
+                DebugOverlaysPlugin,
+                DebugHudPlugin,
+                PerformanceOverlayPlugin,
+            ));
+        }
+
+        if (self.debug || self.profile) && !self.headless {
+            app.add_plugins(MemoryDiagnosticsPlugin);
+        }
+
+        if self.debug || self.profile {
+            app.add_plugins((
+                FrameTimeDiagnosticsPlugin::default(),
+                LogDiagnosticsPlugin::default(),
+            ));
+        }
+
+        app
+    }
+}
+
+/// Appends the default port to `server` if it doesn't have one.
+pub fn normalize_server_address(server: &str) -> String {
+    let server = server.trim();
+    if needs_port(server) {
+        format!("{server}:{DEFAULT_PORT}")
+    } else {
+        server.to_string()
+    }
+}
+
+fn needs_port(server: &str) -> bool {
+    if server.starts_with('[') {
+        !server.contains("]:")
+    } else {
+        !server.contains(':')
+    }
+}
+
+/// Plugin that shows the world: chunk meshes, entities, the HUD, and a first
+/// person camera.
+#[derive(Default)]
+pub struct MinecraftWorldViewerPlugin;
+
+impl Plugin for MinecraftWorldViewerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            NoCameraPlayerPlugin,
+            GamepadPlugin,
+            HotbarPlugin,
+            HealthPlugin,
+            GameModePlugin,
+            FlightPlugin,
+            PosePlugin,
+            EntityPlugin,
+            StatusEffectPlugin,
+            MobModelPlugin,
+            PlayerListPlugin,
+            LatencyPlugin,
+            FontPlugin,
+            UiTextPlugin,
+            BillboardPlugin,
+            NameTagPlugin,
+            BlockUpdatePlugin,
+        ))
+        .add_plugins((
+            FirstPersonPlugin,
+            TargetingPlugin,
+            BlockInteractionPlugin,
+            ItemUsePlugin,
+            CraftingPlugin,
+            ContainerPlugin,
+            MapPlugin,
+            VehiclePlugin,
+            LocalPlayerPlugin,
+            BlockSoundPlugin,
+            WorldBorderPlugin,
+            ClientErrorPlugin,
+            GroundPlugin,
+            ScreenshotCapturePlugin::default(),
+            // Only the builder that the `GraphicsPlugin` makes active builds chunks.
+            ChunkBuilderPlugin::<VisibleFacesChunkBuilder>::default(),
+            ChunkBuilderPlugin::<GreedyQuadsChunkBuilder>::default(),
+        ))
+        .add_plugins((
+            SubmergedPlugin,
+            InstancingPlugin,
+            ItemFramePlugin,
+            DroppedItemPlugin,
+            SpecialBlockPlugin,
+            MapOverlayPlugin,
+            GraphicsPlugin,
+        ))
+        .add_systems(Startup, set_up_camera)
+        .add_systems(
+            Update,
+            give_chunk_sections_correct_y_height.in_set(BrineSystems::MeshBuild),
+        );
+    }
+}
+
+fn set_up_camera(mut commands: Commands) {
+    // Moved to the ground once chunks load (see `ground`), or to wherever the
+    // server puts the player.
+    let camera_start = Transform::default();
+
+    commands.spawn((
+        Camera3d::default(),
+        // Replaced by the graphics settings' MSAA on the first frame.
+        Msaa::Sample4,
+        FlyCam,
+        camera_start,
+        GlobalTransform::default(),
+    ));
+}
+
+fn give_chunk_sections_correct_y_height(mut query: Query<(&mut Transform, &BuiltChunkSection)>) {
+    for (mut transform, chunk_section) in query.iter_mut() {
+        let height = (chunk_section.section_y as f32) * 16.0;
+        if transform.translation.y != height {
+            transform.translation.y = height;
+        }
+    }
+}
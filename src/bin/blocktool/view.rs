@@ -2,13 +2,18 @@ use std::str::FromStr;
 
 use bevy::math::{primitives::Cuboid, Rect};
 use bevy::{
-    asset::RenderAssetUsages, input::ButtonInput, pbr::MeshMaterial3d, prelude::*,
+    asset::RenderAssetUsages, pbr::MeshMaterial3d, prelude::*,
     render::render_resource::PrimitiveTopology,
 };
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_mesh::{Indices, Mesh3d};
 
-use brine::debug::DebugWireframePlugin;
+use brine::{
+    debug::DebugWireframePlugin,
+    input::{InputAction, InputMap, InputMapPlugin},
+    screenshot::ScreenshotCapturePlugin,
+    settings::SettingsPlugin,
+};
 use brine_asset::{BakedModel, BlockFace, MinecraftAssets};
 use brine_data::{BlockStateId, MinecraftData};
 use brine_render::texture::{
@@ -151,6 +156,8 @@ fn display_block(block_reference: &str, show_faces: ShowFaces) {
         .add_plugins(DefaultPlugins)
         .add_plugins(DebugWireframePlugin)
         .add_plugins(WorldInspectorPlugin::new())
+        .add_plugins((SettingsPlugin::default(), InputMapPlugin))
+        .add_plugins(ScreenshotCapturePlugin::default())
         .insert_resource(show_faces)
         .insert_resource(mc_data)
         .insert_resource(mc_assets)
@@ -246,7 +253,7 @@ fn setup(
 }
 
 fn next_block_state(
-    input: Res<ButtonInput<KeyCode>>,
+    input: InputMap,
     the_blocks: ResMut<TheBlocks>,
     show_faces: Res<ShowFaces>,
     mc_data: Res<MinecraftData>,
@@ -258,19 +265,22 @@ fn next_block_state(
     materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
 ) {
-    let count = if input.pressed(KeyCode::ShiftLeft) {
+    let count = if input.pressed(InputAction::ViewerSkipModifier) {
         10
     } else {
         1
     };
 
-    let next_block: Box<dyn Fn(&mut TheBlocks)> = if input.just_pressed(KeyCode::ArrowLeft) {
+    let previous = input.just_pressed(InputAction::ViewerPrevious);
+    let next = input.just_pressed(InputAction::ViewerNext);
+
+    let next_block: Box<dyn Fn(&mut TheBlocks)> = if previous {
         Box::new(|b: &mut TheBlocks| {
             for _ in 0..count {
                 b.prev_block()
             }
         })
-    } else if input.just_pressed(KeyCode::ArrowRight) {
+    } else if next {
         Box::new(|b: &mut TheBlocks| {
             for _ in 0..count {
                 b.next_block()
@@ -1,5 +1,6 @@
 mod print;
 mod save;
+mod validate;
 mod view;
 
 use clap::Parser;
@@ -16,6 +17,7 @@ struct Args {
 enum Subcommand {
     Print(print::Args),
     Save(save::Args),
+    Validate(validate::Args),
     View(view::Args),
 }
 
@@ -25,6 +27,7 @@ fn main() {
     match args.command {
         Subcommand::Print(args) => print::main(args),
         Subcommand::Save(args) => save::main(args),
+        Subcommand::Validate(args) => validate::main(args),
         Subcommand::View(args) => view::main(args),
     }
 }
@@ -3,8 +3,8 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use brine::chunk::{load_chunk, Result};
-use brine_chunk::{Chunk, ChunkSection};
+use brine::chunk::{load_block_entities, load_chunk, load_chunk_light, BlockEntities, Result};
+use brine_chunk::{Chunk, ChunkLight, ChunkSection, LightArray};
 use brine_data::{
     blocks::{BlockStateId, StateValue},
     MinecraftData,
@@ -31,8 +31,15 @@ pub(crate) fn main(args: Args) {
 fn print_chunk_from_file(path: &Path, section: Option<i16>) -> Result<()> {
     let data = MinecraftData::for_version("1.21.4");
     let chunk = load_chunk(path)?;
+    let light = load_chunk_light(path)?;
+    let block_entities = load_block_entities(path)?;
 
-    let printer = ChunkPrinter { data, chunk };
+    let printer = ChunkPrinter {
+        data,
+        chunk,
+        light,
+        block_entities,
+    };
 
     printer.print_chunk(section);
 
@@ -42,6 +49,8 @@ fn print_chunk_from_file(path: &Path, section: Option<i16>) -> Result<()> {
 pub struct ChunkPrinter {
     data: MinecraftData,
     chunk: Chunk,
+    light: Option<ChunkLight>,
+    block_entities: Option<BlockEntities>,
 }
 
 impl ChunkPrinter {
@@ -63,6 +72,20 @@ impl ChunkPrinter {
         println!();
         println!("{} Sections:", section_ys.len());
         println!("{:?}", section_ys);
+        println!();
+        match &self.block_entities {
+            Some(block_entities) => println!("Block entities: {}", block_entities.count),
+            None => println!("Block entities: not saved"),
+        }
+        match &self.light {
+            Some(light) => {
+                let sky: Vec<_> = light.sky_light_sections().map(|(y, _)| y).collect();
+                let block: Vec<_> = light.block_light_sections().map(|(y, _)| y).collect();
+                println!("Sky light sections: {:?}", sky);
+                println!("Block light sections: {:?}", block);
+            }
+            None => println!("Light: not saved"),
+        }
 
         if let Some(section_y) = section {
             let section = self
@@ -86,6 +109,20 @@ impl ChunkPrinter {
         println!();
         println!("Position: y = {}", section.chunk_y);
         println!();
+        if let Some(light) = &self.light {
+            let sky = light
+                .sky_light_sections()
+                .find(|(y, _)| *y == section.chunk_y);
+            let block = light
+                .block_light_sections()
+                .find(|(y, _)| *y == section.chunk_y);
+            println!(
+                "Sky light: {}, block light: {}",
+                light_range(sky.map(|(_, array)| array)),
+                light_range(block.map(|(_, array)| array))
+            );
+            println!();
+        }
         println!("{} Blocks:", section.block_count);
         println!();
 
@@ -135,3 +172,17 @@ impl ChunkPrinter {
         counts
     }
 }
+
+/// Describes the light levels in a section's light array.
+fn light_range(array: Option<&LightArray>) -> String {
+    let Some(array) = array else {
+        return String::from("none");
+    };
+
+    let levels = (0..16u8)
+        .flat_map(|y| (0..16u8).flat_map(move |z| (0..16u8).map(move |x| array.get(x, y, z))));
+    let (min, max) = levels.fold((15, 0), |(min, max), level| {
+        (min.min(level), max.max(level))
+    });
+    format!("{min}..={max}")
+}
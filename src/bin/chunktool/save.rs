@@ -3,10 +3,10 @@ use std::path::PathBuf;
 use bevy::{app::AppExit, prelude::*};
 
 use brine_net::CodecReader;
-use brine_proto::{event::clientbound::Disconnect, ProtocolPlugin};
-use brine_proto_backend::{backend_stevenarella::codec::ProtocolCodec, ProtocolBackendPlugin};
+use brine_proto::event::clientbound::Disconnect;
+use brine_proto_backend::ProtocolCodec;
 
-use brine::{chunk::save_packet_if_has_chunk_data, login::LoginPlugin};
+use brine::{app::BrineAppBuilder, chunk::save_packet_if_has_chunk_data, login::ReconnectPolicy};
 
 /// Reads chunk packets from a server and saves them to files.
 ///
@@ -35,28 +35,37 @@ pub struct Args {
     /// Exit after saving this many chunks.
     #[arg(short, long)]
     limit: Option<usize>,
+
+    /// How many times in a row to log in again when the connection is lost
+    /// (0 to exit on the first disconnect).
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    reconnects: u32,
 }
 
 pub fn main(args: Args) {
     let server_addr = format!("{}:{}", args.server, args.port);
 
-    App::new()
-        .add_plugins(MinimalPlugins)
-        .add_plugins(ProtocolPlugin)
-        .add_plugins(ProtocolBackendPlugin)
-        .add_plugins(LoginPlugin::new(server_addr, args.username.clone()))
+    let reconnect = ReconnectPolicy {
+        max_retries: args.reconnects,
+        ..default()
+    };
+
+    BrineAppBuilder::new()
+        .headless()
+        .with_server(server_addr)
+        .with_username(args.username.clone())
+        .with_reconnect(reconnect)
+        .exit_on_disconnect()
+        .build()
         .insert_resource(args)
         .add_systems(Update, (receive_chunks, handle_disconnect))
         .run();
 }
 
-fn handle_disconnect(
-    mut disconnect_events: MessageReader<Disconnect>,
-    mut app_exit: MessageWriter<AppExit>,
-) {
-    if let Some(disconnect) = disconnect_events.read().last() {
+/// Reports disconnects; the app exits once it stops reconnecting.
+fn handle_disconnect(mut disconnect_events: MessageReader<Disconnect>) {
+    for disconnect in disconnect_events.read() {
         println!("Disconnected from server. Reason: {}", disconnect.reason);
-        app_exit.write(AppExit::Success);
     }
 }
 
@@ -0,0 +1,543 @@
+//! Audits chunk dumps against the data pipeline.
+//!
+//! Each block is checked the way the renderer would look it up: its block
+//! state in minecraft-data, its baked models in [`MinecraftAssets`], and the
+//! texture files those models use. Blocks are then drawn as cubes colored by
+//! the first check they fail, so problems stand out across a whole directory
+//! of dumps. Faces between blocks of the same chunk and color are left out,
+//! which keeps chunk borders visible.
+//!
+//! Sections are decoded here rather than with [`Chunk::decode`], which fails
+//! the whole chunk on the first palette ID past the end of a section palette;
+//! such blocks are drawn too.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::RenderAssetUsages, prelude::*, render::render_resource::PrimitiveTopology};
+use bevy_flycam::{FlyCam, NoCameraPlayerPlugin};
+use bevy_mesh::{Indices, Mesh3d};
+
+use brine::{
+    chunk::{load_chunk_data, Error, Result},
+    input::InputMapPlugin,
+    screenshot::ScreenshotCapturePlugin,
+    settings::SettingsPlugin,
+};
+use brine_asset::MinecraftAssets;
+use brine_chunk::{
+    decode::Error as ChunkError, BlockPos, BlockState, BlockStates, Chunk, ChunkPos, ChunkSection,
+    GlobalPalette, Palette, SectionKey, SectionPalette, CHUNK_WIDTH,
+};
+use brine_data::{BlockStateId, MinecraftData};
+
+/// Renders chunks colored by validation results instead of textures.
+#[derive(clap::Args)]
+pub struct Args {
+    /// Chunk data files, or directories of them, to validate.
+    files: Vec<PathBuf>,
+
+    /// Also draw the blocks that pass every check.
+    #[arg(long)]
+    show_valid: bool,
+}
+
+/// The result of checking a block, in the order the checks run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum BlockCheck {
+    Valid,
+
+    /// The palette ID points past the end of the section palette.
+    OutOfPalette,
+
+    /// minecraft-data has no block with this state.
+    UnknownBlockState,
+
+    /// The block state has no baked model, or a model key that doesn't
+    /// resolve.
+    MissingModel,
+
+    /// A model uses a texture that isn't in the texture table or on disk.
+    MissingTexture,
+}
+
+impl BlockCheck {
+    fn color(self) -> Color {
+        match self {
+            Self::Valid => Color::srgb(0.5, 0.5, 0.5),
+            Self::OutOfPalette => Color::srgb(1.0, 0.0, 1.0),
+            Self::UnknownBlockState => Color::srgb(1.0, 0.0, 0.0),
+            Self::MissingModel => Color::srgb(1.0, 0.6, 0.0),
+            Self::MissingTexture => Color::srgb(1.0, 1.0, 0.0),
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Valid => "valid (gray)",
+            Self::OutOfPalette => "palette ID out of range (magenta)",
+            Self::UnknownBlockState => "unknown block state (red)",
+            Self::MissingModel => "missing model (orange)",
+            Self::MissingTexture => "missing texture (yellow)",
+        }
+    }
+}
+
+/// Stands in for palette IDs past the end of a section palette. No real
+/// block state gets anywhere near it.
+const OUT_OF_PALETTE: BlockState = BlockState(u32::MAX);
+
+/// A palette that maps IDs its inner palette doesn't know to
+/// [`OUT_OF_PALETTE`] instead of failing.
+struct LenientPalette<'a, P>(&'a P);
+
+impl<P: Palette> Palette for LenientPalette<'_, P> {
+    fn id_to_block_state(&self, id: u32) -> Option<BlockState> {
+        Some(self.0.id_to_block_state(id).unwrap_or(OUT_OF_PALETTE))
+    }
+}
+
+pub(crate) fn main(args: Args) {
+    let mc_data = MinecraftData::for_version("1.21.4");
+
+    println!("Loading Assets");
+    let mc_assets = MinecraftAssets::new("assets/1.21.4", &mc_data).unwrap();
+
+    let mut validator = Validator::new(&mc_data, &mc_assets);
+    let mut chunks = Vec::new();
+    for path in expand_directories(&args.files) {
+        match load_sections(&path) {
+            Ok((chunk_x, chunk_z, sections)) => {
+                chunks.push(validator.check_chunk(ChunkPos::new(chunk_x, chunk_z), &sections));
+            }
+            Err(e) => println!("ERROR: {}: {}", path.display(), e),
+        }
+    }
+
+    validator.print_summary(chunks.len());
+
+    if chunks.is_empty() {
+        return;
+    }
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((SettingsPlugin::default(), InputMapPlugin))
+        .add_plugins(NoCameraPlayerPlugin)
+        .add_plugins(ScreenshotCapturePlugin::default())
+        .insert_resource(CheckedChunks {
+            chunks,
+            show_valid: args.show_valid,
+        })
+        .add_systems(Startup, spawn_checked_chunks)
+        .run();
+}
+
+/// Replaces directories with the chunk dumps in them.
+fn expand_directories(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("ERROR: {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let mut dumps = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_chunk_dump(path))
+            .collect::<Vec<_>>();
+        dumps.sort();
+        files.extend(dumps);
+    }
+    files
+}
+
+fn is_chunk_dump(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.ends_with(".dump") && !name.starts_with("chunk_light_")
+}
+
+/// Loads the sections of a chunk dump with a [`LenientPalette`].
+fn load_sections(path: &Path) -> Result<(i32, i32, Vec<ChunkSection>)> {
+    let chunk_data = load_chunk_data(path)?;
+    let mut data = chunk_data.data.as_slice();
+
+    let sections = Chunk::bitmask_to_section_y_coordinates(chunk_data.bitmask)
+        .into_iter()
+        .map(|chunk_y| decode_section(chunk_y, &mut data))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|source| Error::Chunk {
+            chunk_x: chunk_data.chunk_x,
+            chunk_z: chunk_data.chunk_z,
+            source,
+        })?;
+
+    Ok((chunk_data.chunk_x, chunk_data.chunk_z, sections))
+}
+
+/// [`ChunkSection::decode`], except that palette IDs out of range decode as
+/// [`OUT_OF_PALETTE`].
+fn decode_section(
+    chunk_y: i16,
+    data: &mut impl io::Read,
+) -> std::result::Result<ChunkSection, ChunkError> {
+    let mut header = [0; 3];
+    data.read_exact(&mut header)?;
+    let block_count = u16::from_be_bytes([header[0], header[1]]);
+
+    // Protocol spec says any value below 4 should be treated as 4.
+    let bits_per_block = header[2].max(4);
+
+    let block_states = if bits_per_block <= SectionPalette::MAX_BITS_PER_BLOCK {
+        let palette = SectionPalette::decode(&GlobalPalette, data)?;
+        BlockStates::decode(bits_per_block, &LenientPalette(&palette), data)?
+    } else {
+        BlockStates::decode(bits_per_block, &GlobalPalette, data)?
+    };
+
+    Ok(ChunkSection {
+        chunk_y,
+        block_count,
+        block_states,
+    })
+}
+
+/// Checks block states against minecraft-data and the baked assets, caching
+/// the result of each state.
+struct Validator<'a> {
+    mc_data: &'a MinecraftData,
+    mc_assets: &'a MinecraftAssets,
+
+    /// `None` for air, which isn't drawn.
+    checks: HashMap<BlockState, Option<BlockCheck>>,
+
+    /// How many blocks failed (or passed) each check, per block state.
+    counts: BTreeMap<BlockCheck, BTreeMap<u32, usize>>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(mc_data: &'a MinecraftData, mc_assets: &'a MinecraftAssets) -> Self {
+        Self {
+            mc_data,
+            mc_assets,
+            checks: HashMap::new(),
+            counts: BTreeMap::new(),
+        }
+    }
+
+    fn check_chunk(&mut self, position: ChunkPos, sections: &[ChunkSection]) -> CheckedChunk {
+        let mut checked = CheckedChunk {
+            position,
+            sections: HashMap::new(),
+        };
+
+        for section in sections {
+            let checks = section
+                .block_states
+                .0
+                .iter()
+                .map(|&block_state| {
+                    let check = self.check(block_state)?;
+                    *self
+                        .counts
+                        .entry(check)
+                        .or_default()
+                        .entry(block_state.0)
+                        .or_default() += 1;
+                    Some(check)
+                })
+                .collect();
+            checked.sections.insert(section.chunk_y, checks);
+        }
+
+        checked
+    }
+
+    fn check(&mut self, block_state: BlockState) -> Option<BlockCheck> {
+        if let Some(&check) = self.checks.get(&block_state) {
+            return check;
+        }
+
+        let check = self.check_uncached(block_state);
+        self.checks.insert(block_state, check);
+        check
+    }
+
+    fn check_uncached(&self, block_state: BlockState) -> Option<BlockCheck> {
+        if block_state == OUT_OF_PALETTE {
+            return Some(BlockCheck::OutOfPalette);
+        }
+
+        let Some(block_state_id) = u16::try_from(block_state.0).ok().map(BlockStateId) else {
+            return Some(BlockCheck::UnknownBlockState);
+        };
+        let Some(block) = self.mc_data.blocks().get_by_state_id(block_state_id) else {
+            return Some(BlockCheck::UnknownBlockState);
+        };
+        if matches!(block.name, "air" | "cave_air" | "void_air") {
+            return None;
+        }
+
+        let Some(baked) = self.mc_assets.block_states().get_by_key(block_state_id) else {
+            return Some(BlockCheck::MissingModel);
+        };
+        let model_keys = baked
+            .models
+            .iter()
+            .flat_map(|grab_bag| grab_bag.choices.iter().copied())
+            .collect::<Vec<_>>();
+        if model_keys.is_empty() {
+            return Some(BlockCheck::MissingModel);
+        }
+
+        let mut textures = Vec::new();
+        for model_key in model_keys {
+            let Some(model) = self.mc_assets.models().get_by_key(model_key) else {
+                return Some(BlockCheck::MissingModel);
+            };
+            textures.extend(model.quads.iter().map(|quad| quad.texture));
+        }
+
+        let texture_exists = |texture| {
+            self.mc_assets
+                .get_texture_path(texture)
+                .is_some_and(|path| Path::new("assets").join(path).exists())
+        };
+        if !textures.into_iter().all(texture_exists) {
+            return Some(BlockCheck::MissingTexture);
+        }
+
+        Some(BlockCheck::Valid)
+    }
+
+    fn print_summary(&self, chunk_count: usize) {
+        println!("Checked {} chunks", chunk_count);
+
+        for (check, states) in &self.counts {
+            let blocks: usize = states.values().sum();
+            println!("{}: {} blocks", check.description(), blocks);
+
+            if *check == BlockCheck::Valid {
+                continue;
+            }
+
+            for (state, count) in states {
+                let block = u16::try_from(*state)
+                    .ok()
+                    .and_then(|id| self.mc_data.blocks().get_by_state_id(BlockStateId(id)));
+                match block {
+                    Some(block) => println!("  {} ({}): {}", state, block.name, count),
+                    None => println!("  {}: {}", state, count),
+                }
+            }
+        }
+    }
+}
+
+/// The check results of each block of a chunk, by section.
+struct CheckedChunk {
+    position: ChunkPos,
+    sections: HashMap<i16, Vec<Option<BlockCheck>>>,
+}
+
+impl CheckedChunk {
+    /// Returns the check result of the block at `block`, or `None` for air
+    /// and blocks outside the chunk.
+    fn get(&self, block: BlockPos) -> Option<BlockCheck> {
+        if block.chunk() != self.position {
+            return None;
+        }
+
+        let section = self.sections.get(&(block.section().y as i16))?;
+        let key = block.section_key();
+        section[BlockStates::xyz_to_index(key.x, key.y, key.z)]
+    }
+
+    /// Builds a mesh of the faces of the blocks with the given check result
+    /// that don't touch another such block, or `None` if there are none.
+    fn build_mesh(&self, check: BlockCheck) -> Option<Mesh> {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+
+        for (&chunk_y, section) in &self.sections {
+            for (index, block) in section.iter().enumerate() {
+                if *block != Some(check) {
+                    continue;
+                }
+
+                let (x, y, z) = BlockStates::index_to_xyz(index);
+                let block = self
+                    .position
+                    .section(chunk_y.into())
+                    .block(SectionKey { x, y, z });
+                // Relative to the chunk's origin, where its meshes are placed.
+                let pos = block - self.position.origin();
+
+                for (normal, corners) in FACES {
+                    if self.get(block + normal) == Some(check) {
+                        continue;
+                    }
+
+                    let first = positions.len() as u32;
+                    positions.extend(corners.map(|corner| (pos.as_vec3() + corner).to_array()));
+                    normals.extend([normal.as_vec3().to_array(); 4]);
+                    indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+                }
+            }
+        }
+
+        if positions.is_empty() {
+            return None;
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_indices(Indices::U32(indices));
+        Some(mesh)
+    }
+}
+
+/// The outward normal and counter-clockwise corners of each face of a unit
+/// cube.
+const FACES: [(IVec3, [Vec3; 4]); 6] = [
+    (
+        IVec3::X,
+        [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+        ],
+    ),
+    (
+        IVec3::NEG_X,
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ],
+    ),
+    (
+        IVec3::Y,
+        [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        ],
+    ),
+    (
+        IVec3::NEG_Y,
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ],
+    ),
+    (
+        IVec3::Z,
+        [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        ],
+    ),
+    (
+        IVec3::NEG_Z,
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ],
+    ),
+];
+
+#[derive(Resource)]
+struct CheckedChunks {
+    chunks: Vec<CheckedChunk>,
+    show_valid: bool,
+}
+
+fn spawn_checked_chunks(
+    checked: Res<CheckedChunks>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let checks = [
+        BlockCheck::Valid,
+        BlockCheck::OutOfPalette,
+        BlockCheck::UnknownBlockState,
+        BlockCheck::MissingModel,
+        BlockCheck::MissingTexture,
+    ]
+    .into_iter()
+    .filter(|&check| checked.show_valid || check != BlockCheck::Valid)
+    .map(|check| {
+        let material = materials.add(StandardMaterial {
+            base_color: check.color(),
+            ..default()
+        });
+        (check, material)
+    })
+    .collect::<Vec<_>>();
+
+    for chunk in &checked.chunks {
+        let translation = chunk.position.origin().as_vec3();
+
+        for (check, material) in &checks {
+            let Some(mesh) = chunk.build_mesh(*check) else {
+                continue;
+            };
+
+            commands.spawn((
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(translation),
+                Name::new(format!("Chunk {}: {:?}", chunk.position, check)),
+            ));
+        }
+    }
+
+    let center = checked
+        .chunks
+        .iter()
+        .map(|chunk| chunk.position.origin().as_vec3().xz() + CHUNK_WIDTH as f32 / 2.0)
+        .sum::<Vec2>()
+        / checked.chunks.len() as f32;
+
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(1.0, 3.0, 2.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    commands.spawn((
+        Camera3d::default(),
+        Msaa::Sample4,
+        FlyCam,
+        Transform::from_xyz(center.x - 48.0, 128.0, center.y - 48.0)
+            .looking_at(Vec3::new(center.x, 64.0, center.y), Vec3::Y),
+    ));
+}
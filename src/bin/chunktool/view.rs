@@ -4,7 +4,6 @@ use std::{
 };
 
 use bevy::{
-    input::ButtonInput,
     log::{Level, LogPlugin},
     pbr::wireframe::{WireframeConfig, WireframePlugin},
     prelude::*,
@@ -16,7 +15,6 @@ use bevy::{
 };
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
-use brine_asset::MinecraftAssets;
 use brine_chunk::{Chunk, ChunkSection};
 use brine_data::MinecraftData;
 use brine_proto::{event, ProtocolPlugin};
@@ -30,8 +28,13 @@ use brine_voxel_v1::{
 };
 
 use brine::{
-    chunk::{load_chunk, Result},
+    chunk::{load_block_entities, load_chunk, load_chunk_light, Result},
     error::log_error,
+    gamepad::ControllerSettings,
+    input::{InputAction, InputMap, InputMapPlugin},
+    loading::AssetLoadingPlugin,
+    screenshot::ScreenshotCapturePlugin,
+    settings::SettingsPlugin,
     DEFAULT_LOG_FILTER,
 };
 use clap::ValueEnum;
@@ -87,6 +90,21 @@ impl Chunks {
     fn load_next_file(&mut self) -> Result<()> {
         let path = self.next_file();
         let chunk = load_chunk(path)?;
+
+        let block_entities = load_block_entities(path)?.map(|block_entities| block_entities.count);
+        let light_sections = load_chunk_light(path)?.map(|light| {
+            (
+                light.sky_light_sections().count(),
+                light.block_light_sections().count(),
+            )
+        });
+        info!(
+            "Loaded {}: block entities {:?}, sky/block light sections {:?}",
+            path.display(),
+            block_entities,
+            light_sections
+        );
+
         self.next_section = chunk.sections.len() - 1;
         self.chunk = Some(chunk);
         Ok(())
@@ -148,13 +166,17 @@ pub fn main(args: Args) {
         WireframePlugin::default(),
         WorldInspectorPlugin::new(),
         ProtocolPlugin,
+        SettingsPlugin::default(),
+        InputMapPlugin,
+        ScreenshotCapturePlugin::default(),
     ));
 
     let mc_data = MinecraftData::for_version("1.21.4");
-    let mc_assets = MinecraftAssets::new("assets/1.21.4", &mc_data).unwrap();
     app.insert_resource(mc_data);
-    app.insert_resource(mc_assets);
-    app.add_plugins(TextureBuilderPlugin);
+    app.add_plugins((
+        AssetLoadingPlugin::new("assets/1.21.4"),
+        TextureBuilderPlugin,
+    ));
 
     app.add_plugins(ChunkBuilderPlugin::<NaiveBlocksChunkBuilder>::shared());
 
@@ -186,14 +208,15 @@ fn load_first_chunk(
 }
 
 fn load_next_chunk(
-    input: Res<ButtonInput<KeyCode>>,
+    input: InputMap,
     mut chunks: ResMut<Chunks>,
     mut chunk_events: MessageWriter<event::clientbound::ChunkData>,
     query: Query<Entity, With<BuiltChunk>>,
     mut commands: Commands,
 ) -> Result<()> {
-    let should_show_next = input.just_pressed(KeyCode::Enter) || input.just_pressed(KeyCode::Space);
-    let should_load_next_file = input.just_pressed(KeyCode::Enter);
+    let should_load_next_file = input.just_pressed(InputAction::ViewerNextFile);
+    let should_show_next =
+        should_load_next_file || input.just_pressed(InputAction::ViewerNextSection);
 
     if should_load_next_file {
         chunks.load_next_file()?;
@@ -230,6 +253,7 @@ impl Plugin for ChunkViewerPlugin {
                 Self::rename_chunks,
                 Self::move_and_rotate,
                 Self::rotate_chunk,
+                Self::rotate_chunk_with_gamepad,
             ),
         );
     }
@@ -268,21 +292,56 @@ impl ChunkViewerPlugin {
         }
     }
 
-    fn rotate_chunk(
-        input: Res<ButtonInput<KeyCode>>,
-        mut query: Query<&mut Transform, With<BuiltChunk>>,
-    ) {
+    fn rotate_chunk(input: InputMap, mut query: Query<&mut Transform, With<BuiltChunk>>) {
+        let turns = [
+            (
+                InputAction::ViewerRotateLeft,
+                Quat::from_rotation_y(-PI / 4.0),
+            ),
+            (
+                InputAction::ViewerRotateRight,
+                Quat::from_rotation_y(PI / 4.0),
+            ),
+            (
+                InputAction::ViewerRotateDown,
+                Quat::from_rotation_x(PI / 4.0),
+            ),
+            (
+                InputAction::ViewerRotateUp,
+                Quat::from_rotation_x(-PI / 4.0),
+            ),
+        ];
+
         for mut transform in query.iter_mut() {
-            for keypress in input.get_just_pressed() {
-                match keypress {
-                    KeyCode::ArrowLeft => transform.rotate(Quat::from_rotation_y(-PI / 4.0)),
-                    KeyCode::ArrowRight => transform.rotate(Quat::from_rotation_y(PI / 4.0)),
-                    KeyCode::ArrowDown => transform.rotate(Quat::from_rotation_x(PI / 4.0)),
-                    KeyCode::ArrowUp => transform.rotate(Quat::from_rotation_x(-PI / 4.0)),
-                    KeyCode::Escape => transform.rotation = Quat::from_rotation_y(PI / 4.0),
-                    _ => {}
+            for (action, turn) in turns {
+                if input.just_pressed(action) {
+                    transform.rotate(turn);
                 }
             }
+            if input.just_pressed(InputAction::ViewerResetView) {
+                transform.rotation = Quat::from_rotation_y(PI / 4.0);
+            }
+        }
+    }
+
+    fn rotate_chunk_with_gamepad(
+        time: Res<Time>,
+        input: InputMap,
+        settings: Res<ControllerSettings>,
+        mut query: Query<&mut Transform, With<BuiltChunk>>,
+    ) {
+        let stick = input
+            .gamepads()
+            .map(|gamepad| gamepad.right_stick())
+            .sum::<Vec2>()
+            .clamp_length_max(1.0);
+        if stick == Vec2::ZERO {
+            return;
+        }
+
+        let angle = stick * settings.look_sensitivity * time.delta_secs();
+        for mut transform in query.iter_mut() {
+            transform.rotate(Quat::from_rotation_y(angle.x) * Quat::from_rotation_x(-angle.y));
         }
     }
 }
@@ -0,0 +1,381 @@
+//! Breaking and placing blocks, with the results shown before the server
+//! confirms them.
+//!
+//! Holding the left mouse button (or the [`Attack`][InputAction::Attack]
+//! binding) digs the [`TargetedBlock`]. Blocks break as soon as digging
+//! starts in creative mode, or for blocks with no hardness; otherwise the
+//! player has to keep digging the same block for its
+//! [`dig_ticks`][brine_data::blocks::Block::dig_ticks], which assumes bare
+//! hands, so tools only make the server-side digging faster. Pressing the
+//! right mouse button (or [`UseItem`][InputAction::UseItem]) while holding a
+//! block item places that block against the targeted face.
+//!
+//! Each action draws a number from [`ActionSequence`] and the block it
+//! changes is updated right away through [`PredictBlockChange`], so breaking
+//! and placing don't wait for a round trip to the server. The
+//! [`BlockUpdatePlugin`][crate::block_update::BlockUpdatePlugin] puts the
+//! server's version of the block back once it acknowledges the action, which
+//! corrects e.g. the orientation of placed stairs (always predicted in their
+//! default state) and undoes edits the server refused.
+
+use bevy::prelude::*;
+use brine_chunk::BlockState;
+use brine_data::{BlockStateId, ItemId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{Reconfigure, Transfer},
+        serverbound::{DigBlock, PlaceBlock},
+        BlockFace, DigStatus, GameMode, Hand, ItemStack,
+    },
+    ActionSequence, BrineSystems,
+};
+
+use crate::{
+    block_update::PredictBlockChange,
+    container::no_screen_open,
+    game_mode::{PlayerAbilities, PlayerGameMode},
+    hotbar::Hotbar,
+    input::{InputAction, InputMap},
+    sound::{BlockSound, BlockSoundKind},
+    targeting::{BlockHit, LoadedBlocks, TargetedBlock},
+};
+
+/// Ticks to wait after breaking a block before digging the next one while
+/// the button is held.
+const BREAK_COOLDOWN_TICKS: u32 = 5;
+
+/// A block the local player is digging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveDig {
+    pub position: IVec3,
+    pub face: BlockFace,
+
+    /// Game ticks since the player started digging.
+    pub ticks: u32,
+
+    /// Game ticks it takes for the block to break.
+    pub total_ticks: u32,
+}
+
+impl ActiveDig {
+    /// Returns how far along digging the block is (`0.0..=1.0`).
+    pub fn progress(&self) -> f32 {
+        (self.ticks as f32 / self.total_ticks.max(1) as f32).min(1.0)
+    }
+
+    fn finished(&self) -> bool {
+        self.ticks >= self.total_ticks
+    }
+}
+
+/// The block the local player is digging, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Digging {
+    pub active: Option<ActiveDig>,
+
+    /// Ticks left before another block can be broken.
+    cooldown: u32,
+}
+
+impl Digging {
+    fn broke_block(&mut self) {
+        self.active = None;
+        self.cooldown = BREAK_COOLDOWN_TICKS;
+    }
+}
+
+/// Plugin that lets the player break and place blocks, predicting the
+/// results.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`Transfer`] and [`Reconfigure`], which stop digging
+///
+/// The plugin sends the following events:
+///
+/// * [`DigBlock`]
+/// * [`PlaceBlock`]
+/// * [`PredictBlockChange`]
+/// * [`BlockSound`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Digging`]
+///
+/// # Ordering
+///
+/// Digging progresses once per game tick, in [`FixedUpdate`].
+pub struct BlockInteractionPlugin;
+
+impl Plugin for BlockInteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Digging>()
+            .add_systems(FixedUpdate, tick_digging)
+            .add_systems(
+                Update,
+                (
+                    reset_digging,
+                    (dig_blocks, place_blocks).run_if(no_screen_open),
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn reset_digging(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut digging: ResMut<Digging>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        digging.set_if_neq(Digging::default());
+    }
+}
+
+fn tick_digging(mut digging: ResMut<Digging>) {
+    digging.cooldown = digging.cooldown.saturating_sub(1);
+    if let Some(active) = &mut digging.active {
+        active.ticks += 1;
+    }
+}
+
+/// Returns whether the player's game mode lets them change blocks.
+fn may_edit_blocks(game_mode: GameMode) -> bool {
+    matches!(game_mode, GameMode::Survival | GameMode::Creative)
+}
+
+/// Returns the face of a block hit by a ray. Rays starting inside the block
+/// hit its top.
+fn hit_face(hit: &BlockHit) -> BlockFace {
+    BlockFace::from_normal(hit.normal).unwrap_or(BlockFace::Up)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn dig_blocks(
+    input: InputMap,
+    mouse: Res<ButtonInput<MouseButton>>,
+    game_mode: Res<PlayerGameMode>,
+    abilities: Res<PlayerAbilities>,
+    targeted: Res<TargetedBlock>,
+    mc_data: Res<MinecraftData>,
+    mut digging: ResMut<Digging>,
+    mut sequence: ResMut<ActionSequence>,
+    mut dig_events: MessageWriter<DigBlock>,
+    mut predict_events: MessageWriter<PredictBlockChange>,
+    mut sound_events: MessageWriter<BlockSound>,
+) {
+    let pressed = mouse.pressed(MouseButton::Left) || input.pressed(InputAction::Attack);
+    let target = targeted
+        .0
+        .filter(|_| pressed && may_edit_blocks(**game_mode));
+
+    // Looking away from the block or letting go of the button stops digging.
+    if let Some(active) = digging.active {
+        if target.map(|hit| hit.position) != Some(active.position) {
+            dig_events.write(DigBlock {
+                position: active.position,
+                face: active.face,
+                status: DigStatus::Cancel,
+                sequence: sequence.next(),
+            });
+            digging.active = None;
+        }
+    }
+
+    let Some(hit) = target else {
+        return;
+    };
+
+    let mut break_block = |status: DigStatus| {
+        let sequence = sequence.next();
+        dig_events.write(DigBlock {
+            position: hit.position,
+            face: hit_face(&hit),
+            status,
+            sequence,
+        });
+        predict_events.write(PredictBlockChange {
+            position: hit.position,
+            block_state: BlockState::AIR,
+            sequence,
+        });
+        sound_events.write(BlockSound {
+            block_state: BlockStateId(hit.block_state.0 as u16),
+            kind: BlockSoundKind::Break,
+        });
+    };
+
+    if let Some(active) = digging.active {
+        if active.finished() {
+            break_block(DigStatus::Finish);
+            digging.broke_block();
+        }
+        return;
+    }
+
+    if digging.cooldown > 0 {
+        return;
+    }
+
+    // Creative mode breaks even blocks that can't be dug, like bedrock.
+    if abilities.instant_break {
+        break_block(DigStatus::Start);
+        digging.broke_block();
+        return;
+    }
+
+    let Some(dig_ticks) = mc_data
+        .blocks()
+        .get_by_state_id(BlockStateId(hit.block_state.0 as u16))
+        .and_then(|block| block.dig_ticks())
+    else {
+        return;
+    };
+
+    if dig_ticks == 0 {
+        break_block(DigStatus::Start);
+        digging.broke_block();
+        return;
+    }
+
+    let face = hit_face(&hit);
+    dig_events.write(DigBlock {
+        position: hit.position,
+        face,
+        status: DigStatus::Start,
+        sequence: sequence.next(),
+    });
+    digging.active = Some(ActiveDig {
+        position: hit.position,
+        face,
+        ticks: 0,
+        total_ticks: dig_ticks,
+    });
+}
+
+/// Returns the default state of the block placed by an item, if it places
+/// one.
+///
+/// Block items are named after their blocks; items that place a block under
+/// another name (e.g., `redstone`, `string`) aren't recognized.
+pub fn placed_block(mc_data: &MinecraftData, item: Option<ItemStack>) -> Option<BlockState> {
+    let item_id = u16::try_from(item?.item_id).ok()?;
+    let item = mc_data.items().get_by_id(ItemId(item_id))?;
+    let state_id = mc_data.blocks().default_state_id_by_name(item.name)?;
+    Some(BlockState(state_id.0 as u32))
+}
+
+/// Returns the hand holding a block item and the block it places, main hand
+/// first.
+pub fn placing_hand(mc_data: &MinecraftData, hotbar: &Hotbar) -> Option<(Hand, BlockState)> {
+    [Hand::Main, Hand::Off]
+        .into_iter()
+        .find_map(|hand| Some((hand, placed_block(mc_data, hotbar.item_in(hand))?)))
+}
+
+/// Returns where on the hit block's face the ray hit it, from `0.0` to `1.0`
+/// on each axis.
+fn cursor_on_block(hit: &BlockHit, origin: Vec3, direction: Vec3) -> Vec3 {
+    let point = origin + direction.normalize_or_zero() * hit.distance;
+    (point - hit.position.as_vec3()).clamp(Vec3::ZERO, Vec3::ONE)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place_blocks(
+    input: InputMap,
+    mouse: Res<ButtonInput<MouseButton>>,
+    game_mode: Res<PlayerGameMode>,
+    targeted: Res<TargetedBlock>,
+    hotbar: Res<Hotbar>,
+    mc_data: Res<MinecraftData>,
+    blocks: LoadedBlocks,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut sequence: ResMut<ActionSequence>,
+    mut place_events: MessageWriter<PlaceBlock>,
+    mut predict_events: MessageWriter<PredictBlockChange>,
+    mut sound_events: MessageWriter<BlockSound>,
+) {
+    let just_pressed =
+        mouse.just_pressed(MouseButton::Right) || input.just_pressed(InputAction::UseItem);
+    if !just_pressed || !may_edit_blocks(**game_mode) {
+        return;
+    }
+
+    let (Some(hit), Ok(camera)) = (targeted.0, cameras.single()) else {
+        return;
+    };
+    let Some((hand, block_state)) = placing_hand(&mc_data, &hotbar) else {
+        return;
+    };
+
+    // Rays starting inside a block have nowhere to put the new one.
+    let Some(face) = BlockFace::from_normal(hit.normal) else {
+        return;
+    };
+    let position = hit.position + face.normal();
+    if blocks.solid_blocks()(position).is_some() {
+        return;
+    }
+
+    let sequence = sequence.next();
+    place_events.write(PlaceBlock {
+        hand,
+        position: hit.position,
+        face,
+        cursor: cursor_on_block(&hit, camera.translation(), *camera.forward()),
+        sequence,
+    });
+    predict_events.write(PredictBlockChange {
+        position,
+        block_state,
+        sequence,
+    });
+    sound_events.write(BlockSound {
+        block_state: BlockStateId(block_state.0 as u16),
+        kind: BlockSoundKind::Place,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digging_finishes_after_total_ticks() {
+        let mut active = ActiveDig {
+            position: IVec3::ZERO,
+            face: BlockFace::Up,
+            ticks: 0,
+            total_ticks: 45,
+        };
+        assert!(!active.finished());
+
+        active.ticks = 30;
+        assert!((active.progress() - 2.0 / 3.0).abs() < 1e-6);
+
+        active.ticks = 45;
+        assert!(active.finished());
+        assert_eq!(active.progress(), 1.0);
+    }
+
+    #[test]
+    fn cursor_is_relative_to_the_block() {
+        let hit = BlockHit {
+            position: IVec3::new(-3, 64, 5),
+            block_state: BlockState(1),
+            normal: IVec3::Y,
+            distance: 2.0,
+        };
+
+        let cursor = cursor_on_block(&hit, Vec3::new(-2.5, 67.0, 5.25), Vec3::NEG_Y);
+        assert!(cursor.abs_diff_eq(Vec3::new(0.5, 1.0, 0.25), 1e-6));
+    }
+}
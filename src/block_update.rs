@@ -0,0 +1,485 @@
+//! Keeping the loaded chunks up to date with block changes.
+//!
+//! Each [`BlockChanged`] the server sends is written into the
+//! [`ChunkSection`] of the loaded chunk it falls in, which is then remeshed
+//! ([`RemeshChunk`]). Changes to chunks that aren't built yet are dropped;
+//! the server sends chunks with their blocks as they are when sent. The
+//! changes are taken from a [`MessageBuffer`], so none are lost to a frame
+//! the plugin's systems don't run in.
+//!
+//! Redstone and the like can change blocks every tick, and the chunk builders
+//! rebuild whole chunks, so remeshes go through the [`RemeshQueue`]: changes
+//! are collected by section, a chunk is remeshed at most once every
+//! [`MIN_REMESH_INTERVAL`] (the first change after a quiet spell at once),
+//! and no more than [`MAX_REMESHES_PER_FRAME`] chunks start remeshing a
+//! frame, those with changes nearest the camera first. A chunk being remeshed
+//! is gone until its new meshes are ready, so changes to it meanwhile are held
+//! and applied to the rebuilt chunk.
+//!
+//! The client can also change blocks itself ahead of the server, e.g. as soon
+//! as the player breaks one, with a [`PredictBlockChange`] carrying the
+//! [`ActionSequence`] number of the action sent for it. As in vanilla, a
+//! predicted block stays until the server acknowledges that sequence number
+//! ([`BlockChangesAcknowledged`]); changes the server sends for it in the
+//! meantime are only remembered. Once acknowledged, the block is set to what
+//! the server last said it is, or back to what it was before the prediction
+//! if the server said nothing, which rolls back the changes it rejected.
+//!
+//! [`ActionSequence`]: brine_proto::ActionSequence
+
+use std::time::Duration;
+
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use brine_chunk::{BlockPos, BlockState, BlockStates, ChunkPos, SectionPos};
+use brine_proto::{
+    event::clientbound::{BlockChanged, BlockChangesAcknowledged, Reconfigure, Transfer},
+    BrineSystems, MessageBuffer, MessageBufferPlugin,
+};
+use brine_voxel_v1::chunk_builder::component::{BuiltChunk, ChunkSection, Rebuilt, RemeshChunk};
+
+/// Shortest time between two remeshes of a chunk for block changes.
+pub const MIN_REMESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Most chunks that start remeshing for block changes in a frame.
+pub const MAX_REMESHES_PER_FRAME: usize = 4;
+
+/// Changes a block locally before the server has confirmed the change.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PredictBlockChange {
+    /// World position of the block.
+    pub position: IVec3,
+
+    /// The block's predicted state.
+    pub block_state: BlockState,
+
+    /// Sequence number of the action sent to the server for the change.
+    pub sequence: i32,
+}
+
+/// A block change to apply: where, to what, and the sequence number of the
+/// action if it is a prediction.
+type Change = (IVec3, BlockState, Option<i32>);
+
+/// A block changed ahead of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Prediction {
+    /// Sequence number of the latest action that changed the block.
+    sequence: i32,
+
+    /// The block's state according to the server.
+    server_state: BlockState,
+}
+
+/// Blocks changed ahead of the server whose actions it hasn't acknowledged
+/// yet.
+#[derive(Resource, Debug, Default)]
+pub struct PredictedBlocks {
+    predictions: HashMap<IVec3, Prediction>,
+}
+
+impl PredictedBlocks {
+    /// Returns whether the block at `position` is waiting for the server.
+    pub fn is_predicted(&self, position: IVec3) -> bool {
+        self.predictions.contains_key(&position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.predictions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predictions.is_empty()
+    }
+
+    /// Records a prediction for the block at `position`, whose state was
+    /// `previous` before it.
+    fn predict(&mut self, position: IVec3, sequence: i32, previous: BlockState) {
+        self.predictions
+            .entry(position)
+            .and_modify(|prediction| prediction.sequence = sequence)
+            .or_insert(Prediction {
+                sequence,
+                server_state: previous,
+            });
+    }
+
+    /// Handles a block change from the server. Returns whether to apply it,
+    /// rather than keep the predicted block until the acknowledgement.
+    fn server_change(&mut self, position: IVec3, block_state: BlockState) -> bool {
+        match self.predictions.get_mut(&position) {
+            Some(prediction) => {
+                prediction.server_state = block_state;
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Ends the predictions made up to and including `sequence`, returning
+    /// the server's state of each of their blocks.
+    fn acknowledge(&mut self, sequence: i32) -> Vec<(IVec3, BlockState)> {
+        let mut settled = Vec::new();
+        self.predictions.retain(|position, prediction| {
+            if prediction.sequence > sequence {
+                return true;
+            }
+            settled.push((*position, prediction.server_state));
+            false
+        });
+        settled
+    }
+}
+
+/// Chunks waiting to be remeshed for block changes. See the
+/// [module documentation][self].
+#[derive(Resource, Debug, Default)]
+pub struct RemeshQueue {
+    /// Sections changed since their chunk last started remeshing.
+    dirty: HashMap<ChunkPos, HashSet<SectionPos>>,
+
+    /// When each chunk last started remeshing, as time since startup, for
+    /// as long as that holds back the next remesh.
+    last_remeshed: HashMap<ChunkPos, Duration>,
+
+    /// Chunks being remeshed, with the changes to them held until they are
+    /// back.
+    remeshing: HashMap<ChunkPos, Vec<Change>>,
+}
+
+impl RemeshQueue {
+    /// Returns the number of chunks with changes waiting to be remeshed.
+    pub fn len(&self) -> usize {
+        self.dirty.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirty.is_empty()
+    }
+
+    /// Records that a block in `section` changed.
+    fn mark(&mut self, section: SectionPos) {
+        self.dirty
+            .entry(section.chunk())
+            .or_default()
+            .insert(section);
+    }
+
+    /// Holds `change` for `chunk` if it is being remeshed. Returns whether it
+    /// was held.
+    fn hold(&mut self, chunk: ChunkPos, change: Change) -> bool {
+        match self.remeshing.get_mut(&chunk) {
+            Some(held) => {
+                held.push(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes up to `limit` chunks that may be remeshed at `now`, those with
+    /// changes nearest `camera` first.
+    fn take_ready(&mut self, now: Duration, camera: Option<Vec3>, limit: usize) -> Vec<ChunkPos> {
+        self.last_remeshed
+            .retain(|_, remeshed| now.saturating_sub(*remeshed) < MIN_REMESH_INTERVAL);
+
+        let mut ready: Vec<(ChunkPos, f32)> = self
+            .dirty
+            .iter()
+            .filter(|(chunk, _)| !self.last_remeshed.contains_key(*chunk))
+            .map(|(chunk, sections)| {
+                let distance = camera.map_or(0.0, |camera| {
+                    sections
+                        .iter()
+                        .map(|section| {
+                            let center = section.origin().as_vec3() + Vec3::splat(8.0);
+                            center.distance_squared(camera)
+                        })
+                        .fold(f32::INFINITY, f32::min)
+                });
+                (*chunk, distance)
+            })
+            .collect();
+        ready.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        ready.truncate(limit);
+
+        ready
+            .into_iter()
+            .map(|(chunk, _)| {
+                self.dirty.remove(&chunk);
+                chunk
+            })
+            .collect()
+    }
+
+    /// Records that `chunk` started remeshing at `now`.
+    fn start_remesh(&mut self, chunk: ChunkPos, now: Duration) {
+        self.last_remeshed.insert(chunk, now);
+        self.remeshing.entry(chunk).or_default();
+    }
+
+    /// Ends the remesh of `chunk`, returning the changes held for it.
+    fn finish_remesh(&mut self, chunk: ChunkPos) -> Vec<Change> {
+        self.remeshing.remove(&chunk).unwrap_or_default()
+    }
+
+    fn clear(&mut self) {
+        self.dirty.clear();
+        self.last_remeshed.clear();
+        self.remeshing.clear();
+    }
+}
+
+/// Plugin that applies block changes to the loaded chunks and rolls back
+/// predicted ones the server rejects.
+///
+/// Requires a chunk builder.
+///
+/// # Events
+///
+/// The plugin registers the following event types:
+///
+/// * [`PredictBlockChange`]
+///
+/// The plugin reads the following events:
+///
+/// * [`PredictBlockChange`]
+/// * [`BlockChanged`]
+/// * [`BlockChangesAcknowledged`]
+/// * [`Transfer`] and [`Reconfigure`], which forget every prediction and
+///   queued remesh
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`PredictedBlocks`]
+/// * [`RemeshQueue`]
+///
+/// It adds a [`MessageBufferPlugin`] for [`BlockChanged`] if there isn't one.
+///
+/// # Ordering
+///
+/// Blocks are changed and chunks marked for remeshing in
+/// [`BrineSystems::WorldUpdate`], so the chunks are remeshed in the following
+/// [`BrineSystems::MeshBuild`].
+pub struct BlockUpdatePlugin;
+
+impl Plugin for BlockUpdatePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<MessageBufferPlugin<BlockChanged>>() {
+            app.add_plugins(MessageBufferPlugin::<BlockChanged>::new("block_changed"));
+        }
+
+        app.add_message::<PredictBlockChange>()
+            .init_resource::<PredictedBlocks>()
+            .init_resource::<RemeshQueue>()
+            .add_systems(
+                Update,
+                (
+                    forget_predictions_on_reset,
+                    apply_block_changes,
+                    start_remeshes,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn forget_predictions_on_reset(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut predicted: ResMut<PredictedBlocks>,
+    mut remeshes: ResMut<RemeshQueue>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        predicted.predictions.clear();
+        remeshes.clear();
+    }
+}
+
+fn apply_block_changes(
+    mut predict_events: MessageReader<PredictBlockChange>,
+    mut changed_events: ResMut<MessageBuffer<BlockChanged>>,
+    mut acknowledged_events: MessageReader<BlockChangesAcknowledged>,
+    mut predicted: ResMut<PredictedBlocks>,
+    mut remeshes: ResMut<RemeshQueue>,
+    new_chunks: Query<(&BuiltChunk, Has<Rebuilt>), Added<BuiltChunk>>,
+    chunks: Query<&BuiltChunk>,
+    mut sections: Query<(Entity, &ChildOf, &mut ChunkSection)>,
+) {
+    let mut changes: Vec<Change> = Vec::new();
+
+    // The changes held for a remeshed chunk came after the data it was
+    // rebuilt from. A chunk sent anew already has them.
+    for (chunk, rebuilt) in new_chunks.iter() {
+        let held = remeshes.finish_remesh(chunk.position());
+        if rebuilt {
+            changes.extend(held);
+        }
+    }
+
+    for prediction in predict_events.read() {
+        changes.push((
+            prediction.position,
+            prediction.block_state,
+            Some(prediction.sequence),
+        ));
+    }
+    for changed in changed_events.drain() {
+        if predicted.server_change(changed.position, changed.block_state) {
+            changes.push((changed.position, changed.block_state, None));
+        }
+    }
+    for acknowledged in acknowledged_events.read() {
+        let settled = predicted.acknowledge(acknowledged.sequence);
+        changes.extend(
+            settled
+                .into_iter()
+                .map(|(position, block_state)| (position, block_state, None)),
+        );
+    }
+
+    if changes.is_empty() {
+        return;
+    }
+
+    let mut section_map = HashMap::new();
+    for (entity, parent, section) in sections.iter() {
+        if let Ok(chunk) = chunks.get(parent.parent()) {
+            let key = chunk.position().section(section.0.chunk_y.into());
+            section_map.insert(key, entity);
+        }
+    }
+
+    for (position, block_state, sequence) in changes {
+        let block = BlockPos::from(position);
+        let Some(&entity) = section_map.get(&block.section()) else {
+            remeshes.hold(block.chunk(), (position, block_state, sequence));
+            continue;
+        };
+        let Ok((_, _, mut section)) = sections.get_mut(entity) else {
+            continue;
+        };
+
+        let local = block.section_key();
+        let block_index = BlockStates::xyz_to_index(local.x, local.y, local.z);
+        let previous = std::mem::replace(&mut section.0.block_states.0[block_index], block_state);
+
+        if let Some(sequence) = sequence {
+            predicted.predict(position, sequence, previous);
+        }
+        if previous != block_state {
+            remeshes.mark(block.section());
+        }
+    }
+}
+
+fn start_remeshes(
+    time: Res<Time>,
+    cameras: Query<&Transform, With<Camera3d>>,
+    chunks: Query<(Entity, &BuiltChunk)>,
+    mut remeshes: ResMut<RemeshQueue>,
+    mut commands: Commands,
+) {
+    if remeshes.is_empty() {
+        return;
+    }
+
+    let now = time.elapsed();
+    let camera = cameras.single().ok().map(|camera| camera.translation);
+    let ready = remeshes.take_ready(now, camera, MAX_REMESHES_PER_FRAME);
+    if ready.is_empty() {
+        return;
+    }
+
+    for (entity, chunk) in chunks.iter() {
+        let position = chunk.position();
+        if ready.contains(&position) {
+            commands.entity(entity).insert(RemeshChunk);
+            remeshes.start_remesh(position, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STONE: BlockState = BlockState(1);
+    const DIRT: BlockState = BlockState(10);
+
+    #[test]
+    fn rolls_back_rejected_predictions() {
+        let mut predicted = PredictedBlocks::default();
+        let broken = IVec3::new(1, 64, 2);
+        let placed = IVec3::new(1, 65, 2);
+
+        // The player breaks a stone block (action 1) and places dirt (2).
+        predicted.predict(broken, 1, STONE);
+        predicted.predict(placed, 2, BlockState::AIR);
+
+        // The server agrees with the break, but the placement's change is
+        // held back until it's acknowledged.
+        assert!(!predicted.server_change(broken, BlockState::AIR));
+        assert_eq!(predicted.acknowledge(1), vec![(broken, BlockState::AIR)]);
+        assert!(predicted.is_predicted(placed));
+
+        // The server said nothing about the dirt, so it goes away.
+        assert_eq!(predicted.acknowledge(2), vec![(placed, BlockState::AIR)]);
+        assert!(predicted.is_empty());
+
+        // Changes to blocks nobody predicted apply at once.
+        assert!(predicted.server_change(placed, DIRT));
+    }
+
+    #[test]
+    fn remeshes_are_spaced_out_and_nearest_first() {
+        let mut remeshes = RemeshQueue::default();
+        let near = SectionPos::new(0, 4, 0);
+        let far = SectionPos::new(5, 4, 0);
+        let camera = Some(Vec3::new(8.0, 72.0, 8.0));
+        let start = Duration::from_secs(10);
+
+        // Changes to the same section and chunk are remeshed together.
+        remeshes.mark(far);
+        remeshes.mark(near);
+        remeshes.mark(near + IVec3::Y);
+        assert_eq!(remeshes.len(), 2);
+        assert_eq!(remeshes.take_ready(start, camera, 1), vec![near.chunk()]);
+        remeshes.start_remesh(near.chunk(), start);
+
+        // Changes to a chunk being remeshed wait for it.
+        let change = (IVec3::new(1, 64, 1), STONE, None);
+        assert!(remeshes.hold(near.chunk(), change));
+        assert!(!remeshes.hold(SectionPos::new(9, 0, 9).chunk(), change));
+        assert_eq!(remeshes.finish_remesh(near.chunk()), vec![change]);
+
+        // The chunk just remeshed waits out the interval.
+        remeshes.mark(near);
+        let soon = start + MIN_REMESH_INTERVAL / 2;
+        assert_eq!(remeshes.take_ready(soon, camera, 4), vec![far.chunk()]);
+        let later = start + MIN_REMESH_INTERVAL;
+        assert_eq!(remeshes.take_ready(later, camera, 4), vec![near.chunk()]);
+        assert!(remeshes.is_empty());
+    }
+
+    #[test]
+    fn later_predictions_keep_the_server_state() {
+        let mut predicted = PredictedBlocks::default();
+        let position = IVec3::new(0, 0, 0);
+
+        predicted.predict(position, 1, STONE);
+        predicted.predict(position, 2, BlockState::AIR);
+
+        // The first acknowledgement doesn't cover the second change.
+        assert!(predicted.acknowledge(1).is_empty());
+        assert_eq!(predicted.acknowledge(2), vec![(position, STONE)]);
+    }
+}
@@ -4,6 +4,10 @@
 //! <https://github.com/PrismarineJS/prismarine-chunk/tree/master/test>, i.e.
 //! binary blob stored in `{file}.dump` and extra information stored as JSON in
 //! `{file}.meta`.
+//!
+//! The light data and block entities the server sent with the chunk are kept,
+//! protocol-encoded, in `{file}.light` and `{file}.blockentities`. Older
+//! dumps don't have them.
 
 use std::{
     fs, io,
@@ -12,21 +16,58 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use brine_chunk::{decode::Error as ChunkError, Chunk};
-use brine_proto_backend::backend_stevenarella::{chunks::ChunkData, codec::Packet};
+use brine_chunk::{
+    decode::{Error as ChunkError, VarIntRead},
+    Chunk, ChunkLight,
+};
+use brine_proto_backend::chunks::{chunk_data, chunk_extras, ChunkData, ChunkExtras, Packet};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    //#[error(transparent)]
-    //Packet(#[from] PacketError),
-    #[error(transparent)]
-    Chunk(#[from] ChunkError),
+    #[error("failed to decode chunk ({chunk_x}, {chunk_z}): {source}")]
+    Chunk {
+        chunk_x: i32,
+        chunk_z: i32,
+        #[source]
+        source: ChunkError,
+    },
+
+    #[error("failed to decode light data in {}: {source}", path.display())]
+    Light {
+        path: PathBuf,
+        #[source]
+        source: ChunkError,
+    },
+
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid chunk metadata in {}: {source}", path.display())]
+    Json {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
 
-    #[error(transparent)]
-    Io(#[from] io::Error),
+impl Error {
+    fn io(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
 
-    #[error(transparent)]
-    Json(#[from] serde_json::Error),
+    fn json(path: &Path) -> impl FnOnce(serde_json::Error) -> Self + '_ {
+        move |source| Self::Json {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -48,13 +89,14 @@ pub fn load_chunk_data(path: impl AsRef<Path>) -> Result<ChunkData<Vec<u8>>> {
     let dump_path = path.with_extension("dump");
     let meta_path = path.with_extension("meta");
 
+    let meta_file = fs::File::open(&meta_path).map_err(Error::io(&meta_path))?;
     let ChunkMeta {
         chunk_x,
         chunk_z,
         bitmask,
-    } = serde_json::from_reader(fs::File::open(meta_path)?)?;
+    } = serde_json::from_reader(meta_file).map_err(Error::json(&meta_path))?;
 
-    let data = fs::read(dump_path)?;
+    let data = fs::read(&dump_path).map_err(Error::io(&dump_path))?;
 
     Ok(ChunkData {
         chunk_x,
@@ -62,29 +104,92 @@ pub fn load_chunk_data(path: impl AsRef<Path>) -> Result<ChunkData<Vec<u8>>> {
         bitmask,
         full_chunk: true,
         data,
+        heightmap: None,
     })
 }
 
 /// Loads a chunk from a pair of `.dump` and `.meta` files.
 pub fn load_chunk(path: impl AsRef<Path>) -> Result<Chunk> {
-    let chunk = load_chunk_data(path)?.decode()?;
+    let chunk_data = load_chunk_data(path)?;
 
-    Ok(chunk)
+    chunk_data.decode().map_err(|source| Error::Chunk {
+        chunk_x: chunk_data.chunk_x,
+        chunk_z: chunk_data.chunk_z,
+        source,
+    })
+}
+
+/// The block entities of a chunk, with their NBT left undecoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEntities {
+    pub count: usize,
+
+    /// The protocol-encoded block entity array, count included.
+    pub data: Vec<u8>,
+}
+
+/// Loads the light data saved along with a chunk, if there is any.
+pub fn load_chunk_light(path: impl AsRef<Path>) -> Result<Option<ChunkLight>> {
+    let light_path = path.as_ref().with_extension("light");
+    let Some(data) = read_if_exists(&light_path)? else {
+        return Ok(None);
+    };
+
+    ChunkLight::decode(&mut data.as_slice())
+        .map(Some)
+        .map_err(|source| Error::Light {
+            path: light_path,
+            source,
+        })
+}
+
+/// Loads the block entities saved along with a chunk, if there are any.
+pub fn load_block_entities(path: impl AsRef<Path>) -> Result<Option<BlockEntities>> {
+    let block_entities_path = path.as_ref().with_extension("blockentities");
+    let Some(data) = read_if_exists(&block_entities_path)? else {
+        return Ok(None);
+    };
+
+    let count = data
+        .as_slice()
+        .read_var_i32()
+        .map_err(Error::io(&block_entities_path))?;
+    Ok(Some(BlockEntities {
+        count: count.max(0) as usize,
+        data,
+    }))
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(Error::io(path)(err)),
+    }
 }
 
 /// Saves a chunk packet to a pair of `chunk_{X}_{Z}.dump` and
-/// `chunk_{X}_{Z}.meta` files in the directory pointed to by `path`.
+/// `chunk_{X}_{Z}.meta` files in the directory pointed to by `path`, along
+/// with its `.light` and `.blockentities`.
+///
+/// A light update replaces the `.light` file of its chunk, but returns `None`
+/// as it doesn't save a chunk.
 pub fn save_packet_if_has_chunk_data(
     packet: &Packet,
     path: impl AsRef<Path>,
 ) -> Result<Option<PathBuf>> {
+    if let Some(extras) = chunk_extras(packet) {
+        save_chunk_extras(&extras, path.as_ref())?;
+    }
+
     if let Some(ChunkData {
         chunk_x,
         chunk_z,
         bitmask,
         full_chunk: true,
         data,
-    }) = ChunkData::from_packet(packet)
+        ..
+    }) = chunk_data(packet)
     {
         let mut path = PathBuf::from(path.as_ref());
         path.push(format!("chunk_{}_{}.dump", chunk_x, chunk_z));
@@ -97,12 +202,27 @@ pub fn save_packet_if_has_chunk_data(
             chunk_z,
             bitmask,
         };
-        serde_json::to_writer(fs::File::create(meta_path)?, &meta)?;
+        let meta_file = fs::File::create(&meta_path).map_err(Error::io(&meta_path))?;
+        serde_json::to_writer(meta_file, &meta).map_err(Error::json(&meta_path))?;
 
-        fs::write(&dump_path, data)?;
+        fs::write(&dump_path, data).map_err(Error::io(&dump_path))?;
 
         Ok(Some(dump_path))
     } else {
         Ok(None)
     }
 }
+
+fn save_chunk_extras(extras: &ChunkExtras, dir: &Path) -> Result<()> {
+    let path = dir.join(format!("chunk_{}_{}", extras.chunk_x, extras.chunk_z));
+
+    let light_path = path.with_extension("light");
+    fs::write(&light_path, &extras.light).map_err(Error::io(&light_path))?;
+
+    if let Some(block_entities) = &extras.block_entities {
+        let block_entities_path = path.with_extension("blockentities");
+        fs::write(&block_entities_path, block_entities).map_err(Error::io(&block_entities_path))?;
+    }
+
+    Ok(())
+}
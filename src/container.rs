@@ -0,0 +1,937 @@
+//! Container windows: chests, furnaces, crafting tables and the like.
+//!
+//! When the server opens a window ([`ContainerOpened`]), a screen shows the
+//! container's slots above the player's inventory. Clicking slots moves items
+//! around as in vanilla:
+//!
+//! * Left click picks up, puts down or swaps a stack, or adds the held stack
+//!   to one of the same item.
+//! * Right click picks up half a stack, or puts down one item.
+//! * Shift-click (or whatever [`InputAction::QuickMoveModifier`] is bound to)
+//!   moves a stack to the other part of the window.
+//! * Dragging a held stack across several slots spreads it evenly between
+//!   them (left button), or puts one item in each (right button).
+//!
+//! Each click is sent as a [`ClickContainer`] along with the slots the client
+//! expects it to change, and the window is updated right away. Clicks whose
+//! outcome depends on what the container does with items (crafting results,
+//! furnace slots) are sent without a prediction; the server sends back
+//! whatever changed.
+//!
+//! The [`Inventory`][InputAction::Inventory] binding or grabbing the cursor
+//! closes the window.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use bevy::{
+    prelude::*,
+    ui::RelativeCursorPosition,
+    window::{CursorGrabMode, CursorOptions, PrimaryWindow},
+};
+use brine_data::{ItemId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{
+            ContainerClosed, ContainerContents, ContainerOpened, ContainerProperty, ContainerSlot,
+            CursorItem, Reconfigure, Transfer,
+        },
+        serverbound::{ClickContainer, CloseContainer},
+        ClickMode, ContainerKind, ItemStack,
+    },
+    BrineSystems,
+};
+
+use crate::{
+    crafting::{spawn_recipe_list, stack_label, CraftingScreen},
+    input::{InputAction, InputMap},
+};
+
+/// Number of player inventory slots (main inventory, then hotbar) after the
+/// container's own slots.
+const PLAYER_INVENTORY_SLOTS: usize = 36;
+
+/// Slot number of clicks outside any slot, e.g. to start or end a drag.
+const OUTSIDE_SLOT: i16 = -999;
+
+/// Stack size of items the [`MinecraftData`] doesn't know.
+const DEFAULT_MAX_STACK: i32 = 64;
+
+/// Furnace properties: fuel time left and total, and cooking progress and
+/// total.
+const FURNACE_FUEL_LEFT: i16 = 0;
+const FURNACE_FUEL_TOTAL: i16 = 1;
+const FURNACE_PROGRESS: i16 = 2;
+const FURNACE_PROGRESS_TOTAL: i16 = 3;
+
+const SLOT_SIZE: f32 = 48.0;
+const SLOT_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const SLOT_HOVERED_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+
+/// An open container window, as the client last knew or predicted it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerWindow {
+    pub window_id: i32,
+    pub kind: ContainerKind,
+    pub title: String,
+
+    /// The window's last state ID, which clicks echo.
+    state_id: i32,
+
+    /// The container's slots, then the player's main inventory and hotbar.
+    slots: Vec<Option<ItemStack>>,
+
+    /// The stack held with the cursor.
+    carried: Option<ItemStack>,
+    properties: BTreeMap<i16, i16>,
+}
+
+impl ContainerWindow {
+    pub fn new(window_id: i32, kind: ContainerKind, title: impl Into<String>) -> Self {
+        let slot_count = kind
+            .slot_count()
+            .map_or(0, |count| count + PLAYER_INVENTORY_SLOTS);
+        Self {
+            window_id,
+            kind,
+            title: title.into(),
+            state_id: 0,
+            slots: vec![None; slot_count],
+            carried: None,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// Returns the stack held with the cursor.
+    pub fn carried(&self) -> Option<ItemStack> {
+        self.carried
+    }
+
+    /// Returns the number of slots the container has before the player's
+    /// inventory.
+    pub fn container_slots(&self) -> usize {
+        self.kind
+            .slot_count()
+            .unwrap_or_else(|| self.slots.len().saturating_sub(PLAYER_INVENTORY_SLOTS))
+    }
+
+    /// Returns the value of a property, e.g. a furnace's cooking progress.
+    pub fn property(&self, property: i16) -> Option<i16> {
+        self.properties.get(&property).copied()
+    }
+
+    /// Returns whether clicks on `slot` just move items around, so their
+    /// outcome can be predicted.
+    fn is_storage_slot(&self, slot: usize) -> bool {
+        slot < self.slots.len() && (slot >= self.container_slots() || self.kind.is_storage())
+    }
+
+    /// Returns whether the held stack can be spread onto `slot` by dragging.
+    fn can_drag_to(&self, slot: usize) -> bool {
+        let Some(carried) = self.carried else {
+            return false;
+        };
+        self.is_storage_slot(slot)
+            && self.slots[slot].is_none_or(|item| item.item_id == carried.item_id)
+    }
+
+    fn click_packet(
+        &self,
+        slot: i16,
+        button: i8,
+        mode: ClickMode,
+        changed_slots: Vec<(i16, Option<ItemStack>)>,
+    ) -> ClickContainer {
+        ClickContainer {
+            window_id: self.window_id,
+            state_id: self.state_id,
+            slot,
+            button,
+            mode,
+            changed_slots,
+            carried_item: self.carried,
+        }
+    }
+
+    /// Applies `change` to the window and returns the slots it changed.
+    fn predict(&mut self, change: impl FnOnce(&mut Self)) -> Vec<(i16, Option<ItemStack>)> {
+        let before = self.slots.clone();
+        change(self);
+        before
+            .iter()
+            .zip(&self.slots)
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(slot, (_, after))| (slot as i16, *after))
+            .collect()
+    }
+
+    /// Clicks `slot` with the left or right button.
+    pub fn click(
+        &mut self,
+        slot: usize,
+        right: bool,
+        max_stack: impl Fn(i32) -> i32,
+    ) -> ClickContainer {
+        let button = i8::from(right);
+        let changed_slots = if self.is_storage_slot(slot) {
+            self.predict(|window| window.pick_up_or_put_down(slot, right, max_stack))
+        } else {
+            Vec::new()
+        };
+        self.click_packet(slot as i16, button, ClickMode::Pickup, changed_slots)
+    }
+
+    fn pick_up_or_put_down(&mut self, slot: usize, right: bool, max_stack: impl Fn(i32) -> i32) {
+        let (slot_item, carried) = match (self.slots[slot], self.carried) {
+            (None, None) => return,
+            (Some(item), None) if right => {
+                let taken = (item.count + 1) / 2;
+                (
+                    with_count(item, item.count - taken),
+                    with_count(item, taken),
+                )
+            }
+            (Some(item), None) => (None, Some(item)),
+            (None, Some(carried)) if right => (
+                with_count(carried, 1),
+                with_count(carried, carried.count - 1),
+            ),
+            (None, Some(carried)) => (Some(carried), None),
+            (Some(item), Some(carried)) if item.item_id == carried.item_id => {
+                let room = (max_stack(item.item_id) - item.count).max(0);
+                let moved = if right { 1 } else { carried.count }.min(room);
+                (
+                    with_count(item, item.count + moved),
+                    with_count(carried, carried.count - moved),
+                )
+            }
+            (Some(item), Some(carried)) => (Some(carried), Some(item)),
+        };
+        self.slots[slot] = slot_item;
+        self.carried = carried;
+    }
+
+    /// Shift-clicks `slot`, moving its stack to the other part of the window.
+    pub fn quick_move(&mut self, slot: usize, max_stack: impl Fn(i32) -> i32) -> ClickContainer {
+        let changed_slots = if self.kind.is_storage() && slot < self.slots.len() {
+            let container_slots = self.container_slots();
+            self.predict(|window| {
+                if slot < container_slots {
+                    // Into the inventory, hotbar first.
+                    window.move_stack(slot, container_slots..window.slots.len(), true, max_stack);
+                } else {
+                    window.move_stack(slot, 0..container_slots, false, max_stack);
+                }
+            })
+        } else {
+            Vec::new()
+        };
+        self.click_packet(slot as i16, 0, ClickMode::QuickMove, changed_slots)
+    }
+
+    /// Moves as much of the stack in `from` into the slots in `to` as fits:
+    /// onto stacks of the same item first, then into empty slots.
+    fn move_stack(
+        &mut self,
+        from: usize,
+        to: Range<usize>,
+        reverse: bool,
+        max_stack: impl Fn(i32) -> i32,
+    ) {
+        let Some(mut stack) = self.slots[from] else {
+            return;
+        };
+        let order: Vec<usize> = if reverse {
+            to.rev().collect()
+        } else {
+            to.collect()
+        };
+
+        for &slot in &order {
+            if let Some(item) = &mut self.slots[slot] {
+                if item.item_id == stack.item_id {
+                    let moved = (max_stack(item.item_id) - item.count).clamp(0, stack.count);
+                    item.count += moved;
+                    stack.count -= moved;
+                }
+            }
+        }
+        for &slot in &order {
+            if stack.count > 0 && self.slots[slot].is_none() {
+                self.slots[slot] = Some(stack);
+                stack.count = 0;
+            }
+        }
+
+        self.slots[from] = with_count(stack, stack.count);
+    }
+
+    /// Drags the held stack across `slots`, returning the clicks that start
+    /// the drag, add each slot to it, and end it. Slots the stack can't go
+    /// in are skipped.
+    pub fn drag(
+        &mut self,
+        slots: &[usize],
+        right: bool,
+        max_stack: impl Fn(i32) -> i32,
+    ) -> Vec<ClickContainer> {
+        let slots: Vec<usize> = slots
+            .iter()
+            .copied()
+            .filter(|&slot| self.can_drag_to(slot))
+            .collect();
+        let (start, add, end) = if right { (4, 5, 6) } else { (0, 1, 2) };
+
+        let mut clicks =
+            vec![self.click_packet(OUTSIDE_SLOT, start, ClickMode::QuickCraft, Vec::new())];
+        for &slot in &slots {
+            clicks.push(self.click_packet(slot as i16, add, ClickMode::QuickCraft, Vec::new()));
+        }
+        let changed_slots = self.predict(|window| window.spread(&slots, right, max_stack));
+        clicks.push(self.click_packet(OUTSIDE_SLOT, end, ClickMode::QuickCraft, changed_slots));
+
+        clicks
+    }
+
+    fn spread(&mut self, slots: &[usize], right: bool, max_stack: impl Fn(i32) -> i32) {
+        let Some(carried) = self.carried else {
+            return;
+        };
+        if slots.is_empty() {
+            return;
+        }
+
+        let per_slot = if right {
+            1
+        } else {
+            carried.count / slots.len() as i32
+        };
+        let max = max_stack(carried.item_id);
+        let mut remaining = carried.count;
+        for &slot in slots {
+            let existing = self.slots[slot].map_or(0, |item| item.count);
+            let added = per_slot.min(max - existing).min(remaining).max(0);
+            remaining -= added;
+            self.slots[slot] = with_count(carried, existing + added);
+        }
+        self.carried = with_count(carried, remaining);
+    }
+}
+
+fn with_count(stack: ItemStack, count: i32) -> Option<ItemStack> {
+    (count > 0).then_some(ItemStack { count, ..stack })
+}
+
+/// The container window the server has opened, if any.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Deref)]
+pub struct OpenContainer(pub Option<ContainerWindow>);
+
+/// Run condition that is true while neither a container window nor the
+/// crafting screen is open, for gameplay input that would get in their way.
+pub fn no_screen_open(
+    container: Option<Res<OpenContainer>>,
+    crafting: Option<Res<CraftingScreen>>,
+) -> bool {
+    !container.is_some_and(|container| container.is_some())
+        && !crafting.is_some_and(|crafting| crafting.is_open())
+}
+
+#[derive(Component, Debug)]
+struct ContainerScreenRoot {
+    window_id: i32,
+    slot_count: usize,
+}
+
+/// A slot of the open window, by index.
+#[derive(Component, Debug)]
+struct SlotButton(usize);
+
+#[derive(Component, Debug)]
+struct SlotText(usize);
+
+/// Text following the cursor that shows the held stack.
+#[derive(Component, Debug)]
+struct CarriedText;
+
+/// Text showing a furnace's fuel and cooking progress.
+#[derive(Component, Debug)]
+struct PropertyText;
+
+/// A drag in progress: the slots the cursor has crossed while holding a
+/// stack and a mouse button.
+#[derive(Debug)]
+struct Drag {
+    right: bool,
+    slots: Vec<usize>,
+}
+
+/// Plugin that tracks the [`OpenContainer`] and shows it, sending the
+/// player's clicks to the server.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ContainerOpened`]
+/// * [`ContainerContents`]
+/// * [`ContainerSlot`]
+/// * [`ContainerProperty`]
+/// * [`ContainerClosed`]
+/// * [`CursorItem`]
+/// * [`Transfer`] and [`Reconfigure`], which close the window
+///
+/// The plugin sends the following events:
+///
+/// * [`ClickContainer`]
+/// * [`CloseContainer`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`OpenContainer`]
+///
+/// # Ordering
+///
+/// The window is closed with the inventory binding after the
+/// [`CraftingPlugin`][crate::crafting::CraftingPlugin] has checked it, so the
+/// same press doesn't also open the crafting screen.
+pub struct ContainerPlugin;
+
+impl Plugin for ContainerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenContainer>().add_systems(
+            Update,
+            (
+                handle_container_events,
+                close_container_with_input,
+                sync_container_screen,
+                click_container_slots,
+                update_container_screen,
+            )
+                .chain()
+                .in_set(BrineSystems::WorldUpdate)
+                .after(crate::crafting::toggle_crafting_screen),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_container_events(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut opened_events: MessageReader<ContainerOpened>,
+    mut contents_events: MessageReader<ContainerContents>,
+    mut slot_events: MessageReader<ContainerSlot>,
+    mut property_events: MessageReader<ContainerProperty>,
+    mut closed_events: MessageReader<ContainerClosed>,
+    mut cursor_events: MessageReader<CursorItem>,
+    mut open: ResMut<OpenContainer>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+    if transferred || reconfigured {
+        open.set_if_neq(OpenContainer::default());
+    }
+
+    for opened in opened_events.read() {
+        debug!("Opened {:?} window {}", opened.kind, opened.window_id);
+        open.0 = Some(ContainerWindow::new(
+            opened.window_id,
+            opened.kind,
+            opened.title.clone(),
+        ));
+    }
+
+    for closed in closed_events.read() {
+        if open
+            .0
+            .as_ref()
+            .is_some_and(|window| window.window_id == closed.window_id)
+        {
+            open.0 = None;
+        }
+    }
+
+    let Some(window) = &mut open.0 else {
+        return;
+    };
+
+    for contents in contents_events.read() {
+        if contents.window_id == window.window_id {
+            window.state_id = contents.state_id;
+            window.slots = contents.items.clone();
+        }
+    }
+    for event in slot_events.read() {
+        if event.window_id != window.window_id {
+            continue;
+        }
+        window.state_id = event.state_id;
+        if let Some(slot) = window.slots.get_mut(usize::from(event.slot)) {
+            *slot = event.item;
+        }
+    }
+    for event in property_events.read() {
+        if event.window_id == window.window_id {
+            window.properties.insert(event.property, event.value);
+        }
+    }
+    if let Some(cursor) = cursor_events.read().last() {
+        window.carried = cursor.item;
+    }
+}
+
+fn close_container_with_input(
+    input: InputMap,
+    mut open: ResMut<OpenContainer>,
+    mut cursors: Query<&mut CursorOptions, With<PrimaryWindow>>,
+    mut close_events: MessageWriter<CloseContainer>,
+) {
+    let Some(window) = &open.0 else {
+        return;
+    };
+
+    // Grabbing the cursor again, e.g. with Escape, closes the window too.
+    let cursor_grabbed = cursors
+        .iter()
+        .any(|cursor| cursor.grab_mode != CursorGrabMode::None);
+    if !input.just_pressed(InputAction::Inventory) && !cursor_grabbed {
+        return;
+    }
+
+    close_events.write(CloseContainer {
+        window_id: window.window_id,
+    });
+    open.0 = None;
+
+    for mut cursor in cursors.iter_mut() {
+        cursor.grab_mode = CursorGrabMode::Locked;
+        cursor.visible = false;
+    }
+}
+
+/// Spawns the screen for the open window, or despawns it once the window is
+/// closed. The screen is rebuilt if the window's slot count changes, since
+/// the slot count of unknown kinds of containers is only known once their
+/// contents arrive.
+fn sync_container_screen(
+    mut commands: Commands,
+    open: Res<OpenContainer>,
+    roots: Query<(Entity, &ContainerScreenRoot)>,
+    mut cursors: Query<&mut CursorOptions, With<PrimaryWindow>>,
+) {
+    if !open.is_changed() {
+        return;
+    }
+
+    let mut shown = false;
+    for (entity, root) in roots.iter() {
+        let current = open.0.as_ref().is_some_and(|window| {
+            window.window_id == root.window_id && window.slots.len() == root.slot_count
+        });
+        if current {
+            shown = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let Some(window) = &open.0 else {
+        return;
+    };
+    if shown {
+        return;
+    }
+
+    spawn_container_screen(&mut commands, window);
+
+    // Free the cursor so slots can be clicked.
+    for mut cursor in cursors.iter_mut() {
+        cursor.grab_mode = CursorGrabMode::None;
+        cursor.visible = true;
+    }
+}
+
+fn spawn_container_screen(commands: &mut Commands, window: &ContainerWindow) {
+    let container_slots = window.container_slots();
+
+    commands
+        .spawn((
+            Name::new("Container Screen"),
+            ContainerScreenRoot {
+                window_id: window.window_id,
+                slot_count: window.slots.len(),
+            },
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(window.title.clone()),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            match window.kind {
+                ContainerKind::Crafting => {
+                    parent
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(24.0),
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            spawn_slot_grid(row, 1..10, 3);
+                            row.spawn((Text::new("->"), TextColor(Color::WHITE)));
+                            spawn_slot(row, 0);
+                        });
+                    spawn_recipe_list(parent, window.window_id, 3);
+                }
+                ContainerKind::Furnace | ContainerKind::BlastFurnace | ContainerKind::Smoker => {
+                    parent
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(24.0),
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            // Ingredient above fuel.
+                            spawn_slot_grid(row, 0..2, 1);
+                            row.spawn((Text::new("->"), TextColor(Color::WHITE)));
+                            spawn_slot(row, 2);
+                        });
+                    parent.spawn((PropertyText, Text::default(), TextColor(Color::WHITE)));
+                }
+                ContainerKind::Dispenser => spawn_slot_grid(parent, 0..container_slots, 3),
+                ContainerKind::Hopper => spawn_slot_grid(parent, 0..container_slots, 5),
+                _ => spawn_slot_grid(parent, 0..container_slots, 9),
+            }
+
+            // The player's main inventory, then the hotbar a little apart.
+            let hotbar = container_slots + PLAYER_INVENTORY_SLOTS - 9;
+            spawn_slot_grid(parent, container_slots..hotbar, 9);
+            spawn_slot_grid(parent, hotbar..hotbar + 9, 9);
+        });
+
+    commands.spawn((
+        Name::new("Carried Item"),
+        ContainerScreenRoot {
+            window_id: window.window_id,
+            slot_count: window.slots.len(),
+        },
+        CarriedText,
+        Text::default(),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        GlobalZIndex(1),
+    ));
+}
+
+fn spawn_slot_grid(parent: &mut ChildSpawnerCommands, slots: Range<usize>, columns: u16) {
+    parent
+        .spawn(Node {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::px(columns, SLOT_SIZE),
+            row_gap: Val::Px(2.0),
+            column_gap: Val::Px(2.0),
+            ..default()
+        })
+        .with_children(|grid| {
+            for slot in slots {
+                spawn_slot(grid, slot);
+            }
+        });
+}
+
+fn spawn_slot(parent: &mut ChildSpawnerCommands, slot: usize) {
+    parent
+        .spawn((
+            SlotButton(slot),
+            RelativeCursorPosition::default(),
+            Node {
+                width: Val::Px(SLOT_SIZE),
+                height: Val::Px(SLOT_SIZE),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(SLOT_COLOR),
+        ))
+        .with_children(|slot_node| {
+            slot_node.spawn((
+                SlotText(slot),
+                Text::default(),
+                TextFont {
+                    font_size: 10.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(Justify::Center),
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn click_container_slots(
+    mouse: Res<ButtonInput<MouseButton>>,
+    input: InputMap,
+    mc_data: Res<MinecraftData>,
+    mut slots: Query<(&SlotButton, &RelativeCursorPosition, &mut BackgroundColor)>,
+    mut open: ResMut<OpenContainer>,
+    mut drag: Local<Option<Drag>>,
+    mut click_events: MessageWriter<ClickContainer>,
+) {
+    let mut hovered = None;
+    for (slot, cursor, mut background) in slots.iter_mut() {
+        let over = cursor.cursor_over();
+        if over {
+            hovered = Some(slot.0);
+        }
+        background.set_if_neq(BackgroundColor(if over {
+            SLOT_HOVERED_COLOR
+        } else {
+            SLOT_COLOR
+        }));
+    }
+
+    let Some(window) = open.0.as_mut() else {
+        *drag = None;
+        return;
+    };
+    let max_stack = |item_id: i32| {
+        u16::try_from(item_id)
+            .ok()
+            .and_then(|item_id| mc_data.items().get_by_id(ItemId(item_id)))
+            .map_or(DEFAULT_MAX_STACK, |item| i32::from(item.stack_size))
+    };
+
+    if let Some(current) = drag.as_mut() {
+        let button = if current.right {
+            MouseButton::Right
+        } else {
+            MouseButton::Left
+        };
+
+        if mouse.pressed(button) {
+            let carried = window.carried.map_or(0, |carried| carried.count);
+            if let Some(slot) = hovered {
+                if !current.slots.contains(&slot)
+                    && window.can_drag_to(slot)
+                    && (current.slots.len() as i32) < carried
+                {
+                    current.slots.push(slot);
+                }
+            }
+            return;
+        }
+
+        let Drag { right, slots } = drag.take().unwrap();
+        match slots[..] {
+            [slot] => {
+                click_events.write(window.click(slot, right, max_stack));
+            }
+            _ => {
+                click_events.write_batch(window.drag(&slots, right, max_stack));
+            }
+        }
+        return;
+    }
+
+    let Some(slot) = hovered else {
+        return;
+    };
+    for (button, right) in [(MouseButton::Left, false), (MouseButton::Right, true)] {
+        if !mouse.just_pressed(button) {
+            continue;
+        }
+
+        if !right && input.pressed(InputAction::QuickMoveModifier) {
+            click_events.write(window.quick_move(slot, max_stack));
+        } else if window.can_drag_to(slot) {
+            // Holding a stack: a click if the button comes back up over the
+            // same slot, a drag if it crosses others first.
+            *drag = Some(Drag {
+                right,
+                slots: vec![slot],
+            });
+        } else {
+            click_events.write(window.click(slot, right, max_stack));
+        }
+        return;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_container_screen(
+    open: Res<OpenContainer>,
+    mc_data: Res<MinecraftData>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut slot_texts: Query<
+        (Ref<SlotText>, &mut Text),
+        (Without<CarriedText>, Without<PropertyText>),
+    >,
+    mut carried_texts: Query<(&mut Text, &mut Node), (With<CarriedText>, Without<PropertyText>)>,
+    mut property_texts: Query<&mut Text, With<PropertyText>>,
+) {
+    let Some(window) = &open.0 else {
+        return;
+    };
+
+    // The held stack follows the cursor.
+    let cursor = windows.iter().find_map(Window::cursor_position);
+    for (mut text, mut node) in carried_texts.iter_mut() {
+        if let Some(cursor) = cursor {
+            node.left = Val::Px(cursor.x + 8.0);
+            node.top = Val::Px(cursor.y + 8.0);
+        }
+        if open.is_changed() {
+            text.0 = window
+                .carried
+                .map_or_else(String::new, |item| stack_label(&mc_data, item));
+        }
+    }
+
+    for (slot, mut text) in slot_texts.iter_mut() {
+        if !open.is_changed() && !slot.is_added() {
+            continue;
+        }
+        let item = window.slots.get(slot.0).copied().flatten();
+        text.0 = item.map_or_else(String::new, |item| stack_label(&mc_data, item));
+    }
+
+    for mut text in property_texts.iter_mut() {
+        let percent = |value: i16, total: i16| {
+            let value = window.property(value).unwrap_or(0);
+            match window.property(total) {
+                Some(total) if total > 0 => i32::from(value) * 100 / i32::from(total),
+                _ => 0,
+            }
+        };
+        text.0 = format!(
+            "Fuel: {}%  Cooking: {}%",
+            percent(FURNACE_FUEL_LEFT, FURNACE_FUEL_TOTAL),
+            percent(FURNACE_PROGRESS, FURNACE_PROGRESS_TOTAL)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STONE: i32 = 1;
+    const DIRT: i32 = 2;
+
+    fn stack(item_id: i32, count: i32) -> Option<ItemStack> {
+        Some(ItemStack { item_id, count })
+    }
+
+    fn chest() -> ContainerWindow {
+        let mut window = ContainerWindow::new(3, ContainerKind::Generic { rows: 1 }, "Chest");
+        window.state_id = 5;
+        window
+    }
+
+    fn max_stack(_: i32) -> i32 {
+        64
+    }
+
+    #[test]
+    fn clicks_pick_up_and_put_down() {
+        let mut window = chest();
+        window.slots[0] = stack(STONE, 10);
+
+        let click = window.click(0, true, max_stack);
+        assert_eq!(click.changed_slots, vec![(0, stack(STONE, 5))]);
+        assert_eq!(click.carried_item, stack(STONE, 5));
+        assert_eq!((click.window_id, click.state_id), (3, 5));
+
+        let click = window.click(1, true, max_stack);
+        assert_eq!(click.changed_slots, vec![(1, stack(STONE, 1))]);
+        assert_eq!(window.carried(), stack(STONE, 4));
+
+        // Different items swap.
+        window.slots[2] = stack(DIRT, 3);
+        window.click(2, false, max_stack);
+        assert_eq!(window.slots()[2], stack(STONE, 4));
+        assert_eq!(window.carried(), stack(DIRT, 3));
+    }
+
+    #[test]
+    fn shift_click_moves_to_the_hotbar_first() {
+        let mut window = chest();
+        let last_slot = window.slots.len() - 1;
+        window.slots[0] = stack(STONE, 40);
+        window.slots[last_slot - 1] = stack(STONE, 60);
+
+        let click = window.quick_move(0, max_stack);
+        assert_eq!(click.mode, ClickMode::QuickMove);
+        assert_eq!(
+            click.changed_slots,
+            vec![
+                (0, None),
+                (last_slot as i16 - 1, stack(STONE, 64)),
+                (last_slot as i16, stack(STONE, 36)),
+            ]
+        );
+    }
+
+    #[test]
+    fn drags_spread_the_held_stack() {
+        let mut window = chest();
+        window.carried = stack(STONE, 10);
+        window.slots[1] = stack(DIRT, 1);
+
+        let clicks = window.drag(&[0, 1, 2, 3], false, max_stack);
+        let buttons: Vec<_> = clicks
+            .iter()
+            .map(|click| (click.slot, click.button))
+            .collect();
+        // The dirt slot is skipped.
+        assert_eq!(buttons, vec![(-999, 0), (0, 1), (2, 1), (3, 1), (-999, 2)]);
+        assert_eq!(
+            clicks.last().unwrap().changed_slots,
+            vec![
+                (0, stack(STONE, 3)),
+                (2, stack(STONE, 3)),
+                (3, stack(STONE, 3))
+            ]
+        );
+        assert_eq!(window.carried(), stack(STONE, 1));
+    }
+
+    #[test]
+    fn result_slots_are_left_to_the_server() {
+        let mut window = ContainerWindow::new(1, ContainerKind::Crafting, "Crafting");
+        window.slots[0] = stack(STONE, 1);
+
+        let click = window.click(0, false, max_stack);
+        assert!(click.changed_slots.is_empty());
+        assert_eq!(window.slots()[0], stack(STONE, 1));
+    }
+}
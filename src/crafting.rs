@@ -0,0 +1,766 @@
+//! Crafting: the recipes the server has unlocked for the player, and a
+//! crafting screen that crafts from them.
+//!
+//! The screen opens with the [`Inventory`][InputAction::Inventory] binding
+//! and shows the 2x2 crafting grid of the player's inventory, its result, and
+//! the recipe book. Clicking a recipe asks the server to move its ingredients
+//! into the grid ([`PlaceRecipe`]), which also works for ingredients given as
+//! tags the client can't resolve; shift-clicking fills the grid with as many
+//! sets as possible. The Craft button then shift-clicks the result slot,
+//! moving everything that can be crafted into the inventory.
+//!
+//! Recipes that need a 3x3 grid are listed but can only be placed in a
+//! crafting table, whose [container screen][crate::container] lists the
+//! recipe book too.
+//!
+//! On servers whose recipes the backend can't read (see
+//! [`RecipeBookUnavailable`]), the screen and the crafting table's recipe
+//! list only say why, since nothing could be placed in the grid.
+
+use std::collections::BTreeMap;
+
+use bevy::{
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+    window::{CursorGrabMode, CursorOptions, PrimaryWindow},
+};
+use brine_data::{ItemId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{
+            InventoryContents, InventorySlot, RecipeBookUnavailable, RecipesAdded, RecipesRemoved,
+            Reconfigure, Transfer,
+        },
+        serverbound::{ClickContainer, CloseContainer, PlaceRecipe},
+        ClickMode, ItemStack, Recipe,
+    },
+    BrineSystems,
+};
+
+use crate::{
+    container::OpenContainer,
+    input::{InputAction, InputMap},
+};
+
+/// Window ID of the player's inventory, whose crafting grid the screen shows.
+const INVENTORY_WINDOW: i32 = 0;
+
+/// Width and height of the inventory's crafting grid.
+const INVENTORY_GRID_SIZE: u8 = 2;
+
+/// Inventory window slot of the crafting result; the grid follows it.
+const RESULT_SLOT: usize = 0;
+
+const GRID_SLOTS: usize = (INVENTORY_GRID_SIZE * INVENTORY_GRID_SIZE) as usize;
+
+/// Pixels scrolled per line of mouse wheel scrolling in the recipe list.
+const PIXELS_PER_SCROLL_LINE: f32 = 24.0;
+
+const SLOT_SIZE: f32 = 64.0;
+const SLOT_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.45, 0.45, 0.6);
+const DISABLED_BUTTON_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
+
+/// The crafting recipes the server has unlocked for the local player, by ID.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct RecipeBook {
+    recipes: BTreeMap<i32, Recipe>,
+
+    /// Why there is no recipe book on this server, if there isn't.
+    unavailable: Option<String>,
+}
+
+impl RecipeBook {
+    /// Returns why the server's recipes can't be shown, if they can't.
+    pub fn unavailable(&self) -> Option<&str> {
+        self.unavailable.as_deref()
+    }
+
+    pub fn get(&self, id: i32) -> Option<&Recipe> {
+        self.recipes.get(&id)
+    }
+
+    /// Returns the recipes by increasing ID.
+    pub fn iter(&self) -> impl Iterator<Item = &Recipe> {
+        self.recipes.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    fn add(&mut self, added: &RecipesAdded) {
+        if added.replace {
+            self.recipes.clear();
+        }
+        for recipe in &added.recipes {
+            self.recipes.insert(recipe.id, recipe.clone());
+        }
+    }
+
+    fn remove(&mut self, ids: &[i32]) {
+        for id in ids {
+            self.recipes.remove(id);
+        }
+    }
+}
+
+/// The contents of the player's inventory crafting grid.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct CraftingGrid {
+    /// The inventory's last state ID, which clicks echo.
+    state_id: i32,
+    result: Option<ItemStack>,
+    grid: [Option<ItemStack>; GRID_SLOTS],
+}
+
+impl CraftingGrid {
+    /// Returns what the ingredients in the grid make.
+    pub fn result(&self) -> Option<ItemStack> {
+        self.result
+    }
+
+    /// Returns the items in the grid, row by row.
+    pub fn grid(&self) -> &[Option<ItemStack>; GRID_SLOTS] {
+        &self.grid
+    }
+
+    fn set_slot(&mut self, slot: usize, item: Option<ItemStack>) {
+        if slot == RESULT_SLOT {
+            self.result = item;
+        } else if let Some(cell) = self.grid.get_mut(slot - 1) {
+            *cell = item;
+        }
+    }
+
+    /// Returns the click that takes as many results as possible into the
+    /// inventory.
+    fn craft_all(&self) -> ClickContainer {
+        ClickContainer {
+            window_id: INVENTORY_WINDOW,
+            state_id: self.state_id,
+            slot: RESULT_SLOT as i16,
+            button: 0,
+            mode: ClickMode::QuickMove,
+            // Which inventory slots the results land in is up to the server,
+            // which sends them back.
+            changed_slots: Vec::new(),
+            carried_item: None,
+        }
+    }
+}
+
+/// Whether the crafting screen is open.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CraftingScreen {
+    open: bool,
+}
+
+impl CraftingScreen {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+#[derive(Component, Debug)]
+pub(crate) struct CraftingScreenRoot;
+
+/// Text showing the contents of an inventory window slot.
+#[derive(Component, Debug)]
+struct SlotText(usize);
+
+/// A list of the recipes in the [`RecipeBook`] that places them in the
+/// crafting grid of a window.
+#[derive(Component, Debug)]
+struct RecipeList {
+    window_id: i32,
+    grid_size: u8,
+}
+
+#[derive(Component, Debug)]
+struct RecipeButton {
+    window_id: i32,
+    recipe_id: i32,
+    fits: bool,
+}
+
+#[derive(Component, Debug)]
+struct CraftButton;
+
+/// Plugin that tracks the [`RecipeBook`] and the [`CraftingGrid`], and shows
+/// the crafting screen.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`RecipesAdded`]
+/// * [`RecipesRemoved`]
+/// * [`RecipeBookUnavailable`]
+/// * [`InventoryContents`]
+/// * [`InventorySlot`]
+/// * [`Transfer`] and [`Reconfigure`], which empty the recipe book and close
+///   the screen
+///
+/// The plugin sends the following events:
+///
+/// * [`PlaceRecipe`]
+/// * [`ClickContainer`]
+/// * [`CloseContainer`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`RecipeBook`]
+/// * [`CraftingGrid`]
+/// * [`CraftingScreen`]
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecipeBook>()
+            .init_resource::<CraftingGrid>()
+            .init_resource::<CraftingScreen>()
+            .add_systems(
+                Update,
+                (
+                    reset_crafting,
+                    handle_crafting_events,
+                    toggle_crafting_screen,
+                    update_recipe_list,
+                    update_slot_texts,
+                    scroll_recipe_list,
+                    press_recipe_buttons,
+                    press_craft_button,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn reset_crafting(
+    mut commands: Commands,
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut recipe_book: ResMut<RecipeBook>,
+    mut grid: ResMut<CraftingGrid>,
+    mut screen: ResMut<CraftingScreen>,
+    roots: Query<Entity, With<CraftingScreenRoot>>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        let unavailable = recipe_book.unavailable.take();
+        *recipe_book = RecipeBook::default();
+        // Reconfiguring doesn't change the server's version.
+        if !transferred {
+            recipe_book.unavailable = unavailable;
+        }
+        *grid = CraftingGrid::default();
+        screen.set_if_neq(CraftingScreen::default());
+        for root in roots.iter() {
+            commands.entity(root).despawn();
+        }
+    }
+}
+
+fn handle_crafting_events(
+    mut added_events: MessageReader<RecipesAdded>,
+    mut removed_events: MessageReader<RecipesRemoved>,
+    mut unavailable_events: MessageReader<RecipeBookUnavailable>,
+    mut contents_events: MessageReader<InventoryContents>,
+    mut slot_events: MessageReader<InventorySlot>,
+    mut recipe_book: ResMut<RecipeBook>,
+    mut grid: ResMut<CraftingGrid>,
+) {
+    for added in added_events.read() {
+        recipe_book.add(added);
+    }
+    for removed in removed_events.read() {
+        recipe_book.remove(&removed.ids);
+    }
+    for unavailable in unavailable_events.read() {
+        recipe_book.unavailable = Some(unavailable.reason.clone());
+    }
+
+    for contents in contents_events.read() {
+        grid.state_id = contents.state_id;
+        for (slot, item) in contents.items.iter().enumerate().take(GRID_SLOTS + 1) {
+            grid.set_slot(slot, *item);
+        }
+    }
+    for event in slot_events.read() {
+        if let Some(state_id) = event.state_id {
+            grid.state_id = state_id;
+        }
+        grid.set_slot(usize::from(event.slot), event.item);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn toggle_crafting_screen(
+    mut commands: Commands,
+    input: InputMap,
+    recipe_book: Res<RecipeBook>,
+    open_container: Option<Res<OpenContainer>>,
+    mut screen: ResMut<CraftingScreen>,
+    roots: Query<Entity, With<CraftingScreenRoot>>,
+    mut cursors: Query<&mut CursorOptions, With<PrimaryWindow>>,
+    mut close_events: MessageWriter<CloseContainer>,
+) {
+    // Grabbing the cursor again, e.g. with Escape, closes the screen too.
+    let cursor_grabbed = cursors
+        .iter()
+        .any(|cursor| cursor.grab_mode != CursorGrabMode::None);
+    let toggled = input.just_pressed(InputAction::Inventory);
+
+    if !screen.open {
+        // The inventory binding closes container screens instead.
+        let container_open = open_container.is_some_and(|container| container.is_some());
+        if toggled && !container_open {
+            screen.open = true;
+            spawn_crafting_screen(&mut commands, recipe_book.unavailable());
+
+            // Free the cursor so the buttons can be clicked.
+            for mut cursor in cursors.iter_mut() {
+                cursor.grab_mode = CursorGrabMode::None;
+                cursor.visible = true;
+            }
+        }
+        return;
+    }
+
+    if !toggled && !cursor_grabbed {
+        return;
+    }
+
+    screen.open = false;
+    for root in roots.iter() {
+        commands.entity(root).despawn();
+    }
+    for mut cursor in cursors.iter_mut() {
+        cursor.grab_mode = CursorGrabMode::Locked;
+        cursor.visible = false;
+    }
+
+    // The server moves whatever is left in the grid back into the inventory.
+    close_events.write(CloseContainer {
+        window_id: INVENTORY_WINDOW,
+    });
+}
+
+/// Spawns the crafting screen, or only a message saying why there's nothing
+/// to craft with if the recipe book is `unavailable`.
+fn spawn_crafting_screen(commands: &mut Commands, unavailable: Option<&str>) {
+    commands
+        .spawn((
+            Name::new("Crafting Screen"),
+            CraftingScreenRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Crafting"),
+                TextFont {
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            if let Some(reason) = unavailable {
+                parent.spawn((
+                    Text::new(format!("{reason}, so crafting is unavailable.")),
+                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ));
+                return;
+            }
+
+            parent
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(24.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(Node {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::px(
+                            u16::from(INVENTORY_GRID_SIZE),
+                            SLOT_SIZE,
+                        ),
+                        row_gap: Val::Px(4.0),
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    })
+                    .with_children(|grid| {
+                        for slot in RESULT_SLOT + 1..=GRID_SLOTS {
+                            spawn_slot(grid, slot);
+                        }
+                    });
+
+                    row.spawn((Text::new("->"), TextColor(Color::WHITE)));
+                    spawn_slot(row, RESULT_SLOT);
+
+                    row.spawn((
+                        CraftButton,
+                        Button,
+                        Node {
+                            width: Val::Px(120.0),
+                            height: Val::Px(40.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((Text::new("Craft"), TextColor(Color::WHITE)));
+                    });
+                });
+
+            spawn_recipe_list(parent, INVENTORY_WINDOW, INVENTORY_GRID_SIZE);
+        });
+}
+
+/// Spawns a scrolling list of the recipe book whose buttons place recipes in
+/// the `grid_size` crafting grid of the window `window_id`.
+pub(crate) fn spawn_recipe_list(parent: &mut ChildSpawnerCommands, window_id: i32, grid_size: u8) {
+    parent.spawn((
+        Text::new("Click a recipe to place it; shift-click to fill the grid."),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+    ));
+
+    parent.spawn((
+        RecipeList {
+            window_id,
+            grid_size,
+        },
+        Node {
+            width: Val::Px(640.0),
+            max_height: Val::Percent(40.0),
+            flex_wrap: FlexWrap::Wrap,
+            row_gap: Val::Px(4.0),
+            column_gap: Val::Px(4.0),
+            overflow: Overflow::scroll_y(),
+            ..default()
+        },
+        ScrollPosition::default(),
+    ));
+}
+
+fn spawn_slot(parent: &mut ChildSpawnerCommands, slot: usize) {
+    parent
+        .spawn((
+            Node {
+                width: Val::Px(SLOT_SIZE),
+                height: Val::Px(SLOT_SIZE),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(SLOT_COLOR),
+        ))
+        .with_children(|slot_node| {
+            slot_node.spawn((
+                SlotText(slot),
+                Text::default(),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(Justify::Center),
+            ));
+        });
+}
+
+pub(crate) fn item_name(mc_data: &MinecraftData, item_id: i32) -> String {
+    u16::try_from(item_id)
+        .ok()
+        .and_then(|item_id| mc_data.items().get_by_id(ItemId(item_id)))
+        .map_or_else(
+            || format!("#{item_id}"),
+            |item| item.display_name.to_string(),
+        )
+}
+
+/// Returns the item's name, preceded by the count if there is more than one.
+pub(crate) fn stack_label(mc_data: &MinecraftData, stack: ItemStack) -> String {
+    let name = item_name(mc_data, stack.item_id);
+    if stack.count > 1 {
+        format!("{} {}", stack.count, name)
+    } else {
+        name
+    }
+}
+
+/// Returns the recipes to list, those that fit in the grid first, then by
+/// the name of what they make.
+fn listed_recipes(
+    recipe_book: &RecipeBook,
+    grid_size: u8,
+    mut name: impl FnMut(i32) -> String,
+) -> Vec<(&Recipe, bool)> {
+    let mut recipes: Vec<_> = recipe_book
+        .iter()
+        .map(|recipe| {
+            let fits = recipe.shape.fits_in(grid_size);
+            (!fits, name(recipe.result.item_id), recipe)
+        })
+        .collect();
+    recipes.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    recipes
+        .into_iter()
+        .map(|(too_big, _, recipe)| (recipe, !too_big))
+        .collect()
+}
+
+fn update_recipe_list(
+    mut commands: Commands,
+    recipe_book: Res<RecipeBook>,
+    mc_data: Res<MinecraftData>,
+    lists: Query<(Entity, Ref<RecipeList>)>,
+) {
+    for (entity, list) in lists.iter() {
+        if !recipe_book.is_changed() && !list.is_added() {
+            continue;
+        }
+
+        commands.entity(entity).despawn_related::<Children>();
+
+        let recipes = listed_recipes(&recipe_book, list.grid_size, |item_id| {
+            item_name(&mc_data, item_id)
+        });
+        let window_id = list.window_id;
+        commands.entity(entity).with_children(|list| {
+            if recipes.is_empty() {
+                let message = recipe_book.unavailable().unwrap_or("No recipes unlocked");
+                list.spawn((Text::new(message), TextColor(Color::srgb(0.8, 0.8, 0.8))));
+            }
+
+            for (recipe, fits) in recipes {
+                let mut label = stack_label(&mc_data, recipe.result);
+                if !fits {
+                    label.push_str(" (crafting table)");
+                }
+                list.spawn((
+                    RecipeButton {
+                        window_id,
+                        recipe_id: recipe.id,
+                        fits,
+                    },
+                    Button,
+                    Node {
+                        width: Val::Px(208.0),
+                        height: Val::Px(28.0),
+                        align_items: AlignItems::Center,
+                        padding: UiRect::horizontal(Val::Px(6.0)),
+                        ..default()
+                    },
+                    BackgroundColor(if fits {
+                        BUTTON_COLOR
+                    } else {
+                        DISABLED_BUTTON_COLOR
+                    }),
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Text::new(label),
+                        TextFont {
+                            font_size: 13.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+            }
+        });
+    }
+}
+
+fn update_slot_texts(
+    grid: Res<CraftingGrid>,
+    mc_data: Res<MinecraftData>,
+    mut texts: Query<(Ref<SlotText>, &mut Text)>,
+) {
+    for (slot, mut text) in texts.iter_mut() {
+        if !grid.is_changed() && !slot.is_added() {
+            continue;
+        }
+
+        let item = if slot.0 == RESULT_SLOT {
+            grid.result
+        } else {
+            grid.grid[slot.0 - 1]
+        };
+        text.0 = item.map_or_else(String::new, |item| stack_label(&mc_data, item));
+    }
+}
+
+fn scroll_recipe_list(
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut lists: Query<&mut ScrollPosition, With<RecipeList>>,
+) {
+    let pixels = match mouse_scroll.unit {
+        MouseScrollUnit::Line => mouse_scroll.delta.y * PIXELS_PER_SCROLL_LINE,
+        MouseScrollUnit::Pixel => mouse_scroll.delta.y,
+    };
+    if pixels == 0.0 {
+        return;
+    }
+
+    for mut scroll in lists.iter_mut() {
+        // Bevy clamps the position to the content when laying out.
+        scroll.y = (scroll.y - pixels).max(0.0);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn press_recipe_buttons(
+    input: InputMap,
+    mut buttons: Query<(&Interaction, &RecipeButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut place_events: MessageWriter<PlaceRecipe>,
+) {
+    for (interaction, button, mut background) in buttons.iter_mut() {
+        if !button.fits {
+            continue;
+        }
+
+        background.0 = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+
+        if *interaction == Interaction::Pressed {
+            place_events.write(PlaceRecipe {
+                window_id: button.window_id,
+                recipe_id: button.recipe_id,
+                make_all: input.pressed(InputAction::QuickMoveModifier),
+            });
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn press_craft_button(
+    grid: Res<CraftingGrid>,
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<CraftButton>),
+    >,
+    mut click_events: MessageWriter<ClickContainer>,
+) {
+    for (interaction, mut background) in buttons.iter_mut() {
+        background.0 = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+
+        if *interaction == Interaction::Pressed && grid.result.is_some() {
+            click_events.write(grid.craft_all());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_proto::event::{Ingredient, RecipeShape};
+
+    use super::*;
+
+    fn recipe(id: i32, width: u8, result_item_id: i32) -> Recipe {
+        Recipe {
+            id,
+            shape: RecipeShape::Shaped {
+                width,
+                height: 1,
+                ingredients: vec![Some(Ingredient::Items(vec![1])); usize::from(width)],
+            },
+            result: ItemStack {
+                item_id: result_item_id,
+                count: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn recipe_book_adds_replaces_and_removes() {
+        let mut book = RecipeBook::default();
+        book.add(&RecipesAdded {
+            recipes: vec![recipe(1, 1, 10), recipe(2, 1, 20)],
+            replace: false,
+        });
+        book.add(&RecipesAdded {
+            recipes: vec![recipe(3, 1, 30)],
+            replace: false,
+        });
+        book.remove(&[2]);
+        assert_eq!(book.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 3]);
+
+        book.add(&RecipesAdded {
+            recipes: vec![recipe(4, 1, 40)],
+            replace: true,
+        });
+        assert_eq!(book.len(), 1);
+        assert!(book.get(4).is_some());
+    }
+
+    #[test]
+    fn recipes_that_fit_are_listed_first() {
+        let mut book = RecipeBook::default();
+        book.add(&RecipesAdded {
+            recipes: vec![recipe(1, 3, 1), recipe(2, 2, 3), recipe(3, 1, 2)],
+            replace: false,
+        });
+
+        let listed: Vec<_> = listed_recipes(&book, 2, |item_id| item_id.to_string())
+            .into_iter()
+            .map(|(recipe, fits)| (recipe.id, fits))
+            .collect();
+        assert_eq!(listed, vec![(3, true), (2, true), (1, false)]);
+    }
+
+    #[test]
+    fn grid_tracks_crafting_slots_and_state() {
+        let stick = ItemStack {
+            item_id: 5,
+            count: 4,
+        };
+        let mut grid = CraftingGrid::default();
+        grid.set_slot(RESULT_SLOT, Some(stick));
+        grid.set_slot(3, Some(stick));
+        // Not part of the grid.
+        grid.set_slot(36, Some(stick));
+        grid.state_id = 7;
+
+        assert_eq!(grid.result(), Some(stick));
+        assert_eq!(grid.grid(), &[None, None, Some(stick), None]);
+
+        let click = grid.craft_all();
+        assert_eq!((click.slot, click.state_id), (0, 7));
+        assert_eq!(click.mode, ClickMode::QuickMove);
+    }
+}
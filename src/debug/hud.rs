@@ -0,0 +1,282 @@
+use std::fmt::Write;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use brine_chunk::BlockPos;
+use brine_data::{BlockStateId, MinecraftData};
+use brine_net::NetworkResource;
+use brine_proto::{
+    event::clientbound::{BlockChanged, ChunkData},
+    MessageBuffer,
+};
+use brine_proto_backend::ProtocolCodec;
+use brine_render::ui_text::MinecraftText;
+use brine_voxel_v1::chunk_builder::component::{BuiltChunk, ChunkBiomes};
+
+use crate::{
+    game_mode::{PlayerGameMode, ServerDifficulty},
+    input::{InputAction, InputMap, KeyBindings},
+    latency::Latency,
+    player::look_angles,
+    targeting::TargetedBlock,
+};
+
+/// Plugin that shows an F3-style text overlay with information about the
+/// player's position, the targeted block, and client performance.
+///
+/// Pressing and releasing the debug modifier key (F3 by default) on its own
+/// toggles the overlay. Releasing it after using it as a modifier for another
+/// shortcut (e.g., F3 + B) does not.
+pub struct DebugHudPlugin;
+
+impl Plugin for DebugHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud)
+            .add_systems(Update, (toggle_hud, update_hud).chain());
+    }
+}
+
+/// Marker component for the debug HUD text.
+#[derive(Component)]
+pub struct DebugHud;
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Debug HUD"),
+        DebugHud,
+        MinecraftText::new("").with_shadow(false),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.0),
+            left: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            box_sizing: BoxSizing::ContentBox,
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+fn toggle_hud(
+    input: InputMap,
+    bindings: Res<KeyBindings>,
+    mut used_as_modifier: Local<bool>,
+    mut huds: Query<&mut Visibility, With<DebugHud>>,
+) {
+    let modifier_keys = bindings.get(InputAction::DebugModifier);
+
+    if input.pressed(InputAction::DebugModifier)
+        && input
+            .keyboard()
+            .get_just_pressed()
+            .any(|key| !modifier_keys.contains(key))
+    {
+        *used_as_modifier = true;
+    }
+
+    if input.just_released(InputAction::DebugModifier) {
+        if !*used_as_modifier {
+            for mut visibility in huds.iter_mut() {
+                visibility.toggle_visible_hidden();
+            }
+        }
+        *used_as_modifier = false;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_hud(
+    mut huds: Query<(&mut MinecraftText, &Visibility), With<DebugHud>>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    chunks: Query<(&BuiltChunk, Option<&ChunkBiomes>)>,
+    targeted: Res<TargetedBlock>,
+    mc_data: Res<MinecraftData>,
+    diagnostics: Res<DiagnosticsStore>,
+    net_resource: Option<Res<NetworkResource<ProtocolCodec>>>,
+    chunk_buffer: Option<Res<MessageBuffer<ChunkData>>>,
+    block_buffer: Option<Res<MessageBuffer<BlockChanged>>>,
+    game_mode: Option<Res<PlayerGameMode>>,
+    difficulty: Option<Res<ServerDifficulty>>,
+    latency: Option<Res<Latency>>,
+) {
+    let Ok((mut text, visibility)) = huds.single_mut() else {
+        return;
+    };
+
+    if visibility == Visibility::Hidden {
+        return;
+    }
+
+    let mut out = String::new();
+
+    if let Some(fps) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    {
+        let _ = writeln!(out, "{fps:.0} fps");
+    }
+
+    if let Some(chunk_buffer) = chunk_buffer {
+        let _ = writeln!(
+            out,
+            "Chunk queue: {} pending, {} dropped",
+            chunk_buffer.len(),
+            chunk_buffer.dropped()
+        );
+    }
+
+    if let Some(block_buffer) = block_buffer {
+        let _ = writeln!(
+            out,
+            "Block change queue: {} pending, {} dropped",
+            block_buffer.len(),
+            block_buffer.dropped()
+        );
+    }
+
+    if let Ok(camera) = cameras.single() {
+        let position = camera.translation();
+        let block = BlockPos::containing(position);
+        let section = block.section();
+        let local = block.section_key();
+
+        let _ = writeln!(
+            out,
+            "XYZ: {:.3} / {:.3} / {:.3}",
+            position.x, position.y, position.z
+        );
+        let _ = writeln!(out, "Block: {} {} {}", block.x, block.y, block.z);
+        let _ = writeln!(
+            out,
+            "Chunk: {} {} {} in {} {} {}",
+            local.x, local.y, local.z, section.x, section.y, section.z
+        );
+
+        let forward = camera.forward();
+        let (yaw, pitch) = look_angles(*forward);
+        let _ = writeln!(
+            out,
+            "Facing: {} ({:.1} / {:.1})",
+            facing(*forward),
+            yaw,
+            pitch
+        );
+
+        let biome = chunks
+            .iter()
+            .find(|(built, _)| built.chunk_x == chunk.x && built.chunk_z == chunk.z)
+            .and_then(|(_, biomes)| biomes)
+            .map(|biomes| biomes.0.get(local.x as u8, local.z as u8));
+        match biome {
+            Some(biome) => {
+                let _ = writeln!(out, "Biome: {}", biome.0);
+            }
+            None => {
+                let _ = writeln!(out, "Biome: unknown");
+            }
+        }
+    }
+
+    if let (Some(game_mode), Some(difficulty)) = (game_mode, difficulty) {
+        let _ = writeln!(
+            out,
+            "Game mode: {:?}, difficulty: {:?}{}",
+            game_mode.0,
+            difficulty.difficulty,
+            if difficulty.locked { " (locked)" } else { "" }
+        );
+    }
+
+    if let Some(hit) = targeted.0 {
+        let position = hit.position;
+        let _ = writeln!(
+            out,
+            "\n§nTargeted Block§r: {} {} {}",
+            position.x, position.y, position.z
+        );
+
+        let block_state_id = BlockStateId(hit.block_state.0 as u16);
+        match mc_data.blocks().get_by_state_id(block_state_id) {
+            Some(block) => {
+                let _ = writeln!(out, "minecraft:{}", block.name);
+
+                let mut properties: Vec<_> = block.state.iter().collect();
+                properties.sort_by_key(|(name, _)| **name);
+                for (name, value) in properties {
+                    let _ = writeln!(out, "{name}: {value}");
+                }
+            }
+            None => {
+                let _ = writeln!(out, "unknown block state {}", block_state_id.0);
+            }
+        }
+    }
+
+    if let Some(net_resource) = net_resource {
+        let stats = net_resource.stats();
+        let _ = writeln!(
+            out,
+            "\nNetwork: {}, {} rx / {} tx packets",
+            if net_resource.is_connected() {
+                "connected"
+            } else {
+                "disconnected"
+            },
+            stats.packets_received,
+            stats.packets_sent
+        );
+
+        let codec = net_resource.codec();
+        if let Some(threshold) = codec.compression_threshold() {
+            let _ = write!(out, "Compression: threshold {} B", threshold);
+            if let Some(ratio) = codec.inbound_compression().ratio() {
+                let _ = write!(out, ", {:.1}x rx", ratio);
+            }
+            if codec.outbound_compression_disabled() {
+                out.push_str(", tx off");
+            } else if let Some(ratio) = codec.outbound_compression().ratio() {
+                let _ = write!(out, ", {:.1}x tx", ratio);
+            }
+            let _ = writeln!(out);
+        }
+
+        let samples = latency
+            .as_ref()
+            .and_then(|latency| Some((latency, latency.last()?, latency.average()?)));
+        if let Some((latency, last, average)) = samples {
+            let _ = write!(
+                out,
+                "Ping: {} ms (avg {} ms",
+                last.as_millis(),
+                average.as_millis()
+            );
+            if let Some(jitter) = latency.jitter() {
+                let _ = write!(out, ", jitter {} ms", jitter.as_millis());
+            }
+            let _ = writeln!(out, ")");
+        }
+    }
+
+    if text.text != out {
+        text.text = out;
+    }
+}
+
+/// Returns a Minecraft-style description of the cardinal direction closest to
+/// the given horizontal facing.
+fn facing(forward: Vec3) -> &'static str {
+    if forward.x.abs() > forward.z.abs() {
+        if forward.x > 0.0 {
+            "east (Towards positive X)"
+        } else {
+            "west (Towards negative X)"
+        }
+    } else if forward.z > 0.0 {
+        "south (Towards positive Z)"
+    } else {
+        "north (Towards negative Z)"
+    }
+}
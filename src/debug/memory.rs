@@ -0,0 +1,188 @@
+use std::{collections::HashSet, mem, time::Duration};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+    time::common_conditions::on_timer,
+};
+use bevy_mesh::Indices;
+use brine_render::texture::TextureAtlas;
+use brine_voxel_v1::chunk_builder::component::{
+    BuiltChunkSection, ChunkBiomes, ChunkSection as ChunkSectionComponent,
+};
+
+const BYTES_PER_MIB: f64 = 1024.0 * 1024.0;
+
+/// How often the estimates are recomputed. Walking every chunk section is too
+/// slow to do each frame.
+const MEASURE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Plugin that estimates how much memory the world's chunks and textures use,
+/// broken down per subsystem.
+///
+/// Meshes and images keep their CPU copy by default, so each figure is roughly
+/// the size of both the CPU copy and the GPU upload.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`MemoryUsage`], reflected so it shows up in the inspector
+///
+/// The plugin publishes the following diagnostics, in MiB:
+///
+/// * [`MemoryUsage::CHUNK_MESHES`]
+/// * [`MemoryUsage::CHUNK_ATLASES`]
+/// * [`MemoryUsage::TEXTURE_ATLASES`]
+/// * [`MemoryUsage::CHUNK_DATA`]
+/// * [`MemoryUsage::TOTAL`]
+pub struct MemoryDiagnosticsPlugin;
+
+impl Plugin for MemoryDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        for path in [
+            MemoryUsage::CHUNK_MESHES,
+            MemoryUsage::CHUNK_ATLASES,
+            MemoryUsage::TEXTURE_ATLASES,
+            MemoryUsage::CHUNK_DATA,
+            MemoryUsage::TOTAL,
+        ] {
+            app.register_diagnostic(Diagnostic::new(path).with_suffix(" MiB"));
+        }
+
+        app.register_type::<MemoryUsage>()
+            .init_resource::<MemoryUsage>()
+            .add_systems(
+                Update,
+                (measure_memory_usage, publish_memory_usage)
+                    .chain()
+                    .run_if(on_timer(MEASURE_INTERVAL)),
+            );
+    }
+}
+
+/// Estimated memory use, in bytes, as of the last measurement.
+#[derive(Resource, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct MemoryUsage {
+    /// Vertex and index buffers of built chunk section meshes.
+    pub chunk_meshes: usize,
+
+    /// Texture atlases referenced by chunk section materials, each counted
+    /// once however many sections share it.
+    pub chunk_atlases: usize,
+
+    /// Atlases stitched by the texture manager.
+    pub texture_atlases: usize,
+
+    /// Block states and biomes retained for built chunks.
+    pub chunk_data: usize,
+}
+
+impl MemoryUsage {
+    pub const CHUNK_MESHES: DiagnosticPath = DiagnosticPath::const_new("brine/memory/chunk_meshes");
+    pub const CHUNK_ATLASES: DiagnosticPath =
+        DiagnosticPath::const_new("brine/memory/chunk_atlases");
+    pub const TEXTURE_ATLASES: DiagnosticPath =
+        DiagnosticPath::const_new("brine/memory/texture_atlases");
+    pub const CHUNK_DATA: DiagnosticPath = DiagnosticPath::const_new("brine/memory/chunk_data");
+    pub const TOTAL: DiagnosticPath = DiagnosticPath::const_new("brine/memory/total");
+
+    pub fn total(&self) -> usize {
+        self.chunk_meshes + self.chunk_atlases + self.texture_atlases + self.chunk_data
+    }
+}
+
+/// Returns the size of a mesh's vertex and index buffers.
+pub fn mesh_size(mesh: &Mesh) -> usize {
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() * mem::size_of::<u16>(),
+        Some(Indices::U32(indices)) => indices.len() * mem::size_of::<u32>(),
+        None => 0,
+    };
+
+    mesh.get_vertex_buffer_size() + indices
+}
+
+/// Returns the size of an image's pixel data, or zero if it only lives on the
+/// GPU.
+pub fn image_size(image: &Image) -> usize {
+    image.data.as_ref().map_or(0, Vec::len)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn measure_memory_usage(
+    sections: Query<(&Mesh3d, &MeshMaterial3d<StandardMaterial>), With<BuiltChunkSection>>,
+    section_data: Query<(), With<ChunkSectionComponent>>,
+    biomes: Query<(), With<ChunkBiomes>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    images: Res<Assets<Image>>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut usage: ResMut<MemoryUsage>,
+) {
+    let mut measured = MemoryUsage::default();
+    let mut chunk_atlases = HashSet::new();
+
+    for (mesh, material) in sections.iter() {
+        if let Some(mesh) = meshes.get(mesh) {
+            measured.chunk_meshes += mesh_size(mesh);
+        }
+
+        if let Some(texture) = materials
+            .get(material)
+            .and_then(|material| material.base_color_texture.as_ref())
+        {
+            chunk_atlases.insert(texture.id());
+        }
+    }
+
+    measured.chunk_data = section_data.iter().count() * mem::size_of::<brine_chunk::ChunkSection>();
+    measured.chunk_data += biomes.iter().count() * mem::size_of::<brine_chunk::Biomes>();
+
+    measured.chunk_atlases = chunk_atlases
+        .into_iter()
+        .filter_map(|id| images.get(id))
+        .map(image_size)
+        .sum();
+
+    measured.texture_atlases = texture_atlases
+        .iter()
+        .filter_map(|(_, atlas)| images.get(&atlas.texture))
+        .map(image_size)
+        .sum();
+
+    usage.set_if_neq(measured);
+}
+
+fn publish_memory_usage(usage: Res<MemoryUsage>, mut diagnostics: Diagnostics) {
+    let mib = |bytes: usize| bytes as f64 / BYTES_PER_MIB;
+
+    diagnostics.add_measurement(&MemoryUsage::CHUNK_MESHES, || mib(usage.chunk_meshes));
+    diagnostics.add_measurement(&MemoryUsage::CHUNK_ATLASES, || mib(usage.chunk_atlases));
+    diagnostics.add_measurement(&MemoryUsage::TEXTURE_ATLASES, || mib(usage.texture_atlases));
+    diagnostics.add_measurement(&MemoryUsage::CHUNK_DATA, || mib(usage.chunk_data));
+    diagnostics.add_measurement(&MemoryUsage::TOTAL, || mib(usage.total()));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::RenderAssetUsages;
+    use bevy_mesh::PrimitiveTopology;
+
+    use super::*;
+
+    #[test]
+    fn mesh_size_counts_vertices_and_indices() {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0f32; 3]; 4]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0f32; 2]; 4]);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2, 0, 2, 3]));
+
+        // 4 vertices of 12 + 8 bytes, then 6 four-byte indices.
+        assert_eq!(mesh_size(&mesh), 4 * 20 + 6 * 4);
+    }
+}
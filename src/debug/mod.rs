@@ -1,3 +1,13 @@
+mod hud;
+mod memory;
+mod overlays;
+mod performance;
 mod wireframe;
 
+pub use hud::{DebugHud, DebugHudPlugin};
+pub use memory::{image_size, mesh_size, MemoryDiagnosticsPlugin, MemoryUsage};
+pub use overlays::{
+    DebugOverlaysPlugin, EnableBlockOutline, EnableChunkBorders, EnableHitboxes, Hitbox,
+};
+pub use performance::{PerformanceOverlay, PerformanceOverlayPlugin};
 pub use wireframe::{DebugWireframePlugin, EnableWireframe};
@@ -0,0 +1,207 @@
+use bevy::{color::palettes::css, prelude::*};
+use brine_chunk::{CHUNK_HEIGHT, CHUNK_MIN_Y, CHUNK_WIDTH, SECTION_HEIGHT};
+
+use crate::{
+    input::{InputAction, InputMap},
+    targeting::TargetedBlock,
+};
+
+/// Number of chunks around the camera's chunk to draw borders for.
+const CHUNK_BORDER_RADIUS: i32 = 1;
+
+pub struct DebugOverlaysPlugin;
+
+impl Plugin for DebugOverlaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Hitbox>()
+            .register_type::<EnableHitboxes>()
+            .register_type::<EnableChunkBorders>()
+            .register_type::<EnableBlockOutline>()
+            .add_systems(Startup, spawn_components)
+            .add_systems(
+                Update,
+                (
+                    toggle_overlays,
+                    draw_hitboxes,
+                    draw_chunk_borders,
+                    draw_block_outline,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Axis-aligned bounding box of an entity, centered horizontally on the
+/// entity's position and extending upwards from it.
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct Hitbox {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct EnableHitboxes {
+    pub enable: bool,
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct EnableChunkBorders {
+    pub enable: bool,
+}
+
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct EnableBlockOutline {
+    pub enable: bool,
+}
+
+fn spawn_components(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Debug Hitboxes"),
+        EnableHitboxes { enable: false },
+    ));
+    commands.spawn((
+        Name::new("Debug Chunk Borders"),
+        EnableChunkBorders { enable: false },
+    ));
+    commands.spawn((
+        Name::new("Debug Block Outline"),
+        EnableBlockOutline { enable: true },
+    ));
+}
+
+fn toggle_overlays(
+    input: InputMap,
+    mut hitboxes: Query<&mut EnableHitboxes>,
+    mut chunk_borders: Query<&mut EnableChunkBorders>,
+    mut block_outline: Query<&mut EnableBlockOutline>,
+) {
+    if !input.pressed(InputAction::DebugModifier) {
+        return;
+    }
+
+    if input.just_pressed(InputAction::ToggleHitboxes) {
+        for mut component in hitboxes.iter_mut() {
+            component.enable = !component.enable;
+            info!("Hitboxes: {}", on_off(component.enable));
+        }
+    }
+
+    if input.just_pressed(InputAction::ToggleChunkBorders) {
+        for mut component in chunk_borders.iter_mut() {
+            component.enable = !component.enable;
+            info!("Chunk borders: {}", on_off(component.enable));
+        }
+    }
+
+    if input.just_pressed(InputAction::ToggleBlockOutline) {
+        for mut component in block_outline.iter_mut() {
+            component.enable = !component.enable;
+            info!("Block outline: {}", on_off(component.enable));
+        }
+    }
+}
+
+fn on_off(enable: bool) -> &'static str {
+    if enable {
+        "shown"
+    } else {
+        "hidden"
+    }
+}
+
+fn draw_hitboxes(
+    component: Query<&EnableHitboxes>,
+    hitboxes: Query<(&Hitbox, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    if !component.single().is_ok_and(|component| component.enable) {
+        return;
+    }
+
+    for (hitbox, transform) in hitboxes.iter() {
+        let size = Vec3::new(hitbox.width, hitbox.height, hitbox.width);
+        let center = transform.translation() + Vec3::Y * hitbox.height / 2.0;
+        gizmos.cuboid(
+            Transform::from_translation(center).with_scale(size),
+            css::WHITE,
+        );
+
+        // Facing direction, like the vanilla hitbox view.
+        let eye = transform.translation() + Vec3::Y * hitbox.height * 0.85;
+        gizmos.line(eye, eye + *transform.forward() * 2.0, css::BLUE);
+    }
+}
+
+fn draw_chunk_borders(
+    component: Query<&EnableChunkBorders>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut gizmos: Gizmos,
+) {
+    if !component.single().is_ok_and(|component| component.enable) {
+        return;
+    }
+
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let width = CHUNK_WIDTH as f32;
+    let min_y = CHUNK_MIN_Y as f32;
+    let max_y = min_y + CHUNK_HEIGHT as f32;
+
+    let camera_chunk = (camera.translation() / width).floor();
+
+    // Vertical lines at the corners of the surrounding chunks.
+    for dx in -CHUNK_BORDER_RADIUS..=CHUNK_BORDER_RADIUS + 1 {
+        for dz in -CHUNK_BORDER_RADIUS..=CHUNK_BORDER_RADIUS + 1 {
+            let x = (camera_chunk.x + dx as f32) * width;
+            let z = (camera_chunk.z + dz as f32) * width;
+            gizmos.line(Vec3::new(x, min_y, z), Vec3::new(x, max_y, z), css::RED);
+        }
+    }
+
+    // Section boundaries of the chunk the camera is in.
+    let x = camera_chunk.x * width;
+    let z = camera_chunk.z * width;
+    let mut y = min_y;
+    while y <= max_y {
+        let color = if y == min_y || y == max_y {
+            css::RED
+        } else {
+            css::YELLOW
+        };
+        gizmos.linestrip(
+            [
+                Vec3::new(x, y, z),
+                Vec3::new(x + width, y, z),
+                Vec3::new(x + width, y, z + width),
+                Vec3::new(x, y, z + width),
+                Vec3::new(x, y, z),
+            ],
+            color,
+        );
+        y += SECTION_HEIGHT as f32;
+    }
+}
+
+fn draw_block_outline(
+    component: Query<&EnableBlockOutline>,
+    targeted: Res<TargetedBlock>,
+    mut gizmos: Gizmos,
+) {
+    if !component.single().is_ok_and(|component| component.enable) {
+        return;
+    }
+
+    if let Some(hit) = targeted.0 {
+        let center = hit.position.as_vec3() + Vec3::splat(0.5);
+        gizmos.cuboid(
+            Transform::from_translation(center).with_scale(Vec3::splat(1.002)),
+            css::BLACK,
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::{
+    diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::entity::Entities,
+    prelude::*,
+};
+use bevy_inspector_egui::{
+    bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass},
+    egui,
+};
+use brine_proto::{event::clientbound::ChunkData, MessageBuffer};
+use brine_proto_backend::ProtocolBackendPlugin;
+use brine_voxel_v1::chunk_builder::component::{BuiltChunk, PendingChunk};
+
+use crate::{
+    entity::ServerEntities,
+    input::{InputAction, InputMap},
+};
+
+/// Frame times the graph draws guide lines at, in milliseconds: 60 and 30 fps.
+const GUIDE_FRAME_TIMES: [f64; 2] = [1000.0 / 60.0, 1000.0 / 30.0];
+
+/// Size of the frame time graph, in points.
+const GRAPH_SIZE: egui::Vec2 = egui::vec2(240.0, 64.0);
+
+/// Plugin that shows an egui window with what the client spends its time on:
+/// a graph of recent frame times, the chunks waiting to be meshed, network
+/// throughput, and entity counts.
+///
+/// F3 + P toggles the window. Requires the [`FrameTimeDiagnosticsPlugin`] for
+/// the graph, and adds the [`EguiPlugin`] unless it was already added.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`PerformanceOverlay`]
+pub struct PerformanceOverlayPlugin;
+
+impl Plugin for PerformanceOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+
+        app.init_resource::<PerformanceOverlay>()
+            .add_systems(Update, toggle_overlay)
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_overlay.run_if(|overlay: Res<PerformanceOverlay>| overlay.shown),
+            );
+    }
+}
+
+/// Whether the performance overlay is shown.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PerformanceOverlay {
+    pub shown: bool,
+}
+
+fn toggle_overlay(input: InputMap, mut overlay: ResMut<PerformanceOverlay>) {
+    if input.pressed(InputAction::DebugModifier)
+        && input.just_pressed(InputAction::TogglePerformanceOverlay)
+    {
+        overlay.shown = !overlay.shown;
+        info!(
+            "Performance overlay: {}",
+            if overlay.shown { "shown" } else { "hidden" }
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_overlay(
+    mut contexts: EguiContexts,
+    diagnostics: Res<DiagnosticsStore>,
+    chunk_buffer: Option<Res<MessageBuffer<ChunkData>>>,
+    pending_chunks: Query<(), With<PendingChunk>>,
+    built_chunks: Query<(), With<BuiltChunk>>,
+    server_entities: Option<Res<ServerEntities>>,
+    entities: &Entities,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let frame_times: Vec<f64> = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .map(|frame_time| frame_time.values().copied().collect())
+        .unwrap_or_default();
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+    };
+
+    egui::Window::new("Performance")
+        .anchor(egui::Align2::RIGHT_TOP, [-4.0, 4.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            if let (Some(fps), Some(frame_time)) = (
+                smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+                smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+            ) {
+                let worst = frame_times.iter().copied().fold(0.0, f64::max);
+                ui.label(format!(
+                    "{fps:.0} fps, {frame_time:.1} ms per frame, {worst:.1} ms worst"
+                ));
+            }
+            frame_time_graph(ui, &frame_times);
+
+            ui.separator();
+            ui.label(format!(
+                "Chunks: {} waiting, {} meshing, {} built",
+                chunk_buffer.map_or(0, |buffer| buffer.len()),
+                pending_chunks.iter().count(),
+                built_chunks.iter().count()
+            ));
+
+            let throughput = |path| smoothed(path).unwrap_or_default();
+            ui.label(format!(
+                "Network: {:.1} KiB/s in, {:.1} KiB/s out",
+                throughput(&ProtocolBackendPlugin::INBOUND_THROUGHPUT),
+                throughput(&ProtocolBackendPlugin::OUTBOUND_THROUGHPUT)
+            ));
+
+            ui.label(format!(
+                "Entities: {} from the server, {} in total",
+                server_entities.map_or(0, |entities| entities.len()),
+                entities.len()
+            ));
+        });
+}
+
+/// Draws a bar for each of `frame_times` (in milliseconds), colored by the
+/// frame rate it falls under, with lines at the [`GUIDE_FRAME_TIMES`].
+fn frame_time_graph(ui: &mut egui::Ui, frame_times: &[f64]) {
+    let (rect, _) = ui.allocate_exact_size(GRAPH_SIZE, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(160));
+
+    let top = graph_top(frame_times);
+    let height_of = |frame_time: f64| rect.bottom() - (frame_time / top) as f32 * rect.height();
+
+    let bar_width = rect.width() / frame_times.len().max(1) as f32;
+    for (i, frame_time) in frame_times.iter().copied().enumerate() {
+        let left = rect.left() + i as f32 * bar_width;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left, height_of(frame_time)),
+            egui::pos2(left + bar_width, rect.bottom()),
+        );
+        painter.rect_filled(bar, 0.0, frame_time_color(frame_time));
+    }
+
+    for guide in GUIDE_FRAME_TIMES {
+        painter.hline(
+            rect.x_range(),
+            height_of(guide),
+            egui::Stroke::new(1.0, egui::Color32::GRAY),
+        );
+    }
+}
+
+/// Returns the frame time at the top of the graph: the longest frame, but no
+/// less than the last guide line, so that the guides are always drawn.
+fn graph_top(frame_times: &[f64]) -> f64 {
+    frame_times
+        .iter()
+        .copied()
+        .fold(GUIDE_FRAME_TIMES[GUIDE_FRAME_TIMES.len() - 1], f64::max)
+}
+
+fn frame_time_color(frame_time: f64) -> egui::Color32 {
+    if frame_time <= GUIDE_FRAME_TIMES[0] {
+        egui::Color32::GREEN
+    } else if frame_time <= GUIDE_FRAME_TIMES[1] {
+        egui::Color32::YELLOW
+    } else {
+        egui::Color32::RED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_fits_the_longest_frame_and_the_guides() {
+        assert_eq!(graph_top(&[]), 1000.0 / 30.0);
+        assert_eq!(graph_top(&[5.0, 10.0]), 1000.0 / 30.0);
+        assert_eq!(graph_top(&[5.0, 80.0, 10.0]), 80.0);
+    }
+}
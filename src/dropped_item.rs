@@ -0,0 +1,350 @@
+//! Dropped items, bobbing and spinning on the ground.
+//!
+//! Item entities learn what they are from [`EntityItemSet`] and get a
+//! [`DroppedItem`]. Items that place a full block are drawn as a small cube
+//! with the block's texture; everything else is a flat sprite of its item (or
+//! block) texture. Like vanilla, larger stacks show a few copies of the model
+//! scattered around each other, and every item bobs and spins with a phase of
+//! its own. The models are [`InstancedMesh`]es, so a floor covered in the
+//! same item costs one draw call.
+
+use std::f32::consts::TAU;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use brine_asset::{BlockFace, MinecraftAssets};
+use brine_data::{EntityTypeId, ItemId, MinecraftData};
+use brine_proto::{
+    event::{clientbound::EntityItemSet, ItemStack},
+    BrineSystems,
+};
+use brine_render::instancing::InstancedMesh;
+
+use crate::entity::{ServerEntities, ServerEntity};
+
+/// Ticks per second, which vanilla's bobbing and spinning are expressed in.
+const TICKS_PER_SECOND: f32 = 20.0;
+
+/// Size of the cube drawn for block items, in blocks.
+const BLOCK_SIZE: f32 = 0.25;
+
+/// Size of the sprite drawn for other items, in blocks.
+const SPRITE_SIZE: f32 = 0.5;
+
+/// How far apart the sprites of a stack are, front to back.
+const SPRITE_SPACING: f32 = 1.0 / 16.0;
+
+/// Where the copies of a stack's model are, relative to the first, before
+/// they are scaled to the model. Vanilla scatters them at random.
+const SCATTER: [Vec3; 5] = [
+    Vec3::ZERO,
+    Vec3::new(0.3, 0.2, -0.25),
+    Vec3::new(-0.25, 0.35, 0.2),
+    Vec3::new(0.15, 0.1, 0.35),
+    Vec3::new(-0.3, 0.25, -0.15),
+];
+
+/// The item an item entity is.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DroppedItem(pub ItemStack);
+
+/// The bobbing, spinning model of a [`DroppedItem`], as a child of its entity.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct DroppedItemModel {
+    /// Offset into the bobbing and spinning, in radians, so that items
+    /// dropped together don't move in step.
+    phase: f32,
+
+    /// Time since the model was attached, in ticks.
+    age: f32,
+}
+
+impl DroppedItemModel {
+    /// The model's transform relative to its entity, vanilla's bob of up to
+    /// 0.2 blocks and a full turn every 2π seconds.
+    fn transform(&self, lift: f32) -> Transform {
+        let bob = (self.age / 10.0 + self.phase).sin() * 0.1 + 0.1;
+        let spin = self.age / TICKS_PER_SECOND + self.phase;
+        Transform::from_xyz(0.0, lift + bob, 0.0).with_rotation(Quat::from_rotation_y(spin))
+    }
+}
+
+/// How an item is drawn.
+#[derive(Debug, Clone)]
+struct ItemModel {
+    mesh: InstancedMesh,
+
+    /// Whether the item is a block, drawn as a cube, rather than a sprite.
+    block: bool,
+}
+
+impl ItemModel {
+    /// How high above the entity's feet the model's center is at rest.
+    fn lift(&self) -> f32 {
+        if self.block {
+            BLOCK_SIZE / 2.0
+        } else {
+            SPRITE_SIZE / 2.0
+        }
+    }
+
+    /// Where each copy drawn for a stack of `count` items is, relative to the
+    /// model.
+    fn copy_offsets(&self, count: i32) -> impl Iterator<Item = Vec3> + '_ {
+        SCATTER
+            .iter()
+            .take(rendered_copies(count))
+            .enumerate()
+            .map(|(i, scatter)| {
+                if self.block {
+                    *scatter * BLOCK_SIZE
+                } else {
+                    // Sprites are stacked front to back, slightly askew.
+                    (*scatter * SPRITE_SIZE * 0.25).with_z(-(i as f32) * SPRITE_SPACING)
+                }
+            })
+    }
+}
+
+/// The meshes of dropped items, and the models of the items seen so far.
+#[derive(Resource)]
+struct DroppedItemModels {
+    cube: Handle<Mesh>,
+    sprite: Handle<Mesh>,
+    models: HashMap<i32, Option<ItemModel>>,
+}
+
+impl DroppedItemModels {
+    fn get(
+        &mut self,
+        item_id: i32,
+        mc_data: &MinecraftData,
+        mc_assets: &MinecraftAssets,
+        asset_server: &AssetServer,
+    ) -> Option<ItemModel> {
+        let (cube, sprite) = (&self.cube, &self.sprite);
+        self.models
+            .entry(item_id)
+            .or_insert_with(|| {
+                let item = mc_data
+                    .items()
+                    .get_by_id(ItemId(u16::try_from(item_id).ok()?))?;
+                let block_state = mc_data.blocks().default_state_id_by_name(item.name);
+
+                // Blocks with a full north face are drawn as cubes; other
+                // blocks (torches, flowers...) as sprites of their texture.
+                let block_texture = block_state.and_then(|block_state| {
+                    mc_assets
+                        .get_texture_path_for_block_state_and_face(block_state, BlockFace::North)
+                });
+                let (mesh, block, texture) = match block_texture {
+                    Some(texture) => (cube, true, texture),
+                    None => {
+                        let texture = block_state
+                            .and_then(|_| {
+                                mc_assets.get_texture_path_by_name(&format!("block/{}", item.name))
+                            })
+                            .unwrap_or_else(|| {
+                                mc_assets.get_any_texture_path(&format!("item/{}", item.name))
+                            });
+                        (sprite, false, texture)
+                    }
+                };
+
+                Some(ItemModel {
+                    mesh: InstancedMesh {
+                        mesh: mesh.clone(),
+                        texture: asset_server.load(texture),
+                    },
+                    block,
+                })
+            })
+            .clone()
+    }
+}
+
+/// Plugin that gives item entities a [`DroppedItem`] and draws it.
+///
+/// Requires the [`MinecraftData`] resource and the
+/// [`InstancingPlugin`][brine_render::instancing::InstancingPlugin], and waits
+/// for [`MinecraftAssets`] before drawing any items.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`EntityItemSet`]
+pub struct DroppedItemPlugin;
+
+impl Plugin for DroppedItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                set_dropped_items,
+                create_dropped_item_meshes.run_if(resource_added::<MinecraftAssets>),
+                (attach_dropped_item_models, animate_dropped_items)
+                    .run_if(resource_exists::<DroppedItemModels>),
+            )
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+/// Returns how many copies of an item's model vanilla draws for a stack of
+/// `count`.
+fn rendered_copies(count: i32) -> usize {
+    match count {
+        49.. => 5,
+        33..=48 => 4,
+        17..=32 => 3,
+        2..=16 => 2,
+        _ => 1,
+    }
+}
+
+fn create_dropped_item_meshes(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(DroppedItemModels {
+        cube: meshes.add(Cuboid::from_length(BLOCK_SIZE)),
+        sprite: meshes.add(Cuboid::new(SPRITE_SIZE, SPRITE_SIZE, SPRITE_SIZE / 16.0)),
+        models: HashMap::default(),
+    });
+}
+
+fn set_dropped_items(
+    mut commands: Commands,
+    mut item_events: MessageReader<EntityItemSet>,
+    mc_data: Res<MinecraftData>,
+    server_entities: Res<ServerEntities>,
+    entities: Query<(&ServerEntity, Option<&DroppedItem>)>,
+) {
+    for item_set in item_events.read() {
+        let Some(entity) = server_entities.get(item_set.entity_id) else {
+            continue;
+        };
+        let Ok((server_entity, dropped_item)) = entities.get(entity) else {
+            continue;
+        };
+        let is_item = u16::try_from(server_entity.entity_type)
+            .ok()
+            .and_then(|id| mc_data.entity_types().get_by_id(EntityTypeId(id)))
+            .is_some_and(|entity_type| entity_type.name == "item");
+        if !is_item {
+            continue;
+        }
+
+        match item_set.item {
+            Some(item) if dropped_item != Some(&DroppedItem(item)) => {
+                commands.entity(entity).insert(DroppedItem(item));
+            }
+            Some(_) => {}
+            None => {
+                commands.entity(entity).remove::<DroppedItem>();
+            }
+        }
+    }
+}
+
+/// Replaces the model of each item entity whose [`DroppedItem`] changed. Items
+/// set before the meshes were created count as changed the first time this
+/// runs.
+#[allow(clippy::too_many_arguments)]
+fn attach_dropped_item_models(
+    mut commands: Commands,
+    mc_data: Res<MinecraftData>,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut models: ResMut<DroppedItemModels>,
+    items: Query<(Entity, &ServerEntity, &DroppedItem, Option<&Children>), Changed<DroppedItem>>,
+    existing_models: Query<(), With<DroppedItemModel>>,
+    mut removed: RemovedComponents<DroppedItem>,
+) {
+    for entity in removed.read() {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.despawn_related::<Children>();
+        }
+    }
+
+    for (entity, server_entity, item, children) in items.iter() {
+        for child in children.into_iter().flatten() {
+            if existing_models.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+
+        let Some(model) = models.get(item.0.item_id, &mc_data, &mc_assets, &asset_server) else {
+            continue;
+        };
+
+        // Vanilla picks a random phase; the entity ID is as good as any.
+        let phase = (server_entity.id as f32 * 2.399_963).rem_euclid(TAU);
+        let animation = DroppedItemModel { phase, age: 0.0 };
+        let lift = model.lift();
+
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn((
+                    Name::new("Dropped Item"),
+                    animation,
+                    animation.transform(lift),
+                    Visibility::default(),
+                ))
+                .with_children(|model_parent| {
+                    for offset in model.copy_offsets(item.0.count) {
+                        model_parent
+                            .spawn((model.mesh.clone(), Transform::from_translation(offset)));
+                    }
+                });
+        });
+    }
+}
+
+fn animate_dropped_items(
+    time: Res<Time>,
+    mut models: Query<(&mut DroppedItemModel, &mut Transform, &ChildOf)>,
+    items: Query<&DroppedItem>,
+    models_by_item: Res<DroppedItemModels>,
+) {
+    let ticks = time.delta_secs() * TICKS_PER_SECOND;
+
+    for (mut model, mut transform, parent) in models.iter_mut() {
+        let Some(item_model) = items
+            .get(parent.parent())
+            .ok()
+            .and_then(|item| models_by_item.models.get(&item.0.item_id))
+            .and_then(Option::as_ref)
+        else {
+            continue;
+        };
+
+        model.age += ticks;
+        *transform = model.transform(item_model.lift());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn larger_stacks_show_more_copies() {
+        assert_eq!(rendered_copies(1), 1);
+        assert_eq!(rendered_copies(2), 2);
+        assert_eq!(rendered_copies(16), 2);
+        assert_eq!(rendered_copies(17), 3);
+        assert_eq!(rendered_copies(48), 4);
+        assert_eq!(rendered_copies(64), 5);
+    }
+
+    #[test]
+    fn items_bob_above_the_ground() {
+        for age in 0..200 {
+            let model = DroppedItemModel {
+                phase: 1.0,
+                age: age as f32,
+            };
+            let height = model.transform(0.125).translation.y;
+            assert!((0.125..=0.325 + 1e-6).contains(&height));
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Entities the server has told the client about.
+
+use std::f32::consts::PI;
+
+use bevy::{math::DVec3, platform::collections::HashMap, prelude::*};
+use brine_data::{EntityTypeId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{
+            EntitiesRemoved, EntityDamaged, EntityMoved, EntityPosition, EntitySpawned, JoinedGame,
+            Reconfigure, Transfer,
+        },
+        Uuid,
+    },
+    BrineSystems,
+};
+
+use crate::debug::Hitbox;
+
+/// How quickly rendered entities catch up with their position on the server,
+/// per second. Movement packets arrive at most once per tick, so without
+/// smoothing entities would visibly jump.
+const INTERPOLATION_RATE: f32 = 15.0;
+
+/// Entities that move further than this in one update are moved there
+/// immediately instead of sliding.
+const MAX_INTERPOLATED_DISTANCE: f32 = 8.0;
+
+/// How long an entity looks hurt after taking damage, in seconds (vanilla's
+/// 10 ticks).
+const HURT_DURATION: f32 = 0.5;
+
+/// An entity that exists on the server.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ServerEntity {
+    /// ID the server uses for the entity.
+    pub id: i32,
+
+    pub uuid: Uuid,
+
+    /// Numeric entity type; see [`brine_data::EntityTypes`].
+    pub entity_type: i32,
+}
+
+/// Where an entity is on the server.
+///
+/// The entity's [`Transform`] trails this to smooth out movement.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct ServerPosition(pub DVec3);
+
+/// Which way an entity is facing, in degrees.
+///
+/// A yaw of 0 faces south (+Z) and increases clockwise when viewed from above;
+/// a positive pitch looks down.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct EntityLook {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub head_yaw: f32,
+}
+
+impl EntityLook {
+    /// Returns the rotation that turns the Bevy forward direction (-Z) to face
+    /// `yaw`.
+    pub fn body_rotation(&self) -> Quat {
+        Quat::from_rotation_y(PI - self.yaw.to_radians())
+    }
+}
+
+/// Marks an entity that recently took damage, which models show by flashing
+/// red.
+///
+/// Removed once the flash is over.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Hurt {
+    /// Seconds until the entity stops looking hurt.
+    pub remaining: f32,
+}
+
+/// Mapping from server entity IDs to the Bevy entities representing them.
+#[derive(Resource, Debug, Default)]
+pub struct ServerEntities(HashMap<i32, Entity>);
+
+impl ServerEntities {
+    /// Returns the Bevy entity for the server entity with the given ID.
+    pub fn get(&self, entity_id: i32) -> Option<Entity> {
+        self.0.get(&entity_id).copied()
+    }
+
+    /// Returns how many server entities there are.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The server's entity ID for the local player, once the client has joined
+/// the world.
+///
+/// The local player has no [`ServerEntity`]; events about this ID concern the
+/// camera.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LocalPlayerId(pub Option<i32>);
+
+/// Plugin that spawns, moves and despawns an entity for every entity the
+/// server sends.
+///
+/// Spawned entities have [`ServerEntity`], [`ServerPosition`], [`EntityLook`]
+/// and [`Hitbox`] components, but nothing to render; see
+/// [`MobModelPlugin`][crate::mob_model::MobModelPlugin].
+///
+/// Requires the [`MinecraftData`] resource.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`EntitySpawned`]
+/// * [`EntityMoved`]
+/// * [`EntitiesRemoved`]
+/// * [`EntityDamaged`], which makes the entity [`Hurt`]
+/// * [`JoinedGame`]
+/// * [`Transfer`] and [`Reconfigure`], which despawn every entity
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`ServerEntities`]
+/// * [`LocalPlayerId`]
+pub struct EntityPlugin;
+
+impl Plugin for EntityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerEntities>()
+            .init_resource::<LocalPlayerId>()
+            .add_systems(
+                Update,
+                (
+                    despawn_entities_on_reset,
+                    track_local_player,
+                    spawn_entities,
+                    move_entities,
+                    despawn_entities,
+                    hurt_entities,
+                    recover_hurt_entities,
+                    interpolate_entities,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn despawn_entities_on_reset(
+    mut commands: Commands,
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut server_entities: ResMut<ServerEntities>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        for (_, entity) in server_entities.0.drain() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn track_local_player(
+    mut joined_events: MessageReader<JoinedGame>,
+    mut local_player: ResMut<LocalPlayerId>,
+) {
+    if let Some(joined) = joined_events.read().last() {
+        local_player.set_if_neq(LocalPlayerId(Some(joined.entity_id)));
+    }
+}
+
+fn spawn_entities(
+    mut commands: Commands,
+    mut spawned_events: MessageReader<EntitySpawned>,
+    mc_data: Res<MinecraftData>,
+    mut server_entities: ResMut<ServerEntities>,
+) {
+    for spawned in spawned_events.read() {
+        let entity_type = u16::try_from(spawned.entity_type)
+            .ok()
+            .and_then(|id| mc_data.entity_types().get_by_id(EntityTypeId(id)));
+
+        let (name, hitbox) = match entity_type {
+            Some(entity_type) => (
+                entity_type.display_name.to_string(),
+                Hitbox {
+                    width: entity_type.width,
+                    height: entity_type.height,
+                },
+            ),
+            None => {
+                warn!(
+                    "Entity {} has unknown type {}",
+                    spawned.entity_id, spawned.entity_type
+                );
+                (
+                    format!("Entity type {}", spawned.entity_type),
+                    Hitbox {
+                        width: 1.0,
+                        height: 1.0,
+                    },
+                )
+            }
+        };
+
+        let look = EntityLook {
+            yaw: spawned.yaw,
+            pitch: spawned.pitch,
+            head_yaw: spawned.head_yaw,
+        };
+
+        let entity = commands
+            .spawn((
+                Name::new(name),
+                ServerEntity {
+                    id: spawned.entity_id,
+                    uuid: spawned.uuid,
+                    entity_type: spawned.entity_type,
+                },
+                ServerPosition(spawned.position),
+                look,
+                hitbox,
+                Transform::from_translation(spawned.position.as_vec3())
+                    .with_rotation(look.body_rotation()),
+                Visibility::default(),
+            ))
+            .id();
+
+        // The server may reuse an ID without removing the old entity first.
+        if let Some(old) = server_entities.0.insert(spawned.entity_id, entity) {
+            commands.entity(old).despawn();
+        }
+    }
+}
+
+fn move_entities(
+    mut moved_events: MessageReader<EntityMoved>,
+    server_entities: Res<ServerEntities>,
+    mut entities: Query<(&mut ServerPosition, &mut EntityLook)>,
+) {
+    for moved in moved_events.read() {
+        let Some(entity) = server_entities.get(moved.entity_id) else {
+            continue;
+        };
+        let Ok((mut position, mut look)) = entities.get_mut(entity) else {
+            continue;
+        };
+
+        match moved.position {
+            Some(EntityPosition::Relative(delta)) => position.0 += delta,
+            Some(EntityPosition::Absolute(absolute)) => position.0 = absolute,
+            None => {}
+        }
+
+        if let Some(yaw) = moved.yaw {
+            look.yaw = yaw;
+        }
+        if let Some(pitch) = moved.pitch {
+            look.pitch = pitch;
+        }
+        if let Some(head_yaw) = moved.head_yaw {
+            look.head_yaw = head_yaw;
+        }
+    }
+}
+
+fn despawn_entities(
+    mut commands: Commands,
+    mut removed_events: MessageReader<EntitiesRemoved>,
+    mut server_entities: ResMut<ServerEntities>,
+) {
+    for removed in removed_events.read() {
+        for entity_id in &removed.entity_ids {
+            if let Some(entity) = server_entities.0.remove(entity_id) {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn hurt_entities(
+    mut commands: Commands,
+    mut damaged_events: MessageReader<EntityDamaged>,
+    server_entities: Res<ServerEntities>,
+) {
+    for damaged in damaged_events.read() {
+        if let Some(entity) = server_entities.get(damaged.entity_id) {
+            commands.entity(entity).insert(Hurt {
+                remaining: HURT_DURATION,
+            });
+        }
+    }
+}
+
+fn recover_hurt_entities(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut entities: Query<(Entity, &mut Hurt)>,
+) {
+    for (entity, mut hurt) in entities.iter_mut() {
+        hurt.remaining -= time.delta_secs();
+        if hurt.remaining <= 0.0 {
+            commands.entity(entity).remove::<Hurt>();
+        }
+    }
+}
+
+fn interpolate_entities(
+    time: Res<Time>,
+    mut entities: Query<(&ServerPosition, &EntityLook, &mut Transform), With<ServerEntity>>,
+) {
+    let t = 1.0 - (-INTERPOLATION_RATE * time.delta_secs()).exp();
+
+    for (position, look, mut transform) in entities.iter_mut() {
+        let target = position.0.as_vec3();
+
+        if transform.translation.distance(target) > MAX_INTERPOLATED_DISTANCE {
+            transform.translation = target;
+        } else {
+            transform.translation = transform.translation.lerp(target, t);
+        }
+        transform.rotation = transform.rotation.slerp(look.body_rotation(), t);
+    }
+}
@@ -1,7 +1,11 @@
-use std::fmt;
+//! Error handling helpers, and the on-screen list of errors.
+
+use std::{collections::VecDeque, fmt};
 
 use bevy::{app::AppExit, prelude::*};
 
+use brine_proto::{BrineSystems, ClientError};
+
 /// System that can be chained onto the end of another system to log any errors.
 pub fn log_error<T, E: fmt::Display>(In(result): In<Result<T, E>>) {
     if let Err(e) = result {
@@ -18,3 +22,189 @@ pub fn exit_on_error<T, E: fmt::Display>(
         app_exit.write(AppExit::error());
     }
 }
+
+/// How long an error stays on screen, in seconds.
+const ERROR_DISPLAY_SECONDS: f32 = 10.0;
+
+/// Most errors shown at once; older ones are dropped.
+const MAX_SHOWN_ERRORS: usize = 4;
+
+/// Plugin that logs [`ClientError`]s and shows the recent ones in the
+/// bottom-left corner of the screen, each with a hint on what to do about it.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ClientError`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`RecentErrors`]
+pub struct ClientErrorPlugin;
+
+impl Plugin for ClientErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecentErrors>()
+            .add_systems(Startup, spawn_error_list)
+            .add_systems(
+                Update,
+                (collect_errors, update_error_list)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+/// An error shown on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentError {
+    pub error: ClientError,
+
+    /// How many times the error happened in a row.
+    pub count: usize,
+
+    /// When the error last happened, in seconds since startup.
+    pub last_seen: f32,
+}
+
+/// The errors currently shown on screen, oldest first.
+#[derive(Resource, Debug, Default)]
+pub struct RecentErrors {
+    errors: VecDeque<RecentError>,
+}
+
+impl RecentErrors {
+    /// Adds an error that happened at `now`. Repeats of the latest error are
+    /// counted rather than listed again.
+    pub fn push(&mut self, error: ClientError, now: f32) {
+        if let Some(latest) = self.errors.back_mut() {
+            if latest.error == error {
+                latest.count += 1;
+                latest.last_seen = now;
+                return;
+            }
+        }
+
+        if self.errors.len() == MAX_SHOWN_ERRORS {
+            self.errors.pop_front();
+        }
+        self.errors.push_back(RecentError {
+            error,
+            count: 1,
+            last_seen: now,
+        });
+    }
+
+    /// Forgets errors that haven't happened for a while.
+    pub fn expire(&mut self, now: f32) {
+        self.errors
+            .retain(|error| now - error.last_seen < ERROR_DISPLAY_SECONDS);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecentError> {
+        self.errors.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The HUD text listing recent errors.
+#[derive(Component, Debug)]
+struct ErrorList;
+
+fn spawn_error_list(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Errors"),
+        ErrorList,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.4, 0.4)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+    ));
+}
+
+fn collect_errors(
+    time: Res<Time>,
+    mut client_errors: MessageReader<ClientError>,
+    mut recent_errors: ResMut<RecentErrors>,
+) {
+    let now = time.elapsed_secs();
+
+    for error in client_errors.read() {
+        warn!("{} ({})", error.message, error.hint());
+        recent_errors.push(error.clone(), now);
+    }
+
+    recent_errors.expire(now);
+}
+
+fn update_error_list(
+    recent_errors: Res<RecentErrors>,
+    mut texts: Query<&mut Text, With<ErrorList>>,
+) {
+    let lines: Vec<_> = recent_errors
+        .iter()
+        .map(|recent| {
+            let repeats = if recent.count > 1 {
+                format!(" (x{})", recent.count)
+            } else {
+                String::new()
+            };
+            format!(
+                "{}{}\n  {}",
+                recent.error.message,
+                repeats,
+                recent.error.hint()
+            )
+        })
+        .collect();
+    let message = lines.join("\n");
+
+    for mut text in texts.iter_mut() {
+        if text.0 != message {
+            text.0.clone_from(&message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_proto::ClientErrorKind;
+
+    use super::*;
+
+    fn error(message: &str) -> ClientError {
+        ClientError::new(ClientErrorKind::Connection, message)
+    }
+
+    #[test]
+    fn repeated_errors_are_counted_and_expire() {
+        let mut errors = RecentErrors::default();
+        errors.push(error("a"), 0.0);
+        errors.push(error("a"), 1.0);
+        errors.push(error("b"), 2.0);
+
+        let counts: Vec<_> = errors.iter().map(|recent| recent.count).collect();
+        assert_eq!(counts, [2, 1]);
+
+        errors.expire(1.0 + ERROR_DISPLAY_SECONDS);
+        let messages: Vec<_> = errors
+            .iter()
+            .map(|recent| recent.error.message.as_str())
+            .collect();
+        assert_eq!(messages, ["b"]);
+    }
+}
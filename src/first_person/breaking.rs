@@ -0,0 +1,195 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use brine_asset::MinecraftAssets;
+use brine_proto::event::clientbound::BlockBreakProgress;
+
+/// Number of destroy stage textures (`block/destroy_stage_0..9`).
+const DESTROY_STAGES: usize = 10;
+
+/// Overlay cubes are slightly larger than a block to avoid z-fighting.
+const OVERLAY_SIZE: f32 = 1.002;
+
+/// Digging progress of the local player.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct DiggingProgress {
+    /// World position of the block being dug, if any.
+    pub target: Option<IVec3>,
+
+    /// Progress towards breaking the target block, in the range `0.0..=1.0`.
+    pub progress: f32,
+}
+
+impl DiggingProgress {
+    /// Returns the destroy stage (`0..=9`) to display for the current
+    /// progress, or `None` if the player is not digging.
+    pub fn stage(&self) -> Option<u8> {
+        self.target?;
+        let stage = (self.progress.clamp(0.0, 1.0) * DESTROY_STAGES as f32) as u8;
+        Some(stage.min(DESTROY_STAGES as u8 - 1))
+    }
+}
+
+/// A crack overlay drawn on top of a block that is being broken.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockBreakingOverlay {
+    pub position: IVec3,
+    pub stage: u8,
+}
+
+/// Who is breaking the block that an overlay belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Breaker {
+    LocalPlayer,
+    Entity(i32),
+}
+
+type OverlayQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut BlockBreakingOverlay,
+        &'static mut Transform,
+        &'static mut MeshMaterial3d<StandardMaterial>,
+    ),
+>;
+
+#[derive(Resource)]
+struct DestroyStageAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+#[derive(Resource, Default)]
+struct BreakingOverlays {
+    overlays: HashMap<Breaker, Entity>,
+}
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<DiggingProgress>()
+        .init_resource::<BreakingOverlays>()
+        .add_systems(
+            Update,
+            (
+                load_destroy_stage_assets.run_if(resource_added::<MinecraftAssets>),
+                (handle_block_break_progress, update_local_digging_overlay)
+                    .run_if(resource_exists::<DestroyStageAssets>),
+            )
+                .chain(),
+        );
+}
+
+fn load_destroy_stage_assets(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut stage_materials = Vec::with_capacity(DESTROY_STAGES);
+
+    for stage in 0..DESTROY_STAGES {
+        let name = format!("block/destroy_stage_{stage}");
+        let Some(path) = mc_assets.get_texture_path_by_name(&name) else {
+            warn!("Missing texture {}, block breaking overlay disabled", name);
+            return;
+        };
+
+        stage_materials.push(materials.add(StandardMaterial {
+            base_color_texture: Some(asset_server.load(path)),
+            alpha_mode: AlphaMode::Multiply,
+            unlit: true,
+            ..default()
+        }));
+    }
+
+    commands.insert_resource(DestroyStageAssets {
+        mesh: meshes.add(Cuboid::from_length(OVERLAY_SIZE)),
+        materials: stage_materials,
+    });
+}
+
+fn handle_block_break_progress(
+    mut commands: Commands,
+    mut events: MessageReader<BlockBreakProgress>,
+    assets: Res<DestroyStageAssets>,
+    mut overlays: ResMut<BreakingOverlays>,
+    mut query: OverlayQuery,
+) {
+    for event in events.read() {
+        let breaker = Breaker::Entity(event.entity_id);
+        set_overlay(
+            &mut commands,
+            &assets,
+            &mut overlays,
+            &mut query,
+            breaker,
+            event.stage.map(|stage| (event.position, stage)),
+        );
+    }
+}
+
+fn update_local_digging_overlay(
+    mut commands: Commands,
+    digging: Res<DiggingProgress>,
+    assets: Res<DestroyStageAssets>,
+    mut overlays: ResMut<BreakingOverlays>,
+    mut query: OverlayQuery,
+) {
+    if !digging.is_changed() {
+        return;
+    }
+
+    let overlay = digging.target.zip(digging.stage());
+    set_overlay(
+        &mut commands,
+        &assets,
+        &mut overlays,
+        &mut query,
+        Breaker::LocalPlayer,
+        overlay,
+    );
+}
+
+/// Moves, re-textures, spawns, or despawns the overlay belonging to `breaker`.
+fn set_overlay(
+    commands: &mut Commands,
+    assets: &DestroyStageAssets,
+    overlays: &mut BreakingOverlays,
+    query: &mut OverlayQuery,
+    breaker: Breaker,
+    overlay: Option<(IVec3, u8)>,
+) {
+    let Some((position, stage)) = overlay else {
+        if let Some(entity) = overlays.overlays.remove(&breaker) {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    let material = assets.materials[(stage as usize).min(DESTROY_STAGES - 1)].clone();
+    let transform = Transform::from_translation(position.as_vec3() + Vec3::splat(0.5));
+    let component = BlockBreakingOverlay { position, stage };
+
+    if let Some(&entity) = overlays.overlays.get(&breaker) {
+        if let Ok((mut existing, mut existing_transform, mut existing_material)) =
+            query.get_mut(entity)
+        {
+            if *existing != component {
+                *existing = component;
+                *existing_transform = transform;
+                existing_material.0 = material;
+            }
+            return;
+        }
+    }
+
+    let entity = commands
+        .spawn((
+            Name::new("Block Breaking Overlay"),
+            component,
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(material),
+            transform,
+        ))
+        .id();
+    overlays.overlays.insert(breaker, entity);
+}
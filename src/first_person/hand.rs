@@ -0,0 +1,153 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use brine_asset::{BlockFace, MinecraftAssets};
+use brine_data::BlockStateId;
+
+use super::DiggingProgress;
+
+/// Position of the hand relative to the camera.
+const HAND_OFFSET: Vec3 = Vec3::new(0.4, -0.35, -0.6);
+
+/// Full swings per second while digging.
+const SWING_RATE: f32 = 3.0;
+
+/// The block currently held in the local player's main hand, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HeldBlock(pub Option<BlockStateId>);
+
+/// Marker component for the first-person hand attached to a camera.
+#[derive(Component, Debug, Default)]
+pub struct FirstPersonHand {
+    /// Swing animation phase in the range `0.0..1.0`.
+    swing: f32,
+}
+
+/// Marker component for the block model held by the first-person hand.
+#[derive(Component, Debug, Default)]
+struct HeldBlockModel;
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<HeldBlock>().add_systems(
+        Update,
+        (
+            attach_hand_to_cameras,
+            update_held_block.run_if(resource_exists::<MinecraftAssets>),
+            swing_hand,
+        ),
+    );
+}
+
+fn hand_rest_transform() -> Transform {
+    Transform::from_translation(HAND_OFFSET).with_rotation(Quat::from_euler(
+        EulerRot::XYZ,
+        -0.35,
+        0.3,
+        0.0,
+    ))
+}
+
+fn attach_hand_to_cameras(
+    mut commands: Commands,
+    cameras: Query<Entity, Added<Camera3d>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for camera in cameras.iter() {
+        let arm_mesh = meshes.add(Cuboid::new(0.15, 0.15, 0.55));
+        let arm_material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.84, 0.63, 0.5),
+            unlit: true,
+            ..default()
+        });
+
+        let block_mesh = meshes.add(Cuboid::from_length(0.25));
+        let block_material = materials.add(StandardMaterial {
+            unlit: true,
+            alpha_mode: AlphaMode::Mask(0.5),
+            ..default()
+        });
+
+        commands.entity(camera).with_children(|parent| {
+            parent
+                .spawn((
+                    Name::new("First-Person Hand"),
+                    FirstPersonHand::default(),
+                    hand_rest_transform(),
+                    Visibility::default(),
+                ))
+                .with_children(|hand| {
+                    hand.spawn((
+                        Mesh3d(arm_mesh),
+                        MeshMaterial3d(arm_material),
+                        Transform::default(),
+                    ));
+                    hand.spawn((
+                        HeldBlockModel,
+                        Mesh3d(block_mesh),
+                        MeshMaterial3d(block_material),
+                        Transform::from_xyz(0.0, 0.12, -0.3)
+                            .with_rotation(Quat::from_rotation_y(PI / 4.0)),
+                        Visibility::Hidden,
+                    ));
+                });
+        });
+    }
+}
+
+/// Updates the held block model's texture when the [`HeldBlock`] changes.
+fn update_held_block(
+    held_block: Res<HeldBlock>,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut models: Query<(&MeshMaterial3d<StandardMaterial>, &mut Visibility), With<HeldBlockModel>>,
+    added_models: Query<(), Added<HeldBlockModel>>,
+) {
+    if !held_block.is_changed() && !mc_assets.is_added() && added_models.is_empty() {
+        return;
+    }
+
+    let texture = held_block.0.and_then(|block_state_id| {
+        mc_assets.get_texture_path_for_block_state_and_face(block_state_id, BlockFace::North)
+    });
+
+    for (material, mut visibility) in models.iter_mut() {
+        match texture.as_ref() {
+            Some(path) => {
+                if let Some(material) = materials.get_mut(&material.0) {
+                    material.base_color_texture = Some(asset_server.load(path.clone()));
+                }
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+/// Swings the hand back and forth while the local player is digging.
+fn swing_hand(
+    time: Res<Time>,
+    digging: Res<DiggingProgress>,
+    mut hands: Query<(&mut FirstPersonHand, &mut Transform)>,
+) {
+    for (mut hand, mut transform) in hands.iter_mut() {
+        if digging.target.is_some() || hand.swing > 0.0 {
+            hand.swing += time.delta_secs() * SWING_RATE;
+            if hand.swing >= 1.0 {
+                // Finish the current swing before coming to rest.
+                hand.swing = if digging.target.is_some() {
+                    hand.swing.fract()
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        let rest = hand_rest_transform();
+        let arc = (hand.swing * PI).sin();
+
+        transform.translation = rest.translation + Vec3::new(-0.1, 0.05, -0.1) * arc;
+        transform.rotation = rest.rotation * Quat::from_rotation_x(-0.8 * arc);
+    }
+}
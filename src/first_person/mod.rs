@@ -0,0 +1,39 @@
+//! First-person view rendering.
+//!
+//! This module draws the player's arm (and the block they are holding, if
+//! any) in the corner of the view, as well as the crack overlay on blocks that
+//! are being broken.
+
+mod breaking;
+mod hand;
+
+use bevy::prelude::*;
+
+pub use breaking::{BlockBreakingOverlay, DiggingProgress};
+pub use hand::{FirstPersonHand, HeldBlock};
+
+/// Plugin that renders the first-person hand and block-breaking overlays.
+///
+/// The hand is attached to every entity with a [`Camera3d`] component.
+///
+/// # Events
+///
+/// The plugin listens for the following events:
+///
+/// * [`brine_proto::event::clientbound::BlockBreakProgress`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`DiggingProgress`]: the local player's digging progress. Whatever drives
+///   block interaction is expected to update this.
+/// * [`HeldBlock`]: the block the local player is holding, if any.
+pub struct FirstPersonPlugin;
+
+impl Plugin for FirstPersonPlugin {
+    fn build(&self, app: &mut App) {
+        hand::build(app);
+        breaking::build(app);
+    }
+}
@@ -0,0 +1,121 @@
+//! Starting and stopping flight in creative mode.
+//!
+//! When the [`PlayerAbilities`] allow flight, tapping
+//! [`Ascend`][InputAction::Ascend] twice in quick succession toggles flying,
+//! as in vanilla, and tells the server with a [`SetFlying`] event. Spectators
+//! always fly and can't land.
+//!
+//! There is no player controller yet, so the fly camera moves the same way
+//! whether or not the player is flying; what changes is its speed, which
+//! follows the flying or walking speed the server sent (see
+//! [`PlayerAbilities::speed_multiplier`]).
+
+use bevy::prelude::*;
+use brine_proto::{
+    event::{serverbound::SetFlying, GameMode},
+    BrineSystems,
+};
+
+use crate::{
+    game_mode::{PlayerAbilities, PlayerGameMode},
+    input::{InputAction, InputMap},
+};
+
+/// The longest gap between two jump presses that still counts as a double
+/// tap, in seconds. Vanilla allows 7 ticks.
+const DOUBLE_TAP_SECONDS: f64 = 0.35;
+
+/// Plugin that lets the player start and stop flying when allowed to.
+///
+/// # Events
+///
+/// The plugin sends the following events:
+///
+/// * [`SetFlying`]
+///
+/// # Ordering
+///
+/// Flying is toggled in [`BrineSystems::WorldUpdate`], after the
+/// [`GameModePlugin`][crate::game_mode::GameModePlugin] has applied the
+/// abilities the server sent this frame.
+pub struct FlightPlugin;
+
+impl Plugin for FlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_flying
+                .in_set(BrineSystems::WorldUpdate)
+                .after(crate::game_mode::handle_game_mode_events),
+        );
+    }
+}
+
+/// Recognizes two presses of a button within [`DOUBLE_TAP_SECONDS`].
+#[derive(Debug, Default)]
+struct DoubleTap {
+    last_press: Option<f64>,
+}
+
+impl DoubleTap {
+    /// Records a press at `now` and returns whether it completes a double
+    /// tap. A completed double tap doesn't count as the first press of the
+    /// next one.
+    fn press(&mut self, now: f64) -> bool {
+        match self.last_press.take() {
+            Some(last) if now - last <= DOUBLE_TAP_SECONDS => true,
+            _ => {
+                self.last_press = Some(now);
+                false
+            }
+        }
+    }
+}
+
+fn toggle_flying(
+    time: Res<Time<Real>>,
+    input: InputMap,
+    game_mode: Res<PlayerGameMode>,
+    mut abilities: ResMut<PlayerAbilities>,
+    mut double_tap: Local<DoubleTap>,
+    mut flying_events: MessageWriter<SetFlying>,
+) {
+    if **game_mode == GameMode::Spectator {
+        return;
+    }
+
+    if !input.just_pressed(InputAction::Ascend) {
+        return;
+    }
+
+    let double_tapped = double_tap.press(time.elapsed_secs_f64());
+    if !double_tapped || !abilities.may_fly {
+        return;
+    }
+
+    abilities.flying = !abilities.flying;
+    info!("Flying: {}", if abilities.flying { "on" } else { "off" });
+    flying_events.write(SetFlying {
+        flying: abilities.flying,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_tap_needs_two_quick_presses() {
+        let mut double_tap = DoubleTap::default();
+
+        assert!(!double_tap.press(1.0));
+        assert!(double_tap.press(1.2));
+
+        // The third press starts over rather than toggling again.
+        assert!(!double_tap.press(1.3));
+
+        // Too slow.
+        assert!(!double_tap.press(2.0));
+        assert!(double_tap.press(2.1));
+    }
+}
@@ -0,0 +1,132 @@
+//! The player's game mode and abilities, and the world's difficulty, as last
+//! sent by the server.
+
+use bevy::prelude::*;
+use brine_proto::{
+    event::{
+        clientbound::{
+            AbilitiesChanged, DifficultyChanged, GameModeChanged, Reconfigure, Transfer,
+        },
+        Abilities, Difficulty, GameMode,
+    },
+    BrineSystems,
+};
+
+/// The local player's game mode.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct PlayerGameMode(pub GameMode);
+
+/// The world's difficulty.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ServerDifficulty {
+    pub difficulty: Difficulty,
+
+    /// Whether the difficulty can no longer be changed.
+    pub locked: bool,
+}
+
+/// What the server allows the local player to do.
+///
+/// The [`FlightPlugin`][crate::flight::FlightPlugin] changes
+/// [`flying`][Abilities::flying] itself when the player starts or stops
+/// flying; everything else only changes when the server says so.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct PlayerAbilities(pub Abilities);
+
+impl PlayerAbilities {
+    /// Returns whether falling hurts the player.
+    pub fn takes_fall_damage(&self) -> bool {
+        !self.invulnerable
+    }
+
+    /// Returns how much faster than usual the player moves: the flying speed
+    /// while flying, and the walking speed otherwise, relative to their
+    /// defaults.
+    pub fn speed_multiplier(&self) -> f32 {
+        let defaults = Abilities::default();
+        if self.flying {
+            self.flying_speed / defaults.flying_speed
+        } else {
+            self.walking_speed / defaults.walking_speed
+        }
+    }
+}
+
+/// Plugin that keeps track of the [`PlayerGameMode`], [`ServerDifficulty`]
+/// and [`PlayerAbilities`].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`GameModeChanged`]
+/// * [`DifficultyChanged`]
+/// * [`AbilitiesChanged`]
+/// * [`Transfer`] and [`Reconfigure`], which reset the resources to their
+///   defaults until the server sends them again
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`PlayerGameMode`]
+/// * [`ServerDifficulty`]
+/// * [`PlayerAbilities`]
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerGameMode>()
+            .init_resource::<ServerDifficulty>()
+            .init_resource::<PlayerAbilities>()
+            .add_systems(
+                Update,
+                (reset_game_mode, handle_game_mode_events)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn reset_game_mode(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut game_mode: ResMut<PlayerGameMode>,
+    mut difficulty: ResMut<ServerDifficulty>,
+    mut abilities: ResMut<PlayerAbilities>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        game_mode.set_if_neq(PlayerGameMode::default());
+        difficulty.set_if_neq(ServerDifficulty::default());
+        abilities.set_if_neq(PlayerAbilities::default());
+    }
+}
+
+pub(crate) fn handle_game_mode_events(
+    mut game_mode_events: MessageReader<GameModeChanged>,
+    mut difficulty_events: MessageReader<DifficultyChanged>,
+    mut abilities_events: MessageReader<AbilitiesChanged>,
+    mut game_mode: ResMut<PlayerGameMode>,
+    mut difficulty: ResMut<ServerDifficulty>,
+    mut abilities: ResMut<PlayerAbilities>,
+) {
+    if let Some(event) = game_mode_events.read().last() {
+        if game_mode.set_if_neq(PlayerGameMode(event.game_mode)) {
+            info!("Game mode: {:?}", event.game_mode);
+        }
+    }
+
+    if let Some(event) = difficulty_events.read().last() {
+        difficulty.set_if_neq(ServerDifficulty {
+            difficulty: event.difficulty,
+            locked: event.locked,
+        });
+    }
+
+    if let Some(event) = abilities_events.read().last() {
+        abilities.set_if_neq(PlayerAbilities(event.abilities));
+    }
+}
@@ -0,0 +1,142 @@
+//! Gamepad camera and movement controls.
+//!
+//! Buttons are handled through the [`InputMap`] like keys are; this module
+//! adds the analog sticks: the left stick moves the fly camera and the right
+//! stick turns it.
+
+use bevy::prelude::*;
+use bevy_flycam::FlyCam;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_mode::PlayerAbilities,
+    input::{InputAction, InputMap},
+    status_effect::LocalStatusEffects,
+};
+
+/// Pitch limit in radians, matching the fly camera's mouse look.
+const MAX_PITCH: f32 = 1.54;
+
+/// Gamepad sensitivity and dead zone options.
+#[derive(Debug, Clone, PartialEq, Resource, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ControllerSettings {
+    /// How fast the camera turns, in radians per second at full deflection.
+    pub look_sensitivity: f32,
+
+    /// How fast the camera moves, in blocks per second at full deflection.
+    pub move_speed: f32,
+
+    /// Stick deflection (0.0 to 1.0) below which input is ignored.
+    pub dead_zone: f32,
+
+    /// Whether pushing the right stick up looks down.
+    pub invert_y: bool,
+}
+
+impl Default for ControllerSettings {
+    fn default() -> Self {
+        Self {
+            look_sensitivity: 3.0,
+            move_speed: 12.0,
+            dead_zone: 0.15,
+            invert_y: false,
+        }
+    }
+}
+
+/// Plugin that lets a gamepad move and turn the [`FlyCam`].
+///
+/// If the [`SettingsPlugin`][crate::settings::SettingsPlugin] is added before
+/// this plugin, the [`ControllerSettings`] come from the settings file;
+/// otherwise the defaults are used.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`ControllerSettings`]
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControllerSettings>()
+            .add_systems(PreUpdate, apply_dead_zone)
+            .add_systems(Update, move_fly_cam);
+    }
+}
+
+/// Applies the configured dead zone to Bevy's per-gamepad axis settings, so
+/// that stick values read from [`Gamepad`] are already filtered.
+fn apply_dead_zone(settings: Res<ControllerSettings>, mut gamepads: Query<&mut GamepadSettings>) {
+    let dead_zone = settings.dead_zone.clamp(0.0, 1.0);
+
+    for mut gamepad_settings in gamepads.iter_mut() {
+        if !settings.is_changed() && !gamepad_settings.is_added() {
+            continue;
+        }
+
+        let axis_settings = &mut gamepad_settings.default_axis_settings;
+        axis_settings.set_deadzone_lowerbound(-dead_zone);
+        axis_settings.set_deadzone_upperbound(dead_zone);
+    }
+}
+
+fn move_fly_cam(
+    time: Res<Time>,
+    settings: Res<ControllerSettings>,
+    status_effects: Option<Res<LocalStatusEffects>>,
+    abilities: Option<Res<PlayerAbilities>>,
+    input: InputMap,
+    mut cameras: Query<&mut Transform, With<FlyCam>>,
+) {
+    let (move_stick, look_stick) = input.gamepads().fold(
+        (Vec2::ZERO, Vec2::ZERO),
+        |(move_stick, look_stick), gamepad| {
+            (
+                move_stick + gamepad.left_stick(),
+                look_stick + gamepad.right_stick(),
+            )
+        },
+    );
+    let move_stick = move_stick.clamp_length_max(1.0);
+    let look_stick = look_stick.clamp_length_max(1.0);
+
+    let vertical = match (
+        input.gamepad_pressed(InputAction::Ascend),
+        input.gamepad_pressed(InputAction::Descend),
+    ) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => 0.0,
+    };
+
+    if move_stick == Vec2::ZERO && look_stick == Vec2::ZERO && vertical == 0.0 {
+        return;
+    }
+
+    let delta = time.delta_secs();
+    let invert = if settings.invert_y { -1.0 } else { 1.0 };
+    let speed = settings.move_speed
+        * status_effects.map_or(1.0, |effects| effects.0.speed_multiplier())
+        * abilities.map_or(1.0, |abilities| abilities.speed_multiplier());
+
+    for mut transform in cameras.iter_mut() {
+        if look_stick != Vec2::ZERO {
+            let (mut yaw, mut pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+            yaw -= look_stick.x * settings.look_sensitivity * delta;
+            pitch += look_stick.y * settings.look_sensitivity * delta * invert;
+            pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+            transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+        }
+
+        // Move along the horizontal plane regardless of pitch, like the fly
+        // camera's keyboard controls.
+        let local_z = transform.local_z();
+        let forward = -Vec3::new(local_z.x, 0.0, local_z.z).normalize_or_zero();
+        let right = Vec3::new(local_z.z, 0.0, -local_z.x).normalize_or_zero();
+
+        let velocity = forward * move_stick.y + right * move_stick.x + Vec3::Y * vertical;
+        transform.translation += velocity * speed * delta;
+    }
+}
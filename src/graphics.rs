@@ -0,0 +1,349 @@
+//! Graphics settings, their presets, and applying them at runtime.
+//!
+//! [`GraphicsSettings`] holds everything that trades frame rate for looks,
+//! and lives under `graphics` in the settings file. Whenever it changes, the
+//! [`GraphicsPlugin`] passes it on: MSAA to the camera, vsync to the window,
+//! the chunk mesher to the [`ActiveChunkBuilder`], and ambient occlusion and
+//! leaves to the [`MeshQuality`] (which remeshes every chunk), and how new
+//! chunks come into view to the [`ChunkAppearance`]. Chunks further
+//! away than the render distance are hidden, and the server is asked for no
+//! more than that when logging in.
+//!
+//! Pressing [`CycleGraphicsPreset`][InputAction::CycleGraphicsPreset] switches
+//! to the next [`GraphicsPreset`].
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
+use brine_chunk::ChunkPos;
+use brine_proto::BrineSystems;
+use brine_voxel_v1::{
+    appearance::ChunkAppearance,
+    chunk_builder::{component::BuiltChunk, ChunkBuilderType},
+    quality::{LeavesQuality, MeshQuality},
+    ActiveChunkBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::input::{InputAction, InputMap};
+
+/// How many samples each pixel gets for multisample anti-aliasing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MsaaSamples {
+    Off,
+    Sample2,
+    #[default]
+    Sample4,
+    Sample8,
+}
+
+impl From<MsaaSamples> for Msaa {
+    fn from(samples: MsaaSamples) -> Self {
+        match samples {
+            MsaaSamples::Off => Msaa::Off,
+            MsaaSamples::Sample2 => Msaa::Sample2,
+            MsaaSamples::Sample4 => Msaa::Sample4,
+            MsaaSamples::Sample8 => Msaa::Sample8,
+        }
+    }
+}
+
+/// Which chunk builder meshes chunks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkMesher {
+    /// A quad for every visible block face.
+    #[default]
+    VisibleFaces,
+
+    /// Fewer, larger quads merging the faces of neighboring blocks of the same
+    /// kind. Their textures are stretched rather than repeated.
+    GreedyQuads,
+}
+
+impl ChunkMesher {
+    pub fn builder_type(self) -> ChunkBuilderType {
+        match self {
+            Self::VisibleFaces => ChunkBuilderType::VISIBLE_FACES,
+            Self::GreedyQuads => ChunkBuilderType::GREEDY_QUADS,
+        }
+    }
+}
+
+/// A set of graphics settings, from fastest to best looking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsPreset {
+    Fast,
+    Balanced,
+    Fancy,
+}
+
+impl GraphicsPreset {
+    pub const ALL: [Self; 3] = [Self::Fast, Self::Balanced, Self::Fancy];
+
+    /// Returns the preset after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|preset| *preset == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Settings that trade frame rate for looks.
+///
+/// Vsync, the chunk mesher and how chunks appear aren't part of the
+/// [`GraphicsPreset`]s, which leave them as they are.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// How far away chunks are drawn, in chunks.
+    pub render_distance: u8,
+
+    pub msaa: MsaaSamples,
+    pub vsync: bool,
+    pub chunk_mesher: ChunkMesher,
+    pub ambient_occlusion: bool,
+    pub leaves: LeavesQuality,
+
+    /// How newly loaded chunks come into view.
+    pub chunk_appearance: ChunkAppearance,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            render_distance: 0,
+            msaa: MsaaSamples::default(),
+            vsync: true,
+            chunk_mesher: ChunkMesher::default(),
+            ambient_occlusion: false,
+            leaves: LeavesQuality::default(),
+            chunk_appearance: ChunkAppearance::default(),
+        }
+        .with_preset(GraphicsPreset::Balanced)
+    }
+}
+
+impl GraphicsSettings {
+    /// Changes the settings that `preset` covers to its values.
+    pub fn with_preset(self, preset: GraphicsPreset) -> Self {
+        let (render_distance, msaa, ambient_occlusion, leaves) = match preset {
+            GraphicsPreset::Fast => (6, MsaaSamples::Off, false, LeavesQuality::Fast),
+            GraphicsPreset::Balanced => (12, MsaaSamples::Sample4, true, LeavesQuality::Fast),
+            GraphicsPreset::Fancy => (16, MsaaSamples::Sample4, true, LeavesQuality::Fancy),
+        };
+
+        Self {
+            render_distance,
+            msaa,
+            ambient_occlusion,
+            leaves,
+            ..self
+        }
+    }
+
+    /// Returns the preset these settings match, if any.
+    pub fn preset(&self) -> Option<GraphicsPreset> {
+        GraphicsPreset::ALL
+            .into_iter()
+            .find(|preset| self.clone().with_preset(*preset) == *self)
+    }
+
+    /// The render distance as the view distance of Client Information.
+    pub fn view_distance(&self) -> i8 {
+        i8::try_from(self.render_distance).unwrap_or(i8::MAX)
+    }
+
+    pub fn mesh_quality(&self) -> MeshQuality {
+        MeshQuality {
+            ambient_occlusion: self.ambient_occlusion,
+            leaves: self.leaves,
+        }
+    }
+}
+
+/// Plugin that applies the [`GraphicsSettings`] to the camera, window and
+/// chunk plugins, and cycles through presets.
+///
+/// Should be added after the chunk builder plugins, the
+/// [`MeshQualityPlugin`][brine_voxel_v1::quality::MeshQualityPlugin] and the
+/// [`ChunkAppearancePlugin`][brine_voxel_v1::appearance::ChunkAppearancePlugin].
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`GraphicsSettings`], unless they were already loaded
+/// * [`ActiveChunkBuilder`]
+///
+/// # Ordering
+///
+/// Settings are applied and distant chunks hidden in
+/// [`BrineSystems::RenderPrep`].
+pub struct GraphicsPlugin;
+
+impl Plugin for GraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GraphicsSettings>();
+
+        // The active builder has to be known before the first chunk arrives,
+        // or every builder would build it.
+        let settings = app.world().resource::<GraphicsSettings>();
+        let active_builder = ActiveChunkBuilder(settings.chunk_mesher.builder_type());
+        app.insert_resource(active_builder).add_systems(
+            Update,
+            (
+                cycle_preset,
+                apply_graphics_settings.run_if(resource_changed::<GraphicsSettings>),
+                hide_distant_chunks,
+            )
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+fn cycle_preset(input: InputMap, mut settings: ResMut<GraphicsSettings>) {
+    if !input.just_pressed(InputAction::CycleGraphicsPreset) {
+        return;
+    }
+
+    let preset = settings
+        .preset()
+        .map_or(GraphicsPreset::Fast, GraphicsPreset::next);
+    info!("Graphics preset: {:?}", preset);
+    *settings = settings.clone().with_preset(preset);
+}
+
+fn apply_graphics_settings(
+    settings: Res<GraphicsSettings>,
+    mut cameras: Query<&mut Msaa, With<Camera3d>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut active_builder: ResMut<ActiveChunkBuilder>,
+    mut mesh_quality: Option<ResMut<MeshQuality>>,
+    mut chunk_appearance: Option<ResMut<ChunkAppearance>>,
+) {
+    for mut msaa in cameras.iter_mut() {
+        msaa.set_if_neq(settings.msaa.into());
+    }
+
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    for mut window in windows.iter_mut() {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+
+    active_builder.set_if_neq(ActiveChunkBuilder(settings.chunk_mesher.builder_type()));
+    if let Some(mesh_quality) = mesh_quality.as_mut() {
+        mesh_quality.set_if_neq(settings.mesh_quality());
+    }
+    if let Some(chunk_appearance) = chunk_appearance.as_mut() {
+        chunk_appearance.set_if_neq(settings.chunk_appearance);
+    }
+}
+
+/// Hides the chunks further from the camera's chunk than the render distance,
+/// along either axis.
+fn hide_distant_chunks(
+    settings: Res<GraphicsSettings>,
+    cameras: Query<&Transform, With<Camera3d>>,
+    mut chunks: Query<(&BuiltChunk, &mut Visibility)>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let camera_chunk = ChunkPos::containing(camera.translation);
+    let render_distance = u32::from(settings.render_distance);
+    for (chunk, mut visibility) in chunks.iter_mut() {
+        let shown = if chunk.position().chebyshev_distance(camera_chunk) <= render_distance {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(shown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::camera::visibility::VisibilityPlugin;
+    use brine_voxel_v1::chunk_builder::component::{BuiltChunkBundle, BuiltChunkSectionBundle};
+
+    use super::*;
+
+    #[test]
+    fn presets_are_recognized() {
+        assert_eq!(
+            GraphicsSettings::default().preset(),
+            Some(GraphicsPreset::Balanced)
+        );
+
+        for preset in GraphicsPreset::ALL {
+            let settings = GraphicsSettings {
+                vsync: false,
+                chunk_mesher: ChunkMesher::GreedyQuads,
+                ..default()
+            }
+            .with_preset(preset);
+            assert_eq!(settings.preset(), Some(preset));
+            assert!(!settings.vsync);
+        }
+
+        let custom = GraphicsSettings {
+            render_distance: 20,
+            ..default()
+        };
+        assert_eq!(custom.preset(), None);
+    }
+
+    #[test]
+    fn presets_cycle() {
+        assert_eq!(GraphicsPreset::Fast.next(), GraphicsPreset::Balanced);
+        assert_eq!(GraphicsPreset::Fancy.next(), GraphicsPreset::Fast);
+    }
+
+    #[test]
+    fn sections_of_distant_chunks_are_hidden() {
+        let mut app = App::new();
+        app.add_plugins(VisibilityPlugin)
+            .init_resource::<Assets<Mesh>>()
+            .insert_resource(GraphicsSettings {
+                render_distance: 2,
+                ..default()
+            })
+            .add_systems(Update, hide_distant_chunks);
+        app.world_mut()
+            .spawn((Camera3d::default(), Transform::default()));
+
+        let mut spawn_section = |chunk_x| {
+            let builder = ChunkBuilderType::VISIBLE_FACES;
+            let chunk = app
+                .world_mut()
+                .spawn(BuiltChunkBundle::new(builder, chunk_x, 0))
+                .id();
+            app.world_mut()
+                .spawn((BuiltChunkSectionBundle::new(builder, 4), ChildOf(chunk)))
+                .id()
+        };
+        let near = spawn_section(2);
+        let far = spawn_section(3);
+        app.update();
+
+        let shown = |section| {
+            app.world()
+                .get::<InheritedVisibility>(section)
+                .unwrap()
+                .get()
+        };
+        assert!(shown(near));
+        assert!(!shown(far));
+    }
+}
@@ -0,0 +1,206 @@
+//! Where the ground is in the loaded chunks.
+//!
+//! The heightmap of every full chunk is kept in the [`Heightmaps`] resource,
+//! which answers how high the ground is at any loaded (x, z). Chunks from a
+//! server come with their `MOTION_BLOCKING` heightmap; for the others (e.g.
+//! generated worlds and chunk dumps) it is worked out from their blocks.
+//! Placed blocks raise the ground, but broken ones don't lower it until the
+//! chunk is sent again.
+//!
+//! Until the server says where the player is ([`PlayerTeleported`]), the
+//! camera is put on the ground in the middle of the first chunk that loads,
+//! so that the player doesn't start out in the air or underground in worlds
+//! without a server.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use bevy_flycam::FlyCam;
+use brine_chunk::{
+    BlockPos, BlockState, Chunk, ChunkPos, ChunkRelative, Heightmap, CHUNK_MIN_Y, CHUNK_WIDTH,
+};
+use brine_proto::{
+    event::clientbound::{BlockChanged, ChunkData, PlayerTeleported, Reconfigure, Transfer},
+    BrineSystems,
+};
+
+use crate::player::{Freecam, PLAYER_EYE_HEIGHT};
+
+/// The heightmaps of the loaded chunks.
+#[derive(Resource, Debug, Default)]
+pub struct Heightmaps {
+    chunks: HashMap<ChunkPos, Box<Heightmap>>,
+}
+
+impl Heightmaps {
+    /// Returns the world Y just above the highest block at (x, z), where
+    /// something standing there has its feet, or `None` if the column isn't
+    /// loaded or has no blocks.
+    pub fn ground_height(&self, x: i32, z: i32) -> Option<i32> {
+        let block = BlockPos::new(x, 0, z);
+        let local = block.chunk_relative();
+        self.chunks.get(&block.chunk())?.surface_y(local.x, local.z)
+    }
+
+    /// Keeps the heightmap of a full chunk, working it out if the chunk came
+    /// without one.
+    fn insert(&mut self, chunk: &Chunk) {
+        if !chunk.is_full() {
+            return;
+        }
+        let heightmap = chunk
+            .heightmap
+            .clone()
+            .unwrap_or_else(|| Box::new(Heightmap::from_sections(&chunk.sections)));
+        self.chunks.insert(chunk.position(), heightmap);
+    }
+
+    /// Raises the ground to a block placed at `position` above it.
+    fn place(&mut self, position: IVec3) {
+        let block = BlockPos::from(position);
+        let local = block.chunk_relative();
+        let Some(heightmap) = self.chunks.get_mut(&block.chunk()) else {
+            return;
+        };
+        let Ok(height) = u16::try_from(position.y - CHUNK_MIN_Y as i32 + 1) else {
+            return;
+        };
+        let column = &mut heightmap.0[local.z as usize * CHUNK_WIDTH + local.x as usize];
+        *column = (*column).max(height);
+    }
+}
+
+/// Plugin that keeps the [`Heightmaps`] of the loaded chunks and puts the
+/// player on the ground until the server places it.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ChunkData`] and [`BlockChanged`]
+/// * [`PlayerTeleported`], after which the player is left where the server
+///   put it
+/// * [`Transfer`] and [`Reconfigure`], which forget every heightmap
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Heightmaps`]
+///
+/// # Ordering
+///
+/// Heightmaps are updated in [`BrineSystems::WorldUpdate`].
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Heightmaps>().add_systems(
+            Update,
+            (
+                forget_heightmaps_on_reset,
+                update_heightmaps,
+                place_player_on_ground,
+            )
+                .chain()
+                .in_set(BrineSystems::WorldUpdate),
+        );
+    }
+}
+
+fn forget_heightmaps_on_reset(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut heightmaps: ResMut<Heightmaps>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        heightmaps.chunks.clear();
+    }
+}
+
+fn update_heightmaps(
+    mut chunk_events: MessageReader<ChunkData>,
+    mut changed_events: MessageReader<BlockChanged>,
+    mut heightmaps: ResMut<Heightmaps>,
+) {
+    for chunk in chunk_events.read() {
+        heightmaps.insert(&chunk.chunk_data);
+    }
+    for changed in changed_events.read() {
+        if changed.block_state != BlockState::AIR {
+            heightmaps.place(changed.position);
+        }
+    }
+}
+
+/// Puts the camera on the ground in the middle of the first chunk to load,
+/// unless the server has already placed the player.
+fn place_player_on_ground(
+    mut placed: Local<bool>,
+    mut teleported_events: MessageReader<PlayerTeleported>,
+    mut chunk_events: MessageReader<ChunkData>,
+    heightmaps: Res<Heightmaps>,
+    freecam: Res<Freecam>,
+    mut cameras: Query<&mut Transform, With<FlyCam>>,
+) {
+    if teleported_events.read().count() > 0 {
+        *placed = true;
+    }
+    let loaded: Vec<_> = chunk_events
+        .read()
+        .filter(|chunk| chunk.chunk_data.is_full())
+        .map(|chunk| chunk.chunk_data.position())
+        .collect();
+    if *placed || freecam.enable {
+        return;
+    }
+
+    let middle = CHUNK_WIDTH as u8 / 2;
+    let ground = loaded.into_iter().find_map(|chunk| {
+        let column = chunk.block(ChunkRelative::new(middle, 0, middle));
+        let ground_height = heightmaps.ground_height(column.x, column.z)?;
+        let center = column.as_vec3() + 0.5;
+        Some(Vec3::new(center.x, ground_height as f32, center.z))
+    });
+    let Some(ground) = ground else {
+        return;
+    };
+
+    debug!("Placing the player on the ground at {}", ground);
+    for mut camera in cameras.iter_mut() {
+        camera.translation = ground + Vec3::Y * PLAYER_EYE_HEIGHT;
+    }
+    *placed = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_chunk::{BlockStates, ChunkSection};
+
+    use super::*;
+
+    #[test]
+    fn ground_height_comes_from_the_column_chunk() {
+        let mut block_states = BlockStates::default();
+        block_states.0[BlockStates::xyz_to_index(15, 3, 0)] = BlockState(1);
+        let chunk = Chunk {
+            sections: vec![ChunkSection {
+                chunk_y: 0,
+                block_count: 1,
+                block_states,
+            }],
+            ..Chunk::empty(-1, 2)
+        };
+
+        let mut heightmaps = Heightmaps::default();
+        heightmaps.insert(&chunk);
+
+        assert_eq!(heightmaps.ground_height(-1, 32), Some(4));
+        assert_eq!(heightmaps.ground_height(-2, 32), None);
+        assert_eq!(heightmaps.ground_height(0, 32), None);
+
+        heightmaps.place(IVec3::new(-1, 10, 32));
+        assert_eq!(heightmaps.ground_height(-1, 32), Some(11));
+    }
+}
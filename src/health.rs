@@ -0,0 +1,329 @@
+//! The player's health: a red flash when it drops, and the death screen with
+//! its respawn button when it runs out.
+
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, CursorOptions, PrimaryWindow},
+};
+use brine_proto::{
+    event::{
+        clientbound::{HealthUpdated, Reconfigure, Transfer},
+        serverbound::Respawn,
+    },
+    BrineSystems,
+};
+
+/// Opacity of the red screen tint right after taking damage.
+const MAX_FLASH_ALPHA: f32 = 0.3;
+
+/// How long the damage flash takes to fade, in seconds.
+const FLASH_DURATION: f32 = 0.5;
+
+/// The respawn button only appears after this many seconds, as in vanilla, so
+/// a click meant for something else doesn't respawn right away.
+const RESPAWN_DELAY: f32 = 1.0;
+
+const BUTTON_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.45, 0.45, 0.6);
+
+/// The local player's health and hunger, as last sent by the server.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PlayerHealth {
+    /// Health in half hearts.
+    pub health: f32,
+
+    /// Food level, from `0` to `20`.
+    pub food: i32,
+
+    pub saturation: f32,
+
+    /// Whether the server has sent the health yet; until then the fields hold
+    /// vanilla's starting values.
+    received: bool,
+}
+
+impl Default for PlayerHealth {
+    fn default() -> Self {
+        Self {
+            health: 20.0,
+            food: 20,
+            saturation: 5.0,
+            received: false,
+        }
+    }
+}
+
+impl PlayerHealth {
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    /// Applies an update from the server and returns whether the player lost
+    /// health.
+    fn update(&mut self, update: &HealthUpdated) -> bool {
+        let damaged = self.received && update.health < self.health;
+
+        self.health = update.health;
+        self.food = update.food;
+        self.saturation = update.saturation;
+        self.received = true;
+
+        damaged
+    }
+}
+
+/// Full-screen tint shown briefly when the player takes damage.
+#[derive(Component, Debug, Default)]
+struct DamageFlash {
+    /// Seconds until the flash has faded.
+    remaining: f32,
+}
+
+#[derive(Component, Debug, Default)]
+struct DeathScreen {
+    /// Seconds since the screen was shown.
+    shown_for: f32,
+    respawn_requested: bool,
+}
+
+#[derive(Component, Debug)]
+struct RespawnButton;
+
+/// Plugin that tracks the [`PlayerHealth`], flashes the screen red when the
+/// player takes damage, and shows a death screen whose button respawns the
+/// player.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`HealthUpdated`]
+/// * [`Transfer`] and [`Reconfigure`], which reset the health
+///
+/// The plugin sends the following events:
+///
+/// * [`Respawn`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`PlayerHealth`]
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerHealth>()
+            .add_systems(Startup, spawn_damage_flash)
+            .add_systems(
+                Update,
+                (
+                    reset_health,
+                    handle_health_events,
+                    fade_damage_flash,
+                    show_death_screen,
+                    press_respawn_button,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn reset_health(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut health: ResMut<PlayerHealth>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        *health = PlayerHealth::default();
+    }
+}
+
+fn handle_health_events(
+    mut health_events: MessageReader<HealthUpdated>,
+    mut health: ResMut<PlayerHealth>,
+    mut flashes: Query<&mut DamageFlash>,
+) {
+    for update in health_events.read() {
+        if health.update(update) {
+            for mut flash in flashes.iter_mut() {
+                flash.remaining = FLASH_DURATION;
+            }
+        }
+    }
+}
+
+fn spawn_damage_flash(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Damage Flash"),
+        DamageFlash::default(),
+        BackgroundColor(Color::NONE),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+    ));
+}
+
+fn fade_damage_flash(
+    time: Res<Time>,
+    mut flashes: Query<(&mut DamageFlash, &mut BackgroundColor)>,
+) {
+    for (mut flash, mut background) in flashes.iter_mut() {
+        flash.remaining = (flash.remaining - time.delta_secs()).max(0.0);
+
+        let alpha = flash.remaining / FLASH_DURATION * MAX_FLASH_ALPHA;
+        background.set_if_neq(BackgroundColor(Color::srgba(1.0, 0.0, 0.0, alpha)));
+    }
+}
+
+fn show_death_screen(
+    mut commands: Commands,
+    time: Res<Time>,
+    health: Res<PlayerHealth>,
+    mut screens: Query<(Entity, &mut DeathScreen)>,
+    mut buttons: Query<&mut Visibility, With<RespawnButton>>,
+    mut cursors: Query<&mut CursorOptions, With<PrimaryWindow>>,
+) {
+    if !health.is_dead() {
+        for (entity, _) in screens.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    if screens.is_empty() {
+        spawn_death_screen(&mut commands);
+
+        // Free the cursor so the button can be clicked.
+        for mut cursor in cursors.iter_mut() {
+            cursor.grab_mode = CursorGrabMode::None;
+            cursor.visible = true;
+        }
+        return;
+    }
+
+    for (_, mut screen) in screens.iter_mut() {
+        screen.shown_for += time.delta_secs();
+
+        if screen.shown_for >= RESPAWN_DELAY {
+            for mut visibility in buttons.iter_mut() {
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+        }
+    }
+}
+
+fn spawn_death_screen(commands: &mut Commands) {
+    commands
+        .spawn((
+            Name::new("Death Screen"),
+            DeathScreen::default(),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(48.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.4, 0.1, 0.1, 0.5)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("You Died!"),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            parent
+                .spawn((
+                    RespawnButton,
+                    Button,
+                    Node {
+                        width: Val::Px(400.0),
+                        height: Val::Px(40.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(BUTTON_COLOR),
+                    Visibility::Hidden,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Text::new("Respawn"),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn press_respawn_button(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<RespawnButton>),
+    >,
+    mut screens: Query<&mut DeathScreen>,
+    mut respawn_events: MessageWriter<Respawn>,
+) {
+    for (interaction, mut background) in buttons.iter_mut() {
+        background.0 = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        // The server answers with a Respawn and a health update, which closes
+        // the screen; only ask once until then.
+        for mut screen in screens.iter_mut() {
+            if screen.shown_for >= RESPAWN_DELAY && !screen.respawn_requested {
+                screen.respawn_requested = true;
+                respawn_events.write(Respawn);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(health: f32) -> HealthUpdated {
+        HealthUpdated {
+            health,
+            food: 20,
+            saturation: 5.0,
+        }
+    }
+
+    #[test]
+    fn only_losing_known_health_counts_as_damage() {
+        let mut health = PlayerHealth::default();
+
+        // Joining with less than full health isn't damage.
+        assert!(!health.update(&update(12.0)));
+        assert!(!health.update(&update(14.0)));
+        assert!(health.update(&update(0.0)));
+        assert!(health.is_dead());
+    }
+}
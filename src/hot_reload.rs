@@ -0,0 +1,295 @@
+//! Hot reloading of block models and textures during development.
+//!
+//! Textures are loaded through Bevy's [`AssetServer`], so Bevy already reloads
+//! them when they change on disk. Block models and block state files are read
+//! by [`MinecraftAssets`] instead, so this module loads them as placeholder
+//! [`BlockAssetFile`] assets purely to have Bevy watch them.
+//!
+//! Either kind of change marks the built chunks that contain an affected block
+//! state with [`RemeshChunk`], and the chunk builder rebuilds them.
+//!
+//! Watching needs Bevy's `file_watcher` feature, which the `hot_reload` cargo
+//! feature turns on.
+
+use std::{collections::HashSet, convert::Infallible, path::Path};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadedFolder},
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use brine_asset::{LoadProgress, MinecraftAssets};
+use brine_data::{BlockStateId, MinecraftData};
+use brine_proto::BrineSystems;
+use brine_voxel_v1::chunk_builder::component::{BuiltChunk, ChunkSection, RemeshChunk};
+use futures_lite::future;
+
+use crate::loading::AssetLoadState;
+
+/// Plugin that rebakes [`MinecraftAssets`] when a block model or block state
+/// file changes, and remeshes the chunks whose blocks look different as a
+/// result, or whose textures changed.
+///
+/// Rebaking reads every model again (without the bake cache) on the async
+/// compute task pool, then compares the new bake with the current one to find
+/// the block states that changed. Changes made while a rebake is running are
+/// picked up by another rebake once it finishes.
+///
+/// Requires [`AssetLoadingPlugin`][crate::loading::AssetLoadingPlugin] and a
+/// chunk builder.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`HotReload`]
+///
+/// The plugin replaces [`MinecraftAssets`] after each rebake.
+///
+/// # Ordering
+///
+/// Chunks are marked before [`BrineSystems::MeshBuild`], so they are rebuilt
+/// in the same frame.
+pub struct AssetHotReloadPlugin;
+
+impl Plugin for AssetHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BlockAssetFile>()
+            .register_asset_loader(BlockAssetFileLoader)
+            .init_resource::<HotReload>()
+            .add_systems(OnEnter(AssetLoadState::Loaded), watch_block_asset_files)
+            .add_systems(
+                Update,
+                (
+                    request_rebake,
+                    start_rebake,
+                    finish_rebake,
+                    queue_retextured_block_states,
+                    mark_chunks_for_remesh,
+                )
+                    .chain()
+                    .run_if(resource_exists::<MinecraftAssets>)
+                    .before(BrineSystems::MeshBuild),
+            );
+    }
+}
+
+/// A block model or block state file, loaded only so that Bevy watches it.
+#[derive(Asset, TypePath, Debug)]
+pub struct BlockAssetFile;
+
+#[derive(Default)]
+struct BlockAssetFileLoader;
+
+impl AssetLoader for BlockAssetFileLoader {
+    type Asset = BlockAssetFile;
+    type Settings = ();
+    type Error = Infallible;
+
+    async fn load(
+        &self,
+        _reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        Ok(BlockAssetFile)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+}
+
+/// State of hot reloading.
+#[derive(Resource, Debug, Default)]
+pub struct HotReload {
+    /// Keeps the watched block model and block state files loaded.
+    folders: Vec<Handle<LoadedFolder>>,
+
+    /// Whether a model file has changed since the last rebake started.
+    rebake_requested: bool,
+
+    /// Block states whose chunks have yet to be marked for remeshing.
+    changed_block_states: HashSet<BlockStateId>,
+}
+
+impl HotReload {
+    /// Whether the assets will be rebaked once the current rebake (if any)
+    /// finishes.
+    pub fn rebake_requested(&self) -> bool {
+        self.rebake_requested
+    }
+}
+
+#[derive(Component)]
+struct RebakeTask(Task<brine_asset::api::Result<MinecraftAssets>>);
+
+fn watch_block_asset_files(
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut hot_reload: ResMut<HotReload>,
+) {
+    if !hot_reload.folders.is_empty() {
+        return;
+    }
+
+    let roots = std::iter::once(mc_assets.root())
+        .chain(mc_assets.resource_packs().iter().map(|pack| pack.as_path()));
+
+    for root in roots {
+        // Asset paths are relative to the `assets` directory, like the texture
+        // paths handed out by `MinecraftAssets`.
+        let namespace = root.join("assets/minecraft");
+        let Ok(namespace) = namespace.strip_prefix("assets") else {
+            warn!(
+                "Can't hot reload {}: it's outside the asset directory",
+                namespace.display()
+            );
+            continue;
+        };
+
+        for folder in ["models", "blockstates"] {
+            let folder = asset_server.load_folder(namespace.join(folder));
+            hot_reload.folders.push(folder);
+        }
+
+        info!("Watching {} for model changes", namespace.display());
+    }
+}
+
+fn request_rebake(
+    mut events: MessageReader<AssetEvent<BlockAssetFile>>,
+    asset_server: Res<AssetServer>,
+    mut hot_reload: ResMut<HotReload>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(path) = asset_server.get_path(*id) {
+                info!("{} changed", path);
+            }
+            hot_reload.rebake_requested = true;
+        }
+    }
+}
+
+fn start_rebake(
+    mc_assets: Res<MinecraftAssets>,
+    mc_data: Res<MinecraftData>,
+    tasks: Query<(), With<RebakeTask>>,
+    mut hot_reload: ResMut<HotReload>,
+    mut commands: Commands,
+) {
+    if !hot_reload.rebake_requested || !tasks.is_empty() {
+        return;
+    }
+
+    hot_reload.rebake_requested = false;
+    info!("Rebaking block models");
+
+    let root = mc_assets.root().to_path_buf();
+    let resource_packs = mc_assets.resource_packs().to_vec();
+    let mc_data = mc_data.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let rebaked = MinecraftAssets::new_with_resource_packs(
+            root,
+            &resource_packs,
+            &mc_data,
+            None,
+            &LoadProgress::new(),
+        );
+
+        // Bake here rather than when the block states are compared.
+        if let Ok(rebaked) = &rebaked {
+            rebaked.block_states().bake_all();
+        }
+        rebaked
+    });
+
+    commands.spawn((RebakeTask(task), Name::new("Rebaking Minecraft Assets")));
+}
+
+fn finish_rebake(
+    mut tasks: Query<(Entity, &mut RebakeTask)>,
+    mut mc_assets: ResMut<MinecraftAssets>,
+    mut hot_reload: ResMut<HotReload>,
+    mut commands: Commands,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+
+        match result {
+            Ok(rebaked) => {
+                let changed = mc_assets.changed_block_states(&rebaked);
+                info!(
+                    "Rebaked block models; {} block states changed",
+                    changed.len()
+                );
+
+                hot_reload.changed_block_states.extend(changed);
+                *mc_assets = rebaked;
+            }
+            Err(err) => error!("Failed to rebake block models: {}", err),
+        }
+    }
+}
+
+fn queue_retextured_block_states(
+    mut events: MessageReader<AssetEvent<Image>>,
+    asset_server: Res<AssetServer>,
+    mc_assets: Res<MinecraftAssets>,
+    mut hot_reload: ResMut<HotReload>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        // Atlases and other generated images have no path.
+        let Some(path) = asset_server.get_path(*id) else {
+            continue;
+        };
+
+        let Some(texture) = mc_assets.get_texture_key_by_path(Path::new(path.path())) else {
+            continue;
+        };
+
+        info!("{} changed", path);
+        hot_reload
+            .changed_block_states
+            .extend(mc_assets.block_states_using_texture(texture));
+    }
+}
+
+fn mark_chunks_for_remesh(
+    chunks: Query<(Entity, &Children), With<BuiltChunk>>,
+    sections: Query<&ChunkSection>,
+    mut hot_reload: ResMut<HotReload>,
+    mut commands: Commands,
+) {
+    if hot_reload.changed_block_states.is_empty() {
+        return;
+    }
+
+    let changed = std::mem::take(&mut hot_reload.changed_block_states);
+    let mut remeshed = 0;
+
+    for (entity, children) in chunks.iter() {
+        let affected =
+            sections.iter_many(children).any(|section| {
+                section.0.block_states.iter().any(|(_, _, _, block_state)| {
+                    changed.contains(&BlockStateId(block_state.0 as u16))
+                })
+            });
+
+        if affected {
+            commands.entity(entity).insert(RemeshChunk);
+            remeshed += 1;
+        }
+    }
+
+    debug!("Remeshing {} chunks", remeshed);
+}
@@ -0,0 +1,261 @@
+//! The hotbar: which slot is selected and what the player is holding in
+//! either hand.
+
+use bevy::{
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+};
+use brine_proto::{
+    event::{
+        clientbound::{
+            HotbarSlotSelected, InventoryContents, InventorySlot, Reconfigure, Transfer,
+        },
+        serverbound::{SelectHotbarSlot, SwapHands},
+        Hand, ItemStack,
+    },
+    BrineSystems,
+};
+
+use crate::{
+    container::no_screen_open,
+    input::{InputAction, InputMap},
+};
+
+/// Number of slots in the hotbar.
+pub const HOTBAR_SIZE: usize = 9;
+
+/// Player inventory slot of the leftmost hotbar slot.
+const FIRST_HOTBAR_INVENTORY_SLOT: usize = 36;
+
+/// Player inventory slot of the off-hand.
+const OFFHAND_INVENTORY_SLOT: usize = 45;
+
+/// Pixels of touchpad scrolling that count as one notch of a mouse wheel.
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
+const HOTBAR_SLOT_ACTIONS: [InputAction; HOTBAR_SIZE] = [
+    InputAction::HotbarSlot1,
+    InputAction::HotbarSlot2,
+    InputAction::HotbarSlot3,
+    InputAction::HotbarSlot4,
+    InputAction::HotbarSlot5,
+    InputAction::HotbarSlot6,
+    InputAction::HotbarSlot7,
+    InputAction::HotbarSlot8,
+    InputAction::HotbarSlot9,
+];
+
+/// The local player's hotbar.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct Hotbar {
+    selected: usize,
+    items: [Option<ItemStack>; HOTBAR_SIZE],
+    offhand: Option<ItemStack>,
+}
+
+impl Hotbar {
+    /// Returns the index (`0..9`) of the selected slot.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Returns the item in each hotbar slot, from left to right.
+    pub fn items(&self) -> &[Option<ItemStack>; HOTBAR_SIZE] {
+        &self.items
+    }
+
+    /// Returns the item in the selected slot, i.e. the one held in the main
+    /// hand.
+    pub fn held_item(&self) -> Option<ItemStack> {
+        self.items[self.selected]
+    }
+
+    /// Returns the item held in the off-hand.
+    pub fn offhand_item(&self) -> Option<ItemStack> {
+        self.offhand
+    }
+
+    /// Returns the item held in the given hand.
+    pub fn item_in(&self, hand: Hand) -> Option<ItemStack> {
+        match hand {
+            Hand::Main => self.held_item(),
+            Hand::Off => self.offhand_item(),
+        }
+    }
+
+    /// Returns the slot `offset` slots to the right of the selected one,
+    /// wrapping around at either end.
+    pub fn offset_slot(&self, offset: i32) -> usize {
+        (self.selected as i32 + offset).rem_euclid(HOTBAR_SIZE as i32) as usize
+    }
+
+    fn set_inventory_slot(&mut self, inventory_slot: usize, item: Option<ItemStack>) {
+        if inventory_slot == OFFHAND_INVENTORY_SLOT {
+            self.offhand = item;
+        } else if let Some(hotbar_slot) = inventory_slot.checked_sub(FIRST_HOTBAR_INVENTORY_SLOT) {
+            if let Some(slot) = self.items.get_mut(hotbar_slot) {
+                *slot = item;
+            }
+        }
+    }
+}
+
+/// Plugin that tracks the hotbar and off-hand contents and the selection, and
+/// lets the player change the selection with the number keys, the scroll
+/// wheel, or the [`InputAction::HotbarNext`] and
+/// [`InputAction::HotbarPrevious`] bindings, and swap hands with
+/// [`InputAction::SwapHands`].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`HotbarSlotSelected`]
+/// * [`InventoryContents`]
+/// * [`InventorySlot`]
+/// * [`Transfer`] and [`Reconfigure`], which empty the hotbar
+///
+/// The plugin sends the following events:
+///
+/// * [`SelectHotbarSlot`]
+/// * [`SwapHands`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Hotbar`]
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hotbar>().add_systems(
+            Update,
+            (
+                reset_hotbar,
+                handle_inventory_events,
+                select_slot_with_input.run_if(no_screen_open),
+                swap_hands_with_input,
+            )
+                .chain()
+                .in_set(BrineSystems::WorldUpdate),
+        );
+    }
+}
+
+fn reset_hotbar(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut hotbar: ResMut<Hotbar>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        *hotbar = Hotbar::default();
+    }
+}
+
+fn handle_inventory_events(
+    mut selected_events: MessageReader<HotbarSlotSelected>,
+    mut contents_events: MessageReader<InventoryContents>,
+    mut slot_events: MessageReader<InventorySlot>,
+    mut hotbar: ResMut<Hotbar>,
+) {
+    for contents in contents_events.read() {
+        for (inventory_slot, item) in contents.items.iter().enumerate() {
+            hotbar.set_inventory_slot(inventory_slot, *item);
+        }
+    }
+
+    for event in slot_events.read() {
+        hotbar.set_inventory_slot(usize::from(event.slot), event.item);
+    }
+
+    for event in selected_events.read() {
+        let slot = usize::from(event.slot);
+        if slot < HOTBAR_SIZE {
+            hotbar.selected = slot;
+        } else {
+            warn!("Server selected invalid hotbar slot {}", slot);
+        }
+    }
+}
+
+fn select_slot_with_input(
+    input: InputMap,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut scroll_lines: Local<f32>,
+    mut hotbar: ResMut<Hotbar>,
+    mut select_events: MessageWriter<SelectHotbarSlot>,
+) {
+    *scroll_lines += match mouse_scroll.unit {
+        MouseScrollUnit::Line => mouse_scroll.delta.y,
+        MouseScrollUnit::Pixel => mouse_scroll.delta.y / PIXELS_PER_SCROLL_LINE,
+    };
+
+    // Scrolling down moves to the right.
+    let notches = scroll_lines.trunc();
+    *scroll_lines -= notches;
+    let mut offset = -(notches as i32);
+
+    if input.just_pressed(InputAction::HotbarNext) {
+        offset += 1;
+    }
+    if input.just_pressed(InputAction::HotbarPrevious) {
+        offset -= 1;
+    }
+
+    let mut selected = hotbar.offset_slot(offset);
+    for (slot, action) in HOTBAR_SLOT_ACTIONS.into_iter().enumerate() {
+        if input.just_pressed(action) {
+            selected = slot;
+        }
+    }
+
+    if selected != hotbar.selected {
+        hotbar.selected = selected;
+        select_events.write(SelectHotbarSlot {
+            slot: selected as u8,
+        });
+    }
+}
+
+fn swap_hands_with_input(input: InputMap, mut swap_events: MessageWriter<SwapHands>) {
+    // The server sends the swapped slots back.
+    if input.just_pressed(InputAction::SwapHands) {
+        swap_events.write(SwapHands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_slot_wraps() {
+        let mut hotbar = Hotbar::default();
+        assert_eq!(hotbar.offset_slot(1), 1);
+        assert_eq!(hotbar.offset_slot(-1), 8);
+
+        hotbar.selected = 8;
+        assert_eq!(hotbar.offset_slot(1), 0);
+        assert_eq!(hotbar.offset_slot(-19), 7);
+    }
+
+    #[test]
+    fn only_hotbar_inventory_slots_are_kept() {
+        let stone = ItemStack {
+            item_id: 1,
+            count: 64,
+        };
+        let mut hotbar = Hotbar::default();
+        hotbar.set_inventory_slot(9, Some(stone));
+        hotbar.set_inventory_slot(45, Some(stone));
+        assert_eq!(hotbar.items(), &[None; HOTBAR_SIZE]);
+        assert_eq!(hotbar.offhand_item(), Some(stone));
+
+        hotbar.set_inventory_slot(36, Some(stone));
+        assert_eq!(hotbar.held_item(), Some(stone));
+    }
+}
@@ -0,0 +1,275 @@
+//! Keeping a headless bot from looking idle.
+//!
+//! Servers running anti-AFK plugins kick players that don't move for a while,
+//! which ends long-running chunk-saving and observation bots. The
+//! [`IdlePlugin`] turns the bot's head every so often, steps to a slightly
+//! different spot near where the server last put it, and respawns the bot when
+//! it dies, each as configured by [`IdleBehavior`].
+//!
+//! The bot has no physics, so it never leaves the spot the server put it on
+//! and always claims to be on the ground; servers that disagree teleport it
+//! back, which becomes its new spot.
+
+use std::time::Duration;
+
+use bevy::{math::DVec3, prelude::*};
+use brine_proto::{
+    event::{
+        clientbound::{HealthUpdated, PlayerTeleported, Reconfigure, Transfer},
+        serverbound::{MovePlayer, Respawn},
+    },
+    BrineSystems,
+};
+
+use crate::synthetic::hash;
+
+/// How far from its spot the bot steps, along each horizontal axis.
+const JITTER_DISTANCE: f64 = 0.25;
+
+/// Highest the bot looks up or down, in degrees.
+const MAX_IDLE_PITCH: f32 = 30.0;
+
+/// How long to wait after dying before respawning, and between attempts
+/// while the server hasn't respawned the bot.
+const RESPAWN_DELAY: Duration = Duration::from_secs(1);
+
+/// What a headless bot does to avoid being kicked for idling.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct IdleBehavior {
+    /// How often to turn the head, if at all.
+    pub look_interval: Option<Duration>,
+
+    /// How often to step to another spot within a quarter block of where the
+    /// server put the bot, if at all.
+    pub jitter_interval: Option<Duration>,
+
+    /// Whether to respawn right after dying.
+    pub auto_respawn: bool,
+}
+
+impl Default for IdleBehavior {
+    fn default() -> Self {
+        Self {
+            look_interval: Some(Duration::from_secs(10)),
+            jitter_interval: Some(Duration::from_secs(45)),
+            auto_respawn: true,
+        }
+    }
+}
+
+/// Where the bot is and what it has been doing.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+struct IdleState {
+    /// Where the server last put the bot; steps stay near it. `None` until
+    /// the first teleport.
+    spot: Option<DVec3>,
+
+    position: DVec3,
+    yaw: f32,
+    pitch: f32,
+
+    since_look: Duration,
+    since_jitter: Duration,
+
+    /// Number of idle moves made, which picks the next angles and step.
+    moves: i32,
+
+    /// How long the bot has been dead without respawning, if it is dead.
+    dead_for: Option<Duration>,
+}
+
+/// Plugin that keeps a headless bot looking active, according to its
+/// [`IdleBehavior`].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`PlayerTeleported`]
+/// * [`HealthUpdated`]
+/// * [`Transfer`] and [`Reconfigure`], which stop idling until the next
+///   teleport
+///
+/// The plugin sends the following events:
+///
+/// * [`MovePlayer`]
+/// * [`Respawn`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`IdleBehavior`]
+#[derive(Default)]
+pub struct IdlePlugin {
+    behavior: IdleBehavior,
+}
+
+impl IdlePlugin {
+    /// Idles as `behavior` says instead of as [`IdleBehavior::default`].
+    pub fn with_behavior(mut self, behavior: IdleBehavior) -> Self {
+        self.behavior = behavior;
+        self
+    }
+}
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.behavior.clone())
+            .init_resource::<IdleState>()
+            .add_systems(
+                Update,
+                (reset_idling, track_player, respawn_after_death, idle)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn reset_idling(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut state: ResMut<IdleState>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        state.set_if_neq(IdleState::default());
+    }
+}
+
+fn track_player(
+    mut teleported_events: MessageReader<PlayerTeleported>,
+    mut health_events: MessageReader<HealthUpdated>,
+    mut state: ResMut<IdleState>,
+) {
+    for teleported in teleported_events.read() {
+        state.spot = Some(teleported.position);
+        state.position = teleported.position;
+        state.yaw = teleported.yaw;
+        state.pitch = teleported.pitch;
+    }
+
+    for update in health_events.read() {
+        if update.health > 0.0 {
+            state.dead_for = None;
+        } else if state.dead_for.is_none() {
+            state.dead_for = Some(Duration::ZERO);
+        }
+    }
+}
+
+fn respawn_after_death(
+    time: Res<Time>,
+    behavior: Res<IdleBehavior>,
+    mut state: ResMut<IdleState>,
+    mut respawn_events: MessageWriter<Respawn>,
+) {
+    if !behavior.auto_respawn {
+        return;
+    }
+    let Some(dead_for) = &mut state.dead_for else {
+        return;
+    };
+
+    *dead_for += time.delta();
+    if *dead_for >= RESPAWN_DELAY {
+        info!("Respawning");
+        respawn_events.write(Respawn);
+        *dead_for = Duration::ZERO;
+    }
+}
+
+/// Returns the angles of the `moves`th look around.
+fn look_angles(moves: i32) -> (f32, f32) {
+    let yaw = hash(0, moves, 0) * 360.0 - 180.0;
+    let pitch = (hash(0, moves, 1) * 2.0 - 1.0) * MAX_IDLE_PITCH;
+    (yaw, pitch)
+}
+
+/// Returns where the `moves`th step puts the bot, relative to its spot.
+fn jitter_offset(moves: i32) -> DVec3 {
+    let offset = |axis: i32| (f64::from(hash(0, moves, axis)) * 2.0 - 1.0) * JITTER_DISTANCE;
+    DVec3::new(offset(2), 0.0, offset(3))
+}
+
+/// Returns whether `interval` has passed, and if so starts it again.
+fn is_due(since: &mut Duration, interval: Option<Duration>, delta: Duration) -> bool {
+    let Some(interval) = interval else {
+        return false;
+    };
+
+    *since += delta;
+    if *since < interval {
+        return false;
+    }
+    *since = Duration::ZERO;
+    true
+}
+
+fn idle(
+    time: Res<Time>,
+    behavior: Res<IdleBehavior>,
+    mut state: ResMut<IdleState>,
+    mut move_events: MessageWriter<MovePlayer>,
+) {
+    let Some(spot) = state.spot.filter(|_| state.dead_for.is_none()) else {
+        return;
+    };
+
+    let state = &mut *state;
+    let look = is_due(&mut state.since_look, behavior.look_interval, time.delta());
+    let jitter = is_due(
+        &mut state.since_jitter,
+        behavior.jitter_interval,
+        time.delta(),
+    );
+    if !look && !jitter {
+        return;
+    }
+
+    state.moves = state.moves.wrapping_add(1);
+    if look {
+        (state.yaw, state.pitch) = look_angles(state.moves);
+    }
+    if jitter {
+        state.position = spot + jitter_offset(state.moves);
+    }
+
+    move_events.write(MovePlayer {
+        position: state.position,
+        yaw: state.yaw,
+        pitch: state.pitch,
+        on_ground: true,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_moves_stay_small() {
+        for moves in 0..1000 {
+            let (yaw, pitch) = look_angles(moves);
+            assert!((-180.0..=180.0).contains(&yaw));
+            assert!(pitch.abs() <= MAX_IDLE_PITCH);
+
+            let offset = jitter_offset(moves);
+            assert!(offset.x.abs() <= JITTER_DISTANCE && offset.z.abs() <= JITTER_DISTANCE);
+            assert_eq!(offset.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn intervals_restart_when_due() {
+        let second = Duration::from_secs(1);
+        let mut since = Duration::ZERO;
+
+        assert!(!is_due(&mut since, Some(second * 2), second));
+        assert!(is_due(&mut since, Some(second * 2), second));
+        assert_eq!(since, Duration::ZERO);
+        assert!(!is_due(&mut since, None, second * 10));
+    }
+}
@@ -0,0 +1,486 @@
+//! Remappable input bindings.
+//!
+//! Systems should ask the [`InputMap`] whether an [`InputAction`] is active
+//! rather than checking for specific [`KeyCode`]s or [`GamepadButton`]s, so
+//! that users can change their bindings in the settings file.
+
+use std::collections::BTreeMap;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Something the user can do by pressing a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputAction {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Ascend,
+    Descend,
+    Sprint,
+    ToggleCursorGrab,
+    Chat,
+    Inventory,
+    Screenshot,
+    /// Switches to the next graphics preset.
+    CycleGraphicsPreset,
+    /// Digs the targeted block, like the left mouse button (which always
+    /// does).
+    Attack,
+    /// Uses the held item, like the right mouse button (which always does).
+    UseItem,
+    /// Swaps the items in the main hand and the off-hand.
+    SwapHands,
+    /// Held while clicking in a container or crafting screen to move a whole
+    /// stack to the other inventory, or to craft as many as fit.
+    QuickMoveModifier,
+    /// Shows or hides the map of the loaded chunks.
+    ToggleMap,
+    /// Shows fewer blocks per map pixel.
+    MapZoomIn,
+    /// Shows more blocks per map pixel.
+    MapZoomOut,
+
+    /// Selects a hotbar slot directly.
+    HotbarSlot1,
+    HotbarSlot2,
+    HotbarSlot3,
+    HotbarSlot4,
+    HotbarSlot5,
+    HotbarSlot6,
+    HotbarSlot7,
+    HotbarSlot8,
+    HotbarSlot9,
+    /// Selects the hotbar slot to the right, like scrolling down.
+    HotbarNext,
+    /// Selects the hotbar slot to the left, like scrolling up.
+    HotbarPrevious,
+
+    /// Held to turn other keys into debug shortcuts; tapped alone to toggle the
+    /// debug HUD.
+    DebugModifier,
+    ToggleHitboxes,
+    ToggleChunkBorders,
+    ToggleBlockOutline,
+    TogglePerformanceOverlay,
+    /// Detaches the camera from the player, or puts it back.
+    ToggleFreecam,
+
+    /// Shows the next item in a tool viewer (e.g., block state).
+    ViewerNext,
+    /// Shows the previous item in a tool viewer.
+    ViewerPrevious,
+    /// Held to skip several items at once in a tool viewer.
+    ViewerSkipModifier,
+    /// Shows the next chunk section in the chunk viewer.
+    ViewerNextSection,
+    /// Loads the next file in a tool viewer.
+    ViewerNextFile,
+    /// Turns the model in the chunk viewer an eighth of a turn to the left.
+    ViewerRotateLeft,
+    /// Turns the model in the chunk viewer an eighth of a turn to the right.
+    ViewerRotateRight,
+    /// Tilts the model in the chunk viewer an eighth of a turn away.
+    ViewerRotateUp,
+    /// Tilts the model in the chunk viewer an eighth of a turn closer.
+    ViewerRotateDown,
+    /// Puts the model in the chunk viewer back the way it started.
+    ViewerResetView,
+}
+
+impl InputAction {
+    /// Returns the keys bound to this action by default.
+    pub fn default_keys(self) -> Vec<KeyCode> {
+        use InputAction::*;
+
+        match self {
+            MoveForward => vec![KeyCode::KeyW],
+            MoveBackward => vec![KeyCode::KeyS],
+            MoveLeft => vec![KeyCode::KeyA],
+            MoveRight => vec![KeyCode::KeyD],
+            Ascend => vec![KeyCode::Space],
+            Descend => vec![KeyCode::ShiftLeft],
+            Sprint => vec![KeyCode::ControlLeft],
+            ToggleCursorGrab => vec![KeyCode::Escape],
+            Chat => vec![KeyCode::KeyT],
+            Inventory => vec![KeyCode::KeyE],
+            Screenshot => vec![KeyCode::F2],
+            CycleGraphicsPreset => vec![KeyCode::F6],
+            Attack | UseItem => vec![],
+            SwapHands => vec![KeyCode::KeyF],
+            QuickMoveModifier => vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            ToggleMap => vec![KeyCode::KeyM],
+            MapZoomIn => vec![KeyCode::Equal],
+            MapZoomOut => vec![KeyCode::Minus],
+            HotbarSlot1 => vec![KeyCode::Digit1],
+            HotbarSlot2 => vec![KeyCode::Digit2],
+            HotbarSlot3 => vec![KeyCode::Digit3],
+            HotbarSlot4 => vec![KeyCode::Digit4],
+            HotbarSlot5 => vec![KeyCode::Digit5],
+            HotbarSlot6 => vec![KeyCode::Digit6],
+            HotbarSlot7 => vec![KeyCode::Digit7],
+            HotbarSlot8 => vec![KeyCode::Digit8],
+            HotbarSlot9 => vec![KeyCode::Digit9],
+            HotbarNext | HotbarPrevious => vec![],
+            DebugModifier => vec![KeyCode::F3],
+            ToggleHitboxes => vec![KeyCode::KeyB],
+            ToggleChunkBorders => vec![KeyCode::KeyG],
+            ToggleBlockOutline => vec![KeyCode::KeyO],
+            TogglePerformanceOverlay => vec![KeyCode::KeyP],
+            ToggleFreecam => vec![KeyCode::KeyN],
+            ViewerNext => vec![KeyCode::ArrowRight],
+            ViewerPrevious => vec![KeyCode::ArrowLeft],
+            ViewerSkipModifier => vec![KeyCode::ShiftLeft],
+            ViewerNextSection => vec![KeyCode::Space],
+            ViewerNextFile => vec![KeyCode::Enter],
+            ViewerRotateLeft => vec![KeyCode::ArrowLeft],
+            ViewerRotateRight => vec![KeyCode::ArrowRight],
+            ViewerRotateUp => vec![KeyCode::ArrowUp],
+            ViewerRotateDown => vec![KeyCode::ArrowDown],
+            ViewerResetView => vec![KeyCode::Escape],
+        }
+    }
+
+    /// Returns the gamepad buttons bound to this action by default.
+    pub fn default_gamepad_buttons(self) -> Vec<GamepadButton> {
+        use InputAction::*;
+
+        match self {
+            Ascend => vec![GamepadButton::South],
+            Descend => vec![GamepadButton::East],
+            Sprint => vec![GamepadButton::LeftThumb],
+            Inventory => vec![GamepadButton::North],
+            Screenshot => vec![GamepadButton::Select],
+            Attack => vec![GamepadButton::RightTrigger2],
+            UseItem => vec![GamepadButton::LeftTrigger2],
+            HotbarNext => vec![GamepadButton::RightTrigger],
+            HotbarPrevious => vec![GamepadButton::LeftTrigger],
+            ViewerNext => vec![GamepadButton::DPadRight],
+            ViewerPrevious => vec![GamepadButton::DPadLeft],
+            ViewerSkipModifier => vec![GamepadButton::RightThumb],
+            ViewerNextSection => vec![GamepadButton::South],
+            ViewerNextFile => vec![GamepadButton::West],
+            ViewerResetView => vec![GamepadButton::Start],
+            _ => vec![],
+        }
+    }
+
+    pub const ALL: [Self; 46] = {
+        use InputAction::*;
+        [
+            MoveForward,
+            MoveBackward,
+            MoveLeft,
+            MoveRight,
+            Ascend,
+            Descend,
+            Sprint,
+            ToggleCursorGrab,
+            Chat,
+            Inventory,
+            Screenshot,
+            CycleGraphicsPreset,
+            Attack,
+            UseItem,
+            SwapHands,
+            QuickMoveModifier,
+            ToggleMap,
+            MapZoomIn,
+            MapZoomOut,
+            HotbarSlot1,
+            HotbarSlot2,
+            HotbarSlot3,
+            HotbarSlot4,
+            HotbarSlot5,
+            HotbarSlot6,
+            HotbarSlot7,
+            HotbarSlot8,
+            HotbarSlot9,
+            HotbarNext,
+            HotbarPrevious,
+            DebugModifier,
+            ToggleHitboxes,
+            ToggleChunkBorders,
+            ToggleBlockOutline,
+            TogglePerformanceOverlay,
+            ToggleFreecam,
+            ViewerNext,
+            ViewerPrevious,
+            ViewerSkipModifier,
+            ViewerNextSection,
+            ViewerNextFile,
+            ViewerRotateLeft,
+            ViewerRotateRight,
+            ViewerRotateUp,
+            ViewerRotateDown,
+            ViewerResetView,
+        ]
+    };
+}
+
+/// An input that can be bound to an [`InputAction`].
+pub trait Binding: Copy + Send + Sync + 'static {
+    /// Returns the inputs bound to an action by default.
+    fn defaults(action: InputAction) -> Vec<Self>;
+}
+
+impl Binding for KeyCode {
+    fn defaults(action: InputAction) -> Vec<Self> {
+        action.default_keys()
+    }
+}
+
+impl Binding for GamepadButton {
+    fn defaults(action: InputAction) -> Vec<Self> {
+        action.default_gamepad_buttons()
+    }
+}
+
+/// Mapping from [`InputAction`]s to the inputs that trigger them.
+///
+/// Deserializing only needs to provide the actions that differ from the
+/// defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Resource, Deserialize, Serialize)]
+#[serde(
+    from = "BTreeMap<InputAction, Vec<B>>",
+    into = "BTreeMap<InputAction, Vec<B>>",
+    bound(serialize = "B: Serialize", deserialize = "B: Deserialize<'de>")
+)]
+pub struct Bindings<B: Binding> {
+    bindings: BTreeMap<InputAction, Vec<B>>,
+}
+
+/// Keyboard bindings.
+pub type KeyBindings = Bindings<KeyCode>;
+
+/// Gamepad button bindings.
+pub type GamepadBindings = Bindings<GamepadButton>;
+
+impl<B: Binding> Bindings<B> {
+    /// Returns the inputs bound to an action.
+    pub fn get(&self, action: InputAction) -> &[B] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the inputs bound to an action.
+    pub fn bind(&mut self, action: InputAction, inputs: impl IntoIterator<Item = B>) {
+        self.bindings.insert(action, inputs.into_iter().collect());
+    }
+
+    /// Returns the first input bound to an action, if any.
+    pub fn primary(&self, action: InputAction) -> Option<B> {
+        self.get(action).first().copied()
+    }
+}
+
+impl<B: Binding> Default for Bindings<B> {
+    fn default() -> Self {
+        Self {
+            bindings: InputAction::ALL
+                .into_iter()
+                .map(|action| (action, B::defaults(action)))
+                .collect(),
+        }
+    }
+}
+
+impl<B: Binding> From<BTreeMap<InputAction, Vec<B>>> for Bindings<B> {
+    fn from(overrides: BTreeMap<InputAction, Vec<B>>) -> Self {
+        let mut bindings = Self::default();
+        bindings.bindings.extend(overrides);
+        bindings
+    }
+}
+
+impl<B: Binding> From<Bindings<B>> for BTreeMap<InputAction, Vec<B>> {
+    fn from(bindings: Bindings<B>) -> Self {
+        bindings.bindings
+    }
+}
+
+/// System param for querying the state of [`InputAction`]s.
+///
+/// An action is active if any bound key or any bound button on any connected
+/// gamepad is.
+#[derive(SystemParam)]
+pub struct InputMap<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    key_bindings: Res<'w, KeyBindings>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    gamepad_bindings: Res<'w, GamepadBindings>,
+}
+
+impl InputMap<'_, '_> {
+    /// Returns whether any input bound to the action is held down.
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.keys
+            .any_pressed(self.key_bindings.get(action).iter().copied())
+            || self.gamepad_pressed(action)
+    }
+
+    /// Returns whether any input bound to the action was pressed this frame.
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        let buttons = self.gamepad_bindings.get(action);
+
+        self.keys
+            .any_just_pressed(self.key_bindings.get(action).iter().copied())
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.any_just_pressed(buttons.iter().copied()))
+    }
+
+    /// Returns whether any input bound to the action was released this frame.
+    pub fn just_released(&self, action: InputAction) -> bool {
+        let buttons = self.gamepad_bindings.get(action);
+
+        self.keys
+            .any_just_released(self.key_bindings.get(action).iter().copied())
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.any_just_released(buttons.iter().copied()))
+    }
+
+    /// Returns whether any gamepad button bound to the action is held down,
+    /// ignoring the keyboard.
+    pub fn gamepad_pressed(&self, action: InputAction) -> bool {
+        let buttons = self.gamepad_bindings.get(action);
+
+        self.gamepads
+            .iter()
+            .any(|gamepad| gamepad.any_pressed(buttons.iter().copied()))
+    }
+
+    /// Returns the raw keyboard input.
+    pub fn keyboard(&self) -> &ButtonInput<KeyCode> {
+        &self.keys
+    }
+
+    /// Returns the connected gamepads.
+    pub fn gamepads(&self) -> impl Iterator<Item = &Gamepad> {
+        self.gamepads.iter()
+    }
+}
+
+/// Plugin that provides the [`KeyBindings`] and [`GamepadBindings`] resources
+/// and keeps third-party input handlers (e.g., the fly camera) in sync with
+/// them.
+///
+/// If the [`SettingsPlugin`][crate::settings::SettingsPlugin] is added before
+/// this plugin, the bindings come from the settings file; otherwise the
+/// defaults are used.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`KeyBindings`]
+/// * [`GamepadBindings`]
+pub struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<GamepadBindings>()
+            .add_systems(
+                PreUpdate,
+                sync_fly_cam_bindings.run_if(resource_exists::<bevy_flycam::KeyBindings>),
+            );
+    }
+}
+
+fn sync_fly_cam_bindings(
+    bindings: Res<KeyBindings>,
+    mut fly_cam_bindings: ResMut<bevy_flycam::KeyBindings>,
+) {
+    if !bindings.is_changed() {
+        return;
+    }
+
+    let fly_cam_bindings = &mut *fly_cam_bindings;
+    for (action, fly_cam_key) in [
+        (InputAction::MoveForward, &mut fly_cam_bindings.move_forward),
+        (
+            InputAction::MoveBackward,
+            &mut fly_cam_bindings.move_backward,
+        ),
+        (InputAction::MoveLeft, &mut fly_cam_bindings.move_left),
+        (InputAction::MoveRight, &mut fly_cam_bindings.move_right),
+        (InputAction::Ascend, &mut fly_cam_bindings.move_ascend),
+        (InputAction::Descend, &mut fly_cam_bindings.move_descend),
+        (
+            InputAction::ToggleCursorGrab,
+            &mut fly_cam_bindings.toggle_grab_cursor,
+        ),
+    ] {
+        if let Some(key) = bindings.primary(action) {
+            *fly_cam_key = key;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_bindings_keep_defaults() {
+        let bindings: KeyBindings =
+            serde_json::from_str(r#"{ "move_forward": ["ArrowUp"] }"#).unwrap();
+
+        assert_eq!(bindings.get(InputAction::MoveForward), &[KeyCode::ArrowUp]);
+        assert_eq!(bindings.get(InputAction::MoveBackward), &[KeyCode::KeyS]);
+    }
+
+    #[test]
+    fn gameplay_actions_have_distinct_default_gamepad_buttons() {
+        use InputAction::*;
+
+        // The tool viewers reuse gameplay buttons, as they don't have both.
+        let is_viewer_action = |action: &InputAction| {
+            matches!(
+                action,
+                ViewerNext
+                    | ViewerPrevious
+                    | ViewerSkipModifier
+                    | ViewerNextSection
+                    | ViewerNextFile
+                    | ViewerRotateLeft
+                    | ViewerRotateRight
+                    | ViewerRotateUp
+                    | ViewerRotateDown
+                    | ViewerResetView
+            )
+        };
+
+        for viewer in [false, true] {
+            let mut bound = Vec::new();
+            for action in InputAction::ALL {
+                if is_viewer_action(&action) != viewer && action != Screenshot {
+                    continue;
+                }
+                for button in action.default_gamepad_buttons() {
+                    assert!(!bound.contains(&button), "{:?} is bound twice", button);
+                    bound.push(button);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bindings_round_trip() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(InputAction::Screenshot, [KeyCode::F12]);
+
+        let json = serde_json::to_string(&bindings).unwrap();
+        let parsed: KeyBindings = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, bindings);
+    }
+}
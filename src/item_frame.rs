@@ -0,0 +1,103 @@
+//! Item frames, drawn as instances of one frame model.
+//!
+//! Walls of item frames are common on servers and every frame looks the same,
+//! so they are drawn with [`InstancedMesh`] rather than a mesh each. Frames
+//! face the way the server turns them: their yaw for frames on walls, and a
+//! pitch of -90 or 90 degrees for frames on floors and ceilings.
+//!
+//! The item in a frame isn't known without entity metadata, so frames are
+//! always empty.
+
+use bevy::prelude::*;
+use brine_asset::MinecraftAssets;
+use brine_data::{EntityTypeId, MinecraftData};
+use brine_proto::BrineSystems;
+use brine_render::instancing::InstancedMesh;
+
+use crate::entity::{EntityLook, ServerEntity};
+
+/// Size of the frame, in blocks: 12 by 12 pixels, one pixel thick.
+const FRAME_SIZE: Vec3 = Vec3::new(12.0 / 16.0, 12.0 / 16.0, 1.0 / 16.0);
+
+/// The model of each kind of item frame.
+#[derive(Resource, Debug)]
+struct ItemFrameModels {
+    item_frame: InstancedMesh,
+    glow_item_frame: InstancedMesh,
+}
+
+impl ItemFrameModels {
+    fn get(&self, entity_type_name: &str) -> Option<&InstancedMesh> {
+        match entity_type_name {
+            "item_frame" => Some(&self.item_frame),
+            "glow_item_frame" => Some(&self.glow_item_frame),
+            _ => None,
+        }
+    }
+}
+
+/// Plugin that gives item frame [`ServerEntity`]s an instanced model.
+///
+/// Requires the [`MinecraftData`] resource and the
+/// [`InstancingPlugin`][brine_render::instancing::InstancingPlugin], and waits
+/// for [`MinecraftAssets`] before attaching any models.
+pub struct ItemFramePlugin;
+
+impl Plugin for ItemFramePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                load_item_frame_models.run_if(resource_added::<MinecraftAssets>),
+                attach_item_frame_models.run_if(resource_exists::<ItemFrameModels>),
+            )
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+fn load_item_frame_models(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let mesh = meshes.add(Cuboid::from_size(FRAME_SIZE));
+    let model = |texture: &str| InstancedMesh {
+        mesh: mesh.clone(),
+        texture: asset_server.load(mc_assets.get_any_texture_path(texture)),
+    };
+
+    commands.insert_resource(ItemFrameModels {
+        item_frame: model("block/item_frame"),
+        glow_item_frame: model("block/glow_item_frame"),
+    });
+}
+
+/// Item frames spawned before the models were loaded count as added the first
+/// time this runs, so they get models too.
+fn attach_item_frame_models(
+    mut commands: Commands,
+    mc_data: Res<MinecraftData>,
+    models: Res<ItemFrameModels>,
+    entities: Query<(Entity, &ServerEntity, &EntityLook), Added<ServerEntity>>,
+) {
+    for (entity, server_entity, look) in entities.iter() {
+        let Some(model) = u16::try_from(server_entity.entity_type)
+            .ok()
+            .and_then(|id| mc_data.entity_types().get_by_id(EntityTypeId(id)))
+            .and_then(|entity_type| models.get(entity_type.name))
+        else {
+            continue;
+        };
+
+        // The entity is turned by its yaw; frames on floors and ceilings also
+        // face up or down.
+        commands.entity(entity).with_child((
+            Name::new("Item Frame"),
+            model.clone(),
+            Transform::from_rotation(Quat::from_rotation_x(-look.pitch.to_radians())),
+        ));
+    }
+}
@@ -0,0 +1,453 @@
+//! Using held items: eating, drinking, blocking with a shield, drawing a bow,
+//! looking through a spyglass, and so on.
+//!
+//! Pressing the right mouse button (or the [`UseItem`][InputAction::UseItem]
+//! binding) uses the item in the main hand, or the one in the off-hand if
+//! only that one does something when held down (e.g., a shield next to a
+//! sword). Items that are used over time (see [`UseAnimation`]) keep being
+//! used until they finish or the button is released; others, like snowballs,
+//! are left to the server. Block items used on a block place it instead (see
+//! [`block_interaction`][crate::block_interaction]).
+//!
+//! While an item is in use, a bar under the crosshair shows how far along
+//! eating or drawing is, drawing a bow narrows the field of view, and a
+//! spyglass zooms in and darkens the edges of the screen.
+
+use bevy::prelude::*;
+use bevy_flycam::FlyCam;
+use brine_data::{ItemId, MinecraftData, UseAnimation};
+use brine_proto::{
+    event::{
+        clientbound::{Reconfigure, Transfer},
+        serverbound::{ReleaseUseItem, UseItem},
+        Hand, ItemStack,
+    },
+    BrineSystems,
+};
+
+use crate::{
+    block_interaction::placing_hand,
+    container::no_screen_open,
+    hotbar::Hotbar,
+    input::{InputAction, InputMap},
+    player::{look_angles, LocalPlayer},
+    targeting::TargetedBlock,
+};
+
+/// Ticks it takes to fully draw a bow.
+const BOW_DRAW_TICKS: u32 = 20;
+
+/// Ticks it takes to load a crossbow without Quick Charge.
+const CROSSBOW_LOAD_TICKS: u32 = 25;
+
+/// How much a fully drawn bow narrows the field of view.
+const BOW_ZOOM: f32 = 0.15;
+
+/// Field of view through a spyglass, relative to the normal one.
+const SPYGLASS_FOV: f32 = 0.1;
+
+const PROGRESS_BAR_WIDTH: f32 = 64.0;
+const PROGRESS_BAR_HEIGHT: f32 = 4.0;
+
+/// An item the local player is using.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveItemUse {
+    pub hand: Hand,
+    pub item: ItemStack,
+    pub animation: UseAnimation,
+
+    /// Game ticks since the player started using the item.
+    pub ticks: u32,
+}
+
+impl ActiveItemUse {
+    /// Returns how far along using the item is (`0.0..=1.0`), for items that
+    /// finish or charge up after a while.
+    pub fn progress(&self) -> Option<f32> {
+        let total = match self.animation {
+            UseAnimation::Bow => BOW_DRAW_TICKS,
+            UseAnimation::Crossbow => CROSSBOW_LOAD_TICKS,
+            animation => animation.duration_ticks()?,
+        };
+        Some((self.ticks as f32 / total as f32).min(1.0))
+    }
+
+    /// Returns the field of view while using the item, relative to the normal
+    /// one.
+    pub fn fov_multiplier(&self) -> f32 {
+        match self.animation {
+            UseAnimation::Bow => {
+                let draw = (self.ticks as f32 / BOW_DRAW_TICKS as f32).min(1.0);
+                1.0 - draw * draw * BOW_ZOOM
+            }
+            UseAnimation::Spyglass => SPYGLASS_FOV,
+            _ => 1.0,
+        }
+    }
+
+    /// Returns whether the item finished being used by itself, e.g. the food
+    /// has been eaten.
+    fn finished(&self) -> bool {
+        self.animation
+            .duration_ticks()
+            .is_some_and(|duration| self.ticks >= duration)
+    }
+}
+
+/// The item the local player is using, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Deref)]
+pub struct ItemInUse(pub Option<ActiveItemUse>);
+
+/// Plugin that lets the player use held items and shows the effects of using
+/// them.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`Transfer`] and [`Reconfigure`], which stop using the item
+///
+/// The plugin sends the following events:
+///
+/// * [`UseItem`]
+/// * [`ReleaseUseItem`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`ItemInUse`]
+///
+/// # Ordering
+///
+/// Use durations count up once per game tick, in [`FixedUpdate`].
+pub struct ItemUsePlugin;
+
+impl Plugin for ItemUsePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemInUse>()
+            .add_systems(Startup, spawn_use_overlay)
+            .add_systems(FixedUpdate, tick_item_use)
+            .add_systems(
+                Update,
+                (
+                    reset_item_use,
+                    stop_using_item,
+                    start_using_item.run_if(no_screen_open),
+                    apply_use_fov,
+                    update_use_overlay,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+/// Marks the bar under the crosshair that shows how far along using the item
+/// is.
+#[derive(Component)]
+struct UseProgressBar;
+
+/// Marks the dark frame shown while looking through a spyglass.
+#[derive(Component)]
+struct SpyglassOverlay;
+
+fn spawn_use_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Spyglass Overlay"),
+        SpyglassOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            border: UiRect::axes(Val::Vw(15.0), Val::Vh(10.0)),
+            ..default()
+        },
+        BorderColor::all(Color::BLACK),
+        Visibility::Hidden,
+    ));
+
+    commands
+        .spawn((
+            Name::new("Item Use Progress"),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(PROGRESS_BAR_WIDTH),
+                        height: Val::Px(PROGRESS_BAR_HEIGHT),
+                        margin: UiRect::top(Val::Px(48.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                    Visibility::Hidden,
+                    UseProgressBar,
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE),
+                    ));
+                });
+        });
+}
+
+fn reset_item_use(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut item_in_use: ResMut<ItemInUse>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        item_in_use.set_if_neq(ItemInUse::default());
+    }
+}
+
+fn tick_item_use(mut item_in_use: ResMut<ItemInUse>) {
+    let Some(active) = &mut item_in_use.0 else {
+        return;
+    };
+
+    active.ticks += 1;
+    if active.finished() {
+        // The server finishes it too; there is nothing to release.
+        item_in_use.0 = None;
+    }
+}
+
+/// Returns whether the use button is held down.
+fn use_button_pressed(input: &InputMap, mouse: &ButtonInput<MouseButton>) -> bool {
+    mouse.pressed(MouseButton::Right) || input.pressed(InputAction::UseItem)
+}
+
+/// Returns whether the use button was pressed this frame.
+fn use_button_just_pressed(input: &InputMap, mouse: &ButtonInput<MouseButton>) -> bool {
+    mouse.just_pressed(MouseButton::Right) || input.just_pressed(InputAction::UseItem)
+}
+
+fn stop_using_item(
+    input: InputMap,
+    mouse: Res<ButtonInput<MouseButton>>,
+    hotbar: Res<Hotbar>,
+    mut item_in_use: ResMut<ItemInUse>,
+    mut release_events: MessageWriter<ReleaseUseItem>,
+) {
+    let Some(active) = item_in_use.0 else {
+        return;
+    };
+
+    if hotbar.item_in(active.hand).map(|item| item.item_id) != Some(active.item.item_id) {
+        // Switching away from the item (or running out of it) stops using it
+        // on the server as well.
+        item_in_use.0 = None;
+    } else if !use_button_pressed(&input, &mouse) {
+        release_events.write(ReleaseUseItem);
+        item_in_use.0 = None;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_using_item(
+    input: InputMap,
+    mouse: Res<ButtonInput<MouseButton>>,
+    hotbar: Res<Hotbar>,
+    mc_data: Res<MinecraftData>,
+    targeted: Res<TargetedBlock>,
+    players: Query<&Transform, With<LocalPlayer>>,
+    mut item_in_use: ResMut<ItemInUse>,
+    mut use_events: MessageWriter<UseItem>,
+) {
+    if item_in_use.is_some() || !use_button_just_pressed(&input, &mouse) {
+        return;
+    }
+
+    // Clicking a block with a block item places it instead.
+    if targeted.0.is_some() && placing_hand(&mc_data, &hotbar).is_some() {
+        return;
+    }
+
+    let use_animation = |item: Option<ItemStack>| {
+        let item_id = u16::try_from(item?.item_id).ok()?;
+        mc_data.items().get_by_id(ItemId(item_id))?.use_animation()
+    };
+    let main = HandItem {
+        item: hotbar.held_item(),
+        animation: use_animation(hotbar.held_item()),
+    };
+    let off = HandItem {
+        item: hotbar.offhand_item(),
+        animation: use_animation(hotbar.offhand_item()),
+    };
+
+    let Some(hand) = choose_hand(main, off) else {
+        return;
+    };
+
+    let (yaw, pitch) = players
+        .single()
+        .map_or((0.0, 0.0), |player| look_angles(*player.forward()));
+    use_events.write(UseItem { hand, yaw, pitch });
+
+    let HandItem { item, animation } = match hand {
+        Hand::Main => main,
+        Hand::Off => off,
+    };
+    if let (Some(item), Some(animation)) = (item, animation) {
+        debug!("Using {:?} item in {:?} hand", animation, hand);
+        item_in_use.0 = Some(ActiveItemUse {
+            hand,
+            item,
+            animation,
+            ticks: 0,
+        });
+    }
+}
+
+/// What the player holds in one hand.
+#[derive(Debug, Clone, Copy)]
+struct HandItem {
+    item: Option<ItemStack>,
+    animation: Option<UseAnimation>,
+}
+
+/// Picks the hand whose item to use: the first one, main hand first, whose
+/// item is used over time, or else the first one holding anything.
+fn choose_hand(main: HandItem, off: HandItem) -> Option<Hand> {
+    let hands = [(Hand::Main, main), (Hand::Off, off)];
+
+    hands
+        .iter()
+        .find(|(_, held)| held.animation.is_some())
+        .or_else(|| hands.iter().find(|(_, held)| held.item.is_some()))
+        .map(|(hand, _)| *hand)
+}
+
+fn apply_use_fov(
+    item_in_use: Res<ItemInUse>,
+    mut normal_fov: Local<Option<f32>>,
+    mut cameras: Query<&mut Projection, With<FlyCam>>,
+) {
+    let multiplier = item_in_use.map_or(1.0, |active| active.fov_multiplier());
+
+    for mut projection in cameras.iter_mut() {
+        let Projection::Perspective(perspective) = &mut *projection else {
+            continue;
+        };
+
+        if multiplier == 1.0 {
+            // Put back whatever the field of view was before zooming.
+            if let Some(fov) = normal_fov.take() {
+                perspective.fov = fov;
+            }
+        } else {
+            let fov = *normal_fov.get_or_insert(perspective.fov) * multiplier;
+            if perspective.fov != fov {
+                perspective.fov = fov;
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_use_overlay(
+    item_in_use: Res<ItemInUse>,
+    mut spyglass_overlays: Query<&mut Visibility, With<SpyglassOverlay>>,
+    mut progress_bars: Query<
+        (&mut Visibility, &Children),
+        (With<UseProgressBar>, Without<SpyglassOverlay>),
+    >,
+    mut fills: Query<&mut Node>,
+) {
+    if !item_in_use.is_changed() {
+        return;
+    }
+
+    let looking_through_spyglass =
+        item_in_use.is_some_and(|active| active.animation == UseAnimation::Spyglass);
+    for mut visibility in spyglass_overlays.iter_mut() {
+        visibility.set_if_neq(if looking_through_spyglass {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    let progress = item_in_use.and_then(|active| active.progress());
+    for (mut visibility, children) in progress_bars.iter_mut() {
+        visibility.set_if_neq(if progress.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+
+        for child in children.iter() {
+            if let Ok(mut fill) = fills.get_mut(child) {
+                fill.width = Val::Percent(progress.unwrap_or(0.0) * 100.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEM: ItemStack = ItemStack {
+        item_id: 1,
+        count: 1,
+    };
+
+    fn held(animation: Option<UseAnimation>) -> HandItem {
+        HandItem {
+            item: Some(ITEM),
+            animation,
+        }
+    }
+
+    const EMPTY: HandItem = HandItem {
+        item: None,
+        animation: None,
+    };
+
+    #[test]
+    fn prefers_the_hand_whose_item_is_used_over_time() {
+        let sword = held(None);
+        let shield = held(Some(UseAnimation::Block));
+
+        assert_eq!(choose_hand(sword, shield), Some(Hand::Off));
+        assert_eq!(choose_hand(shield, shield), Some(Hand::Main));
+        assert_eq!(choose_hand(sword, sword), Some(Hand::Main));
+        assert_eq!(choose_hand(EMPTY, sword), Some(Hand::Off));
+        assert_eq!(choose_hand(EMPTY, EMPTY), None);
+    }
+
+    #[test]
+    fn bow_zoom_follows_the_draw() {
+        let mut active = ActiveItemUse {
+            hand: Hand::Main,
+            item: ITEM,
+            animation: UseAnimation::Bow,
+            ticks: 0,
+        };
+        assert_eq!(active.fov_multiplier(), 1.0);
+
+        active.ticks = BOW_DRAW_TICKS * 2;
+        assert_eq!(active.progress(), Some(1.0));
+        assert!((active.fov_multiplier() - (1.0 - BOW_ZOOM)).abs() < 1e-6);
+    }
+}
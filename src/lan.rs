@@ -0,0 +1,185 @@
+//! Discovering worlds opened to LAN.
+//!
+//! A Minecraft client that opens its world to LAN announces it every second
+//! and a half by multicasting `[MOTD]<motd>[/MOTD][AD]<port>[/AD]` to
+//! [`LAN_MULTICAST_GROUP`] on [`LAN_PORT`]. The world is reachable at the
+//! announcing host on the advertised port.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use brine_net::udp::UdpListener;
+
+/// Multicast group LAN worlds are announced to.
+pub const LAN_MULTICAST_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+
+/// Port LAN worlds are announced on.
+pub const LAN_PORT: u16 = 4445;
+
+/// How long a world stays listed after its last announcement.
+const LAN_SERVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A world announced on the local network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanServer {
+    /// Address to connect to.
+    pub addr: SocketAddr,
+
+    /// The world's message of the day, with any formatting codes left in.
+    pub motd: String,
+}
+
+/// The worlds currently announced on the local network, by address.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct LanServers(HashMap<SocketAddr, (LanServer, Duration)>);
+
+impl LanServers {
+    pub fn get(&self, addr: &SocketAddr) -> Option<&LanServer> {
+        self.0.get(addr).map(|(server, _)| server)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LanServer> {
+        self.0.values().map(|(server, _)| server)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Sent when a world is first announced, or when its message of the day
+/// changes.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct LanServerFound(pub LanServer);
+
+/// Sent when a world hasn't been announced for a while.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct LanServerLost {
+    pub addr: SocketAddr,
+}
+
+/// Plugin that listens for worlds announced on the local network.
+///
+/// If the announcement port can't be listened on, a warning is logged and no
+/// worlds are ever found.
+///
+/// # Events
+///
+/// The plugin registers and sends the following events:
+///
+/// * [`LanServerFound`]
+/// * [`LanServerLost`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`LanServers`]
+pub struct LanDiscoveryPlugin;
+
+impl Plugin for LanDiscoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LanServers>()
+            .add_message::<LanServerFound>()
+            .add_message::<LanServerLost>();
+
+        match UdpListener::bind_multicast_v4(LAN_MULTICAST_GROUP, LAN_PORT) {
+            Ok(listener) => {
+                app.insert_resource(LanListener(listener))
+                    .add_systems(Update, (receive_announcements, forget_lost_servers).chain());
+            }
+            Err(err) => warn!("Can't listen for LAN worlds on port {}: {}", LAN_PORT, err),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct LanListener(UdpListener);
+
+fn receive_announcements(
+    listener: Res<LanListener>,
+    time: Res<Time<Real>>,
+    mut lan_servers: ResMut<LanServers>,
+    mut found_events: MessageWriter<LanServerFound>,
+) {
+    let now = time.elapsed();
+
+    while let Some(datagram) = listener.0.try_recv() {
+        let Some((motd, port)) = std::str::from_utf8(&datagram.data)
+            .ok()
+            .and_then(parse_announcement)
+        else {
+            debug!(
+                "Ignoring malformed LAN announcement from {}",
+                datagram.source
+            );
+            continue;
+        };
+
+        let server = LanServer {
+            addr: SocketAddr::new(datagram.source.ip(), port),
+            motd: motd.to_string(),
+        };
+
+        let known = lan_servers.get(&server.addr) == Some(&server);
+        if !known {
+            debug!("Found LAN world {:?} at {}", server.motd, server.addr);
+            found_events.write(LanServerFound(server.clone()));
+        }
+        lan_servers.0.insert(server.addr, (server, now));
+    }
+}
+
+fn forget_lost_servers(
+    time: Res<Time<Real>>,
+    mut lan_servers: ResMut<LanServers>,
+    mut lost_events: MessageWriter<LanServerLost>,
+) {
+    let now = time.elapsed();
+
+    lan_servers.0.retain(|&addr, (_, last_seen)| {
+        let lost = now.saturating_sub(*last_seen) > LAN_SERVER_TIMEOUT;
+        if lost {
+            lost_events.write(LanServerLost { addr });
+        }
+        !lost
+    });
+}
+
+/// Parses an announcement into the message of the day and the port.
+fn parse_announcement(announcement: &str) -> Option<(&str, u16)> {
+    let motd = between(announcement, "[MOTD]", "[/MOTD]")?;
+    let port = between(announcement, "[AD]", "[/AD]")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some((motd, port))
+}
+
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = text.split_once(start)?;
+    let (inner, _) = rest.split_once(end)?;
+    Some(inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announcements_are_parsed() {
+        assert_eq!(
+            parse_announcement("[MOTD]Steve - New World[/MOTD][AD]53127[/AD]"),
+            Some(("Steve - New World", 53127))
+        );
+        assert_eq!(parse_announcement("[MOTD]No port[/MOTD]"), None);
+        assert_eq!(parse_announcement("[MOTD]Bad[/MOTD][AD]lan[/AD]"), None);
+    }
+}
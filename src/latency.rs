@@ -0,0 +1,163 @@
+//! The round trip time to the server.
+//!
+//! The backend measures the round trip regularly while playing
+//! ([`PingMeasured`]). [`Latency`] keeps the last few measurements for their
+//! average and jitter, which are also published as diagnostics and shown on
+//! the debug HUD. The latency the server itself measured for each player is
+//! on the [`PlayerList`][crate::player_list::PlayerList] instead.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+use brine_proto::{
+    event::clientbound::{PingMeasured, Reconfigure, Transfer},
+    BrineSystems,
+};
+
+/// How many round trips the average and jitter are taken over.
+const SAMPLE_COUNT: usize = 16;
+
+/// The latest round trip times to the server, oldest first.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct Latency {
+    samples: VecDeque<Duration>,
+}
+
+impl Latency {
+    /// Round trip time, in milliseconds.
+    pub const ROUND_TRIP: DiagnosticPath = DiagnosticPath::const_new("brine/latency/round_trip");
+
+    /// Jitter (see [`jitter`](Self::jitter)), in milliseconds.
+    pub const JITTER: DiagnosticPath = DiagnosticPath::const_new("brine/latency/jitter");
+
+    /// Returns the latest round trip time.
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().copied()
+    }
+
+    /// Returns the average of the latest round trip times.
+    pub fn average(&self) -> Option<Duration> {
+        let count = u32::try_from(self.samples.len()).ok().filter(|n| *n > 0)?;
+        Some(self.samples.iter().sum::<Duration>() / count)
+    }
+
+    /// Returns how much consecutive round trip times differ on average, or
+    /// `None` until there are two of them.
+    pub fn jitter(&self) -> Option<Duration> {
+        let differences = self.samples.len().checked_sub(1).filter(|n| *n > 0)?;
+        let total: Duration = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|(a, b)| a.abs_diff(*b))
+            .sum();
+        Some(total / differences as u32)
+    }
+
+    fn record(&mut self, round_trip: Duration) {
+        if self.samples.len() == SAMPLE_COUNT {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(round_trip);
+    }
+}
+
+/// Plugin that keeps track of the round trip time to the server.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`PingMeasured`]
+/// * [`Transfer`] and [`Reconfigure`], which forget the measurements
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Latency`]
+///
+/// The plugin publishes the following diagnostics, in milliseconds:
+///
+/// * [`Latency::ROUND_TRIP`]
+/// * [`Latency::JITTER`]
+pub struct LatencyPlugin;
+
+impl Plugin for LatencyPlugin {
+    fn build(&self, app: &mut App) {
+        for path in [Latency::ROUND_TRIP, Latency::JITTER] {
+            app.register_diagnostic(Diagnostic::new(path).with_suffix(" ms"));
+        }
+
+        app.init_resource::<Latency>().add_systems(
+            Update,
+            (reset_latency, record_latency)
+                .chain()
+                .in_set(BrineSystems::WorldUpdate),
+        );
+    }
+}
+
+fn reset_latency(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut latency: ResMut<Latency>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        latency.samples.clear();
+    }
+}
+
+fn record_latency(
+    mut measured_events: MessageReader<PingMeasured>,
+    mut latency: ResMut<Latency>,
+    mut diagnostics: Diagnostics,
+) {
+    for measured in measured_events.read() {
+        latency.record(measured.round_trip);
+
+        diagnostics.add_measurement(&Latency::ROUND_TRIP, || {
+            measured.round_trip.as_secs_f64() * 1000.0
+        });
+        if let Some(jitter) = latency.jitter() {
+            diagnostics.add_measurement(&Latency::JITTER, || jitter.as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn average_and_jitter_cover_the_latest_samples() {
+        let mut latency = Latency::default();
+        assert_eq!(latency.average(), None);
+
+        latency.record(ms(40));
+        assert_eq!(latency.average(), Some(ms(40)));
+        assert_eq!(latency.jitter(), None);
+
+        latency.record(ms(60));
+        latency.record(ms(50));
+        assert_eq!(latency.last(), Some(ms(50)));
+        assert_eq!(latency.average(), Some(ms(50)));
+        assert_eq!(latency.jitter(), Some(ms(15)));
+
+        for _ in 0..SAMPLE_COUNT {
+            latency.record(ms(100));
+        }
+        assert_eq!(latency.average(), Some(ms(100)));
+        assert_eq!(latency.jitter(), Some(Duration::ZERO));
+    }
+}
@@ -3,10 +3,56 @@
 //! This library houses code that is common to the main Brine binary and other
 //! utility binaries in `src/bin/`.
 
+pub mod app;
+pub mod block_interaction;
+pub mod block_update;
 pub mod chunk;
+pub mod container;
+pub mod crafting;
 pub mod debug;
+pub mod dropped_item;
+pub mod entity;
 pub mod error;
+pub mod first_person;
+pub mod flight;
+pub mod game_mode;
+pub mod gamepad;
+pub mod graphics;
+pub mod ground;
+pub mod health;
+pub mod hot_reload;
+pub mod hotbar;
+pub mod idle;
+pub mod input;
+pub mod item_frame;
+pub mod item_use;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lan;
+pub mod latency;
+pub mod loading;
 pub mod login;
+pub mod map;
+pub mod map_overlay;
+pub mod mob_model;
+pub mod name_tag;
+pub mod physics;
+pub mod player;
+pub mod player_list;
+pub mod pose;
+pub mod replay;
+pub mod screenshot;
+#[cfg(feature = "scripting")]
+pub mod scripting;
 pub mod server;
+pub mod settings;
+pub mod sound;
+pub mod special_block;
+pub mod status_effect;
+pub mod submerged;
+pub mod synthetic;
+pub mod targeting;
+pub mod vehicle;
+pub mod world_border;
+pub mod world_diff;
 
 pub const DEFAULT_LOG_FILTER: &str = "wgpu_core=warn,naga=warn";
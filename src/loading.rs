@@ -0,0 +1,347 @@
+//! Loading Minecraft assets in the background at startup.
+//!
+//! Indexing and baking the vanilla assets takes several seconds. Doing it on a
+//! task keeps the window responsive and lets us show a loading screen instead.
+//!
+//! In the browser the asset directory can't be listed, so nothing is baked:
+//! the bake cache is fetched through Bevy's asset server instead, and must
+//! have been written by a native run on the same assets.
+
+use std::{path::PathBuf, sync::Arc};
+
+#[cfg(target_arch = "wasm32")]
+use bevy::asset::{io::Reader, AssetLoader, LoadContext, LoadState};
+use bevy::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use brine_asset::{LoadProgress, MinecraftAssets};
+#[cfg(not(target_arch = "wasm32"))]
+use brine_data::MinecraftData;
+#[cfg(not(target_arch = "wasm32"))]
+use futures_lite::future;
+
+/// Whether [`MinecraftAssets`] are available yet.
+#[derive(States, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetLoadState {
+    #[default]
+    Loading,
+    Loaded,
+    Failed,
+}
+
+/// Progress of the background asset load.
+#[derive(Debug, Clone, Resource)]
+pub struct AssetLoadProgress(pub Arc<LoadProgress>);
+
+/// Plugin that builds [`MinecraftAssets`] on the async compute task pool and
+/// inserts the resource when it is done.
+///
+/// Requires the [`MinecraftData`] resource to be inserted before startup.
+/// While loading, a full-screen loading message is shown. Systems that need
+/// [`MinecraftAssets`] should be gated on the resource existing, or on
+/// [`AssetLoadState::Loaded`].
+///
+/// With [`with_bake_cache`][Self::with_bake_cache], baked block states and
+/// models are reused across runs as long as the assets don't change. With
+/// [`with_resource_packs`][Self::with_resource_packs], resource packs are
+/// layered over the vanilla assets.
+///
+/// If loading fails, the error is logged and the app exits.
+///
+/// In the browser, the bake cache is required, and is fetched from inside
+/// Bevy's asset directory (see [`MinecraftAssets::from_bake_cache`]); resource
+/// packs aren't supported.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`AssetsDirectory`]
+/// * [`AssetLoadProgress`]
+/// * [`MinecraftAssets`] (once loaded)
+///
+/// # States
+///
+/// * [`AssetLoadState`]
+pub struct AssetLoadingPlugin {
+    path: PathBuf,
+    cache_path: Option<PathBuf>,
+    resource_packs: Vec<PathBuf>,
+}
+
+impl AssetLoadingPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            cache_path: None,
+            resource_packs: Vec::new(),
+        }
+    }
+
+    /// Reads and writes baked assets from a cache file at `cache_path`.
+    pub fn with_bake_cache(mut self, cache_path: impl Into<PathBuf>) -> Self {
+        self.cache_path = Some(cache_path.into());
+        self
+    }
+
+    /// Layers the resource packs in `resource_packs` over the vanilla assets,
+    /// lowest priority first. Each pack is a directory containing `assets/`,
+    /// inside Bevy's asset directory.
+    pub fn with_resource_packs(mut self, resource_packs: Vec<PathBuf>) -> Self {
+        self.resource_packs = resource_packs;
+        self
+    }
+}
+
+impl Plugin for AssetLoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AssetLoadState>()
+            .insert_resource(AssetsDirectory {
+                path: self.path.clone(),
+                cache_path: self.cache_path.clone(),
+                resource_packs: self.resource_packs.clone(),
+            })
+            .insert_resource(AssetLoadProgress(Arc::new(LoadProgress::new())))
+            .add_systems(OnEnter(AssetLoadState::Loading), spawn_loading_screen)
+            .add_systems(
+                Update,
+                update_loading_screen.run_if(in_state(AssetLoadState::Loading)),
+            );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Startup, start_loading).add_systems(
+            Update,
+            finish_loading.run_if(in_state(AssetLoadState::Loading)),
+        );
+
+        #[cfg(target_arch = "wasm32")]
+        app.init_asset::<BakeCacheFile>()
+            .register_asset_loader(BakeCacheLoader)
+            .add_systems(Startup, fetch_bake_cache)
+            .add_systems(
+                Update,
+                finish_fetching_bake_cache.run_if(in_state(AssetLoadState::Loading)),
+            );
+    }
+}
+
+/// Directory that the assets are loaded from.
+#[derive(Resource, Debug)]
+pub struct AssetsDirectory {
+    pub path: PathBuf,
+
+    /// Where baked assets are cached, if anywhere.
+    pub cache_path: Option<PathBuf>,
+
+    /// Resource packs layered over the assets, lowest priority first.
+    pub resource_packs: Vec<PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Component)]
+struct LoadAssetsTask(Task<brine_asset::api::Result<MinecraftAssets>>);
+
+#[derive(Component)]
+struct LoadingText;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn start_loading(
+    directory: Res<AssetsDirectory>,
+    mc_data: Res<MinecraftData>,
+    progress: Res<AssetLoadProgress>,
+    mut commands: Commands,
+) {
+    info!("Loading Minecraft assets from {}", directory.path.display());
+
+    let path = directory.path.clone();
+    let cache_path = directory.cache_path.clone();
+    let resource_packs = directory.resource_packs.clone();
+    let mc_data = mc_data.clone();
+    let progress = progress.0.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        mc_data.preload();
+
+        MinecraftAssets::new_with_resource_packs(
+            path,
+            &resource_packs,
+            &mc_data,
+            cache_path.as_deref(),
+            &progress,
+        )
+    });
+
+    commands.spawn((LoadAssetsTask(task), Name::new("Loading Minecraft Assets")));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn finish_loading(
+    mut tasks: Query<(Entity, &mut LoadAssetsTask)>,
+    mut next_state: ResMut<NextState<AssetLoadState>>,
+    mut app_exit: MessageWriter<AppExit>,
+    mut commands: Commands,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(result) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+        insert_assets(result, &mut next_state, &mut app_exit, &mut commands);
+    }
+}
+
+fn insert_assets(
+    result: brine_asset::api::Result<MinecraftAssets>,
+    next_state: &mut NextState<AssetLoadState>,
+    app_exit: &mut MessageWriter<AppExit>,
+    commands: &mut Commands,
+) {
+    match result {
+        Ok(mc_assets) => {
+            info!("Finished loading Minecraft assets");
+            commands.insert_resource(mc_assets);
+            next_state.set(AssetLoadState::Loaded);
+        }
+        Err(err) => {
+            error!("Failed to load Minecraft assets: {}", err);
+            next_state.set(AssetLoadState::Failed);
+            app_exit.write(AppExit::error());
+        }
+    }
+}
+
+/// Contents of a bake cache file.
+#[cfg(target_arch = "wasm32")]
+#[derive(Asset, TypePath, Debug)]
+struct BakeCacheFile(Vec<u8>);
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Default, TypePath)]
+struct BakeCacheLoader;
+
+#[cfg(target_arch = "wasm32")]
+impl AssetLoader for BakeCacheLoader {
+    type Asset = BakeCacheFile;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(BakeCacheFile(bytes))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bin"]
+    }
+}
+
+/// The bake cache being fetched.
+#[cfg(target_arch = "wasm32")]
+#[derive(Resource)]
+struct BakeCacheFetch(Handle<BakeCacheFile>);
+
+#[cfg(target_arch = "wasm32")]
+fn fetch_bake_cache(
+    directory: Res<AssetsDirectory>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AssetLoadState>>,
+    mut app_exit: MessageWriter<AppExit>,
+    mut commands: Commands,
+) {
+    let Some(cache_path) = &directory.cache_path else {
+        let err = std::io::Error::other("the browser can only load baked assets");
+        insert_assets(
+            Err(err.into()),
+            &mut next_state,
+            &mut app_exit,
+            &mut commands,
+        );
+        return;
+    };
+    if !directory.resource_packs.is_empty() {
+        warn!("Resource packs aren't supported in the browser, ignoring them");
+    }
+
+    info!(
+        "Fetching baked Minecraft assets from {}",
+        cache_path.display()
+    );
+    commands.insert_resource(BakeCacheFetch(asset_server.load(cache_path.clone())));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn finish_fetching_bake_cache(
+    fetch: Option<Res<BakeCacheFetch>>,
+    directory: Res<AssetsDirectory>,
+    asset_server: Res<AssetServer>,
+    mut files: ResMut<Assets<BakeCacheFile>>,
+    mut next_state: ResMut<NextState<AssetLoadState>>,
+    mut app_exit: MessageWriter<AppExit>,
+    mut commands: Commands,
+) {
+    let Some(fetch) = fetch else {
+        return;
+    };
+
+    let result = if let Some(file) = files.remove(&fetch.0) {
+        MinecraftAssets::from_bake_cache(&directory.path, &file.0)
+    } else if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&fetch.0) {
+        Err(std::io::Error::other(err.to_string()).into())
+    } else {
+        return;
+    };
+
+    commands.remove_resource::<BakeCacheFetch>();
+    insert_assets(result, &mut next_state, &mut app_exit, &mut commands);
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Loading Screen"),
+            DespawnOnExit(AssetLoadState::Loading),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LoadingText,
+                Text::new("Loading assets"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+fn update_loading_screen(
+    progress: Res<AssetLoadProgress>,
+    mut texts: Query<&mut Text, With<LoadingText>>,
+) {
+    let progress = &progress.0;
+    let message = format!(
+        "Loading assets: {} ({:.0}%)",
+        progress.stage(),
+        progress.fraction() * 100.0
+    );
+
+    for mut text in texts.iter_mut() {
+        if text.0 != message {
+            text.0 = message.clone();
+        }
+    }
+}
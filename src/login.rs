@@ -1,7 +1,9 @@
+use std::time::Duration;
+
 use bevy::{app::AppExit, ecs::schedule::IntoScheduleConfigs, prelude::*};
 
 use brine_proto::event::{
-    clientbound::{Disconnect, LoginSuccess},
+    clientbound::{Disconnect, DisconnectKind, LoginSuccess, Reconfigure, Transfer},
     serverbound::Login,
 };
 
@@ -11,6 +13,42 @@ pub enum GameState {
     Idle,
     Login,
     Play,
+    /// Waiting to log in again after the connection was lost.
+    Reconnecting,
+}
+
+/// When and how often [`LoginPlugin`] logs in again after losing the
+/// connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// How many times in a row to try before giving up.
+    pub max_retries: u32,
+
+    /// How long to wait before the first try. The wait doubles after every
+    /// failed try.
+    pub initial_backoff: Duration,
+
+    /// Longest wait between tries.
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns how long to wait before try number `retry` (counting from 0).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
 }
 
 #[derive(Debug, Clone, Resource)]
@@ -18,9 +56,38 @@ struct LoginInfo {
     server: String,
     username: String,
     exit_on_disconnect: bool,
+    reconnect: Option<ReconnectPolicy>,
 }
 
+/// Tries made since the last successful login.
+#[derive(Debug, Default, Resource)]
+struct ReconnectRetries(u32);
+
+#[derive(Debug, Resource)]
+struct ReconnectTimer(Timer);
+
 /// Simple plugin that initiates login to a Minecraft server on app startup.
+///
+/// With a [`ReconnectPolicy`], the plugin logs in again when the connection
+/// fails or is lost, but not when the server kicks the player or refuses the
+/// login. The same username is used, and after a transfer the server the
+/// client was transferred to.
+///
+/// # Events
+///
+/// The plugin sends the following events:
+///
+/// * [`Login`]
+/// * [`Reconfigure`], when it is about to reconnect, so that everything
+///   received from the lost session is discarded the same way as when the
+///   server restarts configuration; the server sends the world again after
+///   the new login.
+///
+/// The plugin listens for the following events:
+///
+/// * [`LoginSuccess`]
+/// * [`Disconnect`]
+/// * [`Transfer`]
 pub struct LoginPlugin {
     info: LoginInfo,
 }
@@ -32,35 +99,59 @@ impl LoginPlugin {
                 server,
                 username,
                 exit_on_disconnect: false,
+                reconnect: None,
             },
         }
     }
 
+    /// Exits the app when disconnected, once there are no more tries left.
     pub fn exit_on_disconnect(mut self) -> Self {
         self.info.exit_on_disconnect = true;
         self
     }
+
+    /// Logs in again according to `policy` when the connection is lost.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.info.reconnect = Some(policy);
+        self
+    }
 }
 
 impl Plugin for LoginPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.info.clone())
+            .init_resource::<ReconnectRetries>()
             .init_state::<GameState>()
             .add_systems(Startup, initiate_login)
             .add_systems(
                 Update,
                 (await_success, handle_disconnect).run_if(in_state(GameState::Login)),
             )
-            .add_systems(Update, handle_disconnect.run_if(in_state(GameState::Play)));
+            .add_systems(
+                Update,
+                (handle_transfer, handle_disconnect).run_if(in_state(GameState::Play)),
+            )
+            .add_systems(
+                Update,
+                reconnect_after_backoff.run_if(in_state(GameState::Reconnecting)),
+            );
     }
 }
 
 fn initiate_login(
     login_info: Res<LoginInfo>,
+    login_events: MessageWriter<Login>,
+    next_state: ResMut<NextState<GameState>>,
+) {
+    info!("Initiating login");
+    send_login(&login_info, login_events, next_state);
+}
+
+fn send_login(
+    login_info: &LoginInfo,
     mut login_events: MessageWriter<Login>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    info!("Initiating login");
     login_events.write(Login {
         server: login_info.server.clone(),
         username: login_info.username.clone(),
@@ -70,26 +161,107 @@ fn initiate_login(
 
 fn await_success(
     mut login_success_events: MessageReader<LoginSuccess>,
+    mut retries: ResMut<ReconnectRetries>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     if login_success_events.read().last().is_some() {
         info!("Login successful, advancing to state Play");
+        retries.0 = 0;
         next_state.set(GameState::Play);
     }
 }
 
+fn handle_transfer(
+    mut transfer_events: MessageReader<Transfer>,
+    mut login_info: ResMut<LoginInfo>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(transfer) = transfer_events.read().last() {
+        info!("Transferred to {}, logging in again", transfer.server);
+        login_info.server = transfer.server.clone();
+        next_state.set(GameState::Login);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_disconnect(
     login_info: Res<LoginInfo>,
+    state: Res<State<GameState>>,
+    mut retries: ResMut<ReconnectRetries>,
     mut disconnect_events: MessageReader<Disconnect>,
+    mut reconfigure_events: MessageWriter<Reconfigure>,
     mut next_state: ResMut<NextState<GameState>>,
     mut app_exit: MessageWriter<AppExit>,
+    mut commands: Commands,
 ) {
-    if let Some(disconnect) = disconnect_events.read().last() {
-        info!("Disconnected from server. Reason: {}", disconnect.reason);
-        next_state.set(GameState::Idle);
+    // If the server kicked the player and the connection dropped at once, the
+    // kick decides.
+    let Some(disconnect) = disconnect_events
+        .read()
+        .max_by_key(|disconnect| disconnect.kind != DisconnectKind::Network)
+    else {
+        return;
+    };
+    info!("Disconnected from server. Reason: {}", disconnect.reason);
+
+    let retry = login_info.reconnect.filter(|policy| {
+        disconnect.kind == DisconnectKind::Network && retries.0 < policy.max_retries
+    });
+
+    if let Some(policy) = retry {
+        let backoff = policy.backoff(retries.0);
+        retries.0 += 1;
+        info!(
+            "Reconnecting in {:.1}s (try {}/{})",
+            backoff.as_secs_f32(),
+            retries.0,
+            policy.max_retries
+        );
 
-        if login_info.exit_on_disconnect {
-            app_exit.write(AppExit::Success);
+        if *state.get() == GameState::Play {
+            reconfigure_events.write(Reconfigure);
         }
+        commands.insert_resource(ReconnectTimer(Timer::new(backoff, TimerMode::Once)));
+        next_state.set(GameState::Reconnecting);
+        return;
+    }
+
+    next_state.set(GameState::Idle);
+
+    if login_info.exit_on_disconnect {
+        app_exit.write(AppExit::Success);
+    }
+}
+
+fn reconnect_after_backoff(
+    time: Res<Time<Real>>,
+    login_info: Res<LoginInfo>,
+    mut timer: ResMut<ReconnectTimer>,
+    login_events: MessageWriter<Login>,
+    next_state: ResMut<NextState<GameState>>,
+) {
+    if timer.0.tick(time.delta()).is_finished() {
+        info!("Reconnecting to {}", login_info.server);
+        send_login(&login_info, login_events, next_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = ReconnectPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+
+        let backoffs: Vec<_> = (0..5)
+            .map(|retry| policy.backoff(retry).as_secs())
+            .collect();
+        assert_eq!(backoffs, [1, 2, 4, 8, 10]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(10));
     }
 }
@@ -1,42 +1,157 @@
 //! The Brine Minecraft client entrypoint.
 
-use std::path::PathBuf;
-
-use bevy::{
-    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    log::{Level, LogPlugin},
-    prelude::*,
-    render::{
-        render_resource::WgpuFeatures,
-        settings::{RenderCreation, WgpuSettings},
-        RenderPlugin,
-    },
+use std::{fs, net::SocketAddr, path::PathBuf, process, thread, time::Duration};
+
+use bevy::math::IVec2;
+use brine_net::{
+    capture::{Capture, CaptureWriter, ReplayServer},
+    transport::WebSocketTransport,
 };
-use bevy_flycam::{FlyCam, NoCameraPlayerPlugin};
-use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use brine_asset::MinecraftAssets;
-use brine_data::MinecraftData;
 use clap::Parser;
 
-use brine_proto::{AlwaysSuccessfulLoginPlugin, ProtocolPlugin};
-use brine_proto_backend::ProtocolBackendPlugin;
-use brine_voxel_v1::{
-    chunk_builder::{component::BuiltChunkSection, ChunkBuilderPlugin, VisibleFacesChunkBuilder},
-    texture::TextureBuilderPlugin,
+use brine_proto_backend::{
+    status::query_status,
+    version::{parse_protocol_version, supported_version, supported_versions_list},
 };
 
 use brine::{
-    debug::DebugWireframePlugin, login::LoginPlugin, server::ServeChunksFromDirectoryPlugin,
-    DEFAULT_LOG_FILTER,
+    app::{normalize_server_address, BrineAppBuilder, DEFAULT_SERVER, DEFAULT_USERNAME},
+    idle::IdleBehavior,
+    login::ReconnectPolicy,
+    replay::{ReplayPlugin, ReplaySummary},
+    server::ChunkServing,
+    settings::DEFAULT_SETTINGS_PATH,
+    synthetic::{SyntheticWorld, WorldShape},
 };
 
-const DEFAULT_PORT: &str = "25565";
-const SERVER: &str = "localhost:25565";
-const USERNAME: &str = "user";
+/// Pause between updates of a replay, so the client doesn't starve the replay
+/// server.
+const REPLAY_UPDATE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// How long `brine ping` waits for each step of the status request.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Brine Minecraft Client
+///
+/// Runs `play` when no subcommand is given.
 #[derive(Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(flatten)]
+    play: PlayArgs,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    Play(PlayArgs),
+    Headless(HeadlessArgs),
+    Ping(PingArgs),
+    Replay(ReplayArgs),
+}
+
+/// Where to log in, and how.
+#[derive(clap::Args)]
+struct ConnectArgs {
+    /// Address of the server to connect to (host:port), or the ws:// URL of a
+    /// WebSocket proxy in front of it. Defaults to localhost:25565, or
+    /// ws://localhost:25566 in the browser.
+    #[clap(long, value_name = "HOST:PORT")]
+    server: Option<String>,
+
+    /// Username to use when logging into the server.
+    #[clap(long, value_name = "USERNAME", default_value = DEFAULT_USERNAME)]
+    username: String,
+
+    /// Protocol to speak regardless of what the server advertises, as a
+    /// Minecraft version (e.g. 1.21.4) or a protocol number (e.g. 769).
+    #[clap(long, value_name = "VERSION", value_parser = parse_protocol)]
+    protocol: Option<i32>,
+
+    /// Log in without asking the server for its status first. Needs
+    /// `--protocol`, since the status is where the protocol comes from
+    /// otherwise.
+    #[clap(long, requires = "protocol")]
+    skip_status: bool,
+
+    /// Record everything received from the server into a capture file that
+    /// `brine replay` can play back.
+    #[clap(long, value_name = "CAPTURE")]
+    record: Option<PathBuf>,
+
+    /// Log in again (up to 5 times, with increasing waits) when the
+    /// connection is lost. Kicks are not retried.
+    #[clap(long)]
+    reconnect: bool,
+
+    /// Send every packet uncompressed, to diagnose servers with unusual
+    /// compression settings.
+    #[clap(long)]
+    no_outbound_compression: bool,
+
+    /// Report packets that the protocol definitions don't read in full, with
+    /// their bytes, to find definitions missing fields.
+    #[clap(long)]
+    strict_decoding: bool,
+
+    /// Stream what changes in the world every tick, as JSON lines, to
+    /// whatever connects to this address.
+    #[clap(long, value_name = "HOST:PORT")]
+    world_diffs: Option<SocketAddr>,
+}
+
+impl ConnectArgs {
+    fn configure(self, mut builder: BrineAppBuilder) -> BrineAppBuilder {
+        let server = self
+            .server
+            .as_deref()
+            .map(normalize_server_address)
+            .unwrap_or_else(|| DEFAULT_SERVER.to_string());
+        if server.starts_with("ws://") {
+            builder = builder.with_transport(WebSocketTransport);
+        }
+        builder = builder.with_server(server).with_username(self.username);
+
+        if let Some(protocol_version) = self.protocol {
+            builder = builder.with_protocol_version(protocol_version);
+        }
+        if self.skip_status {
+            builder = builder.without_status_ping();
+        }
+        if self.reconnect {
+            builder = builder.with_reconnect(ReconnectPolicy::default());
+        }
+        if self.no_outbound_compression {
+            builder = builder.without_outbound_compression();
+        }
+        if self.strict_decoding {
+            builder = builder.with_strict_decoding();
+        }
+        if let Some(addr) = self.world_diffs {
+            builder = builder.with_world_diff_stream(addr);
+        }
+        if let Some(path) = &self.record {
+            match CaptureWriter::create(path) {
+                Ok(capture) => builder = builder.with_capture(capture),
+                Err(err) => {
+                    eprintln!("Failed to create capture {}: {}", path.display(), err);
+                    process::exit(1);
+                }
+            }
+        }
+
+        builder
+    }
+}
+
+/// Opens a window and joins a server (the default).
+#[derive(clap::Args)]
+struct PlayArgs {
+    #[clap(flatten)]
+    connect: ConnectArgs,
+
     /// Run with additional debug utilities (e.g., egui inspector).
     #[clap(short, long)]
     debug: bool,
@@ -45,133 +160,328 @@ struct Args {
     #[clap(name = "chunks", long, value_name = "CHUNK_DIR")]
     chunk_dir: Option<PathBuf>,
 
-    /// Address of the server to connect to (host:port). Defaults to localhost:25565.
-    #[clap(long, value_name = "HOST:PORT")]
-    server: Option<String>,
+    /// Send the chunks nearest this chunk position first.
+    #[clap(long, value_name = "X,Z", requires = "chunks", value_parser = parse_chunk_center)]
+    chunk_center: Option<(i32, i32)>,
 
-    /// Username to use when logging into the server. Defaults to "user".
-    #[clap(long, value_name = "USERNAME", default_value = USERNAME)]
-    username: String,
+    /// Send at most this many chunks per second.
+    #[clap(long, value_name = "CHUNKS", requires = "chunks", value_parser = parse_chunk_rate)]
+    chunk_rate: Option<f32>,
+
+    /// Keep sending chunk files as they appear in the chunk directory.
+    #[clap(long, requires = "chunks")]
+    watch_chunks: bool,
+
+    /// Run with a fake server that generates a world of this shape, for
+    /// benchmarking.
+    #[clap(long, value_name = "SHAPE", conflicts_with = "chunks")]
+    synthetic: Option<SyntheticShape>,
+
+    /// Seed of the `terrain` synthetic world.
+    #[clap(long, default_value_t = 0, requires = "synthetic")]
+    seed: u64,
+
+    /// How many chunks out from the origin the synthetic world reaches.
+    #[clap(long, value_name = "CHUNKS", default_value_t = SyntheticWorld::default().radius, requires = "synthetic")]
+    synthetic_radius: u32,
+
+    /// Path to the settings file (key bindings, etc.). Created on first change.
+    #[clap(long, value_name = "PATH", default_value = DEFAULT_SETTINGS_PATH)]
+    settings: PathBuf,
+
+    /// Resource pack directory to layer over the vanilla assets (must be
+    /// inside `assets/`). Repeat to stack packs; later ones take priority.
+    #[clap(long = "resource-pack", value_name = "PACK_DIR")]
+    resource_packs: Vec<PathBuf>,
+
+    /// Run for a profiling session: quieter logging and frame time
+    /// diagnostics. Build with `--features trace_chrome` or `trace_tracy` to
+    /// record spans.
+    #[clap(long)]
+    profile: bool,
 }
 
-fn main() {
-    let args = Args::parse();
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum SyntheticShape {
+    Flat,
+    Checkerboard,
+    Terrain,
+}
 
-    let mut app = App::new();
+/// Joins a server without a window and runs until disconnected.
+#[derive(clap::Args)]
+struct HeadlessArgs {
+    #[clap(flatten)]
+    connect: ConnectArgs,
 
-    // Default plugins.
-    let mut default_plugins = DefaultPlugins.set(LogPlugin {
-        level: Level::DEBUG,
-        filter: String::from(DEFAULT_LOG_FILTER),
-        ..default()
-    });
+    /// Keep the player looking around, stepping in place and respawning
+    /// after death, so anti-AFK plugins don't kick it.
+    #[clap(long)]
+    idle: bool,
 
-    if args.debug {
-        default_plugins = default_plugins.set(RenderPlugin {
-            render_creation: RenderCreation::Automatic(WgpuSettings {
-                features: WgpuFeatures::POLYGON_MODE_LINE,
-                ..default()
-            }),
-            ..default()
-        });
-    }
+    /// Seconds between turns of the head while idling (0 never turns).
+    /// Defaults to 10.
+    #[clap(long, value_name = "SECONDS", requires = "idle", value_parser = parse_interval)]
+    look_interval: Option<Duration>,
 
-    app.add_plugins(default_plugins);
+    /// Seconds between steps in place while idling (0 never steps). Defaults
+    /// to 45.
+    #[clap(long, value_name = "SECONDS", requires = "idle", value_parser = parse_interval)]
+    jitter_interval: Option<Duration>,
 
-    // Brine-specific plugins.
+    /// Stay dead instead of respawning while idling.
+    #[clap(long, requires = "idle")]
+    no_respawn: bool,
 
-    app.add_plugins(ProtocolPlugin);
+    /// Rhai script to drive the bot with (see `brine::scripting`).
+    #[cfg(feature = "scripting")]
+    #[clap(long, value_name = "SCRIPT")]
+    script: Option<PathBuf>,
+}
 
-    if let Some(chunk_dir) = args.chunk_dir {
-        app.add_plugins((
-            AlwaysSuccessfulLoginPlugin,
-            ServeChunksFromDirectoryPlugin::new(chunk_dir),
-        ));
-    } else {
-        app.add_plugins(ProtocolBackendPlugin);
-        let server = args
-            .server
-            .as_deref()
-            .map(normalize_server_address)
-            .unwrap_or_else(|| SERVER.to_string());
-        app.add_plugins(LoginPlugin::new(server, args.username.clone()).exit_on_disconnect());
+impl HeadlessArgs {
+    fn idle_behavior(&self) -> Option<IdleBehavior> {
+        if !self.idle {
+            return None;
+        }
+
+        let defaults = IdleBehavior::default();
+        let interval = |arg: Option<Duration>, default| match arg {
+            Some(Duration::ZERO) => None,
+            Some(interval) => Some(interval),
+            None => default,
+        };
+        Some(IdleBehavior {
+            look_interval: interval(self.look_interval, defaults.look_interval),
+            jitter_interval: interval(self.jitter_interval, defaults.jitter_interval),
+            auto_respawn: !self.no_respawn,
+        })
     }
+}
 
-    let mc_data = MinecraftData::for_version("1.21.4");
-    // Point at the vanilla 1.21.4 assets directory (contains assets/, data/, pack.mcmeta).
-    let mc_assets = MinecraftAssets::new("assets/1.21.4", &mc_data).unwrap();
-    app.insert_resource(mc_data);
-    app.insert_resource(mc_assets);
-    app.add_plugins((TextureBuilderPlugin, MinecraftWorldViewerPlugin));
+/// Asks a server for its status, as the server list does.
+#[derive(clap::Args)]
+struct PingArgs {
+    /// Address of the server (host or host:port).
+    #[clap(value_name = "HOST[:PORT]")]
+    server: String,
+}
 
-    // Debugging, diagnostics, and utility plugins.
+/// Replays a session recorded with `--record` without a window, then prints
+/// the chunk count, entity count and player position the client ended up
+/// with.
+#[derive(clap::Args)]
+struct ReplayArgs {
+    /// Capture file recorded with `--record`.
+    #[clap(value_name = "CAPTURE")]
+    capture: PathBuf,
 
-    if args.debug {
-        app.add_plugins((
-            WorldInspectorPlugin::new(),
-            DebugWireframePlugin,
-            FrameTimeDiagnosticsPlugin::default(),
-            LogDiagnosticsPlugin::default(),
-        ));
+    /// How many times faster than it was recorded to replay the session.
+    #[clap(long, value_name = "FACTOR", default_value_t = 10.0, value_parser = parse_speed)]
+    speed: f64,
+
+    /// Compare the end state with this golden file (the output of an earlier
+    /// replay) and fail if it differs.
+    #[clap(long, value_name = "GOLDEN")]
+    expect: Option<PathBuf>,
+}
+
+fn parse_protocol(value: &str) -> Result<i32, String> {
+    parse_protocol_version(value).ok_or_else(|| {
+        format!(
+            "unknown Minecraft version or protocol number `{value}` (supported: {})",
+            supported_versions_list()
+        )
+    })
+}
+
+fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("`{value}` is not a positive number")),
     }
+}
+
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("`{value}` is not a number of seconds"))
+}
 
-    app.run();
+fn parse_chunk_center(value: &str) -> Result<(i32, i32), String> {
+    value
+        .split_once(',')
+        .and_then(|(x, z)| Some((x.trim().parse().ok()?, z.trim().parse().ok()?)))
+        .ok_or_else(|| format!("`{value}` is not a chunk position like `-3,12`"))
+}
+
+fn parse_chunk_rate(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!("`{value}` is not a positive number")),
+    }
 }
 
-fn normalize_server_address(server: &str) -> String {
-    let server = server.trim();
-    if needs_port(server) {
-        format!("{server}:{DEFAULT_PORT}")
-    } else {
-        server.to_string()
+fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    let args = Args::parse();
+    #[cfg(target_arch = "wasm32")]
+    let args = Args::parse_from(page_args());
+
+    match args.command.unwrap_or(Command::Play(args.play)) {
+        Command::Play(args) => play(args),
+        Command::Headless(args) => headless(args),
+        Command::Ping(args) => ping(args),
+        Command::Replay(args) => replay(args),
     }
 }
 
-fn needs_port(server: &str) -> bool {
-    if server.starts_with('[') {
-        !server.contains("]:")
-    } else {
-        !server.contains(':')
+/// Returns the command line the page's query string stands for in the browser,
+/// which has none: each `name=value` parameter becomes `--name value`, and
+/// each parameter without a value becomes `--name`. For example,
+/// `?server=ws://proxy:25566&username=Steve&debug`.
+#[cfg(target_arch = "wasm32")]
+fn page_args() -> Vec<String> {
+    let search = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default();
+
+    let decode = |component: &str| {
+        let component = component.replace('+', " ");
+        js_sys::decode_uri_component(&component)
+            .map(String::from)
+            .unwrap_or(component)
+    };
+
+    let mut args = vec![String::from("brine")];
+    for param in search.trim_start_matches('?').split('&') {
+        match param.split_once('=') {
+            Some((name, value)) => args.extend([format!("--{}", decode(name)), decode(value)]),
+            None if !param.is_empty() => args.push(format!("--{}", decode(param))),
+            None => {}
+        }
     }
+    args
 }
 
-#[derive(Default)]
-pub struct MinecraftWorldViewerPlugin;
+fn play(args: PlayArgs) {
+    let mut builder = BrineAppBuilder::new()
+        .with_settings(args.settings)
+        .with_resource_packs(args.resource_packs);
 
-impl Plugin for MinecraftWorldViewerPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_plugins((
-            NoCameraPlayerPlugin,
-            ChunkBuilderPlugin::<VisibleFacesChunkBuilder>::default(),
-            // ChunkBuilderPlugin::<GreedyQuadsChunkBuilder>::default(),
-        ))
-        .add_systems(Startup, set_up_camera)
-        .add_systems(Update, give_chunk_sections_correct_y_height);
+    builder = match (args.chunk_dir, args.synthetic) {
+        (Some(chunk_dir), _) => {
+            builder
+                .with_chunk_directory(chunk_dir)
+                .with_chunk_serving(ChunkServing {
+                    center: args.chunk_center.map(|(x, z)| IVec2::new(x, z)),
+                    chunks_per_second: args.chunk_rate,
+                    watch: args.watch_chunks,
+                })
+        }
+        (None, Some(shape)) => builder.with_synthetic_world(SyntheticWorld {
+            shape: match shape {
+                SyntheticShape::Flat => WorldShape::Flat,
+                SyntheticShape::Checkerboard => WorldShape::Checkerboard,
+                SyntheticShape::Terrain => WorldShape::Terrain { seed: args.seed },
+            },
+            radius: args.synthetic_radius,
+        }),
+        (None, None) => args.connect.configure(builder).exit_on_disconnect(),
+    };
+    if args.debug {
+        builder = builder.with_debug_tools();
+    }
+    if args.profile {
+        builder = builder.with_profiling();
     }
+
+    builder.build().run();
 }
 
-fn set_up_camera(mut commands: Commands) {
-    // Screenshot coords.
-    let camera_start = Transform::from_translation(Vec3::new(-200.0, 87.8, 157.3))
-        .with_rotation(Quat::from_euler(EulerRot::XYZ, 0.1338, 0.183, -0.025));
+fn headless(args: HeadlessArgs) {
+    let mut builder = BrineAppBuilder::new().headless().exit_on_disconnect();
+    if let Some(behavior) = args.idle_behavior() {
+        builder = builder.with_idle_behavior(behavior);
+    }
+    #[cfg(feature = "scripting")]
+    if let Some(path) = &args.script {
+        match brine::scripting::Script::load(path) {
+            Ok(script) => builder = builder.with_script(script),
+            Err(err) => {
+                eprintln!("Failed to load script {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        }
+    }
 
-    // let camera_start = Transform::from_translation(Vec3::new(-260.0, 115.0, 200.0))
-    //     .looking_at(Vec3::new(-40.0, 100.0, 0.0), Vec3::Y);
+    args.connect.configure(builder).build().run();
+}
+
+fn ping(args: PingArgs) {
+    let server = normalize_server_address(&args.server);
+
+    let status = query_status(&server, PING_TIMEOUT).unwrap_or_else(|err| {
+        eprintln!("Failed to ping {}: {}", server, err);
+        process::exit(1);
+    });
 
-    commands.spawn((
-        Camera3d::default(),
-        Msaa::Sample4,
-        FlyCam,
-        camera_start,
-        GlobalTransform::default(),
-    ));
+    let support = match supported_version(status.protocol_version) {
+        Some(_) => "supported",
+        None => "not supported",
+    };
+    println!("{}", status.description);
+    println!(
+        "Version: {} (protocol {}, {})",
+        status.version_name, status.protocol_version, support
+    );
+    println!("Players: {}/{}", status.players_online, status.players_max);
+    println!("Latency: {} ms", status.latency.as_millis());
 }
 
-fn give_chunk_sections_correct_y_height(mut query: Query<(&mut Transform, &BuiltChunkSection)>) {
-    for (mut transform, chunk_section) in query.iter_mut() {
-        let height = (chunk_section.section_y as f32) * 16.0;
-        if transform.translation.y != height {
-            transform.translation.y = height;
+fn replay(args: ReplayArgs) {
+    let capture = Capture::open(&args.capture).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", args.capture.display(), err);
+        process::exit(1);
+    });
+    let server = ReplayServer::start(capture, args.speed).unwrap_or_else(|err| {
+        eprintln!("Failed to start the replay server: {}", err);
+        process::exit(1);
+    });
+
+    let mut app = BrineAppBuilder::new()
+        .headless()
+        .with_server(server.local_addr().to_string())
+        .build();
+    app.add_plugins(ReplayPlugin::new(server));
+    app.finish();
+    app.cleanup();
+
+    while app.should_exit().is_none() {
+        app.update();
+        thread::sleep(REPLAY_UPDATE_INTERVAL);
+    }
+
+    // Printed to stdout (logs go to stderr) so it can be redirected into a
+    // golden file.
+    let summary = app.world().resource::<ReplaySummary>().to_string();
+    print!("{summary}");
+
+    if let Some(golden) = args.expect {
+        let expected = fs::read_to_string(&golden).unwrap_or_else(|err| {
+            eprintln!("Failed to read {}: {}", golden.display(), err);
+            process::exit(1);
+        });
+        if expected != summary {
+            eprintln!(
+                "End state differs from {}:\n--- expected\n{}--- actual\n{}",
+                golden.display(),
+                expected,
+                summary
+            );
+            process::exit(1);
         }
     }
 }
@@ -0,0 +1,448 @@
+//! Map items: the images the server sends for them, and showing them.
+//!
+//! Each map's pixels arrive as [`MapData`] patches of map colors, which are
+//! turned into RGBA and written into an [`Image`] per map as they come in, so
+//! only the changed rectangle is touched.
+//!
+//! A filled map held in the main hand is shown in front of the camera. Item
+//! stacks don't carry their data components yet, so which map is held is
+//! inferred: the server sends a map's data when it is first held, and the
+//! first map sent while a hotbar slot holds a filled map is taken to be the
+//! one in that slot. Anything else can show a map by having a
+//! [`MapDisplay`]; item frames don't get one by themselves, since their
+//! contents come from entity metadata the client doesn't decode yet.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use brine_data::{ItemId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{MapData, Reconfigure, Transfer},
+        ItemStack, MapIcon, MapPatch,
+    },
+    BrineSystems,
+};
+
+use crate::hotbar::{Hotbar, HOTBAR_SIZE};
+
+/// Width and height of every map, in pixels.
+pub const MAP_SIZE: u32 = 128;
+
+/// Name of the item that shows a map.
+const FILLED_MAP: &str = "filled_map";
+
+/// Position of the held map relative to the camera.
+const HELD_MAP_OFFSET: Vec3 = Vec3::new(0.0, -0.3, -0.55);
+
+/// Width and height of the held map, in blocks.
+const HELD_MAP_SIZE: f32 = 0.45;
+
+/// RGB of each base map color, by ID. Base color 0 is transparent.
+const BASE_COLORS: [u32; 62] = [
+    0x000000, 0x7fb238, 0xf7e9a3, 0xc7c7c7, 0xff0000, 0xa0a0ff, 0xa7a7a7, 0x007c00, 0xffffff,
+    0xa4a8b8, 0x976d4d, 0x707070, 0x4040ff, 0x8f7748, 0xfffcf5, 0xd87f33, 0xb24cd8, 0x6699d8,
+    0xe5e533, 0x7fcc19, 0xf27fa5, 0x4c4c4c, 0x999999, 0x4c7f99, 0x7f3fb2, 0x334cb2, 0x664c33,
+    0x667f33, 0x993333, 0x191919, 0xfaee4d, 0x5cdbd5, 0x4a80ff, 0x00d93a, 0x815631, 0x700200,
+    0xd1b1a1, 0x9f5224, 0x95576c, 0x706c8a, 0xba8524, 0x677535, 0xa04d4e, 0x392923, 0x876b62,
+    0x575c5c, 0x7a4958, 0x4c3e5c, 0x4c3223, 0x4c522a, 0x8e3c2e, 0x251610, 0xbd3031, 0x943f61,
+    0x5c191d, 0x167e86, 0x3a8e8c, 0x562c3e, 0x14b485, 0x646464, 0xd8af93, 0x7fa796,
+];
+
+/// Brightness of each of a base color's four shades, out of 255.
+const SHADES: [u32; 4] = [180, 220, 255, 135];
+
+/// Returns the sRGB color of a map color: a base color in the upper six bits
+/// and a shade in the lower two. Base color 0 and unknown base colors are
+/// transparent.
+pub fn map_color_rgba(color: u8) -> [u8; 4] {
+    let base = usize::from(color >> 2);
+    let shade = SHADES[usize::from(color & 3)];
+    match BASE_COLORS.get(base) {
+        Some(&rgb) if base != 0 => {
+            let channel = |shift: u32| (((rgb >> shift) & 0xff) * shade / 255) as u8;
+            [channel(16), channel(8), channel(0), 255]
+        }
+        _ => [0, 0, 0, 0],
+    }
+}
+
+/// Returns a blank (transparent) map image.
+fn blank_map_image() -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: MAP_SIZE,
+            height: MAP_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Writes the pixels of `patch` into a map image. Pixels outside the map are
+/// ignored.
+fn apply_patch(image: &mut Image, patch: &MapPatch) {
+    let width = usize::from(patch.width);
+    if width == 0 {
+        return;
+    }
+
+    for (index, &color) in patch.colors.iter().enumerate() {
+        let x = u32::from(patch.x) + (index % width) as u32;
+        let z = u32::from(patch.z) + (index / width) as u32;
+        if let Some(pixel) = image.pixel_bytes_mut(UVec3::new(x, z, 0)) {
+            pixel.copy_from_slice(&map_color_rgba(color));
+        }
+    }
+}
+
+/// A map the server has sent data for.
+#[derive(Debug, Clone)]
+pub struct MapState {
+    /// The map's pixels, [`MAP_SIZE`] square.
+    pub image: Handle<Image>,
+
+    /// Zoom level in the range `0..=4`.
+    pub scale: u8,
+
+    pub locked: bool,
+
+    icons: Vec<MapIcon>,
+}
+
+impl MapState {
+    /// Returns the icons drawn on the map.
+    pub fn icons(&self) -> &[MapIcon] {
+        &self.icons
+    }
+}
+
+/// Every map the server has sent data for, by map ID.
+#[derive(Resource, Debug, Default)]
+pub struct Maps(HashMap<i32, MapState>);
+
+impl Maps {
+    pub fn get(&self, map_id: i32) -> Option<&MapState> {
+        self.0.get(&map_id)
+    }
+}
+
+/// Which map each hotbar slot holds, as far as the client can tell.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct HeldMap {
+    /// Map ID and the filled map stack it was inferred for, by hotbar slot.
+    slots: [Option<(ItemStack, i32)>; HOTBAR_SIZE],
+
+    /// The map held in the main hand.
+    held: Option<i32>,
+}
+
+impl HeldMap {
+    /// Returns the ID of the map held in the main hand, if it is known.
+    pub fn get(&self) -> Option<i32> {
+        self.held
+    }
+
+    /// Forgets the maps of slots that no longer hold the stack their map was
+    /// inferred for, then takes `sent_map` (if any) to be the one in the
+    /// selected slot if it holds a filled map of unknown ID.
+    fn update(
+        &mut self,
+        selected: usize,
+        items: &[Option<ItemStack>; HOTBAR_SIZE],
+        is_filled_map: impl Fn(ItemStack) -> bool,
+        sent_map: Option<i32>,
+    ) {
+        for (slot, item) in self.slots.iter_mut().zip(items) {
+            if slot.is_some_and(|(stack, _)| Some(stack) != *item) {
+                *slot = None;
+            }
+        }
+
+        if let (Some(item), Some(map_id)) = (items[selected], sent_map) {
+            if is_filled_map(item) && self.slots[selected].is_none() {
+                self.slots[selected] = Some((item, map_id));
+            }
+        }
+
+        self.held = self.slots[selected].map(|(_, map_id)| map_id);
+    }
+}
+
+/// Shows a map on the entity's [`StandardMaterial`], e.g. a quad in an item
+/// frame.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapDisplay(pub i32);
+
+/// Marker component for the held map in front of a camera.
+#[derive(Component, Debug)]
+struct HeldMapModel;
+
+/// Plugin that keeps [`Maps`] up to date and shows them: the held map in
+/// front of every [`Camera3d`], and any entity with a [`MapDisplay`].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`MapData`]
+/// * [`Transfer`] and [`Reconfigure`], which forget every map
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Maps`]
+/// * [`HeldMap`]
+///
+/// The plugin expects the [`Hotbar`] resource from the
+/// [`HotbarPlugin`][crate::hotbar::HotbarPlugin].
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Maps>()
+            .init_resource::<HeldMap>()
+            .add_systems(
+                Update,
+                (reset_maps, handle_map_events)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            )
+            .add_systems(
+                Update,
+                (
+                    attach_held_map_to_cameras,
+                    update_held_map_model,
+                    update_map_displays,
+                )
+                    .chain()
+                    .in_set(BrineSystems::RenderPrep),
+            );
+    }
+}
+
+fn reset_maps(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut maps: ResMut<Maps>,
+    mut held_map: ResMut<HeldMap>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        maps.0.clear();
+        held_map.set_if_neq(HeldMap::default());
+    }
+}
+
+fn handle_map_events(
+    mut map_events: MessageReader<MapData>,
+    hotbar: Res<Hotbar>,
+    mc_data: Res<MinecraftData>,
+    mut maps: ResMut<Maps>,
+    mut held_map: ResMut<HeldMap>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut sent_map = None;
+
+    for map_data in map_events.read() {
+        let map = maps.0.entry(map_data.map_id).or_insert_with(|| MapState {
+            image: images.add(blank_map_image()),
+            scale: map_data.scale,
+            locked: map_data.locked,
+            icons: Vec::new(),
+        });
+        map.scale = map_data.scale;
+        map.locked = map_data.locked;
+        if let Some(icons) = &map_data.icons {
+            map.icons.clone_from(icons);
+        }
+        if let Some(patch) = &map_data.patch {
+            if let Some(image) = images.get_mut(&map.image) {
+                apply_patch(image, patch);
+            }
+        }
+
+        sent_map = Some(map_data.map_id);
+    }
+
+    if !hotbar.is_changed() && sent_map.is_none() {
+        return;
+    }
+
+    let is_filled_map = |item: ItemStack| {
+        u16::try_from(item.item_id)
+            .ok()
+            .and_then(|item_id| mc_data.items().get_by_id(ItemId(item_id)))
+            .is_some_and(|item| item.name == FILLED_MAP)
+    };
+    let mut updated = held_map.clone();
+    updated.update(hotbar.selected(), hotbar.items(), is_filled_map, sent_map);
+    held_map.set_if_neq(updated);
+}
+
+fn attach_held_map_to_cameras(
+    mut commands: Commands,
+    cameras: Query<Entity, Added<Camera3d>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for camera in cameras.iter() {
+        let mesh = meshes.add(Rectangle::from_length(HELD_MAP_SIZE));
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.9, 0.85, 0.7),
+            unlit: true,
+            alpha_mode: AlphaMode::Mask(0.5),
+            ..default()
+        });
+
+        commands.entity(camera).with_children(|parent| {
+            parent.spawn((
+                Name::new("Held Map"),
+                HeldMapModel,
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_translation(HELD_MAP_OFFSET)
+                    .with_rotation(Quat::from_rotation_x(-0.6)),
+                Visibility::Hidden,
+            ));
+        });
+    }
+}
+
+/// Shows the [`HeldMap`] on the held map models, or hides them if no known
+/// map is held.
+fn update_held_map_model(
+    mut commands: Commands,
+    held_map: Res<HeldMap>,
+    maps: Res<Maps>,
+    mut models: Query<(Entity, &mut Visibility), With<HeldMapModel>>,
+    added_models: Query<(), Added<HeldMapModel>>,
+) {
+    if !held_map.is_changed() && !maps.is_changed() && added_models.is_empty() {
+        return;
+    }
+
+    let map_id = held_map.get().filter(|&map_id| maps.get(map_id).is_some());
+    for (model, mut visibility) in models.iter_mut() {
+        match map_id {
+            Some(map_id) => {
+                commands.entity(model).insert(MapDisplay(map_id));
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                commands.entity(model).remove::<MapDisplay>();
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+/// Points the material of every [`MapDisplay`] at its map's image.
+fn update_map_displays(
+    maps: Res<Maps>,
+    displays: Query<(Ref<MapDisplay>, &MeshMaterial3d<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (display, material) in displays.iter() {
+        if !display.is_changed() && !maps.is_changed() {
+            continue;
+        }
+
+        let image = maps.get(display.0).map(|map| map.image.clone());
+        if let Some(material) = materials.get_mut(&material.0) {
+            if material.base_color_texture != image {
+                material.base_color_texture = image;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILLED_MAP_ID: i32 = 7;
+
+    fn stack(item_id: i32) -> Option<ItemStack> {
+        Some(ItemStack { item_id, count: 1 })
+    }
+
+    #[test]
+    fn map_colors_are_shaded_base_colors() {
+        assert_eq!(map_color_rgba(0), [0, 0, 0, 0]);
+        assert_eq!(map_color_rgba(3), [0, 0, 0, 0]);
+        // White (8), in each shade.
+        assert_eq!(map_color_rgba(8 * 4 + 2), [255, 255, 255, 255]);
+        assert_eq!(map_color_rgba(8 * 4), [180, 180, 180, 255]);
+        assert_eq!(map_color_rgba(8 * 4 + 3), [135, 135, 135, 255]);
+        // Grass (1), darkest-but-one shade.
+        assert_eq!(map_color_rgba(5), [109, 153, 48, 255]);
+        // Past the last base color.
+        assert_eq!(map_color_rgba(62 * 4), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn patches_update_only_their_rectangle() {
+        let mut image = blank_map_image();
+        apply_patch(
+            &mut image,
+            &MapPatch {
+                x: 127,
+                z: 10,
+                width: 2,
+                height: 1,
+                colors: vec![8 * 4 + 2, 8 * 4 + 2],
+            },
+        );
+
+        assert_eq!(
+            image.pixel_bytes(UVec3::new(127, 10, 0)),
+            Some(&[255, 255, 255, 255][..])
+        );
+        assert_eq!(
+            image.pixel_bytes(UVec3::new(126, 10, 0)),
+            Some(&[0, 0, 0, 0][..])
+        );
+        assert_eq!(
+            image.pixel_bytes(UVec3::new(0, 11, 0)),
+            Some(&[0, 0, 0, 0][..])
+        );
+    }
+
+    #[test]
+    fn held_maps_are_inferred_per_slot() {
+        let is_filled_map = |item: ItemStack| item.item_id == FILLED_MAP_ID;
+        let mut items = [None; HOTBAR_SIZE];
+        items[0] = stack(FILLED_MAP_ID);
+        items[1] = stack(1);
+
+        let mut held_map = HeldMap::default();
+        held_map.update(0, &items, is_filled_map, Some(3));
+        assert_eq!(held_map.get(), Some(3));
+
+        // Later updates, e.g. of maps in item frames, don't replace it.
+        held_map.update(0, &items, is_filled_map, Some(4));
+        assert_eq!(held_map.get(), Some(3));
+
+        // Nor are they taken for items that aren't maps.
+        held_map.update(1, &items, is_filled_map, Some(4));
+        assert_eq!(held_map.get(), None);
+        held_map.update(0, &items, is_filled_map, None);
+        assert_eq!(held_map.get(), Some(3));
+
+        // The slot is forgotten once its map is moved away.
+        items[0] = None;
+        held_map.update(0, &items, is_filled_map, None);
+        assert_eq!(held_map.get(), None);
+    }
+}
@@ -0,0 +1,407 @@
+//! A live top-down map of the loaded chunks.
+//!
+//! As on a vanilla map, each block column is drawn in the [`MapColor`] of its
+//! highest block that has one, a shade brighter where it is higher than the
+//! column to its north and a shade darker where it is lower, so hills show.
+//! Columns are worked out when a full chunk arrives, from its heightmap
+//! down. Placed blocks above a column raise it and changed top blocks recolor
+//! it, but broken ones don't lower it until the chunk is sent again, as with
+//! the [`Heightmaps`][crate::ground::Heightmaps].
+//!
+//! The map is centered on the [`LocalPlayer`], drawn at one of
+//! [`ZOOM_LEVELS`] blocks per pixel, and only redrawn when a column, the
+//! player's column or the zoom changes. Since it is drawn straight from the
+//! decoded chunks, it shows chunks that decoded wrong without having to fly
+//! over them.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use brine_chunk::{BlockPos, BlockState, Chunk, ChunkPos, ChunkRelative, Heightmap, CHUNK_MIN_Y};
+use brine_data::{blocks::MapColor, BlockStateId, MinecraftData};
+use brine_proto::{
+    event::clientbound::{BlockChanged, ChunkData, Reconfigure, Transfer},
+    BrineSystems,
+};
+
+use crate::{
+    input::{InputAction, InputMap},
+    map::map_color_rgba,
+    player::LocalPlayer,
+};
+
+/// Width and height of the map, in pixels.
+pub const MAP_OVERLAY_SIZE: u32 = 256;
+
+/// Blocks per pixel at each zoom level, from the closest.
+pub const ZOOM_LEVELS: [i32; 5] = [1, 2, 4, 8, 16];
+
+/// Color of pixels outside the loaded chunks.
+const UNLOADED_COLOR: [u8; 4] = [0, 0, 0, 96];
+
+/// Color of the player's marker in the middle of the map.
+const PLAYER_COLOR: [u8; 4] = [255, 0, 0, 255];
+
+/// Number of columns in a chunk.
+const COLUMNS: usize = 16 * 16;
+
+/// The top of a block column, as drawn on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Column {
+    /// World Y of the highest block with a map color.
+    y: i32,
+    color: MapColor,
+}
+
+/// The columns of a chunk, in Z-X-major order. Columns without any block
+/// with a map color are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MapTile([Option<Column>; COLUMNS]);
+
+impl MapTile {
+    /// Works out the columns of a full chunk, from the top of its heightmap
+    /// down.
+    fn from_chunk(chunk: &Chunk, mut color_of: impl FnMut(BlockState) -> MapColor) -> Self {
+        let computed;
+        let heightmap = match &chunk.heightmap {
+            Some(heightmap) => heightmap,
+            None => {
+                computed = Heightmap::from_sections(&chunk.sections);
+                &computed
+            }
+        };
+
+        let mut columns = [None; COLUMNS];
+        for (index, column) in columns.iter_mut().enumerate() {
+            let (x, z) = ((index % 16) as u8, (index / 16) as u8);
+            let Some(surface_y) = heightmap.surface_y(x, z) else {
+                continue;
+            };
+            *column = (i32::from(CHUNK_MIN_Y)..surface_y).rev().find_map(|y| {
+                let color = color_of(chunk.get_block(ChunkRelative::new(x, y, z)));
+                (color != MapColor::NONE).then_some(Column { y, color })
+            });
+        }
+        Self(columns)
+    }
+
+    fn get(&self, relative: ChunkRelative) -> Option<Column> {
+        self.0[relative.z as usize * 16 + relative.x as usize]
+    }
+
+    /// Updates the column of a changed block. Returns whether it changed.
+    fn block_changed(&mut self, relative: ChunkRelative, color: MapColor) -> bool {
+        let column = &mut self.0[relative.z as usize * 16 + relative.x as usize];
+        let changed = match *column {
+            _ if color == MapColor::NONE => None,
+            Some(top) if relative.y < top.y => None,
+            Some(top) if top.color == color && top.y == relative.y => None,
+            _ => Some(Column {
+                y: relative.y,
+                color,
+            }),
+        };
+        if let Some(top) = changed {
+            *column = Some(top);
+        }
+        changed.is_some()
+    }
+}
+
+/// Returns the shade a column is drawn in, from how high it is compared to
+/// the column to its north.
+fn shade(column: Column, north: Option<Column>) -> u8 {
+    match north {
+        Some(north) if column.y > north.y => 2,
+        Some(north) if column.y < north.y => 0,
+        _ => 1,
+    }
+}
+
+/// The map of the loaded chunks, and how it is shown.
+#[derive(Resource, Debug, Default)]
+pub struct MapOverlay {
+    /// Whether the map is on screen.
+    pub shown: bool,
+
+    /// Index into [`ZOOM_LEVELS`].
+    pub zoom: usize,
+
+    tiles: HashMap<ChunkPos, Box<MapTile>>,
+    colors: HashMap<BlockState, MapColor>,
+
+    /// The player's column and zoom the map was last drawn at, unless columns
+    /// have changed since.
+    drawn: Option<(BlockPos, usize)>,
+}
+
+impl MapOverlay {
+    /// Returns how many blocks wide each pixel of the map is.
+    pub fn blocks_per_pixel(&self) -> i32 {
+        ZOOM_LEVELS[self.zoom.min(ZOOM_LEVELS.len() - 1)]
+    }
+
+    fn column(&self, x: i32, z: i32) -> Option<Column> {
+        let block = BlockPos::new(x, 0, z);
+        self.tiles.get(&block.chunk())?.get(block.chunk_relative())
+    }
+
+    /// Draws the map centered on the column at `center` into `pixels`, RGBA
+    /// rows from north to south.
+    fn draw(&self, center: BlockPos, pixels: &mut [u8]) {
+        let scale = self.blocks_per_pixel();
+        let half = MAP_OVERLAY_SIZE as i32 / 2;
+
+        for (index, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+            let px = (index % MAP_OVERLAY_SIZE as usize) as i32 - half;
+            let pz = (index / MAP_OVERLAY_SIZE as usize) as i32 - half;
+            let (x, z) = (center.x + px * scale, center.z + pz * scale);
+
+            let rgba = if px.abs() <= 1 && pz.abs() <= 1 {
+                PLAYER_COLOR
+            } else if let Some(column) = self.column(x, z) {
+                let shade = shade(column, self.column(x, z - scale));
+                map_color_rgba(column.color.shaded(shade))
+            } else {
+                UNLOADED_COLOR
+            };
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+}
+
+/// Marks the node showing the [`MapOverlay`].
+#[derive(Component, Debug)]
+struct MapOverlayNode;
+
+/// Plugin that keeps a top-down map of the loaded chunks and shows it in the
+/// bottom right corner.
+///
+/// [`ToggleMap`][InputAction::ToggleMap] (M) shows or hides the map, and
+/// [`MapZoomIn`][InputAction::MapZoomIn] and
+/// [`MapZoomOut`][InputAction::MapZoomOut] (+ and -) zoom it. Requires a
+/// [`LocalPlayer`].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ChunkData`] and [`BlockChanged`]
+/// * [`Transfer`] and [`Reconfigure`], which clear the map
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`MapOverlay`]
+///
+/// # Ordering
+///
+/// The map is updated in [`BrineSystems::WorldUpdate`] and drawn in
+/// [`BrineSystems::RenderPrep`].
+pub struct MapOverlayPlugin;
+
+impl Plugin for MapOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapOverlay>()
+            .add_systems(Startup, spawn_map_overlay)
+            .add_systems(
+                Update,
+                (
+                    (clear_map_on_reset, update_map)
+                        .chain()
+                        .run_if(resource_exists::<MinecraftData>)
+                        .in_set(BrineSystems::WorldUpdate),
+                    (toggle_map, draw_map)
+                        .chain()
+                        .in_set(BrineSystems::RenderPrep),
+                ),
+            );
+    }
+}
+
+fn spawn_map_overlay(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = Image::new_fill(
+        Extent3d {
+            width: MAP_OVERLAY_SIZE,
+            height: MAP_OVERLAY_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &UNLOADED_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    commands.spawn((
+        Name::new("Map overlay"),
+        MapOverlayNode,
+        ImageNode::new(images.add(image)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            right: Val::Px(8.0),
+            width: Val::Px(MAP_OVERLAY_SIZE as f32),
+            height: Val::Px(MAP_OVERLAY_SIZE as f32),
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+fn clear_map_on_reset(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut map: ResMut<MapOverlay>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        map.tiles.clear();
+        map.drawn = None;
+    }
+}
+
+fn update_map(
+    mut chunk_events: MessageReader<ChunkData>,
+    mut changed_events: MessageReader<BlockChanged>,
+    mc_data: Res<MinecraftData>,
+    mut map: ResMut<MapOverlay>,
+) {
+    let map = &mut *map;
+    let mut color_of = |block_state: BlockState| {
+        *map.colors.entry(block_state).or_insert_with(|| {
+            mc_data
+                .blocks()
+                .get_by_state_id(BlockStateId(block_state.0 as u16))
+                .map_or(MapColor::NONE, |block| block.map_color())
+        })
+    };
+
+    for chunk in chunk_events.read() {
+        let chunk = &chunk.chunk_data;
+        if !chunk.is_full() {
+            continue;
+        }
+        let tile = MapTile::from_chunk(chunk, &mut color_of);
+        map.tiles.insert(chunk.position(), Box::new(tile));
+        map.drawn = None;
+    }
+
+    for changed in changed_events.read() {
+        let block = BlockPos::from(changed.position);
+        let color = color_of(changed.block_state);
+        if let Some(tile) = map.tiles.get_mut(&block.chunk()) {
+            if tile.block_changed(block.chunk_relative(), color) {
+                map.drawn = None;
+            }
+        }
+    }
+}
+
+fn toggle_map(
+    input: InputMap,
+    mut map: ResMut<MapOverlay>,
+    mut nodes: Query<&mut Visibility, With<MapOverlayNode>>,
+) {
+    if input.just_pressed(InputAction::ToggleMap) {
+        map.shown = !map.shown;
+        map.drawn = None;
+        for mut visibility in nodes.iter_mut() {
+            *visibility = if map.shown {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+    if input.just_pressed(InputAction::MapZoomIn) {
+        map.zoom = map.zoom.saturating_sub(1);
+    }
+    if input.just_pressed(InputAction::MapZoomOut) {
+        map.zoom = (map.zoom + 1).min(ZOOM_LEVELS.len() - 1);
+    }
+}
+
+fn draw_map(
+    mut map: ResMut<MapOverlay>,
+    player: Query<&GlobalTransform, With<LocalPlayer>>,
+    nodes: Query<&ImageNode, With<MapOverlayNode>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let center = BlockPos {
+        y: 0,
+        ..BlockPos::containing(player.translation())
+    };
+    if !map.shown || map.drawn == Some((center, map.zoom)) {
+        return;
+    }
+
+    for node in nodes.iter() {
+        let Some(pixels) = images
+            .get_mut(&node.image)
+            .and_then(|image| image.data.as_mut())
+        else {
+            continue;
+        };
+        map.draw(center, pixels);
+    }
+    map.drawn = Some((center, map.zoom));
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_chunk::{BlockStates, ChunkSection};
+
+    use super::*;
+
+    const GLASS: BlockState = BlockState(1);
+    const STONE: BlockState = BlockState(2);
+
+    fn color_of(block_state: BlockState) -> MapColor {
+        match block_state {
+            STONE => MapColor::STONE,
+            _ => MapColor::NONE,
+        }
+    }
+
+    #[test]
+    fn columns_skip_blocks_without_a_map_color() {
+        let mut section = ChunkSection::empty(0);
+        let mut block_states = BlockStates::default();
+        block_states.0[BlockStates::xyz_to_index(0, 3, 0)] = STONE;
+        block_states.0[BlockStates::xyz_to_index(0, 4, 0)] = GLASS;
+        block_states.0[BlockStates::xyz_to_index(1, 2, 0)] = STONE;
+        section.block_states = block_states;
+        section.block_count = 3;
+
+        let mut chunk = Chunk::empty(0, 0);
+        chunk.sections.push(section);
+        let mut tile = MapTile::from_chunk(&chunk, color_of);
+
+        let top = |x, tile: &MapTile| tile.get(ChunkRelative::new(x, 0, 0));
+        let stone = |y| {
+            Some(Column {
+                y,
+                color: MapColor::STONE,
+            })
+        };
+        assert_eq!(top(0, &tile), stone(3));
+        assert_eq!(top(1, &tile), stone(2));
+        assert_eq!(top(2, &tile), None);
+        assert_eq!(shade(stone(3).unwrap(), stone(2)), 2);
+
+        // Placing raises a column; breaking doesn't lower it.
+        assert!(tile.block_changed(ChunkRelative::new(1, 8, 0), MapColor::STONE));
+        assert!(!tile.block_changed(ChunkRelative::new(0, 3, 0), MapColor::NONE));
+        assert_eq!(top(1, &tile), stone(8));
+        assert_eq!(top(0, &tile), stone(3));
+    }
+}
@@ -0,0 +1,450 @@
+//! Built-in geometry for mob models.
+//!
+//! Models are described the way vanilla describes them: in pixels, with Y
+//! pointing down, the feet at Y = 24 and the face towards -Z. Texture
+//! coordinates use vanilla's box layout, so the vanilla entity textures map on
+//! without any extra data. [`build_part_mesh`] and [`to_local_rotation`]
+//! convert to Bevy's coordinate system.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+
+/// Model pixels per block.
+const PIXELS_PER_BLOCK: f32 = 16.0;
+
+/// Model Y coordinate of the entity's feet.
+const FEET_Y: f32 = 24.0;
+
+/// A mob with a built-in model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MobKind {
+    Zombie,
+    Skeleton,
+    Creeper,
+    Pig,
+    Cow,
+}
+
+impl MobKind {
+    pub const ALL: [MobKind; 5] = [
+        MobKind::Zombie,
+        MobKind::Skeleton,
+        MobKind::Creeper,
+        MobKind::Pig,
+        MobKind::Cow,
+    ];
+
+    /// Returns the mob with the given entity type name (e.g. `zombie`), if it
+    /// has a model.
+    pub fn from_entity_type_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "zombie" => MobKind::Zombie,
+            "skeleton" => MobKind::Skeleton,
+            "creeper" => MobKind::Creeper,
+            "pig" => MobKind::Pig,
+            "cow" => MobKind::Cow,
+            _ => return None,
+        })
+    }
+
+    /// Returns the name of the mob's texture, e.g. `entity/zombie/zombie`.
+    pub fn texture(self) -> &'static str {
+        match self {
+            MobKind::Zombie => "entity/zombie/zombie",
+            MobKind::Skeleton => "entity/skeleton/skeleton",
+            MobKind::Creeper => "entity/creeper/creeper",
+            MobKind::Pig => "entity/pig/pig",
+            MobKind::Cow => "entity/cow/cow",
+        }
+    }
+
+    pub(crate) fn parts(self) -> &'static [PartDef] {
+        match self {
+            MobKind::Zombie => ZOMBIE,
+            MobKind::Skeleton => SKELETON,
+            MobKind::Creeper => CREEPER,
+            MobKind::Pig => PIG,
+            MobKind::Cow => COW,
+        }
+    }
+}
+
+/// How a model part moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PartAnimation {
+    /// Keeps its rest rotation.
+    None,
+
+    /// Follows the entity's pitch and head yaw.
+    Head,
+
+    /// Swings back and forth while walking; `phase` is `0` or `PI` so that
+    /// opposite legs alternate.
+    Leg { phase: f32 },
+
+    /// Swings opposite to the legs while walking and sways slightly when
+    /// idle. `side` is `1` for the right arm and `-1` for the left.
+    Arm { phase: f32, side: f32, raised: bool },
+}
+
+/// A cuboid within a model part, relative to the part's pivot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Cube {
+    pub origin: [f32; 3],
+    pub size: [f32; 3],
+    /// Top-left corner of the cube's area in the texture, in pixels.
+    pub uv: [f32; 2],
+    /// Whether the texture is flipped horizontally, for left limbs that reuse
+    /// the right limb's texture.
+    pub mirror: bool,
+}
+
+/// A separately animated piece of a model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PartDef {
+    pub name: &'static str,
+    pub pivot: [f32; 3],
+    /// Rest rotation around the pivot, in radians.
+    pub rotation: [f32; 3],
+    pub cubes: &'static [Cube],
+    pub animation: PartAnimation,
+}
+
+const fn cube(origin: [f32; 3], size: [f32; 3], uv: [f32; 2]) -> Cube {
+    Cube {
+        origin,
+        size,
+        uv,
+        mirror: false,
+    }
+}
+
+const fn mirrored(origin: [f32; 3], size: [f32; 3], uv: [f32; 2]) -> Cube {
+    Cube {
+        origin,
+        size,
+        uv,
+        mirror: true,
+    }
+}
+
+const fn part(
+    name: &'static str,
+    pivot: [f32; 3],
+    cubes: &'static [Cube],
+    animation: PartAnimation,
+) -> PartDef {
+    PartDef {
+        name,
+        pivot,
+        rotation: [0.0; 3],
+        cubes,
+        animation,
+    }
+}
+
+const fn leg(name: &'static str, pivot: [f32; 3], cubes: &'static [Cube], phase: f32) -> PartDef {
+    part(name, pivot, cubes, PartAnimation::Leg { phase })
+}
+
+const HUMANOID_HEAD: PartDef = part(
+    "head",
+    [0.0, 0.0, 0.0],
+    &[cube([-4.0, -8.0, -4.0], [8.0, 8.0, 8.0], [0.0, 0.0])],
+    PartAnimation::Head,
+);
+
+const HUMANOID_BODY: PartDef = part(
+    "body",
+    [0.0, 0.0, 0.0],
+    &[cube([-4.0, 0.0, -2.0], [8.0, 12.0, 4.0], [16.0, 16.0])],
+    PartAnimation::None,
+);
+
+const ZOMBIE: &[PartDef] = &[
+    HUMANOID_HEAD,
+    HUMANOID_BODY,
+    part(
+        "right_arm",
+        [-5.0, 2.0, 0.0],
+        &[cube([-3.0, -2.0, -2.0], [4.0, 12.0, 4.0], [40.0, 16.0])],
+        PartAnimation::Arm {
+            phase: PI,
+            side: 1.0,
+            raised: true,
+        },
+    ),
+    part(
+        "left_arm",
+        [5.0, 2.0, 0.0],
+        &[cube([-1.0, -2.0, -2.0], [4.0, 12.0, 4.0], [32.0, 48.0])],
+        PartAnimation::Arm {
+            phase: 0.0,
+            side: -1.0,
+            raised: true,
+        },
+    ),
+    leg(
+        "right_leg",
+        [-1.9, 12.0, 0.0],
+        &[cube([-2.0, 0.0, -2.0], [4.0, 12.0, 4.0], [0.0, 16.0])],
+        0.0,
+    ),
+    leg(
+        "left_leg",
+        [1.9, 12.0, 0.0],
+        &[cube([-2.0, 0.0, -2.0], [4.0, 12.0, 4.0], [16.0, 48.0])],
+        PI,
+    ),
+];
+
+const SKELETON: &[PartDef] = &[
+    HUMANOID_HEAD,
+    HUMANOID_BODY,
+    part(
+        "right_arm",
+        [-5.0, 2.0, 0.0],
+        &[cube([-1.0, -2.0, -1.0], [2.0, 12.0, 2.0], [40.0, 16.0])],
+        PartAnimation::Arm {
+            phase: PI,
+            side: 1.0,
+            raised: false,
+        },
+    ),
+    part(
+        "left_arm",
+        [5.0, 2.0, 0.0],
+        &[mirrored([-1.0, -2.0, -1.0], [2.0, 12.0, 2.0], [40.0, 16.0])],
+        PartAnimation::Arm {
+            phase: 0.0,
+            side: -1.0,
+            raised: false,
+        },
+    ),
+    leg(
+        "right_leg",
+        [-2.0, 12.0, 0.0],
+        &[cube([-1.0, 0.0, -1.0], [2.0, 12.0, 2.0], [0.0, 16.0])],
+        0.0,
+    ),
+    leg(
+        "left_leg",
+        [2.0, 12.0, 0.0],
+        &[mirrored([-1.0, 0.0, -1.0], [2.0, 12.0, 2.0], [0.0, 16.0])],
+        PI,
+    ),
+];
+
+const CREEPER_LEG: &[Cube] = &[cube([-2.0, 0.0, -2.0], [4.0, 6.0, 4.0], [0.0, 16.0])];
+
+const CREEPER: &[PartDef] = &[
+    part(
+        "head",
+        [0.0, 6.0, 0.0],
+        &[cube([-4.0, -8.0, -4.0], [8.0, 8.0, 8.0], [0.0, 0.0])],
+        PartAnimation::Head,
+    ),
+    part(
+        "body",
+        [0.0, 6.0, 0.0],
+        &[cube([-4.0, 0.0, -2.0], [8.0, 12.0, 4.0], [16.0, 16.0])],
+        PartAnimation::None,
+    ),
+    leg("right_hind_leg", [-2.0, 18.0, 4.0], CREEPER_LEG, 0.0),
+    leg("left_hind_leg", [2.0, 18.0, 4.0], CREEPER_LEG, PI),
+    leg("right_front_leg", [-2.0, 18.0, -4.0], CREEPER_LEG, PI),
+    leg("left_front_leg", [2.0, 18.0, -4.0], CREEPER_LEG, 0.0),
+];
+
+const PIG_LEG: &[Cube] = &[cube([-2.0, 0.0, -2.0], [4.0, 6.0, 4.0], [0.0, 16.0])];
+
+const PIG: &[PartDef] = &[
+    part(
+        "head",
+        [0.0, 12.0, -6.0],
+        &[
+            cube([-4.0, -4.0, -8.0], [8.0, 8.0, 8.0], [0.0, 0.0]),
+            // Snout.
+            cube([-2.0, 0.0, -9.0], [4.0, 3.0, 1.0], [16.0, 16.0]),
+        ],
+        PartAnimation::Head,
+    ),
+    PartDef {
+        name: "body",
+        pivot: [0.0, 11.0, 2.0],
+        rotation: [FRAC_PI_2, 0.0, 0.0],
+        cubes: &[cube([-5.0, -10.0, -7.0], [10.0, 16.0, 8.0], [28.0, 8.0])],
+        animation: PartAnimation::None,
+    },
+    leg("right_hind_leg", [-3.0, 18.0, 7.0], PIG_LEG, 0.0),
+    leg("left_hind_leg", [3.0, 18.0, 7.0], PIG_LEG, PI),
+    leg("right_front_leg", [-3.0, 18.0, -5.0], PIG_LEG, PI),
+    leg("left_front_leg", [3.0, 18.0, -5.0], PIG_LEG, 0.0),
+];
+
+const COW_LEG: &[Cube] = &[cube([-2.0, 0.0, -2.0], [4.0, 12.0, 4.0], [0.0, 16.0])];
+
+const COW: &[PartDef] = &[
+    part(
+        "head",
+        [0.0, 4.0, -8.0],
+        &[
+            cube([-4.0, -4.0, -6.0], [8.0, 8.0, 6.0], [0.0, 0.0]),
+            // Horns.
+            cube([-5.0, -5.0, -4.0], [1.0, 3.0, 1.0], [22.0, 0.0]),
+            cube([4.0, -5.0, -4.0], [1.0, 3.0, 1.0], [22.0, 0.0]),
+        ],
+        PartAnimation::Head,
+    ),
+    PartDef {
+        name: "body",
+        pivot: [0.0, 5.0, 2.0],
+        rotation: [FRAC_PI_2, 0.0, 0.0],
+        cubes: &[
+            cube([-6.0, -10.0, -7.0], [12.0, 18.0, 10.0], [18.0, 4.0]),
+            // Udder.
+            cube([-2.0, 2.0, -8.0], [4.0, 6.0, 1.0], [52.0, 0.0]),
+        ],
+        animation: PartAnimation::None,
+    },
+    leg("right_hind_leg", [-4.0, 12.0, 7.0], COW_LEG, 0.0),
+    leg("left_hind_leg", [4.0, 12.0, 7.0], COW_LEG, PI),
+    leg("right_front_leg", [-4.0, 12.0, -6.0], COW_LEG, PI),
+    leg("left_front_leg", [4.0, 12.0, -6.0], COW_LEG, 0.0),
+];
+
+/// Converts a model-space position, in pixels, to a Bevy-space offset, in
+/// blocks.
+fn to_local(position: Vec3) -> Vec3 {
+    Vec3::new(-position.x, -position.y, position.z) / PIXELS_PER_BLOCK
+}
+
+/// Returns the translation of a part's pivot relative to the entity's feet.
+pub(crate) fn pivot_translation(pivot: [f32; 3]) -> Vec3 {
+    to_local(Vec3::from(pivot) - Vec3::Y * FEET_Y)
+}
+
+/// Converts a model-space rotation (applied Z, then Y, then X, as vanilla
+/// does) to a Bevy rotation.
+pub(crate) fn to_local_rotation(rotation: [f32; 3]) -> Quat {
+    // Model space is Bevy space rotated half a turn around Z, which flips the
+    // direction of rotations around X and Y.
+    let [x, y, z] = rotation;
+    Quat::from_rotation_z(z) * Quat::from_rotation_y(-y) * Quat::from_rotation_x(-x)
+}
+
+/// Builds the mesh for one model part, relative to its pivot.
+///
+/// Texture coordinates are in texture pixels; the material scales them by the
+/// texture size.
+pub(crate) fn build_part_mesh(part: &PartDef) -> Mesh {
+    let mut positions = Vec::with_capacity(part.cubes.len() * 24);
+    let mut normals = Vec::with_capacity(part.cubes.len() * 24);
+    let mut uvs = Vec::with_capacity(part.cubes.len() * 24);
+    let mut indices = Vec::with_capacity(part.cubes.len() * 36);
+
+    for cube in part.cubes {
+        let [w, h, d] = cube.size;
+        let [u, v] = cube.uv;
+        let min = Vec3::from(cube.origin);
+        let max = min + Vec3::from(cube.size);
+        let (x0, x1) = if cube.mirror {
+            (max.x, min.x)
+        } else {
+            (min.x, max.x)
+        };
+
+        let corners = [
+            Vec3::new(x0, min.y, min.z),
+            Vec3::new(x1, min.y, min.z),
+            Vec3::new(x1, max.y, min.z),
+            Vec3::new(x0, max.y, min.z),
+            Vec3::new(x0, min.y, max.z),
+            Vec3::new(x1, min.y, max.z),
+            Vec3::new(x1, max.y, max.z),
+            Vec3::new(x0, max.y, max.z),
+        ];
+
+        let us = [
+            u,
+            u + d,
+            u + d + w,
+            u + d + w + w,
+            u + d + w + d,
+            u + d + w + d + w,
+        ];
+        let vs = [v, v + d, v + d + h];
+
+        // Corners of each face, and its texture rectangle as (u1, v1, u2, v2).
+        let faces = [
+            ([5, 4, 0, 1], [us[1], vs[0], us[2], vs[1]]),
+            ([2, 3, 7, 6], [us[2], vs[1], us[3], vs[0]]),
+            ([0, 4, 7, 3], [us[0], vs[1], us[1], vs[2]]),
+            ([1, 0, 3, 2], [us[1], vs[1], us[2], vs[2]]),
+            ([5, 1, 2, 6], [us[2], vs[1], us[4], vs[2]]),
+            ([4, 5, 6, 7], [us[4], vs[1], us[5], vs[2]]),
+        ];
+
+        for (face, [u1, v1, u2, v2]) in faces {
+            let mut face_positions = face.map(|corner| to_local(corners[corner]));
+            let mut face_uvs = [[u2, v1], [u1, v1], [u1, v2], [u2, v2]];
+            if cube.mirror {
+                face_positions.reverse();
+                face_uvs.reverse();
+            }
+
+            let normal = (face_positions[1] - face_positions[0])
+                .cross(face_positions[2] - face_positions[0])
+                .normalize_or_zero();
+
+            let base = positions.len() as u32;
+            positions.extend(face_positions.map(<[f32; 3]>::from));
+            normals.extend([<[f32; 3]>::from(normal); 4]);
+            uvs.extend(face_uvs);
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pivots_are_relative_to_feet() {
+        assert_eq!(pivot_translation([0.0, 24.0, 0.0]), Vec3::ZERO);
+        assert_eq!(
+            pivot_translation([-5.0, 8.0, 2.0]),
+            Vec3::new(5.0, 16.0, 2.0) / 16.0
+        );
+    }
+
+    #[test]
+    fn head_cube_sits_above_pivot() {
+        let mesh = build_part_mesh(&HUMANOID_HEAD);
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|positions| positions.as_float3())
+            .unwrap();
+
+        assert_eq!(positions.len(), 24);
+        for position in positions {
+            assert!((0.0..=0.5).contains(&position[1]));
+            assert!((-0.25..=0.25).contains(&position[0]));
+        }
+    }
+}
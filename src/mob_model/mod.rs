@@ -0,0 +1,303 @@
+//! Rendering mobs with built-in models.
+//!
+//! Each supported mob (see [`MobKind`]) has hard-coded geometry in the vanilla
+//! model format and uses the vanilla entity texture from the loaded assets.
+//! Models are attached to [`ServerEntity`]s by entity type, animated from
+//! their movement and [`EntityLook`], and tinted red while [`Hurt`].
+
+mod geometry;
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{image::Image, math::Affine2, platform::collections::HashMap, prelude::*};
+use brine_asset::MinecraftAssets;
+use brine_data::{EntityTypeId, MinecraftData};
+use brine_proto::BrineSystems;
+
+use crate::entity::{EntityLook, Hurt, ServerEntity};
+
+pub use geometry::MobKind;
+use geometry::{build_part_mesh, pivot_translation, to_local_rotation, PartAnimation};
+
+/// Ticks per second, which vanilla's animation speeds are expressed in.
+const TICKS_PER_SECOND: f32 = 20.0;
+
+/// How quickly the walk animation blends in and out, per second.
+const WALK_BLEND_RATE: f32 = 8.0;
+
+/// Tint of [`Hurt`] mobs, approximating vanilla's red overlay.
+const HURT_TINT: Color = Color::srgb(1.0, 0.4, 0.4);
+
+/// Walking animation state of a mob, following vanilla's limb swing.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct MobAnimation {
+    /// Distance walked so far, scaled; drives the phase of the limbs.
+    pub limb_swing: f32,
+
+    /// How far the limbs swing, from `0.0` when standing still to `1.0` when
+    /// walking at full speed.
+    pub limb_swing_amount: f32,
+
+    /// Time since the model was attached, in ticks; drives idle animations.
+    pub age: f32,
+
+    last_position: Option<Vec3>,
+}
+
+/// A piece of a mob model, as a child of the mob's entity.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct MobPart {
+    animation: PartAnimation,
+    rest_rotation: [f32; 3],
+}
+
+/// The materials of a mob's parts, as a component of the mob's entity.
+#[derive(Component, Debug, Clone)]
+struct MobMaterials {
+    normal: Handle<StandardMaterial>,
+    hurt: Handle<StandardMaterial>,
+}
+
+#[derive(Debug)]
+struct MobModelAssets {
+    parts: Vec<Handle<Mesh>>,
+    materials: MobMaterials,
+}
+
+#[derive(Resource, Default)]
+struct MobModels {
+    models: HashMap<MobKind, MobModelAssets>,
+
+    /// Materials whose texture hasn't loaded yet, so their texture coordinates
+    /// can't be scaled to it.
+    pending_textures: Vec<(Handle<StandardMaterial>, Handle<Image>)>,
+}
+
+/// Plugin that gives [`ServerEntity`]s of supported mob types a model and
+/// animates it.
+///
+/// Requires the [`MinecraftData`] resource, and waits for [`MinecraftAssets`]
+/// before attaching any models.
+pub struct MobModelPlugin;
+
+impl Plugin for MobModelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                load_mob_models.run_if(resource_added::<MinecraftAssets>),
+                (
+                    attach_models,
+                    scale_texture_coordinates,
+                    update_mob_animations,
+                    animate_mob_parts,
+                    tint_hurt_mobs,
+                )
+                    .chain()
+                    .run_if(resource_exists::<MobModels>),
+            )
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+fn load_mob_models(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut mob_models = MobModels::default();
+
+    for kind in MobKind::ALL {
+        let texture: Handle<Image> =
+            asset_server.load(mc_assets.get_any_texture_path(kind.texture()));
+        let material = StandardMaterial {
+            base_color_texture: Some(texture.clone()),
+            alpha_mode: AlphaMode::Mask(0.5),
+            unlit: true,
+            cull_mode: None,
+            ..default()
+        };
+        let hurt_material = materials.add(StandardMaterial {
+            base_color: HURT_TINT,
+            ..material.clone()
+        });
+        let material = materials.add(material);
+
+        let parts = kind
+            .parts()
+            .iter()
+            .map(|part| meshes.add(build_part_mesh(part)))
+            .collect();
+
+        mob_models
+            .pending_textures
+            .push((material.clone(), texture.clone()));
+        mob_models
+            .pending_textures
+            .push((hurt_material.clone(), texture));
+        mob_models.models.insert(
+            kind,
+            MobModelAssets {
+                parts,
+                materials: MobMaterials {
+                    normal: material,
+                    hurt: hurt_material,
+                },
+            },
+        );
+    }
+
+    commands.insert_resource(mob_models);
+}
+
+/// Entities spawned before the models were loaded count as added the first
+/// time this runs, so they get models too.
+fn attach_models(
+    mut commands: Commands,
+    mc_data: Res<MinecraftData>,
+    mob_models: Res<MobModels>,
+    entities: Query<(Entity, &ServerEntity), Added<ServerEntity>>,
+) {
+    for (entity, server_entity) in entities.iter() {
+        let Some(kind) = u16::try_from(server_entity.entity_type)
+            .ok()
+            .and_then(|id| mc_data.entity_types().get_by_id(EntityTypeId(id)))
+            .and_then(|entity_type| MobKind::from_entity_type_name(entity_type.name))
+        else {
+            continue;
+        };
+        let Some(model) = mob_models.models.get(&kind) else {
+            continue;
+        };
+
+        commands
+            .entity(entity)
+            .insert((MobAnimation::default(), model.materials.clone()))
+            .with_children(|parent| {
+                for (part, mesh) in kind.parts().iter().zip(&model.parts) {
+                    parent.spawn((
+                        Name::new(part.name),
+                        MobPart {
+                            animation: part.animation,
+                            rest_rotation: part.rotation,
+                        },
+                        Mesh3d(mesh.clone()),
+                        MeshMaterial3d(model.materials.normal.clone()),
+                        Transform::from_translation(pivot_translation(part.pivot))
+                            .with_rotation(to_local_rotation(part.rotation)),
+                    ));
+                }
+            });
+    }
+}
+
+/// Model texture coordinates are in pixels; once a texture has loaded, scales
+/// them to its size.
+fn scale_texture_coordinates(
+    mut mob_models: ResMut<MobModels>,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    mob_models.pending_textures.retain(|(material, texture)| {
+        let Some(image) = images.get(texture) else {
+            return true;
+        };
+        if let Some(material) = materials.get_mut(material) {
+            material.uv_transform = Affine2::from_scale(image.size_f32().recip());
+        }
+        false
+    });
+}
+
+fn update_mob_animations(time: Res<Time>, mut mobs: Query<(&Transform, &mut MobAnimation)>) {
+    let delta = time.delta_secs();
+    if delta <= 0.0 {
+        return;
+    }
+
+    for (transform, mut animation) in mobs.iter_mut() {
+        let position = transform.translation;
+        let moved = animation
+            .last_position
+            .map(|last| (position - last).with_y(0.0).length())
+            .unwrap_or(0.0);
+        animation.last_position = Some(position);
+
+        // Vanilla reaches full swing at a quarter of a block per tick.
+        let blocks_per_tick = moved / delta / TICKS_PER_SECOND;
+        let target = (blocks_per_tick * 4.0).min(1.0);
+        let blend = 1.0 - (-WALK_BLEND_RATE * delta).exp();
+
+        animation.limb_swing_amount += (target - animation.limb_swing_amount) * blend;
+        animation.limb_swing += animation.limb_swing_amount * delta * TICKS_PER_SECOND;
+        animation.age += delta * TICKS_PER_SECOND;
+    }
+}
+
+fn animate_mob_parts(
+    mobs: Query<(&MobAnimation, &EntityLook, &Children)>,
+    mut parts: Query<(&MobPart, &mut Transform)>,
+) {
+    for (animation, look, children) in mobs.iter() {
+        let swing = animation.limb_swing * 0.6662;
+        let amount = animation.limb_swing_amount;
+
+        for child in children.iter() {
+            let Ok((part, mut transform)) = parts.get_mut(child) else {
+                continue;
+            };
+
+            let rotation = match part.animation {
+                PartAnimation::None => part.rest_rotation,
+                PartAnimation::Head => [
+                    look.pitch.to_radians(),
+                    (look.head_yaw - look.yaw).to_radians(),
+                    0.0,
+                ],
+                PartAnimation::Leg { phase } => [(swing + phase).cos() * 1.4 * amount, 0.0, 0.0],
+                PartAnimation::Arm {
+                    phase,
+                    side,
+                    raised,
+                } => {
+                    let idle_x = (animation.age * 0.067).sin() * 0.05;
+                    let idle_z = ((animation.age * 0.09).cos() * 0.05 + 0.05) * side;
+
+                    let x = if raised {
+                        -FRAC_PI_2 + idle_x
+                    } else {
+                        (swing + phase).cos() * amount + idle_x
+                    };
+                    [x, 0.0, idle_z]
+                }
+            };
+
+            transform.rotation = to_local_rotation(rotation);
+        }
+    }
+}
+
+fn tint_hurt_mobs(
+    mobs: Query<(&MobMaterials, &Children, Has<Hurt>)>,
+    mut parts: Query<&mut MeshMaterial3d<StandardMaterial>, With<MobPart>>,
+) {
+    for (materials, children, hurt) in mobs.iter() {
+        let material = if hurt {
+            &materials.hurt
+        } else {
+            &materials.normal
+        };
+
+        for child in children.iter() {
+            if let Ok(mut part_material) = parts.get_mut(child) {
+                if part_material.0 != *material {
+                    part_material.0 = material.clone();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Name tags above players and named mobs.
+//!
+//! Players are named after their [`PlayerList`] entry. Other entities show a
+//! name tag when they have a [`CustomName`] that is always shown, as vanilla
+//! does for mobs named with a name tag; vanilla also shows hidden custom
+//! names while the player looks at the entity, which isn't done here.
+//! Anything can still be given a [`WorldText`] directly and gets the same
+//! occlusion handling.
+
+use bevy::prelude::*;
+use brine_proto::{event::clientbound::EntityCustomName, BrineSystems};
+use brine_render::billboard::{WorldText, WorldTextOccluded};
+
+use crate::{
+    debug::Hitbox,
+    entity::{ServerEntities, ServerEntity},
+    player_list::PlayerList,
+    targeting::{raycast_blocks, LoadedBlocks},
+};
+
+/// How far above the top of an entity's hitbox its name tag is, in blocks.
+const NAME_TAG_HEIGHT: f32 = 0.5;
+
+/// Custom name of a [`ServerEntity`], from its metadata.
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq)]
+pub struct CustomName {
+    pub name: Option<String>,
+
+    /// Whether the name is shown at all times.
+    pub visible: bool,
+}
+
+impl CustomName {
+    /// Returns the name if it is shown at all times.
+    pub fn shown_name(&self) -> Option<&str> {
+        self.name.as_deref().filter(|_| self.visible)
+    }
+}
+
+/// Plugin that gives [`ServerEntity`]s on the [`PlayerList`] and those with a
+/// shown [`CustomName`] a [`WorldText`] name tag, and keeps
+/// [`WorldTextOccluded`] up to date for every [`WorldText`] by raycasting
+/// through the loaded chunks.
+///
+/// Requires [`BillboardPlugin`][brine_render::billboard::BillboardPlugin] and
+/// [`FontPlugin`][brine_render::font::FontPlugin] to draw the tags, and
+/// [`PlayerListPlugin`][crate::player_list::PlayerListPlugin].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`EntityCustomName`]
+pub struct NameTagPlugin;
+
+impl Plugin for NameTagPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (set_custom_names, update_name_tags, update_occlusion)
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+fn set_custom_names(
+    mut commands: Commands,
+    mut name_events: MessageReader<EntityCustomName>,
+    server_entities: Res<ServerEntities>,
+    mut custom_names: Query<Option<&mut CustomName>, With<ServerEntity>>,
+) {
+    for event in name_events.read() {
+        let Some(entity) = server_entities.get(event.entity_id) else {
+            continue;
+        };
+        let Ok(custom_name) = custom_names.get_mut(entity) else {
+            continue;
+        };
+
+        let mut updated = custom_name.as_deref().cloned().unwrap_or_default();
+        if let Some(name) = &event.name {
+            updated.name = name.clone();
+        }
+        if let Some(visible) = event.visible {
+            updated.visible = visible;
+        }
+
+        match custom_name {
+            Some(mut custom_name) => {
+                custom_name.set_if_neq(updated);
+            }
+            None => {
+                commands.entity(entity).insert(updated);
+            }
+        }
+    }
+}
+
+fn update_name_tags(
+    mut commands: Commands,
+    player_list: Res<PlayerList>,
+    mut entities: Query<(
+        Entity,
+        &ServerEntity,
+        &Hitbox,
+        Option<&CustomName>,
+        Option<&mut WorldText>,
+    )>,
+) {
+    for (entity, server_entity, hitbox, custom_name, world_text) in entities.iter_mut() {
+        let name = match (player_list.get(&server_entity.uuid), custom_name) {
+            (Some(entry), _) => entry.shown_name(),
+            (None, Some(custom_name)) => match custom_name.shown_name() {
+                Some(name) => name,
+                None => {
+                    if world_text.is_some() {
+                        commands
+                            .entity(entity)
+                            .remove::<(WorldText, WorldTextOccluded)>();
+                    }
+                    continue;
+                }
+            },
+            // Keep the last known name if a player leaves the list but stays
+            // in the world, as vanilla does.
+            (None, None) => continue,
+        };
+
+        match world_text {
+            Some(mut world_text) => {
+                if world_text.text != name {
+                    world_text.text = name.to_string();
+                }
+            }
+            None => {
+                commands.entity(entity).insert((
+                    WorldText::new(name).with_offset(Vec3::Y * (hitbox.height + NAME_TAG_HEIGHT)),
+                    WorldTextOccluded::default(),
+                ));
+            }
+        }
+    }
+}
+
+fn update_occlusion(
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    blocks: LoadedBlocks,
+    mut texts: Query<(&WorldText, &GlobalTransform, &mut WorldTextOccluded)>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let solid_blocks = blocks.solid_blocks();
+    let origin = camera.translation();
+
+    for (world_text, transform, mut occluded) in texts.iter_mut() {
+        let to_text = transform.translation() + world_text.offset - origin;
+        let distance = to_text.length();
+        if distance > world_text.max_distance {
+            continue;
+        }
+
+        let hit = raycast_blocks(origin, to_text, distance, &solid_blocks);
+        occluded.set_if_neq(WorldTextOccluded(hit.is_some()));
+    }
+}
@@ -0,0 +1,419 @@
+//! Collision of moving boxes with the blocks of the world.
+//!
+//! [`move_and_collide`] moves an axis-aligned box by a velocity and stops it
+//! at the blocks in its way, one axis at a time as vanilla does: vertically
+//! first, then along the horizontal axis it moves furthest on, then the other.
+//! Every axis is swept over the whole distance, so fast boxes can't pass
+//! through thin walls. A box that runs into a block while on the ground steps
+//! up onto it if it is no higher than the box's step height.
+//!
+//! This is shared by the player controller and any entities the client
+//! simulates itself. Blocks collide as full cubes unless they have no
+//! collision box at all (e.g. flowers); [`BlockColliders`] looks them up in
+//! the loaded chunks.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use brine_data::{BlockStateId, MinecraftData};
+
+use crate::targeting::LoadedBlocks;
+
+/// How far a box has to reach into a block to count as touching it. Boxes
+/// that land on or stop against a block end up about this close to it after
+/// rounding, and would catch on the seams between blocks without it.
+const EPSILON: f32 = 1e-5;
+
+/// How high players step up, in blocks.
+pub const PLAYER_STEP_HEIGHT: f32 = 0.6;
+
+/// An axis-aligned box, in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns the box of an entity `width` wide and `height` tall whose feet
+    /// are centered on `feet`.
+    pub fn from_feet(feet: Vec3, width: f32, height: f32) -> Self {
+        let half_width = Vec3::new(width / 2.0, 0.0, width / 2.0);
+        Self {
+            min: feet - half_width,
+            max: feet + half_width + Vec3::Y * height,
+        }
+    }
+
+    /// Returns the center of the bottom face.
+    pub fn feet(&self) -> Vec3 {
+        let center = (self.min + self.max) / 2.0;
+        Vec3::new(center.x, self.min.y, center.z)
+    }
+
+    pub fn translate(self, offset: Vec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Returns whether the boxes overlap along `axis` by more than
+    /// [`EPSILON`].
+    fn overlaps_on(&self, other: &Self, axis: usize) -> bool {
+        self.min[axis] < other.max[axis] - EPSILON && self.max[axis] > other.min[axis] + EPSILON
+    }
+}
+
+/// Where a box ended up after [`move_and_collide`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionResult {
+    /// The box, moved.
+    pub aabb: Aabb,
+
+    /// How far the box moved.
+    pub movement: Vec3,
+
+    /// The axes along which blocks cut the movement short.
+    pub collided: BVec3,
+
+    /// Whether the box is standing on a block, and wasn't moving up.
+    pub on_ground: bool,
+
+    /// Whether the box stepped up onto a block.
+    pub stepped_up: bool,
+}
+
+/// The blocks in the loaded chunks that boxes collide with.
+#[derive(SystemParam)]
+pub struct BlockColliders<'w, 's> {
+    blocks: LoadedBlocks<'w, 's>,
+    mc_data: Res<'w, MinecraftData>,
+}
+
+impl BlockColliders<'_, '_> {
+    /// Moves `aabb` by `velocity` through the loaded chunks. See
+    /// [`move_and_collide`].
+    pub fn move_and_collide(
+        &self,
+        aabb: Aabb,
+        velocity: Vec3,
+        step_height: f32,
+    ) -> CollisionResult {
+        move_and_collide(aabb, velocity, step_height, self.solid_blocks())
+    }
+
+    /// Returns a lookup of whether the block at a world position collides,
+    /// suitable for [`move_and_collide`]. Unloaded blocks don't.
+    pub fn solid_blocks(&self) -> impl Fn(IVec3) -> bool + '_ {
+        let blocks = self.blocks.solid_blocks();
+        move |position| {
+            blocks(position).is_some_and(|block_state| {
+                self.mc_data
+                    .blocks()
+                    .get_by_state_id(BlockStateId(block_state.0 as u16))
+                    .is_some_and(|block| !block.empty)
+            })
+        }
+    }
+}
+
+/// Moves `aabb` by `velocity`, stopping it at the blocks for which `is_solid`
+/// returns `true`. On the ground, the box steps up onto blocks up to
+/// `step_height` high that are in its way.
+///
+/// Boxes that already overlap a block can move out of it freely.
+pub fn move_and_collide(
+    aabb: Aabb,
+    velocity: Vec3,
+    step_height: f32,
+    mut is_solid: impl FnMut(IVec3) -> bool,
+) -> CollisionResult {
+    let blocked = |movement: Vec3| {
+        BVec3::new(
+            (movement.x - velocity.x).abs() > EPSILON,
+            (movement.y - velocity.y).abs() > EPSILON,
+            (movement.z - velocity.z).abs() > EPSILON,
+        )
+    };
+
+    let mut movement = sweep(aabb, velocity, &mut is_solid);
+    let mut collided = blocked(movement);
+    let mut stepped_up = false;
+
+    let landed = collided.y && velocity.y < 0.0;
+    if step_height > 0.0 && (collided.x || collided.z) && (landed || on_ground(aabb, &mut is_solid))
+    {
+        // Go up as far as the step height allows, across, and back down.
+        let up = clip(aabb, 1, step_height, &mut is_solid);
+        let raised = aabb.translate(Vec3::Y * up);
+        let mut step = sweep(
+            raised,
+            Vec3::new(velocity.x, 0.0, velocity.z),
+            &mut is_solid,
+        );
+        step.y = up + clip(raised.translate(step), 1, -up, &mut is_solid);
+
+        let horizontal = |movement: Vec3| Vec2::new(movement.x, movement.z).length_squared();
+        if horizontal(step) > horizontal(movement) + EPSILON {
+            movement = step;
+            collided = blocked(step);
+            stepped_up = true;
+        }
+    }
+
+    let moved = aabb.translate(movement);
+    CollisionResult {
+        aabb: moved,
+        movement,
+        collided,
+        on_ground: velocity.y <= 0.0 && on_ground(moved, &mut is_solid),
+        stepped_up,
+    }
+}
+
+/// Moves `aabb` along each axis in turn, vertically first, and returns how
+/// far it got.
+fn sweep(aabb: Aabb, velocity: Vec3, is_solid: &mut impl FnMut(IVec3) -> bool) -> Vec3 {
+    let axes = if velocity.x.abs() < velocity.z.abs() {
+        [1, 2, 0]
+    } else {
+        [1, 0, 2]
+    };
+
+    let mut movement = Vec3::ZERO;
+    for axis in axes {
+        movement[axis] = clip(aabb.translate(movement), axis, velocity[axis], is_solid);
+    }
+    movement
+}
+
+/// Returns whether there is a block right under `aabb`.
+pub fn on_ground(aabb: Aabb, mut is_solid: impl FnMut(IVec3) -> bool) -> bool {
+    const PROBE: f32 = 1e-3;
+    clip(aabb, 1, -PROBE, &mut is_solid) > -PROBE
+}
+
+/// Returns whether `aabb` fits where it is, without overlapping any block for
+/// which `is_solid` returns `true`. Touching a block doesn't count.
+pub fn fits(aabb: Aabb, mut is_solid: impl FnMut(IVec3) -> bool) -> bool {
+    let min = (aabb.min + EPSILON).floor().as_ivec3();
+    let max = (aabb.max - EPSILON).floor().as_ivec3();
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                if is_solid(IVec3::new(x, y, z)) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Returns how far `aabb` can move along `axis`, up to `distance`, before it
+/// runs into a solid block. It never moves backwards.
+fn clip(aabb: Aabb, axis: usize, distance: f32, is_solid: &mut impl FnMut(IVec3) -> bool) -> f32 {
+    if distance == 0.0 {
+        return 0.0;
+    }
+
+    let mut swept = aabb;
+    if distance > 0.0 {
+        swept.max[axis] += distance;
+    } else {
+        swept.min[axis] += distance;
+    }
+
+    let others = [(axis + 1) % 3, (axis + 2) % 3];
+    let min = swept.min.floor().as_ivec3();
+    let max = swept.max.floor().as_ivec3();
+
+    let mut distance = distance;
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                let position = IVec3::new(x, y, z);
+                let block = Aabb::new(position.as_vec3(), position.as_vec3() + Vec3::ONE);
+                if !others.iter().all(|&other| aabb.overlaps_on(&block, other)) {
+                    continue;
+                }
+
+                if distance > 0.0 && aabb.max[axis] <= block.min[axis] + EPSILON {
+                    if is_solid(position) {
+                        distance = distance.min((block.min[axis] - aabb.max[axis]).max(0.0));
+                    }
+                } else if distance < 0.0 && aabb.min[axis] >= block.max[axis] - EPSILON {
+                    if is_solid(position) {
+                        distance = distance.max((block.max[axis] - aabb.min[axis]).min(0.0));
+                    }
+                }
+            }
+        }
+    }
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::platform::collections::HashSet;
+
+    use super::*;
+
+    const PLAYER_WIDTH: f32 = 0.6;
+    const PLAYER_HEIGHT: f32 = 1.8;
+
+    /// A 9x9 floor whose top is at y = 1, plus `extra` blocks.
+    fn world(extra: &[IVec3]) -> HashSet<IVec3> {
+        let mut blocks: HashSet<_> = (-4..=4)
+            .flat_map(|x| (-4..=4).map(move |z| IVec3::new(x, 0, z)))
+            .collect();
+        blocks.extend(extra);
+        blocks
+    }
+
+    fn player_at(feet: Vec3) -> Aabb {
+        Aabb::from_feet(feet, PLAYER_WIDTH, PLAYER_HEIGHT)
+    }
+
+    fn assert_near(actual: Vec3, expected: Vec3) {
+        assert!(
+            (actual - expected).abs().max_element() < 1e-4,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn falling_box_lands_on_the_floor() {
+        let blocks = world(&[]);
+        let result = move_and_collide(
+            player_at(Vec3::new(0.5, 1.37, 0.5)),
+            Vec3::new(0.0, -0.49, 0.0),
+            PLAYER_STEP_HEIGHT,
+            |position| blocks.contains(&position),
+        );
+
+        assert_near(result.aabb.feet(), Vec3::new(0.5, 1.0, 0.5));
+        assert_eq!(result.collided, BVec3::new(false, true, false));
+        assert!(result.on_ground);
+        assert!(!result.stepped_up);
+    }
+
+    #[test]
+    fn box_slides_across_block_seams() {
+        // Land first, so the box rests on the floor with rounding error.
+        let blocks = world(&[IVec3::new(-4, 1, 1), IVec3::new(-3, 1, 1)]);
+        let is_solid = |position| blocks.contains(&position);
+        let landed = move_and_collide(
+            player_at(Vec3::new(-3.3, 1.37, 0.6999)),
+            Vec3::new(0.0, -0.49, 0.0),
+            PLAYER_STEP_HEIGHT,
+            is_solid,
+        );
+
+        // Along the floor and the wall beside it, over several seams.
+        let result = move_and_collide(
+            landed.aabb,
+            Vec3::new(2.7, -0.08, 0.0),
+            PLAYER_STEP_HEIGHT,
+            is_solid,
+        );
+
+        assert_near(result.movement, Vec3::new(2.7, 0.0, 0.0));
+        assert_eq!(result.collided, BVec3::new(false, true, false));
+        assert!(result.on_ground);
+    }
+
+    #[test]
+    fn fast_box_stops_at_thin_walls() {
+        let blocks = world(&[IVec3::new(3, 1, 0), IVec3::new(3, 2, 0)]);
+        let result = move_and_collide(
+            player_at(Vec3::new(0.5, 1.0, 0.5)),
+            Vec3::new(20.0, 0.0, 0.0),
+            0.0,
+            |position| blocks.contains(&position),
+        );
+
+        assert_near(result.aabb.max, Vec3::new(3.0, 2.8, 0.8));
+        assert!(result.collided.x);
+    }
+
+    #[test]
+    fn box_stops_in_corners() {
+        let blocks = world(&[IVec3::new(1, 1, 0), IVec3::new(0, 1, 1)]);
+        let result = move_and_collide(
+            player_at(Vec3::new(0.5, 1.0, 0.5)),
+            Vec3::new(0.5, 0.0, 0.5),
+            0.0,
+            |position| blocks.contains(&position),
+        );
+
+        assert_near(result.movement, Vec3::new(0.2, 0.0, 0.2));
+        assert_eq!(result.collided, BVec3::new(true, false, true));
+    }
+
+    #[test]
+    fn box_steps_up_blocks_up_to_its_step_height() {
+        let blocks = world(&[IVec3::new(1, 1, 0)]);
+        let is_solid = |position| blocks.contains(&position);
+        let start = player_at(Vec3::new(0.5, 1.0, 0.5));
+        let velocity = Vec3::new(0.5, -0.08, 0.0);
+
+        // Players can't step onto full blocks.
+        let result = move_and_collide(start, velocity, PLAYER_STEP_HEIGHT, is_solid);
+        assert_near(result.movement, Vec3::new(0.2, 0.0, 0.0));
+        assert!(!result.stepped_up);
+
+        let result = move_and_collide(start, velocity, 1.0, is_solid);
+        assert_near(result.aabb.feet(), Vec3::new(1.0, 2.0, 0.5));
+        assert!(result.stepped_up);
+        assert!(result.on_ground);
+    }
+
+    #[test]
+    fn box_does_not_step_up_under_a_low_ceiling() {
+        let blocks = world(&[
+            IVec3::new(1, 1, 0),
+            IVec3::new(0, 3, 0),
+            IVec3::new(1, 3, 0),
+        ]);
+        let result = move_and_collide(
+            player_at(Vec3::new(0.5, 1.0, 0.5)),
+            Vec3::new(0.5, 0.0, 0.0),
+            1.0,
+            |position| blocks.contains(&position),
+        );
+
+        assert_near(result.movement, Vec3::new(0.2, 0.0, 0.0));
+        assert!(!result.stepped_up);
+    }
+
+    #[test]
+    fn box_inside_a_block_can_move_out() {
+        let blocks = world(&[IVec3::new(0, 1, 0)]);
+        let result = move_and_collide(
+            player_at(Vec3::new(0.5, 1.0, 0.5)),
+            Vec3::new(1.0, 0.0, 0.0),
+            0.0,
+            |position| blocks.contains(&position),
+        );
+
+        assert_near(result.movement, Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn boxes_fit_against_blocks_but_not_into_them() {
+        let blocks = world(&[IVec3::new(0, 2, 0)]);
+        let is_solid = |position| blocks.contains(&position);
+        let feet = Vec3::new(0.5, 1.0, 0.5);
+
+        assert!(fits(Aabb::from_feet(feet, PLAYER_WIDTH, 1.0), is_solid));
+        assert!(!fits(player_at(feet), is_solid));
+        assert!(on_ground(player_at(feet), is_solid));
+        assert!(!on_ground(player_at(feet + Vec3::Y * 0.1), is_solid));
+    }
+}
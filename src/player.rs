@@ -0,0 +1,236 @@
+//! The local player's position, and the debug freecam that leaves it behind.
+//!
+//! There is no player controller yet: the [`FlyCam`] camera is flown around
+//! and the [`LocalPlayer`] follows it. Anything that needs to know where the
+//! player is (reporting the position to the server, loading chunks around the
+//! player) should read the [`LocalPlayer`]'s [`Transform`] rather than the
+//! camera's, so that it keeps working while the camera is detached.
+//!
+//! When the server moves the player ([`PlayerTeleported`]), e.g. on joining
+//! or respawning, both the player and the camera are put there, unless the
+//! camera is detached.
+//!
+//! Pressing the [`DebugModifier`][InputAction::DebugModifier] together with
+//! [`ToggleFreecam`][InputAction::ToggleFreecam] detaches the camera, which can
+//! then fly anywhere (e.g., to look at chunk loading or culling from outside)
+//! while the player stays where it was. Toggling again puts the camera back at
+//! the player's eyes.
+
+use std::f32::consts::PI;
+
+use bevy::{color::palettes::css, prelude::*};
+use bevy_flycam::FlyCam;
+use brine_proto::{event::clientbound::PlayerTeleported, BrineSystems};
+
+use crate::{
+    input::{InputAction, InputMap},
+    pose::{Pose, PLAYER_WIDTH},
+};
+
+/// Height of the local player's eyes above its feet while standing. Other
+/// poses have their own (see [`Pose::eye_height`]).
+pub const PLAYER_EYE_HEIGHT: f32 = 1.62;
+
+/// Marks the local player's entity.
+///
+/// Its [`Transform`] is at the player's feet, rotated the way the player is
+/// looking.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct LocalPlayer;
+
+/// Whether the camera is detached from the [`LocalPlayer`].
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Freecam {
+    pub enable: bool,
+}
+
+/// Plugin that spawns the [`LocalPlayer`] and keeps it at the camera unless
+/// the camera is detached.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`PlayerTeleported`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Freecam`]
+///
+/// # Ordering
+///
+/// Teleports are applied in [`BrineSystems::WorldUpdate`]. The player follows
+/// the camera in [`PostUpdate`], after everything in [`Update`] has moved the
+/// camera.
+pub struct LocalPlayerPlugin;
+
+impl Plugin for LocalPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Freecam>()
+            .add_systems(Startup, spawn_local_player)
+            .add_systems(
+                Update,
+                (
+                    toggle_freecam,
+                    draw_detached_player,
+                    teleport_player.in_set(BrineSystems::WorldUpdate),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                follow_camera.before(TransformSystems::Propagate),
+            );
+    }
+}
+
+fn spawn_local_player(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Local Player"),
+        LocalPlayer,
+        Pose::default(),
+        Transform::default(),
+    ));
+}
+
+fn toggle_freecam(
+    input: InputMap,
+    mut freecam: ResMut<Freecam>,
+    players: Query<(&Transform, &Pose), With<LocalPlayer>>,
+    mut cameras: Query<&mut Transform, (With<FlyCam>, Without<LocalPlayer>)>,
+) {
+    if !input.pressed(InputAction::DebugModifier) || !input.just_pressed(InputAction::ToggleFreecam)
+    {
+        return;
+    }
+
+    freecam.enable = !freecam.enable;
+    info!("Freecam: {}", if freecam.enable { "on" } else { "off" });
+
+    if !freecam.enable {
+        // Snap back to where the player is, rather than moving the player to
+        // wherever the camera roamed.
+        let Ok((player, pose)) = players.single() else {
+            return;
+        };
+        for mut camera in cameras.iter_mut() {
+            *camera = eye_transform(player, *pose);
+        }
+    }
+}
+
+pub(crate) fn follow_camera(
+    freecam: Res<Freecam>,
+    cameras: Query<&Transform, With<FlyCam>>,
+    mut players: Query<(&mut Transform, &Pose), (With<LocalPlayer>, Without<FlyCam>)>,
+) {
+    if freecam.enable {
+        return;
+    }
+
+    let (Ok(camera), Ok((mut player, pose))) = (cameras.single(), players.single_mut()) else {
+        return;
+    };
+
+    let feet = Transform {
+        translation: camera.translation - Vec3::Y * pose.eye_height(),
+        ..*camera
+    };
+    player.set_if_neq(feet);
+}
+
+fn teleport_player(
+    mut teleported_events: MessageReader<PlayerTeleported>,
+    freecam: Res<Freecam>,
+    mut players: Query<(&mut Transform, &Pose), With<LocalPlayer>>,
+    mut cameras: Query<&mut Transform, (With<FlyCam>, Without<LocalPlayer>)>,
+) {
+    let Some(teleported) = teleported_events.read().last() else {
+        return;
+    };
+    let Ok((mut player, pose)) = players.single_mut() else {
+        return;
+    };
+
+    *player = Transform::from_translation(teleported.position.as_vec3())
+        .with_rotation(look_rotation(teleported.yaw, teleported.pitch));
+
+    // A detached camera stays where it is, and snaps to the player's new
+    // position when it's attached again.
+    if !freecam.enable {
+        for mut camera in cameras.iter_mut() {
+            *camera = eye_transform(&player, *pose);
+        }
+    }
+}
+
+/// Draws the player's outline and view direction while the camera is away.
+fn draw_detached_player(
+    freecam: Res<Freecam>,
+    players: Query<(&Transform, &Pose), With<LocalPlayer>>,
+    mut gizmos: Gizmos,
+) {
+    if !freecam.enable {
+        return;
+    }
+
+    for (player, pose) in players.iter() {
+        let center = player.translation + Vec3::Y * pose.height() / 2.0;
+        let size = Vec3::new(PLAYER_WIDTH, pose.height(), PLAYER_WIDTH);
+        gizmos.cuboid(
+            Transform::from_translation(center).with_scale(size),
+            css::AQUA,
+        );
+
+        let eye = eye_transform(player, *pose);
+        gizmos.ray(eye.translation, *eye.forward() * 2.0, css::AQUA);
+    }
+}
+
+/// Returns a look direction as Minecraft's yaw and pitch, in degrees.
+///
+/// A yaw of 0 faces south (+Z) and increases clockwise when viewed from above;
+/// a positive pitch looks down.
+pub fn look_angles(forward: Vec3) -> (f32, f32) {
+    let yaw = (-forward.x).atan2(forward.z).to_degrees();
+    let pitch = (-forward.y).asin().to_degrees();
+    (yaw, pitch)
+}
+
+/// Returns the rotation that turns the Bevy forward direction (-Z) to look
+/// in the direction given by Minecraft's yaw and pitch, in degrees; the
+/// inverse of [`look_angles`].
+pub fn look_rotation(yaw: f32, pitch: f32) -> Quat {
+    Quat::from_euler(
+        EulerRot::YXZ,
+        PI - yaw.to_radians(),
+        -pitch.to_radians(),
+        0.0,
+    )
+}
+
+fn eye_transform(player: &Transform, pose: Pose) -> Transform {
+    Transform {
+        translation: player.translation + Vec3::Y * pose.eye_height(),
+        ..*player
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn look_rotation_inverts_look_angles() {
+        for (yaw, pitch) in [(0.0, 0.0), (90.0, 30.0), (-135.0, -60.0)] {
+            let forward = look_rotation(yaw, pitch) * Vec3::NEG_Z;
+            let (actual_yaw, actual_pitch) = look_angles(forward);
+            assert!((actual_yaw - yaw).abs() < 1e-3, "{actual_yaw} != {yaw}");
+            assert!(
+                (actual_pitch - pitch).abs() < 1e-3,
+                "{actual_pitch} != {pitch}"
+            );
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! The players the server lists as online.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use brine_proto::{
+    event::{
+        clientbound::{
+            PlayerAdded, PlayerDisplayName, PlayerLatency, PlayersRemoved, Reconfigure, Transfer,
+        },
+        Uuid,
+    },
+    BrineSystems,
+};
+
+/// A player on the player list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerListEntry {
+    /// The name of the player's profile.
+    pub name: String,
+
+    /// Name the server wants shown instead of [`name`](Self::name), as plain
+    /// text.
+    pub display_name: Option<String>,
+
+    /// The player's latency as measured by the server, in milliseconds, once
+    /// the server has sent it.
+    pub latency_ms: Option<i32>,
+}
+
+impl PlayerListEntry {
+    /// Returns the name to show for the player.
+    pub fn shown_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// The players currently on the player list, by UUID.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct PlayerList(HashMap<Uuid, PlayerListEntry>);
+
+impl PlayerList {
+    pub fn get(&self, uuid: &Uuid) -> Option<&PlayerListEntry> {
+        self.0.get(uuid)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Uuid, &PlayerListEntry)> {
+        self.0.iter()
+    }
+}
+
+/// Plugin that keeps the [`PlayerList`] up to date.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`PlayerAdded`]
+/// * [`PlayerDisplayName`]
+/// * [`PlayerLatency`]
+/// * [`PlayersRemoved`]
+/// * [`Transfer`] and [`Reconfigure`], which clear the list
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`PlayerList`]
+pub struct PlayerListPlugin;
+
+impl Plugin for PlayerListPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerList>().add_systems(
+            Update,
+            (reset_player_list, handle_player_list_events)
+                .chain()
+                .in_set(BrineSystems::WorldUpdate),
+        );
+    }
+}
+
+fn reset_player_list(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut player_list: ResMut<PlayerList>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        player_list.0.clear();
+    }
+}
+
+fn handle_player_list_events(
+    mut added_events: MessageReader<PlayerAdded>,
+    mut display_name_events: MessageReader<PlayerDisplayName>,
+    mut latency_events: MessageReader<PlayerLatency>,
+    mut removed_events: MessageReader<PlayersRemoved>,
+    mut player_list: ResMut<PlayerList>,
+) {
+    for added in added_events.read() {
+        player_list.0.insert(
+            added.uuid,
+            PlayerListEntry {
+                name: added.name.clone(),
+                display_name: None,
+                latency_ms: None,
+            },
+        );
+    }
+
+    // Display names and latencies arrive in the same packet as the player
+    // they belong to, so they are applied after additions.
+    for event in display_name_events.read() {
+        if let Some(entry) = player_list.0.get_mut(&event.uuid) {
+            entry.display_name.clone_from(&event.display_name);
+        }
+    }
+    for event in latency_events.read() {
+        if let Some(entry) = player_list.0.get_mut(&event.uuid) {
+            entry.latency_ms = Some(event.latency_ms);
+        }
+    }
+
+    for removed in removed_events.read() {
+        for uuid in &removed.uuids {
+            player_list.0.remove(uuid);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! The local player's pose: standing, crouching, swimming or gliding.
+//!
+//! Holding [`Descend`][InputAction::Descend] sneaks, which crouches the player
+//! unless they are flying. Holding [`Sprint`][InputAction::Sprint] while
+//! moving forward sprints until the player stops moving forward, and
+//! sprinting with the eyes under water swims until the player leaves the
+//! water. Pressing [`Ascend`][InputAction::Ascend] in mid-air while wearing an
+//! elytra glides until the player lands or enters water.
+//!
+//! Each [`Pose`] has its own bounding box and eye height. When the pose
+//! changes, the camera moves to the new eye height and the feet stay where
+//! they are. As in vanilla, a player with no room to stand up stays crouched,
+//! or swimming if there's no room to crouch either.
+//!
+//! The server is told when the player starts or stops sneaking or sprinting
+//! ([`SetSneaking`], [`SetSprinting`]) and when they start gliding
+//! ([`StartGliding`]), which is how it knows the pose to show other players.
+//! It works out swimming from sprinting, and ends glides itself.
+
+use bevy::prelude::*;
+use bevy_flycam::FlyCam;
+use brine_data::{BlockStateId, ItemId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{InventoryContents, InventorySlot, JoinedGame, Reconfigure, Transfer},
+        serverbound::{SetSneaking, SetSprinting, StartGliding},
+        GameMode, ItemStack,
+    },
+    BrineSystems,
+};
+
+use crate::{
+    game_mode::{PlayerAbilities, PlayerGameMode},
+    health::PlayerHealth,
+    input::{InputAction, InputMap},
+    physics::{self, Aabb, BlockColliders},
+    player::{Freecam, LocalPlayer, PLAYER_EYE_HEIGHT},
+    targeting::LoadedBlocks,
+    vehicle::Riding,
+};
+
+/// Width of the local player's bounding box, in every pose.
+pub const PLAYER_WIDTH: f32 = 0.6;
+
+/// Player inventory slot of the chestplate, where the elytra is worn.
+const CHEST_INVENTORY_SLOT: usize = 6;
+
+/// Players can't start sprinting with this much food or less, unless they
+/// may fly.
+const SPRINT_MIN_FOOD: i32 = 6;
+
+/// How the local player holds themselves, which decides the size of their
+/// bounding box and how high their eyes are.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Pose {
+    #[default]
+    Standing,
+    Crouching,
+    Swimming,
+    Gliding,
+}
+
+impl Pose {
+    /// Returns the height of the bounding box.
+    pub fn height(self) -> f32 {
+        match self {
+            Self::Standing => 1.8,
+            Self::Crouching => 1.5,
+            Self::Swimming | Self::Gliding => 0.6,
+        }
+    }
+
+    /// Returns the height of the eyes above the feet.
+    pub fn eye_height(self) -> f32 {
+        match self {
+            Self::Standing => PLAYER_EYE_HEIGHT,
+            Self::Crouching => 1.27,
+            Self::Swimming | Self::Gliding => 0.4,
+        }
+    }
+
+    /// Returns the bounding box of a player in this pose whose feet are at
+    /// `feet`.
+    pub fn aabb(self, feet: Vec3) -> Aabb {
+        Aabb::from_feet(feet, PLAYER_WIDTH, self.height())
+    }
+}
+
+/// What the local player is doing that affects their pose.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerStance {
+    pub sneaking: bool,
+    pub sprinting: bool,
+    pub swimming: bool,
+    pub gliding: bool,
+}
+
+impl PlayerStance {
+    /// Returns the pose the stance calls for, if there is room for it.
+    pub fn pose(&self, flying: bool) -> Pose {
+        if self.gliding {
+            Pose::Gliding
+        } else if self.swimming {
+            Pose::Swimming
+        } else if self.sneaking && !flying {
+            Pose::Crouching
+        } else {
+            Pose::Standing
+        }
+    }
+}
+
+/// The item worn in the chest slot, which may be an elytra.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ChestItem(Option<ItemStack>);
+
+/// Plugin that keeps track of the [`Pose`] of the [`LocalPlayer`] and tells
+/// the server what it is doing.
+///
+/// Requires the [`MinecraftData`] resource.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`InventoryContents`] and [`InventorySlot`], for the elytra
+/// * [`JoinedGame`], [`Transfer`] and [`Reconfigure`], which make the player
+///   stand
+///
+/// The plugin sends the following events:
+///
+/// * [`SetSneaking`]
+/// * [`SetSprinting`]
+/// * [`StartGliding`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`PlayerStance`]
+///
+/// # Ordering
+///
+/// The stance and pose are updated in [`BrineSystems::WorldUpdate`], after
+/// the [`GameModePlugin`][crate::game_mode::GameModePlugin] has applied the
+/// abilities the server sent this frame.
+pub struct PosePlugin;
+
+impl Plugin for PosePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerStance>()
+            .init_resource::<ChestItem>()
+            .add_systems(
+                Update,
+                (reset_stance, track_chest_item, update_stance, update_pose)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate)
+                    .after(crate::game_mode::handle_game_mode_events),
+            );
+    }
+}
+
+fn reset_stance(
+    mut joined_events: MessageReader<JoinedGame>,
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut stance: ResMut<PlayerStance>,
+    mut chest: ResMut<ChestItem>,
+) {
+    let joined = joined_events.read().count() > 0;
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if joined || transferred || reconfigured {
+        *stance = PlayerStance::default();
+        if transferred || reconfigured {
+            *chest = ChestItem::default();
+        }
+    }
+}
+
+fn track_chest_item(
+    mut contents_events: MessageReader<InventoryContents>,
+    mut slot_events: MessageReader<InventorySlot>,
+    mut chest: ResMut<ChestItem>,
+) {
+    for contents in contents_events.read() {
+        if let Some(item) = contents.items.get(CHEST_INVENTORY_SLOT) {
+            chest.0 = *item;
+        }
+    }
+
+    for event in slot_events.read() {
+        if usize::from(event.slot) == CHEST_INVENTORY_SLOT {
+            chest.0 = event.item;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_stance(
+    input: InputMap,
+    game_mode: Res<PlayerGameMode>,
+    abilities: Res<PlayerAbilities>,
+    health: Res<PlayerHealth>,
+    freecam: Res<Freecam>,
+    riding: Res<Riding>,
+    chest: Res<ChestItem>,
+    mc_data: Res<MinecraftData>,
+    colliders: BlockColliders,
+    loaded: LoadedBlocks,
+    players: Query<(&Transform, &Pose), With<LocalPlayer>>,
+    mut stance: ResMut<PlayerStance>,
+    mut sneaking_events: MessageWriter<SetSneaking>,
+    mut sprinting_events: MessageWriter<SetSprinting>,
+    mut gliding_events: MessageWriter<StartGliding>,
+) {
+    let Ok((player, pose)) = players.single() else {
+        return;
+    };
+
+    let blocks = loaded.solid_blocks();
+    let is_water = |position: Vec3| {
+        blocks(position.floor().as_ivec3()).is_some_and(|block_state| {
+            mc_data
+                .blocks()
+                .get_by_state_id(BlockStateId(block_state.0 as u16))
+                .is_some_and(|block| block.name == "water")
+        })
+    };
+    let feet = player.translation;
+    let in_water = is_water(feet);
+    let under_water = is_water(feet + Vec3::Y * pose.eye_height());
+    let on_ground = physics::on_ground(pose.aabb(feet), colliders.solid_blocks());
+    let wearing_elytra = chest
+        .0
+        .and_then(|item| u16::try_from(item.item_id).ok())
+        .and_then(|item_id| mc_data.items().get_by_id(ItemId(item_id)))
+        .is_some_and(|item| item.name == "elytra");
+
+    // Spectators, riders and players left behind by the freecam keep still.
+    let controlled =
+        **game_mode != GameMode::Spectator && riding.vehicle_id.is_none() && !freecam.enable;
+
+    let mut next = *stance;
+    next.sneaking = controlled && input.pressed(InputAction::Descend);
+
+    let may_sprint = controlled
+        && input.pressed(InputAction::MoveForward)
+        && (health.food > SPRINT_MIN_FOOD || abilities.may_fly)
+        && (!next.sneaking || stance.swimming);
+    next.sprinting = may_sprint && (stance.sprinting || input.pressed(InputAction::Sprint));
+
+    // Swimming starts under water and goes on until out of it.
+    let wet = if stance.swimming {
+        in_water
+    } else {
+        under_water
+    };
+    next.swimming = next.sprinting && wet;
+
+    let may_glide = controlled && wearing_elytra && !on_ground && !in_water && !abilities.flying;
+    next.gliding = may_glide && (stance.gliding || input.just_pressed(InputAction::Ascend));
+
+    if next.sneaking != stance.sneaking {
+        sneaking_events.write(SetSneaking {
+            sneaking: next.sneaking,
+        });
+    }
+    if next.sprinting != stance.sprinting {
+        sprinting_events.write(SetSprinting {
+            sprinting: next.sprinting,
+        });
+    }
+    if next.gliding && !stance.gliding {
+        gliding_events.write(StartGliding);
+    }
+    stance.set_if_neq(next);
+}
+
+/// Puts the player in the pose their stance calls for, or the closest one
+/// there is room for, and moves the camera to the new eye height.
+fn update_pose(
+    stance: Res<PlayerStance>,
+    game_mode: Res<PlayerGameMode>,
+    abilities: Res<PlayerAbilities>,
+    freecam: Res<Freecam>,
+    colliders: BlockColliders,
+    mut players: Query<(&Transform, &mut Pose), With<LocalPlayer>>,
+    mut cameras: Query<&mut Transform, (With<FlyCam>, Without<LocalPlayer>)>,
+) {
+    let Ok((player, mut pose)) = players.single_mut() else {
+        return;
+    };
+
+    let wanted = stance.pose(abilities.flying);
+    let next = if **game_mode == GameMode::Spectator {
+        // Spectators go through blocks.
+        wanted
+    } else {
+        let is_solid = colliders.solid_blocks();
+        [wanted, Pose::Crouching, Pose::Swimming]
+            .into_iter()
+            .find(|pose| physics::fits(pose.aabb(player.translation), &is_solid))
+            .unwrap_or(wanted)
+    };
+    if *pose == next {
+        return;
+    }
+
+    debug!("Pose: {:?}", next);
+    if !freecam.enable {
+        for mut camera in cameras.iter_mut() {
+            camera.translation.y += next.eye_height() - pose.eye_height();
+        }
+    }
+    *pose = next;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flying_players_do_not_crouch() {
+        let sneaking = PlayerStance {
+            sneaking: true,
+            ..default()
+        };
+        assert_eq!(sneaking.pose(false), Pose::Crouching);
+        assert_eq!(sneaking.pose(true), Pose::Standing);
+
+        let gliding = PlayerStance {
+            gliding: true,
+            swimming: true,
+            ..sneaking
+        };
+        assert_eq!(gliding.pose(false), Pose::Gliding);
+    }
+}
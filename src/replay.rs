@@ -0,0 +1,208 @@
+//! Headless replay of a recorded session, for regression testing.
+//!
+//! A session recorded with `brine --record <capture>` is served back by a
+//! [`ReplayServer`] while the client logs in as usual, so the replay goes
+//! through the same codec, backend and plugins as the live session did. Once
+//! everything has been replayed, the state the client ended up in is
+//! summarized in a [`ReplaySummary`] that can be compared with one from an
+//! earlier run.
+
+use std::fmt;
+
+use bevy::{app::AppExit, math::DVec3, platform::collections::HashSet, prelude::*};
+use brine_net::{capture::ReplayServer, NetworkEvent};
+use brine_proto::{
+    event::clientbound::{
+        ChunkData, Disconnect, DisconnectKind, PlayerTeleported, Reconfigure, Transfer,
+    },
+    BrineSystems,
+};
+use brine_proto_backend::ProtocolCodec;
+
+use crate::entity::ServerEntities;
+
+/// Updates to keep running after the last connection closes, so that packets
+/// still queued make it through the pipeline.
+const SETTLE_UPDATES: u32 = 5;
+
+/// Plugin that follows a replayed session and exits once it is over.
+///
+/// Requires a [`ProtocolBackendPlugin`][brine_proto_backend::ProtocolBackendPlugin]
+/// connecting to the [`ReplayServer`], and an
+/// [`EntityPlugin`][crate::entity::EntityPlugin] to count entities.
+///
+/// The app exits a few updates after the server has sent everything and the
+/// connection has closed, or as soon as the client is disconnected.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ChunkData`]
+/// * [`PlayerTeleported`]
+/// * [`Disconnect`]
+/// * [`Transfer`] and [`Reconfigure`], which forget every chunk
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`ReplaySummary`]
+///
+/// # Ordering
+///
+/// The summary is updated in [`BrineSystems::WorldUpdate`], like the rest of
+/// the world.
+pub struct ReplayPlugin {
+    server: ReplayServer,
+}
+
+impl ReplayPlugin {
+    pub fn new(server: ReplayServer) -> Self {
+        Self { server }
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Replay {
+            server: self.server.clone(),
+            settle_updates: None,
+        })
+        .init_resource::<ReplaySummary>()
+        .add_systems(
+            Update,
+            (
+                forget_chunks_on_reset,
+                count_chunks,
+                track_player_position,
+                count_entities,
+                finish_replay,
+            )
+                .chain()
+                .in_set(BrineSystems::WorldUpdate),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct Replay {
+    server: ReplayServer,
+
+    /// Updates left before exiting, once the replay is over.
+    settle_updates: Option<u32>,
+}
+
+/// The state the client is in at the end of a replay.
+///
+/// Its [`Display`][fmt::Display] output is meant to be stored as a golden file
+/// and diffed against later replays of the same capture.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct ReplaySummary {
+    /// Columns of every chunk received since the last reset.
+    pub chunks: HashSet<IVec2>,
+
+    /// Entities the server has told the client about.
+    pub entities: usize,
+
+    /// Where the server last put the player.
+    pub player_position: Option<DVec3>,
+
+    /// Why the client was disconnected, if the server disconnected it.
+    pub disconnect: Option<String>,
+}
+
+impl fmt::Display for ReplaySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "chunks: {}", self.chunks.len())?;
+        writeln!(f, "entities: {}", self.entities)?;
+        match self.player_position {
+            Some(position) => writeln!(
+                f,
+                "player_position: {:.3} {:.3} {:.3}",
+                position.x, position.y, position.z
+            )?,
+            None => writeln!(f, "player_position: none")?,
+        }
+        if let Some(reason) = &self.disconnect {
+            writeln!(f, "disconnect: {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+fn forget_chunks_on_reset(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut summary: ResMut<ReplaySummary>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        summary.chunks.clear();
+    }
+}
+
+fn count_chunks(mut chunk_events: MessageReader<ChunkData>, mut summary: ResMut<ReplaySummary>) {
+    for chunk in chunk_events.read() {
+        let chunk = &chunk.chunk_data;
+        summary
+            .chunks
+            .insert(IVec2::new(chunk.chunk_x, chunk.chunk_z));
+    }
+}
+
+fn track_player_position(
+    mut teleported_events: MessageReader<PlayerTeleported>,
+    mut summary: ResMut<ReplaySummary>,
+) {
+    if let Some(teleported) = teleported_events.read().last() {
+        summary.player_position = Some(teleported.position);
+    }
+}
+
+fn count_entities(server_entities: Res<ServerEntities>, mut summary: ResMut<ReplaySummary>) {
+    summary.entities = server_entities.len();
+}
+
+fn finish_replay(
+    mut network_events: MessageReader<NetworkEvent<ProtocolCodec>>,
+    mut disconnect_events: MessageReader<Disconnect>,
+    mut replay: ResMut<Replay>,
+    mut summary: ResMut<ReplaySummary>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    // Running out of capture closes the connection, which the backend reports
+    // as a lost connection; that isn't part of the recorded session.
+    let recorded_disconnect = disconnect_events
+        .read()
+        .filter(|disconnect| {
+            disconnect.kind != DisconnectKind::Network || !replay.server.is_finished()
+        })
+        .last();
+    if let Some(disconnect) = recorded_disconnect {
+        summary.disconnect = Some(disconnect.reason.clone());
+        app_exit.write(AppExit::Success);
+        return;
+    }
+
+    // The status ping's connection closes too, but always before the server
+    // has run out of connections to replay.
+    let closed = network_events
+        .read()
+        .filter(|event| matches!(event, NetworkEvent::Disconnected))
+        .count()
+        > 0;
+    if closed && replay.server.is_finished() && replay.settle_updates.is_none() {
+        replay.settle_updates = Some(SETTLE_UPDATES);
+    }
+
+    if let Some(updates) = &mut replay.settle_updates {
+        if *updates == 0 {
+            app_exit.write(AppExit::Success);
+        } else {
+            *updates -= 1;
+        }
+    }
+}
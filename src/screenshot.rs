@@ -0,0 +1,162 @@
+//! Capturing screenshots of the primary window.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::{save_to_disk, Screenshot},
+};
+
+use crate::input::{InputAction, InputMap};
+
+/// Default directory (relative to the working directory) that screenshots are
+/// saved to.
+pub const DEFAULT_SCREENSHOT_DIR: &str = "screenshots";
+
+/// Requests a screenshot of the primary window.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Message)]
+pub struct TakeScreenshot {
+    /// Path of the PNG file to write. If `None`, a timestamped file is created
+    /// in the plugin's screenshot directory.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Resource)]
+struct ScreenshotDirectory(PathBuf);
+
+/// Plugin that captures the primary window to a PNG file when the screenshot key
+/// (F2 by default) is pressed or when a [`TakeScreenshot`] event is sent.
+///
+/// # Events
+///
+/// The plugin registers the following events:
+///
+/// * [`TakeScreenshot`]
+pub struct ScreenshotCapturePlugin {
+    directory: PathBuf,
+}
+
+impl ScreenshotCapturePlugin {
+    /// Saves timestamped screenshots to `directory` instead of the default
+    /// [`DEFAULT_SCREENSHOT_DIR`].
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+impl Default for ScreenshotCapturePlugin {
+    fn default() -> Self {
+        Self::new(DEFAULT_SCREENSHOT_DIR)
+    }
+}
+
+impl Plugin for ScreenshotCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<TakeScreenshot>()
+            .insert_resource(ScreenshotDirectory(self.directory.clone()))
+            .add_systems(Update, (screenshot_on_key_press, take_screenshots).chain());
+    }
+}
+
+fn screenshot_on_key_press(input: InputMap, mut screenshot_events: MessageWriter<TakeScreenshot>) {
+    if input.just_pressed(InputAction::Screenshot) {
+        screenshot_events.write(TakeScreenshot::default());
+    }
+}
+
+fn take_screenshots(
+    mut screenshot_events: MessageReader<TakeScreenshot>,
+    directory: Res<ScreenshotDirectory>,
+    mut commands: Commands,
+) {
+    for event in screenshot_events.read() {
+        let path = match event.path.clone() {
+            Some(path) => path,
+            None => match next_screenshot_path(&directory.0) {
+                Ok(path) => path,
+                Err(err) => {
+                    error!(
+                        "Failed to create screenshot directory {}: {}",
+                        directory.0.display(),
+                        err
+                    );
+                    continue;
+                }
+            },
+        };
+
+        debug!("Capturing screenshot to {}", path.display());
+
+        commands
+            .spawn(Screenshot::primary_window())
+            .observe(save_to_disk(path));
+    }
+}
+
+/// Returns an unused path of the form `<dir>/YYYY-MM-DD_HH.MM.SS.png`, creating
+/// the directory if needed.
+fn next_screenshot_path(directory: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(directory)?;
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let timestamp = format_timestamp(seconds);
+
+    let mut path = directory.join(format!("{timestamp}.png"));
+    let mut suffix = 1;
+    while path.exists() {
+        path = directory.join(format!("{timestamp}_{suffix}.png"));
+        suffix += 1;
+    }
+
+    Ok(path)
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD_HH.MM.SS` UTC
+/// timestamp.
+fn format_timestamp(unix_seconds: u64) -> String {
+    let days = (unix_seconds / 86_400) as i64;
+    let seconds_of_day = unix_seconds % 86_400;
+
+    // Convert days since the epoch to a civil date. See
+    // <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}.{:02}.{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        (seconds_of_day / 60) % 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_formatting() {
+        assert_eq!(format_timestamp(0), "1970-01-01_00.00.00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29_00.00.00");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14_22.13.20");
+    }
+}
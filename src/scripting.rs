@@ -0,0 +1,362 @@
+//! Driving a headless bot with a [Rhai](https://rhai.rs) script.
+//!
+//! Needs the `scripting` cargo feature. The script's top-level statements run
+//! once when the app starts; after that, the plugin calls the functions it
+//! defines when something happens:
+//!
+//! * `on_chat(text)` for each chat message, as plain text
+//! * `on_chunk_loaded(x, z)` for each chunk the server sends, in chunk
+//!   coordinates
+//! * `on_health_changed(health, food)` whenever the server sends the
+//!   player's health, in half hearts, and food level
+//!
+//! Handlers the script doesn't define are skipped. They can keep state between
+//! calls in `this`, an object map shared by all of them. They act on the
+//! world with these functions:
+//!
+//! * `chat(text)` sends a chat message
+//! * `move_to(x, y, z)` walks the player in a straight line to a position,
+//!   replacing any earlier target. There is no physics or path finding, so
+//!   the way must be clear.
+//! * `disconnect()` leaves the server and exits the app
+//!
+//! ```text
+//! fn on_chat(text) {
+//!     if text.ends_with("come here") {
+//!         move_to(0, 64, 0);
+//!     } else if text.ends_with("bye") {
+//!         disconnect();
+//!     }
+//! }
+//!
+//! fn on_chunk_loaded(x, z) {
+//!     this.chunks = (this.chunks ?? 0) + 1;
+//! }
+//! ```
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{app::AppExit, math::DVec3, prelude::*};
+use brine_proto::{
+    event::{
+        clientbound::{ChatMessage, ChunkData, HealthUpdated, PlayerTeleported},
+        serverbound::{MovePlayer, SendChatMessage},
+    },
+    BrineSystems, TICKS_PER_SECOND,
+};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+
+/// How fast `move_to` walks, in blocks per second (vanilla's walking speed).
+const WALK_SPEED: f64 = 4.317;
+
+/// A compiled script.
+#[derive(Debug, Clone)]
+pub struct Script {
+    ast: AST,
+}
+
+impl Script {
+    /// Reads and compiles the script at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<EvalAltResult>> {
+        let ast = Engine::new().compile_file(path.as_ref().to_path_buf())?;
+        Ok(Self { ast })
+    }
+}
+
+/// Something a script asked for.
+#[derive(Debug, Clone, PartialEq)]
+enum ScriptAction {
+    Chat(String),
+    MoveTo(DVec3),
+    Disconnect,
+}
+
+/// The running script, and the actions it asked for since they were last
+/// carried out.
+#[derive(Resource)]
+struct ScriptRuntime {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+
+    /// The script's `this`.
+    state: Dynamic,
+
+    actions: Arc<Mutex<Vec<ScriptAction>>>,
+}
+
+impl ScriptRuntime {
+    fn new(script: &Script) -> Self {
+        let actions = Arc::<Mutex<Vec<ScriptAction>>>::default();
+        let mut engine = Engine::new();
+
+        let push = |actions: &Arc<Mutex<Vec<ScriptAction>>>, action| {
+            actions.lock().unwrap().push(action);
+        };
+        let queue = actions.clone();
+        engine.register_fn("chat", move |text: &str| {
+            push(&queue, ScriptAction::Chat(text.to_string()));
+        });
+        let queue = actions.clone();
+        engine.register_fn("move_to", move |x: f64, y: f64, z: f64| {
+            push(&queue, ScriptAction::MoveTo(DVec3::new(x, y, z)));
+        });
+        let queue = actions.clone();
+        engine.register_fn("move_to", move |x: i64, y: i64, z: i64| {
+            let target = DVec3::new(x as f64, y as f64, z as f64);
+            push(&queue, ScriptAction::MoveTo(target));
+        });
+        let queue = actions.clone();
+        engine.register_fn("disconnect", move || {
+            push(&queue, ScriptAction::Disconnect);
+        });
+        engine.on_print(|text| info!("[script] {}", text));
+        engine.on_debug(|text, _, _| debug!("[script] {}", text));
+
+        Self {
+            engine,
+            ast: script.ast.clone(),
+            scope: Scope::new(),
+            state: Map::new().into(),
+            actions,
+        }
+    }
+
+    /// Runs the script's top-level statements.
+    fn run(&mut self) {
+        if let Err(err) = self.engine.run_ast_with_scope(&mut self.scope, &self.ast) {
+            error!("Script failed: {}", err);
+        }
+    }
+
+    /// Calls the script's `name` handler, if it has one.
+    fn call(&mut self, name: &str, args: Vec<Dynamic>) {
+        let defined = self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == args.len());
+        if !defined {
+            return;
+        }
+
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            name,
+            args,
+        );
+        if let Err(err) = result {
+            error!("Script handler {} failed: {}", name, err);
+        }
+    }
+
+    fn take_actions(&self) -> Vec<ScriptAction> {
+        std::mem::take(&mut *self.actions.lock().unwrap())
+    }
+}
+
+/// Where the player is and where a script sent them.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+struct ScriptedMove {
+    /// Where the player is, once the server has put them somewhere.
+    position: Option<DVec3>,
+    pitch: f32,
+
+    target: Option<DVec3>,
+}
+
+/// Plugin that runs a [`Script`] against the headless client.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ChatMessage`]
+/// * [`ChunkData`]
+/// * [`HealthUpdated`]
+/// * [`PlayerTeleported`], which also stops walking to the `move_to` target
+///
+/// The plugin sends the following events:
+///
+/// * [`SendChatMessage`]
+/// * [`MovePlayer`]
+/// * [`AppExit`]
+///
+/// # Ordering
+///
+/// Walking to a `move_to` target advances once per game tick, in
+/// [`FixedUpdate`].
+pub struct ScriptingPlugin {
+    script: Script,
+}
+
+impl ScriptingPlugin {
+    pub fn new(script: Script) -> Self {
+        Self { script }
+    }
+}
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ScriptRuntime::new(&self.script))
+            .init_resource::<ScriptedMove>()
+            .add_systems(Startup, run_script)
+            .add_systems(FixedUpdate, walk_to_target)
+            .add_systems(
+                Update,
+                (call_handlers, carry_out_actions)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn run_script(mut runtime: ResMut<ScriptRuntime>) {
+    runtime.run();
+}
+
+fn call_handlers(
+    mut chat_events: MessageReader<ChatMessage>,
+    mut chunk_events: MessageReader<ChunkData>,
+    mut health_events: MessageReader<HealthUpdated>,
+    mut runtime: ResMut<ScriptRuntime>,
+) {
+    for chat in chat_events.read() {
+        runtime.call("on_chat", vec![chat.text.clone().into()]);
+    }
+    for chunk in chunk_events.read() {
+        let chunk = &chunk.chunk_data;
+        runtime.call(
+            "on_chunk_loaded",
+            vec![
+                i64::from(chunk.chunk_x).into(),
+                i64::from(chunk.chunk_z).into(),
+            ],
+        );
+    }
+    for update in health_events.read() {
+        runtime.call(
+            "on_health_changed",
+            vec![
+                f64::from(update.health).into(),
+                i64::from(update.food).into(),
+            ],
+        );
+    }
+}
+
+fn carry_out_actions(
+    runtime: Res<ScriptRuntime>,
+    mut teleported_events: MessageReader<PlayerTeleported>,
+    mut scripted_move: ResMut<ScriptedMove>,
+    mut chat_events: MessageWriter<SendChatMessage>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    // The server moving the player means the walk went wrong.
+    for teleported in teleported_events.read() {
+        scripted_move.position = Some(teleported.position);
+        scripted_move.pitch = teleported.pitch;
+        scripted_move.target = None;
+    }
+
+    for action in runtime.take_actions() {
+        match action {
+            ScriptAction::Chat(message) => {
+                chat_events.write(SendChatMessage { message });
+            }
+            ScriptAction::MoveTo(target) => scripted_move.target = Some(target),
+            ScriptAction::Disconnect => {
+                info!("Script disconnected");
+                app_exit.write(AppExit::Success);
+            }
+        }
+    }
+}
+
+/// Returns the yaw facing along `direction`, in degrees (0 faces +Z,
+/// increasing clockwise when viewed from above).
+fn yaw_towards(direction: DVec3) -> f32 {
+    (-direction.x).atan2(direction.z).to_degrees() as f32
+}
+
+/// Returns where a tick of walking from `position` to `target` ends.
+fn step_towards(position: DVec3, target: DVec3) -> DVec3 {
+    position.move_towards(target, WALK_SPEED / TICKS_PER_SECOND)
+}
+
+fn walk_to_target(
+    mut scripted_move: ResMut<ScriptedMove>,
+    mut move_events: MessageWriter<MovePlayer>,
+) {
+    let (Some(position), Some(target)) = (scripted_move.position, scripted_move.target) else {
+        return;
+    };
+
+    let next = step_towards(position, target);
+    move_events.write(MovePlayer {
+        position: next,
+        yaw: yaw_towards(target - position),
+        pitch: scripted_move.pitch,
+        on_ground: true,
+    });
+
+    scripted_move.position = Some(next);
+    if next == target {
+        scripted_move.target = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_actions_are_queued() {
+        let script = Script {
+            ast: Engine::new()
+                .compile(
+                    "fn on_chat(text) { this.seen = (this.seen ?? 0) + 1; chat(text); } \
+                     fn on_chunk_loaded(x, z) { move_to(x * 16, 64, z * 16); disconnect(); }",
+                )
+                .unwrap(),
+        };
+        let mut runtime = ScriptRuntime::new(&script);
+        runtime.run();
+
+        runtime.call("on_chat", vec!["hi".into()]);
+        runtime.call("on_chat", vec!["again".into()]);
+        runtime.call("on_chunk_loaded", vec![1_i64.into(), (-2_i64).into()]);
+        runtime.call("on_health_changed", vec![20.0_f64.into(), 20_i64.into()]);
+
+        assert_eq!(
+            runtime.take_actions(),
+            [
+                ScriptAction::Chat("hi".to_string()),
+                ScriptAction::Chat("again".to_string()),
+                ScriptAction::MoveTo(DVec3::new(16.0, 64.0, -32.0)),
+                ScriptAction::Disconnect,
+            ]
+        );
+        let state = runtime.state.read_lock::<Map>().unwrap();
+        assert_eq!(state["seen"].as_int(), Ok(2));
+    }
+
+    #[test]
+    fn walking_faces_and_stops_at_the_target() {
+        assert_eq!(yaw_towards(DVec3::Z), 0.0);
+        assert!((yaw_towards(DVec3::NEG_X) - 90.0).abs() < 1e-4);
+
+        let target = DVec3::new(0.1, 64.0, 0.0);
+        assert_eq!(step_towards(DVec3::new(0.0, 64.0, 0.0), target), target);
+
+        let step = step_towards(DVec3::ZERO, DVec3::new(10.0, 0.0, 0.0));
+        assert!((step.x - WALK_SPEED / TICKS_PER_SECOND).abs() < 1e-9);
+    }
+}
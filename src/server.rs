@@ -1,29 +1,64 @@
 use std::{
     any::Any,
+    collections::VecDeque,
     fs,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use bevy::{
+    platform::collections::HashSet,
     prelude::*,
     tasks::{IoTaskPool, Task},
 };
 
 use brine_chunk::Chunk;
-use brine_proto::event::clientbound::ChunkData;
+use brine_proto::{event::clientbound::ChunkData, BrineSystems, ClientError, ClientErrorKind};
 use futures_lite::future;
 
-use crate::chunk::{load_chunk, Result};
+use crate::chunk::{load_chunk, Error, Result};
+
+/// How often a watched chunk directory is checked for new files.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How [`ServeChunksFromDirectoryPlugin`] sends its chunks.
+///
+/// By default every chunk is sent as soon as it's loaded, as if the server
+/// had sent the whole world at once.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChunkServing {
+    /// Send the chunks nearest this chunk position first, like a server does
+    /// around the player. Files that aren't named `chunk_{X}_{Z}.dump` go
+    /// last.
+    pub center: Option<IVec2>,
+
+    /// Send at most this many chunks per second.
+    pub chunks_per_second: Option<f32>,
+
+    /// Keep checking the directory for new chunk files, and send those too.
+    pub watch: bool,
+}
 
 /// A plugin that acts as a phony server, sending ChunkData events containing
 /// data read from a directory of chunk data files.
 pub struct ServeChunksFromDirectoryPlugin<P> {
     path: P,
+    serving: ChunkServing,
 }
 
 impl<P> ServeChunksFromDirectoryPlugin<P> {
     pub fn new(path: P) -> Self {
-        Self { path }
+        Self {
+            path,
+            serving: ChunkServing::default(),
+        }
+    }
+
+    /// Sends the chunks in the order, at the rate and for as long as
+    /// `serving` says.
+    pub fn with_serving(mut self, serving: ChunkServing) -> Self {
+        self.serving = serving;
+        self
     }
 }
 
@@ -33,22 +68,114 @@ where
 {
     fn build(&self, app: &mut App) {
         let path = PathBuf::from(self.path.as_ref());
-        app.insert_resource(ChunkDirectory { path });
-        app.add_systems(Startup, load_chunks);
-        app.add_systems(Update, send_chunks);
+        app.insert_resource(ChunkDirectory {
+            path,
+            serving: self.serving,
+        });
+        app.init_resource::<ChunkQueue>();
+        app.add_systems(Startup, scan_directory);
+        app.add_systems(
+            Update,
+            (
+                scan_directory.run_if(watch_interval_elapsed),
+                start_loading_chunks,
+                send_chunks,
+            )
+                .chain()
+                .in_set(BrineSystems::ProtocolDecode),
+        );
     }
 }
 
 #[derive(Resource, Debug)]
 pub struct ChunkDirectory {
     path: PathBuf,
+    serving: ChunkServing,
+}
+
+/// Chunk files found in the directory, and which of them are still to be
+/// loaded.
+#[derive(Resource, Debug, Default)]
+struct ChunkQueue {
+    known: HashSet<PathBuf>,
+    /// In the order to load them in.
+    pending: VecDeque<PathBuf>,
+    /// How many chunks may be loaded before the rate limit kicks in.
+    allowance: f32,
+}
+
+impl ChunkQueue {
+    /// Queues the files not seen before, keeping the queue sorted by distance
+    /// from `center`, if any.
+    fn add(&mut self, paths: impl IntoIterator<Item = PathBuf>, center: Option<IVec2>) {
+        let mut new_paths: Vec<_> = paths
+            .into_iter()
+            .filter(|path| self.known.insert(path.clone()))
+            .collect();
+        if new_paths.is_empty() {
+            return;
+        }
+
+        new_paths.sort();
+        self.pending.extend(new_paths);
+
+        if let Some(center) = center {
+            // Stable, so files at the same distance stay in name order.
+            self.pending
+                .make_contiguous()
+                .sort_by_key(|path| match chunk_position(path) {
+                    Some(position) => (position - center).length_squared(),
+                    None => i32::MAX,
+                });
+        }
+    }
+
+    /// Removes and returns the files to load now, given that `elapsed` has
+    /// passed since the last call.
+    fn take(&mut self, chunks_per_second: Option<f32>, elapsed: Duration) -> Vec<PathBuf> {
+        let count = match chunks_per_second {
+            Some(rate) => {
+                // Don't save up more than a second's worth while idle.
+                self.allowance = (self.allowance + rate * elapsed.as_secs_f32()).min(rate.max(1.0));
+                let count = (self.allowance as usize).min(self.pending.len());
+                self.allowance -= count as f32;
+                count
+            }
+            None => self.pending.len(),
+        };
+
+        self.pending.drain(..count).collect()
+    }
+}
+
+/// Returns the chunk position in a file name like `chunk_{X}_{Z}.dump`.
+fn chunk_position(path: &Path) -> Option<IVec2> {
+    let name = path.file_stem()?.to_str()?;
+    let (x, z) = name.strip_prefix("chunk_")?.split_once('_')?;
+    Some(IVec2::new(x.parse().ok()?, z.parse().ok()?))
 }
 
 #[derive(Component)]
 struct LoadChunkTask(Task<Result<Chunk>>);
 
-fn load_chunks(chunk_directory: Res<ChunkDirectory>, mut commands: Commands) {
-    let task_pool = IoTaskPool::get();
+fn watch_interval_elapsed(
+    chunk_directory: Res<ChunkDirectory>,
+    time: Res<Time<Real>>,
+    mut since_scan: Local<Duration>,
+) -> bool {
+    if !chunk_directory.serving.watch {
+        return false;
+    }
+
+    *since_scan += time.delta();
+    if *since_scan < WATCH_INTERVAL {
+        return false;
+    }
+    *since_scan = Duration::ZERO;
+    true
+}
+
+fn scan_directory(chunk_directory: Res<ChunkDirectory>, mut queue: ResMut<ChunkQueue>) {
     let entries = match fs::read_dir(&chunk_directory.path) {
         Ok(entries) => entries,
         Err(err) => {
@@ -57,6 +184,7 @@ fn load_chunks(chunk_directory: Res<ChunkDirectory>, mut commands: Commands) {
         }
     };
 
+    let mut paths = Vec::new();
     for entry in entries {
         let entry = match entry {
             Ok(entry) => entry,
@@ -72,10 +200,23 @@ fn load_chunks(chunk_directory: Res<ChunkDirectory>, mut commands: Commands) {
             continue;
         }
 
-        let path = entry.path();
+        paths.push(entry.path());
+    }
+
+    queue.add(paths, chunk_directory.serving.center);
+}
+
+fn start_loading_chunks(
+    chunk_directory: Res<ChunkDirectory>,
+    time: Res<Time<Real>>,
+    mut queue: ResMut<ChunkQueue>,
+    mut commands: Commands,
+) {
+    let task_pool = IoTaskPool::get();
+
+    for path in queue.take(chunk_directory.serving.chunks_per_second, time.delta()) {
         let chunk_name = path.to_string_lossy().to_string();
-        let task_path = path.clone();
-        let task = task_pool.spawn(async move { load_chunk(task_path) });
+        let task = task_pool.spawn(async move { load_chunk(path) });
 
         commands.spawn((
             LoadChunkTask(task),
@@ -87,6 +228,7 @@ fn load_chunks(chunk_directory: Res<ChunkDirectory>, mut commands: Commands) {
 fn send_chunks(
     mut tasks: Query<(Entity, &mut LoadChunkTask)>,
     mut chunk_events: MessageWriter<ChunkData>,
+    mut client_errors: MessageWriter<ClientError>,
     mut commands: Commands,
 ) {
     for (task_entity, mut task) in tasks.iter_mut() {
@@ -98,9 +240,73 @@ fn send_chunks(
                 }
                 Err(err) => {
                     error!("{}", err);
+                    if let Error::Chunk {
+                        chunk_x, chunk_z, ..
+                    } = err
+                    {
+                        client_errors.write(ClientError::new(
+                            ClientErrorKind::Chunk { chunk_x, chunk_z },
+                            err.to_string(),
+                        ));
+                    }
                     commands.entity(task_entity).despawn();
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn chunks_are_queued_nearest_first() {
+        let mut queue = ChunkQueue::default();
+        queue.add(
+            paths(&[
+                "chunk_5_5.dump",
+                "other.dump",
+                "chunk_0_1.dump",
+                "chunk_-1_0.dump",
+            ]),
+            Some(IVec2::ZERO),
+        );
+        // Already known files aren't queued again.
+        queue.add(
+            paths(&["chunk_0_0.dump", "chunk_5_5.dump"]),
+            Some(IVec2::ZERO),
+        );
+
+        assert_eq!(
+            queue.take(None, Duration::ZERO),
+            paths(&[
+                "chunk_0_0.dump",
+                "chunk_-1_0.dump",
+                "chunk_0_1.dump",
+                "chunk_5_5.dump",
+                "other.dump",
+            ])
+        );
+    }
+
+    #[test]
+    fn rate_limit_spreads_chunks_over_time() {
+        let mut queue = ChunkQueue::default();
+        queue.add(
+            (0..10).map(|x| PathBuf::from(format!("chunk_{x}_0.dump"))),
+            None,
+        );
+
+        let rate = Some(4.0);
+        assert_eq!(queue.take(rate, Duration::from_millis(100)).len(), 0);
+        assert_eq!(queue.take(rate, Duration::from_millis(200)).len(), 1);
+        // A long pause only allows a second's worth.
+        assert_eq!(queue.take(rate, Duration::from_secs(10)).len(), 4);
+        assert_eq!(queue.pending.len(), 5);
+    }
+}
@@ -0,0 +1,169 @@
+//! Persistent user settings.
+//!
+//! Settings are stored as JSON in a single file (by default
+//! [`DEFAULT_SETTINGS_PATH`]). Each section of the file is exposed to the app
+//! as its own resource, and the file is rewritten whenever one of those
+//! resources changes.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use brine_proto_backend::packet_log::PacketLogConfig;
+use brine_voxel_v1::waving::WavingSettings;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::log_error,
+    gamepad::ControllerSettings,
+    graphics::GraphicsSettings,
+    input::{GamepadBindings, KeyBindings},
+};
+
+/// Default location of the settings file, relative to the working directory.
+pub const DEFAULT_SETTINGS_PATH: &str = "settings.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Contents of the settings file.
+///
+/// Missing sections and fields take their default values, so older settings
+/// files keep working as new options are added.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub controls: KeyBindings,
+    pub gamepad_controls: GamepadBindings,
+    pub gamepad: ControllerSettings,
+    pub packet_log: PacketLogConfig,
+    pub waving: WavingSettings,
+    pub graphics: GraphicsSettings,
+}
+
+impl Settings {
+    /// Loads settings from a file, returning the defaults if it doesn't exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        match fs::File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(io::BufReader::new(file))?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes settings to a file, creating parent directories as needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        serde_json::to_writer_pretty(fs::File::create(path)?, self)?;
+        Ok(())
+    }
+}
+
+/// Path that the settings were loaded from and will be saved to.
+#[derive(Debug, Clone, Resource)]
+pub struct SettingsPath(pub PathBuf);
+
+/// Plugin that loads the settings file on startup and saves it when settings
+/// change.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`SettingsPath`]
+/// * [`KeyBindings`]
+/// * [`GamepadBindings`]
+/// * [`ControllerSettings`]
+/// * [`PacketLogConfig`]
+/// * [`WavingSettings`]
+/// * [`GraphicsSettings`]
+pub struct SettingsPlugin {
+    path: PathBuf,
+}
+
+impl SettingsPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Default for SettingsPlugin {
+    fn default() -> Self {
+        Self::new(DEFAULT_SETTINGS_PATH)
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load(&self.path).unwrap_or_else(|err| {
+            warn!(
+                "Failed to load settings from {}, using defaults: {}",
+                self.path.display(),
+                err
+            );
+            Settings::default()
+        });
+
+        app.insert_resource(SettingsPath(self.path.clone()))
+            .insert_resource(settings.controls)
+            .insert_resource(settings.gamepad_controls)
+            .insert_resource(settings.gamepad)
+            .insert_resource(settings.packet_log)
+            .insert_resource(settings.waving)
+            .insert_resource(settings.graphics)
+            .add_systems(Last, save_settings_on_change.pipe(log_error));
+    }
+}
+
+fn save_settings_on_change(
+    path: Res<SettingsPath>,
+    controls: Res<KeyBindings>,
+    gamepad_controls: Res<GamepadBindings>,
+    gamepad: Res<ControllerSettings>,
+    packet_log: Res<PacketLogConfig>,
+    waving: Res<WavingSettings>,
+    graphics: Res<GraphicsSettings>,
+) -> Result<()> {
+    if !modified(&controls)
+        && !modified(&gamepad_controls)
+        && !modified(&gamepad)
+        && !modified(&packet_log)
+        && !modified(&waving)
+        && !modified(&graphics)
+    {
+        return Ok(());
+    }
+
+    let settings = Settings {
+        controls: controls.clone(),
+        gamepad_controls: gamepad_controls.clone(),
+        gamepad: gamepad.clone(),
+        packet_log: packet_log.clone(),
+        waving: waving.clone(),
+        graphics: graphics.clone(),
+    };
+
+    debug!("Saving settings to {}", path.0.display());
+    settings.save(&path.0)
+}
+
+/// Returns whether a resource changed after it was first inserted.
+fn modified(resource: &impl DetectChanges) -> bool {
+    resource.is_changed() && !resource.is_added()
+}
@@ -0,0 +1,360 @@
+//! Block sounds: breaking, placing, hitting and walking on blocks.
+//!
+//! Which sounds a block makes comes from its [`SoundGroup`] in `brine_data`.
+//! Sound groups name sound events (e.g. `block.stone.step`), which the
+//! `sounds.json` of the loaded assets maps to `.ogg` files under
+//! `assets/minecraft/sounds`. Vanilla ships those outside the client jar, so
+//! without them (or a resource pack providing them) blocks stay silent.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{audio::Volume, platform::collections::HashMap, prelude::*};
+use brine_asset::MinecraftAssets;
+use brine_data::{blocks::SoundGroup, BlockStateId, MinecraftData};
+use serde::Deserialize;
+
+use crate::{error::log_error, targeting::LoadedBlocks};
+
+/// Height of the player's eyes above their feet, in blocks.
+const EYE_HEIGHT: f32 = 1.62;
+
+/// Horizontal distance between footsteps, in blocks.
+const STEP_DISTANCE: f32 = 1.7;
+
+/// Moving further than this in one frame is a teleport rather than walking.
+const MAX_STEP_MOVEMENT: f32 = 4.0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to read {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("failed to parse {}: {source}", path.display())]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// What happened to a block to make it play a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockSoundKind {
+    Break,
+    Step,
+    Place,
+    /// Hit while being mined.
+    Hit,
+    /// Landed on after a fall.
+    Fall,
+}
+
+impl BlockSoundKind {
+    /// Returns the sound event, volume and pitch for this kind of sound in
+    /// the given group, scaled the way vanilla scales them.
+    pub fn sound(self, group: &SoundGroup) -> (&'static str, f32, f32) {
+        match self {
+            Self::Break => (
+                group.break_sound,
+                (group.volume + 1.0) / 2.0,
+                group.pitch * 0.8,
+            ),
+            Self::Step => (group.step_sound, group.volume * 0.15, group.pitch),
+            Self::Place => (
+                group.place_sound,
+                (group.volume + 1.0) / 2.0,
+                group.pitch * 0.8,
+            ),
+            Self::Hit => (
+                group.hit_sound,
+                (group.volume + 1.0) / 8.0,
+                group.pitch * 0.5,
+            ),
+            Self::Fall => (group.fall_sound, group.volume * 0.5, group.pitch * 0.75),
+        }
+    }
+}
+
+/// Message that plays the sound of something happening to a block, such as
+/// the player breaking or placing it.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSound {
+    pub block_state: BlockStateId,
+    pub kind: BlockSoundKind,
+}
+
+/// One of the sound files a sound event picks from.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SoundVariant {
+    /// Path of the file under `assets/minecraft/sounds`, without `.ogg`.
+    pub name: String,
+
+    #[serde(default = "one")]
+    pub volume: f32,
+
+    #[serde(default = "one")]
+    pub pitch: f32,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// The sound events of the loaded assets, by name.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct SoundEvents {
+    events: HashMap<String, Vec<SoundVariant>>,
+
+    /// Root of the sound files, relative to Bevy's asset directory.
+    sounds_path: PathBuf,
+}
+
+impl SoundEvents {
+    /// Loads the `assets/minecraft/sounds.json` under an asset root.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join("assets/minecraft/sounds.json");
+        let sounds_path = root
+            .join("assets/minecraft/sounds")
+            .strip_prefix("assets")
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let json = match fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    sounds_path,
+                    ..default()
+                })
+            }
+            Err(source) => return Err(Error::Io { path, source }),
+        };
+
+        let events = parse_sounds_json(&json).map_err(|source| Error::Json { path, source })?;
+
+        Ok(Self {
+            events,
+            sounds_path,
+        })
+    }
+
+    pub fn get(&self, event: &str) -> Option<&[SoundVariant]> {
+        self.events.get(event).map(Vec::as_slice)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the Bevy asset path of a variant's sound file.
+    pub fn file_path(&self, variant: &SoundVariant) -> PathBuf {
+        self.sounds_path.join(format!("{}.ogg", variant.name))
+    }
+}
+
+/// An entry of a sound event's `sounds` list, which is either just a file
+/// name or an object with more settings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SoundEntry {
+    Name(String),
+    Variant {
+        #[serde(flatten)]
+        variant: SoundVariant,
+
+        #[serde(default)]
+        weight: Option<u32>,
+
+        /// `event` entries refer to another sound event rather than a file.
+        #[serde(default, rename = "type")]
+        kind: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct SoundEventJson {
+    #[serde(default)]
+    sounds: Vec<SoundEntry>,
+}
+
+/// Parses a `sounds.json`, repeating variants by their weight so they can be
+/// picked uniformly.
+fn parse_sounds_json(json: &str) -> serde_json::Result<HashMap<String, Vec<SoundVariant>>> {
+    let events: HashMap<String, SoundEventJson> = serde_json::from_str(json)?;
+
+    Ok(events
+        .into_iter()
+        .map(|(name, event)| {
+            let variants = event
+                .sounds
+                .into_iter()
+                .flat_map(|entry| {
+                    let (variant, weight) = match entry {
+                        SoundEntry::Name(name) => (
+                            SoundVariant {
+                                name,
+                                volume: 1.0,
+                                pitch: 1.0,
+                            },
+                            1,
+                        ),
+                        SoundEntry::Variant { kind, .. } if kind.as_deref() == Some("event") => {
+                            return Vec::new();
+                        }
+                        SoundEntry::Variant {
+                            variant, weight, ..
+                        } => (variant, weight.unwrap_or(1)),
+                    };
+                    vec![variant; weight as usize]
+                })
+                .collect();
+            (name, variants)
+        })
+        .collect())
+}
+
+/// Plugin that plays block sounds: footsteps when the camera moves over
+/// blocks, and whatever [`BlockSound`] messages ask for.
+///
+/// # Events
+///
+/// The plugin registers and reads the following events:
+///
+/// * [`BlockSound`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`SoundEvents`] (once [`MinecraftAssets`] are loaded)
+pub struct BlockSoundPlugin;
+
+impl Plugin for BlockSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<BlockSound>()
+            .add_systems(
+                Update,
+                load_sound_events
+                    .pipe(log_error)
+                    .run_if(resource_added::<MinecraftAssets>),
+            )
+            .add_systems(
+                Update,
+                (play_footsteps, play_block_sounds)
+                    .chain()
+                    .run_if(resource_exists::<SoundEvents>),
+            );
+    }
+}
+
+fn load_sound_events(mut commands: Commands, mc_assets: Res<MinecraftAssets>) -> Result<()> {
+    // Insert empty sound events on failure so the game runs on silently.
+    commands.init_resource::<SoundEvents>();
+
+    let sound_events = SoundEvents::load(mc_assets.root())?;
+    if sound_events.is_empty() {
+        info!("No sounds.json in the assets; block sounds are disabled");
+    }
+    commands.insert_resource(sound_events);
+
+    Ok(())
+}
+
+fn play_footsteps(
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    blocks: LoadedBlocks,
+    mut block_sounds: MessageWriter<BlockSound>,
+    mut last_position: Local<Option<Vec3>>,
+    mut distance: Local<f32>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+    let position = camera.translation();
+
+    let moved = last_position.map_or(0.0, |last| (position - last).xz().length());
+    *last_position = Some(position);
+    if moved > MAX_STEP_MOVEMENT {
+        *distance = 0.0;
+        return;
+    }
+
+    *distance += moved;
+    if *distance < STEP_DISTANCE {
+        return;
+    }
+    *distance = 0.0;
+
+    // Only step on a block right under the player's feet.
+    let below_feet = (position - Vec3::Y * (EYE_HEIGHT + 0.2)).floor().as_ivec3();
+    if let Some(block_state) = blocks.solid_blocks()(below_feet) {
+        block_sounds.write(BlockSound {
+            block_state: BlockStateId(block_state.0 as u16),
+            kind: BlockSoundKind::Step,
+        });
+    }
+}
+
+fn play_block_sounds(
+    mut commands: Commands,
+    mut block_sounds: MessageReader<BlockSound>,
+    mc_data: Res<MinecraftData>,
+    sound_events: Res<SoundEvents>,
+    asset_server: Res<AssetServer>,
+    mut next_variant: Local<usize>,
+) {
+    for block_sound in block_sounds.read() {
+        let Some(block) = mc_data.blocks().get_by_state_id(block_sound.block_state) else {
+            continue;
+        };
+        let (event, volume, pitch) = block_sound.kind.sound(&block.sound_group());
+        let Some(variants) = sound_events.get(event).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+
+        // Cycle through the variants rather than picking at random, which
+        // also avoids playing the same file twice in a row.
+        *next_variant = next_variant.wrapping_add(1);
+        let variant = &variants[*next_variant % variants.len()];
+
+        commands.spawn((
+            Name::new(event),
+            AudioPlayer::new(asset_server.load(sound_events.file_path(variant))),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::Linear(volume * variant.volume))
+                .with_speed(pitch * variant.pitch),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sounds_json() {
+        let events = parse_sounds_json(
+            r#"{
+                "block.stone.step": {
+                    "sounds": [
+                        "block/stone/step1",
+                        {"name": "block/stone/step2", "volume": 0.5, "weight": 2},
+                        {"name": "block.other", "type": "event"}
+                    ],
+                    "subtitle": "subtitles.block.generic.footsteps"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let variants = &events["block.stone.step"];
+        assert_eq!(variants.len(), 3);
+        assert_eq!(variants[0].name, "block/stone/step1");
+        assert_eq!(variants[1].volume, 0.5);
+        assert_eq!(variants[1], variants[2]);
+    }
+}
@@ -0,0 +1,358 @@
+//! Beacon beams.
+//!
+//! Like vanilla, a beam is two square columns rising from the beacon up to
+//! the top of the world, both with the beacon beam texture scrolling up them:
+//! an inner column that spins about its axis, and a wider, faint one around
+//! it that doesn't.
+//!
+//! Only active beacons shine: those standing on at least the first level of
+//! a pyramid, a 3×3 layer of iron, gold, diamond, emerald or netherite blocks.
+//! Beams are added and removed as the blocks below beacons load and change.
+//! Vanilla also stops a beam at the first opaque block above the beacon and
+//! tints it with stained glass; neither is done here, so every beam is white
+//! and reaches the sky.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    math::Affine2,
+    mesh::{Indices, PrimitiveTopology},
+    platform::collections::HashSet,
+    prelude::*,
+};
+use brine_asset::MinecraftAssets;
+use brine_chunk::BlockState;
+use brine_data::{BlockId, MinecraftData};
+use brine_proto::BrineSystems;
+use brine_voxel_v1::chunk_builder::component::ChunkSection;
+
+use crate::targeting::LoadedBlocks;
+
+use super::{SpecialBlock, SpecialBlockRenderers, SpecialRenderer};
+
+const BEACON_BEAM: SpecialRenderer = SpecialRenderer("beacon_beam");
+
+const BEAM_TEXTURE: &str = "entity/beacon_beam";
+
+/// Blocks that beacon pyramids are built of.
+const BASE_BLOCKS: [&str; 5] = [
+    "iron_block",
+    "gold_block",
+    "diamond_block",
+    "emerald_block",
+    "netherite_block",
+];
+
+/// Height that beams rise to.
+const BEAM_TOP: f32 = 1024.0;
+
+/// Half the width of the inner column, in blocks.
+const INNER_RADIUS: f32 = 0.2;
+
+/// Half the width of the outer column, in blocks.
+const OUTER_RADIUS: f32 = 0.25;
+
+/// Opacity of the outer column.
+const OUTER_ALPHA: f32 = 32.0 / 255.0;
+
+/// Ticks per second, which vanilla's animation speeds are expressed in.
+const TICKS_PER_SECOND: f32 = 20.0;
+
+/// How far the inner column spins each tick, in degrees.
+const SPIN_PER_TICK: f32 = 2.25;
+
+/// How far the texture scrolls each tick, in repeats of the texture.
+const SCROLL_PER_TICK: f32 = 0.2;
+
+#[derive(Resource, Debug)]
+struct BeamMaterials {
+    inner: Handle<StandardMaterial>,
+    outer: Handle<StandardMaterial>,
+}
+
+/// The states of the [`BASE_BLOCKS`].
+#[derive(Resource, Debug, Default)]
+struct BeaconBases(HashSet<BlockState>);
+
+impl BeaconBases {
+    fn from_data(mc_data: &MinecraftData) -> Self {
+        let blocks = mc_data.blocks();
+        let states = BASE_BLOCKS
+            .iter()
+            .filter_map(|name| {
+                let block = blocks.get_by_name(name)?;
+                let states = blocks.iter_states_for_block(BlockId(block.id))?;
+                Some(states.map(|(state_id, _)| BlockState(u32::from(state_id.0))))
+            })
+            .flatten()
+            .collect();
+        Self(states)
+    }
+
+    /// Returns whether a beacon at `position` is active: whether the 3×3
+    /// blocks below it are all base blocks, as `get_block` finds them.
+    fn is_active(&self, position: IVec3, get_block: impl Fn(IVec3) -> Option<BlockState>) -> bool {
+        (-1..=1).all(|x| {
+            (-1..=1).all(|z| {
+                get_block(position + IVec3::new(x, -1, z))
+                    .is_some_and(|block_state| self.0.contains(&block_state))
+            })
+        })
+    }
+}
+
+/// A column of a beam.
+#[derive(Component, Debug)]
+struct BeamColumn;
+
+/// The inner, spinning column of a beam.
+#[derive(Component, Debug)]
+struct InnerBeam;
+
+/// Plugin that draws beams above active beacons.
+///
+/// Requires the [`MinecraftData`] resource, and waits for [`MinecraftAssets`]
+/// before drawing any beams.
+pub struct BeaconBeamPlugin;
+
+impl Plugin for BeaconBeamPlugin {
+    fn build(&self, app: &mut App) {
+        app.world_mut()
+            .get_resource_or_init::<SpecialBlockRenderers>()
+            .register(BEACON_BEAM, &["beacon"]);
+
+        app.add_systems(
+            Update,
+            (
+                find_beacon_bases.run_if(resource_added::<MinecraftData>),
+                load_beam_materials.run_if(resource_added::<MinecraftAssets>),
+                (
+                    update_beams.run_if(resource_exists::<BeaconBases>),
+                    animate_beams.run_if(any_with_component::<InnerBeam>),
+                )
+                    .run_if(resource_exists::<BeamMaterials>),
+            )
+                .chain()
+                .in_set(BrineSystems::RenderPrep),
+        );
+    }
+}
+
+fn find_beacon_bases(mut commands: Commands, mc_data: Res<MinecraftData>) {
+    commands.insert_resource(BeaconBases::from_data(&mc_data));
+}
+
+fn load_beam_materials(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let path = mc_assets.get_any_texture_path(BEAM_TEXTURE);
+    let texture: Handle<Image> =
+        asset_server.load_with_settings(path, |settings: &mut ImageLoaderSettings| {
+            settings.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::nearest()
+            });
+        });
+
+    let inner = StandardMaterial {
+        base_color_texture: Some(texture),
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    };
+    let outer = StandardMaterial {
+        base_color: Color::WHITE.with_alpha(OUTER_ALPHA),
+        alpha_mode: AlphaMode::Blend,
+        ..inner.clone()
+    };
+    commands.insert_resource(BeamMaterials {
+        inner: materials.add(inner),
+        outer: materials.add(outer),
+    });
+}
+
+/// Gives beacons that became active a beam, and takes it from those that
+/// stopped being active, whenever beacons or chunk sections come and go or
+/// blocks change.
+#[allow(clippy::too_many_arguments)]
+fn update_beams(
+    mut commands: Commands,
+    beam_materials: Res<BeamMaterials>,
+    bases: Res<BeaconBases>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    loaded_blocks: LoadedBlocks,
+    beacons: Query<(Entity, &SpecialBlock, Option<&Children>)>,
+    columns: Query<Entity, With<BeamColumn>>,
+    changed: Query<(), Or<(Added<SpecialBlock>, Changed<ChunkSection>)>>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+
+    let get_block = loaded_blocks.solid_blocks();
+    for (entity, block, children) in beacons.iter() {
+        if block.renderer != BEACON_BEAM {
+            continue;
+        }
+
+        let beam: Vec<Entity> = children
+            .map(|children| columns.iter_many(children).collect())
+            .unwrap_or_default();
+        let active = bases.is_active(block.position.into(), &get_block);
+
+        if !active {
+            for column in beam {
+                commands.entity(column).despawn();
+            }
+            continue;
+        }
+
+        let height = BEAM_TOP - block.position.y as f32;
+        if !beam.is_empty() || height <= 0.0 {
+            continue;
+        }
+
+        let center = Transform::from_xyz(0.5, 0.0, 0.5);
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("Inner beam"),
+                BeamColumn,
+                InnerBeam,
+                Mesh3d(meshes.add(beam_mesh(INNER_RADIUS, height))),
+                MeshMaterial3d(beam_materials.inner.clone()),
+                center,
+            ));
+            parent.spawn((
+                Name::new("Outer beam"),
+                BeamColumn,
+                Mesh3d(meshes.add(beam_mesh(OUTER_RADIUS, height))),
+                MeshMaterial3d(beam_materials.outer.clone()),
+                center,
+            ));
+        });
+    }
+}
+
+/// Scrolls the beam texture and spins the inner columns, all in step.
+fn animate_beams(
+    time: Res<Time>,
+    beam_materials: Res<BeamMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut inner_beams: Query<&mut Transform, With<InnerBeam>>,
+) {
+    let ticks = time.elapsed_secs_wrapped() * TICKS_PER_SECOND;
+
+    let scroll =
+        Affine2::from_translation(Vec2::new(0.0, (-SCROLL_PER_TICK * ticks).rem_euclid(1.0)));
+    for handle in [&beam_materials.inner, &beam_materials.outer] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.uv_transform = scroll;
+        }
+    }
+
+    let rotation = Quat::from_rotation_y((ticks * SPIN_PER_TICK - 45.0).to_radians());
+    for mut transform in inner_beams.iter_mut() {
+        transform.rotation = rotation;
+    }
+}
+
+/// Builds the four sides of a column `radius` blocks from its axis and
+/// `height` blocks tall, standing on the origin. Like vanilla, the texture
+/// repeats every `2 * radius` blocks up the column.
+fn beam_mesh(radius: f32, height: f32) -> Mesh {
+    let corners = [
+        Vec2::new(-radius, -radius),
+        Vec2::new(radius, -radius),
+        Vec2::new(radius, radius),
+        Vec2::new(-radius, radius),
+    ];
+    let repeats = height * 0.5 / radius;
+
+    let mut positions = Vec::with_capacity(16);
+    let mut normals = Vec::with_capacity(16);
+    let mut uvs = Vec::with_capacity(16);
+    let mut indices = Vec::with_capacity(24);
+
+    for side in 0..4 {
+        let [a, b] = [corners[side], corners[(side + 1) % 4]];
+        let normal = (a + b).normalize().extend(0.0).xzy();
+
+        let start = positions.len() as u32;
+        positions.extend([
+            [a.x, 0.0, a.y],
+            [b.x, 0.0, b.y],
+            [b.x, height, b.y],
+            [a.x, height, a.y],
+        ]);
+        normals.extend([normal.to_array(); 4]);
+        uvs.extend([[0.0, repeats], [1.0, repeats], [1.0, 0.0], [0.0, 0.0]]);
+        indices.extend([0, 2, 1, 0, 3, 2].map(|index| start + index));
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::mesh::VertexAttributeValues;
+
+    use super::*;
+
+    #[test]
+    fn beam_texture_repeats_every_diameter() {
+        let mesh = beam_mesh(INNER_RADIUS, 100.0);
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("beam has no positions");
+        };
+        assert_eq!(positions.len(), 16);
+        assert!(positions
+            .iter()
+            .all(|[x, _, z]| x.abs() == INNER_RADIUS && z.abs() == INNER_RADIUS));
+        assert!(positions.iter().any(|[_, y, _]| *y == 100.0));
+
+        let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("beam has no texture coordinates");
+        };
+        let repeats = uvs.iter().map(|[_, v]| *v).fold(0.0, f32::max);
+        assert!((repeats - 250.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn beacons_are_active_on_a_full_layer() {
+        let iron = BlockState(5);
+        let bases = BeaconBases([iron].into_iter().collect());
+        let beacon = IVec3::new(10, 64, -3);
+
+        let layer = |missing: Option<IVec3>, other: BlockState| {
+            move |position: IVec3| {
+                let below = position.y == beacon.y - 1
+                    && (position.xz() - beacon.xz()).abs().max_element() <= 1;
+                match below {
+                    true if Some(position) == missing => None,
+                    true if position.x == beacon.x => Some(other),
+                    true => Some(iron),
+                    false => None,
+                }
+            }
+        };
+
+        assert!(bases.is_active(beacon, layer(None, iron)));
+        assert!(!bases.is_active(beacon, layer(None, BlockState(1))));
+        assert!(!bases.is_active(beacon, layer(Some(beacon - IVec3::new(1, 1, 1)), iron)));
+    }
+}
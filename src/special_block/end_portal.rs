@@ -0,0 +1,161 @@
+//! End portals and end gateways.
+//!
+//! Their block models have no faces, so they are drawn whole here, with an
+//! [`EndPortalMaterial`]: vanilla's starfield, layers of the end portal
+//! texture laid over the end sky in screen space, so the stars stay put as the
+//! portal moves across the screen. Each layer is turned, scaled and tinted by
+//! its own amount and drifts slowly with the time of day.
+//!
+//! Like vanilla, an end portal is a sheet with a top face 12 pixels up and a
+//! bottom face 6 pixels up, and an end gateway is a whole cube with one more
+//! layer of stars. Faces against other blocks are drawn too.
+
+use bevy::{
+    asset::embedded_asset,
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    prelude::*,
+    render::render_resource::AsBindGroup,
+    shader::ShaderRef,
+};
+use brine_asset::MinecraftAssets;
+use brine_proto::BrineSystems;
+
+use super::{SpecialBlock, SpecialBlockRenderers, SpecialRenderer};
+
+const SHADER_PATH: &str = "embedded://brine/special_block/end_portal.wgsl";
+
+const END_PORTAL: SpecialRenderer = SpecialRenderer("end_portal");
+const END_GATEWAY: SpecialRenderer = SpecialRenderer("end_gateway");
+
+const SKY_TEXTURE: &str = "environment/end_sky";
+const PORTAL_TEXTURE: &str = "entity/end_portal";
+
+/// Heights of the top and bottom faces of an end portal, in blocks.
+const PORTAL_TOP: f32 = 12.0 / 16.0;
+const PORTAL_BOTTOM: f32 = 6.0 / 16.0;
+
+/// Material that draws the end portal starfield.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct EndPortalMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    pub sky: Handle<Image>,
+
+    #[texture(2)]
+    #[sampler(3)]
+    pub portal: Handle<Image>,
+
+    /// Number of layers of stars, at most 16.
+    #[uniform(4)]
+    pub layers: u32,
+}
+
+impl Material for EndPortalMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_PATH.into()
+    }
+}
+
+#[derive(Resource, Debug)]
+struct EndPortalAssets {
+    portal_material: Handle<EndPortalMaterial>,
+    gateway_material: Handle<EndPortalMaterial>,
+    top: Handle<Mesh>,
+    bottom: Handle<Mesh>,
+    cube: Handle<Mesh>,
+}
+
+/// Plugin that draws end portals and end gateways with an
+/// [`EndPortalMaterial`].
+///
+/// Waits for [`MinecraftAssets`] before drawing any portals.
+pub struct EndPortalPlugin;
+
+impl Plugin for EndPortalPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "end_portal.wgsl");
+
+        let mut renderers = app
+            .world_mut()
+            .get_resource_or_init::<SpecialBlockRenderers>();
+        renderers.register(END_PORTAL, &["end_portal"]);
+        renderers.register(END_GATEWAY, &["end_gateway"]);
+
+        app.add_plugins(MaterialPlugin::<EndPortalMaterial>::default())
+            .add_systems(
+                Update,
+                (
+                    load_end_portal_assets.run_if(resource_added::<MinecraftAssets>),
+                    attach_end_portals.run_if(resource_exists::<EndPortalAssets>),
+                )
+                    .chain()
+                    .in_set(BrineSystems::RenderPrep),
+            );
+    }
+}
+
+fn load_end_portal_assets(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<EndPortalMaterial>>,
+) {
+    let load = |name: &str| -> Handle<Image> {
+        let path = mc_assets.get_any_texture_path(name);
+        asset_server.load_with_settings(path, |settings: &mut ImageLoaderSettings| {
+            settings.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::nearest()
+            });
+        })
+    };
+    let portal = EndPortalMaterial {
+        sky: load(SKY_TEXTURE),
+        portal: load(PORTAL_TEXTURE),
+        layers: 15,
+    };
+    let gateway = EndPortalMaterial {
+        layers: 16,
+        ..portal.clone()
+    };
+
+    commands.insert_resource(EndPortalAssets {
+        portal_material: materials.add(portal),
+        gateway_material: materials.add(gateway),
+        top: meshes.add(Plane3d::new(Vec3::Y, Vec2::splat(0.5))),
+        bottom: meshes.add(Plane3d::new(Vec3::NEG_Y, Vec2::splat(0.5))),
+        cube: meshes.add(Cuboid::from_length(1.0)),
+    });
+}
+
+fn attach_end_portals(
+    mut commands: Commands,
+    assets: Res<EndPortalAssets>,
+    blocks: Query<(Entity, &SpecialBlock), Added<SpecialBlock>>,
+) {
+    for (entity, block) in blocks.iter() {
+        let (material, faces) = match block.renderer {
+            END_PORTAL => (
+                &assets.portal_material,
+                vec![
+                    (assets.top.clone(), PORTAL_TOP),
+                    (assets.bottom.clone(), PORTAL_BOTTOM),
+                ],
+            ),
+            END_GATEWAY => (&assets.gateway_material, vec![(assets.cube.clone(), 0.5)]),
+            _ => continue,
+        };
+
+        commands.entity(entity).with_children(|parent| {
+            for (mesh, height) in faces {
+                parent.spawn((
+                    Mesh3d(mesh),
+                    MeshMaterial3d(material.clone()),
+                    Transform::from_xyz(0.5, height, 0.5),
+                ));
+            }
+        });
+    }
+}
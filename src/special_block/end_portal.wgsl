@@ -0,0 +1,60 @@
+// Fragment shader of the `EndPortalMaterial`: vanilla's end portal starfield,
+// layers of the portal texture over the end sky, all in screen space.
+
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::{globals, view},
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var sky_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var sky_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var portal_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var portal_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(4) var<uniform> layers: u32;
+
+// Length of a Minecraft day, in seconds. The layers drift with the time of day.
+const DAY_LENGTH: f32 = 1200.0;
+
+// Tint of the sky, then of each layer.
+const COLORS = array<vec3<f32>, 16>(
+    vec3(0.022087, 0.098399, 0.110818),
+    vec3(0.011892, 0.095924, 0.089485),
+    vec3(0.027636, 0.101689, 0.100326),
+    vec3(0.046564, 0.109883, 0.114838),
+    vec3(0.064901, 0.117696, 0.097189),
+    vec3(0.063761, 0.086895, 0.123646),
+    vec3(0.084817, 0.111994, 0.166380),
+    vec3(0.097489, 0.154120, 0.091064),
+    vec3(0.106152, 0.131144, 0.195191),
+    vec3(0.097721, 0.110188, 0.187229),
+    vec3(0.133516, 0.138278, 0.148582),
+    vec3(0.070006, 0.243332, 0.235792),
+    vec3(0.196766, 0.142899, 0.214696),
+    vec3(0.047281, 0.315338, 0.321970),
+    vec3(0.204675, 0.390010, 0.302066),
+    vec3(0.080955, 0.314821, 0.661491),
+);
+
+// Returns where the point at `uv` on the screen is in the portal texture, in
+// the layer numbered `layer` from 1: turned and scaled, then drifting.
+fn layer_uv(uv: vec2<f32>, layer: f32, time: f32) -> vec2<f32> {
+    let angle = radians((layer * layer * 4321.0 + layer * 9.0) * 2.0);
+    let turn = mat2x2(cos(angle), -sin(angle), sin(angle), cos(angle));
+    let scale = (4.5 - layer / 4.0) * 2.0;
+    let drift = vec2(17.0 / layer, (2.0 + layer / 1.5) * time * 1.5);
+    return (turn * uv * scale + drift) * 0.5 + 0.25;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = (in.position.xy - view.viewport.xy) / view.viewport.zw;
+    let time = fract(globals.time / DAY_LENGTH);
+
+    var colors = COLORS;
+    var color = textureSample(sky_texture, sky_sampler, uv).rgb * colors[0];
+    for (var i = 0u; i < min(layers, 16u); i++) {
+        let portal = textureSample(portal_texture, portal_sampler, layer_uv(uv, f32(i + 1u), time));
+        color += portal.rgb * colors[i];
+    }
+    return vec4(color, 1.0);
+}
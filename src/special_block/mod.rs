@@ -0,0 +1,208 @@
+//! Blocks drawn by code rather than by their block model.
+//!
+//! Some blocks look like more than their JSON model can say: a beacon shines
+//! a beam into the sky, and an end portal is a window onto a starfield that
+//! follows the camera. Vanilla draws these with block entity renderers.
+//!
+//! Renderers register the blocks they draw with the [`SpecialBlockRenderers`].
+//! When a chunk section is built, each of its blocks drawn by a renderer gets
+//! a child entity of the section with a [`SpecialBlock`] component, placed at
+//! the block's corner, which the renderer's own systems then give whatever
+//! meshes and materials they need. Remeshed chunks respawn their sections, so
+//! the entities of special blocks follow changes to the blocks.
+//!
+//! The block's own model is still meshed with the chunk, so renderers only
+//! draw what the model lacks.
+
+mod beacon;
+mod end_portal;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use brine_chunk::{BlockPos, BlockState, BlockStates, SectionKey};
+use brine_data::{BlockId, MinecraftData};
+use brine_proto::BrineSystems;
+use brine_voxel_v1::chunk_builder::component::{BuiltChunk, ChunkSection};
+
+pub use beacon::BeaconBeamPlugin;
+pub use end_portal::{EndPortalMaterial, EndPortalPlugin};
+
+/// The name of a special block renderer, which tells renderers' blocks apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpecialRenderer(pub &'static str);
+
+/// A block drawn by a special block renderer, as a child of its chunk section
+/// placed at the block's corner.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecialBlock {
+    pub renderer: SpecialRenderer,
+    pub block_state: BlockState,
+
+    pub position: BlockPos,
+}
+
+/// Which blocks each special block renderer draws.
+#[derive(Resource, Debug, Default)]
+pub struct SpecialBlockRenderers {
+    /// The names of the blocks each renderer registered.
+    blocks: Vec<(&'static str, SpecialRenderer)>,
+
+    /// The renderer of each block state, once the blocks are known.
+    states: HashMap<BlockState, SpecialRenderer>,
+}
+
+impl SpecialBlockRenderers {
+    /// Has `renderer` draw the blocks named `blocks`, in all their states.
+    pub fn register(&mut self, renderer: SpecialRenderer, blocks: &[&'static str]) {
+        self.blocks
+            .extend(blocks.iter().map(|&block| (block, renderer)));
+    }
+
+    /// Finds the states of the registered blocks, and forgets any found
+    /// before.
+    pub fn resolve(&mut self, mc_data: &MinecraftData) {
+        let blocks = mc_data.blocks();
+        self.states = self
+            .blocks
+            .iter()
+            .filter_map(|&(name, renderer)| {
+                let block = blocks.get_by_name(name)?;
+                let states = blocks.iter_states_for_block(BlockId(block.id))?;
+                Some(states.map(move |(state_id, _)| (BlockState(u32::from(state_id.0)), renderer)))
+            })
+            .flatten()
+            .collect();
+    }
+
+    /// Returns the renderer that draws a block state, if any.
+    #[inline]
+    pub fn get(&self, block_state: BlockState) -> Option<SpecialRenderer> {
+        self.states.get(&block_state).copied()
+    }
+
+    /// Returns the blocks of a chunk section drawn by a renderer, with their
+    /// position in the section.
+    pub fn find_in_section(
+        &self,
+        section: &brine_chunk::ChunkSection,
+    ) -> Vec<(SectionKey, BlockState, SpecialRenderer)> {
+        if self.states.is_empty() || section.block_count == 0 {
+            return Vec::new();
+        }
+
+        section
+            .block_states
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &block_state)| {
+                let renderer = self.get(block_state)?;
+                let (x, y, z) = BlockStates::index_to_xyz(index);
+                Some((SectionKey { x, y, z }, block_state, renderer))
+            })
+            .collect()
+    }
+}
+
+/// Plugin that finds the blocks of built chunk sections drawn by special
+/// block renderers, and the renderers for beacon beams and end portals.
+///
+/// Requires the [`MinecraftData`] resource.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`SpecialBlockRenderers`]
+///
+/// # Ordering
+///
+/// [`SpecialBlock`]s are spawned, and drawn, in [`BrineSystems::RenderPrep`].
+pub struct SpecialBlockPlugin;
+
+impl Plugin for SpecialBlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpecialBlockRenderers>()
+            .add_plugins((BeaconBeamPlugin, EndPortalPlugin))
+            .add_systems(
+                Update,
+                (
+                    resolve_special_blocks.run_if(resource_added::<MinecraftData>),
+                    spawn_special_blocks,
+                )
+                    .chain()
+                    .in_set(BrineSystems::RenderPrep),
+            );
+    }
+}
+
+fn resolve_special_blocks(
+    mc_data: Res<MinecraftData>,
+    mut renderers: ResMut<SpecialBlockRenderers>,
+) {
+    renderers.resolve(&mc_data);
+}
+
+fn spawn_special_blocks(
+    mut commands: Commands,
+    renderers: Res<SpecialBlockRenderers>,
+    sections: Query<(Entity, &ChunkSection, &ChildOf), Added<ChunkSection>>,
+    chunks: Query<&BuiltChunk>,
+) {
+    for (entity, section, child_of) in sections.iter() {
+        let blocks = renderers.find_in_section(&section.0);
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let Ok(chunk) = chunks.get(child_of.parent()) else {
+            continue;
+        };
+        let section_position = chunk.position().section(section.0.chunk_y.into());
+
+        commands.entity(entity).with_children(|parent| {
+            for (key, block_state, renderer) in blocks {
+                let position = section_position.block(key);
+                parent.spawn((
+                    Name::new(format!("{} {position}", renderer.0)),
+                    SpecialBlock {
+                        renderer,
+                        block_state,
+                        position,
+                    },
+                    Transform::from_translation((position - section_position.origin()).as_vec3()),
+                    Visibility::default(),
+                ));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_chunk::ChunkSection;
+
+    use super::*;
+
+    #[test]
+    fn finds_blocks_drawn_by_renderers() {
+        let beacon = BlockState(1);
+        let stone = BlockState(2);
+        let beacon_beam = SpecialRenderer("beacon_beam");
+        let renderers = SpecialBlockRenderers {
+            states: [(beacon, beacon_beam)].into_iter().collect(),
+            ..default()
+        };
+
+        let mut section = ChunkSection::empty(4);
+        section.block_states.0[BlockStates::xyz_to_index(3, 5, 7)] = beacon;
+        section.block_states.0[BlockStates::xyz_to_index(3, 4, 7)] = stone;
+        section.block_count = 2;
+        assert_eq!(
+            renderers.find_in_section(&section),
+            vec![(SectionKey { x: 3, y: 5, z: 7 }, beacon, beacon_beam)]
+        );
+
+        section.block_count = 0;
+        assert!(renderers.find_in_section(&section).is_empty());
+    }
+}
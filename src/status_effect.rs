@@ -0,0 +1,458 @@
+//! Status effects (potion effects) on the player and other entities.
+//!
+//! Effects on the local player are listed in the HUD and change how the
+//! client behaves: Speed and Slowness scale the fly camera's movement speed
+//! (on top of the flying or walking speed in the [`PlayerAbilities`]),
+//! and Night Vision brightens the picture. Durations count down once per game
+//! tick, in `FixedUpdate`.
+
+use bevy::{platform::collections::HashMap, prelude::*, render::view::ColorGrading};
+use bevy_flycam::MovementSettings;
+use brine_proto::{
+    event::clientbound::{EntityEffectAdded, EntityEffectRemoved, Reconfigure, Transfer},
+    BrineSystems, TICKS_PER_SECOND,
+};
+
+use crate::{
+    entity::{LocalPlayerId, ServerEntities},
+    game_mode::PlayerAbilities,
+};
+
+/// The fly camera's speed without any effects, in blocks per second.
+const BASE_FLY_SPEED: f32 = 12.0;
+
+/// Night Vision starts flickering when this many ticks are left, as in vanilla.
+const NIGHT_VISION_FLICKER_TICKS: i32 = 200;
+
+/// Gamma applied to the picture at full Night Vision strength; lower is
+/// brighter.
+const NIGHT_VISION_GAMMA: f32 = 0.6;
+
+/// Vanilla's status effects by protocol ID, as `(name, display name)`.
+const EFFECTS: [(&str, &str); 39] = [
+    ("speed", "Speed"),
+    ("slowness", "Slowness"),
+    ("haste", "Haste"),
+    ("mining_fatigue", "Mining Fatigue"),
+    ("strength", "Strength"),
+    ("instant_health", "Instant Health"),
+    ("instant_damage", "Instant Damage"),
+    ("jump_boost", "Jump Boost"),
+    ("nausea", "Nausea"),
+    ("regeneration", "Regeneration"),
+    ("resistance", "Resistance"),
+    ("fire_resistance", "Fire Resistance"),
+    ("water_breathing", "Water Breathing"),
+    ("invisibility", "Invisibility"),
+    ("blindness", "Blindness"),
+    ("night_vision", "Night Vision"),
+    ("hunger", "Hunger"),
+    ("weakness", "Weakness"),
+    ("poison", "Poison"),
+    ("wither", "Wither"),
+    ("health_boost", "Health Boost"),
+    ("absorption", "Absorption"),
+    ("saturation", "Saturation"),
+    ("glowing", "Glowing"),
+    ("levitation", "Levitation"),
+    ("luck", "Luck"),
+    ("unluck", "Bad Luck"),
+    ("slow_falling", "Slow Falling"),
+    ("conduit_power", "Conduit Power"),
+    ("dolphins_grace", "Dolphin's Grace"),
+    ("bad_omen", "Bad Omen"),
+    ("hero_of_the_village", "Hero of the Village"),
+    ("darkness", "Darkness"),
+    ("trial_omen", "Trial Omen"),
+    ("raid_omen", "Raid Omen"),
+    ("wind_charged", "Wind Charged"),
+    ("weaving", "Weaving"),
+    ("oozing", "Oozing"),
+    ("infested", "Infested"),
+];
+
+/// Numeric status effect ID, as sent by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectId(pub i32);
+
+impl EffectId {
+    pub const SPEED: Self = Self(0);
+    pub const SLOWNESS: Self = Self(1);
+    pub const NIGHT_VISION: Self = Self(15);
+
+    /// Returns the effect's name, e.g. `night_vision`.
+    pub fn name(self) -> Option<&'static str> {
+        self.entry().map(|(name, _)| name)
+    }
+
+    /// Returns the effect's English display name, e.g. `Night Vision`.
+    pub fn display_name(self) -> Option<&'static str> {
+        self.entry().map(|(_, display_name)| display_name)
+    }
+
+    fn entry(self) -> Option<(&'static str, &'static str)> {
+        usize::try_from(self.0)
+            .ok()
+            .and_then(|index| EFFECTS.get(index))
+            .copied()
+    }
+}
+
+/// An effect that an entity currently has.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveEffect {
+    /// Level of the effect minus one.
+    pub amplifier: i32,
+
+    /// Remaining duration in ticks, or `None` if the effect lasts forever.
+    pub remaining_ticks: Option<i32>,
+
+    pub show_icon: bool,
+}
+
+impl ActiveEffect {
+    /// Returns the level of the effect, e.g. `2` for Speed II.
+    pub fn level(&self) -> i32 {
+        self.amplifier + 1
+    }
+}
+
+/// The status effects an entity has.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct StatusEffects(HashMap<EffectId, ActiveEffect>);
+
+impl StatusEffects {
+    pub fn get(&self, effect: EffectId) -> Option<&ActiveEffect> {
+        self.0.get(&effect)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EffectId, &ActiveEffect)> {
+        self.0.iter().map(|(id, effect)| (*id, effect))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns how much faster than normal the entity moves, following the
+    /// movement speed modifiers of Speed (+20% per level) and Slowness (-15%
+    /// per level).
+    pub fn speed_multiplier(&self) -> f32 {
+        let level = |effect| self.get(effect).map_or(0, ActiveEffect::level) as f32;
+
+        let speed = 1.0 + 0.2 * level(EffectId::SPEED);
+        let slowness = 1.0 - 0.15 * level(EffectId::SLOWNESS);
+        (speed * slowness).max(0.0)
+    }
+
+    /// Returns the strength of Night Vision, from `0.0` (none) to `1.0`. Like
+    /// vanilla, it flickers during the last ten seconds.
+    pub fn night_vision_strength(&self) -> f32 {
+        match self.get(EffectId::NIGHT_VISION) {
+            None => 0.0,
+            Some(ActiveEffect {
+                remaining_ticks: Some(ticks),
+                ..
+            }) if *ticks <= NIGHT_VISION_FLICKER_TICKS => {
+                0.7 + (*ticks as f32 * std::f32::consts::PI * 0.2).sin() * 0.3
+            }
+            Some(_) => 1.0,
+        }
+    }
+
+    fn insert(&mut self, event: &EntityEffectAdded) {
+        self.0.insert(
+            EffectId(event.effect_id),
+            ActiveEffect {
+                amplifier: event.amplifier,
+                remaining_ticks: event.duration,
+                show_icon: event.show_icon,
+            },
+        );
+    }
+
+    fn remove(&mut self, effect: EffectId) {
+        self.0.remove(&effect);
+    }
+
+    /// Counts down the effects' durations. The server removes expired effects
+    /// itself; until it does, they stay at zero.
+    fn tick(&mut self) {
+        for effect in self.0.values_mut() {
+            if let Some(remaining) = &mut effect.remaining_ticks {
+                *remaining = (*remaining - 1).max(0);
+            }
+        }
+    }
+}
+
+/// The status effects of the local player.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct LocalStatusEffects(pub StatusEffects);
+
+/// The HUD text listing the local player's effects.
+#[derive(Component, Debug)]
+struct EffectList;
+
+/// Plugin that tracks the [`StatusEffects`] of [`ServerEntity`]s and of the
+/// local player, shows the player's effects in the HUD, and applies their
+/// client-side modifiers.
+///
+/// [`ServerEntity`]: crate::entity::ServerEntity
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`EntityEffectAdded`]
+/// * [`EntityEffectRemoved`]
+/// * [`Transfer`] and [`Reconfigure`], which clear the player's effects
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`LocalStatusEffects`]
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LocalStatusEffects>()
+            .add_systems(Startup, spawn_effect_list)
+            .add_systems(FixedUpdate, tick_effects)
+            .add_systems(
+                Update,
+                (
+                    reset_local_effects,
+                    handle_effect_events,
+                    update_effect_list,
+                    apply_speed,
+                    apply_night_vision,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            );
+    }
+}
+
+fn reset_local_effects(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut local_effects: ResMut<LocalStatusEffects>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        *local_effects = LocalStatusEffects::default();
+    }
+}
+
+fn handle_effect_events(
+    mut commands: Commands,
+    mut added_events: MessageReader<EntityEffectAdded>,
+    mut removed_events: MessageReader<EntityEffectRemoved>,
+    local_player: Res<LocalPlayerId>,
+    server_entities: Res<ServerEntities>,
+    mut local_effects: ResMut<LocalStatusEffects>,
+    mut entities: Query<&mut StatusEffects>,
+) {
+    for added in added_events.read() {
+        if local_player.0 == Some(added.entity_id) {
+            local_effects.0.insert(added);
+        } else if let Some(entity) = server_entities.get(added.entity_id) {
+            match entities.get_mut(entity) {
+                Ok(mut effects) => effects.insert(added),
+                Err(_) => {
+                    let mut effects = StatusEffects::default();
+                    effects.insert(added);
+                    commands.entity(entity).insert(effects);
+                }
+            }
+        }
+    }
+
+    for removed in removed_events.read() {
+        let effect = EffectId(removed.effect_id);
+        if local_player.0 == Some(removed.entity_id) {
+            local_effects.0.remove(effect);
+        } else if let Some(entity) = server_entities.get(removed.entity_id) {
+            if let Ok(mut effects) = entities.get_mut(entity) {
+                effects.remove(effect);
+            }
+        }
+    }
+}
+
+/// Runs once per game tick.
+fn tick_effects(
+    mut local_effects: ResMut<LocalStatusEffects>,
+    mut entities: Query<&mut StatusEffects>,
+) {
+    if !local_effects.0.is_empty() {
+        local_effects.0.tick();
+    }
+    for mut effects in entities.iter_mut() {
+        effects.tick();
+    }
+}
+
+fn spawn_effect_list(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Status Effects"),
+        EffectList,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        },
+    ));
+}
+
+/// Formats an effect level as a Roman numeral, as vanilla does up to 10.
+fn level_numeral(level: i32) -> String {
+    const NUMERALS: [&str; 10] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+
+    usize::try_from(level - 1)
+        .ok()
+        .and_then(|index| NUMERALS.get(index))
+        .map_or_else(|| level.to_string(), |numeral| numeral.to_string())
+}
+
+/// Formats a duration in ticks as `m:ss`, or `∞` for infinite effects.
+fn format_duration(ticks: Option<i32>) -> String {
+    match ticks {
+        Some(ticks) => {
+            let seconds = (ticks.max(0) as u32).div_ceil(TICKS_PER_SECOND as u32);
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        }
+        None => String::from("∞"),
+    }
+}
+
+fn update_effect_list(
+    local_effects: Res<LocalStatusEffects>,
+    mut texts: Query<&mut Text, With<EffectList>>,
+) {
+    let mut effects: Vec<_> = local_effects
+        .0
+        .iter()
+        .filter(|(_, effect)| effect.show_icon)
+        .collect();
+    effects.sort_by_key(|(id, _)| id.0);
+
+    let lines: Vec<_> = effects
+        .into_iter()
+        .map(|(id, effect)| {
+            let name = id
+                .display_name()
+                .map_or_else(|| format!("Effect {}", id.0), str::to_string);
+            let name = if effect.level() > 1 {
+                format!("{name} {}", level_numeral(effect.level()))
+            } else {
+                name
+            };
+            format!("{name} {}", format_duration(effect.remaining_ticks))
+        })
+        .collect();
+    let message = lines.join("\n");
+
+    for mut text in texts.iter_mut() {
+        if text.0 != message {
+            text.0.clone_from(&message);
+        }
+    }
+}
+
+fn apply_speed(
+    local_effects: Res<LocalStatusEffects>,
+    abilities: Option<Res<PlayerAbilities>>,
+    mut movement: ResMut<MovementSettings>,
+) {
+    let speed = BASE_FLY_SPEED
+        * local_effects.0.speed_multiplier()
+        * abilities.map_or(1.0, |abilities| abilities.speed_multiplier());
+    if movement.speed != speed {
+        movement.speed = speed;
+    }
+}
+
+fn apply_night_vision(
+    mut commands: Commands,
+    local_effects: Res<LocalStatusEffects>,
+    mut cameras: Query<(Entity, Option<&mut ColorGrading>), With<Camera3d>>,
+) {
+    let strength = local_effects.0.night_vision_strength();
+    let gamma = 1.0 + (NIGHT_VISION_GAMMA - 1.0) * strength;
+
+    for (camera, color_grading) in cameras.iter_mut() {
+        match color_grading {
+            Some(mut color_grading) if color_grading.midtones.gamma != gamma => {
+                set_gamma(&mut color_grading, gamma);
+            }
+            None if strength > 0.0 => {
+                let mut color_grading = ColorGrading::default();
+                set_gamma(&mut color_grading, gamma);
+                commands.entity(camera).insert(color_grading);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn set_gamma(color_grading: &mut ColorGrading, gamma: f32) {
+    for section in color_grading.all_sections_mut() {
+        section.gamma = gamma;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(effect: EffectId, amplifier: i32) -> EntityEffectAdded {
+        EntityEffectAdded {
+            entity_id: 1,
+            effect_id: effect.0,
+            amplifier,
+            duration: Some(600),
+            ambient: false,
+            show_particles: true,
+            show_icon: true,
+        }
+    }
+
+    #[test]
+    fn speed_and_slowness_scale_movement() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(effects.speed_multiplier(), 1.0);
+
+        effects.insert(&effect(EffectId::SPEED, 1));
+        assert!((effects.speed_multiplier() - 1.4).abs() < 1e-6);
+
+        effects.insert(&effect(EffectId::SLOWNESS, 0));
+        assert!((effects.speed_multiplier() - 1.4 * 0.85).abs() < 1e-6);
+
+        effects.remove(EffectId::SPEED);
+        effects.insert(&effect(EffectId::SLOWNESS, 9));
+        assert_eq!(effects.speed_multiplier(), 0.0);
+    }
+
+    #[test]
+    fn effect_labels() {
+        assert_eq!(EffectId::NIGHT_VISION.display_name(), Some("Night Vision"));
+        assert_eq!(EffectId(39).name(), None);
+        assert_eq!(level_numeral(4), "IV");
+        assert_eq!(level_numeral(11), "11");
+        assert_eq!(format_duration(Some(1210)), "1:01");
+        assert_eq!(format_duration(None), "∞");
+    }
+}
@@ -0,0 +1,148 @@
+//! What the camera looks like under water or lava.
+//!
+//! When the camera's eye is below the surface of a fluid, as the
+//! [`FluidStates`] say how high it is, the world is fogged and the screen is
+//! tinted with the fluid's color: a few dozen blocks of blue water, or barely
+//! a block of orange lava.
+
+use bevy::{pbr::FogFalloff, prelude::*, transform::TransformSystems};
+use brine_voxel_v1::fluid::{FluidKind, FluidStates, WATER_COLOR};
+
+use crate::targeting::LoadedBlocks;
+
+/// How far the camera sees under water.
+const WATER_VISIBILITY: f32 = 48.0;
+
+/// How far the camera sees in lava.
+const LAVA_VISIBILITY: f32 = 1.0;
+
+/// Color of the fog under water, darker than the water itself.
+const WATER_FOG_COLOR: Color = Color::srgb(0.05, 0.15, 0.35);
+
+const LAVA_COLOR: Color = Color::srgb(0.6, 0.1, 0.0);
+
+/// Opacity of the screen tint under water and in lava.
+const WATER_TINT_ALPHA: f32 = 0.2;
+const LAVA_TINT_ALPHA: f32 = 0.6;
+
+/// The fluid the camera's eye is in, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct CameraFluid(pub Option<FluidKind>);
+
+/// Plugin that fogs and tints the view when the camera is in a fluid.
+///
+/// Does nothing without the [`FluidStates`] resource (see
+/// [`FluidPlugin`][brine_voxel_v1::fluid::FluidPlugin]).
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`CameraFluid`]
+///
+/// # Ordering
+///
+/// The camera's fluid is found in [`PostUpdate`], after the camera has moved
+/// for the frame.
+pub struct SubmergedPlugin;
+
+impl Plugin for SubmergedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraFluid>()
+            .add_systems(Startup, spawn_fluid_tint)
+            .add_systems(
+                PostUpdate,
+                (
+                    update_camera_fluid.run_if(resource_exists::<FluidStates>),
+                    (update_fog, update_fluid_tint).run_if(resource_changed::<CameraFluid>),
+                )
+                    .chain()
+                    .after(TransformSystems::Propagate),
+            );
+    }
+}
+
+#[derive(Component)]
+struct FluidTint;
+
+fn spawn_fluid_tint(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Fluid Tint"),
+        FluidTint,
+        BackgroundColor(Color::NONE),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+    ));
+}
+
+fn update_camera_fluid(
+    fluids: Res<FluidStates>,
+    blocks: LoadedBlocks,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut camera_fluid: ResMut<CameraFluid>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let blocks = blocks.solid_blocks();
+    let fluid_at = |position: IVec3| blocks(position).and_then(|block| fluids.get(block));
+
+    let eye = camera.translation();
+    let block = eye.floor().as_ivec3();
+    let kind = fluid_at(block).and_then(|fluid| {
+        // The fluid fills its block if there's more of it above.
+        let above = fluid_at(block + IVec3::Y).is_some_and(|above| above.kind == fluid.kind);
+        let surface = block.y as f32 + if above { 1.0 } else { fluid.height() };
+        (eye.y < surface).then_some(fluid.kind)
+    });
+
+    camera_fluid.set_if_neq(CameraFluid(kind));
+}
+
+fn update_fog(
+    camera_fluid: Res<CameraFluid>,
+    cameras: Query<Entity, With<Camera3d>>,
+    mut commands: Commands,
+) {
+    let fog = camera_fluid.map(|kind| {
+        let (color, visibility) = match kind {
+            FluidKind::Water => (WATER_FOG_COLOR, WATER_VISIBILITY),
+            FluidKind::Lava => (LAVA_COLOR, LAVA_VISIBILITY),
+        };
+        DistanceFog {
+            color,
+            falloff: FogFalloff::Linear {
+                start: 0.0,
+                end: visibility,
+            },
+            ..default()
+        }
+    });
+
+    for camera in cameras.iter() {
+        match &fog {
+            Some(fog) => commands.entity(camera).insert(fog.clone()),
+            None => commands.entity(camera).remove::<DistanceFog>(),
+        };
+    }
+}
+
+fn update_fluid_tint(
+    camera_fluid: Res<CameraFluid>,
+    mut tints: Query<&mut BackgroundColor, With<FluidTint>>,
+) {
+    let color = match **camera_fluid {
+        Some(FluidKind::Water) => WATER_COLOR.with_alpha(WATER_TINT_ALPHA),
+        Some(FluidKind::Lava) => LAVA_COLOR.with_alpha(LAVA_TINT_ALPHA),
+        None => Color::NONE,
+    };
+
+    for mut tint in tints.iter_mut() {
+        tint.0 = color;
+    }
+}
@@ -0,0 +1,339 @@
+//! A generated world, for benchmarking meshing and rendering without a server
+//! or chunk dumps.
+//!
+//! Chunks are generated in a square around the origin and sent as
+//! [`ChunkData`] events, nearest first, the same way
+//! [`ServeChunksFromDirectoryPlugin`][crate::server::ServeChunksFromDirectoryPlugin]
+//! sends chunk files. The same [`SyntheticWorld`] always generates the same
+//! blocks, so runs can be compared.
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use futures_lite::future;
+
+use brine_chunk::{
+    BlockState, BlockStates, Chunk, ChunkRelative, ChunkSection, SectionKey, CHUNK_MIN_Y,
+    CHUNK_WIDTH, SECTIONS_PER_CHUNK, SECTION_Y_BASE,
+};
+use brine_data::MinecraftData;
+use brine_proto::{event::clientbound::ChunkData, BrineSystems};
+
+/// Y of the surface of [`WorldShape::Flat`] and [`WorldShape::Checkerboard`]
+/// worlds, as in a vanilla superflat world.
+const FLAT_SURFACE_Y: i32 = CHUNK_MIN_Y as i32 + 3;
+
+/// Lowest surface Y of [`WorldShape::Terrain`] worlds.
+const TERRAIN_BASE_Y: i32 = 50;
+
+/// What a [`SyntheticWorld`] looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldShape {
+    /// Bedrock, two layers of dirt and grass, like a vanilla superflat world.
+    Flat,
+
+    /// A single layer alternating between two colors every block, over
+    /// bedrock. Nothing can be merged, so this is the worst case for greedy
+    /// meshing.
+    Checkerboard,
+
+    /// Rolling hills of stone, dirt and grass, shaped by noise from `seed`.
+    Terrain { seed: u64 },
+}
+
+/// Which chunks [`SyntheticWorldPlugin`] generates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticWorld {
+    pub shape: WorldShape,
+
+    /// How many chunks to generate out from the origin chunk in each
+    /// direction, so `(2 * radius + 1)²` chunks in all.
+    pub radius: u32,
+}
+
+impl Default for SyntheticWorld {
+    fn default() -> Self {
+        Self {
+            shape: WorldShape::Flat,
+            radius: 8,
+        }
+    }
+}
+
+/// A plugin that acts as a phony server, sending ChunkData events containing
+/// generated chunks.
+///
+/// # Resources
+///
+/// The plugin needs the [`MinecraftData`] resource to look up block states.
+pub struct SyntheticWorldPlugin {
+    world: SyntheticWorld,
+}
+
+impl SyntheticWorldPlugin {
+    pub fn new(world: SyntheticWorld) -> Self {
+        Self { world }
+    }
+}
+
+impl Plugin for SyntheticWorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SyntheticWorldConfig(self.world));
+        app.add_systems(Startup, start_generating_chunks);
+        app.add_systems(Update, send_chunks.in_set(BrineSystems::ProtocolDecode));
+    }
+}
+
+#[derive(Resource)]
+struct SyntheticWorldConfig(SyntheticWorld);
+
+#[derive(Component)]
+struct GenerateChunkTask(Task<Chunk>);
+
+/// The block states the generator places.
+#[derive(Debug, Clone, Copy)]
+struct Palette {
+    bedrock: BlockState,
+    stone: BlockState,
+    dirt: BlockState,
+    grass: BlockState,
+    light: BlockState,
+    dark: BlockState,
+}
+
+impl Palette {
+    fn new(mc_data: &MinecraftData) -> Self {
+        let block = |name| match mc_data.blocks().default_state_id_by_name(name) {
+            Some(state_id) => BlockState(state_id.0 as u32),
+            None => {
+                warn!("Unknown block {}, generating air instead", name);
+                BlockState::AIR
+            }
+        };
+
+        Self {
+            bedrock: block("bedrock"),
+            stone: block("stone"),
+            dirt: block("dirt"),
+            grass: block("grass_block"),
+            light: block("white_concrete"),
+            dark: block("black_concrete"),
+        }
+    }
+}
+
+fn start_generating_chunks(
+    config: Res<SyntheticWorldConfig>,
+    mc_data: Res<MinecraftData>,
+    mut commands: Commands,
+) {
+    let SyntheticWorld { shape, radius } = config.0;
+    let palette = Palette::new(&mc_data);
+    let radius = radius as i32;
+
+    let mut positions: Vec<_> = (-radius..=radius)
+        .flat_map(|chunk_x| (-radius..=radius).map(move |chunk_z| IVec2::new(chunk_x, chunk_z)))
+        .collect();
+    positions.sort_by_key(|position| position.length_squared());
+
+    info!(
+        "Generating {} chunks of a {:?} world",
+        positions.len(),
+        shape
+    );
+
+    let task_pool = AsyncComputeTaskPool::get();
+    for position in positions {
+        let task =
+            task_pool.spawn(async move { generate_chunk(shape, &palette, position.x, position.y) });
+
+        commands.spawn((
+            GenerateChunkTask(task),
+            Name::new(format!("Generating Chunk ({}, {})", position.x, position.y)),
+        ));
+    }
+}
+
+fn send_chunks(
+    mut tasks: Query<(Entity, &mut GenerateChunkTask)>,
+    mut chunk_events: MessageWriter<ChunkData>,
+    mut commands: Commands,
+) {
+    for (task_entity, mut task) in tasks.iter_mut() {
+        if let Some(chunk_data) = future::block_on(future::poll_once(&mut task.0)) {
+            chunk_events.write(ChunkData { chunk_data });
+            commands.entity(task_entity).despawn();
+        }
+    }
+}
+
+fn generate_chunk(shape: WorldShape, palette: &Palette, chunk_x: i32, chunk_z: i32) -> Chunk {
+    let mut chunk = Chunk::empty(chunk_x, chunk_z);
+    let position = chunk.position();
+
+    let mut surface = [[0; CHUNK_WIDTH]; CHUNK_WIDTH];
+    for (x, column) in surface.iter_mut().enumerate() {
+        for (z, surface_y) in column.iter_mut().enumerate() {
+            let block = position.block(ChunkRelative::new(x as u8, 0, z as u8));
+            *surface_y = surface_height(shape, block.x, block.z);
+        }
+    }
+
+    for section in 0..SECTIONS_PER_CHUNK {
+        let chunk_y = SECTION_Y_BASE + section as i16;
+        let section_position = position.section(chunk_y.into());
+
+        let mut block_states = BlockStates::default();
+        let mut block_count = 0;
+        for (index, block_state) in block_states.0.iter_mut().enumerate() {
+            let (x, y, z) = BlockStates::index_to_xyz(index);
+            let block = section_position.block(SectionKey { x, y, z });
+            let surface_y = surface[x as usize][z as usize];
+
+            *block_state = match shape {
+                _ if block.y == CHUNK_MIN_Y as i32 => palette.bedrock,
+                _ if block.y > surface_y => BlockState::AIR,
+                WorldShape::Checkerboard => {
+                    if (block.x + block.z).rem_euclid(2) == 0 {
+                        palette.light
+                    } else {
+                        palette.dark
+                    }
+                }
+                _ if block.y == surface_y => palette.grass,
+                _ if block.y >= surface_y - 3 => palette.dirt,
+                WorldShape::Flat => palette.dirt,
+                WorldShape::Terrain { .. } => palette.stone,
+            };
+
+            if *block_state != BlockState::AIR {
+                block_count += 1;
+            }
+        }
+
+        if block_count > 0 {
+            chunk.sections.push(ChunkSection {
+                chunk_y,
+                block_count,
+                block_states,
+            });
+        }
+    }
+
+    chunk
+}
+
+/// Returns the Y of the topmost block of the column at the given block X and
+/// Z.
+fn surface_height(shape: WorldShape, x: i32, z: i32) -> i32 {
+    match shape {
+        WorldShape::Flat => FLAT_SURFACE_Y,
+        WorldShape::Checkerboard => CHUNK_MIN_Y as i32 + 1,
+        WorldShape::Terrain { seed } => {
+            // A few octaves of value noise, each twice as detailed and half as
+            // tall as the one before, for hills up to 60 blocks high.
+            let mut height = 0.0;
+            let mut amplitude = 32.0;
+            let mut frequency = 1.0 / 64.0;
+            for octave in 0..4 {
+                let seed = seed.wrapping_add(octave);
+                height += amplitude * value_noise(seed, x as f32 * frequency, z as f32 * frequency);
+                amplitude /= 2.0;
+                frequency *= 2.0;
+            }
+            TERRAIN_BASE_Y + height as i32
+        }
+    }
+}
+
+/// Returns smoothly varying noise between 0 and 1, which is random at integer
+/// coordinates and interpolated between them.
+fn value_noise(seed: u64, x: f32, z: f32) -> f32 {
+    let (x0, z0) = (x.floor(), z.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, tz) = (smooth(x - x0), smooth(z - z0));
+    let (x0, z0) = (x0 as i32, z0 as i32);
+
+    let near = lerp(hash(seed, x0, z0), hash(seed, x0 + 1, z0), tx);
+    let far = lerp(hash(seed, x0, z0 + 1), hash(seed, x0 + 1, z0 + 1), tx);
+    lerp(near, far, tz)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Returns a number between 0 and 1 that looks random, but is always the same
+/// for the same arguments.
+pub(crate) fn hash(seed: u64, x: i32, z: i32) -> f32 {
+    // The SplitMix64 finalizer.
+    let mut h = seed
+        ^ (x as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (z as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALETTE: Palette = Palette {
+        bedrock: BlockState(1),
+        stone: BlockState(2),
+        dirt: BlockState(3),
+        grass: BlockState(4),
+        light: BlockState(5),
+        dark: BlockState(6),
+    };
+
+    fn block_at(chunk: &Chunk, x: u8, y: i32, z: u8) -> BlockState {
+        chunk.get_block(ChunkRelative::new(x, y, z))
+    }
+
+    #[test]
+    fn flat_world_is_superflat() {
+        let chunk = generate_chunk(WorldShape::Flat, &PALETTE, -1, 2);
+
+        assert_eq!(chunk.sections.len(), 1);
+        assert_eq!(chunk.sections[0].block_count, 16 * 16 * 4);
+        let column: Vec<_> = (-64..-59).map(|y| block_at(&chunk, 5, y, 7)).collect();
+        assert_eq!(
+            column,
+            [
+                PALETTE.bedrock,
+                PALETTE.dirt,
+                PALETTE.dirt,
+                PALETTE.grass,
+                BlockState::AIR
+            ]
+        );
+    }
+
+    #[test]
+    fn checkerboard_alternates_across_chunks() {
+        let west = generate_chunk(WorldShape::Checkerboard, &PALETTE, -1, 0);
+        let east = generate_chunk(WorldShape::Checkerboard, &PALETTE, 0, 0);
+
+        assert_eq!(block_at(&west, 15, -63, 0), PALETTE.dark);
+        assert_eq!(block_at(&east, 0, -63, 0), PALETTE.light);
+        assert_eq!(block_at(&east, 1, -63, 0), PALETTE.dark);
+        assert_eq!(block_at(&east, 1, -63, 1), PALETTE.light);
+    }
+
+    #[test]
+    fn terrain_depends_only_on_seed() {
+        let terrain = |seed| generate_chunk(WorldShape::Terrain { seed }, &PALETTE, 3, -4);
+
+        assert_eq!(terrain(7), terrain(7));
+        assert_ne!(terrain(7), terrain(8));
+
+        for (x, z) in [(0, 0), (40, -17), (-300, 1000)] {
+            let height = surface_height(WorldShape::Terrain { seed: 7 }, x, z);
+            assert!((TERRAIN_BASE_Y..TERRAIN_BASE_Y + 60).contains(&height));
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Determining which block the player is looking at.
+
+use bevy::{ecs::system::SystemParam, platform::collections::HashMap, prelude::*};
+use brine_chunk::{BlockPos, BlockState, SectionPos};
+use brine_voxel_v1::chunk_builder::component::{BuiltChunk, ChunkSection};
+
+/// Maximum distance (in blocks) at which a block can be targeted.
+pub const REACH_DISTANCE: f32 = 5.0;
+
+/// A block hit by a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockHit {
+    /// World position of the block that was hit.
+    pub position: IVec3,
+
+    /// Block state of the block that was hit.
+    pub block_state: BlockState,
+
+    /// Normal of the face that was hit, or zero if the ray started inside the
+    /// block.
+    pub normal: IVec3,
+
+    /// Distance from the ray origin to the hit point.
+    pub distance: f32,
+}
+
+/// The block currently targeted by the camera, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct TargetedBlock(pub Option<BlockHit>);
+
+/// Plugin that keeps the [`TargetedBlock`] resource up to date by casting a
+/// ray from the camera into the loaded chunk sections.
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`TargetedBlock`]
+/// * [`SectionIndex`], which [`LoadedBlocks`] needs
+///
+/// # Ordering
+///
+/// The [`SectionIndex`] is updated in [`PreUpdate`], so sections built during
+/// a frame can be looked up from the next one.
+pub struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TargetedBlock>()
+            .init_resource::<SectionIndex>()
+            .add_systems(PreUpdate, update_section_index)
+            .add_systems(Update, update_targeted_block);
+    }
+}
+
+/// The entity holding each built chunk section, by position.
+///
+/// Kept up to date as sections are spawned and despawned, so that looking up
+/// a block only touches the section it is in instead of going through every
+/// loaded section.
+#[derive(Resource, Debug, Default)]
+pub struct SectionIndex {
+    entities: HashMap<SectionPos, Entity>,
+    positions: HashMap<Entity, SectionPos>,
+}
+
+impl SectionIndex {
+    /// Returns the entity of the section at `position`, if it is built.
+    pub fn get(&self, position: SectionPos) -> Option<Entity> {
+        self.entities.get(&position).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn insert(&mut self, position: SectionPos, entity: Entity) {
+        if let Some(replaced) = self.entities.insert(position, entity) {
+            self.positions.remove(&replaced);
+        }
+        self.positions.insert(entity, position);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(position) = self.positions.remove(&entity) else {
+            return;
+        };
+        // A rebuilt section may have taken the position already.
+        if self.entities.get(&position) == Some(&entity) {
+            self.entities.remove(&position);
+        }
+    }
+}
+
+/// The blocks of all built chunk sections, for raycasting against.
+#[derive(SystemParam)]
+pub struct LoadedBlocks<'w, 's> {
+    index: Res<'w, SectionIndex>,
+    sections: Query<'w, 's, &'static ChunkSection>,
+}
+
+impl LoadedBlocks<'_, '_> {
+    /// Returns a lookup of the non-air block at a world position, suitable for
+    /// [`raycast_blocks`].
+    pub fn solid_blocks(&self) -> impl Fn(IVec3) -> Option<BlockState> + '_ {
+        move |position| {
+            let position = BlockPos::from(position);
+            let entity = self.index.get(position.section())?;
+            let section = self.sections.get(entity).ok()?;
+            let block_state = section.0.get_block(position.section_key()).ok()?;
+            (block_state != BlockState::AIR).then_some(block_state)
+        }
+    }
+}
+
+/// Adds newly built sections to the [`SectionIndex`] and drops the ones that
+/// were despawned.
+fn update_section_index(
+    mut index: ResMut<SectionIndex>,
+    mut removed: RemovedComponents<ChunkSection>,
+    added: Query<(Entity, &ChildOf, &ChunkSection), Added<ChunkSection>>,
+    chunks: Query<&BuiltChunk>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+
+    for (entity, parent, section) in added.iter() {
+        if let Ok(chunk) = chunks.get(parent.parent()) {
+            let position = chunk.position().section(section.0.chunk_y.into());
+            index.insert(position, entity);
+        }
+    }
+}
+
+fn update_targeted_block(
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    blocks: LoadedBlocks,
+    mut targeted: ResMut<TargetedBlock>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let hit = raycast_blocks(
+        camera.translation(),
+        *camera.forward(),
+        REACH_DISTANCE,
+        blocks.solid_blocks(),
+    );
+
+    targeted.set_if_neq(TargetedBlock(hit));
+}
+
+/// Walks the blocks along a ray using the voxel traversal algorithm of Amanatides
+/// and Woo, returning the first block for which `get_solid_block` returns
+/// `Some`.
+pub fn raycast_blocks(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    mut get_solid_block: impl FnMut(IVec3) -> Option<BlockState>,
+) -> Option<BlockHit> {
+    let direction = direction.try_normalize()?;
+
+    let mut position = origin.floor().as_ivec3();
+    let step = direction.signum().as_ivec3();
+
+    // Distance along the ray to cross one block on each axis.
+    let delta = direction.recip().abs();
+
+    // Distance along the ray to the first block boundary on each axis. Axes
+    // the ray is parallel to are never crossed.
+    let mut next_boundary = Vec3::select(
+        direction.cmpgt(Vec3::ZERO),
+        (position.as_vec3() + 1.0 - origin) * delta,
+        (origin - position.as_vec3()) * delta,
+    );
+    next_boundary = Vec3::select(direction.cmpeq(Vec3::ZERO), Vec3::INFINITY, next_boundary);
+
+    let mut normal = IVec3::ZERO;
+    let mut distance = 0.0;
+
+    while distance <= max_distance {
+        if let Some(block_state) = get_solid_block(position) {
+            return Some(BlockHit {
+                position,
+                block_state,
+                normal,
+                distance,
+            });
+        }
+
+        let axis = if next_boundary.x < next_boundary.y && next_boundary.x < next_boundary.z {
+            0
+        } else if next_boundary.y < next_boundary.z {
+            1
+        } else {
+            2
+        };
+
+        distance = next_boundary[axis];
+        next_boundary[axis] += delta[axis];
+        position[axis] += step[axis];
+        normal = IVec3::ZERO;
+        normal[axis] = -step[axis];
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raycast_hits_face_facing_origin() {
+        let hit = raycast_blocks(Vec3::new(0.5, 0.5, 0.5), Vec3::X, 5.0, |position| {
+            (position == IVec3::new(3, 0, 0)).then_some(BlockState(1))
+        })
+        .unwrap();
+
+        assert_eq!(hit.position, IVec3::new(3, 0, 0));
+        assert_eq!(hit.normal, IVec3::NEG_X);
+        assert!((hit.distance - 2.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rebuilt_sections_replace_despawned_ones_in_the_index() {
+        let mut app = App::new();
+        app.init_resource::<SectionIndex>()
+            .add_systems(Update, update_section_index);
+
+        let spawn_section = |app: &mut App| {
+            let chunk = app
+                .world_mut()
+                .spawn(BuiltChunk {
+                    chunk_x: 2,
+                    chunk_z: -1,
+                    ..default()
+                })
+                .id();
+            app.world_mut()
+                .spawn((
+                    ChunkSection(brine_chunk::ChunkSection::empty(3)),
+                    ChildOf(chunk),
+                ))
+                .id()
+        };
+        let position = SectionPos::new(2, 3, -1);
+
+        let old = spawn_section(&mut app);
+        app.update();
+        assert_eq!(
+            app.world().resource::<SectionIndex>().get(position),
+            Some(old)
+        );
+
+        let new = spawn_section(&mut app);
+        app.world_mut().despawn(old);
+        app.update();
+        let index = app.world().resource::<SectionIndex>();
+        assert_eq!(index.get(position), Some(new));
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn raycast_respects_max_distance() {
+        let hit = raycast_blocks(Vec3::new(0.5, 0.5, 0.5), Vec3::NEG_Z, 5.0, |position| {
+            (position == IVec3::new(0, 0, -10)).then_some(BlockState(1))
+        });
+
+        assert_eq!(hit, None);
+    }
+}
@@ -0,0 +1,344 @@
+//! Riding boats, minecarts, horses and other vehicles.
+//!
+//! The server says who rides what with [`PassengersSet`]. While the local
+//! player is a passenger, the camera sits in the vehicle's seat instead of
+//! flying freely (the [`LocalPlayer`][crate::player::LocalPlayer] follows it
+//! there as usual), and the movement inputs are sent as [`SteerVehicle`].
+//! Sneaking ([`Descend`][InputAction::Descend]) dismounts.
+//!
+//! The first passenger controls the vehicle. The server moves minecarts
+//! itself, but boats and horses are moved by the client that controls them,
+//! which reports where they went with [`MoveVehicle`]. There is no vehicle
+//! physics yet: a controlled vehicle turns to where the player looks and
+//! glides forward or backward at a fixed speed, and the server's
+//! [`VehicleMoved`] puts it back where it belongs when it disagrees. Whether
+//! a horse is saddled isn't known (entity metadata isn't decoded), so every
+//! horse the player rides first is treated as controlled; pigs and striders,
+//! which need an item on a stick, never are.
+
+use bevy::prelude::*;
+use bevy_flycam::FlyCam;
+use brine_data::{EntityTypeId, MinecraftData};
+use brine_proto::{
+    event::{
+        clientbound::{
+            EntitiesRemoved, JoinedGame, PassengersSet, Reconfigure, Transfer, VehicleMoved,
+        },
+        serverbound::{MoveVehicle, SteerVehicle},
+    },
+    BrineSystems, TICKS_PER_SECOND,
+};
+
+use crate::{
+    debug::Hitbox,
+    entity::{EntityLook, LocalPlayerId, ServerEntities, ServerEntity, ServerPosition},
+    input::{InputAction, InputMap},
+    player::{look_angles, Freecam, PLAYER_EYE_HEIGHT},
+};
+
+/// How far below the top of a vehicle's hitbox its rider's feet are.
+const SEAT_DEPTH: f32 = 0.6;
+
+/// The vehicle the local player is riding, if any.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Riding {
+    /// Server ID of the vehicle.
+    pub vehicle_id: Option<i32>,
+
+    /// Whether the player is the vehicle's first passenger, who steers it.
+    pub controlling: bool,
+
+    /// The movement inputs last sent to the server.
+    pub steering: SteerVehicle,
+}
+
+impl Riding {
+    /// Applies a change of `passengers.vehicle_id`'s passengers, given the
+    /// local player's server ID.
+    fn update(&mut self, passengers: &PassengersSet, local_player_id: i32) {
+        if passengers.passenger_ids.contains(&local_player_id) {
+            self.vehicle_id = Some(passengers.vehicle_id);
+            self.controlling = passengers.passenger_ids.first() == Some(&local_player_id);
+        } else if self.vehicle_id == Some(passengers.vehicle_id) {
+            self.dismount();
+        }
+    }
+
+    fn dismount(&mut self) {
+        self.vehicle_id = None;
+        self.controlling = false;
+    }
+}
+
+/// Returns how fast a vehicle of the given entity type moves when its rider
+/// steers it, in blocks per second, or `None` if the rider can't steer it.
+fn steering_speed(entity_type: &str) -> Option<f32> {
+    match entity_type {
+        name if name.ends_with("_boat") || name.ends_with("_raft") => Some(8.0),
+        "horse" | "donkey" | "mule" | "skeleton_horse" | "zombie_horse" => Some(9.0),
+        "camel" => Some(4.0),
+        _ => None,
+    }
+}
+
+/// Plugin that lets the local player ride vehicles.
+///
+/// Requires the [`MinecraftData`] resource.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`PassengersSet`]
+/// * [`VehicleMoved`]
+/// * [`EntitiesRemoved`], [`JoinedGame`], [`Transfer`] and [`Reconfigure`],
+///   which end the ride
+///
+/// The plugin sends the following events:
+///
+/// * [`SteerVehicle`]
+/// * [`MoveVehicle`]
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`Riding`]
+///
+/// # Ordering
+///
+/// Passengers and steering are updated in [`BrineSystems::WorldUpdate`]. A
+/// controlled vehicle moves once per game tick, in [`FixedUpdate`]. The camera
+/// is moved to the seat in [`PostUpdate`], after everything in [`Update`] has
+/// moved it and before the player follows it.
+pub struct VehiclePlugin;
+
+impl Plugin for VehiclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Riding>()
+            .add_systems(
+                Update,
+                (handle_passenger_events, correct_vehicle, steer_vehicle)
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            )
+            .add_systems(FixedUpdate, drive_vehicle)
+            .add_systems(
+                PostUpdate,
+                seat_camera
+                    .before(crate::player::follow_camera)
+                    .before(TransformSystems::Propagate),
+            );
+    }
+}
+
+fn handle_passenger_events(
+    mut passengers_events: MessageReader<PassengersSet>,
+    mut removed_events: MessageReader<EntitiesRemoved>,
+    mut joined_events: MessageReader<JoinedGame>,
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    local_player: Res<LocalPlayerId>,
+    mut riding: ResMut<Riding>,
+) {
+    let joined = joined_events.read().count() > 0;
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+    if joined || transferred || reconfigured {
+        riding.dismount();
+    }
+
+    for removed in removed_events.read() {
+        if riding
+            .vehicle_id
+            .is_some_and(|vehicle_id| removed.entity_ids.contains(&vehicle_id))
+        {
+            riding.dismount();
+        }
+    }
+
+    let Some(local_player_id) = local_player.0 else {
+        passengers_events.clear();
+        return;
+    };
+    for passengers in passengers_events.read() {
+        let was_riding = riding.vehicle_id;
+        riding.update(passengers, local_player_id);
+        if riding.vehicle_id != was_riding {
+            match riding.vehicle_id {
+                Some(vehicle_id) => info!("Riding entity {}", vehicle_id),
+                None => info!("Dismounted"),
+            }
+        }
+    }
+}
+
+/// Puts the controlled vehicle where the server says it is.
+fn correct_vehicle(
+    mut vehicle_events: MessageReader<VehicleMoved>,
+    riding: Res<Riding>,
+    server_entities: Res<ServerEntities>,
+    mut vehicles: Query<(&mut ServerPosition, &mut EntityLook)>,
+) {
+    let Some(moved) = vehicle_events.read().last() else {
+        return;
+    };
+    let Some(vehicle) = riding
+        .vehicle_id
+        .and_then(|vehicle_id| server_entities.get(vehicle_id))
+    else {
+        return;
+    };
+    let Ok((mut position, mut look)) = vehicles.get_mut(vehicle) else {
+        return;
+    };
+
+    position.0 = moved.position;
+    look.yaw = moved.yaw;
+    look.pitch = moved.pitch;
+}
+
+/// Sends the movement inputs whenever they change while riding, and releases
+/// them once the ride ends.
+fn steer_vehicle(
+    input: InputMap,
+    mut riding: ResMut<Riding>,
+    mut steer_events: MessageWriter<SteerVehicle>,
+) {
+    let steering = if riding.vehicle_id.is_some() {
+        SteerVehicle {
+            forward: input.pressed(InputAction::MoveForward),
+            backward: input.pressed(InputAction::MoveBackward),
+            left: input.pressed(InputAction::MoveLeft),
+            right: input.pressed(InputAction::MoveRight),
+            jump: input.pressed(InputAction::Ascend),
+            sneak: input.pressed(InputAction::Descend),
+        }
+    } else {
+        SteerVehicle::default()
+    };
+
+    if riding.steering != steering {
+        riding.steering = steering;
+        steer_events.write(steering);
+    }
+}
+
+/// Moves the vehicle the player controls and reports where it went.
+fn drive_vehicle(
+    riding: Res<Riding>,
+    server_entities: Res<ServerEntities>,
+    mc_data: Res<MinecraftData>,
+    cameras: Query<&Transform, With<FlyCam>>,
+    mut vehicles: Query<(&ServerEntity, &mut ServerPosition, &mut EntityLook)>,
+    mut move_events: MessageWriter<MoveVehicle>,
+) {
+    if !riding.controlling {
+        return;
+    }
+    let Some(vehicle) = riding
+        .vehicle_id
+        .and_then(|vehicle_id| server_entities.get(vehicle_id))
+    else {
+        return;
+    };
+    let (Ok((server_entity, mut position, mut look)), Ok(camera)) =
+        (vehicles.get_mut(vehicle), cameras.single())
+    else {
+        return;
+    };
+
+    let speed = u16::try_from(server_entity.entity_type)
+        .ok()
+        .and_then(|id| mc_data.entity_types().get_by_id(EntityTypeId(id)))
+        .and_then(|entity_type| steering_speed(entity_type.name));
+    let Some(speed) = speed else {
+        return;
+    };
+
+    let (yaw, _) = look_angles(*camera.forward());
+    look.yaw = yaw;
+
+    let steering = riding.steering;
+    let direction = f32::from(i8::from(steering.forward) - i8::from(steering.backward));
+    let heading = look.body_rotation() * Vec3::NEG_Z;
+    position.0 += (heading * direction * speed / TICKS_PER_SECOND as f32).as_dvec3();
+
+    move_events.write(MoveVehicle {
+        position: position.0,
+        yaw: look.yaw,
+        pitch: look.pitch,
+    });
+}
+
+/// Keeps the camera in the vehicle's seat while riding.
+fn seat_camera(
+    riding: Res<Riding>,
+    freecam: Res<Freecam>,
+    server_entities: Res<ServerEntities>,
+    vehicles: Query<(&Transform, &Hitbox), Without<FlyCam>>,
+    mut cameras: Query<&mut Transform, With<FlyCam>>,
+) {
+    if freecam.enable {
+        return;
+    }
+    let Some(vehicle) = riding
+        .vehicle_id
+        .and_then(|vehicle_id| server_entities.get(vehicle_id))
+    else {
+        return;
+    };
+    let Ok((vehicle, hitbox)) = vehicles.get(vehicle) else {
+        return;
+    };
+
+    let eye = vehicle.translation + Vec3::Y * (hitbox.height - SEAT_DEPTH + PLAYER_EYE_HEIGHT);
+    for mut camera in cameras.iter_mut() {
+        camera.translation = eye;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passengers(vehicle_id: i32, passenger_ids: &[i32]) -> PassengersSet {
+        PassengersSet {
+            vehicle_id,
+            passenger_ids: passenger_ids.to_vec(),
+        }
+    }
+
+    #[test]
+    fn tracks_the_ridden_vehicle() {
+        let mut riding = Riding::default();
+
+        // Someone else gets into a boat.
+        riding.update(&passengers(10, &[3]), 7);
+        assert_eq!(riding.vehicle_id, None);
+
+        // The player gets in behind them, so doesn't steer.
+        riding.update(&passengers(10, &[3, 7]), 7);
+        assert_eq!((riding.vehicle_id, riding.controlling), (Some(10), false));
+
+        // The driver leaves, so the player takes over.
+        riding.update(&passengers(10, &[7]), 7);
+        assert_eq!((riding.vehicle_id, riding.controlling), (Some(10), true));
+
+        // Another vehicle emptying doesn't matter.
+        riding.update(&passengers(11, &[]), 7);
+        assert_eq!(riding.vehicle_id, Some(10));
+
+        riding.update(&passengers(10, &[]), 7);
+        assert_eq!((riding.vehicle_id, riding.controlling), (None, false));
+    }
+
+    #[test]
+    fn only_some_vehicles_are_steered() {
+        assert!(steering_speed("oak_boat").is_some());
+        assert!(steering_speed("bamboo_chest_raft").is_some());
+        assert!(steering_speed("horse").is_some());
+        assert!(steering_speed("minecart").is_none());
+        assert!(steering_speed("pig").is_none());
+    }
+}
@@ -0,0 +1,575 @@
+//! The world border: tracking it, drawing it, and keeping the player inside it.
+
+use std::time::Duration;
+
+use bevy::{
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    math::{Affine2, DVec2},
+    prelude::*,
+    transform::TransformSystems,
+};
+use brine_asset::MinecraftAssets;
+use brine_proto::{
+    event::clientbound::{Reconfigure, Transfer, WorldBorderUpdate},
+    BrineSystems,
+};
+
+use crate::player::Freecam;
+
+/// Texture scrolled across the border wall.
+const FORCEFIELD_TEXTURE: &str = "misc/forcefield";
+
+/// The wall is drawn when the camera is within this many blocks of it, fading
+/// in as the camera gets closer.
+const WALL_VISIBLE_DISTANCE: f64 = 192.0;
+
+/// Number of blocks covered by one repetition of the forcefield texture.
+const WALL_TEXTURE_SIZE: f32 = 2.0;
+
+/// Time for the forcefield texture to scroll one full repetition.
+const WALL_SCROLL_PERIOD: f32 = 3.0;
+
+/// How close the center of the camera may get to the border; half the width
+/// of a player.
+const PLAYER_HALF_WIDTH: f64 = 0.3;
+
+/// Opacity of the screen tint when standing right at the border.
+const MAX_TINT_ALPHA: f32 = 0.35;
+
+const STATIONARY_COLOR: Color = Color::srgb(0.125, 0.627, 1.0);
+const SHRINKING_COLOR: Color = Color::srgb(1.0, 0.188, 0.188);
+const GROWING_COLOR: Color = Color::srgb(0.251, 1.0, 0.502);
+
+/// Whether the world border is moving, and in which direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStatus {
+    Stationary,
+    Shrinking,
+    Growing,
+}
+
+/// The world border as last described by the server.
+///
+/// Coordinates and diameters are in blocks.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct WorldBorder {
+    /// Center of the border on the X/Z plane.
+    pub center: DVec2,
+
+    /// Distance from the world origin beyond which portals won't teleport.
+    pub portal_teleport_boundary: i32,
+
+    /// Players closer than this many blocks to the border are warned.
+    pub warning_blocks: i32,
+
+    /// A moving border warns players this long before it reaches them.
+    pub warning_time: Duration,
+
+    from_diameter: f64,
+    to_diameter: f64,
+    lerp_duration: Duration,
+    lerp_elapsed: Duration,
+}
+
+impl Default for WorldBorder {
+    /// The vanilla border, which nobody will ever reach.
+    fn default() -> Self {
+        Self {
+            center: DVec2::ZERO,
+            portal_teleport_boundary: 29_999_984,
+            warning_blocks: 5,
+            warning_time: Duration::from_secs(15),
+            from_diameter: 59_999_968.0,
+            to_diameter: 59_999_968.0,
+            lerp_duration: Duration::ZERO,
+            lerp_elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl WorldBorder {
+    /// Returns the current diameter, part of the way through any lerp.
+    pub fn diameter(&self) -> f64 {
+        if self.lerp_elapsed >= self.lerp_duration {
+            return self.to_diameter;
+        }
+
+        let t = self.lerp_elapsed.as_secs_f64() / self.lerp_duration.as_secs_f64();
+        self.from_diameter + (self.to_diameter - self.from_diameter) * t
+    }
+
+    /// Returns the diameter the border is moving towards.
+    pub fn target_diameter(&self) -> f64 {
+        self.to_diameter
+    }
+
+    /// Returns the time left until the border reaches its target diameter.
+    pub fn remaining(&self) -> Duration {
+        self.lerp_duration.saturating_sub(self.lerp_elapsed)
+    }
+
+    pub fn status(&self) -> BorderStatus {
+        if self.remaining().is_zero() || self.from_diameter == self.to_diameter {
+            BorderStatus::Stationary
+        } else if self.to_diameter < self.from_diameter {
+            BorderStatus::Shrinking
+        } else {
+            BorderStatus::Growing
+        }
+    }
+
+    /// Sets the diameter immediately, cancelling any lerp.
+    pub fn set_diameter(&mut self, diameter: f64) {
+        self.lerp_to(diameter, diameter, Duration::ZERO);
+    }
+
+    /// Starts moving the border from `from` to `to` over `duration`.
+    pub fn lerp_to(&mut self, from: f64, to: f64, duration: Duration) {
+        self.from_diameter = from;
+        self.to_diameter = to;
+        self.lerp_duration = duration;
+        self.lerp_elapsed = Duration::ZERO;
+    }
+
+    /// Advances any lerp in progress by `delta`.
+    pub fn tick(&mut self, delta: Duration) {
+        self.lerp_elapsed = (self.lerp_elapsed + delta).min(self.lerp_duration);
+    }
+
+    /// Returns the minimum corner of the border.
+    pub fn min(&self) -> DVec2 {
+        self.center - DVec2::splat(self.diameter() / 2.0)
+    }
+
+    /// Returns the maximum corner of the border.
+    pub fn max(&self) -> DVec2 {
+        self.center + DVec2::splat(self.diameter() / 2.0)
+    }
+
+    /// Returns the distance from `position` (on the X/Z plane) to the nearest
+    /// edge of the border. Negative if `position` is outside the border.
+    pub fn distance_inside(&self, position: DVec2) -> f64 {
+        let to_min = position - self.min();
+        let to_max = self.max() - position;
+        to_min.min(to_max).min_element()
+    }
+
+    /// Returns how strongly a player at `position` should be warned about the
+    /// border, from `0.0` (not at all) to `1.0` (at or outside the border).
+    pub fn warning_strength(&self, position: DVec2) -> f32 {
+        // A moving border warns of where it will be `warning_time` from now.
+        let moving_distance = if self.status() == BorderStatus::Stationary {
+            0.0
+        } else {
+            let speed =
+                (self.to_diameter - self.from_diameter).abs() / self.lerp_duration.as_secs_f64();
+            let remaining_distance = (self.to_diameter - self.diameter()).abs();
+            (speed * self.warning_time.as_secs_f64()).min(remaining_distance)
+        };
+        let warning_distance = f64::from(self.warning_blocks).max(moving_distance);
+
+        if warning_distance <= 0.0 {
+            return 0.0;
+        }
+
+        let distance = self.distance_inside(position).max(0.0);
+        (1.0 - distance / warning_distance).max(0.0) as f32
+    }
+
+    /// Returns `position` moved inside the border, `margin` blocks from its
+    /// edges.
+    pub fn clamp(&self, position: DVec2, margin: f64) -> DVec2 {
+        let min = self.min() + margin;
+        let max = (self.max() - margin).max(min);
+        position.clamp(min, max)
+    }
+}
+
+/// Plugin that tracks the world border, draws it as an animated translucent
+/// wall, tints the screen red when the player gets close to it, and stops the
+/// camera from leaving it. A camera detached by the [`Freecam`] is left
+/// alone, as the player it left behind doesn't move.
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`WorldBorderUpdate`]
+/// * [`Transfer`] and [`Reconfigure`], which reset the border
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`WorldBorder`]
+pub struct WorldBorderPlugin;
+
+impl Plugin for WorldBorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldBorder>()
+            .add_systems(Startup, spawn_warning_tint)
+            .add_systems(
+                Update,
+                (
+                    (
+                        reset_world_border,
+                        handle_world_border_updates,
+                        tick_world_border,
+                    )
+                        .chain()
+                        .in_set(BrineSystems::WorldUpdate),
+                    load_wall_assets.run_if(resource_added::<MinecraftAssets>),
+                    update_warning_tint,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    keep_camera_inside_border,
+                    update_wall.run_if(resource_exists::<WallMaterials>),
+                )
+                    .chain()
+                    .before(TransformSystems::Propagate),
+            );
+    }
+}
+
+/// One of the four sides of the border wall.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum WallSide {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl WallSide {
+    const ALL: [WallSide; 4] = [
+        WallSide::North,
+        WallSide::South,
+        WallSide::West,
+        WallSide::East,
+    ];
+}
+
+/// One material per side, since each side's texture is tiled differently.
+#[derive(Resource)]
+struct WallMaterials {
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+#[derive(Component)]
+struct WarningTint;
+
+fn reset_world_border(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut border: ResMut<WorldBorder>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        *border = WorldBorder::default();
+    }
+}
+
+fn handle_world_border_updates(
+    mut events: MessageReader<WorldBorderUpdate>,
+    mut border: ResMut<WorldBorder>,
+) {
+    for event in events.read() {
+        match *event {
+            WorldBorderUpdate::Initialize {
+                center_x,
+                center_z,
+                old_diameter,
+                new_diameter,
+                lerp_millis,
+                portal_teleport_boundary,
+                warning_blocks,
+                warning_seconds,
+            } => {
+                border.center = DVec2::new(center_x, center_z);
+                border.lerp_to(old_diameter, new_diameter, millis(lerp_millis));
+                border.portal_teleport_boundary = portal_teleport_boundary;
+                border.warning_blocks = warning_blocks;
+                border.warning_time = Duration::from_secs(warning_seconds.max(0) as u64);
+            }
+            WorldBorderUpdate::Center { x, z } => {
+                border.center = DVec2::new(x, z);
+            }
+            WorldBorderUpdate::LerpSize {
+                old_diameter,
+                new_diameter,
+                lerp_millis,
+            } => {
+                border.lerp_to(old_diameter, new_diameter, millis(lerp_millis));
+            }
+            WorldBorderUpdate::Size { diameter } => {
+                border.set_diameter(diameter);
+            }
+            WorldBorderUpdate::WarningDelay { seconds } => {
+                border.warning_time = Duration::from_secs(seconds.max(0) as u64);
+            }
+            WorldBorderUpdate::WarningDistance { blocks } => {
+                border.warning_blocks = blocks;
+            }
+        }
+    }
+}
+
+fn millis(millis: i64) -> Duration {
+    Duration::from_millis(millis.max(0) as u64)
+}
+
+fn tick_world_border(time: Res<Time>, mut border: ResMut<WorldBorder>) {
+    if !border.remaining().is_zero() {
+        border.tick(time.delta());
+    }
+}
+
+fn keep_camera_inside_border(
+    border: Res<WorldBorder>,
+    freecam: Res<Freecam>,
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+) {
+    // The player follows the camera, so clamping the camera keeps the player
+    // inside too; a detached camera may fly beyond the border to look at it.
+    if freecam.enable {
+        return;
+    }
+
+    for mut transform in cameras.iter_mut() {
+        let position = DVec2::new(
+            f64::from(transform.translation.x),
+            f64::from(transform.translation.z),
+        );
+        let clamped = border.clamp(position, PLAYER_HALF_WIDTH);
+
+        if clamped != position {
+            transform.translation.x = clamped.x as f32;
+            transform.translation.z = clamped.y as f32;
+        }
+    }
+}
+
+fn load_wall_assets(
+    mut commands: Commands,
+    mc_assets: Res<MinecraftAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let path = mc_assets.get_any_texture_path(FORCEFIELD_TEXTURE);
+    let texture: Handle<Image> =
+        asset_server.load_with_settings(path, |settings: &mut ImageLoaderSettings| {
+            settings.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::nearest()
+            });
+        });
+
+    let mesh = meshes.add(Rectangle::new(1.0, 1.0));
+    let mut side_materials = Vec::with_capacity(WallSide::ALL.len());
+
+    for side in WallSide::ALL {
+        let material = materials.add(StandardMaterial {
+            base_color: STATIONARY_COLOR,
+            base_color_texture: Some(texture.clone()),
+            alpha_mode: AlphaMode::Add,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        });
+
+        commands.spawn((
+            Name::new("World Border"),
+            side,
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::default(),
+            Visibility::Hidden,
+        ));
+        side_materials.push(material);
+    }
+
+    commands.insert_resource(WallMaterials {
+        materials: side_materials,
+    });
+}
+
+fn update_wall(
+    time: Res<Time>,
+    border: Res<WorldBorder>,
+    wall_materials: Res<WallMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cameras: Query<&Transform, (With<Camera3d>, Without<WallSide>)>,
+    mut walls: Query<(&WallSide, &mut Transform, &mut Visibility)>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+
+    let camera_position = DVec2::new(
+        f64::from(camera.translation.x),
+        f64::from(camera.translation.z),
+    );
+    let min = border.min();
+    let max = border.max();
+
+    let color = match border.status() {
+        BorderStatus::Stationary => STATIONARY_COLOR,
+        BorderStatus::Shrinking => SHRINKING_COLOR,
+        BorderStatus::Growing => GROWING_COLOR,
+    };
+    let scroll = (time.elapsed_secs() % WALL_SCROLL_PERIOD) / WALL_SCROLL_PERIOD;
+    let height = (WALL_VISIBLE_DISTANCE * 2.0) as f32;
+
+    for (side, mut transform, mut visibility) in walls.iter_mut() {
+        // Position of the wall across the border, and the axis it runs along.
+        let (wall, camera_along, along_min, along_max) = match side {
+            WallSide::North => (min.y, camera_position.x, min.x, max.x),
+            WallSide::South => (max.y, camera_position.x, min.x, max.x),
+            WallSide::West => (min.x, camera_position.y, min.y, max.y),
+            WallSide::East => (max.x, camera_position.y, min.y, max.y),
+        };
+        let camera_across = match side {
+            WallSide::North | WallSide::South => camera_position.y,
+            WallSide::West | WallSide::East => camera_position.x,
+        };
+
+        // Only draw the part of the wall near the camera, which also keeps the
+        // coordinates small enough for f32.
+        let distance = (wall - camera_across).abs();
+        let start = along_min.max(camera_along - WALL_VISIBLE_DISTANCE);
+        let end = along_max.min(camera_along + WALL_VISIBLE_DISTANCE);
+
+        if distance >= WALL_VISIBLE_DISTANCE || start >= end {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Visible;
+
+        let width = (end - start) as f32;
+        let middle = ((start + end) / 2.0) as f32;
+        let bottom = camera.translation.y - height / 2.0;
+
+        transform.translation = match side {
+            WallSide::North | WallSide::South => {
+                Vec3::new(middle, camera.translation.y, wall as f32)
+            }
+            WallSide::West | WallSide::East => Vec3::new(wall as f32, camera.translation.y, middle),
+        };
+        transform.rotation = match side {
+            WallSide::North | WallSide::South => Quat::IDENTITY,
+            WallSide::West | WallSide::East => Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+        };
+        transform.scale = Vec3::new(width, height, 1.0);
+
+        let Some(material) = wall_materials
+            .materials
+            .get(*side as usize)
+            .and_then(|handle| materials.get_mut(handle))
+        else {
+            continue;
+        };
+
+        // Fade in sharply as the camera approaches.
+        let alpha = (1.0 - distance / WALL_VISIBLE_DISTANCE).powi(4) as f32;
+        material.base_color = color.with_alpha(alpha);
+
+        // Anchor the texture to world coordinates so that it doesn't slide
+        // around as the camera moves, then scroll it over time.
+        let repeats = Vec2::new(width, height) / WALL_TEXTURE_SIZE;
+        let offset = Vec2::new(start as f32, bottom) / WALL_TEXTURE_SIZE;
+        material.uv_transform = Affine2::from_scale_angle_translation(
+            repeats,
+            0.0,
+            (offset + Vec2::splat(scroll)).fract(),
+        );
+    }
+}
+
+fn spawn_warning_tint(mut commands: Commands) {
+    commands.spawn((
+        Name::new("World Border Warning"),
+        WarningTint,
+        BackgroundColor(Color::NONE),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+    ));
+}
+
+fn update_warning_tint(
+    border: Res<WorldBorder>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut tints: Query<&mut BackgroundColor, With<WarningTint>>,
+) {
+    let strength = cameras
+        .single()
+        .map(|camera| {
+            let translation = camera.translation();
+            border.warning_strength(DVec2::new(
+                f64::from(translation.x),
+                f64::from(translation.z),
+            ))
+        })
+        .unwrap_or(0.0);
+
+    for mut tint in tints.iter_mut() {
+        let color = Color::srgba(1.0, 0.0, 0.0, strength * MAX_TINT_ALPHA);
+        if tint.0 != color {
+            tint.0 = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lerps_diameter() {
+        let mut border = WorldBorder::default();
+        border.lerp_to(100.0, 50.0, Duration::from_secs(10));
+        assert_eq!(border.status(), BorderStatus::Shrinking);
+        assert_eq!(border.diameter(), 100.0);
+
+        border.tick(Duration::from_secs(5));
+        assert_eq!(border.diameter(), 75.0);
+
+        border.tick(Duration::from_secs(60));
+        assert_eq!(border.diameter(), 50.0);
+        assert_eq!(border.status(), BorderStatus::Stationary);
+    }
+
+    #[test]
+    fn distance_and_warning() {
+        let mut border = WorldBorder {
+            center: DVec2::new(10.0, 0.0),
+            warning_blocks: 5,
+            ..default()
+        };
+        border.set_diameter(20.0);
+
+        assert_eq!(border.distance_inside(DVec2::new(10.0, 0.0)), 10.0);
+        assert_eq!(border.distance_inside(DVec2::new(18.0, 0.0)), 2.0);
+        assert_eq!(border.distance_inside(DVec2::new(25.0, 0.0)), -5.0);
+
+        assert_eq!(border.warning_strength(DVec2::new(10.0, 0.0)), 0.0);
+        assert!((border.warning_strength(DVec2::new(18.0, 0.0)) - 0.6).abs() < 1e-6);
+        assert_eq!(border.warning_strength(DVec2::new(25.0, 0.0)), 1.0);
+
+        assert_eq!(
+            border.clamp(DVec2::new(25.0, -30.0), 0.5),
+            DVec2::new(19.5, -9.5)
+        );
+    }
+}
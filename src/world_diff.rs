@@ -0,0 +1,556 @@
+//! Consolidated per-tick diffs of the world, for programs outside the client.
+//!
+//! A [`WorldDiff`] says what changed in the world over one game tick: chunks
+//! that were added or removed, chunk data that was received, blocks that
+//! changed, and entities that spawned, moved or were removed. Changes within
+//! the tick are folded together: a block changed twice is reported once with
+//! its last state, an entity that moved several times is reported once at
+//! where it ended up, and an entity that spawned and was removed in the same
+//! tick isn't reported at all.
+//!
+//! Diffs are built from the same protocol events the rest of the client
+//! reads, so they describe the world as the server sent it, not as it is
+//! drawn. The server never unloads chunks explicitly; every chunk and entity
+//! is removed when the client is transferred or reconfigured.
+//!
+//! Applying a diff takes removals first, then added chunks and chunk data,
+//! then block changes, then entity spawns and moves.
+//!
+//! Diffs are sent to every [`WorldDiffs::subscribe`]r, and can be streamed to
+//! another process as JSON, one diff per line (see
+//! [`WorldDiffPlugin::with_stream`]).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufWriter, Write},
+    net::{SocketAddr, TcpListener},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+use bevy::{
+    math::DVec3,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use brine_chunk::{BlockPos, BlockState, Chunk, ChunkPos};
+use brine_proto::{
+    event::clientbound::{
+        BlockChanged, ChunkData, EntitiesRemoved, EntityMoved, EntityPosition, EntitySpawned,
+        Reconfigure, Transfer,
+    },
+    BrineSystems,
+};
+use serde::{Deserialize, Serialize};
+
+/// Everything that changed in the world over one game tick.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WorldDiff {
+    /// Number of the tick, counting from 1 at the first tick after the
+    /// [`WorldDiffPlugin`] was added. Ticks where nothing changed have no
+    /// diff.
+    pub tick: u64,
+
+    /// Chunks that were loaded, sorted.
+    pub chunks_added: Vec<ChunkPos>,
+
+    /// Chunks that were unloaded, sorted.
+    pub chunks_removed: Vec<ChunkPos>,
+
+    /// Data received for loaded chunks, full or partial, in the order it was
+    /// received. Only sent to subscribers that were subscribed when it was
+    /// received.
+    pub chunk_data: Vec<Chunk>,
+
+    /// Blocks that changed since their chunk's data was received,
+    /// sorted by position.
+    pub blocks_changed: Vec<ChangedBlock>,
+
+    /// Entities that spawned, by ID.
+    pub entities_spawned: Vec<SpawnedEntity>,
+
+    /// Entities that moved, other than those that spawned, by ID.
+    pub entities_moved: Vec<MovedEntity>,
+
+    /// IDs of entities that were removed, sorted.
+    pub entities_removed: Vec<i32>,
+}
+
+impl WorldDiff {
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.chunks_added.is_empty()
+            && self.chunks_removed.is_empty()
+            && self.chunk_data.is_empty()
+            && self.blocks_changed.is_empty()
+            && self.entities_spawned.is_empty()
+            && self.entities_moved.is_empty()
+            && self.entities_removed.is_empty()
+    }
+}
+
+/// A block's new state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ChangedBlock {
+    pub position: BlockPos,
+    pub block_state: BlockState,
+}
+
+/// An entity that spawned, at where it was at the end of the tick.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct SpawnedEntity {
+    pub entity_id: i32,
+
+    /// Numeric entity type, as used by the registry of the server's version.
+    pub entity_type: i32,
+
+    /// World position of the entity's feet.
+    pub position: DVec3,
+}
+
+/// Where an entity was at the end of the tick.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct MovedEntity {
+    pub entity_id: i32,
+
+    /// World position of the entity's feet.
+    pub position: DVec3,
+}
+
+/// Subscriptions to [`WorldDiff`]s.
+#[derive(Resource, Debug, Default)]
+pub struct WorldDiffs {
+    subscribers: Vec<Sender<Arc<WorldDiff>>>,
+}
+
+impl WorldDiffs {
+    /// Returns a receiver of every diff from the next tick on.
+    ///
+    /// Diffs only describe changes, so a subscriber should subscribe before
+    /// the client logs in to know the whole world. Diffs are queued until they
+    /// are received; the subscription ends when the receiver is dropped.
+    pub fn subscribe(&mut self) -> Receiver<Arc<WorldDiff>> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Whether anything is subscribed.
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.is_empty()
+    }
+
+    /// Sends `diff` to every subscriber, forgetting those that are gone.
+    fn send(&mut self, diff: WorldDiff) {
+        let diff = Arc::new(diff);
+        self.subscribers
+            .retain(|subscriber| subscriber.send(diff.clone()).is_ok());
+    }
+}
+
+/// Plugin that builds a [`WorldDiff`] every game tick and sends it to the
+/// subscribers of [`WorldDiffs`].
+///
+/// Events are read in `Update`, in [`BrineSystems::WorldUpdate`], and each
+/// diff is sent in [`FixedUpdate`].
+///
+/// # Events
+///
+/// The plugin reads the following events:
+///
+/// * [`ChunkData`]
+/// * [`BlockChanged`]
+/// * [`EntitySpawned`], [`EntityMoved`] and [`EntitiesRemoved`]
+/// * [`Transfer`] and [`Reconfigure`], which remove every chunk and entity
+///
+/// # Resources
+///
+/// The plugin registers the following resources:
+///
+/// * [`WorldDiffs`]
+#[derive(Debug, Default)]
+pub struct WorldDiffPlugin {
+    stream: Option<SocketAddr>,
+}
+
+impl WorldDiffPlugin {
+    /// Also streams diffs as JSON lines to whatever connects to `addr`.
+    ///
+    /// One consumer is served at a time. Diffs are queued while none is
+    /// connected, so the first consumer gets every diff from the start, and
+    /// later ones get diffs from where the last one disconnected.
+    pub fn with_stream(mut self, addr: SocketAddr) -> Self {
+        self.stream = Some(addr);
+        self
+    }
+}
+
+impl Plugin for WorldDiffPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldDiffs>()
+            .init_resource::<WorldTracker>()
+            .add_systems(
+                Update,
+                (
+                    forget_world_on_reset,
+                    track_chunks,
+                    track_blocks,
+                    track_entities,
+                )
+                    .chain()
+                    .in_set(BrineSystems::WorldUpdate),
+            )
+            .add_systems(FixedUpdate, send_world_diff);
+
+        if let Some(addr) = self.stream {
+            let diffs = app.world_mut().resource_mut::<WorldDiffs>().subscribe();
+            if let Err(err) = start_stream(addr, diffs) {
+                warn!("Can't stream world diffs on {}: {}", addr, err);
+            }
+        }
+    }
+}
+
+/// What is loaded, and what changed since the last diff.
+#[derive(Resource, Debug, Default)]
+struct WorldTracker {
+    tick: u64,
+    chunks: HashSet<ChunkPos>,
+    entities: HashMap<i32, DVec3>,
+    pending: PendingDiff,
+}
+
+/// Changes since the last diff, folded together.
+#[derive(Debug, Default)]
+struct PendingDiff {
+    chunks_added: BTreeSet<ChunkPos>,
+    chunks_removed: BTreeSet<ChunkPos>,
+    chunk_data: Vec<Chunk>,
+    blocks_changed: BTreeMap<BlockPos, BlockState>,
+    entities_spawned: BTreeMap<i32, SpawnedEntity>,
+    entities_moved: BTreeMap<i32, DVec3>,
+    entities_removed: BTreeSet<i32>,
+}
+
+impl WorldTracker {
+    /// Removes every chunk and entity.
+    fn reset(&mut self) {
+        let pending = &mut self.pending;
+        for chunk in self.chunks.drain() {
+            if !pending.chunks_added.remove(&chunk) {
+                pending.chunks_removed.insert(chunk);
+            }
+        }
+        for (entity_id, _) in self.entities.drain() {
+            if pending.entities_spawned.remove(&entity_id).is_none() {
+                pending.entities_removed.insert(entity_id);
+            }
+        }
+        pending.chunk_data.clear();
+        pending.blocks_changed.clear();
+        pending.entities_moved.clear();
+    }
+
+    /// Records data received for a chunk. The chunk's data itself is only
+    /// kept if `keep_data` is set.
+    fn chunk_received(&mut self, chunk: &Chunk, keep_data: bool) {
+        let position = chunk.position();
+        if self.chunks.insert(position) {
+            self.pending.chunks_added.insert(position);
+        }
+
+        // The data is newer than any change to the blocks it covers.
+        let sections: HashSet<_> = chunk
+            .sections
+            .iter()
+            .map(|section| position.section(section.chunk_y.into()))
+            .collect();
+        let full = chunk.is_full();
+        self.pending.blocks_changed.retain(|block, _| {
+            block.chunk() != position || !(full || sections.contains(&block.section()))
+        });
+
+        if keep_data {
+            if full {
+                self.pending
+                    .chunk_data
+                    .retain(|data| data.position() != position);
+            }
+            self.pending.chunk_data.push(chunk.clone());
+        }
+    }
+
+    fn block_changed(&mut self, position: BlockPos, block_state: BlockState) {
+        if self.chunks.contains(&position.chunk()) {
+            self.pending.blocks_changed.insert(position, block_state);
+        }
+    }
+
+    fn entity_spawned(&mut self, entity: SpawnedEntity) {
+        self.entities.insert(entity.entity_id, entity.position);
+        self.pending.entities_moved.remove(&entity.entity_id);
+        self.pending
+            .entities_spawned
+            .insert(entity.entity_id, entity);
+    }
+
+    fn entity_moved(&mut self, entity_id: i32, position: &EntityPosition) {
+        let Some(current) = self.entities.get_mut(&entity_id) else {
+            return;
+        };
+        *current = match *position {
+            EntityPosition::Relative(offset) => *current + offset,
+            EntityPosition::Absolute(position) => position,
+        };
+
+        match self.pending.entities_spawned.get_mut(&entity_id) {
+            Some(spawned) => spawned.position = *current,
+            None => {
+                self.pending.entities_moved.insert(entity_id, *current);
+            }
+        }
+    }
+
+    fn entity_removed(&mut self, entity_id: i32) {
+        if self.entities.remove(&entity_id).is_none() {
+            return;
+        }
+        self.pending.entities_moved.remove(&entity_id);
+        if self.pending.entities_spawned.remove(&entity_id).is_none() {
+            self.pending.entities_removed.insert(entity_id);
+        }
+    }
+
+    /// Returns the diff of the next tick, and starts the one after.
+    fn next_diff(&mut self) -> WorldDiff {
+        self.tick += 1;
+        let pending = std::mem::take(&mut self.pending);
+        WorldDiff {
+            tick: self.tick,
+            chunks_added: pending.chunks_added.into_iter().collect(),
+            chunks_removed: pending.chunks_removed.into_iter().collect(),
+            chunk_data: pending.chunk_data,
+            blocks_changed: pending
+                .blocks_changed
+                .into_iter()
+                .map(|(position, block_state)| ChangedBlock {
+                    position,
+                    block_state,
+                })
+                .collect(),
+            entities_spawned: pending.entities_spawned.into_values().collect(),
+            entities_moved: pending
+                .entities_moved
+                .into_iter()
+                .map(|(entity_id, position)| MovedEntity {
+                    entity_id,
+                    position,
+                })
+                .collect(),
+            entities_removed: pending.entities_removed.into_iter().collect(),
+        }
+    }
+}
+
+fn forget_world_on_reset(
+    mut transfer_events: MessageReader<Transfer>,
+    mut reconfigure_events: MessageReader<Reconfigure>,
+    mut tracker: ResMut<WorldTracker>,
+) {
+    let transferred = transfer_events.read().count() > 0;
+    let reconfigured = reconfigure_events.read().count() > 0;
+
+    if transferred || reconfigured {
+        tracker.reset();
+    }
+}
+
+fn track_chunks(
+    mut chunk_events: MessageReader<ChunkData>,
+    diffs: Res<WorldDiffs>,
+    mut tracker: ResMut<WorldTracker>,
+) {
+    let keep_data = diffs.has_subscribers();
+    for event in chunk_events.read() {
+        tracker.chunk_received(&event.chunk_data, keep_data);
+    }
+}
+
+fn track_blocks(mut block_events: MessageReader<BlockChanged>, mut tracker: ResMut<WorldTracker>) {
+    for event in block_events.read() {
+        tracker.block_changed(event.position.into(), event.block_state);
+    }
+}
+
+fn track_entities(
+    mut spawn_events: MessageReader<EntitySpawned>,
+    mut move_events: MessageReader<EntityMoved>,
+    mut remove_events: MessageReader<EntitiesRemoved>,
+    mut tracker: ResMut<WorldTracker>,
+) {
+    for event in spawn_events.read() {
+        tracker.entity_spawned(SpawnedEntity {
+            entity_id: event.entity_id,
+            entity_type: event.entity_type,
+            position: event.position,
+        });
+    }
+    for event in move_events.read() {
+        if let Some(position) = &event.position {
+            tracker.entity_moved(event.entity_id, position);
+        }
+    }
+    for event in remove_events.read() {
+        for &entity_id in &event.entity_ids {
+            tracker.entity_removed(entity_id);
+        }
+    }
+}
+
+fn send_world_diff(mut tracker: ResMut<WorldTracker>, mut diffs: ResMut<WorldDiffs>) {
+    let diff = tracker.next_diff();
+    if !diff.is_empty() && diffs.has_subscribers() {
+        diffs.send(diff);
+    }
+}
+
+/// Streams `diffs` to consumers connecting to `addr`, on a thread of its own.
+fn start_stream(addr: SocketAddr, diffs: Receiver<Arc<WorldDiff>>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    info!("Streaming world diffs on {}", listener.local_addr()?);
+
+    thread::Builder::new()
+        .name(String::from("world diff stream"))
+        .spawn(move || loop {
+            let (stream, consumer) = match listener.accept() {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    error!("World diff stream failed to accept a consumer: {}", err);
+                    return;
+                }
+            };
+
+            debug!("Streaming world diffs to {}", consumer);
+            let mut writer = BufWriter::new(stream);
+            let result = diffs.iter().try_for_each(|diff| {
+                serde_json::to_writer(&mut writer, &*diff)?;
+                writer.write_all(b"\n")?;
+                writer.flush()
+            });
+            match result {
+                // The client has exited.
+                Ok(()) => return,
+                Err(err) => debug!("Stopped streaming world diffs to {}: {}", consumer, err),
+            }
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use brine_proto::{buffer::MessageBuffer, MessageBufferPlugin};
+
+    use super::*;
+
+    fn spawn(tracker: &mut WorldTracker, entity_id: i32, position: DVec3) {
+        tracker.entity_spawned(SpawnedEntity {
+            entity_id,
+            entity_type: 0,
+            position,
+        });
+    }
+
+    #[test]
+    fn changes_within_a_tick_are_folded_together() {
+        let mut tracker = WorldTracker::default();
+        spawn(&mut tracker, 1, DVec3::ZERO);
+        tracker.next_diff();
+
+        spawn(&mut tracker, 2, DVec3::ZERO);
+        tracker.entity_moved(2, &EntityPosition::Relative(DVec3::X));
+        tracker.entity_moved(1, &EntityPosition::Relative(DVec3::Y));
+        tracker.entity_moved(1, &EntityPosition::Relative(DVec3::Y));
+        spawn(&mut tracker, 3, DVec3::ZERO);
+        tracker.entity_removed(3);
+
+        let diff = tracker.next_diff();
+        assert_eq!(diff.tick, 2);
+        assert_eq!(
+            diff.entities_spawned,
+            [SpawnedEntity {
+                entity_id: 2,
+                entity_type: 0,
+                position: DVec3::X,
+            }]
+        );
+        assert_eq!(
+            diff.entities_moved,
+            [MovedEntity {
+                entity_id: 1,
+                position: DVec3::new(0.0, 2.0, 0.0),
+            }]
+        );
+        assert!(diff.entities_removed.is_empty());
+        assert!(tracker.next_diff().is_empty());
+    }
+
+    #[test]
+    fn reset_removes_only_what_was_already_reported() {
+        let mut tracker = WorldTracker::default();
+        tracker.chunk_received(&Chunk::empty(0, 0), false);
+        spawn(&mut tracker, 1, DVec3::ZERO);
+        tracker.next_diff();
+
+        tracker.chunk_received(&Chunk::empty(1, 0), false);
+        tracker.block_changed(BlockPos::new(1, 2, 3), BlockState(1));
+        spawn(&mut tracker, 2, DVec3::ZERO);
+        tracker.reset();
+        tracker.chunk_received(&Chunk::empty(0, 0), false);
+
+        let diff = tracker.next_diff();
+        assert_eq!(diff.chunks_removed, [ChunkPos::new(0, 0)]);
+        assert_eq!(diff.chunks_added, [ChunkPos::new(0, 0)]);
+        assert!(diff.blocks_changed.is_empty());
+        assert!(diff.entities_spawned.is_empty());
+        assert_eq!(diff.entities_removed, [1]);
+    }
+
+    #[test]
+    fn buffered_chunks_are_still_tracked() {
+        let mut app = App::new();
+        app.configure_sets(
+            Update,
+            (
+                BrineSystems::ProtocolDecode,
+                BrineSystems::WorldUpdate,
+                BrineSystems::ProtocolEncode,
+                BrineSystems::MeshBuild,
+            )
+                .chain(),
+        )
+        .add_message::<ChunkData>()
+        .add_message::<BlockChanged>()
+        .add_message::<EntitySpawned>()
+        .add_message::<EntityMoved>()
+        .add_message::<EntitiesRemoved>()
+        .add_message::<Transfer>()
+        .add_message::<Reconfigure>()
+        .add_plugins((
+            MessageBufferPlugin::<ChunkData>::new("chunks"),
+            WorldDiffPlugin::default(),
+        ));
+
+        app.world_mut().write_message(ChunkData {
+            chunk_data: Chunk::empty(1, 2),
+        });
+        app.update();
+
+        let tracker = app.world().resource::<WorldTracker>();
+        assert!(tracker.chunks.contains(&ChunkPos::new(1, 2)));
+        assert_eq!(app.world().resource::<MessageBuffer<ChunkData>>().len(), 1);
+    }
+}
@@ -0,0 +1,89 @@
+//! End-to-end tests against a real Minecraft server.
+//!
+//! These tests are ignored by default because they need a server to connect
+//! to. `cargo xtask run-test-server --version 1.21.4 --run-tests` starts one and
+//! runs them. To run them against a server that is already running:
+//!
+//! ```text
+//! BRINE_TEST_SERVER=localhost:25565 cargo test --test test_server -- --ignored
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use brine::{
+    app::{BrineAppBuilder, DEFAULT_SERVER},
+    login::GameState,
+};
+use brine_proto::event::clientbound::{ChunkData, Disconnect};
+
+const USERNAME: &str = "BrineTest";
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The test server's view distance of 4 sends a 9x9 area around spawn, so a
+/// full row of that means chunks are being streamed and decoded.
+const MIN_CHUNKS: usize = 9;
+
+#[derive(Resource, Default)]
+struct Received {
+    chunks: usize,
+    disconnect: Option<String>,
+}
+
+fn record_messages(
+    mut chunk_events: MessageReader<ChunkData>,
+    mut disconnect_events: MessageReader<Disconnect>,
+    mut received: ResMut<Received>,
+) {
+    received.chunks += chunk_events.read().count();
+
+    if let Some(disconnect) = disconnect_events.read().last() {
+        received.disconnect = Some(disconnect.reason.clone());
+    }
+}
+
+fn test_server_address() -> String {
+    std::env::var("BRINE_TEST_SERVER").unwrap_or_else(|_| String::from(DEFAULT_SERVER))
+}
+
+#[test]
+#[ignore = "needs a running server, see `cargo xtask run-test-server`"]
+fn login_and_receive_chunks() {
+    let server = test_server_address();
+
+    let mut app = BrineAppBuilder::new()
+        .headless()
+        .with_server(server.clone())
+        .with_username(USERNAME)
+        .build();
+    app.init_resource::<Received>()
+        .add_systems(Update, record_messages);
+    app.finish();
+    app.cleanup();
+
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        app.update();
+
+        let received = app.world().resource::<Received>();
+        if let Some(reason) = &received.disconnect {
+            panic!("Disconnected from {server}: {reason}");
+        }
+        if received.chunks >= MIN_CHUNKS {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "Received {} of {MIN_CHUNKS} chunks from {server} within {TIMEOUT:?}",
+            received.chunks
+        );
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(
+        app.world().resource::<State<GameState>>().get(),
+        &GameState::Play
+    );
+}
@@ -0,0 +1,327 @@
+//! Comparing the vanilla assets of two Minecraft versions.
+//!
+//! Reports which block models, block textures, and blockstates were added,
+//! removed, or changed, and flags new or changed files that use features the
+//! `brine_asset` baking path doesn't handle yet.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+/// Element rotation angles that `EighthRotation` accepts; anything else makes
+/// baking panic.
+const SUPPORTED_ELEMENT_ANGLES: [f64; 5] = [-45.0, -22.5, 0.0, 22.5, 45.0];
+
+/// Face rotations that the cuboid bakery can map texture coordinates for.
+const SUPPORTED_FACE_ROTATIONS: [u64; 4] = [0, 90, 180, 270];
+
+const KNOWN_MODEL_KEYS: [&str; 7] = [
+    "parent",
+    "display",
+    "textures",
+    "elements",
+    "ambientocclusion",
+    "gui_light",
+    "overrides",
+];
+const KNOWN_ELEMENT_KEYS: [&str; 5] = ["from", "to", "rotation", "shade", "faces"];
+const KNOWN_FACE_KEYS: [&str; 5] = ["uv", "texture", "cullface", "rotation", "tintindex"];
+const KNOWN_BLOCKSTATE_KEYS: [&str; 2] = ["variants", "multipart"];
+
+#[derive(Clone, Copy)]
+enum Category {
+    Blockstates,
+    BlockModels,
+    BlockTextures,
+}
+
+impl Category {
+    const ALL: [Self; 3] = [Self::Blockstates, Self::BlockModels, Self::BlockTextures];
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Blockstates => "Blockstates",
+            Self::BlockModels => "Block models",
+            Self::BlockTextures => "Block textures",
+        }
+    }
+
+    /// Directory under `assets/minecraft`.
+    fn dir(self) -> &'static str {
+        match self {
+            Self::Blockstates => "blockstates",
+            Self::BlockModels => "models/block",
+            Self::BlockTextures => "textures/block",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Blockstates | Self::BlockModels => "json",
+            Self::BlockTextures => "png",
+        }
+    }
+}
+
+struct CategoryDiff {
+    category: Category,
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+    /// Added or changed files that baking can't fully handle, with reasons.
+    unsupported: Vec<(String, Vec<String>)>,
+}
+
+/// Diffs `assets/<from>` against `assets/<to>` and prints a report.
+pub fn diff_assets(root: &Path, from: &str, to: &str) -> Result<()> {
+    let from_dir = namespace_dir(root, from)?;
+    let to_dir = namespace_dir(root, to)?;
+
+    println!("# Asset changes from {from} to {to}");
+
+    for category in Category::ALL {
+        let diff = diff_category(category, &from_dir, &to_dir)?;
+        print_diff(&diff);
+    }
+
+    Ok(())
+}
+
+fn namespace_dir(root: &Path, version: &str) -> Result<PathBuf> {
+    let dir = root
+        .join("assets")
+        .join(version)
+        .join("assets")
+        .join("minecraft");
+    if !dir.exists() {
+        bail!(
+            "{} is missing, run `cargo xtask fetch-assets --version {version}` first",
+            dir.display()
+        );
+    }
+    Ok(dir)
+}
+
+fn diff_category(category: Category, from_dir: &Path, to_dir: &Path) -> Result<CategoryDiff> {
+    let from_files = list_files(&from_dir.join(category.dir()), category.extension())?;
+    let to_files = list_files(&to_dir.join(category.dir()), category.extension())?;
+
+    let mut diff = CategoryDiff {
+        category,
+        added: to_files.difference(&from_files).cloned().collect(),
+        removed: from_files.difference(&to_files).cloned().collect(),
+        changed: Vec::new(),
+        unsupported: Vec::new(),
+    };
+
+    for name in from_files.intersection(&to_files) {
+        let relative = format!("{}/{name}.{}", category.dir(), category.extension());
+        let old = fs::read(from_dir.join(&relative))?;
+        let new = fs::read(to_dir.join(&relative))?;
+        if old != new {
+            diff.changed.push(name.clone());
+        }
+    }
+
+    for name in diff.added.iter().chain(&diff.changed) {
+        let relative = format!("{}/{name}.{}", category.dir(), category.extension());
+        let path = to_dir.join(&relative);
+        let reasons = match category {
+            Category::Blockstates => check_blockstate(&read_json(&path)?, to_dir),
+            Category::BlockModels => check_model(&read_json(&path)?),
+            Category::BlockTextures => check_texture(&path),
+        };
+        if !reasons.is_empty() {
+            diff.unsupported.push((name.clone(), reasons));
+        }
+    }
+
+    Ok(diff)
+}
+
+fn print_diff(diff: &CategoryDiff) {
+    println!();
+    println!(
+        "## {} (+{} -{} ~{})",
+        diff.category.title(),
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+
+    for (heading, names) in [
+        ("Added", &diff.added),
+        ("Removed", &diff.removed),
+        ("Changed", &diff.changed),
+    ] {
+        if names.is_empty() {
+            continue;
+        }
+        println!();
+        println!("### {heading}");
+        for name in names {
+            println!("- {name}");
+        }
+    }
+
+    if !diff.unsupported.is_empty() {
+        println!();
+        println!("### Not supported by baking yet");
+        for (name, reasons) in &diff.unsupported {
+            println!("- {name}: {}", reasons.join("; "));
+        }
+    }
+}
+
+fn check_blockstate(blockstate: &Value, to_dir: &Path) -> Vec<String> {
+    let mut reasons = unknown_keys(blockstate, &KNOWN_BLOCKSTATE_KEYS);
+
+    // Every variant and multipart case names one model or a list of weighted
+    // alternatives.
+    let mut models = Vec::new();
+    if let Some(variants) = blockstate.get("variants").and_then(Value::as_object) {
+        models.extend(variants.values());
+    }
+    if let Some(cases) = blockstate.get("multipart").and_then(Value::as_array) {
+        models.extend(cases.iter().filter_map(|case| case.get("apply")));
+    }
+
+    let mut missing = BTreeSet::new();
+    for model in models {
+        let alternatives = match model {
+            Value::Array(alternatives) => alternatives.iter().collect(),
+            other => vec![other],
+        };
+        for alternative in alternatives {
+            let Some(name) = alternative.get("model").and_then(Value::as_str) else {
+                continue;
+            };
+            let path = strip_namespace(name);
+            if !to_dir.join("models").join(format!("{path}.json")).exists() {
+                missing.insert(name.to_string());
+            }
+        }
+    }
+    reasons.extend(
+        missing
+            .into_iter()
+            .map(|name| format!("references missing model {name}")),
+    );
+
+    reasons
+}
+
+fn check_model(model: &Value) -> Vec<String> {
+    let mut reasons = unknown_keys(model, &KNOWN_MODEL_KEYS);
+
+    if let Some(parent) = model.get("parent").and_then(Value::as_str) {
+        if strip_namespace(parent).starts_with("builtin/") {
+            reasons.push(format!("builtin parent {parent} is not baked"));
+        }
+    }
+
+    let elements = model.get("elements").and_then(Value::as_array);
+    for element in elements.into_iter().flatten() {
+        for reason in unknown_keys(element, &KNOWN_ELEMENT_KEYS) {
+            reasons.push(format!("element {reason}"));
+        }
+
+        if let Some(rotation) = element.get("rotation") {
+            let angle = rotation.get("angle").and_then(Value::as_f64);
+            if let Some(angle) = angle.filter(|angle| !SUPPORTED_ELEMENT_ANGLES.contains(angle)) {
+                reasons.push(format!("element rotation angle {angle} (baking panics)"));
+            }
+            if rotation.get("rescale").and_then(Value::as_bool) == Some(true) {
+                reasons.push(String::from(
+                    "rescaled element rotation (rescale is ignored)",
+                ));
+            }
+        }
+
+        let faces = element.get("faces").and_then(Value::as_object);
+        for face in faces.into_iter().flat_map(|faces| faces.values()) {
+            for reason in unknown_keys(face, &KNOWN_FACE_KEYS) {
+                reasons.push(format!("face {reason}"));
+            }
+            let rotation = face.get("rotation").and_then(Value::as_u64);
+            if let Some(rotation) =
+                rotation.filter(|rotation| !SUPPORTED_FACE_ROTATIONS.contains(rotation))
+            {
+                reasons.push(format!("face rotation {rotation} (face is dropped)"));
+            }
+        }
+    }
+
+    reasons.sort();
+    reasons.dedup();
+    reasons
+}
+
+fn check_texture(path: &Path) -> Vec<String> {
+    let mut meta_path = path.as_os_str().to_owned();
+    meta_path.push(".mcmeta");
+
+    if Path::new(&meta_path).exists() {
+        vec![String::from(
+            "animated texture (only drawn as a single image)",
+        )]
+    } else {
+        Vec::new()
+    }
+}
+
+fn unknown_keys(value: &Value, known: &[&str]) -> Vec<String> {
+    value
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.keys())
+        .filter(|key| !known.contains(&key.as_str()))
+        .map(|key| format!("unrecognized key \"{key}\""))
+        .collect()
+}
+
+fn strip_namespace(location: &str) -> &str {
+    location.strip_prefix("minecraft:").unwrap_or(location)
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Lists files with `extension` under `dir` by their path relative to `dir`,
+/// without the extension.
+fn list_files(dir: &Path, extension: &str) -> Result<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+    if dir.exists() {
+        collect_files(dir, dir, extension, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_files(
+    base: &Path,
+    dir: &Path,
+    extension: &str,
+    files: &mut BTreeSet<String>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(base, &path, extension, files)?;
+        } else if path.extension().is_some_and(|ext| ext == extension) {
+            let relative = path.strip_prefix(base).unwrap_or(&path).with_extension("");
+            let name = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Packaging everything `setup` downloads into one archive for offline machines.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::source::{minecraft_data_archive_path, version_json_path, Source};
+
+/// Downloads the metadata and jars for `versions` and the minecraft-data
+/// archive for `reference` from `source`, and writes them to a zip at `output`
+/// in the mirror layout that `--mirror` accepts.
+pub fn export_bundle(
+    source: &Source,
+    versions: &[String],
+    reference: &str,
+    include_server: bool,
+    output: &Path,
+) -> Result<()> {
+    let staging = tempfile::tempdir()?;
+    let root = staging.path();
+
+    for version in versions {
+        let version_json = source.version_json(version)?;
+        let details = serde_json::from_value(version_json.clone())
+            .with_context(|| format!("failed to parse metadata for {version}"))?;
+
+        let version_dir = root.join("versions").join(version);
+        fs::create_dir_all(&version_dir)?;
+        fs::write(
+            root.join(version_json_path(version)),
+            serde_json::to_string_pretty(&version_json)?,
+        )?;
+
+        println!("Downloading {version} client.jar");
+        source.fetch_client_jar(version, &details, &version_dir.join("client.jar"))?;
+
+        if include_server {
+            println!("Downloading {version} server.jar");
+            source.fetch_server_jar(version, &details, &version_dir.join("server.jar"))?;
+        }
+    }
+
+    println!("Downloading minecraft-data ({reference})");
+    fs::create_dir_all(root.join("minecraft-data"))?;
+    source.fetch_minecraft_data(
+        reference,
+        &root.join(minecraft_data_archive_path(reference)),
+    )?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_zip(root, output)?;
+
+    println!(
+        "Bundle written to {}; use it with `--mirror {}`",
+        output.display(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Zips the contents of `dir`. Everything in a bundle is already compressed or
+/// tiny, so entries are stored as-is.
+fn write_zip(dir: &Path, output: &Path) -> Result<()> {
+    let file =
+        File::create(output).with_context(|| format!("failed to create {}", output.display()))?;
+    let mut writer = ZipWriter::new(file);
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let mut entries = fs::read_dir(&current)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.path());
+
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+
+            let name = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            writer.start_file(name, options)?;
+            io::copy(&mut File::open(&path)?, &mut writer)?;
+        }
+    }
+
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! File downloads with progress, retries, resume, and checksum validation.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{blocking::Client, header, StatusCode};
+use sha1_smol::Sha1;
+
+/// Total number of attempts before giving up on a download.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubled after every failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Minimum time between progress updates.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Downloads `url` to `destination`.
+///
+/// The file is written to `<destination>.part` and only renamed into place
+/// once it is complete and, if `expected_sha1` is given, its SHA-1 matches.
+/// Failed attempts are retried with exponential backoff, resuming from the
+/// partial file when the server supports range requests.
+pub fn download(url: &str, destination: &Path, expected_sha1: Option<&str>) -> Result<()> {
+    let client = Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .timeout(None)
+        .build()?;
+    let part_path = part_path(destination);
+
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        match try_download(&client, url, &part_path, expected_sha1) {
+            Ok(()) => {
+                fs::rename(&part_path, destination).with_context(|| {
+                    format!(
+                        "failed to move {} to {}",
+                        part_path.display(),
+                        destination.display()
+                    )
+                })?;
+                return Ok(());
+            }
+            Err(err) if attempt < MAX_ATTEMPTS => {
+                eprintln!(
+                    "Download attempt {attempt}/{MAX_ATTEMPTS} failed: {err:#}; retrying in {backoff:?}"
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            Err(err) => {
+                return Err(err.context(format!(
+                    "failed to download {url} after {MAX_ATTEMPTS} attempts"
+                )))
+            }
+        }
+    }
+
+    unreachable!("the last attempt always returns")
+}
+
+fn part_path(destination: &Path) -> PathBuf {
+    let mut part = destination.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+fn try_download(
+    client: &Client,
+    url: &str,
+    part_path: &Path,
+    expected_sha1: Option<&str>,
+) -> Result<()> {
+    let mut hasher = Sha1::new();
+    let mut downloaded = hash_existing(part_path, &mut hasher)?;
+
+    let mut request = client.get(url);
+    if downloaded > 0 {
+        request = request.header(header::RANGE, format!("bytes={downloaded}-"));
+    }
+    let mut response = request.send()?;
+
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file doesn't match what the server has; start over.
+        fs::remove_file(part_path)?;
+        bail!("server rejected resuming at byte {downloaded}");
+    }
+
+    let resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+    if !resumed {
+        response = response.error_for_status()?;
+        // The server sent the whole file, so start over.
+        hasher.reset();
+        downloaded = 0;
+    }
+    let total = response.content_length().map(|len| len + downloaded);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part_path)
+        .with_context(|| format!("failed to open {}", part_path.display()))?;
+
+    let name = part_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map_or("", |name| name.trim_end_matches(".part"));
+    let mut progress = Progress::new(name, downloaded, total);
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = response.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        hasher.update(&buffer[..read]);
+        progress.advance(read as u64);
+    }
+    file.flush()?;
+    progress.finish();
+
+    if let Some(total) = total {
+        if progress.downloaded != total {
+            bail!(
+                "connection closed after {} of {total} bytes",
+                progress.downloaded
+            );
+        }
+    }
+
+    if let Some(expected) = expected_sha1 {
+        let actual = hasher.digest().to_string();
+        if !actual.eq_ignore_ascii_case(expected) {
+            // Don't resume from corrupt data on the next attempt.
+            fs::remove_file(part_path)?;
+            return Err(anyhow!("SHA-1 mismatch: expected {expected}, got {actual}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Feeds an existing partial download into `hasher`, returning its length.
+fn hash_existing(part_path: &Path, hasher: &mut Sha1) -> Result<u64> {
+    let mut file = match File::open(part_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut buffer = vec![0; 64 * 1024];
+    let mut len = 0;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(len);
+        }
+        hasher.update(&buffer[..read]);
+        len += read as u64;
+    }
+}
+
+/// Prints download progress to stderr, in place when it is a terminal.
+///
+/// Lines are labelled with the file name, since `setup` downloads two files
+/// at once.
+struct Progress<'a> {
+    name: &'a str,
+    downloaded: u64,
+    total: Option<u64>,
+    last_print: Instant,
+    interactive: bool,
+}
+
+impl<'a> Progress<'a> {
+    fn new(name: &'a str, downloaded: u64, total: Option<u64>) -> Self {
+        Self {
+            name,
+            downloaded,
+            total,
+            last_print: Instant::now(),
+            interactive: io::stderr().is_terminal(),
+        }
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.downloaded += bytes;
+        if self.interactive && self.last_print.elapsed() >= PROGRESS_INTERVAL {
+            self.print();
+            self.last_print = Instant::now();
+        }
+    }
+
+    fn finish(&self) {
+        if self.interactive {
+            self.print();
+            eprintln!();
+        }
+    }
+
+    fn print(&self) {
+        let downloaded = mebibytes(self.downloaded);
+        match self.total {
+            Some(total) if total > 0 => eprint!(
+                "\r  {}: {downloaded:.1} / {:.1} MiB ({:.0}%)",
+                self.name,
+                mebibytes(total),
+                self.downloaded as f64 / total as f64 * 100.0
+            ),
+            _ => eprint!("\r  {}: {downloaded:.1} MiB", self.name),
+        }
+    }
+}
+
+fn mebibytes(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
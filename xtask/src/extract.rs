@@ -0,0 +1,109 @@
+//! Zip extraction across all cores.
+//!
+//! Client jars and GitHub archives hold thousands of small deflated files, so
+//! extracting them is bound by decompression and file creation rather than by
+//! reading the archive. A `ZipArchive` reads one entry at a time, so every
+//! worker opens the archive itself and takes entries from a shared counter.
+
+use std::fs::{self, File};
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use anyhow::{Context, Result};
+use zip::ZipArchive;
+
+/// Extracts the entries of the zip at `zip_path` for which `map` returns a
+/// path, to that path under `destination`. `map` is given each entry's path
+/// within the archive.
+///
+/// Returns the mapped paths of the files written, in no particular order.
+pub fn extract(
+    zip_path: &Path,
+    destination: &Path,
+    map: impl Fn(&Path) -> Option<PathBuf> + Sync,
+) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(destination)?;
+
+    let entries = open(zip_path)?.len();
+    let workers = thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(entries.max(1));
+    let next_entry = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    let written = thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let result = extract_entries(zip_path, destination, &map, &next_entry, &failed);
+                    if result.is_err() {
+                        // Stop the other workers early.
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    result
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|err| panic::resume_unwind(err))
+            })
+            .collect::<Result<Vec<_>>>()
+    })
+    .with_context(|| format!("failed to extract {}", zip_path.display()))?;
+
+    Ok(written.into_iter().flatten().collect())
+}
+
+/// Extracts entries until there are none left or another worker failed.
+fn extract_entries(
+    zip_path: &Path,
+    destination: &Path,
+    map: &(impl Fn(&Path) -> Option<PathBuf> + Sync),
+    next_entry: &AtomicUsize,
+    failed: &AtomicBool,
+) -> Result<Vec<PathBuf>> {
+    let mut archive = open(zip_path)?;
+    let mut written = Vec::new();
+
+    while !failed.load(Ordering::Relaxed) {
+        let index = next_entry.fetch_add(1, Ordering::Relaxed);
+        if index >= archive.len() {
+            break;
+        }
+
+        let mut entry = archive.by_index(index)?;
+        let Some(relative) = entry.enclosed_name().and_then(map) else {
+            continue;
+        };
+
+        let out_path = destination.join(&relative);
+        if entry.name().ends_with('/') {
+            fs::create_dir_all(&out_path)?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut outfile = File::create(&out_path)
+            .with_context(|| format!("failed to create {}", out_path.display()))?;
+        io::copy(&mut entry, &mut outfile)?;
+        written.push(relative);
+    }
+
+    Ok(written)
+}
+
+fn open(zip_path: &Path) -> Result<ZipArchive<File>> {
+    ZipArchive::new(File::open(zip_path)?)
+        .with_context(|| format!("failed to open {}", zip_path.display()))
+}
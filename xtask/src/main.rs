@@ -1,16 +1,27 @@
-use std::fs::{self, File};
-use std::io;
+use std::fs;
+use std::panic;
 use std::path::{Path, PathBuf};
+use std::thread;
 
 use anyhow::{anyhow, bail, Context, Result};
 use clap::{Parser, Subcommand};
 use reqwest::blocking;
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::json;
-use tempfile::NamedTempFile;
-use zip::ZipArchive;
 
+use crate::source::Source;
+
+mod asset_diff;
+mod bundle;
+mod download;
+mod extract;
 mod protocol;
+mod protocol_diff;
+mod prune;
+mod registries;
+mod source;
+mod test_server;
+mod verify;
 
 const VERSION_MANIFEST_URL: &str =
     "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
@@ -37,14 +48,23 @@ enum Command {
         /// Re-download even if the target directory already exists.
         #[arg(long)]
         force: bool,
+        /// Mirror directory, bundle zip, or base URL to use instead of Mojang's servers.
+        #[arg(long)]
+        mirror: Option<String>,
     },
     /// Refresh the bundled minecraft-data files from PrismarineJS.
     FetchMinecraftData {
         /// Git reference to download (branch, tag, or commit).
         #[arg(long, default_value = "master")]
         reference: String,
+        /// Mirror directory, bundle zip, or base URL to use instead of GitHub.
+        #[arg(long)]
+        mirror: Option<String>,
     },
     /// Refresh minecraft-data and download the requested game's assets.
+    ///
+    /// Both are fetched at the same time, so one downloads while the other
+    /// extracts.
     Setup {
         #[arg(long)]
         version: String,
@@ -52,6 +72,29 @@ enum Command {
         reference: String,
         #[arg(long)]
         force: bool,
+        /// Mirror directory, bundle zip, or base URL to download from.
+        #[arg(long)]
+        mirror: Option<String>,
+    },
+    /// Package everything `setup` downloads into one zip for offline machines.
+    ///
+    /// Copy the zip over and run `cargo xtask setup --mirror <zip>` there.
+    ExportBundle {
+        /// Minecraft versions to include (e.g., 1.21.4). May be repeated.
+        #[arg(long = "version", required = true)]
+        versions: Vec<String>,
+        /// minecraft-data git reference to include.
+        #[arg(long, default_value = "master")]
+        reference: String,
+        /// Also include the server jars (for `run-test-server`).
+        #[arg(long)]
+        server: bool,
+        /// Where to write the bundle. Defaults to target/brine-bundle.zip.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Mirror to build the bundle from instead of the official servers.
+        #[arg(long)]
+        mirror: Option<String>,
     },
     /// Parse minecraft-data proto definitions into a packet index.
     GenerateProtocol {
@@ -59,22 +102,152 @@ enum Command {
         #[arg(long)]
         version: String,
     },
+    /// Generate typed block, item and entity type IDs for `brine_data`.
+    ///
+    /// Writes `crates/brine_data/src/registries/generated.rs`, which is
+    /// compiled with `brine_data`'s `registries` feature.
+    GenerateRegistries {
+        /// Minecraft version identifier (e.g., 1.21.4).
+        #[arg(long)]
+        version: String,
+    },
+    /// Compare the packet indices of two versions and write a change report.
+    ///
+    /// Writes `<from>_to_<to>.md` and `<from>_to_<to>.json`.
+    DiffProtocol {
+        /// Version to compare from (e.g., 1.20.4).
+        #[arg(long)]
+        from: String,
+        /// Version to compare to (e.g., 1.21.4).
+        #[arg(long)]
+        to: String,
+        /// Directory to write the reports to. Defaults to target/generated/protocol/diff.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check downloaded assets and minecraft-data for a version.
+    ///
+    /// Downloads the client jar to compare against its SHA-1 in the Mojang
+    /// manifest and against the extracted assets, checks that protocol.json
+    /// parses, and reports textures and parent models that models reference but
+    /// that are missing.
+    Verify {
+        /// Minecraft version identifier (e.g., 1.21.4).
+        #[arg(long)]
+        version: String,
+        /// Skip the checks that need to download the client jar.
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Report block model, texture, and blockstate changes between two versions.
+    ///
+    /// Both versions' assets must have been fetched. New or changed files that
+    /// use features the baking path doesn't support yet are flagged.
+    DiffAssets {
+        /// Version to compare from (e.g., 1.20.4).
+        #[arg(long)]
+        from: String,
+        /// Version to compare to (e.g., 1.21.4).
+        #[arg(long)]
+        to: String,
+    },
+    /// Download the official server jar and run it for end-to-end testing.
+    ///
+    /// The server runs in offline mode on a superflat world with a fixed seed.
+    /// Running it accepts the Minecraft EULA on your behalf.
+    RunTestServer {
+        /// Minecraft version identifier (e.g., 1.21.4).
+        #[arg(long)]
+        version: String,
+        /// Port for the server to listen on.
+        #[arg(long, default_value_t = 25565)]
+        port: u16,
+        /// Java executable used to run the server.
+        #[arg(long, default_value = "java")]
+        java: String,
+        /// Delete the existing world so it is regenerated from the seed.
+        #[arg(long)]
+        fresh: bool,
+        /// Run the client integration tests once the server is ready, then stop it.
+        #[arg(long)]
+        run_tests: bool,
+        /// Mirror directory, bundle zip, or base URL to get the server jar from.
+        #[arg(long)]
+        mirror: Option<String>,
+    },
+    /// Delete minecraft-data files that brine doesn't use for the given versions.
+    PruneMinecraftData {
+        /// Minecraft versions to keep data for (e.g., 1.21.4). May be repeated.
+        #[arg(long = "version", required = true)]
+        versions: Vec<String>,
+        /// Report what would be removed without deleting anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
-        Command::FetchAssets { version, force } => fetch_assets(&version, force),
-        Command::FetchMinecraftData { reference } => fetch_minecraft_data(&reference),
+        Command::FetchAssets {
+            version,
+            force,
+            mirror,
+        } => fetch_assets(&version, force, &Source::new(mirror.as_deref())?),
+        Command::FetchMinecraftData { reference, mirror } => {
+            fetch_minecraft_data(&reference, &Source::new(mirror.as_deref())?)
+        }
         Command::Setup {
             version,
             reference,
             force,
+            mirror,
+        } => {
+            let source = Source::new(mirror.as_deref())?;
+            setup(&version, &reference, force, &source)
+        }
+        Command::ExportBundle {
+            versions,
+            reference,
+            server,
+            output,
+            mirror,
         } => {
-            fetch_minecraft_data(&reference)?;
-            fetch_assets(&version, force)
+            let output =
+                output.unwrap_or_else(|| workspace_root().join("target").join("brine-bundle.zip"));
+            bundle::export_bundle(
+                &Source::new(mirror.as_deref())?,
+                &versions,
+                &reference,
+                server,
+                &output,
+            )
         }
         Command::GenerateProtocol { version } => generate_protocol(&version),
+        Command::GenerateRegistries { version } => generate_registries(&version),
+        Command::DiffProtocol { from, to, output } => diff_protocol(&from, &to, output),
+        Command::Verify { version, offline } => verify(&version, offline),
+        Command::DiffAssets { from, to } => asset_diff::diff_assets(&workspace_root(), &from, &to),
+        Command::RunTestServer {
+            version,
+            port,
+            java,
+            fresh,
+            run_tests,
+            mirror,
+        } => test_server::run_test_server(
+            &Source::new(mirror.as_deref())?,
+            &test_server::TestServerOptions {
+                version,
+                port,
+                java,
+                fresh,
+                run_tests,
+            },
+        ),
+        Command::PruneMinecraftData { versions, dry_run } => {
+            prune_minecraft_data(&versions, dry_run)
+        }
     }
 }
 
@@ -85,7 +258,7 @@ fn workspace_root() -> PathBuf {
         .to_path_buf()
 }
 
-fn fetch_assets(version: &str, force: bool) -> Result<()> {
+fn fetch_assets(version: &str, force: bool, source: &Source) -> Result<()> {
     let root = workspace_root();
     let output_dir = root.join("assets").join(version);
 
@@ -106,24 +279,15 @@ fn fetch_assets(version: &str, force: bool) -> Result<()> {
         }
     }
 
-    println!("Downloading Minecraft {version} client metadata");
-    let manifest: VersionManifest = fetch_json(VERSION_MANIFEST_URL)?;
-    let entry = manifest
-        .versions
-        .into_iter()
-        .find(|v| v.id == version)
-        .ok_or_else(|| anyhow!("Version {version} not found in the Mojang manifest"))?;
-
-    let details: VersionDetails = fetch_json(&entry.url)?;
-    let client_url = details.downloads.client.url;
+    let details = source.version_details(version)?;
 
     println!("Downloading client.jar (this may take a moment)");
-    let temp_file = NamedTempFile::new()?;
-    download_to_path(&client_url, temp_file.path())
-        .with_context(|| format!("failed to download client jar from {client_url}"))?;
+    let temp_dir = tempfile::tempdir()?;
+    let jar_path = temp_dir.path().join("client.jar");
+    source.fetch_client_jar(version, &details, &jar_path)?;
 
     println!("Extracting assets and data to {}", output_dir.display());
-    let pack_exists = extract_client_payload(temp_file.path(), &output_dir)?;
+    let pack_exists = extract_client_payload(&jar_path, &output_dir)?;
     ensure_pack_metadata(
         &output_dir,
         version,
@@ -135,7 +299,7 @@ fn fetch_assets(version: &str, force: bool) -> Result<()> {
     Ok(())
 }
 
-fn fetch_minecraft_data(reference: &str) -> Result<()> {
+fn fetch_minecraft_data(reference: &str, source: &Source) -> Result<()> {
     let root = workspace_root();
     let base = root.join("third_party").join("minecraft-data-rs");
     if !base.exists() {
@@ -151,37 +315,32 @@ fn fetch_minecraft_data(reference: &str) -> Result<()> {
     }
     fs::create_dir_all(&target)?;
 
-    let url = format!("{MINECRAFT_DATA_ZIP_URL}/{}", reference);
     println!("Downloading minecraft-data ({reference})");
-    let temp_file = NamedTempFile::new()?;
-    download_to_path(&url, temp_file.path())
-        .with_context(|| format!("failed to download minecraft-data archive from {url}"))?;
+    let temp_dir = tempfile::tempdir()?;
+    let archive_path = temp_dir.path().join("minecraft-data.zip");
+    source.fetch_minecraft_data(reference, &archive_path)?;
 
     println!("Extracting minecraft-data into {}", target.display());
-    extract_repo_archive(temp_file.path(), &target)?;
+    extract_repo_archive(&archive_path, &target)?;
     println!("minecraft-data refreshed from {reference}");
     Ok(())
 }
 
+fn setup(version: &str, reference: &str, force: bool, source: &Source) -> Result<()> {
+    thread::scope(|scope| {
+        let minecraft_data = scope.spawn(|| fetch_minecraft_data(reference, source));
+        let assets = fetch_assets(version, force, source);
+        let minecraft_data = minecraft_data
+            .join()
+            .unwrap_or_else(|err| panic::resume_unwind(err));
+        minecraft_data.and(assets)
+    })
+}
+
 fn generate_protocol(version: &str) -> Result<()> {
     let root = workspace_root();
-    let proto_dir = root
-        .join("third_party")
-        .join("minecraft-data-rs")
-        .join("minecraft-data")
-        .join("data")
-        .join("pc")
-        .join(version);
-    let proto_path = proto_dir.join("protocol.json");
-    if !proto_path.exists() {
-        bail!(
-            "missing {}, run `cargo xtask fetch-minecraft-data` first",
-            proto_path.display()
-        );
-    }
-
-    let protocol_version = lookup_protocol_version(&root, version)?;
-    let index = protocol::build_packet_index(&proto_path, version, protocol_version)?;
+    let index = load_packet_index(&root, version)?;
+    let protocol_version = index.protocol_version;
     let out_dir = root
         .join("target")
         .join("generated")
@@ -203,15 +362,161 @@ fn generate_protocol(version: &str) -> Result<()> {
     Ok(())
 }
 
-fn download_to_path(url: &str, destination: &Path) -> Result<()> {
-    let mut response = blocking::get(url).with_context(|| format!("failed to download {url}"))?;
+fn generate_registries(version: &str) -> Result<()> {
+    let root = workspace_root();
+    let data_dir = root
+        .join("third_party")
+        .join("minecraft-data-rs")
+        .join("minecraft-data")
+        .join("data");
+    let output = root
+        .join("crates")
+        .join("brine_data")
+        .join("src")
+        .join("registries")
+        .join("generated.rs");
+    registries::generate_registries(&data_dir, version, &output)?;
+    println!("Registries for {version} written to {}", output.display());
+    Ok(())
+}
 
-    let mut writer = File::create(destination)
-        .with_context(|| format!("failed to create {}", destination.display()))?;
-    io::copy(&mut response, &mut writer)?;
+fn verify(version: &str, offline: bool) -> Result<()> {
+    let root = workspace_root();
+    let assets_dir = root.join("assets").join(version);
+    if !assets_dir.exists() {
+        bail!(
+            "{} is missing, run `cargo xtask fetch-assets --version {version}` first",
+            assets_dir.display()
+        );
+    }
+
+    let mut reports = Vec::new();
+
+    if offline {
+        println!("Skipping client jar checks (--offline)");
+    } else {
+        reports.push(verify_client_jar(version, &assets_dir)?);
+    }
+
+    let proto_path = protocol_json_path(&root, version);
+    let protocol_version = lookup_protocol_version(&root, version)?;
+    reports.push(verify::verify_protocol(
+        &proto_path,
+        version,
+        protocol_version,
+    ));
+
+    reports.push(verify::verify_model_references(&assets_dir)?);
+
+    for report in &reports {
+        report.print();
+    }
+
+    let problems: usize = reports.iter().map(|report| report.problems.len()).sum();
+    if problems > 0 {
+        bail!("verification of {version} found {problems} problems");
+    }
+    println!("Assets and data for {version} verified");
+    Ok(())
+}
+
+fn verify_client_jar(version: &str, assets_dir: &Path) -> Result<verify::Report> {
+    let details = fetch_version_details(version)?;
+    let client = details.downloads.client;
+
+    // The download fails if the jar doesn't match the SHA-1 in the manifest.
+    println!("Downloading client.jar to compare against");
+    let temp_dir = tempfile::tempdir()?;
+    let jar_path = temp_dir.path().join("client.jar");
+    download::download(&client.url, &jar_path, Some(&client.sha1))?;
+
+    verify::verify_extracted_assets(&jar_path, assets_dir)
+}
+
+fn prune_minecraft_data(versions: &[String], dry_run: bool) -> Result<()> {
+    let root = workspace_root();
+    let checkout = root
+        .join("third_party")
+        .join("minecraft-data-rs")
+        .join("minecraft-data");
+    if !checkout.exists() {
+        bail!(
+            "{} is missing, run `cargo xtask fetch-minecraft-data` first",
+            checkout.display()
+        );
+    }
+
+    let summary = prune::prune_minecraft_data(&checkout, versions, dry_run)?;
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    println!(
+        "{verb} {} files ({:.1} MiB), kept {} files for {}",
+        summary.files_removed,
+        summary.bytes_removed as f64 / (1024.0 * 1024.0),
+        summary.files_kept,
+        versions.join(", ")
+    );
+    Ok(())
+}
+
+fn protocol_json_path(root: &Path, version: &str) -> PathBuf {
+    root.join("third_party")
+        .join("minecraft-data-rs")
+        .join("minecraft-data")
+        .join("data")
+        .join("pc")
+        .join(version)
+        .join("protocol.json")
+}
+
+fn load_packet_index(root: &Path, version: &str) -> Result<protocol::PacketIndex> {
+    let proto_path = protocol_json_path(root, version);
+    if !proto_path.exists() {
+        bail!(
+            "missing {}, run `cargo xtask fetch-minecraft-data` first",
+            proto_path.display()
+        );
+    }
+
+    let protocol_version = lookup_protocol_version(root, version)?;
+    protocol::build_packet_index(&proto_path, version, protocol_version)
+}
+
+fn diff_protocol(from: &str, to: &str, output: Option<PathBuf>) -> Result<()> {
+    let root = workspace_root();
+    let from_index = load_packet_index(&root, from)?;
+    let to_index = load_packet_index(&root, to)?;
+    let diff = protocol_diff::diff_indices(&from_index, &to_index);
+
+    let out_dir = output.unwrap_or_else(|| {
+        root.join("target")
+            .join("generated")
+            .join("protocol")
+            .join("diff")
+    });
+    fs::create_dir_all(&out_dir)?;
+    let markdown_path = out_dir.join(format!("{from}_to_{to}.md"));
+    let json_path = out_dir.join(format!("{from}_to_{to}.json"));
+    fs::write(&markdown_path, protocol_diff::render_markdown(&diff))?;
+    fs::write(&json_path, serde_json::to_string_pretty(&diff)?)?;
+
+    let count = |f: fn(&protocol_diff::DirectionDiff) -> usize| -> usize {
+        diff.changes.iter().map(f).sum()
+    };
+    println!(
+        "Protocol {from} -> {to}: {} added, {} removed, {} changed packets\nReport written to {} and {}",
+        count(|change| change.added.len()),
+        count(|change| change.removed.len()),
+        count(|change| change.changed.len()),
+        markdown_path.display(),
+        json_path.display()
+    );
     Ok(())
 }
 
+fn fetch_version_details(version: &str) -> Result<VersionDetails> {
+    Source::Upstream.version_details(version)
+}
+
 fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T> {
     blocking::get(url)
         .with_context(|| format!("failed to download {url}"))?
@@ -220,79 +525,24 @@ fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T> {
 }
 
 fn extract_client_payload(jar_path: &Path, destination: &Path) -> Result<bool> {
-    fs::create_dir_all(destination)?;
-
-    let file = File::open(jar_path)?;
-    let mut archive = ZipArchive::new(file)?;
-    let mut pack_found = false;
-
-    for index in 0..archive.len() {
-        let mut entry = archive.by_index(index)?;
-        let Some(rel_path) = entry.enclosed_name().map(|p| p.to_owned()) else {
-            continue;
-        };
-        let rel_str = rel_path.to_string_lossy();
-        let include = rel_str.starts_with("assets/")
-            || rel_str.starts_with("data/")
-            || rel_str == "pack.mcmeta";
-        if !include {
-            continue;
-        }
+    let written = extract::extract(jar_path, destination, |path| {
+        is_client_payload(&path.to_string_lossy()).then(|| path.to_owned())
+    })?;
 
-        let out_path = destination.join(&*rel_path);
-        if entry.name().ends_with('/') {
-            fs::create_dir_all(&out_path)?;
-            continue;
-        }
-
-        if let Some(parent) = out_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        let mut outfile = File::create(&out_path)?;
-        io::copy(&mut entry, &mut outfile)?;
-
-        if rel_str == "pack.mcmeta" {
-            pack_found = true;
-        }
-    }
+    Ok(written.iter().any(|path| path == Path::new("pack.mcmeta")))
+}
 
-    Ok(pack_found)
+/// Whether a file in the client jar is one that `fetch-assets` extracts.
+fn is_client_payload(rel_path: &str) -> bool {
+    rel_path.starts_with("assets/") || rel_path.starts_with("data/") || rel_path == "pack.mcmeta"
 }
 
 fn extract_repo_archive(zip_path: &Path, destination: &Path) -> Result<()> {
-    let file = File::open(zip_path)?;
-    let mut archive = ZipArchive::new(file)?;
-
-    for index in 0..archive.len() {
-        let mut entry = archive.by_index(index)?;
-        let Some(path) = entry.enclosed_name().map(|p| p.to_owned()) else {
-            continue;
-        };
-
-        // Skip the top-level directory that GitHub archives wrap files in.
-        let mut components = path.components();
-        if components.next().is_none() {
-            continue;
-        }
-        let relative: PathBuf = components.collect();
-        if relative.as_os_str().is_empty() {
-            continue;
-        }
-
-        let out_path = destination.join(relative);
-        if entry.name().ends_with('/') {
-            fs::create_dir_all(&out_path)?;
-            continue;
-        }
-
-        if let Some(parent) = out_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        let mut outfile = File::create(&out_path)?;
-        io::copy(&mut entry, &mut outfile)?;
-    }
+    // Skip the top-level directory that GitHub archives wrap files in.
+    extract::extract(zip_path, destination, |path| {
+        let relative: PathBuf = path.components().skip(1).collect();
+        (!relative.as_os_str().is_empty()).then_some(relative)
+    })?;
 
     Ok(())
 }
@@ -318,11 +568,14 @@ struct VersionDetails {
 #[derive(Deserialize)]
 struct VersionDownloads {
     client: VersionFile,
+    #[serde(default)]
+    server: Option<VersionFile>,
 }
 
 #[derive(Deserialize)]
 struct VersionFile {
     url: String,
+    sha1: String,
 }
 
 #[derive(Deserialize)]
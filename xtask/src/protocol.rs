@@ -894,7 +894,7 @@ fn parse_direction(
     Ok(out)
 }
 
-fn to_pascal_case(name: &str) -> String {
+pub(crate) fn to_pascal_case(name: &str) -> String {
     name.split(|c: char| !c.is_ascii_alphanumeric())
         .filter(|part| !part.is_empty())
         .map(|part| {
@@ -1088,14 +1088,14 @@ fn direction_rust_name(direction: DirectionKind) -> &'static str {
     }
 }
 
-fn direction_label(direction: DirectionKind) -> &'static str {
+pub(crate) fn direction_label(direction: DirectionKind) -> &'static str {
     match direction {
         DirectionKind::Clientbound => "clientbound",
         DirectionKind::Serverbound => "serverbound",
     }
 }
 
-fn format_packet_id(id: i32) -> String {
+pub(crate) fn format_packet_id(id: i32) -> String {
     format!("{id:#04x}")
 }
 
@@ -1303,7 +1303,34 @@ fn render_tag_value(tag_type: &str, tag: i32) -> String {
 
 const HELPERS_PRELUDE: &str = r#"
 use crate::protocol::*;
-use std::io;
+use std::cell::Cell;
+use std::io::{self, Read};
+
+thread_local! {
+    static MAX_ARRAY_LENGTH: Cell<usize> = const { Cell::new(1 << 20) };
+}
+
+/// Elements reserved up front for an array, however many it claims to have.
+const MAX_ARRAY_PREALLOCATION: usize = 1024;
+
+/// Runs `f` with the length of arrays read on this thread limited to `max`
+/// elements (bytes, for byte arrays).
+pub fn with_max_array_length<T>(max: usize, f: impl FnOnce() -> T) -> T {
+    let previous = MAX_ARRAY_LENGTH.with(|limit| limit.replace(max));
+    let result = f();
+    MAX_ARRAY_LENGTH.with(|limit| limit.set(previous));
+    result
+}
+
+fn check_array_length(length: i32) -> Result<usize, Error> {
+    let max = MAX_ARRAY_LENGTH.with(Cell::get);
+    match usize::try_from(length) {
+        Ok(length) if length <= max => Ok(length),
+        _ => Err(Error::Err(format!(
+            "array length {length} is outside of 0..={max}"
+        ))),
+    }
+}
 
 // Helper types emitted by the generator; these should be moved or replaced with
 // hand-tuned implementations as needed.
@@ -1321,8 +1348,9 @@ where
 {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Self, Error> {
         let count_varint = Count::read_from(buf)?;
-        let count: i32 = VarInt::from(count_varint).0;
-        let mut values = Vec::with_capacity(count as usize);
+        let count = check_array_length(VarInt::from(count_varint).0)?;
+        // Grow as elements are actually read rather than trusting the count.
+        let mut values = Vec::with_capacity(count.min(MAX_ARRAY_PREALLOCATION));
         for _ in 0..count {
             values.push(T::read_from(buf)?);
         }
@@ -1333,7 +1361,7 @@ where
     }
 
     fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
-        let count = VarInt(self.values.len() as i32);
+        let count = Count::from(VarInt(self.values.len() as i32));
         count.write_to(buf)?;
         for v in &self.values {
             v.write_to(buf)?;
@@ -1386,9 +1414,12 @@ where
 {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Self, Error> {
         let len_varint = Count::read_from(buf)?;
-        let len: i32 = VarInt::from(len_varint).0;
-        let mut data = vec![0u8; len as usize];
-        buf.read_exact(&mut data)?;
+        let len = check_array_length(VarInt::from(len_varint).0)?;
+        let mut data = Vec::with_capacity(len.min(MAX_ARRAY_PREALLOCATION));
+        buf.by_ref().take(len as u64).read_to_end(&mut data)?;
+        if data.len() != len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "byte array ends early").into());
+        }
         Ok(Self {
             data,
             _phantom: std::marker::PhantomData,
@@ -1396,7 +1427,7 @@ where
     }
 
     fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
-        let len = VarInt(self.data.len() as i32);
+        let len = Count::from(VarInt(self.data.len() as i32));
         len.write_to(buf)?;
         buf.write_all(&self.data)?;
         Ok(())
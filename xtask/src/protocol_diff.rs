@@ -0,0 +1,305 @@
+//! Comparing the packet indices of two protocol versions.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::protocol::{
+    direction_label, format_packet_id, DirectionPackets, PacketField, PacketIndex, PacketSummary,
+};
+
+#[derive(Debug, Serialize)]
+pub struct ProtocolDiff {
+    pub from: VersionInfo,
+    pub to: VersionInfo,
+    /// Only the state/direction pairs that changed.
+    pub changes: Vec<DirectionDiff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub minecraft_version: String,
+    pub protocol_version: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectionDiff {
+    pub state: String,
+    pub direction: &'static str,
+    pub added: Vec<PacketRef>,
+    pub removed: Vec<PacketRef>,
+    pub changed: Vec<PacketChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PacketRef {
+    pub name: String,
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PacketChange {
+    pub name: String,
+    pub old_id: i32,
+    pub new_id: i32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields_removed: Vec<String>,
+    /// Fields present in both versions whose type changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields_changed: Vec<String>,
+    /// Whether the common fields appear in a different order.
+    pub fields_reordered: bool,
+}
+
+impl PacketChange {
+    fn is_empty(&self) -> bool {
+        self.old_id == self.new_id
+            && self.fields_added.is_empty()
+            && self.fields_removed.is_empty()
+            && self.fields_changed.is_empty()
+            && !self.fields_reordered
+    }
+}
+
+/// Compares two packet indices, matching packets by state, direction, and name.
+pub fn diff_indices(from: &PacketIndex, to: &PacketIndex) -> ProtocolDiff {
+    let from_directions = directions_by_key(from);
+    let to_directions = directions_by_key(to);
+
+    let keys: BTreeSet<_> = from_directions
+        .keys()
+        .chain(to_directions.keys())
+        .copied()
+        .collect();
+
+    let changes = keys
+        .into_iter()
+        .map(|(state, direction)| {
+            let old = from_directions
+                .get(&(state, direction))
+                .map(|d| d.packets.as_slice())
+                .unwrap_or_default();
+            let new = to_directions
+                .get(&(state, direction))
+                .map(|d| d.packets.as_slice())
+                .unwrap_or_default();
+            diff_direction(state, direction, old, new)
+        })
+        .filter(|diff| {
+            !diff.added.is_empty() || !diff.removed.is_empty() || !diff.changed.is_empty()
+        })
+        .collect();
+
+    ProtocolDiff {
+        from: VersionInfo {
+            minecraft_version: from.minecraft_version.clone(),
+            protocol_version: from.protocol_version,
+        },
+        to: VersionInfo {
+            minecraft_version: to.minecraft_version.clone(),
+            protocol_version: to.protocol_version,
+        },
+        changes,
+    }
+}
+
+fn directions_by_key(index: &PacketIndex) -> BTreeMap<(&str, &'static str), &DirectionPackets> {
+    index
+        .states
+        .iter()
+        .flat_map(|state| {
+            state.directions.iter().map(move |direction| {
+                (
+                    (state.state.as_str(), direction_label(direction.direction)),
+                    direction,
+                )
+            })
+        })
+        .collect()
+}
+
+fn diff_direction(
+    state: &str,
+    direction: &'static str,
+    old: &[PacketSummary],
+    new: &[PacketSummary],
+) -> DirectionDiff {
+    let old_by_name: BTreeMap<_, _> = old.iter().map(|p| (p.name.as_str(), p)).collect();
+    let new_by_name: BTreeMap<_, _> = new.iter().map(|p| (p.name.as_str(), p)).collect();
+
+    let added = new
+        .iter()
+        .filter(|p| !old_by_name.contains_key(p.name.as_str()))
+        .map(packet_ref)
+        .collect();
+    let removed = old
+        .iter()
+        .filter(|p| !new_by_name.contains_key(p.name.as_str()))
+        .map(packet_ref)
+        .collect();
+    let changed = new
+        .iter()
+        .filter_map(|new_packet| {
+            let old_packet = old_by_name.get(new_packet.name.as_str())?;
+            let change = diff_packet(old_packet, new_packet);
+            (!change.is_empty()).then_some(change)
+        })
+        .collect();
+
+    DirectionDiff {
+        state: state.to_string(),
+        direction,
+        added,
+        removed,
+        changed,
+    }
+}
+
+fn diff_packet(old: &PacketSummary, new: &PacketSummary) -> PacketChange {
+    let old_fields: BTreeMap<_, _> = old
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), &f.ty))
+        .collect();
+    let new_fields: BTreeMap<_, _> = new
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), &f.ty))
+        .collect();
+
+    let fields_added = new
+        .fields
+        .iter()
+        .filter(|f| !old_fields.contains_key(f.name.as_str()))
+        .map(|f| f.name.clone())
+        .collect();
+    let fields_removed = old
+        .fields
+        .iter()
+        .filter(|f| !new_fields.contains_key(f.name.as_str()))
+        .map(|f| f.name.clone())
+        .collect();
+    let fields_changed = new
+        .fields
+        .iter()
+        .filter(|f| {
+            old_fields
+                .get(f.name.as_str())
+                .is_some_and(|old_ty| **old_ty != f.ty)
+        })
+        .map(|f| f.name.clone())
+        .collect();
+
+    let common_order = |fields: &[PacketField], other: &BTreeMap<&str, _>| {
+        fields
+            .iter()
+            .filter(|f| other.contains_key(f.name.as_str()))
+            .map(|f| f.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let fields_reordered =
+        common_order(&old.fields, &new_fields) != common_order(&new.fields, &old_fields);
+
+    PacketChange {
+        name: new.name.clone(),
+        old_id: old.id,
+        new_id: new.id,
+        fields_added,
+        fields_removed,
+        fields_changed,
+        fields_reordered,
+    }
+}
+
+fn packet_ref(packet: &PacketSummary) -> PacketRef {
+    PacketRef {
+        name: packet.name.clone(),
+        id: packet.id,
+    }
+}
+
+/// Renders the diff as a markdown report.
+pub fn render_markdown(diff: &ProtocolDiff) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Protocol changes from {} ({}) to {} ({})",
+        diff.from.minecraft_version,
+        diff.from.protocol_version,
+        diff.to.minecraft_version,
+        diff.to.protocol_version
+    );
+
+    if diff.changes.is_empty() {
+        let _ = writeln!(out, "\nNo packet changes.");
+        return out;
+    }
+
+    for change in &diff.changes {
+        let _ = writeln!(out, "\n## {} {}", change.state, change.direction);
+
+        if !change.added.is_empty() {
+            let _ = writeln!(out, "\n### Added\n");
+            for packet in &change.added {
+                let _ = writeln!(out, "- `{}` ({})", packet.name, format_packet_id(packet.id));
+            }
+        }
+
+        if !change.removed.is_empty() {
+            let _ = writeln!(out, "\n### Removed\n");
+            for packet in &change.removed {
+                let _ = writeln!(out, "- `{}` ({})", packet.name, format_packet_id(packet.id));
+            }
+        }
+
+        if !change.changed.is_empty() {
+            let _ = writeln!(out, "\n### Changed\n");
+            let _ = writeln!(out, "| Packet | ID | Fields |");
+            let _ = writeln!(out, "| --- | --- | --- |");
+            for packet in &change.changed {
+                let id = if packet.old_id == packet.new_id {
+                    format_packet_id(packet.new_id)
+                } else {
+                    format!(
+                        "{} → {}",
+                        format_packet_id(packet.old_id),
+                        format_packet_id(packet.new_id)
+                    )
+                };
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} |",
+                    packet.name,
+                    id,
+                    describe_field_changes(packet)
+                );
+            }
+        }
+    }
+
+    out
+}
+
+fn describe_field_changes(packet: &PacketChange) -> String {
+    let mut parts = Vec::new();
+    if !packet.fields_added.is_empty() {
+        parts.push(format!("added {}", packet.fields_added.join(", ")));
+    }
+    if !packet.fields_removed.is_empty() {
+        parts.push(format!("removed {}", packet.fields_removed.join(", ")));
+    }
+    if !packet.fields_changed.is_empty() {
+        parts.push(format!("retyped {}", packet.fields_changed.join(", ")));
+    }
+    if packet.fields_reordered {
+        parts.push(String::from("reordered"));
+    }
+    if parts.is_empty() {
+        String::from("unchanged")
+    } else {
+        parts.join("; ")
+    }
+}
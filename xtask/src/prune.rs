@@ -0,0 +1,201 @@
+//! Pruning the vendored minecraft-data checkout down to what brine uses.
+//!
+//! minecraft-data ships every table for every edition and version, and all of
+//! it is compiled into `minecraft-data-rs`. Brine only reads a handful of
+//! tables for the versions it supports, so everything else can be deleted to
+//! shrink the repository, the build, and the binary.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Value};
+
+/// Data tables that brine reads through `brine_data` or the xtask commands.
+pub const KEPT_TABLES: [&str; 5] = ["blocks", "entities", "items", "protocol", "version"];
+
+/// Edition whose data is kept; everything else (e.g. bedrock) is removed.
+const EDITION: &str = "pc";
+
+#[derive(Debug, Default)]
+pub struct PruneSummary {
+    pub files_removed: usize,
+    pub bytes_removed: u64,
+    pub files_kept: usize,
+}
+
+/// Deletes every file under `checkout` that isn't needed for `versions`, and
+/// rewrites `data/dataPaths.json` to only list what remains.
+///
+/// With `dry_run`, nothing is modified and the summary reports what would be
+/// removed.
+pub fn prune_minecraft_data(
+    checkout: &Path,
+    versions: &[String],
+    dry_run: bool,
+) -> Result<PruneSummary> {
+    let data_dir = checkout.join("data");
+    let data_paths_path = data_dir.join("dataPaths.json");
+    let data_paths: Value = serde_json::from_str(
+        &fs::read_to_string(&data_paths_path)
+            .with_context(|| format!("failed to read {}", data_paths_path.display()))?,
+    )
+    .with_context(|| format!("failed to parse {}", data_paths_path.display()))?;
+
+    let (kept_paths, kept_files) = resolve_kept_files(&data_paths, versions)?;
+
+    let mut summary = PruneSummary::default();
+    let mut entries = fs::read_dir(checkout)
+        .with_context(|| format!("failed to read {}", checkout.display()))?
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let path = entry.path();
+        if path == data_dir {
+            prune_dir(&data_dir, &data_dir, &kept_files, dry_run, &mut summary)?;
+        } else if entry.file_type()?.is_dir() {
+            // Top-level files (README, LICENSE) are tiny; directories hold
+            // docs, schemas, and tooling that nothing here uses.
+            remove_path(&path, dry_run, &mut summary)?;
+        } else {
+            summary.files_kept += 1;
+        }
+    }
+
+    if !dry_run {
+        let mut rewritten = Map::new();
+        rewritten.insert(EDITION.to_string(), Value::Object(kept_paths));
+        fs::write(
+            &data_paths_path,
+            serde_json::to_string_pretty(&Value::Object(rewritten))?,
+        )
+        .with_context(|| format!("failed to write {}", data_paths_path.display()))?;
+    }
+
+    Ok(summary)
+}
+
+/// Resolves the files under `data/` that must be kept, along with the pruned
+/// contents of `dataPaths.json`.
+///
+/// Files are given relative to `data/` using `/` separators.
+fn resolve_kept_files(
+    data_paths: &Value,
+    versions: &[String],
+) -> Result<(Map<String, Value>, BTreeSet<String>)> {
+    let edition = data_paths
+        .get(EDITION)
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("dataPaths.json has no \"{EDITION}\" section"))?;
+
+    let mut kept_paths = Map::new();
+    let mut kept_files = BTreeSet::from(["dataPaths.json".to_string()]);
+
+    for version in versions {
+        let tables = edition
+            .get(version)
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow!("version {version} is not listed in dataPaths.json"))?;
+
+        let mut kept_tables = Map::new();
+        for table in KEPT_TABLES {
+            let Some(dir) = tables.get(table).and_then(Value::as_str) else {
+                continue;
+            };
+            kept_files.insert(format!("{dir}/{table}.json"));
+            kept_tables.insert(table.to_string(), Value::String(dir.to_string()));
+        }
+
+        kept_paths.insert(version.clone(), Value::Object(kept_tables));
+    }
+
+    Ok((kept_paths, kept_files))
+}
+
+fn prune_dir(
+    data_dir: &Path,
+    dir: &Path,
+    kept_files: &BTreeSet<String>,
+    dry_run: bool,
+    summary: &mut PruneSummary,
+) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let path = entry.path();
+        let relative = relative_name(data_dir, &path);
+
+        if is_common_data(&relative) {
+            // Shared tables such as protocolVersions.json are small and are
+            // read to resolve versions.
+            count_files(&path, &mut summary.files_kept)?;
+        } else if entry.file_type()?.is_dir() {
+            prune_dir(data_dir, &path, kept_files, dry_run, summary)?;
+            if !dry_run && fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        } else if kept_files.contains(&relative) {
+            summary.files_kept += 1;
+        } else {
+            remove_path(&path, dry_run, summary)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_common_data(relative: &str) -> bool {
+    relative == format!("{EDITION}/common") || relative.starts_with(&format!("{EDITION}/common/"))
+}
+
+fn relative_name(data_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(data_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn remove_path(path: &Path, dry_run: bool, summary: &mut PruneSummary) -> Result<()> {
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+    for file in &files {
+        summary.bytes_removed += fs::metadata(file)?.len();
+    }
+    summary.files_removed += files.len();
+
+    if !dry_run {
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+        .with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+fn count_files(path: &Path, count: &mut usize) -> Result<()> {
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+    *count += files.len();
+    Ok(())
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_files(&entry?.path(), files)?;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Generating typed block, item and entity type IDs for `brine_data`.
+//!
+//! Numeric IDs change between versions, so they can't be written into code
+//! by hand without going stale. This writes one version's IDs from
+//! minecraft-data as constants (`block::STONE`, the block's default state)
+//! and enums (`block::BlockKind::Stone`) into
+//! `crates/brine_data/src/registries/generated.rs`, which `brine_data`
+//! compiles with its `registries` feature.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+use crate::protocol::to_pascal_case;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockRecord {
+    id: u16,
+    name: String,
+    default_state: u16,
+}
+
+#[derive(Deserialize)]
+struct NamedRecord {
+    id: u16,
+    name: String,
+}
+
+/// One entry of a generated registry.
+struct Entry {
+    id: u16,
+    name: String,
+    /// Value of the entry's constant, if it isn't the ID.
+    constant: Option<u16>,
+}
+
+/// One generated module.
+struct Registry {
+    module: &'static str,
+    what: &'static str,
+    kind: &'static str,
+    id_type: &'static str,
+    constant_type: &'static str,
+    constant_doc: &'static str,
+    entries: Vec<Entry>,
+}
+
+/// Reads the blocks, items and entity types of `version` from the
+/// minecraft-data checkout at `data_dir` and writes the generated module to
+/// `output`.
+pub fn generate_registries(data_dir: &Path, version: &str, output: &Path) -> Result<()> {
+    let blocks: Vec<BlockRecord> = read_table(data_dir, version, "blocks")?;
+    let items: Vec<NamedRecord> = read_table(data_dir, version, "items")?;
+    let entities: Vec<NamedRecord> = read_table(data_dir, version, "entities")?;
+
+    let registries = [
+        Registry {
+            module: "block",
+            what: "blocks",
+            kind: "BlockKind",
+            id_type: "BlockId",
+            constant_type: "BlockStateId",
+            constant_doc: "Default state of each block, as found in chunk data.",
+            entries: blocks
+                .into_iter()
+                .map(|block| Entry {
+                    id: block.id,
+                    name: block.name,
+                    constant: Some(block.default_state),
+                })
+                .collect(),
+        },
+        Registry {
+            module: "item",
+            what: "items",
+            kind: "ItemKind",
+            id_type: "ItemId",
+            constant_type: "ItemId",
+            constant_doc: "ID of each item.",
+            entries: named_entries(items),
+        },
+        Registry {
+            module: "entity",
+            what: "entity types",
+            kind: "EntityKind",
+            id_type: "EntityTypeId",
+            constant_type: "EntityTypeId",
+            constant_doc: "ID of each entity type.",
+            entries: named_entries(entities),
+        },
+    ];
+
+    let mut source = String::new();
+    writeln!(
+        source,
+        "// @generated by xtask::generate-registries for Minecraft {version}"
+    )?;
+    writeln!(source, "// Do not edit by hand.")?;
+    writeln!(source)?;
+    writeln!(source, "/// Minecraft version the IDs are for.")?;
+    writeln!(source, "pub const VERSION: &str = \"{version}\";")?;
+    for registry in &registries {
+        writeln!(source)?;
+        write_registry(&mut source, registry)?;
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, source).with_context(|| format!("failed to write {}", output.display()))?;
+    Ok(())
+}
+
+fn named_entries(records: Vec<NamedRecord>) -> Vec<Entry> {
+    records
+        .into_iter()
+        .map(|record| Entry {
+            id: record.id,
+            name: record.name,
+            constant: None,
+        })
+        .collect()
+}
+
+/// Reads `<table>.json` for `version`, wherever `dataPaths.json` says it is.
+fn read_table<T: DeserializeOwned>(data_dir: &Path, version: &str, table: &str) -> Result<Vec<T>> {
+    let data_paths_path = data_dir.join("dataPaths.json");
+    let data_paths: Value =
+        serde_json::from_str(&fs::read_to_string(&data_paths_path).with_context(|| {
+            format!(
+                "failed to read {}, run `cargo xtask fetch-minecraft-data` first",
+                data_paths_path.display()
+            )
+        })?)
+        .with_context(|| format!("failed to parse {}", data_paths_path.display()))?;
+
+    let dir = data_paths
+        .pointer(&format!("/pc/{version}/{table}"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("minecraft-data has no {table} for {version}"))?;
+    let path = data_dir.join(dir).join(format!("{table}.json"));
+
+    serde_json::from_str(
+        &fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?,
+    )
+    .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn write_registry(source: &mut String, registry: &Registry) -> Result<()> {
+    let Registry {
+        module,
+        what,
+        kind,
+        id_type,
+        constant_type,
+        constant_doc,
+        entries,
+    } = registry;
+
+    let mut entries: Vec<&Entry> = entries.iter().collect();
+    entries.sort_by_key(|entry| entry.id);
+    let mut variants = BTreeSet::new();
+    for entry in &entries {
+        if !variants.insert(variant_name(&entry.name)) {
+            bail!("two {what} are both named {}", entry.name);
+        }
+    }
+
+    let imports = if id_type == constant_type {
+        id_type.to_string()
+    } else {
+        format!("{{{id_type}, {constant_type}}}")
+    };
+
+    writeln!(source, "/// IDs of the {what}.")?;
+    writeln!(source, "pub mod {module} {{")?;
+    writeln!(source, "    use crate::{imports};")?;
+    writeln!(source)?;
+
+    writeln!(source, "    // {constant_doc}")?;
+    for entry in &entries {
+        writeln!(
+            source,
+            "    pub const {}: {constant_type} = {constant_type}({});",
+            constant_name(&entry.name),
+            entry.constant.unwrap_or(entry.id)
+        )?;
+    }
+    writeln!(source)?;
+
+    writeln!(source, "    /// The {what}, by ID.")?;
+    writeln!(
+        source,
+        "    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]"
+    )?;
+    writeln!(source, "    #[repr(u16)]")?;
+    writeln!(source, "    pub enum {kind} {{")?;
+    for entry in &entries {
+        writeln!(
+            source,
+            "        {} = {},",
+            variant_name(&entry.name),
+            entry.id
+        )?;
+    }
+    writeln!(source, "    }}")?;
+    writeln!(source)?;
+
+    writeln!(source, "    impl {kind} {{")?;
+    writeln!(source, "        /// All the {what}, in ID order.")?;
+    writeln!(
+        source,
+        "        pub const ALL: [Self; {}] = [",
+        entries.len()
+    )?;
+    for entry in &entries {
+        writeln!(source, "            Self::{},", variant_name(&entry.name))?;
+    }
+    writeln!(source, "        ];")?;
+    writeln!(source)?;
+
+    writeln!(source, "        #[inline]")?;
+    writeln!(source, "        pub const fn id(self) -> {id_type} {{")?;
+    writeln!(source, "            {id_type}(self as u16)")?;
+    writeln!(source, "        }}")?;
+    writeln!(source)?;
+
+    writeln!(source, "        pub const fn name(self) -> &'static str {{")?;
+    writeln!(source, "            match self {{")?;
+    for entry in &entries {
+        writeln!(
+            source,
+            "                Self::{} => \"{}\",",
+            variant_name(&entry.name),
+            entry.name
+        )?;
+    }
+    writeln!(source, "            }}")?;
+    writeln!(source, "        }}")?;
+    writeln!(source)?;
+
+    if constant_type != id_type {
+        writeln!(
+            source,
+            "        pub const fn default_state(self) -> {constant_type} {{"
+        )?;
+        writeln!(source, "            match self {{")?;
+        for entry in &entries {
+            writeln!(
+                source,
+                "                Self::{} => {},",
+                variant_name(&entry.name),
+                constant_name(&entry.name)
+            )?;
+        }
+        writeln!(source, "            }}")?;
+        writeln!(source, "        }}")?;
+        writeln!(source)?;
+    }
+
+    writeln!(
+        source,
+        "        pub fn from_id(id: {id_type}) -> Option<Self> {{"
+    )?;
+    writeln!(source, "            Self::ALL")?;
+    writeln!(
+        source,
+        "                .binary_search_by_key(&id.0, |kind| *kind as u16)"
+    )?;
+    writeln!(source, "                .ok()")?;
+    writeln!(source, "                .map(|index| Self::ALL[index])")?;
+    writeln!(source, "        }}")?;
+    writeln!(source)?;
+
+    writeln!(
+        source,
+        "        pub fn from_name(name: &str) -> Option<Self> {{"
+    )?;
+    writeln!(
+        source,
+        "            Self::ALL.into_iter().find(|kind| kind.name() == name)"
+    )?;
+    writeln!(source, "        }}")?;
+    writeln!(source, "    }}")?;
+    writeln!(source, "}}")?;
+    Ok(())
+}
+
+fn constant_name(name: &str) -> String {
+    let constant = name.to_ascii_uppercase();
+    if constant.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{constant}")
+    } else {
+        constant
+    }
+}
+
+fn variant_name(name: &str) -> String {
+    let variant = to_pascal_case(name);
+    if variant.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{variant}")
+    } else {
+        variant
+    }
+}
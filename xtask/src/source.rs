@@ -0,0 +1,208 @@
+//! Where downloads come from: the official servers or a local/remote mirror.
+//!
+//! A mirror is a directory (or a zip of one, as written by `export-bundle`) or
+//! a base URL with this layout:
+//!
+//! ```text
+//! versions/<version>.json         version metadata from piston-meta
+//! versions/<version>/client.jar
+//! versions/<version>/server.jar   optional
+//! minecraft-data/<reference>.zip  PrismarineJS/minecraft-data archive
+//! ```
+//!
+//! A `/` in a minecraft-data reference (e.g. a `feature/x` branch) is
+//! written as `_` in its archive's name, which stays one file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use tempfile::TempDir;
+
+use crate::{
+    download, extract, fetch_json, verify::sha1_file, VersionDetails, VersionFile, VersionManifest,
+    MINECRAFT_DATA_ZIP_URL, VERSION_MANIFEST_URL,
+};
+
+pub enum Source {
+    /// Mojang's piston-meta and GitHub.
+    Upstream,
+    /// A mirror directory on disk.
+    Directory {
+        root: PathBuf,
+        /// Keeps an extracted bundle alive for as long as it is used.
+        _extracted: Option<TempDir>,
+    },
+    /// A mirror served over HTTP(S).
+    Url(String),
+}
+
+impl Source {
+    /// Uses `mirror` if given, or the official servers otherwise.
+    ///
+    /// URLs are recognized by their `http://` or `https://` prefix; anything
+    /// else is a path to a mirror directory or bundle zip.
+    pub fn new(mirror: Option<&str>) -> Result<Self> {
+        let Some(mirror) = mirror else {
+            return Ok(Self::Upstream);
+        };
+
+        if mirror.starts_with("http://") || mirror.starts_with("https://") {
+            return Ok(Self::Url(mirror.trim_end_matches('/').to_string()));
+        }
+
+        let path = PathBuf::from(mirror);
+        if path.is_dir() {
+            Ok(Self::Directory {
+                root: path,
+                _extracted: None,
+            })
+        } else if path.is_file() {
+            let extracted = tempfile::tempdir()?;
+            println!("Extracting bundle {}", path.display());
+            extract_zip(&path, extracted.path())?;
+            Ok(Self::Directory {
+                root: extracted.path().to_path_buf(),
+                _extracted: Some(extracted),
+            })
+        } else {
+            bail!("mirror {} does not exist", path.display())
+        }
+    }
+
+    /// Fetches the raw version metadata JSON for `version`.
+    pub fn version_json(&self, version: &str) -> Result<Value> {
+        println!("Downloading Minecraft {version} metadata");
+        match self {
+            Self::Upstream => {
+                let manifest: VersionManifest = fetch_json(VERSION_MANIFEST_URL)?;
+                let entry = manifest
+                    .versions
+                    .into_iter()
+                    .find(|v| v.id == version)
+                    .ok_or_else(|| anyhow!("Version {version} not found in the Mojang manifest"))?;
+                fetch_json(&entry.url)
+            }
+            Self::Directory { root, .. } => {
+                let path = root.join(version_json_path(version));
+                let contents = fs::read_to_string(&path).with_context(|| {
+                    format!("Version {version} not found in mirror ({})", path.display())
+                })?;
+                serde_json::from_str(&contents)
+                    .with_context(|| format!("failed to parse {}", path.display()))
+            }
+            Self::Url(base) => fetch_json(&format!("{base}/{}", version_json_path(version))),
+        }
+    }
+
+    pub fn version_details(&self, version: &str) -> Result<VersionDetails> {
+        serde_json::from_value(self.version_json(version)?)
+            .with_context(|| format!("failed to parse metadata for {version}"))
+    }
+
+    /// Fetches the client jar described by `details` to `destination`.
+    pub fn fetch_client_jar(
+        &self,
+        version: &str,
+        details: &VersionDetails,
+        destination: &Path,
+    ) -> Result<()> {
+        self.fetch_jar(
+            version,
+            "client.jar",
+            &details.downloads.client,
+            destination,
+        )
+    }
+
+    /// Fetches the server jar described by `details` to `destination`.
+    pub fn fetch_server_jar(
+        &self,
+        version: &str,
+        details: &VersionDetails,
+        destination: &Path,
+    ) -> Result<()> {
+        let server = details
+            .downloads
+            .server
+            .as_ref()
+            .ok_or_else(|| anyhow!("Version {version} has no server download"))?;
+        self.fetch_jar(version, "server.jar", server, destination)
+    }
+
+    fn fetch_jar(
+        &self,
+        version: &str,
+        name: &str,
+        file: &VersionFile,
+        destination: &Path,
+    ) -> Result<()> {
+        let mirror_path = format!("versions/{version}/{name}");
+        match self {
+            Self::Upstream => download::download(&file.url, destination, Some(&file.sha1)),
+            Self::Directory { root, .. } => {
+                copy_verified(&root.join(mirror_path), destination, Some(&file.sha1))
+            }
+            Self::Url(base) => download::download(
+                &format!("{base}/{mirror_path}"),
+                destination,
+                Some(&file.sha1),
+            ),
+        }
+    }
+
+    /// Fetches the minecraft-data archive for `reference` to `destination`.
+    pub fn fetch_minecraft_data(&self, reference: &str, destination: &Path) -> Result<()> {
+        let mirror_path = minecraft_data_archive_path(reference);
+        match self {
+            Self::Upstream => download::download(
+                &format!("{MINECRAFT_DATA_ZIP_URL}/{reference}"),
+                destination,
+                None,
+            ),
+            Self::Directory { root, .. } => {
+                copy_verified(&root.join(mirror_path), destination, None)
+            }
+            Self::Url(base) => {
+                download::download(&format!("{base}/{mirror_path}"), destination, None)
+            }
+        }
+    }
+}
+
+pub fn version_json_path(version: &str) -> String {
+    format!("versions/{version}.json")
+}
+
+pub fn minecraft_data_archive_path(reference: &str) -> String {
+    format!("minecraft-data/{}.zip", reference.replace('/', "_"))
+}
+
+fn copy_verified(source: &Path, destination: &Path, expected_sha1: Option<&str>) -> Result<()> {
+    fs::copy(source, destination).with_context(|| {
+        format!(
+            "failed to copy {} to {}",
+            source.display(),
+            destination.display()
+        )
+    })?;
+
+    if let Some(expected) = expected_sha1 {
+        let actual = sha1_file(destination)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            fs::remove_file(destination)?;
+            bail!(
+                "{} has SHA-1 {actual}, expected {expected}",
+                source.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_zip(zip_path: &Path, destination: &Path) -> Result<()> {
+    extract::extract(zip_path, destination, |path| Some(path.to_owned()))?;
+    Ok(())
+}
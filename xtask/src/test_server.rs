@@ -0,0 +1,198 @@
+//! Running a vanilla server locally for end-to-end protocol testing.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{source::Source, verify::sha1_file, workspace_root};
+
+/// How long to wait for the server to finish generating spawn chunks.
+const READY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The line a vanilla server prints once it accepts connections, e.g.
+/// `[Server thread/INFO]: Done (4.123s)! For help, type "help"`.
+const READY_MARKER: &str = "For help, type \"help\"";
+
+/// Integration test target that exercises the client against the server.
+const INTEGRATION_TEST: &str = "test_server";
+
+pub struct TestServerOptions {
+    pub version: String,
+    pub port: u16,
+    pub java: String,
+    pub fresh: bool,
+    pub run_tests: bool,
+}
+
+/// Downloads (from `source`) and configures the server, starts it, and waits
+/// until it is ready.
+///
+/// Then either runs the integration tests against it and stops it, or keeps it
+/// running in the foreground until it exits.
+pub fn run_test_server(source: &Source, options: &TestServerOptions) -> Result<()> {
+    let server_dir = workspace_root()
+        .join("target")
+        .join("test-server")
+        .join(&options.version);
+    fs::create_dir_all(&server_dir)?;
+
+    let jar_path = ensure_server_jar(source, &options.version, &server_dir)?;
+    configure(&server_dir, options)?;
+
+    let mut server = TestServer::start(&options.java, &jar_path, &server_dir)?;
+    let address = format!("localhost:{}", options.port);
+    println!("Test server for {} ready on {address}", options.version);
+
+    if !options.run_tests {
+        println!("Press Ctrl+C to stop the server");
+        server.wait()?;
+        return Ok(());
+    }
+
+    let test_status = run_integration_tests(&address);
+    server.stop()?;
+
+    let test_status = test_status?;
+    if !test_status.success() {
+        bail!("integration tests failed ({test_status})");
+    }
+    Ok(())
+}
+
+/// Fetches the server jar from `source` unless an up-to-date copy is already
+/// present.
+fn ensure_server_jar(source: &Source, version: &str, server_dir: &Path) -> Result<PathBuf> {
+    let jar_path = server_dir.join("server.jar");
+    let details = source.version_details(version)?;
+    let server = details
+        .downloads
+        .server
+        .as_ref()
+        .ok_or_else(|| anyhow!("Version {version} has no server download"))?;
+
+    if jar_path.exists() && sha1_file(&jar_path)? == server.sha1 {
+        return Ok(jar_path);
+    }
+
+    println!("Downloading server.jar");
+    source.fetch_server_jar(version, &details, &jar_path)?;
+
+    Ok(jar_path)
+}
+
+/// Accepts the EULA and writes a server.properties suitable for testing: offline
+/// mode, a fixed seed, and a small superflat world with nothing spawning.
+fn configure(server_dir: &Path, options: &TestServerOptions) -> Result<()> {
+    fs::write(server_dir.join("eula.txt"), "eula=true\n")?;
+
+    let properties = format!(
+        "\
+online-mode=false
+server-port={port}
+level-seed=brine
+level-type=minecraft\\:flat
+generate-structures=false
+difficulty=peaceful
+gamemode=creative
+spawn-monsters=false
+spawn-animals=false
+spawn-npcs=false
+view-distance=4
+simulation-distance=4
+max-players=4
+motd=Brine test server
+",
+        port = options.port
+    );
+    fs::write(server_dir.join("server.properties"), properties)?;
+
+    let world_dir = server_dir.join("world");
+    if options.fresh && world_dir.exists() {
+        fs::remove_dir_all(&world_dir)
+            .with_context(|| format!("failed to remove {}", world_dir.display()))?;
+    }
+
+    Ok(())
+}
+
+fn run_integration_tests(address: &str) -> Result<ExitStatus> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    println!("Running integration tests against {address}");
+
+    Command::new(cargo)
+        .args(["test", "--test", INTEGRATION_TEST, "--", "--ignored"])
+        .current_dir(workspace_root())
+        .env("BRINE_TEST_SERVER", address)
+        .status()
+        .context("failed to run cargo test")
+}
+
+struct TestServer {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl TestServer {
+    fn start(java: &str, jar_path: &Path, server_dir: &Path) -> Result<Self> {
+        println!("Starting server in {}", server_dir.display());
+
+        let mut child = Command::new(java)
+            .args(["-Xmx2G", "-jar"])
+            .arg(jar_path)
+            .arg("nogui")
+            .current_dir(server_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start `{java}`; is a Java runtime installed?"))?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // Echo the server log and signal once it reports that it's ready.
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                println!("[server] {line}");
+                if line.contains(READY_MARKER) {
+                    let _ = ready_tx.send(());
+                }
+            }
+        });
+
+        let mut server = Self { child, stdin };
+        match ready_rx.recv_timeout(READY_TIMEOUT) {
+            Ok(()) => Ok(server),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                server.child.kill()?;
+                bail!("server did not become ready within {READY_TIMEOUT:?}")
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                let status = server.child.wait()?;
+                bail!("server exited before becoming ready ({status})")
+            }
+        }
+    }
+
+    fn wait(&mut self) -> Result<()> {
+        let status = self.child.wait()?;
+        println!("Server exited ({status})");
+        Ok(())
+    }
+
+    /// Asks the server to save and shut down, and waits for it to exit.
+    fn stop(&mut self) -> Result<()> {
+        writeln!(self.stdin, "stop")?;
+        self.stdin.flush()?;
+        self.wait()
+    }
+}
@@ -0,0 +1,204 @@
+//! Integrity checks for downloaded assets and minecraft-data.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use sha1_smol::Sha1;
+use zip::ZipArchive;
+
+/// Maximum number of problems listed per check before the rest are summarized.
+const MAX_LISTED: usize = 20;
+
+/// Problems found by one verification check.
+pub struct Report {
+    pub name: &'static str,
+    pub checked: usize,
+    pub problems: Vec<String>,
+}
+
+impl Report {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            checked: 0,
+            problems: Vec::new(),
+        }
+    }
+
+    pub fn print(&self) {
+        if self.problems.is_empty() {
+            println!("[ok] {}: {} checked", self.name, self.checked);
+            return;
+        }
+
+        println!(
+            "[FAILED] {}: {} problems in {} checked",
+            self.name,
+            self.problems.len(),
+            self.checked
+        );
+        for problem in self.problems.iter().take(MAX_LISTED) {
+            println!("    {problem}");
+        }
+        if self.problems.len() > MAX_LISTED {
+            println!("    ... and {} more", self.problems.len() - MAX_LISTED);
+        }
+    }
+}
+
+/// Returns the hex-encoded SHA-1 of the file at `path`.
+pub fn sha1_file(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(Sha1::from(bytes).digest().to_string())
+}
+
+/// Compares every asset file that `fetch-assets` extracts from the client jar
+/// against the copy in `assets_dir`.
+///
+/// The jar itself should already have been checked against the manifest.
+pub fn verify_extracted_assets(jar_path: &Path, assets_dir: &Path) -> Result<Report> {
+    let mut report = Report::new("extracted assets");
+    let mut archive = ZipArchive::new(File::open(jar_path)?)?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(rel_path) = entry.enclosed_name().map(|p| p.to_owned()) else {
+            continue;
+        };
+        if !crate::is_client_payload(&rel_path.to_string_lossy()) {
+            continue;
+        }
+        report.checked += 1;
+
+        let mut expected = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut expected)?;
+
+        let on_disk = assets_dir.join(&rel_path);
+        match fs::read(&on_disk) {
+            Ok(actual) => {
+                if actual != expected {
+                    report.problems.push(format!(
+                        "{} differs from the client jar",
+                        rel_path.display()
+                    ));
+                }
+            }
+            Err(_) => report
+                .problems
+                .push(format!("{} is missing", rel_path.display())),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Checks that `protocol.json` parses and every packet's types resolve.
+pub fn verify_protocol(proto_path: &Path, version: &str, protocol_version: i32) -> Report {
+    let mut report = Report::new("protocol.json");
+
+    match crate::protocol::build_packet_index(proto_path, version, protocol_version) {
+        Ok(index) => {
+            report.checked = index
+                .states
+                .iter()
+                .flat_map(|state| &state.directions)
+                .map(|direction| direction.packets.len())
+                .sum();
+        }
+        Err(err) => report.problems.push(format!("{err:#}")),
+    }
+
+    report
+}
+
+/// Reports textures and parent models that block and item models reference but
+/// that don't exist in `assets_dir`.
+pub fn verify_model_references(assets_dir: &Path) -> Result<Report> {
+    let mut report = Report::new("model references");
+    let assets_root = assets_dir.join("assets");
+
+    let mut model_files = Vec::new();
+    collect_json_files(
+        &assets_root.join("minecraft").join("models"),
+        &mut model_files,
+    )?;
+    model_files.sort();
+
+    for model_file in model_files {
+        report.checked += 1;
+        let relative = model_file.strip_prefix(assets_dir).unwrap_or(&model_file);
+
+        let model: Value = match fs::read_to_string(&model_file)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(serde_json::from_str(&contents)?))
+        {
+            Ok(model) => model,
+            Err(err) => {
+                report
+                    .problems
+                    .push(format!("{}: failed to parse: {err}", relative.display()));
+                continue;
+            }
+        };
+
+        if let Some(parent) = model.get("parent").and_then(Value::as_str) {
+            if !parent.starts_with("builtin/") {
+                let path = resource_path(&assets_root, parent, "models", "json");
+                if !path.exists() {
+                    report.problems.push(format!(
+                        "{}: parent model {parent} not found",
+                        relative.display()
+                    ));
+                }
+            }
+        }
+
+        let textures = model.get("textures").and_then(Value::as_object);
+        for texture in textures.into_iter().flat_map(|t| t.values()) {
+            let Some(texture) = texture.as_str() else {
+                continue;
+            };
+            // `#name` refers to another texture variable, not a file.
+            if texture.starts_with('#') {
+                continue;
+            }
+            let path = resource_path(&assets_root, texture, "textures", "png");
+            if !path.exists() {
+                report.problems.push(format!(
+                    "{}: texture {texture} not found",
+                    relative.display()
+                ));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Maps a resource location like `minecraft:block/stone` to its file under
+/// `assets_root`.
+fn resource_path(assets_root: &Path, location: &str, kind: &str, extension: &str) -> PathBuf {
+    let (namespace, path) = location.split_once(':').unwrap_or(("minecraft", location));
+    assets_root
+        .join(namespace)
+        .join(kind)
+        .join(format!("{path}.{extension}"))
+}
+
+fn collect_json_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_json_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}